@@ -0,0 +1,171 @@
+//! Roll-up progress of a group of timers
+//!
+//! `pmon status --group team:payments --aggregate` combines every running
+//! timer tagged `team:payments` into a single bar. Each timer counts in
+//! proportion to the length of its window, so a two-week deadline that is
+//! half done weighs more than a one-hour task that is finished.
+
+use crate::compare::TimerEntry;
+use crate::progress_state::ProgressState;
+use crate::timezone::civil_duration;
+
+/// Keep the timers tagged `group`, or every timer when there is no group
+pub fn in_group(entries: Vec<TimerEntry>, group: Option<&str>) -> Vec<TimerEntry> {
+    match group {
+        Some(group) => entries
+            .into_iter()
+            .filter(|entry| entry.update.tags.iter().any(|tag| tag == group))
+            .collect(),
+        None => entries,
+    }
+}
+
+/// Combine timers into one window, weighting each by its duration
+///
+/// The combined window runs from the earliest start to the latest end. Its
+/// percentage is the share of all the timers' time that has been used up,
+/// with each timer capped at 100%. Timers without a known percentage or
+/// with an empty window do not count; `None` is returned when none count.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDateTime;
+/// use pmon::aggregate::aggregate_progress;
+/// use pmon::compare::TimerEntry;
+/// use pmon::output::{render_json, JsonUpdate, UpdateContext};
+/// use pmon::progress_state::ProgressState;
+///
+/// let at = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+/// let entry = |name: &str, end| {
+///     let state = ProgressState::new(at("2025-01-27 09:00:00"), at(end), at("2025-01-27 10:00:00"));
+///     let json = render_json(&state, &UpdateContext::default());
+///     TimerEntry {
+///         name: name.parse().unwrap(),
+///         update: JsonUpdate::parse(&json).unwrap(),
+///     }
+/// };
+/// // One hour of a four-hour window, and a finished one-hour window
+/// let entries = [entry("long", "2025-01-27 13:00:00"), entry("short", "2025-01-27 10:00:00")];
+/// let combined = aggregate_progress(&entries).unwrap();
+/// assert_eq!(combined.percentage, 40.0);
+/// assert_eq!(combined.end, at("2025-01-27 13:00:00"));
+/// ```
+pub fn aggregate_progress(entries: &[TimerEntry]) -> Option<ProgressState> {
+    let states: Vec<(ProgressState, f64)> = entries
+        .iter()
+        .map(|entry| {
+            let state = entry.update.state();
+            let duration = civil_duration(state.start, state.end).num_seconds() as f64;
+            (state, duration)
+        })
+        .filter(|(state, duration)| state.percentage.is_finite() && *duration > 0.0)
+        .collect();
+
+    let total: f64 = states.iter().map(|(_, duration)| duration).sum();
+    let used: f64 = states
+        .iter()
+        .map(|(state, duration)| state.percentage.clamp(0.0, 100.0) / 100.0 * duration)
+        .sum();
+    Some(ProgressState {
+        start: states.iter().map(|(state, _)| state.start).min()?,
+        end: states.iter().map(|(state, _)| state.end).max()?,
+        current: states.iter().map(|(state, _)| state.current).max()?,
+        percentage: used * 100.0 / total,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::{render_json, JsonUpdate, UpdateContext};
+    use chrono::NaiveDateTime;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn entry(name: &str, start: &str, end: &str, tags: &[&str]) -> TimerEntry {
+        let state = ProgressState::new(
+            create_test_datetime(start),
+            create_test_datetime(end),
+            create_test_datetime("2025-01-27 10:00:00"),
+        );
+        let tags: Vec<String> = tags.iter().map(|tag| tag.to_string()).collect();
+        let context = UpdateContext {
+            tags: &tags,
+            ..UpdateContext::default()
+        };
+        TimerEntry {
+            name: name.parse().unwrap(),
+            update: JsonUpdate::parse(&render_json(&state, &context)).unwrap(),
+        }
+    }
+
+    fn timers() -> Vec<TimerEntry> {
+        vec![
+            // 1h of 4h used
+            entry(
+                "api",
+                "2025-01-27 09:00:00",
+                "2025-01-27 13:00:00",
+                &["team:payments"],
+            ),
+            // Not started yet: 0 of 2h used
+            entry(
+                "ledger",
+                "2025-01-27 11:00:00",
+                "2025-01-27 13:00:00",
+                &["team:payments", "sev2"],
+            ),
+            // Overdue: capped at its 2h
+            entry(
+                "search",
+                "2025-01-27 07:00:00",
+                "2025-01-27 09:00:00",
+                &["team:search"],
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_in_group() {
+        let names = |entries: Vec<TimerEntry>| -> Vec<String> {
+            entries
+                .iter()
+                .map(|entry| entry.name.as_str().to_string())
+                .collect()
+        };
+        assert_eq!(
+            names(in_group(timers(), Some("team:payments"))),
+            ["api", "ledger"]
+        );
+        assert_eq!(names(in_group(timers(), Some("sev2"))), ["ledger"]);
+        assert!(in_group(timers(), Some("team")).is_empty());
+        assert_eq!(in_group(timers(), None).len(), 3);
+    }
+
+    #[test]
+    fn test_aggregate_is_weighted_by_duration() {
+        let payments = in_group(timers(), Some("team:payments"));
+        let combined = aggregate_progress(&payments).unwrap();
+        // 1h used out of 6h
+        assert!((combined.percentage - 100.0 / 6.0).abs() < 1e-9);
+        assert_eq!(combined.start, create_test_datetime("2025-01-27 09:00:00"));
+        assert_eq!(combined.end, create_test_datetime("2025-01-27 13:00:00"));
+
+        // 1h + 2h used out of 8h
+        let combined = aggregate_progress(&timers()).unwrap();
+        assert_eq!(combined.percentage, 37.5);
+        assert_eq!(combined.start, create_test_datetime("2025-01-27 07:00:00"));
+    }
+
+    #[test]
+    fn test_aggregate_without_countable_timers() {
+        assert!(aggregate_progress(&[]).is_none());
+
+        let mut unknown = timers().remove(0);
+        unknown.update.percent = None;
+        assert!(aggregate_progress(&[unknown]).is_none());
+    }
+}
@@ -0,0 +1,270 @@
+//! Rate limiting for hook alerts
+//!
+//! Hooks fire once per event, but a short `--interval`, chained windows, or
+//! several hooks on the same channel can still produce a burst of
+//! notifications. The `[alerts]` table of the configuration file sets the
+//! least time between two alerts on a channel:
+//!
+//! ```toml
+//! [alerts]
+//! min_interval = "5m"          # every channel; defaults to 0s, no limit
+//! bell = "0s"                  # per-channel overrides for run, post,
+//! notify = "10m"               # notify, say, and bell
+//! ```
+//!
+//! Alerts arriving on a channel before its interval has passed are
+//! coalesced: only the latest is kept, and it is delivered once the
+//! interval is up. Identical alerts fired together are delivered once.
+
+use crate::deadline::parse_lead_time;
+use crate::error::PbResult;
+use crate::hooks::{Firing, HookAction};
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+
+/// Channels alerts are delivered through, as named by [`HookAction::channel`]
+pub const CHANNELS: [&str; 5] = ["run", "post", "notify", "say", "bell"];
+
+/// The `[alerts]` table in the configuration file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AlertConfig {
+    /// Least time between two alerts on any channel, such as `5m`
+    pub min_interval: Option<String>,
+    /// Least time between two commands
+    pub run: Option<String>,
+    /// Least time between two webhook posts
+    pub post: Option<String>,
+    /// Least time between two desktop notifications
+    pub notify: Option<String>,
+    /// Least time between two spoken announcements
+    pub say: Option<String>,
+    /// Least time between two terminal bells
+    pub bell: Option<String>,
+}
+
+/// Delivery state of one channel
+#[derive(Debug, Clone)]
+struct Channel {
+    name: &'static str,
+    interval: Duration,
+    last_sent: Option<Instant>,
+    pending: Option<Firing>,
+}
+
+impl Channel {
+    fn is_due(&self, now: Instant) -> bool {
+        self.last_sent
+            .is_none_or(|last| now.duration_since(last) >= self.interval)
+    }
+}
+
+/// Coalesces hook firings so each channel alerts at most once per interval
+#[derive(Debug, Clone)]
+pub struct AlertManager {
+    channels: Vec<Channel>,
+}
+
+impl Default for AlertManager {
+    fn default() -> Self {
+        Self::with_intervals(|_| Duration::ZERO)
+    }
+}
+
+impl AlertManager {
+    fn with_intervals(interval: impl Fn(&str) -> Duration) -> Self {
+        Self {
+            channels: CHANNELS
+                .iter()
+                .map(|&name| Channel {
+                    name,
+                    interval: interval(name),
+                    last_sent: None,
+                    pending: None,
+                })
+                .collect(),
+        }
+    }
+
+    /// Validate the `[alerts]` table of the configuration
+    pub fn from_config(config: &AlertConfig) -> PbResult<Self> {
+        let parse = |value: &Option<String>| -> PbResult<Option<Duration>> {
+            value
+                .as_deref()
+                .map(|value| Ok(parse_lead_time(value)?.to_std().unwrap_or_default()))
+                .transpose()
+        };
+        let default = parse(&config.min_interval)?.unwrap_or_default();
+        let overrides = [
+            ("run", parse(&config.run)?),
+            ("post", parse(&config.post)?),
+            ("notify", parse(&config.notify)?),
+            ("say", parse(&config.say)?),
+            ("bell", parse(&config.bell)?),
+        ];
+        Ok(Self::with_intervals(|name| {
+            overrides
+                .iter()
+                .find(|(channel, _)| *channel == name)
+                .and_then(|(_, interval)| *interval)
+                .unwrap_or(default)
+        }))
+    }
+
+    /// Least time between two alerts on `channel`
+    pub fn interval(&self, channel: &str) -> Duration {
+        self.channels
+            .iter()
+            .find(|c| c.name == channel)
+            .map_or(Duration::ZERO, |c| c.interval)
+    }
+
+    fn channel_mut(&mut self, action: &HookAction) -> &mut Channel {
+        // Channels are kept in the order of CHANNELS
+        let index = match action {
+            HookAction::Run(_) => 0,
+            #[cfg(feature = "net")]
+            HookAction::Post(..) => 1,
+            HookAction::Notify(_) => 2,
+            HookAction::Say(_) => 3,
+            HookAction::Bell => 4,
+        };
+        &mut self.channels[index]
+    }
+
+    /// Firings to deliver now, out of `firings` and those held back earlier
+    pub fn admit(&mut self, firings: Vec<Firing>, now: Instant) -> Vec<Firing> {
+        let mut due = Vec::new();
+        for firing in firings {
+            let channel = self.channel_mut(&firing.action);
+            if channel.interval.is_zero() {
+                if !due.contains(&firing) {
+                    due.push(firing);
+                }
+            } else {
+                channel.pending = Some(firing);
+            }
+        }
+        for channel in &mut self.channels {
+            if channel.pending.is_some() && channel.is_due(now) {
+                channel.last_sent = Some(now);
+                due.extend(channel.pending.take());
+            }
+        }
+        due
+    }
+
+    /// Firings held back so far, delivered regardless of the intervals
+    ///
+    /// Used when the run ends, so the last alert of each channel is not lost.
+    pub fn flush(&mut self, now: Instant) -> Vec<Firing> {
+        self.channels
+            .iter_mut()
+            .filter_map(|channel| {
+                let firing = channel.pending.take()?;
+                channel.last_sent = Some(now);
+                Some(firing)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hooks::HookEvent;
+
+    fn firing(event: HookEvent, action: HookAction) -> Firing {
+        Firing { event, action }
+    }
+
+    fn notify(event: HookEvent) -> Firing {
+        firing(event, HookAction::Notify("{event}".to_string()))
+    }
+
+    fn manager(toml: &str) -> PbResult<AlertManager> {
+        let config: AlertConfig = toml::from_str(toml).unwrap();
+        AlertManager::from_config(&config)
+    }
+
+    #[test]
+    fn test_intervals_from_config() {
+        let alerts = manager("min_interval = \"5m\"\nbell = \"0s\"\n").unwrap();
+        assert_eq!(alerts.interval("notify"), Duration::from_secs(300));
+        assert_eq!(alerts.interval("run"), Duration::from_secs(300));
+        assert_eq!(alerts.interval("bell"), Duration::ZERO);
+        assert_eq!(
+            manager("say = \"1m\"\n").unwrap().interval("say"),
+            Duration::from_secs(60)
+        );
+        assert_eq!(AlertManager::default().interval("post"), Duration::ZERO);
+
+        assert!(manager("notify = \"often\"\n").is_err());
+    }
+
+    #[test]
+    fn test_without_limit_everything_is_delivered_once() {
+        let mut alerts = AlertManager::default();
+        let now = Instant::now();
+        let firings = vec![
+            notify(HookEvent::Milestone(50)),
+            notify(HookEvent::Milestone(50)),
+            notify(HookEvent::Milestone(75)),
+        ];
+        assert_eq!(
+            alerts.admit(firings, now),
+            [
+                notify(HookEvent::Milestone(50)),
+                notify(HookEvent::Milestone(75))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_alerts_within_interval_are_coalesced() {
+        let mut alerts = manager("notify = \"5m\"\n").unwrap();
+        let start = Instant::now();
+        let at = |seconds| start + Duration::from_secs(seconds);
+
+        assert_eq!(
+            alerts.admit(vec![notify(HookEvent::Milestone(25))], at(0)),
+            [notify(HookEvent::Milestone(25))]
+        );
+        // Held back, then replaced by the newer alert
+        assert!(alerts
+            .admit(vec![notify(HookEvent::Milestone(50))], at(60))
+            .is_empty());
+        assert!(alerts
+            .admit(vec![notify(HookEvent::Milestone(75))], at(120))
+            .is_empty());
+        assert!(alerts.admit(Vec::new(), at(299)).is_empty());
+        assert_eq!(
+            alerts.admit(Vec::new(), at(300)),
+            [notify(HookEvent::Milestone(75))]
+        );
+        assert!(alerts.admit(Vec::new(), at(900)).is_empty());
+    }
+
+    #[test]
+    fn test_channels_are_limited_separately() {
+        let mut alerts = manager("min_interval = \"1m\"\n").unwrap();
+        let now = Instant::now();
+        let bell = firing(HookEvent::Complete, HookAction::Bell);
+        assert_eq!(
+            alerts.admit(vec![notify(HookEvent::Complete), bell.clone()], now),
+            [notify(HookEvent::Complete), bell]
+        );
+    }
+
+    #[test]
+    fn test_flush_delivers_held_alerts() {
+        let mut alerts = manager("notify = \"5m\"\n").unwrap();
+        let now = Instant::now();
+        alerts.admit(vec![notify(HookEvent::Milestone(90))], now);
+        assert!(alerts
+            .admit(vec![notify(HookEvent::Complete)], now)
+            .is_empty());
+        assert_eq!(alerts.flush(now), [notify(HookEvent::Complete)]);
+        assert!(alerts.flush(now).is_empty());
+    }
+}
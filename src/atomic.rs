@@ -0,0 +1,71 @@
+//! Writing files so that readers never see half of them
+//!
+//! State that pmon keeps between invocations is replaced whole: the new
+//! contents are written and flushed to a temporary file beside the target,
+//! which is then renamed over it. A crash or a full disk leaves either the
+//! old file or the new one, never a truncated mix.
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Replace the file at `path` with `contents`, creating its directory
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    fs::create_dir_all(dir)?;
+    let temp = temp_path(path);
+    let written = File::create(&temp).and_then(|mut file| {
+        file.write_all(contents)?;
+        file.sync_all()
+    });
+    if let Err(e) = written.and_then(|()| fs::rename(&temp, path)) {
+        let _ = fs::remove_file(&temp);
+        return Err(e);
+    }
+    // Make the rename itself durable; not every platform can open a directory
+    #[cfg(unix)]
+    if let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+/// Temporary file that [`write_atomic`] renames to `path`
+///
+/// The process id keeps two pmon processes saving at once from writing to
+/// the same temporary file.
+pub fn temp_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{name}.{}.tmp", std::process::id()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_atomic_replaces_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("state.json");
+        write_atomic(&path, b"first\n").unwrap();
+        write_atomic(&path, b"second\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second\n");
+        // Nothing is left behind beside the file
+        let entries = fs::read_dir(path.parent().unwrap()).unwrap().count();
+        assert_eq!(entries, 1);
+    }
+
+    #[test]
+    fn test_write_atomic_keeps_the_old_file_on_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        write_atomic(&path, b"old\n").unwrap();
+        // A directory where the temporary file should go makes the write fail
+        fs::create_dir(temp_path(&path)).unwrap();
+        assert!(write_atomic(&path, b"new\n").is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "old\n");
+    }
+}
@@ -0,0 +1,277 @@
+//! README badges showing how much of a window has passed
+//!
+//! `--format badge` prints the URL of a shields.io badge such as
+//! `progress | 42%` and exits, so a status line can be kept current from a
+//! scheduled job:
+//!
+//! ```sh
+//! echo "![progress]($(pmon --format badge --start 2025-07-01 --end 2025-09-30))"
+//! ```
+//!
+//! `--format badge-svg` draws the same badge as a self-contained SVG for
+//! pages that cannot load images from shields.io. The badge turns from
+//! green through yellow and orange to red as the end approaches.
+
+use crate::layout::fit_label;
+use crate::progress_state::ProgressState;
+use crate::snapshot::escape_xml;
+use unicode_width::UnicodeWidthStr;
+
+/// Left-hand text of a badge without a label
+pub const DEFAULT_BADGE_LABEL: &str = "progress";
+
+/// Static badges on shields.io
+const SHIELDS_URL: &str = "https://img.shields.io/badge";
+
+/// Height of an SVG badge in pixels
+const BADGE_HEIGHT: u32 = 20;
+
+/// Approximate width of one column of badge text in pixels
+const COLUMN_WIDTH: u32 = 7;
+
+/// Horizontal padding around each half of an SVG badge in pixels
+const PADDING: u32 = 10;
+
+/// Background of the label half of an SVG badge
+const LABEL_COLOR: &str = "#555";
+
+/// Color of a badge, one of shields.io's named colors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BadgeColor {
+    /// Less than three quarters of the window has passed
+    Green,
+    /// Less than 90% has passed
+    Yellow,
+    /// The end is close
+    Orange,
+    /// The window has elapsed
+    Red,
+}
+
+impl BadgeColor {
+    /// Color for a window `percentage` of the way through
+    pub fn for_percentage(percentage: f64) -> Self {
+        match percentage {
+            p if p >= 100.0 => Self::Red,
+            p if p >= 90.0 => Self::Orange,
+            p if p >= 75.0 => Self::Yellow,
+            _ => Self::Green,
+        }
+    }
+
+    /// Name shields.io knows the color by
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Green => "green",
+            Self::Yellow => "yellow",
+            Self::Orange => "orange",
+            Self::Red => "red",
+        }
+    }
+
+    /// CSS color shields.io draws for [`BadgeColor::name`]
+    pub fn hex(self) -> &'static str {
+        match self {
+            Self::Green => "#97ca00",
+            Self::Yellow => "#dfb317",
+            Self::Orange => "#fe7d37",
+            Self::Red => "#e05d44",
+        }
+    }
+}
+
+/// Whole percentage shown on a badge, never rounded up to 100%
+///
+/// # Examples
+///
+/// ```
+/// use pmon::badge::format_badge_percentage;
+///
+/// assert_eq!(format_badge_percentage(42.9), "42%");
+/// assert_eq!(format_badge_percentage(99.99), "99%");
+/// assert_eq!(format_badge_percentage(f64::NAN), "--%");
+/// ```
+pub fn format_badge_percentage(percentage: f64) -> String {
+    if percentage.is_finite() {
+        format!("{}%", percentage.max(0.0).floor())
+    } else {
+        "--%".to_string()
+    }
+}
+
+/// URL of a shields.io badge for `state`
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDateTime;
+/// use pmon::badge::render_badge_url;
+/// use pmon::progress_state::ProgressState;
+///
+/// let at = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+/// let state = ProgressState::new(
+///     at("2025-01-27 09:00:00"),
+///     at("2025-01-27 17:00:00"),
+///     at("2025-01-27 12:30:00"),
+/// );
+/// assert_eq!(
+///     render_badge_url(&state, Some("Q3 roadmap")),
+///     "https://img.shields.io/badge/Q3_roadmap-43%25-green"
+/// );
+/// ```
+pub fn render_badge_url(state: &ProgressState, label: Option<&str>) -> String {
+    let label = fit_label(label.unwrap_or(DEFAULT_BADGE_LABEL));
+    let color = BadgeColor::for_percentage(state.percentage);
+    format!(
+        "{SHIELDS_URL}/{}-{}-{}",
+        escape_badge_text(&label),
+        escape_badge_text(&format_badge_percentage(state.percentage)),
+        color.name()
+    )
+}
+
+/// Render a badge for `state` as a self-contained SVG image
+pub fn render_badge_svg(state: &ProgressState, label: Option<&str>) -> String {
+    let label = fit_label(label.unwrap_or(DEFAULT_BADGE_LABEL));
+    let label = label.as_ref();
+    let value = format_badge_percentage(state.percentage);
+    let color = BadgeColor::for_percentage(state.percentage);
+    let label_width = text_width(label);
+    let value_width = text_width(&value);
+    let width = label_width + value_width;
+    let title = escape_xml(&format!("{label}: {value}"));
+    let (label, value) = (escape_xml(label), escape_xml(&value));
+    format!(
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" role="img" aria-label="{title}">"#,
+            "\n  <title>{title}</title>\n",
+            r##"  <clipPath id="r"><rect width="{width}" height="{height}" rx="3" fill="#fff"/></clipPath>"##,
+            "\n",
+            r#"  <g clip-path="url(#r)"><rect width="{label_width}" height="{height}" fill="{label_color}"/><rect x="{label_width}" width="{value_width}" height="{height}" fill="{color}"/></g>"#,
+            "\n",
+            r##"  <g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11"><text x="{label_x}" y="14">{label}</text><text x="{value_x}" y="14">{value}</text></g>"##,
+            "\n</svg>"
+        ),
+        width = width,
+        height = BADGE_HEIGHT,
+        title = title,
+        label_width = label_width,
+        value_width = value_width,
+        label_color = LABEL_COLOR,
+        color = color.hex(),
+        label_x = f64::from(label_width) / 2.0,
+        value_x = f64::from(label_width) + f64::from(value_width) / 2.0,
+        label = label,
+        value = value,
+    )
+}
+
+/// Width of one half of an SVG badge holding `text`
+fn text_width(text: &str) -> u32 {
+    u32::try_from(text.width())
+        .unwrap_or(u32::MAX / COLUMN_WIDTH)
+        .saturating_mul(COLUMN_WIDTH)
+        .saturating_add(PADDING)
+}
+
+/// Encode text for a shields.io badge path
+///
+/// Dashes and underscores are doubled and spaces become underscores, as
+/// shields.io expects; everything else outside the URL-safe characters is
+/// percent-encoded.
+fn escape_badge_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '-' => escaped.push_str("--"),
+            '_' => escaped.push_str("__"),
+            ' ' => escaped.push('_'),
+            'A'..='Z' | 'a'..='z' | '0'..='9' | '.' | '~' => escaped.push(ch),
+            _ => {
+                let mut bytes = [0; 4];
+                for byte in ch.encode_utf8(&mut bytes).bytes() {
+                    escaped.push_str(&format!("%{byte:02X}"));
+                }
+            }
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn state_at(current: &str) -> ProgressState {
+        ProgressState::new(
+            create_test_datetime("2025-01-27 09:00:00"),
+            create_test_datetime("2025-01-27 19:00:00"),
+            create_test_datetime(current),
+        )
+    }
+
+    #[test]
+    fn test_colors_follow_progress() {
+        assert_eq!(BadgeColor::for_percentage(0.0), BadgeColor::Green);
+        assert_eq!(BadgeColor::for_percentage(74.9), BadgeColor::Green);
+        assert_eq!(BadgeColor::for_percentage(75.0), BadgeColor::Yellow);
+        assert_eq!(BadgeColor::for_percentage(90.0), BadgeColor::Orange);
+        assert_eq!(BadgeColor::for_percentage(100.0), BadgeColor::Red);
+        assert_eq!(BadgeColor::for_percentage(250.0), BadgeColor::Red);
+        assert_eq!(BadgeColor::for_percentage(f64::NAN), BadgeColor::Green);
+    }
+
+    #[test]
+    fn test_badge_percentage() {
+        assert_eq!(format_badge_percentage(0.0), "0%");
+        assert_eq!(format_badge_percentage(-3.0), "0%");
+        assert_eq!(format_badge_percentage(100.0), "100%");
+        assert_eq!(format_badge_percentage(133.7), "133%");
+        assert_eq!(format_badge_percentage(f64::INFINITY), "--%");
+    }
+
+    #[test]
+    fn test_badge_url() {
+        assert_eq!(
+            render_badge_url(&state_at("2025-01-27 17:00:00"), None),
+            "https://img.shields.io/badge/progress-80%25-yellow"
+        );
+        assert_eq!(
+            render_badge_url(&state_at("2025-01-27 20:00:00"), Some("v2.1 release")),
+            "https://img.shields.io/badge/v2.1_release-110%25-red"
+        );
+    }
+
+    #[test]
+    fn test_escape_badge_text() {
+        assert_eq!(escape_badge_text("sprint-42"), "sprint--42");
+        assert_eq!(escape_badge_text("snake_case"), "snake__case");
+        assert_eq!(escape_badge_text("a b/c?"), "a_b%2Fc%3F");
+        assert_eq!(escape_badge_text("締切"), "%E7%B7%A0%E5%88%87");
+    }
+
+    #[test]
+    fn test_badge_svg() {
+        let svg = render_badge_svg(&state_at("2025-01-27 18:00:00"), Some("R&D"));
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.ends_with("</svg>"));
+        assert!(svg.contains("<title>R&amp;D: 90%</title>"));
+        assert!(svg.contains(r##"fill="#fe7d37""##));
+        // 3 columns of label and 3 of value, each padded
+        assert!(svg.contains(r#"width="62" height="20""#));
+        assert!(svg.contains(r#"<text x="15.5" y="14">R&amp;D</text>"#));
+        assert!(svg.contains(r#"<text x="46.5" y="14">90%</text>"#));
+    }
+
+    #[test]
+    fn test_badge_svg_measures_wide_characters() {
+        let svg = render_badge_svg(&state_at("2025-01-27 09:00:00"), Some("締切"));
+        // Two double-width characters take four columns
+        assert!(svg.contains(r##"<rect width="38" height="20" fill="#555"/>"##));
+    }
+}
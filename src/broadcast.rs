@@ -0,0 +1,385 @@
+//! Named timers that other terminals can attach to
+//!
+//! `pmon --name standup --end +15m` mirrors every update into a small state
+//! file named after the timer. `pmon attach standup`, run in any other
+//! terminal on the same machine (or through `--remote`), reads that file
+//! and renders the same countdown, so one timer can be shown on a laptop and
+//! a hallway monitor at once.
+//!
+//! The state directory is created private to the user, and one owned by
+//! anybody else is refused, since it may sit in a shared temporary
+//! directory. Each update records the id of the process publishing it, so a
+//! file left behind by a timer that was killed is recognized as stale and
+//! removed instead of showing a timer that no longer runs.
+
+use crate::atomic::write_atomic;
+use crate::bus::{ProgressEvent, ProgressUpdate, Subscriber};
+use crate::error::PbError;
+use crate::output::{render_json, JsonUpdate};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Option name used in error messages
+const OPTION_NAME: &str = "--name";
+
+/// Longest accepted timer name
+pub const MAX_NAME_LENGTH: usize = 64;
+
+/// Name of a timer shared with `pmon attach`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimerName(String);
+
+impl TimerName {
+    /// The name as given
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Path of the state file mirroring this timer
+    pub fn state_path(&self) -> PathBuf {
+        state_dir().join(format!("{}.json", self.0))
+    }
+}
+
+impl FromStr for TimerName {
+    type Err = PbError;
+
+    /// Accept letters, digits, `-`, `_`, and `.`, not starting with `.`
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let name = input.trim();
+        let is_valid = !name.is_empty()
+            && name.len() <= MAX_NAME_LENGTH
+            && !name.starts_with('.')
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c));
+        if !is_valid {
+            return Err(PbError::invalid_argument(OPTION_NAME, input));
+        }
+        Ok(Self(name.to_string()))
+    }
+}
+
+/// Directory holding the state files of running named timers
+///
+/// Uses `$XDG_RUNTIME_DIR/pmon` when set, so files are private to the user
+/// and cleared on logout, and the system temporary directory otherwise.
+pub fn state_dir() -> PathBuf {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("pmon")
+}
+
+/// Mirrors a named timer's updates into its state file
+///
+/// The file is removed when the publisher is dropped, which tells attached
+/// viewers that the timer has ended.
+#[derive(Debug)]
+pub struct TimerPublisher {
+    path: PathBuf,
+}
+
+impl TimerPublisher {
+    /// Publish updates for `name`
+    pub fn new(name: &TimerName) -> Self {
+        Self::at(name.state_path())
+    }
+
+    /// Publish updates to an explicit state file
+    pub fn at(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Replace the state file with `json`, one [`render_json`] line
+    ///
+    /// The update is stamped with this process's id and written atomically,
+    /// so readers never see a partially written update.
+    ///
+    /// [`render_json`]: crate::output::render_json
+    pub fn publish(&self, json: &str) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            create_private_dir(dir)?;
+        }
+        let mut update: serde_json::Map<String, serde_json::Value> = serde_json::from_str(json)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        update.insert("pid".to_string(), std::process::id().into());
+        let line = serde_json::Value::Object(update).to_string();
+        write_atomic(&self.path, format!("{line}\n").as_bytes())
+    }
+}
+
+impl Subscriber for TimerPublisher {
+    fn on_event(
+        &mut self,
+        event: ProgressEvent,
+        update: &ProgressUpdate,
+        _out: &mut dyn Write,
+    ) -> io::Result<()> {
+        if event == ProgressEvent::Tick {
+            // Attached viewers only miss an update if this fails
+            let _ = self.publish(&render_json(update.state, update.context));
+        }
+        Ok(())
+    }
+}
+
+impl Drop for TimerPublisher {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Create `dir` readable only by the current user, or check that it is
+///
+/// An existing directory owned by another user, or a symbolic link in its
+/// place, is refused with [`io::ErrorKind::PermissionDenied`].
+pub fn create_private_dir(dir: &Path) -> io::Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(dir)?;
+    let metadata = fs::symlink_metadata(dir)?;
+    if !metadata.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("{} is not a directory", dir.display()),
+        ));
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        // SAFETY: geteuid(2) cannot fail and touches no memory
+        if metadata.uid() != unsafe { libc::geteuid() } {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("{} belongs to another user", dir.display()),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Whether the process with id `pid` is still running
+#[cfg(unix)]
+pub fn process_is_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // SAFETY: kill(2) with signal 0 only checks that the process exists
+    let signaled = unsafe { libc::kill(pid, 0) } == 0;
+    // Another user's process cannot be signaled, but it exists
+    signaled || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// Without a way to ask, every publisher is taken to be running
+#[cfg(not(unix))]
+pub fn process_is_alive(_pid: u32) -> bool {
+    true
+}
+
+/// Read the latest update from a state file
+///
+/// Returns `Ok(None)` when the file does not exist, i.e. the timer is not
+/// running. A file whose publishing process has exited is stale: it is
+/// removed and also reported as `Ok(None)`.
+pub fn read_update(path: &Path) -> io::Result<Option<JsonUpdate>> {
+    match fs::read_to_string(path) {
+        Ok(contents) => {
+            let update = JsonUpdate::parse(contents.trim())
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            if update.pid.is_some_and(|pid| !process_is_alive(pid)) {
+                let _ = fs::remove_file(path);
+                return Ok(None);
+            }
+            Ok(Some(update))
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Named timers running on this machine, sorted by name
+///
+/// State files that cannot be read, such as one being replaced, are skipped.
+pub fn running_timers() -> io::Result<Vec<(TimerName, JsonUpdate)>> {
+    running_timers_in(&state_dir())
+}
+
+/// Named timers with state files in `dir`, sorted by name
+pub fn running_timers_in(dir: &Path) -> io::Result<Vec<(TimerName, JsonUpdate)>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut timers = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_suffix(".json"))
+            .and_then(|name| name.parse::<TimerName>().ok());
+        if let (Some(name), Ok(Some(update))) = (name, read_update(&path)) {
+            timers.push((name, update));
+        }
+    }
+    timers.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+    Ok(timers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::{render_json, UpdateContext};
+    use crate::progress_state::ProgressState;
+    use chrono::NaiveDateTime;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_parse_timer_name() {
+        let name: TimerName = "standup-2".parse().unwrap();
+        assert_eq!(name.as_str(), "standup-2");
+        assert!(name.state_path().ends_with("pmon/standup-2.json"));
+
+        let too_long = "x".repeat(MAX_NAME_LENGTH + 1);
+        for input in [
+            "",
+            ".hidden",
+            "../etc",
+            "a/b",
+            "with space",
+            too_long.as_str(),
+        ] {
+            assert!(
+                matches!(
+                    input.parse::<TimerName>(),
+                    Err(PbError::InvalidArgument { .. })
+                ),
+                "Expected '{input}' to be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_publish_and_read_update() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("demo.json");
+        assert!(read_update(&path).unwrap().is_none());
+
+        let state = ProgressState::new(
+            create_test_datetime("2025-01-27 09:00:00"),
+            create_test_datetime("2025-01-27 10:00:00"),
+            create_test_datetime("2025-01-27 09:30:00"),
+        );
+        let context = UpdateContext {
+            label: Some("Demo"),
+            ..UpdateContext::default()
+        };
+
+        {
+            let publisher = TimerPublisher::at(path.clone());
+            publisher.publish(&render_json(&state, &context)).unwrap();
+            let update = read_update(&path).unwrap().unwrap();
+            assert_eq!(update.label.as_deref(), Some("Demo"));
+            assert_eq!(update.state(), state);
+        }
+
+        // Dropping the publisher ends the timer
+        assert!(read_update(&path).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_read_update_drops_files_of_exited_processes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("standup.json");
+        let state = ProgressState::new(
+            create_test_datetime("2025-01-27 09:00:00"),
+            create_test_datetime("2025-01-27 10:00:00"),
+            create_test_datetime("2025-01-27 09:30:00"),
+        );
+        let publisher = TimerPublisher::at(path.clone());
+        publisher
+            .publish(&render_json(&state, &UpdateContext::default()))
+            .unwrap();
+        let update = read_update(&path).unwrap().unwrap();
+        assert_eq!(update.pid, Some(std::process::id()));
+
+        // A publisher killed before it could clean up leaves its file behind
+        std::mem::forget(publisher);
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let exited = child.id();
+        child.wait().unwrap();
+        let json = fs::read_to_string(&path).unwrap().replace(
+            &format!("\"pid\":{}", std::process::id()),
+            &format!("\"pid\":{exited}"),
+        );
+        fs::write(&path, json).unwrap();
+        assert!(read_update(&path).unwrap().is_none());
+        assert!(!path.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_state_dir_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let state = dir.path().join("pmon");
+        create_private_dir(&state).unwrap();
+        let mode = fs::metadata(&state).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+
+        // A symbolic link planted in place of the directory is refused
+        let link = dir.path().join("planted");
+        std::os::unix::fs::symlink(&state, &link).unwrap();
+        assert_eq!(
+            create_private_dir(&link).unwrap_err().kind(),
+            io::ErrorKind::PermissionDenied
+        );
+    }
+
+    #[test]
+    fn test_list_running_timers() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(running_timers_in(&dir.path().join("missing"))
+            .unwrap()
+            .is_empty());
+
+        let state = ProgressState::new(
+            create_test_datetime("2025-01-27 09:00:00"),
+            create_test_datetime("2025-01-27 10:00:00"),
+            create_test_datetime("2025-01-27 09:30:00"),
+        );
+        let json = render_json(&state, &UpdateContext::default());
+        for file in ["review.json", "deploy.json", "deploy.json.tmp"] {
+            fs::write(dir.path().join(file), &json).unwrap();
+        }
+        fs::write(dir.path().join("broken.json"), "{").unwrap();
+
+        let names: Vec<String> = running_timers_in(dir.path())
+            .unwrap()
+            .into_iter()
+            .map(|(name, _)| name.as_str().to_string())
+            .collect();
+        assert_eq!(names, ["deploy", "review"]);
+    }
+
+    #[test]
+    fn test_read_corrupt_update() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("broken.json");
+        fs::write(&path, "{\"label\":").unwrap();
+        assert_eq!(
+            read_update(&path).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! Burndown chart of the work left over the window
+//!
+//! With `--burndown`, a small line chart is drawn beneath the bar: the
+//! expected linear burndown from 100% left at the start to 0% at the end,
+//! and the actual amount left at each update. The actual line follows the
+//! work reported through `--progress-file` when it is given, and the time
+//! left otherwise, so sprint leads can see the two diverge as the window
+//! goes on.
+
+/// Columns of the plot area, matching the width of the progress bar
+pub const CHART_WIDTH: usize = 40;
+
+/// Rows of the plot area, from 100% left at the top to 0% at the bottom
+pub const CHART_HEIGHT: usize = 8;
+
+/// Mark of the expected linear burndown
+const EXPECTED_MARK: char = '·';
+
+/// Mark of the actual amount left
+const ACTUAL_MARK: char = '█';
+
+/// Amounts left recorded over a window
+#[derive(Debug, Clone, Default)]
+pub struct Burndown {
+    /// Fraction of the window elapsed and percent left, in recording order
+    samples: Vec<(f64, f64)>,
+}
+
+impl Burndown {
+    /// Record `remaining` percent left once `elapsed` percent of the window
+    /// has passed
+    pub fn record(&mut self, elapsed: f64, remaining: f64) {
+        if elapsed.is_finite() && remaining.is_finite() {
+            self.samples
+                .push(((elapsed / 100.0).max(0.0), remaining.clamp(0.0, 100.0)));
+        }
+    }
+
+    /// Render the chart, with `legend` naming what the actual line tracks
+    ///
+    /// Updates past the end of the window are drawn in the last column.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pmon::burndown::{Burndown, CHART_HEIGHT};
+    ///
+    /// let mut burndown = Burndown::default();
+    /// burndown.record(0.0, 100.0);
+    /// burndown.record(50.0, 80.0);
+    /// let lines = burndown.render("work left");
+    /// assert_eq!(lines.len(), CHART_HEIGHT + 2);
+    /// assert!(lines[0].starts_with("100% │█"));
+    /// assert!(lines.last().unwrap().ends_with("· expected  █ work left"));
+    /// ```
+    pub fn render(&self, legend: &str) -> Vec<String> {
+        let mut grid = vec![vec![' '; CHART_WIDTH]; CHART_HEIGHT];
+        let row_of = |remaining: f64| {
+            let row = ((100.0 - remaining) / 100.0 * (CHART_HEIGHT - 1) as f64).round();
+            (row.max(0.0) as usize).min(CHART_HEIGHT - 1)
+        };
+        let column_of =
+            |elapsed: f64| ((elapsed * CHART_WIDTH as f64) as usize).min(CHART_WIDTH - 1);
+
+        let expected_rows = (0..CHART_WIDTH)
+            .map(|column| row_of(100.0 - (column as f64 + 0.5) / CHART_WIDTH as f64 * 100.0));
+        for (column, row) in expected_rows.enumerate() {
+            grid[row][column] = EXPECTED_MARK;
+        }
+        // The latest update in a column wins
+        for &(elapsed, remaining) in &self.samples {
+            grid[row_of(remaining)][column_of(elapsed)] = ACTUAL_MARK;
+        }
+
+        let mut lines: Vec<String> = grid
+            .into_iter()
+            .enumerate()
+            .map(|(row, cells)| {
+                let axis = match row {
+                    0 => "100%",
+                    row if row == CHART_HEIGHT - 1 => "  0%",
+                    _ => "    ",
+                };
+                let cells: String = cells.into_iter().collect();
+                format!("{axis} │{}", cells.trim_end())
+            })
+            .collect();
+        lines.push(format!("     └{}", "─".repeat(CHART_WIDTH)));
+        lines.push(format!(
+            "      {EXPECTED_MARK} expected  {ACTUAL_MARK} {legend}"
+        ));
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(lines: &[String], column: usize) -> Vec<char> {
+        lines[..CHART_HEIGHT]
+            .iter()
+            .map(|line| line.chars().nth(6 + column).unwrap_or(' '))
+            .collect()
+    }
+
+    #[test]
+    fn test_empty_chart_shows_expected_line() {
+        let lines = Burndown::default().render("time left");
+        assert_eq!(lines[0], "100% │···");
+        assert_eq!(lines[CHART_HEIGHT - 1].chars().last(), Some('·'));
+        assert!(lines[CHART_HEIGHT - 1].starts_with("  0% │     "));
+        assert_eq!(lines[CHART_HEIGHT], format!("     └{}", "─".repeat(40)));
+    }
+
+    #[test]
+    fn test_actual_line_shows_divergence() {
+        let mut burndown = Burndown::default();
+        burndown.record(0.0, 100.0);
+        // Halfway through the window with nothing done
+        burndown.record(50.0, 100.0);
+        let lines = burndown.render("work left");
+
+        let halfway = column(&lines, CHART_WIDTH / 2);
+        assert_eq!(halfway[0], ACTUAL_MARK);
+        assert_eq!(halfway[4], EXPECTED_MARK);
+        // Columns not reached yet only show the expected line
+        assert!(!column(&lines, CHART_WIDTH / 2 + 1).contains(&ACTUAL_MARK));
+    }
+
+    #[test]
+    fn test_overtime_is_drawn_in_last_column() {
+        let mut burndown = Burndown::default();
+        burndown.record(150.0, 0.0);
+        burndown.record(f64::NAN, 50.0);
+        let lines = burndown.render("work left");
+        assert_eq!(
+            column(&lines, CHART_WIDTH - 1)[CHART_HEIGHT - 1],
+            ACTUAL_MARK
+        );
+        assert_eq!(burndown.samples.len(), 1);
+    }
+}
@@ -0,0 +1,328 @@
+//! Progress events shared by everything that follows a window
+//!
+//! The progress loop publishes each update to an [`EventBus`], which turns
+//! it into [`ProgressEvent`]s: a `Tick` for every update, a `Milestone` as
+//! the window passes 25, 50, 75, and 90 percent, `Completed` when it reaches
+//! its end, `Overtime` when it runs past it, and `Paused` when updates stop
+//! while pmon waits for input. Milestones from the configuration file raise
+//! `Reached` once the window gets to them. Machine-readable output, hooks, the state
+//! file of `pmon attach`, D-Bus widgets, and telemetry subscribe to the bus
+//! rather than each checking the progress on its own.
+
+use crate::milestones::MilestoneTrigger;
+use crate::output::UpdateContext;
+use crate::progress_state::ProgressState;
+use chrono::Duration;
+use std::io::{self, Write};
+use std::time::Instant;
+
+/// Percentages announced with [`ProgressEvent::Milestone`]
+pub const MILESTONES: [u16; 4] = [25, 50, 75, 90];
+
+/// Something that happened to a window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressEvent {
+    /// The window was updated
+    Tick,
+    /// The window passed this percentage
+    Milestone(u16),
+    /// The window reached the watched milestone at this index
+    Reached(usize),
+    /// The window reached its end
+    Completed,
+    /// The window ran past its end
+    Overtime,
+    /// Updates stopped while pmon waits for input
+    Paused,
+}
+
+/// An update as subscribers see it
+#[derive(Debug, Clone, Copy)]
+pub struct ProgressUpdate<'a> {
+    /// Progress of the window
+    pub state: &'a ProgressState,
+    /// Timer details of the update
+    pub context: &'a UpdateContext<'a>,
+    /// When the update was made, sped up along with a `--demo`
+    pub now: Instant,
+}
+
+/// Something that reacts to progress events
+pub trait Subscriber {
+    /// React to `event`, writing any output to `out`
+    ///
+    /// Subscribers deal with failures of their own services; only a failure
+    /// to write `out` is returned.
+    fn on_event(
+        &mut self,
+        event: ProgressEvent,
+        update: &ProgressUpdate,
+        out: &mut dyn Write,
+    ) -> io::Result<()>;
+}
+
+/// The subscribers of a window and which of its events have been raised
+#[derive(Default)]
+pub struct EventBus<'a> {
+    subscribers: Vec<&'a mut dyn Subscriber>,
+    milestones_passed: usize,
+    watched: Vec<(MilestoneTrigger, bool)>,
+    started: bool,
+    completed: bool,
+    overtime: bool,
+}
+
+impl<'a> EventBus<'a> {
+    /// A bus with no subscribers for a new window
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send the events of later updates to `subscriber`, after the earlier ones
+    pub fn subscribe(&mut self, subscriber: &'a mut dyn Subscriber) {
+        self.subscribers.push(subscriber);
+    }
+
+    /// Raise [`ProgressEvent::Reached`] as the window reaches `triggers`
+    ///
+    /// Triggers already reached on the first update are passed over
+    /// silently, so joining a window late does not replay them.
+    pub fn watch(&mut self, triggers: Vec<MilestoneTrigger>) {
+        self.watched = triggers
+            .into_iter()
+            .map(|trigger| (trigger, false))
+            .collect();
+    }
+
+    /// Events raised by an update to `state`, `Tick` first
+    ///
+    /// Milestones are raised once per window. `Completed` and `Overtime` are
+    /// raised again when a snooze moves the end and the window reaches it
+    /// once more.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::NaiveDateTime;
+    /// use pmon::bus::{EventBus, ProgressEvent};
+    /// use pmon::ProgressState;
+    ///
+    /// let at = |time| NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M").unwrap();
+    /// let state = ProgressState::new(at("2025-01-27 09:00"), at("2025-01-27 10:00"), at("2025-01-27 09:30"));
+    /// let mut bus = EventBus::new();
+    /// assert_eq!(
+    ///     bus.events(&state),
+    ///     [ProgressEvent::Tick, ProgressEvent::Milestone(25), ProgressEvent::Milestone(50)]
+    /// );
+    /// assert_eq!(bus.events(&state), [ProgressEvent::Tick]);
+    /// ```
+    pub fn events(&mut self, state: &ProgressState) -> Vec<ProgressEvent> {
+        let mut events = vec![ProgressEvent::Tick];
+        while let Some(&percent) = MILESTONES.get(self.milestones_passed) {
+            if state.percentage < f64::from(percent) {
+                break;
+            }
+            events.push(ProgressEvent::Milestone(percent));
+            self.milestones_passed += 1;
+        }
+        for (index, (trigger, reached)) in self.watched.iter_mut().enumerate() {
+            if !*reached && trigger.is_reached(state) {
+                *reached = true;
+                if self.started {
+                    events.push(ProgressEvent::Reached(index));
+                }
+            }
+        }
+        self.started = true;
+        let completed = state.is_complete();
+        if completed && !self.completed {
+            events.push(ProgressEvent::Completed);
+        }
+        self.completed = completed;
+        let overtime = state.overtime() > Duration::zero();
+        if overtime && !self.overtime {
+            events.push(ProgressEvent::Overtime);
+        }
+        self.overtime = overtime;
+        events
+    }
+
+    /// Send the events of `update` to every subscriber, in order
+    pub fn publish(&mut self, update: &ProgressUpdate, out: &mut dyn Write) -> io::Result<()> {
+        for event in self.events(update.state) {
+            self.send(event, update, out)?;
+        }
+        Ok(())
+    }
+
+    /// Tell the subscribers that updates stop after `update` for a while
+    pub fn pause(&mut self, update: &ProgressUpdate, out: &mut dyn Write) -> io::Result<()> {
+        self.send(ProgressEvent::Paused, update, out)
+    }
+
+    fn send(
+        &mut self,
+        event: ProgressEvent,
+        update: &ProgressUpdate,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        for subscriber in self.subscribers.iter_mut() {
+            subscriber.on_event(event, update, out)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn state(end: &str, current: &str) -> ProgressState {
+        ProgressState::new(
+            create_test_datetime("2025-01-27 09:00:00"),
+            create_test_datetime(end),
+            create_test_datetime(current),
+        )
+    }
+
+    /// Records the events it receives and writes their names
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<ProgressEvent>,
+    }
+
+    impl Subscriber for Recorder {
+        fn on_event(
+            &mut self,
+            event: ProgressEvent,
+            _update: &ProgressUpdate,
+            out: &mut dyn Write,
+        ) -> io::Result<()> {
+            self.events.push(event);
+            writeln!(out, "{event:?}")
+        }
+    }
+
+    #[test]
+    fn test_events_follow_the_window() {
+        let mut bus = EventBus::new();
+        let end = "2025-01-27 10:00:00";
+        assert_eq!(
+            bus.events(&state(end, "2025-01-27 09:10:00")),
+            [ProgressEvent::Tick]
+        );
+        assert_eq!(
+            bus.events(&state(end, "2025-01-27 09:55:00")),
+            [
+                ProgressEvent::Tick,
+                ProgressEvent::Milestone(25),
+                ProgressEvent::Milestone(50),
+                ProgressEvent::Milestone(75),
+                ProgressEvent::Milestone(90),
+            ]
+        );
+        assert_eq!(
+            bus.events(&state(end, "2025-01-27 10:00:00")),
+            [ProgressEvent::Tick, ProgressEvent::Completed]
+        );
+        assert_eq!(
+            bus.events(&state(end, "2025-01-27 10:01:00")),
+            [ProgressEvent::Tick, ProgressEvent::Overtime]
+        );
+        assert_eq!(
+            bus.events(&state(end, "2025-01-27 10:02:00")),
+            [ProgressEvent::Tick]
+        );
+    }
+
+    #[test]
+    fn test_watched_milestones_are_reached_once() {
+        let mut bus = EventBus::new();
+        bus.watch(vec![
+            "+5m".parse().unwrap(),
+            "-10m".parse().unwrap(),
+            "9:30".parse().unwrap(),
+        ]);
+        let end = "2025-01-27 10:00:00";
+        // Joining after the first milestone passes it over
+        assert_eq!(
+            bus.events(&state(end, "2025-01-27 09:10:00")),
+            [ProgressEvent::Tick]
+        );
+        assert_eq!(
+            bus.events(&state(end, "2025-01-27 09:30:00")),
+            [
+                ProgressEvent::Tick,
+                ProgressEvent::Milestone(25),
+                ProgressEvent::Milestone(50),
+                ProgressEvent::Reached(2),
+            ]
+        );
+        assert_eq!(
+            bus.events(&state(end, "2025-01-27 09:51:00")),
+            [
+                ProgressEvent::Tick,
+                ProgressEvent::Milestone(75),
+                ProgressEvent::Reached(1),
+            ]
+        );
+        assert_eq!(
+            bus.events(&state(end, "2025-01-27 09:52:00")),
+            [ProgressEvent::Tick]
+        );
+    }
+
+    #[test]
+    fn test_snoozed_window_completes_again() {
+        let mut bus = EventBus::new();
+        bus.events(&state("2025-01-27 10:00:00", "2025-01-27 10:00:00"));
+        // Snoozing moves the end; milestones are not raised a second time
+        let snoozed = "2025-01-27 10:05:00";
+        assert_eq!(
+            bus.events(&state(snoozed, "2025-01-27 10:01:00")),
+            [ProgressEvent::Tick]
+        );
+        assert_eq!(
+            bus.events(&state(snoozed, "2025-01-27 10:05:00")),
+            [ProgressEvent::Tick, ProgressEvent::Completed]
+        );
+    }
+
+    #[test]
+    fn test_publish_reaches_subscribers_in_order() {
+        let (mut first, mut second) = (Recorder::default(), Recorder::default());
+        let state = state("2025-01-27 10:00:00", "2025-01-27 10:00:00");
+        let context = UpdateContext::default();
+        let update = ProgressUpdate {
+            state: &state,
+            context: &context,
+            now: Instant::now(),
+        };
+        let mut out = Vec::new();
+        let mut bus = EventBus::new();
+        bus.subscribe(&mut first);
+        bus.subscribe(&mut second);
+        bus.publish(&update, &mut out).unwrap();
+        bus.pause(&update, &mut out).unwrap();
+        drop(bus);
+
+        let expected = [
+            ProgressEvent::Tick,
+            ProgressEvent::Milestone(25),
+            ProgressEvent::Milestone(50),
+            ProgressEvent::Milestone(75),
+            ProgressEvent::Milestone(90),
+            ProgressEvent::Completed,
+            ProgressEvent::Paused,
+        ];
+        assert_eq!(first.events, expected);
+        assert_eq!(second.events, expected);
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("Tick\nTick\nMilestone(25)\nMilestone(25)\n"));
+    }
+}
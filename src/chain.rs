@@ -0,0 +1,289 @@
+//! Timer chaining for the pmon CLI tool
+//!
+//! Several windows can run back-to-back in one process by separating them
+//! with `--then` on the command line:
+//!
+//! ```text
+//! pmon --end +25m --label focus --then --end +5m --label break
+//! ```
+//!
+//! Each chained window starts where the previous one ended unless it gives
+//! its own `--start`, and relative end times are measured from that start.
+
+use crate::error::PbResult;
+use crate::time_parser::{parse_time_with_base, validate_times};
+use chrono::{Duration, NaiveDateTime};
+use clap::Parser;
+
+/// Separator between chained windows on the command line
+pub const THEN_SEPARATOR: &str = "--then";
+
+/// Arguments accepted after each `--then`
+#[derive(Parser, Debug, Clone, PartialEq, Eq)]
+#[command(name = "--then")]
+pub struct SegmentArgs {
+    /// Start time; defaults to the end of the previous window
+    #[arg(short, long, help = "Start time (defaults to the previous end)")]
+    pub start: Option<String>,
+
+    /// End time, relative times are based on this window's start
+    #[arg(short, long, help = "End time")]
+    pub end: String,
+
+    /// Label for this window
+    #[arg(short, long, help = "Label displayed before the progress bar")]
+    pub label: Option<String>,
+}
+
+/// One resolved window in a chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    /// Label shown while this window runs
+    pub label: Option<String>,
+    /// Start of the window
+    pub start: NaiveDateTime,
+    /// End of the window
+    pub end: NaiveDateTime,
+}
+
+impl Segment {
+    /// Length of the window
+    pub fn duration(&self) -> Duration {
+        self.end - self.start
+    }
+
+    /// Restart the window at `now`, keeping its length
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::NaiveDateTime;
+    /// use pmon::chain::Segment;
+    ///
+    /// let at = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+    /// let mut meeting = Segment {
+    ///     label: None,
+    ///     start: at("2025-01-27 10:00:00"),
+    ///     end: at("2025-01-27 10:30:00"),
+    /// };
+    ///
+    /// meeting.reanchor(at("2025-01-27 10:07:00"));
+    /// assert_eq!(meeting.start, at("2025-01-27 10:07:00"));
+    /// assert_eq!(meeting.end, at("2025-01-27 10:37:00"));
+    /// ```
+    pub fn reanchor(&mut self, now: NaiveDateTime) {
+        let duration = self.duration();
+        self.start = now;
+        self.end = now + duration;
+    }
+
+    /// Push the end to `by` after `now`, keeping the start
+    ///
+    /// Used to snooze a window that has already elapsed.
+    pub fn snooze(&mut self, now: NaiveDateTime, by: Duration) {
+        self.end = now.max(self.end) + by;
+    }
+
+    /// Move the whole window later (positive `by`) or earlier (negative `by`)
+    pub fn shift(&mut self, by: Duration) {
+        self.start += by;
+        self.end += by;
+    }
+}
+
+/// Split command-line arguments at each `--then`
+///
+/// The first group keeps the program name and the main options; every
+/// following group holds the arguments of one chained window, without the
+/// separator itself.
+///
+/// # Examples
+///
+/// ```
+/// use pmon::chain::split_chain_args;
+///
+/// let groups = split_chain_args(["pmon", "--end", "+25m", "--then", "--end", "+5m"]);
+/// assert_eq!(groups, vec![vec!["pmon", "--end", "+25m"], vec!["--end", "+5m"]]);
+/// ```
+pub fn split_chain_args<I, T>(args: I) -> Vec<Vec<String>>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<String>,
+{
+    let mut groups = vec![Vec::new()];
+    let mut after_double_dash = false;
+    for arg in args {
+        let arg = arg.into();
+        // Everything after `--` belongs to a wrapped command
+        after_double_dash |= arg == "--";
+        if arg == THEN_SEPARATOR && !after_double_dash {
+            groups.push(Vec::new());
+        } else if let Some(group) = groups.last_mut() {
+            group.push(arg);
+        }
+    }
+    groups
+}
+
+/// Resolve chained windows that follow `first`
+///
+/// Returns the full chain, beginning with `first`. Every window is validated
+/// the same way as the main one.
+pub fn resolve_chain(first: Segment, rest: &[SegmentArgs]) -> PbResult<Vec<Segment>> {
+    let mut chain = vec![first];
+    for args in rest {
+        let previous_end = chain[chain.len() - 1].end;
+        let start = match &args.start {
+            Some(start) => parse_time_with_base(start, Some(previous_end))?,
+            None => previous_end,
+        };
+        let end = parse_time_with_base(&args.end, Some(start))?;
+        validate_times(start, end)?;
+        chain.push(Segment {
+            label: args.label.clone(),
+            start,
+            end,
+        });
+    }
+    Ok(chain)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn first_segment() -> Segment {
+        Segment {
+            label: Some("focus".to_string()),
+            start: create_test_datetime("2025-01-27 09:00:00"),
+            end: create_test_datetime("2025-01-27 09:25:00"),
+        }
+    }
+
+    fn segment_args(args: &[&str]) -> SegmentArgs {
+        SegmentArgs::try_parse_from(std::iter::once(THEN_SEPARATOR).chain(args.iter().copied()))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_split_without_then() {
+        let groups = split_chain_args(["pmon", "--end", "12:00"]);
+        assert_eq!(groups, vec![vec!["pmon", "--end", "12:00"]]);
+    }
+
+    #[test]
+    fn test_split_multiple_then() {
+        let groups = split_chain_args([
+            "pmon", "-e", "+25m", "--then", "-e", "+5m", "-l", "break", "--then", "-e", "+25m",
+        ]);
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[1], vec!["-e", "+5m", "-l", "break"]);
+        assert_eq!(groups[2], vec!["-e", "+25m"]);
+    }
+
+    #[test]
+    fn test_split_stops_at_double_dash() {
+        let groups = split_chain_args(["pmon", "-e", "+5m", "--", "pmon", "--then", "-e", "+1m"]);
+        assert_eq!(
+            groups,
+            vec![vec![
+                "pmon", "-e", "+5m", "--", "pmon", "--then", "-e", "+1m"
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_resolve_back_to_back() {
+        let rest = vec![
+            segment_args(&["--end", "+5m", "--label", "break"]),
+            segment_args(&["--end", "+25m"]),
+        ];
+        let chain = resolve_chain(first_segment(), &rest).unwrap();
+
+        assert_eq!(chain.len(), 3);
+        assert_eq!(chain[1].start, create_test_datetime("2025-01-27 09:25:00"));
+        assert_eq!(chain[1].end, create_test_datetime("2025-01-27 09:30:00"));
+        assert_eq!(chain[1].label.as_deref(), Some("break"));
+        assert_eq!(chain[2].start, create_test_datetime("2025-01-27 09:30:00"));
+        assert_eq!(chain[2].end, create_test_datetime("2025-01-27 09:55:00"));
+        assert_eq!(chain[2].label, None);
+    }
+
+    #[test]
+    fn test_resolve_explicit_start() {
+        let rest = vec![segment_args(&[
+            "--start",
+            "2025-01-27 10:00:00",
+            "--end",
+            "+1h",
+        ])];
+        let chain = resolve_chain(first_segment(), &rest).unwrap();
+
+        assert_eq!(chain[1].start, create_test_datetime("2025-01-27 10:00:00"));
+        assert_eq!(chain[1].end, create_test_datetime("2025-01-27 11:00:00"));
+    }
+
+    #[test]
+    fn test_resolve_rejects_invalid_window() {
+        let rest = vec![segment_args(&[
+            "--start",
+            "2025-01-27 10:00:00",
+            "--end",
+            "2025-01-27 09:00:00",
+        ])];
+        assert!(resolve_chain(first_segment(), &rest).is_err());
+
+        let rest = vec![segment_args(&["--end", "whenever"])];
+        assert!(resolve_chain(first_segment(), &rest).is_err());
+    }
+
+    #[test]
+    fn test_reanchor_keeps_length() {
+        let mut segment = first_segment();
+        segment.reanchor(create_test_datetime("2025-01-27 09:10:00"));
+        assert_eq!(segment.start, create_test_datetime("2025-01-27 09:10:00"));
+        assert_eq!(segment.end, create_test_datetime("2025-01-27 09:35:00"));
+        assert_eq!(segment.duration(), Duration::minutes(25));
+    }
+
+    #[test]
+    fn test_shift_both_directions() {
+        let mut segment = first_segment();
+        segment.shift(Duration::minutes(5));
+        assert_eq!(segment.start, create_test_datetime("2025-01-27 09:05:00"));
+        assert_eq!(segment.end, create_test_datetime("2025-01-27 09:30:00"));
+
+        segment.shift(-Duration::minutes(10));
+        assert_eq!(segment.start, create_test_datetime("2025-01-27 08:55:00"));
+        assert_eq!(segment.end, create_test_datetime("2025-01-27 09:20:00"));
+    }
+
+    #[test]
+    fn test_snooze_extends_from_now() {
+        let mut segment = first_segment();
+        segment.snooze(
+            create_test_datetime("2025-01-27 09:27:00"),
+            Duration::minutes(5),
+        );
+        assert_eq!(segment.start, create_test_datetime("2025-01-27 09:00:00"));
+        assert_eq!(segment.end, create_test_datetime("2025-01-27 09:32:00"));
+
+        // Snoozing before the end extends from the end itself
+        let mut segment = first_segment();
+        segment.snooze(
+            create_test_datetime("2025-01-27 09:20:00"),
+            Duration::minutes(5),
+        );
+        assert_eq!(segment.end, create_test_datetime("2025-01-27 09:30:00"));
+    }
+
+    #[test]
+    fn test_segment_args_require_end() {
+        let result = SegmentArgs::try_parse_from([THEN_SEPARATOR, "--label", "break"]);
+        assert!(result.is_err());
+    }
+}
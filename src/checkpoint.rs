@@ -0,0 +1,218 @@
+//! Named checkpoints inside a time window
+//!
+//! Checkpoints mark intermediate deadlines such as `demo@14:00` or
+//! `retro@16:00`. Each one gets its own countdown, rendered as a small list
+//! beneath the main progress bar in verbose mode.
+
+use crate::error::{PbError, PbResult};
+use crate::layout::{max_display_width, pad_end};
+use crate::progress_bar::DurationFormat;
+use crate::time_parser::parse_time_with_base;
+use chrono::NaiveDateTime;
+use std::str::FromStr;
+
+/// Option name used in error messages
+const OPTION_NAME: &str = "--checkpoint";
+
+/// A checkpoint as given on the command line, before its time is resolved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckpointSpec {
+    /// Checkpoint name
+    pub name: String,
+    /// Unparsed time expression (any format accepted by `--end`)
+    pub time: String,
+}
+
+impl FromStr for CheckpointSpec {
+    type Err = PbError;
+
+    /// Parse a `NAME@TIME` checkpoint such as `demo@14:00`
+    ///
+    /// The last `@` separates the name from the time, so names may contain
+    /// `@` themselves.
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let invalid = || PbError::invalid_argument(OPTION_NAME, input);
+
+        let (name, time) = input.rsplit_once('@').ok_or_else(invalid)?;
+        let (name, time) = (name.trim(), time.trim());
+        if name.is_empty() || time.is_empty() {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            time: time.to_string(),
+        })
+    }
+}
+
+impl CheckpointSpec {
+    /// Resolve the time expression, using `base` for relative times
+    pub fn resolve(&self, base: NaiveDateTime) -> PbResult<Checkpoint> {
+        Ok(Checkpoint {
+            name: self.name.clone(),
+            at: parse_time_with_base(&self.time, Some(base))?,
+        })
+    }
+}
+
+/// A checkpoint with a concrete time
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// Checkpoint name
+    pub name: String,
+    /// Moment the checkpoint is reached
+    pub at: NaiveDateTime,
+}
+
+/// Resolve all checkpoint specs and sort them chronologically
+pub fn resolve_checkpoints(
+    specs: &[CheckpointSpec],
+    base: NaiveDateTime,
+) -> PbResult<Vec<Checkpoint>> {
+    let mut checkpoints = specs
+        .iter()
+        .map(|spec| spec.resolve(base))
+        .collect::<PbResult<Vec<_>>>()?;
+    checkpoints.sort_by_key(|checkpoint| checkpoint.at);
+    Ok(checkpoints)
+}
+
+/// Render one line per checkpoint with its time and individual countdown
+///
+/// Names are padded to a common display width so the times line up.
+/// Checkpoints that have already been reached are marked as passed.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDateTime;
+/// use pmon::checkpoint::{render_checkpoints, Checkpoint};
+/// use pmon::progress_bar::DurationFormat;
+///
+/// let at = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+/// let checkpoints = vec![
+///     Checkpoint { name: "demo".into(), at: at("2025-01-27 14:00:00") },
+///     Checkpoint { name: "retro".into(), at: at("2025-01-27 16:00:00") },
+/// ];
+///
+/// let lines = render_checkpoints(&checkpoints, at("2025-01-27 14:30:00"), DurationFormat::default());
+/// assert_eq!(lines, vec!["  demo  14:00  passed", "  retro 16:00  in 1h 30m"]);
+/// ```
+pub fn render_checkpoints(
+    checkpoints: &[Checkpoint],
+    current: NaiveDateTime,
+    format: DurationFormat,
+) -> Vec<String> {
+    let name_width = max_display_width(checkpoints.iter().map(|c| c.name.as_str()));
+
+    checkpoints
+        .iter()
+        .map(|checkpoint| {
+            let status = if checkpoint.at <= current {
+                "passed".to_string()
+            } else {
+                format!("in {}", format.remaining(checkpoint.at - current))
+            };
+            format!(
+                "  {} {}  {status}",
+                pad_end(&checkpoint.name, name_width),
+                checkpoint.at.format("%H:%M")
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_parse_spec() {
+        let spec: CheckpointSpec = "demo@14:00".parse().unwrap();
+        assert_eq!(spec.name, "demo");
+        assert_eq!(spec.time, "14:00");
+
+        let spec: CheckpointSpec = "ping @alice@ +30m".parse().unwrap();
+        assert_eq!(spec.name, "ping @alice");
+        assert_eq!(spec.time, "+30m");
+    }
+
+    #[test]
+    fn test_parse_invalid_spec() {
+        for input in ["", "demo", "@14:00", "demo@", " @ "] {
+            let result = input.parse::<CheckpointSpec>();
+            assert!(
+                matches!(result, Err(PbError::InvalidArgument { .. })),
+                "Expected '{input}' to be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_resolve_relative_and_sorted() {
+        let base = create_test_datetime("2025-01-27 09:00:00");
+        let specs: Vec<CheckpointSpec> = ["retro@+7h", "demo@+5h"]
+            .iter()
+            .map(|spec| spec.parse().unwrap())
+            .collect();
+
+        let checkpoints = resolve_checkpoints(&specs, base).unwrap();
+        assert_eq!(checkpoints[0].name, "demo");
+        assert_eq!(
+            checkpoints[0].at,
+            create_test_datetime("2025-01-27 14:00:00")
+        );
+        assert_eq!(checkpoints[1].name, "retro");
+        assert_eq!(
+            checkpoints[1].at,
+            create_test_datetime("2025-01-27 16:00:00")
+        );
+    }
+
+    #[test]
+    fn test_resolve_invalid_time() {
+        let spec: CheckpointSpec = "demo@noonish".parse().unwrap();
+        assert!(spec
+            .resolve(create_test_datetime("2025-01-27 09:00:00"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_render_aligns_wide_names() {
+        let checkpoints = vec![
+            Checkpoint {
+                name: "発表".to_string(),
+                at: create_test_datetime("2025-01-27 14:00:00"),
+            },
+            Checkpoint {
+                name: "retro".to_string(),
+                at: create_test_datetime("2025-01-27 16:00:00"),
+            },
+        ];
+
+        let lines = render_checkpoints(
+            &checkpoints,
+            create_test_datetime("2025-01-27 13:15:00"),
+            DurationFormat::default(),
+        );
+        assert_eq!(
+            lines,
+            vec!["  発表  14:00  in 45m", "  retro 16:00  in 2h 45m"]
+        );
+    }
+
+    #[test]
+    fn test_render_empty() {
+        let lines = render_checkpoints(
+            &[],
+            create_test_datetime("2025-01-27 13:15:00"),
+            DurationFormat::default(),
+        );
+        assert!(lines.is_empty());
+    }
+}
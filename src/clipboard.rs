@@ -0,0 +1,79 @@
+//! Copying snapshots of the bar to the system clipboard
+//!
+//! `pmon snapshot --copy` and the `c` key hand a plain-text line, such as
+//! `[████████░░░░] 66.7% (2h 0m elapsed, 1h 0m remaining)`, to the
+//! platform's clipboard tool for pasting into chat: `pbcopy` on macOS,
+//! PowerShell's `Set-Clipboard` on Windows, and the first installed of
+//! [`CLIPBOARD_PROGRAMS`] elsewhere. Copying needs the `clipboard` feature.
+
+use crate::error::{PbError, PbResult};
+
+/// Clipboard tools tried in order outside macOS and Windows, with their
+/// arguments
+pub const CLIPBOARD_PROGRAMS: [(&str, &[&str]); 3] = [
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+];
+
+/// Put `text` on the system clipboard
+#[cfg(feature = "clipboard")]
+pub fn copy(text: &str) -> PbResult<()> {
+    use crate::doctor::find_program;
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("pbcopy")
+    } else if cfg!(windows) {
+        // Read as UTF-8 so emoji cells survive the trip
+        let mut command = Command::new("powershell");
+        command.args([
+            "-NoProfile",
+            "-Command",
+            "[Console]::InputEncoding = [Text.Encoding]::UTF8; \
+             Set-Clipboard -Value ([Console]::In.ReadToEnd())",
+        ]);
+        command
+    } else {
+        let (program, args) = CLIPBOARD_PROGRAMS
+            .into_iter()
+            .find(|(program, _)| find_program(program).is_some())
+            .ok_or_else(|| clipboard_error("install wl-clipboard, xclip, or xsel"))?;
+        let mut command = Command::new(program);
+        command.args(args);
+        command
+    };
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| clipboard_error(e.to_string()))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(text.as_bytes())
+            .map_err(|e| clipboard_error(e.to_string()))?;
+    }
+    let status = child.wait().map_err(|e| clipboard_error(e.to_string()))?;
+    if !status.success() {
+        return Err(clipboard_error(format!(
+            "{} exited with {status}",
+            command.get_program().to_string_lossy()
+        )));
+    }
+    Ok(())
+}
+
+/// The clipboard cannot be reached without the `clipboard` feature
+#[cfg(not(feature = "clipboard"))]
+pub fn copy(_text: &str) -> PbResult<()> {
+    Err(clipboard_error(
+        "pmon was built without the `clipboard` feature",
+    ))
+}
+
+/// Delivery error raised while copying
+fn clipboard_error(reason: impl Into<String>) -> PbError {
+    PbError::delivery("the clipboard", reason)
+}
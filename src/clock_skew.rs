@@ -0,0 +1,152 @@
+//! Detection of system clock adjustments during a run
+//!
+//! Progress is computed from wall-clock time, which jumps when the system
+//! clock is adjusted (manual changes, NTP steps, resuming from suspend with a
+//! corrected clock). Comparing how far the wall clock moved with how far a
+//! monotonic clock moved between two updates reveals such jumps.
+
+use chrono::{Duration, NaiveDateTime};
+use std::time::Instant;
+
+/// Differences smaller than this are treated as scheduling jitter
+pub const SKEW_THRESHOLD: Duration = Duration::seconds(2);
+
+/// Tracks wall-clock jumps relative to a monotonic clock
+#[derive(Debug, Clone)]
+pub struct SkewDetector {
+    last_wall: NaiveDateTime,
+    last_instant: Instant,
+    total_skew: Duration,
+}
+
+impl SkewDetector {
+    /// Start tracking from a paired wall-clock and monotonic reading
+    pub fn new(wall: NaiveDateTime, instant: Instant) -> Self {
+        Self {
+            last_wall: wall,
+            last_instant: instant,
+            total_skew: Duration::zero(),
+        }
+    }
+
+    /// Record a new paired reading and return the jump since the previous one
+    ///
+    /// Returns `None` when the two clocks agree within [`SKEW_THRESHOLD`].
+    /// A positive jump means the wall clock moved forward more than real
+    /// time passed.
+    pub fn observe(&mut self, wall: NaiveDateTime, instant: Instant) -> Option<Duration> {
+        let wall_delta = wall - self.last_wall;
+        let monotonic_delta = Duration::from_std(instant.duration_since(self.last_instant))
+            .unwrap_or(Duration::zero());
+        self.last_wall = wall;
+        self.last_instant = instant;
+
+        let jump = wall_delta - monotonic_delta;
+        if jump.abs() < SKEW_THRESHOLD {
+            return None;
+        }
+        self.total_skew += jump;
+        Some(jump)
+    }
+
+    /// Net wall-clock adjustment observed so far
+    pub fn total_skew(&self) -> Duration {
+        self.total_skew
+    }
+
+    /// Whether any adjustment has been observed
+    pub fn has_skew(&self) -> bool {
+        self.total_skew != Duration::zero()
+    }
+}
+
+/// Describe the net clock adjustment for the verbose view
+///
+/// # Examples
+///
+/// ```
+/// use chrono::Duration;
+/// use pmon::clock_skew::format_skew_warning;
+///
+/// assert_eq!(
+///     format_skew_warning(Duration::seconds(-90)),
+///     "⚠ System clock adjusted by -1m 30s during this run"
+/// );
+/// ```
+pub fn format_skew_warning(skew: Duration) -> String {
+    let sign = if skew < Duration::zero() { "-" } else { "+" };
+    let seconds = skew.num_seconds().abs();
+    let amount = if seconds >= 60 {
+        format!("{}m {}s", seconds / 60, seconds % 60)
+    } else {
+        format!("{seconds}s")
+    };
+    format!("⚠ System clock adjusted by {sign}{amount} during this run")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_no_skew_when_clocks_agree() {
+        let instant = Instant::now();
+        let mut detector = SkewDetector::new(create_test_datetime("2025-01-27 10:00:00"), instant);
+
+        let jump = detector.observe(
+            create_test_datetime("2025-01-27 10:01:00"),
+            instant + std::time::Duration::from_secs(60),
+        );
+        assert_eq!(jump, None);
+        assert!(!detector.has_skew());
+    }
+
+    #[test]
+    fn test_detects_forward_and_backward_jumps() {
+        let instant = Instant::now();
+        let mut detector = SkewDetector::new(create_test_datetime("2025-01-27 10:00:00"), instant);
+
+        // Wall clock moved 6 minutes while only one minute passed
+        let jump = detector.observe(
+            create_test_datetime("2025-01-27 10:06:00"),
+            instant + std::time::Duration::from_secs(60),
+        );
+        assert_eq!(jump, Some(Duration::minutes(5)));
+
+        // Then set back by 2 minutes
+        let jump = detector.observe(
+            create_test_datetime("2025-01-27 10:05:00"),
+            instant + std::time::Duration::from_secs(120),
+        );
+        assert_eq!(jump, Some(Duration::minutes(-2)));
+        assert_eq!(detector.total_skew(), Duration::minutes(3));
+    }
+
+    #[test]
+    fn test_small_jitter_is_ignored() {
+        let instant = Instant::now();
+        let mut detector = SkewDetector::new(create_test_datetime("2025-01-27 10:00:00"), instant);
+
+        let jump = detector.observe(
+            create_test_datetime("2025-01-27 10:01:01"),
+            instant + std::time::Duration::from_secs(60),
+        );
+        assert_eq!(jump, None);
+    }
+
+    #[test]
+    fn test_format_skew_warning() {
+        assert_eq!(
+            format_skew_warning(Duration::seconds(5)),
+            "⚠ System clock adjusted by +5s during this run"
+        );
+        assert_eq!(
+            format_skew_warning(Duration::seconds(303)),
+            "⚠ System clock adjusted by +5m 3s during this run"
+        );
+    }
+}
@@ -0,0 +1,79 @@
+//! Terminal colors
+//!
+//! With the `color` feature this is the `colored` crate. Without it, the
+//! same methods return the text unchanged, so renderers are written once and
+//! a minimal build prints plain output.
+
+#[cfg(feature = "color")]
+pub use colored::{control, Colorize};
+
+/// Plain stand-ins for the `colored` methods pmon uses
+#[cfg(not(feature = "color"))]
+pub trait Colorize: std::fmt::Display + Sized {
+    fn red(self) -> String {
+        self.to_string()
+    }
+    fn green(self) -> String {
+        self.to_string()
+    }
+    fn yellow(self) -> String {
+        self.to_string()
+    }
+    fn blue(self) -> String {
+        self.to_string()
+    }
+    fn magenta(self) -> String {
+        self.to_string()
+    }
+    fn cyan(self) -> String {
+        self.to_string()
+    }
+    fn bold(self) -> String {
+        self.to_string()
+    }
+    fn dimmed(self) -> String {
+        self.to_string()
+    }
+    fn reversed(self) -> String {
+        self.to_string()
+    }
+}
+
+#[cfg(not(feature = "color"))]
+impl Colorize for &str {}
+
+#[cfg(not(feature = "color"))]
+impl Colorize for String {}
+
+/// Stand-in for `colored::control`; there is nothing to turn on or off
+#[cfg(not(feature = "color"))]
+pub mod control {
+    /// Colors are never written without the `color` feature
+    pub fn set_override(_override_colorize: bool) {}
+}
+
+/// Colors a bar can be given, such as `color = "yellow"` in the configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BarColor {
+    Red,
+    Yellow,
+    Green,
+    Blue,
+    Magenta,
+    Cyan,
+}
+
+impl BarColor {
+    /// Color `text`, unless colors are turned off
+    pub fn paint(self, text: &str) -> String {
+        match self {
+            BarColor::Red => text.red().to_string(),
+            BarColor::Yellow => text.yellow().to_string(),
+            BarColor::Green => text.green().to_string(),
+            BarColor::Blue => text.blue().to_string(),
+            BarColor::Magenta => text.magenta().to_string(),
+            BarColor::Cyan => text.cyan().to_string(),
+        }
+    }
+}
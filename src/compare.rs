@@ -0,0 +1,208 @@
+//! Side-by-side comparison of running named timers
+//!
+//! `pmon compare` shows one bar per named timer, sorted so the timer with
+//! the least time left comes first, and marks the next one to expire. In a
+//! terminal, the arrow keys select a timer and Enter focuses it, replacing
+//! the bars with its detailed stats until Enter or Esc is pressed again.
+
+use crate::broadcast::TimerName;
+use crate::layout::fit_label;
+use crate::output::JsonUpdate;
+use crate::progress_bar::{format_percentage, DurationFormat};
+use crate::progress_state::ProgressState;
+
+/// Marker appended to the bar of the next timer to expire
+pub const NEXT_MARKER: &str = "← next";
+
+/// A running named timer and its latest update
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimerEntry {
+    /// Name the timer was started with
+    pub name: TimerName,
+    /// Latest update it published
+    pub update: JsonUpdate,
+}
+
+impl TimerEntry {
+    /// Label shown for the timer: its own label, or its name
+    pub fn label(&self) -> &str {
+        self.update.label.as_deref().unwrap_or(self.name.as_str())
+    }
+
+    fn state(&self) -> ProgressState {
+        self.update.state()
+    }
+}
+
+/// Sort timers by time left, running timers first and elapsed ones last
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDateTime;
+/// use pmon::compare::{sort_by_remaining, TimerEntry};
+/// use pmon::output::{render_json, JsonUpdate, UpdateContext};
+/// use pmon::progress_state::ProgressState;
+///
+/// let at = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+/// let entry = |name: &str, end| {
+///     let state = ProgressState::new(at("2025-01-27 09:00:00"), at(end), at("2025-01-27 09:30:00"));
+///     let json = render_json(&state, &UpdateContext::default());
+///     TimerEntry {
+///         name: name.parse().unwrap(),
+///         update: JsonUpdate::parse(&json).unwrap(),
+///     }
+/// };
+/// let mut entries = vec![
+///     entry("retro", "2025-01-27 11:00:00"),
+///     entry("standup", "2025-01-27 09:15:00"),
+///     entry("review", "2025-01-27 10:00:00"),
+/// ];
+/// sort_by_remaining(&mut entries);
+/// let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+/// assert_eq!(names, ["review", "retro", "standup"]);
+/// ```
+pub fn sort_by_remaining(entries: &mut [TimerEntry]) {
+    entries.sort_by_key(|entry| {
+        let state = entry.state();
+        (
+            state.is_complete(),
+            state.remaining(),
+            entry.name.as_str().to_string(),
+        )
+    });
+}
+
+/// Index of the running timer with the least time left
+pub fn next_to_expire(entries: &[TimerEntry]) -> Option<usize> {
+    entries
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| !entry.state().is_complete())
+        .min_by_key(|(_, entry)| entry.state().remaining())
+        .map(|(index, _)| index)
+}
+
+/// Detailed stats of one timer, shown when it is focused
+pub fn render_details(entry: &TimerEntry, format: DurationFormat) -> Vec<String> {
+    let state = entry.state();
+    let time = |time: chrono::NaiveDateTime| time.format("%Y-%m-%d %H:%M:%S").to_string();
+    let heading = match &entry.update.label {
+        Some(label) => format!("{} ({})", entry.name.as_str(), fit_label(label)),
+        None => entry.name.as_str().to_string(),
+    };
+    let mut lines = vec![
+        heading,
+        format!("  Progress   {}", format_percentage(state.percentage)),
+        format!("  Started    {}", time(state.start)),
+        format!("  Ends       {}", time(state.end)),
+        format!("  Elapsed    {}", format.elapsed(state.elapsed())),
+    ];
+    if state.is_complete() {
+        lines.push(format!("  Overtime   {}", format.elapsed(state.overtime())));
+    } else {
+        lines.push(format!(
+            "  Remaining  {}",
+            format.remaining(state.remaining())
+        ));
+    }
+    if let Some(message) = &entry.update.message {
+        lines.push(format!("  Message    {message}"));
+    }
+    if !entry.update.tags.is_empty() {
+        lines.push(format!("  Tags       {}", entry.update.tags.join(", ")));
+    }
+    lines.push(format!("  Updated    {}", time(state.current)));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::{render_json, UpdateContext};
+    use crate::progress_bar::DurationStyle;
+    use chrono::NaiveDateTime;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn entry(name: &str, label: Option<&str>, end: &str) -> TimerEntry {
+        let state = ProgressState::new(
+            create_test_datetime("2025-01-27 09:00:00"),
+            create_test_datetime(end),
+            create_test_datetime("2025-01-27 09:30:00"),
+        );
+        let context = UpdateContext {
+            label,
+            message: Some("stay focused"),
+            ..UpdateContext::default()
+        };
+        TimerEntry {
+            name: name.parse().unwrap(),
+            update: JsonUpdate::parse(&render_json(&state, &context)).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_sort_and_next_to_expire() {
+        let mut entries = vec![
+            entry("late", None, "2025-01-27 12:00:00"),
+            entry("done", None, "2025-01-27 09:20:00"),
+            entry("soon", None, "2025-01-27 09:45:00"),
+        ];
+        assert_eq!(next_to_expire(&entries), Some(2));
+
+        sort_by_remaining(&mut entries);
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["soon", "late", "done"]);
+        assert_eq!(next_to_expire(&entries), Some(0));
+    }
+
+    #[test]
+    fn test_no_next_when_all_elapsed() {
+        let entries = vec![entry("done", None, "2025-01-27 09:20:00")];
+        assert_eq!(next_to_expire(&entries), None);
+        assert!(next_to_expire(&[]).is_none());
+    }
+
+    #[test]
+    fn test_label_falls_back_to_name() {
+        assert_eq!(
+            entry("standup", None, "2025-01-27 10:00:00").label(),
+            "standup"
+        );
+        assert_eq!(
+            entry("standup", Some("Standup"), "2025-01-27 10:00:00").label(),
+            "Standup"
+        );
+    }
+
+    #[test]
+    fn test_render_details() {
+        let lines = render_details(
+            &entry("review", Some("Review"), "2025-01-27 10:00:00"),
+            DurationStyle::Compact.into(),
+        );
+        assert_eq!(
+            lines,
+            [
+                "review (Review)",
+                "  Progress   50.0%",
+                "  Started    2025-01-27 09:00:00",
+                "  Ends       2025-01-27 10:00:00",
+                "  Elapsed    30m",
+                "  Remaining  30m",
+                "  Message    stay focused",
+                "  Updated    2025-01-27 09:30:00",
+            ]
+        );
+
+        let mut done = entry("done", None, "2025-01-27 09:20:00");
+        done.update.tags = vec!["team:payments".to_string(), "sev2".to_string()];
+        let lines = render_details(&done, DurationStyle::Compact.into());
+        assert_eq!(lines[0], "done");
+        assert!(lines.contains(&"  Overtime   10m".to_string()));
+        assert!(lines.contains(&"  Tags       team:payments, sev2".to_string()));
+    }
+}
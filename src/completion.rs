@@ -0,0 +1,208 @@
+//! The message printed when a window completes
+//!
+//! By default pmon announces the end of a window in English. `--lang`
+//! picks another language, and `--lang auto` follows the locale
+//! (`$LC_ALL`, `$LC_MESSAGES`, or `$LANG`), falling back to English.
+//! `--complete-message` replaces it with a template, and an empty template
+//! prints nothing.
+//!
+//! # Placeholders
+//!
+//! - `{label}` - the window's label, or nothing
+//! - `{percent}` - percentage with one decimal place
+//! - `{elapsed}` - time since the start
+//! - `{overtime}` - time spent past the end
+//! - `{end}` - end time as `YYYY-MM-DD HH:MM`
+
+use crate::error::{PbError, PbResult};
+use crate::progress_bar::{format_percentage, DurationFormat};
+use crate::progress_state::ProgressState;
+
+/// A language the default message is translated into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+    Spanish,
+    French,
+    Japanese,
+}
+
+impl Language {
+    /// Language of a locale name such as `ja_JP.UTF-8`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pmon::completion::Language;
+    ///
+    /// assert_eq!(Language::from_locale("ja_JP.UTF-8"), Some(Language::Japanese));
+    /// assert_eq!(Language::from_locale("de"), Some(Language::German));
+    /// assert_eq!(Language::from_locale("C"), Some(Language::English));
+    /// assert_eq!(Language::from_locale("pt_BR"), None);
+    /// ```
+    pub fn from_locale(locale: &str) -> Option<Self> {
+        let code = locale
+            .split(['_', '.', '@', '-'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match code.as_str() {
+            "c" | "posix" | "en" => Some(Self::English),
+            "de" => Some(Self::German),
+            "es" => Some(Self::Spanish),
+            "fr" => Some(Self::French),
+            "ja" => Some(Self::Japanese),
+            _ => None,
+        }
+    }
+
+    /// Language of the first locale variable that is set and not empty
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|locale| !locale.is_empty())
+            .and_then(|locale| Self::from_locale(&locale))
+            .unwrap_or_default()
+    }
+
+    /// The default completion message in this language
+    pub fn complete_message(self) -> &'static str {
+        match self {
+            Self::English => "Progress completed! Time range has elapsed.",
+            Self::German => "Fortschritt abgeschlossen! Der Zeitraum ist abgelaufen.",
+            Self::Spanish => "¡Progreso completado! El intervalo de tiempo ha terminado.",
+            Self::French => "Progression terminée ! La période est écoulée.",
+            Self::Japanese => "完了しました！期間が終了しました。",
+        }
+    }
+}
+
+/// Parse a `--lang` value: a language code, or `auto` for the locale's
+///
+/// # Examples
+///
+/// ```
+/// use pmon::completion::{parse_language, Language};
+///
+/// assert_eq!(parse_language("ja").unwrap(), Language::Japanese);
+/// assert_eq!(parse_language("fr_CA").unwrap(), Language::French);
+/// assert!(parse_language("klingon").is_err());
+/// ```
+pub fn parse_language(input: &str) -> PbResult<Language> {
+    let input = input.trim();
+    if input.eq_ignore_ascii_case("auto") {
+        return Ok(Language::from_env());
+    }
+    Language::from_locale(input).ok_or_else(|| PbError::invalid_argument("--lang", input))
+}
+
+/// Fill the placeholders of a completion message template
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDateTime;
+/// use pmon::completion::render_complete_message;
+/// use pmon::progress_bar::{DurationFormat, DurationStyle};
+/// use pmon::progress_state::ProgressState;
+///
+/// let at = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+/// let state = ProgressState::new(
+///     at("2025-01-27 09:00:00"),
+///     at("2025-01-27 17:00:00"),
+///     at("2025-01-27 17:05:00"),
+/// );
+/// let format = DurationFormat::from(DurationStyle::Compact);
+/// assert_eq!(
+///     render_complete_message("{label} done, {overtime} over", &state, Some("Deploy"), format),
+///     "Deploy done, 5m over"
+/// );
+/// ```
+pub fn render_complete_message(
+    template: &str,
+    state: &ProgressState,
+    label: Option<&str>,
+    format: DurationFormat,
+) -> String {
+    template
+        .replace("{label}", label.unwrap_or_default())
+        .replace("{percent}", &format_percentage(state.percentage))
+        .replace("{elapsed}", &format.elapsed(state.elapsed()))
+        .replace("{overtime}", &format.elapsed(state.overtime()))
+        .replace("{end}", &state.end.format("%Y-%m-%d %H:%M").to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::progress_bar::DurationStyle;
+    use chrono::NaiveDateTime;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_from_locale() {
+        assert_eq!(
+            Language::from_locale("en_US.UTF-8"),
+            Some(Language::English)
+        );
+        assert_eq!(Language::from_locale("fr_CA"), Some(Language::French));
+        assert_eq!(Language::from_locale("es_ES@euro"), Some(Language::Spanish));
+        assert_eq!(Language::from_locale("POSIX"), Some(Language::English));
+        assert_eq!(Language::from_locale(""), None);
+    }
+
+    #[test]
+    fn test_every_language_has_a_message() {
+        for language in [
+            Language::English,
+            Language::German,
+            Language::Spanish,
+            Language::French,
+            Language::Japanese,
+        ] {
+            assert!(!language.complete_message().is_empty(), "{language:?}");
+        }
+        assert_eq!(
+            Language::default().complete_message(),
+            "Progress completed! Time range has elapsed."
+        );
+    }
+
+    #[test]
+    fn test_parse_language() {
+        assert_eq!(parse_language("de").unwrap(), Language::German);
+        assert_eq!(parse_language(" es_ES ").unwrap(), Language::Spanish);
+        assert_eq!(parse_language("C").unwrap(), Language::English);
+        assert!(parse_language("pt_BR").is_err());
+        assert!(parse_language("").is_err());
+    }
+
+    #[test]
+    fn test_render_placeholders() {
+        let state = ProgressState::new(
+            create_test_datetime("2025-01-27 09:00:00"),
+            create_test_datetime("2025-01-27 11:00:00"),
+            create_test_datetime("2025-01-27 11:00:00"),
+        );
+        let format = DurationFormat::from(DurationStyle::Colon);
+        assert_eq!(
+            render_complete_message(
+                "[{label}] {percent} after {elapsed}, {overtime} over, ended {end}",
+                &state,
+                None,
+                format
+            ),
+            "[] 100.0% after 02:00:00, 00:00:00 over, ended 2025-01-27 11:00"
+        );
+        assert_eq!(
+            render_complete_message("{unknown} {label}", &state, Some("Lunch"), format),
+            "{unknown} Lunch"
+        );
+    }
+}
@@ -0,0 +1,367 @@
+//! User configuration file
+//!
+//! Settings that outlive a single invocation live in a TOML file, by default
+//! `~/.config/pmon/config.toml` (or `$XDG_CONFIG_HOME/pmon/config.toml`).
+//! Set `PMON_CONFIG` to use another file. A missing file means defaults.
+//!
+//! ```toml
+//! schema_version = 1          # optional, see the migrate module
+//!
+//! [store]
+//! backend = "sqlite"          # or "json" (default)
+//! path = "/var/lib/pmon/db"   # optional, defaults to the data directory
+//! encrypt = true              # seal labels; see the encryption module
+//!
+//! [[hooks]]                   # see the hooks module for all options
+//! on = "complete"
+//! notify = "{label} is done"
+//!
+//! [[milestones]]              # see the milestones module for all options
+//! at = "-15m"
+//! color = "yellow"
+//!
+//! [alerts]                    # see the alerts module for all options
+//! min_interval = "5m"
+//!
+//! [solar]                     # see the solar module for all options
+//! location = "35.68,139.69"
+//! ```
+
+use crate::alerts::{AlertConfig, AlertManager};
+use crate::error::{PbError, PbResult};
+use crate::hooks::{HookConfig, HookRegistry};
+use crate::migrate::{migrate, Migration};
+use crate::milestones::{MilestoneConfig, Milestones};
+use crate::solar::SolarConfig;
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Environment variable naming an explicit configuration file
+pub const CONFIG_ENV: &str = "PMON_CONFIG";
+
+/// Version of the configuration layout read by this release
+///
+/// Files may state the version they were written for with a top-level
+/// `schema_version`; files without one are read as the current version.
+pub const CONFIG_SCHEMA_VERSION: u32 = 1;
+
+/// Steps upgrading configuration files written for earlier releases
+const CONFIG_MIGRATIONS: &[Migration<toml::Table>] = &[];
+
+/// Settings read from the configuration file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Where named timers and history are kept
+    pub store: StoreConfig,
+    /// Actions run at milestones, completion, and overtime
+    pub hooks: Vec<HookConfig>,
+    /// Milestones with their own actions and bar colors
+    pub milestones: Vec<MilestoneConfig>,
+    /// How often each hook channel may alert
+    pub alerts: AlertConfig,
+    /// Place and dawn method for solar windows
+    pub solar: SolarConfig,
+}
+
+/// The `[store]` table
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StoreConfig {
+    /// Storage backend
+    pub backend: StoreBackend,
+    /// File holding the store; defaults to a backend-specific file in [`data_dir`]
+    pub path: Option<PathBuf>,
+    /// Encrypt labels before they are saved, requiring the `encryption`
+    /// feature
+    pub encrypt: bool,
+}
+
+/// Storage backends selectable with `backend = "..."`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StoreBackend {
+    /// A single JSON file, with no extra dependencies
+    #[default]
+    Json,
+    /// An SQLite database, requiring the `sqlite` feature
+    Sqlite,
+}
+
+impl StoreBackend {
+    /// Name used in the configuration file and error messages
+    pub fn name(self) -> &'static str {
+        match self {
+            StoreBackend::Json => "json",
+            StoreBackend::Sqlite => "sqlite",
+        }
+    }
+}
+
+impl StoreConfig {
+    /// File holding the store, honoring an explicit `path`
+    pub fn resolved_path(&self) -> PathBuf {
+        self.path.clone().unwrap_or_else(|| {
+            data_dir().join(match self.backend {
+                StoreBackend::Json => "store.json",
+                StoreBackend::Sqlite => "store.sqlite3",
+            })
+        })
+    }
+}
+
+impl Config {
+    /// Parse the contents of a configuration file
+    ///
+    /// `path` is only used in error messages.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pmon::config::{Config, StoreBackend};
+    ///
+    /// let config = Config::parse("[store]\nbackend = \"sqlite\"\n", "config.toml").unwrap();
+    /// assert_eq!(config.store.backend, StoreBackend::Sqlite);
+    /// assert!(Config::parse("[store]\nbackend = \"csv\"\n", "config.toml").is_err());
+    /// ```
+    pub fn parse(contents: &str, path: &str) -> PbResult<Self> {
+        let mut table: toml::Table =
+            toml::from_str(contents).map_err(|e| PbError::invalid_config(path, e.message()))?;
+        let version = match table.remove("schema_version") {
+            None => CONFIG_SCHEMA_VERSION,
+            Some(toml::Value::Integer(version)) => u32::try_from(version)
+                .map_err(|_| PbError::invalid_config(path, "invalid schema_version"))?,
+            Some(_) => return Err(PbError::invalid_config(path, "invalid schema_version")),
+        };
+        migrate(
+            &mut table,
+            version,
+            CONFIG_SCHEMA_VERSION,
+            CONFIG_MIGRATIONS,
+        )
+        .map_err(|e| PbError::invalid_config(path, e))?;
+        let config = Self::deserialize(toml::Value::Table(table))
+            .map_err(|e| PbError::invalid_config(path, e.message()))?;
+        config
+            .hook_registry()
+            .and(config.milestone_set())
+            .and(config.alert_manager())
+            .map_err(|e| PbError::invalid_config(path, e.to_string()))?;
+        Ok(config)
+    }
+
+    /// The configured hooks, ready to run
+    pub fn hook_registry(&self) -> PbResult<HookRegistry> {
+        HookRegistry::from_config(&self.hooks)
+    }
+
+    /// The configured milestones, ready to watch
+    pub fn milestone_set(&self) -> PbResult<Milestones> {
+        Milestones::from_config(&self.milestones)
+    }
+
+    /// The configured alert rate limits
+    pub fn alert_manager(&self) -> PbResult<AlertManager> {
+        AlertManager::from_config(&self.alerts)
+    }
+
+    /// Load a configuration file, using defaults when it does not exist
+    pub fn load_from(path: &Path) -> PbResult<Self> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Self::parse(&contents, &path.display().to_string()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(PbError::invalid_config(
+                path.display().to_string(),
+                e.to_string(),
+            )),
+        }
+    }
+
+    /// Load the user's configuration file from [`config_path`]
+    pub fn load() -> PbResult<Self> {
+        Self::load_from(&config_path())
+    }
+}
+
+/// Location of the user's configuration file
+pub fn config_path() -> PathBuf {
+    if let Some(path) = std::env::var_os(CONFIG_ENV) {
+        return PathBuf::from(path);
+    }
+    xdg_dir("XDG_CONFIG_HOME", ".config")
+        .join("pmon")
+        .join("config.toml")
+}
+
+/// Directory of user themes, `themes` beside the configuration file
+///
+/// Each `NAME.toml` in it is a theme selectable with `--theme NAME`.
+pub fn themes_dir() -> PathBuf {
+    config_path()
+        .parent()
+        .map_or_else(|| PathBuf::from("themes"), |dir| dir.join("themes"))
+}
+
+/// Directory for saved timers and history
+///
+/// Uses `$XDG_DATA_HOME/pmon`, falling back to `~/.local/share/pmon`.
+pub fn data_dir() -> PathBuf {
+    xdg_dir("XDG_DATA_HOME", ".local/share").join("pmon")
+}
+
+/// An XDG base directory, or `fallback` under the home directory
+fn xdg_dir(variable: &str, fallback: &str) -> PathBuf {
+    std::env::var_os(variable)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(fallback)))
+        .unwrap_or_else(std::env::temp_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solar::DawnMethod;
+
+    #[test]
+    fn test_parse_empty_config_uses_defaults() {
+        let config = Config::parse("", "config.toml").unwrap();
+        assert_eq!(config, Config::default());
+        assert_eq!(config.store.backend, StoreBackend::Json);
+        assert!(config.store.resolved_path().ends_with("pmon/store.json"));
+    }
+
+    #[test]
+    fn test_parse_store_config() {
+        let config = Config::parse(
+            "[store]\nbackend = \"sqlite\"\npath = \"/srv/pmon.db\"\n",
+            "config.toml",
+        )
+        .unwrap();
+        assert_eq!(config.store.backend, StoreBackend::Sqlite);
+        assert_eq!(config.store.resolved_path(), PathBuf::from("/srv/pmon.db"));
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_settings() {
+        for contents in ["[store]\nbackend = \"csv\"\n", "[stroe]\n", "store = 3\n"] {
+            assert!(
+                matches!(
+                    Config::parse(contents, "config.toml"),
+                    Err(PbError::InvalidConfig { .. })
+                ),
+                "Expected {contents:?} to be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_hooks() {
+        let config = Config::parse(
+            "[[hooks]]\non = \"complete\"\nbell = true\n\n[[hooks]]\non = \"milestone\"\nat = [50]\nrun = [\"true\"]\n",
+            "config.toml",
+        )
+        .unwrap();
+        assert_eq!(config.hooks.len(), 2);
+        assert!(!config.hook_registry().unwrap().is_empty());
+
+        // Secrets are referenced, never written into the file
+        let error = Config::parse(
+            "[[hooks]]\non = \"complete\"\npost = \"http://relay.local\"\ntoken = \"hunter2\"\n",
+            "config.toml",
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("env:VARIABLE or keyring:ACCOUNT"));
+
+        let error = Config::parse("[[hooks]]\non = \"complete\"\n", "config.toml").unwrap_err();
+        assert!(matches!(error, PbError::InvalidConfig { .. }));
+        assert!(error
+            .to_string()
+            .contains("a hook needs run, post, notify, say, or bell"));
+    }
+
+    #[test]
+    fn test_parse_milestones() {
+        let config = Config::parse(
+            "[[milestones]]\nat = \"-15m\"\ncolor = \"yellow\"\nrun = [\"true\"]\n",
+            "config.toml",
+        )
+        .unwrap();
+        assert_eq!(config.milestones.len(), 1);
+        assert!(!config.milestone_set().unwrap().is_empty());
+
+        let error = Config::parse(
+            "[[milestones]]\nat = \"soon\"\ncolor = \"red\"\n",
+            "config.toml",
+        )
+        .unwrap_err();
+        assert!(matches!(error, PbError::InvalidConfig { .. }));
+        assert!(Config::parse(
+            "[[milestones]]\nat = \"50%\"\ncolor = \"teal\"\n",
+            "config.toml"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_parse_solar() {
+        let config = Config::parse(
+            "[solar]\nlocation = \"21.42,39.83\"\nmethod = \"umm-al-qura\"\n",
+            "config.toml",
+        )
+        .unwrap();
+        let location = config.solar.location().unwrap().unwrap();
+        assert_eq!((location.latitude, location.longitude), (21.42, 39.83));
+        assert_eq!(config.solar.method, Some(DawnMethod::UmmAlQura));
+
+        assert!(Config::parse("[solar]\nmethod = \"hanafi\"\n", "config.toml").is_err());
+        let config = Config::parse("[solar]\nlocation = \"Makkah\"\n", "config.toml").unwrap();
+        assert!(config.solar.location().is_err());
+    }
+
+    #[test]
+    fn test_parse_alerts() {
+        let config = Config::parse(
+            "[alerts]\nmin_interval = \"5m\"\nbell = \"0s\"\n",
+            "config.toml",
+        )
+        .unwrap();
+        let alerts = config.alert_manager().unwrap();
+        assert_eq!(alerts.interval("notify").as_secs(), 300);
+        assert!(alerts.interval("bell").is_zero());
+
+        let error = Config::parse("[alerts]\nnotify = \"5 minutes\"\n", "config.toml");
+        assert!(matches!(error, Err(PbError::InvalidConfig { .. })));
+    }
+
+    #[test]
+    fn test_parse_schema_version() {
+        let config = Config::parse(
+            "schema_version = 1\n\n[store]\nbackend = \"sqlite\"\n",
+            "config.toml",
+        )
+        .unwrap();
+        assert_eq!(config.store.backend, StoreBackend::Sqlite);
+
+        for contents in ["schema_version = 99\n", "schema_version = \"1\"\n"] {
+            assert!(
+                matches!(
+                    Config::parse(contents, "config.toml"),
+                    Err(PbError::InvalidConfig { .. })
+                ),
+                "Expected {contents:?} to be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_load_missing_file_uses_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::load_from(&dir.path().join("missing.toml")).unwrap();
+        assert_eq!(config, Config::default());
+    }
+}
@@ -0,0 +1,279 @@
+//! D-Bus service for desktop widgets on Linux
+//!
+//! With the `dbus` feature, `pmon --dbus --end 17:00` publishes the running
+//! timer on the session bus as `org.pmon.Timer` (or `org.pmon.Timer.NAME`
+//! for a timer started with `--name`), at the object path
+//! `/org/pmon/Timer`. The `org.pmon.Timer` interface has read-only
+//! properties for the label, percent, elapsed and remaining seconds, end,
+//! and completion, announced through `PropertiesChanged` on every update,
+//! and a `Milestone` signal as the window passes 25, 50, 75, 90, and 100
+//! percent. GNOME Shell extensions and KDE Plasma widgets can then display
+//! pmon timers without parsing terminal output.
+
+use crate::broadcast::TimerName;
+use crate::bus::{ProgressEvent, ProgressUpdate, Subscriber};
+use crate::error::{PbError, PbResult};
+use crate::progress_state::ProgressState;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use zbus::blocking::connection::Builder;
+use zbus::blocking::Connection;
+use zbus::interface;
+use zbus::zvariant::Value;
+
+/// Well-known bus name of an unnamed timer
+pub const SERVICE_NAME: &str = "org.pmon.Timer";
+
+/// Interface carrying the timer's properties and signals
+pub const INTERFACE_NAME: &str = "org.pmon.Timer";
+
+/// Object path the timer is served at
+pub const OBJECT_PATH: &str = "/org/pmon/Timer";
+
+/// Target named in errors
+const SESSION_BUS: &str = "D-Bus session bus";
+
+/// Well-known bus name for a timer, named after it when it has a name
+///
+/// Bus name elements only allow letters, digits, and `_`, and may not start
+/// with a digit, so other characters are replaced.
+///
+/// # Examples
+///
+/// ```
+/// use pmon::dbus::bus_name;
+///
+/// assert_eq!(bus_name(None), "org.pmon.Timer");
+/// let name = "release-2.1".parse().unwrap();
+/// assert_eq!(bus_name(Some(&name)), "org.pmon.Timer.release_2_1");
+/// let name = "9am".parse().unwrap();
+/// assert_eq!(bus_name(Some(&name)), "org.pmon.Timer._9am");
+/// ```
+pub fn bus_name(name: Option<&TimerName>) -> String {
+    let Some(name) = name else {
+        return SERVICE_NAME.to_string();
+    };
+    let element: String = name
+        .as_str()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if element.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("{SERVICE_NAME}._{element}")
+    } else {
+        format!("{SERVICE_NAME}.{element}")
+    }
+}
+
+/// Object served at [`OBJECT_PATH`]
+struct TimerObject {
+    label: String,
+    state: Option<ProgressState>,
+}
+
+impl TimerObject {
+    fn percent(&self) -> f64 {
+        self.state
+            .as_ref()
+            .map(|state| state.percentage)
+            .filter(|percent| percent.is_finite())
+            .unwrap_or_default()
+    }
+
+    fn elapsed_seconds(&self) -> i64 {
+        self.state
+            .as_ref()
+            .map_or(0, |state| state.elapsed().num_seconds())
+    }
+
+    fn remaining_seconds(&self) -> i64 {
+        self.state
+            .as_ref()
+            .map_or(0, |state| state.remaining().num_seconds())
+    }
+
+    fn end(&self) -> String {
+        self.state.as_ref().map_or_else(String::new, |state| {
+            state.end.format("%Y-%m-%dT%H:%M:%S").to_string()
+        })
+    }
+
+    fn complete(&self) -> bool {
+        self.state.as_ref().is_some_and(ProgressState::is_complete)
+    }
+}
+
+#[interface(name = "org.pmon.Timer")]
+impl TimerObject {
+    /// Label of the window, empty when none was given
+    #[zbus(property, name = "Label")]
+    fn label_property(&self) -> String {
+        self.label.clone()
+    }
+
+    /// Percent of the window elapsed
+    #[zbus(property, name = "Percent")]
+    fn percent_property(&self) -> f64 {
+        self.percent()
+    }
+
+    /// Seconds since the window started
+    #[zbus(property, name = "ElapsedSeconds")]
+    fn elapsed_seconds_property(&self) -> i64 {
+        self.elapsed_seconds()
+    }
+
+    /// Seconds left, negative once the window has passed
+    #[zbus(property, name = "RemainingSeconds")]
+    fn remaining_seconds_property(&self) -> i64 {
+        self.remaining_seconds()
+    }
+
+    /// End of the window as `YYYY-MM-DDTHH:MM:SS` local time
+    #[zbus(property, name = "End")]
+    fn end_property(&self) -> String {
+        self.end()
+    }
+
+    /// Whether the window has elapsed
+    #[zbus(property, name = "Complete")]
+    fn complete_property(&self) -> bool {
+        self.complete()
+    }
+
+    /// Emitted as the window passes each of [`MILESTONES`]
+    #[zbus(signal)]
+    async fn milestone(
+        emitter: &zbus::object_server::SignalEmitter<'_>,
+        percent: f64,
+    ) -> zbus::Result<()>;
+}
+
+/// A timer published on the session bus
+pub struct DbusService {
+    connection: Connection,
+}
+
+impl DbusService {
+    /// Claim the timer's bus name on the session bus and serve its object
+    pub fn start(name: Option<&TimerName>, label: Option<&str>) -> PbResult<Self> {
+        let failed = |e: zbus::Error| PbError::delivery(SESSION_BUS, e.to_string());
+        let object = TimerObject {
+            label: label.unwrap_or_default().to_string(),
+            state: None,
+        };
+        let connection = Builder::session()
+            .and_then(|builder| builder.name(bus_name(name)))
+            .and_then(|builder| builder.serve_at(OBJECT_PATH, object))
+            .and_then(Builder::build)
+            .map_err(failed)?;
+        Ok(Self { connection })
+    }
+
+    /// Publish a progress update through `PropertiesChanged`
+    pub fn update(&mut self, state: &ProgressState) -> PbResult<()> {
+        let failed = |e: zbus::Error| PbError::delivery(SESSION_BUS, e.to_string());
+        let object = self
+            .connection
+            .object_server()
+            .interface::<_, TimerObject>(OBJECT_PATH)
+            .map_err(failed)?;
+        let changed: HashMap<&str, Value> = {
+            let mut object = object.get_mut();
+            object.state = Some(state.clone());
+            HashMap::from([
+                ("Percent", Value::from(object.percent())),
+                ("ElapsedSeconds", Value::from(object.elapsed_seconds())),
+                ("RemainingSeconds", Value::from(object.remaining_seconds())),
+                ("End", Value::from(object.end())),
+                ("Complete", Value::from(object.complete())),
+            ])
+        };
+        self.connection
+            .emit_signal(
+                None::<()>,
+                OBJECT_PATH,
+                "org.freedesktop.DBus.Properties",
+                "PropertiesChanged",
+                &(INTERFACE_NAME, changed, Vec::<&str>::new()),
+            )
+            .map_err(failed)?;
+        Ok(())
+    }
+
+    /// Send the `Milestone` signal for `percent`
+    pub fn signal_milestone(&self, percent: f64) -> PbResult<()> {
+        self.connection
+            .emit_signal(
+                None::<()>,
+                OBJECT_PATH,
+                INTERFACE_NAME,
+                "Milestone",
+                &(percent,),
+            )
+            .map_err(|e| PbError::delivery(SESSION_BUS, e.to_string()))
+    }
+}
+
+impl Subscriber for DbusService {
+    fn on_event(
+        &mut self,
+        event: ProgressEvent,
+        update: &ProgressUpdate,
+        _out: &mut dyn Write,
+    ) -> io::Result<()> {
+        // Widgets only miss an update if this fails
+        let _ = match event {
+            ProgressEvent::Tick => self.update(update.state),
+            ProgressEvent::Milestone(percent) => self.signal_milestone(f64::from(percent)),
+            ProgressEvent::Completed => self.signal_milestone(100.0),
+            ProgressEvent::Reached(_) | ProgressEvent::Overtime | ProgressEvent::Paused => Ok(()),
+        };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_bus_names_are_valid_elements() {
+        for (name, expected) in [
+            ("standup", "org.pmon.Timer.standup"),
+            ("focus.block", "org.pmon.Timer.focus_block"),
+            ("2025_review", "org.pmon.Timer._2025_review"),
+        ] {
+            let name: TimerName = name.parse().unwrap();
+            assert_eq!(bus_name(Some(&name)), expected);
+            assert!(zbus::names::WellKnownName::try_from(bus_name(Some(&name))).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_object_properties() {
+        let mut object = TimerObject {
+            label: "Standup".to_string(),
+            state: None,
+        };
+        assert_eq!(object.percent(), 0.0);
+        assert_eq!(object.end(), "");
+        assert!(!object.complete());
+
+        object.state = Some(ProgressState::new(
+            create_test_datetime("2025-01-27 09:00:00"),
+            create_test_datetime("2025-01-27 09:20:00"),
+            create_test_datetime("2025-01-27 09:05:00"),
+        ));
+        assert_eq!(object.percent(), 25.0);
+        assert_eq!(object.elapsed_seconds(), 300);
+        assert_eq!(object.remaining_seconds(), 900);
+        assert_eq!(object.end(), "2025-01-27T09:20:00");
+        assert!(!object.complete());
+    }
+}
@@ -0,0 +1,222 @@
+//! One-shot deadline checks for git hooks and CI
+//!
+//! `pmon deadline --end 2025-08-01 --warn 7d` prints a single status line
+//! and exits with a code describing how close the deadline is: 0 when there
+//! is still time, 1 inside the warning window, and 2 inside the fail window
+//! (by default, once the deadline has passed). A pre-commit hook or CI step
+//! can then nag, or block, as a release date approaches.
+
+use crate::error::{PbError, PbResult};
+use crate::layout::fit_label;
+use crate::progress_bar::format_duration_compact;
+use crate::timezone::civil_duration;
+use chrono::{Duration, NaiveDateTime};
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Lead times such as `30m` or `2w`
+#[allow(clippy::unwrap_used)] // A literal pattern, compiled by every test
+static LEAD_TIME: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(\d{1,6})([smhdw])$").unwrap());
+
+/// How close a deadline is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DeadlineStatus {
+    /// More time is left than the warning window
+    Ok,
+    /// Within the warning window
+    Warning,
+    /// Within the fail window
+    Failed,
+}
+
+impl DeadlineStatus {
+    /// Classify the time left before a deadline
+    ///
+    /// A status is reached once no more than its window is left, so
+    /// `fail = 0d` fails only after the deadline.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::Duration;
+    /// use pmon::deadline::DeadlineStatus;
+    ///
+    /// let warn = Some(Duration::days(7));
+    /// let fail = Duration::zero();
+    /// assert_eq!(DeadlineStatus::evaluate(Duration::days(10), warn, fail), DeadlineStatus::Ok);
+    /// assert_eq!(DeadlineStatus::evaluate(Duration::days(3), warn, fail), DeadlineStatus::Warning);
+    /// assert_eq!(DeadlineStatus::evaluate(Duration::hours(-1), warn, fail), DeadlineStatus::Failed);
+    /// ```
+    pub fn evaluate(remaining: Duration, warn: Option<Duration>, fail: Duration) -> Self {
+        if remaining <= fail {
+            DeadlineStatus::Failed
+        } else if warn.is_some_and(|warn| remaining <= warn) {
+            DeadlineStatus::Warning
+        } else {
+            DeadlineStatus::Ok
+        }
+    }
+
+    /// Process exit code for this status
+    pub fn exit_code(self) -> i32 {
+        match self {
+            DeadlineStatus::Ok => 0,
+            DeadlineStatus::Warning => 1,
+            DeadlineStatus::Failed => 2,
+        }
+    }
+
+    /// Tag starting the status line
+    pub fn tag(self) -> &'static str {
+        match self {
+            DeadlineStatus::Ok => "OK",
+            DeadlineStatus::Warning => "WARN",
+            DeadlineStatus::Failed => "FAIL",
+        }
+    }
+}
+
+/// Parse a window before the deadline such as `7d`, `36h`, or `0d`
+///
+/// Accepts a whole number with a unit of `s`, `m`, `h`, `d`, or `w`.
+/// Unlike relative times, zero is allowed.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::Duration;
+/// use pmon::deadline::parse_lead_time;
+///
+/// assert_eq!(parse_lead_time("2w").unwrap(), Duration::weeks(2));
+/// assert_eq!(parse_lead_time("0d").unwrap(), Duration::zero());
+/// assert!(parse_lead_time("-1d").is_err());
+/// ```
+pub fn parse_lead_time(input: &str) -> PbResult<Duration> {
+    let invalid = || PbError::invalid_argument("lead time", input);
+    let captures = LEAD_TIME.captures(input.trim()).ok_or_else(invalid)?;
+    let amount: i64 = captures[1].parse().map_err(|_| invalid())?;
+    Ok(match &captures[2] {
+        "s" => Duration::seconds(amount),
+        "m" => Duration::minutes(amount),
+        "h" => Duration::hours(amount),
+        "d" => Duration::days(amount),
+        _ => Duration::weeks(amount),
+    })
+}
+
+/// Single-line report of a deadline check
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDateTime;
+/// use pmon::deadline::{format_deadline_status, DeadlineStatus};
+///
+/// let at = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+/// let line = format_deadline_status(
+///     DeadlineStatus::Warning,
+///     Some("Release 2.1"),
+///     at("2025-07-27 12:00:00"),
+///     at("2025-08-01 00:00:00"),
+/// );
+/// assert_eq!(line, "WARN Release 2.1: 4d 12h left (due 2025-08-01 00:00)");
+/// ```
+pub fn format_deadline_status(
+    status: DeadlineStatus,
+    label: Option<&str>,
+    now: NaiveDateTime,
+    end: NaiveDateTime,
+) -> String {
+    let remaining = civil_duration(now, end);
+    let name = fit_label(label.unwrap_or("Deadline"));
+    let due = end.format("%Y-%m-%d %H:%M");
+    if remaining < Duration::zero() {
+        format!(
+            "{} {name}: {} overdue (due {due})",
+            status.tag(),
+            format_duration_compact(-remaining, true)
+        )
+    } else {
+        format!(
+            "{} {name}: {} left (due {due})",
+            status.tag(),
+            format_duration_compact(remaining, true)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_evaluate_boundaries() {
+        let warn = Some(Duration::days(7));
+        let fail = Duration::days(1);
+        let evaluate = |remaining| DeadlineStatus::evaluate(remaining, warn, fail);
+
+        assert_eq!(
+            evaluate(Duration::days(7) + Duration::seconds(1)),
+            DeadlineStatus::Ok
+        );
+        assert_eq!(evaluate(Duration::days(7)), DeadlineStatus::Warning);
+        assert_eq!(
+            evaluate(Duration::days(1) + Duration::seconds(1)),
+            DeadlineStatus::Warning
+        );
+        assert_eq!(evaluate(Duration::days(1)), DeadlineStatus::Failed);
+        assert_eq!(evaluate(Duration::days(-30)), DeadlineStatus::Failed);
+    }
+
+    #[test]
+    fn test_evaluate_without_warning_window() {
+        let fail = Duration::zero();
+        assert_eq!(
+            DeadlineStatus::evaluate(Duration::seconds(1), None, fail),
+            DeadlineStatus::Ok
+        );
+        assert_eq!(
+            DeadlineStatus::evaluate(Duration::zero(), None, fail),
+            DeadlineStatus::Failed
+        );
+    }
+
+    #[test]
+    fn test_exit_codes_order_by_severity() {
+        let codes: Vec<_> = [
+            DeadlineStatus::Ok,
+            DeadlineStatus::Warning,
+            DeadlineStatus::Failed,
+        ]
+        .map(DeadlineStatus::exit_code)
+        .to_vec();
+        assert_eq!(codes, [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_parse_lead_time() {
+        assert_eq!(parse_lead_time("90m").unwrap(), Duration::minutes(90));
+        assert_eq!(parse_lead_time(" 36h ").unwrap(), Duration::hours(36));
+        for input in ["", "7", "d", "1.5d", "7x", "1234567d"] {
+            assert!(
+                matches!(parse_lead_time(input), Err(PbError::InvalidArgument { .. })),
+                "Expected '{input}' to be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_format_overdue_deadline() {
+        let line = format_deadline_status(
+            DeadlineStatus::Failed,
+            None,
+            create_test_datetime("2025-08-03 06:00:00"),
+            create_test_datetime("2025-08-01 00:00:00"),
+        );
+        assert_eq!(line, "FAIL Deadline: 2d 6h overdue (due 2025-08-01 00:00)");
+    }
+}
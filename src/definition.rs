@@ -0,0 +1,321 @@
+//! Shareable timer definitions
+//!
+//! A definition is the recipe for a named timer: its window, label, and
+//! display options, kept exactly as they were typed so relative times such
+//! as `+15m` are resolved again each time the timer is run. Definitions are
+//! saved whenever a timer is started with `--name`, and move between
+//! machines as TOML with `pmon export NAME > timer.toml` and
+//! `pmon import timer.toml`:
+//!
+//! ```toml
+//! name = "standup"
+//! label = "Daily standup"
+//! end = "+15m"
+//! interval = 1
+//! duration_style = "colon"
+//! theme = "ocean"
+//! status_messages = [">100=overtime by {overtime}"]
+//! ```
+
+use crate::broadcast::TimerName;
+use crate::cli::Cli;
+use crate::error::{PbError, PbResult};
+use crate::themes::is_theme_name;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory};
+use serde::{Deserialize, Serialize};
+
+/// Window and display options of a named timer, as given on the command line
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TimerDefinition {
+    /// Name the timer is shared and stored under
+    pub name: String,
+    /// Label shown before the bar
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Start time, if one was given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<String>,
+    /// End time
+    pub end: String,
+    /// Update interval in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval: Option<u64>,
+    /// IANA zone the times are given in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    /// Duration format
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_style: Option<String>,
+    /// Name of the theme the bar is drawn with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    /// Rounding direction for the remaining time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub round_remaining: Option<String>,
+    /// Reference pace such as `60%/day`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pace: Option<String>,
+    /// Show the percentage inside the bar
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub overlay_percent: bool,
+    /// Show timestamps instead of durations
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub timestamps: bool,
+    /// `RANGE=TEXT` status messages
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub status_messages: Vec<String>,
+    /// `NAME@TIME` checkpoints
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checkpoints: Vec<String>,
+    /// Tags for grouping related timers
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+/// Raw value of an option given on the command line, ignoring defaults
+fn given_value(matches: &ArgMatches, id: &str) -> Option<String> {
+    given_values(matches, id).pop()
+}
+
+/// Raw values of a repeatable option given on the command line
+fn given_values(matches: &ArgMatches, id: &str) -> Vec<String> {
+    if matches.value_source(id) != Some(ValueSource::CommandLine) {
+        return Vec::new();
+    }
+    matches
+        .get_raw(id)
+        .into_iter()
+        .flatten()
+        .map(|value| value.to_string_lossy().into_owned())
+        .collect()
+}
+
+impl TimerDefinition {
+    /// Capture the options of the main window in `args`
+    ///
+    /// `args` includes the program name but no `--then` windows; chained
+    /// windows and options unrelated to the window's display are not kept.
+    pub fn from_args<I, T>(name: &TimerName, args: I) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<std::ffi::OsString> + Clone,
+    {
+        let matches = Cli::command().try_get_matches_from(args)?;
+        Ok(Self {
+            name: name.as_str().to_string(),
+            label: given_value(&matches, "label"),
+            start: given_value(&matches, "start"),
+            end: given_value(&matches, "end").unwrap_or_default(),
+            interval: given_value(&matches, "interval").and_then(|value| value.parse().ok()),
+            timezone: given_value(&matches, "timezone"),
+            duration_style: given_value(&matches, "duration_style"),
+            theme: given_value(&matches, "theme"),
+            round_remaining: given_value(&matches, "round_remaining"),
+            pace: given_value(&matches, "pace"),
+            overlay_percent: matches.get_flag("overlay_percent"),
+            timestamps: matches.get_flag("timestamps"),
+            status_messages: given_values(&matches, "status_messages"),
+            checkpoints: given_values(&matches, "checkpoints"),
+            tags: given_values(&matches, "tags"),
+        })
+    }
+
+    /// Command line options that recreate this timer, without the program name
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pmon::definition::TimerDefinition;
+    ///
+    /// let definition = TimerDefinition {
+    ///     name: "standup".to_string(),
+    ///     end: "+15m".to_string(),
+    ///     overlay_percent: true,
+    ///     ..TimerDefinition::default()
+    /// };
+    /// assert_eq!(definition.to_args(), ["--end=+15m", "--overlay-percent", "--name=standup"]);
+    /// ```
+    pub fn to_args(&self) -> Vec<String> {
+        let single = [
+            ("start", &self.start),
+            ("end", &Some(self.end.clone())),
+            ("label", &self.label),
+            ("interval", &self.interval.map(|i| i.to_string())),
+            ("timezone", &self.timezone),
+            ("duration-style", &self.duration_style),
+            ("theme", &self.theme),
+            ("round-remaining", &self.round_remaining),
+            ("pace", &self.pace),
+        ];
+        let mut args: Vec<String> = single
+            .into_iter()
+            .filter_map(|(option, value)| value.as_ref().map(|v| format!("--{option}={v}")))
+            .collect();
+        for (option, enabled) in [
+            ("overlay-percent", self.overlay_percent),
+            ("timestamps", self.timestamps),
+        ] {
+            if enabled {
+                args.push(format!("--{option}"));
+            }
+        }
+        for (option, values) in [
+            ("status-message", &self.status_messages),
+            ("checkpoint", &self.checkpoints),
+            ("tag", &self.tags),
+        ] {
+            args.extend(values.iter().map(|value| format!("--{option}={value}")));
+        }
+        args.push(format!("--name={}", self.name));
+        args
+    }
+
+    /// Parse and check a definition read from the TOML file at `path`
+    pub fn from_toml(contents: &str, path: &str) -> PbResult<Self> {
+        let definition: Self =
+            toml::from_str(contents).map_err(|e| PbError::invalid_config(path, e.message()))?;
+        definition.validate()?;
+        Ok(definition)
+    }
+
+    /// Render as TOML, as read by [`TimerDefinition::from_toml`]
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).unwrap_or_default()
+    }
+
+    /// Check that the definition would start a valid timer
+    ///
+    /// The theme is only checked by name: a definition may come from a
+    /// machine whose themes are not installed here yet.
+    pub fn validate(&self) -> PbResult<()> {
+        if let Some(theme) = self.theme.as_deref().filter(|name| !is_theme_name(name)) {
+            return Err(PbError::invalid_argument("--theme", theme));
+        }
+        let without_theme = Self {
+            theme: None,
+            ..self.clone()
+        };
+        let args = std::iter::once("pmon".to_string()).chain(without_theme.to_args());
+        let cli = Cli::try_parse_chain_from(args).map_err(|e| {
+            let message = e.to_string();
+            let first_line = message.lines().next().unwrap_or_default();
+            PbError::invalid_argument("timer definition", first_line.trim_start_matches("error: "))
+        })?;
+        cli.validate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(name: &str) -> TimerName {
+        name.parse().unwrap()
+    }
+
+    #[test]
+    fn test_from_args_keeps_given_options() {
+        let definition = TimerDefinition::from_args(
+            &name("standup"),
+            [
+                "pmon",
+                "--start=-5m",
+                "--end",
+                "+15m",
+                "--label",
+                "Daily standup",
+                "--duration-style",
+                "colon",
+                "--status-message",
+                "<50=warming up",
+                "--status-message",
+                ">100=over by {overtime}",
+                "--overlay-percent",
+                "--name",
+                "standup",
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(definition.name, "standup");
+        assert_eq!(definition.start.as_deref(), Some("-5m"));
+        assert_eq!(definition.end, "+15m");
+        assert_eq!(definition.label.as_deref(), Some("Daily standup"));
+        assert_eq!(definition.duration_style.as_deref(), Some("colon"));
+        assert_eq!(
+            definition.status_messages,
+            ["<50=warming up", ">100=over by {overtime}"]
+        );
+        assert!(definition.overlay_percent);
+        assert!(!definition.timestamps);
+        // Defaults are not written out
+        assert_eq!(definition.interval, None);
+        assert_eq!(definition.round_remaining, None);
+    }
+
+    #[test]
+    fn test_toml_round_trip() {
+        let definition = TimerDefinition {
+            name: "release".to_string(),
+            label: Some("Release 2.1".to_string()),
+            end: "2025-08-01 17:00".to_string(),
+            interval: Some(5),
+            timezone: Some("Europe/Berlin".to_string()),
+            duration_style: Some("colon".to_string()),
+            theme: Some("ocean".to_string()),
+            checkpoints: vec!["freeze@2025-07-25".to_string()],
+            timestamps: true,
+            ..TimerDefinition::default()
+        };
+        let toml = definition.to_toml();
+        assert!(toml.contains("name = \"release\""));
+        assert!(toml.contains("theme = \"ocean\""));
+        assert!(!toml.contains("overlay_percent"));
+        assert_eq!(
+            TimerDefinition::from_toml(&toml, "timer.toml").unwrap(),
+            definition
+        );
+    }
+
+    #[test]
+    fn test_to_args_recreate_definition() {
+        let definition = TimerDefinition {
+            name: "focus".to_string(),
+            start: Some("-10m".to_string()),
+            end: "+50m".to_string(),
+            pace: Some("60%/h".to_string()),
+            status_messages: vec![">90=wrap up".to_string()],
+            tags: vec!["team:payments".to_string(), "sev2".to_string()],
+            ..TimerDefinition::default()
+        };
+        let args = std::iter::once("pmon".to_string()).chain(definition.to_args());
+        assert_eq!(
+            TimerDefinition::from_args(&name("focus"), args).unwrap(),
+            definition
+        );
+    }
+
+    #[test]
+    fn test_from_toml_rejects_invalid_definitions() {
+        let cases = [
+            "name = \"x\"\n",                                  // no end
+            "name = \"x\"\nend = \"+1h\"\ncolour = \"red\"\n", // unknown key
+            "name = \"x\"\nend = \"+1h\"\ninterval = 0\n",     // invalid value
+            "name = \"../x\"\nend = \"+1h\"\n",                // invalid name
+            "name = \"x\"\nend = \"+1h\"\npace = \"fast\"\n",  // unparsable option
+            "name = \"x\"\nend = \"+1h\"\ntheme = \"../x\"\n", // invalid theme name
+        ];
+        for contents in cases {
+            assert!(
+                TimerDefinition::from_toml(contents, "timer.toml").is_err(),
+                "Expected {contents:?} to be rejected"
+            );
+        }
+    }
+}
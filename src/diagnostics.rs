@@ -0,0 +1,57 @@
+//! Debug log for bug reports
+//!
+//! pmon emits `tracing` events as it works: how each time argument was read,
+//! what it detected about the terminal, how long each update took, and
+//! which keys it handled. They are discarded unless `--debug` installs a
+//! subscriber, which writes them as plain lines to stderr or, with
+//! `--debug-file`, appends them to a file. The interactive display redraws
+//! stderr's terminal in raw mode, so a file keeps the log readable there.
+
+use crate::error::{PbError, PbResult};
+use std::fs::OpenOptions;
+use std::path::Path;
+use std::sync::Mutex;
+use tracing::Level;
+
+/// Option name used in error messages
+const OPTION_NAME: &str = "--debug-file";
+
+/// Start writing debug events to `file`, or to stderr when `None`
+///
+/// Only the first call in a process installs the log; later calls do
+/// nothing.
+pub fn init_debug_log(file: Option<&Path>) -> PbResult<()> {
+    let builder = tracing_subscriber::fmt()
+        .with_max_level(Level::DEBUG)
+        .with_ansi(false);
+    // A log that is already installed keeps being written to
+    let _ = match file {
+        Some(path) => {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|e| {
+                    PbError::invalid_argument(OPTION_NAME, format!("{}: {e}", path.display()))
+                })?;
+            builder.with_writer(Mutex::new(file)).try_init()
+        }
+        None => builder.with_writer(std::io::stderr).try_init(),
+    };
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unwritable_file_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing").join("pmon.log");
+        assert!(matches!(
+            init_debug_log(Some(&path)),
+            Err(PbError::InvalidArgument { .. })
+        ));
+    }
+}
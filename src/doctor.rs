@@ -0,0 +1,458 @@
+//! Self-test for bug reports
+//!
+//! `pmon doctor` checks what pmon depends on outside itself: how many
+//! colors the terminal shows, whether wide characters take the two columns
+//! pmon assumes, whether the terminal has an alternate screen, which desktop
+//! notifier `notify` hooks would use, whether the configuration file is
+//! valid, and whether the clock can be trusted. Each check is a pure
+//! function of what was probed, so the report can be tested without a
+//! terminal. The command exits with 1 when any check fails.
+
+use crate::config::Config;
+use crate::error::PbResult;
+use crate::time_source::{drift_warning, TimeSource};
+use chrono::{Datelike, Duration, NaiveDateTime};
+use std::ffi::OsStr;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Clocks reading a year before this are assumed to be unset
+pub const EARLIEST_PLAUSIBLE_YEAR: i32 = 2024;
+
+/// Character drawn to measure how wide the terminal renders wide characters
+pub const WIDE_PROBE: char = '締';
+
+/// How a check came out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CheckStatus {
+    /// Works as pmon expects
+    Ok,
+    /// Works, but output may look wrong
+    Warn,
+    /// Broken; pmon will misbehave
+    Fail,
+}
+
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CheckStatus::Ok => "ok",
+            CheckStatus::Warn => "warn",
+            CheckStatus::Fail => "FAIL",
+        })
+    }
+}
+
+/// One line of the report
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    /// What was checked, such as `Color`
+    pub name: &'static str,
+    pub status: CheckStatus,
+    /// What was found
+    pub detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// What is known about the terminal the report is written to
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TerminalProbe {
+    /// Whether the output is a terminal outside of CI
+    pub is_interactive: bool,
+    /// `$TERM`
+    pub term: Option<String>,
+    /// `$COLORTERM`
+    pub colorterm: Option<String>,
+    /// Whether `$NO_COLOR` is set
+    pub no_color: bool,
+    /// The first of `$LC_ALL`, `$LC_CTYPE`, and `$LANG` that is set
+    pub locale: Option<String>,
+    /// Columns the terminal advanced for [`WIDE_PROBE`], when measured
+    pub wide_char_columns: Option<u16>,
+}
+
+impl TerminalProbe {
+    /// Read the terminal's environment variables
+    pub fn from_env(is_interactive: bool) -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        Self {
+            is_interactive,
+            term: var("TERM"),
+            colorterm: var("COLORTERM"),
+            no_color: std::env::var_os("NO_COLOR").is_some(),
+            locale: var("LC_ALL")
+                .or_else(|| var("LC_CTYPE"))
+                .or_else(|| var("LANG")),
+            wide_char_columns: None,
+        }
+    }
+
+    fn is_dumb(&self) -> bool {
+        matches!(self.term.as_deref(), None | Some("dumb"))
+    }
+}
+
+/// How many colors the terminal shows
+pub fn check_color(probe: &TerminalProbe) -> Check {
+    let check = |status, detail: String| Check::new("Color", status, detail);
+    if !cfg!(feature = "color") {
+        return check(
+            CheckStatus::Ok,
+            "off: pmon was built without the `color` feature".to_string(),
+        );
+    }
+    if probe.no_color {
+        return check(CheckStatus::Ok, "off: NO_COLOR is set".to_string());
+    }
+    if !probe.is_interactive {
+        return check(
+            CheckStatus::Ok,
+            "off: output is not an interactive terminal".to_string(),
+        );
+    }
+    let term = probe.term.as_deref().unwrap_or("");
+    match probe.colorterm.as_deref() {
+        Some("truecolor" | "24bit") => check(CheckStatus::Ok, "24-bit color".to_string()),
+        _ if term.contains("256color") => {
+            check(CheckStatus::Ok, format!("256 colors (TERM={term})"))
+        }
+        _ if probe.is_dumb() => check(
+            CheckStatus::Warn,
+            format!("TERM={term:?} may not show colors or redraw the bar"),
+        ),
+        _ => check(CheckStatus::Ok, format!("basic colors (TERM={term})")),
+    }
+}
+
+/// Whether wide characters, such as in Japanese labels, line up
+pub fn check_unicode(probe: &TerminalProbe) -> Check {
+    let check = |status, detail: String| Check::new("Unicode", status, detail);
+    let locale = probe.locale.as_deref().unwrap_or("unset");
+    let is_utf8 = {
+        let locale = locale.to_ascii_lowercase();
+        locale.contains("utf-8") || locale.contains("utf8")
+    };
+    if !is_utf8 {
+        let found = match &probe.locale {
+            Some(locale) => format!("locale {locale} is not UTF-8"),
+            None => "no locale is set".to_string(),
+        };
+        return check(
+            CheckStatus::Warn,
+            format!("{found}; bars and labels may be garbled"),
+        );
+    }
+    match probe.wide_char_columns {
+        Some(2) => check(
+            CheckStatus::Ok,
+            format!("{locale}; {WIDE_PROBE} takes 2 columns"),
+        ),
+        Some(columns) => check(
+            CheckStatus::Warn,
+            format!("{locale}; {WIDE_PROBE} takes {columns} columns, so labels will misalign"),
+        ),
+        None => check(
+            CheckStatus::Ok,
+            format!("{locale}; width not measured without an interactive terminal"),
+        ),
+    }
+}
+
+/// Whether the terminal can switch to an alternate screen
+///
+/// pmon draws in the main screen, so this only matters to full-screen
+/// modes; the answer is inferred from `$TERM`.
+pub fn check_alternate_screen(probe: &TerminalProbe) -> Check {
+    let check = |status, detail: String| Check::new("Alt screen", status, detail);
+    match probe.term.as_deref() {
+        _ if probe.is_dumb() => check(
+            CheckStatus::Warn,
+            "unavailable: TERM is unset or dumb".to_string(),
+        ),
+        Some(term) if term.starts_with("linux") || term.starts_with("vt1") => {
+            check(CheckStatus::Warn, format!("unlikely on TERM={term}"))
+        }
+        Some(term) => check(CheckStatus::Ok, format!("likely supported (TERM={term})")),
+        None => check(CheckStatus::Warn, "unknown".to_string()),
+    }
+}
+
+/// Which program shows desktop notifications for `notify` hooks
+///
+/// `find` looks a program up, normally with [`find_program`].
+pub fn check_notifications(find: impl Fn(&str) -> Option<PathBuf>) -> Check {
+    let check = |status, detail: String| Check::new("Notify", status, detail);
+    if !cfg!(feature = "notifications") {
+        return check(
+            CheckStatus::Warn,
+            "pmon was built without the `notifications` feature".to_string(),
+        );
+    }
+    let program = if cfg!(target_os = "macos") {
+        "osascript"
+    } else {
+        "notify-send"
+    };
+    match find(program) {
+        Some(path) => check(CheckStatus::Ok, format!("{program} ({})", path.display())),
+        None => check(
+            CheckStatus::Warn,
+            format!("{program} not found; notify hooks will fail"),
+        ),
+    }
+}
+
+/// Whether the configuration file at `path` loads
+pub fn check_config(path: &Path) -> Check {
+    let check = |status, detail: String| Check::new("Config", status, detail);
+    match Config::load_from(path) {
+        Ok(_) if !path.exists() => check(
+            CheckStatus::Ok,
+            format!("{} not found; using defaults", path.display()),
+        ),
+        Ok(config) => check(
+            CheckStatus::Ok,
+            format!("{} ({} hooks)", path.display(), config.hooks.len()),
+        ),
+        Err(e) => check(CheckStatus::Fail, e.to_string()),
+    }
+}
+
+/// Whether the clock reading `now` can be trusted
+///
+/// `offset` is how far the local clock is behind `source`, as measured by
+/// [`TimeSource::measure_offset`].
+pub fn check_clock(now: NaiveDateTime, source: &TimeSource, offset: PbResult<Duration>) -> Check {
+    let check = |status, detail: String| Check::new("Clock", status, detail);
+    let now_text = now.format("%Y-%m-%d %H:%M:%S");
+    if now.year() < EARLIEST_PLAUSIBLE_YEAR {
+        return check(
+            CheckStatus::Fail,
+            format!("reads {now_text}; the system clock looks unset"),
+        );
+    }
+    if source == &TimeSource::Local {
+        return check(CheckStatus::Ok, format!("{now_text} from the local clock"));
+    }
+    let server = source.describe();
+    match offset {
+        Ok(offset) => match drift_warning(&server, offset) {
+            Some(warning) => check(CheckStatus::Warn, warning),
+            None => check(
+                CheckStatus::Ok,
+                format!("{now_text}; within 1s of {server}"),
+            ),
+        },
+        Err(e) => check(CheckStatus::Warn, format!("{e}; using the local clock")),
+    }
+}
+
+/// Look `program` up in `$PATH`
+pub fn find_program(program: &str) -> Option<PathBuf> {
+    find_in_path(program, &std::env::var_os("PATH")?)
+}
+
+fn find_in_path(program: &str, path: &OsStr) -> Option<PathBuf> {
+    std::env::split_paths(path)
+        .map(|dir| dir.join(program))
+        .find(|candidate| candidate.is_file())
+}
+
+/// The report printed by `pmon doctor`
+pub fn render_report(checks: &[Check]) -> String {
+    let width = checks
+        .iter()
+        .map(|check| check.name.len())
+        .max()
+        .unwrap_or(0);
+    let mut report = format!("pmon {}\n", env!("CARGO_PKG_VERSION"));
+    for check in checks {
+        report.push_str(&format!(
+            "  {:<4}  {:<width$}  {}\n",
+            check.status.to_string(),
+            check.name,
+            check.detail
+        ));
+    }
+    report
+}
+
+/// Exit code of `pmon doctor`: 1 when any check failed
+pub fn exit_code(checks: &[Check]) -> i32 {
+    i32::from(checks.iter().any(|check| check.status == CheckStatus::Fail))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::PbError;
+
+    fn terminal(term: &str) -> TerminalProbe {
+        TerminalProbe {
+            is_interactive: true,
+            term: Some(term.to_string()),
+            locale: Some("en_US.UTF-8".to_string()),
+            ..TerminalProbe::default()
+        }
+    }
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    #[cfg(feature = "color")]
+    fn test_color_depth() {
+        assert_eq!(
+            check_color(&terminal("xterm-256color")).detail,
+            "256 colors (TERM=xterm-256color)"
+        );
+        let truecolor = TerminalProbe {
+            colorterm: Some("truecolor".to_string()),
+            ..terminal("xterm")
+        };
+        assert_eq!(check_color(&truecolor).detail, "24-bit color");
+        assert_eq!(check_color(&terminal("dumb")).status, CheckStatus::Warn);
+
+        let piped = TerminalProbe {
+            is_interactive: false,
+            ..terminal("xterm-256color")
+        };
+        assert!(check_color(&piped).detail.starts_with("off"));
+        let no_color = TerminalProbe {
+            no_color: true,
+            ..terminal("xterm-256color")
+        };
+        assert_eq!(check_color(&no_color).detail, "off: NO_COLOR is set");
+    }
+
+    #[test]
+    fn test_unicode_width() {
+        let measured = |columns| TerminalProbe {
+            wide_char_columns: Some(columns),
+            ..terminal("xterm")
+        };
+        assert_eq!(check_unicode(&measured(2)).status, CheckStatus::Ok);
+        assert_eq!(check_unicode(&measured(1)).status, CheckStatus::Warn);
+        assert_eq!(check_unicode(&terminal("xterm")).status, CheckStatus::Ok);
+
+        let posix = TerminalProbe {
+            locale: Some("C".to_string()),
+            ..terminal("xterm")
+        };
+        assert_eq!(check_unicode(&posix).status, CheckStatus::Warn);
+    }
+
+    #[test]
+    fn test_alternate_screen() {
+        assert_eq!(
+            check_alternate_screen(&terminal("xterm-256color")).status,
+            CheckStatus::Ok
+        );
+        assert_eq!(
+            check_alternate_screen(&terminal("dumb")).status,
+            CheckStatus::Warn
+        );
+        assert_eq!(
+            check_alternate_screen(&terminal("linux")).status,
+            CheckStatus::Warn
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "notifications")]
+    fn test_notifications() {
+        let found = check_notifications(|program| Some(PathBuf::from("/usr/bin").join(program)));
+        assert_eq!(found.status, CheckStatus::Ok);
+        assert!(found.detail.contains("/usr/bin/"));
+        assert_eq!(check_notifications(|_| None).status, CheckStatus::Warn);
+    }
+
+    #[test]
+    fn test_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        assert!(check_config(&path)
+            .detail
+            .ends_with("not found; using defaults"));
+
+        std::fs::write(&path, "[[hooks]]\non = \"complete\"\nbell = true\n").unwrap();
+        let check = check_config(&path);
+        assert_eq!(check.status, CheckStatus::Ok);
+        assert!(check.detail.ends_with("(1 hooks)"));
+
+        std::fs::write(&path, "[store]\nbackend = \"csv\"\n").unwrap();
+        assert_eq!(check_config(&path).status, CheckStatus::Fail);
+    }
+
+    #[test]
+    fn test_clock() {
+        let now = create_test_datetime("2025-01-27 10:00:00");
+        let local = TimeSource::Local;
+        assert_eq!(
+            check_clock(now, &local, Ok(Duration::zero())).status,
+            CheckStatus::Ok
+        );
+
+        let unset = create_test_datetime("1970-01-01 00:00:05");
+        assert_eq!(
+            check_clock(unset, &local, Ok(Duration::zero())).status,
+            CheckStatus::Fail
+        );
+
+        let ntp: TimeSource = "ntp://pool.ntp.org".parse().unwrap();
+        assert_eq!(
+            check_clock(now, &ntp, Ok(Duration::milliseconds(200))).status,
+            CheckStatus::Ok
+        );
+        let drifted = check_clock(now, &ntp, Ok(Duration::seconds(30)));
+        assert_eq!(drifted.status, CheckStatus::Warn);
+        assert_eq!(
+            drifted.detail,
+            "Local clock is 30.0s behind pool.ntp.org:123"
+        );
+        let unreachable = Err(PbError::time_source_unavailable(
+            "pool.ntp.org:123",
+            "timed out",
+        ));
+        assert_eq!(
+            check_clock(now, &ntp, unreachable).status,
+            CheckStatus::Warn
+        );
+    }
+
+    #[test]
+    fn test_find_in_path() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("notify-send"), "").unwrap();
+        let path = std::env::join_paths([Path::new("/nonexistent"), dir.path()]).unwrap();
+        assert_eq!(
+            find_in_path("notify-send", &path),
+            Some(dir.path().join("notify-send"))
+        );
+        assert_eq!(find_in_path("osascript", &path), None);
+    }
+
+    #[test]
+    fn test_report_and_exit_code() {
+        let checks = vec![
+            Check::new("Color", CheckStatus::Ok, "256 colors"),
+            Check::new("Config", CheckStatus::Fail, "invalid"),
+        ];
+        let report = render_report(&checks);
+        assert!(report.starts_with("pmon "));
+        assert!(report.contains("\n  ok    Color   256 colors\n"));
+        assert!(report.contains("\n  FAIL  Config  invalid\n"));
+        assert_eq!(exit_code(&checks), 1);
+        assert_eq!(exit_code(&checks[..1]), 0);
+    }
+}
@@ -0,0 +1,368 @@
+//! Encrypted labels for stores on shared machines
+//!
+//! With `encrypt = true` in the `[store]` table, the labels of saved timers,
+//! runs, and definitions, the notes taken during runs, and webhook events
+//! waiting to be sent again, are sealed with XChaCha20-Poly1305 before they reach the store, so titles
+//! such as "HR: offer deadline" cannot be read from the file by other users
+//! of the machine. Times, names, and tags stay
+//! in the clear so listings and history still work.
+//!
+//! The 32-byte key is given as 64 hexadecimal digits in `PMON_STATE_KEY`,
+//! or stored in the OS keyring as `pmon/state-key` when pmon is built with
+//! the `keyring` feature. A new key can be made with `openssl rand -hex 32`.
+//! Labels saved before encryption was turned on are still read as they are.
+
+use crate::config::StoreBackend;
+use crate::definition::TimerDefinition;
+use crate::error::{PbError, PbResult};
+use crate::notes::Note;
+use crate::secrets::lookup_secret;
+use crate::store::{PendingDelivery, RunRecord, Store, TimerRecord};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+
+/// Environment variable holding the store key
+pub const STATE_KEY_ENV: &str = "PMON_STATE_KEY";
+
+/// Keyring account holding the store key
+pub const STATE_KEY_ACCOUNT: &str = "state-key";
+
+/// Prefix marking a sealed value, followed by the hex nonce and ciphertext
+const SEALED_PREFIX: &str = "enc:v1:";
+
+/// Length of an XChaCha20 nonce in bytes
+const NONCE_LENGTH: usize = 24;
+
+/// Seals and opens values with the store key
+pub struct Sealer {
+    cipher: XChaCha20Poly1305,
+}
+
+impl Sealer {
+    /// Use a key given as 64 hexadecimal digits
+    pub fn from_hex(key: &str) -> PbResult<Self> {
+        let key = decode_hex(key.trim())
+            .filter(|key| key.len() == 32)
+            .ok_or_else(|| encryption_error("the key must be 64 hexadecimal digits"))?;
+        let cipher = XChaCha20Poly1305::new_from_slice(&key)
+            .map_err(|_| encryption_error("the key must be 64 hexadecimal digits"))?;
+        Ok(Self { cipher })
+    }
+
+    /// Use the key from [`STATE_KEY_ENV`] or the keyring
+    pub fn load() -> PbResult<Self> {
+        let key = lookup_secret(STATE_KEY_ENV, STATE_KEY_ACCOUNT)
+            .map_err(encryption_error)?
+            .ok_or_else(|| {
+                encryption_error(format!(
+                    "encrypt = true needs a key in {STATE_KEY_ENV} or the keyring"
+                ))
+            })?;
+        Self::from_hex(&key)
+    }
+
+    /// Seal `value` under a fresh random nonce
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pmon::encryption::Sealer;
+    ///
+    /// let sealer = Sealer::from_hex(&"7f".repeat(32)).unwrap();
+    /// let sealed = sealer.seal("HR: offer deadline").unwrap();
+    /// assert!(!sealed.contains("offer"));
+    /// assert_eq!(sealer.open(&sealed).unwrap(), "HR: offer deadline");
+    /// // Values saved before encryption was turned on read as they are
+    /// assert_eq!(sealer.open("standup").unwrap(), "standup");
+    /// ```
+    pub fn seal(&self, value: &str) -> PbResult<String> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, value.as_bytes())
+            .map_err(|_| encryption_error("could not encrypt a label"))?;
+        Ok(format!(
+            "{SEALED_PREFIX}{}{}",
+            encode_hex(&nonce),
+            encode_hex(&ciphertext)
+        ))
+    }
+
+    /// Open a value sealed by [`Sealer::seal`], passing others through
+    pub fn open(&self, value: &str) -> PbResult<String> {
+        let Some(hex) = value.strip_prefix(SEALED_PREFIX) else {
+            return Ok(value.to_string());
+        };
+        let bytes = decode_hex(hex)
+            .filter(|bytes| bytes.len() > NONCE_LENGTH)
+            .ok_or_else(|| encryption_error("a sealed label is damaged"))?;
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LENGTH);
+        let plaintext = self
+            .cipher
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| {
+                encryption_error(format!(
+                    "a label cannot be decrypted; is {STATE_KEY_ENV} the key it was saved with?"
+                ))
+            })?;
+        String::from_utf8(plaintext).map_err(|_| encryption_error("a sealed label is damaged"))
+    }
+
+    fn seal_label(&self, label: &Option<String>) -> PbResult<Option<String>> {
+        label.as_deref().map(|label| self.seal(label)).transpose()
+    }
+
+    fn open_label(&self, label: &Option<String>) -> PbResult<Option<String>> {
+        label.as_deref().map(|label| self.open(label)).transpose()
+    }
+
+    fn open_timer(&self, timer: TimerRecord) -> PbResult<TimerRecord> {
+        Ok(TimerRecord {
+            label: self.open_label(&timer.label)?,
+            ..timer
+        })
+    }
+
+    fn seal_notes(&self, notes: &[Note]) -> PbResult<Vec<Note>> {
+        notes
+            .iter()
+            .map(|note| {
+                Ok(Note {
+                    text: self.seal(&note.text)?,
+                    ..note.clone()
+                })
+            })
+            .collect()
+    }
+
+    fn open_notes(&self, notes: &[Note]) -> PbResult<Vec<Note>> {
+        notes
+            .iter()
+            .map(|note| {
+                Ok(Note {
+                    text: self.open(&note.text)?,
+                    ..note.clone()
+                })
+            })
+            .collect()
+    }
+
+    fn open_run(&self, run: RunRecord) -> PbResult<RunRecord> {
+        Ok(RunRecord {
+            label: self.open_label(&run.label)?,
+            notes: self.open_notes(&run.notes)?,
+            ..run
+        })
+    }
+}
+
+/// A store whose labels are sealed before they are saved
+pub struct EncryptedStore {
+    inner: Box<dyn Store>,
+    sealer: Sealer,
+}
+
+impl EncryptedStore {
+    /// Seal the labels kept in `inner` with `sealer`
+    pub fn new(inner: Box<dyn Store>, sealer: Sealer) -> Self {
+        Self { inner, sealer }
+    }
+}
+
+impl Store for EncryptedStore {
+    fn backend(&self) -> StoreBackend {
+        self.inner.backend()
+    }
+
+    fn save_timer(&mut self, timer: &TimerRecord) -> PbResult<()> {
+        self.inner.save_timer(&TimerRecord {
+            label: self.sealer.seal_label(&timer.label)?,
+            ..timer.clone()
+        })
+    }
+
+    fn timer(&self, name: &str) -> PbResult<Option<TimerRecord>> {
+        self.inner
+            .timer(name)?
+            .map(|timer| self.sealer.open_timer(timer))
+            .transpose()
+    }
+
+    fn timers(&self) -> PbResult<Vec<TimerRecord>> {
+        self.inner
+            .timers()?
+            .into_iter()
+            .map(|timer| self.sealer.open_timer(timer))
+            .collect()
+    }
+
+    fn remove_timer(&mut self, name: &str) -> PbResult<bool> {
+        self.inner.remove_timer(name)
+    }
+
+    fn record_run(&mut self, run: &RunRecord) -> PbResult<()> {
+        self.inner.record_run(&RunRecord {
+            label: self.sealer.seal_label(&run.label)?,
+            notes: self.sealer.seal_notes(&run.notes)?,
+            ..run.clone()
+        })
+    }
+
+    fn history(&self) -> PbResult<Vec<RunRecord>> {
+        self.inner
+            .history()?
+            .into_iter()
+            .map(|run| self.sealer.open_run(run))
+            .collect()
+    }
+
+    fn save_definition(&mut self, definition: &TimerDefinition) -> PbResult<()> {
+        self.inner.save_definition(&TimerDefinition {
+            label: self.sealer.seal_label(&definition.label)?,
+            ..definition.clone()
+        })
+    }
+
+    fn definition(&self, name: &str) -> PbResult<Option<TimerDefinition>> {
+        self.inner
+            .definition(name)?
+            .map(|definition| {
+                Ok(TimerDefinition {
+                    label: self.sealer.open_label(&definition.label)?,
+                    ..definition
+                })
+            })
+            .transpose()
+    }
+
+    fn pending_deliveries(&self) -> PbResult<Vec<PendingDelivery>> {
+        self.inner
+            .pending_deliveries()?
+            .into_iter()
+            .map(|delivery| {
+                Ok(PendingDelivery {
+                    body: self.sealer.open(&delivery.body)?,
+                    ..delivery
+                })
+            })
+            .collect()
+    }
+
+    fn set_pending_deliveries(&mut self, pending: &[PendingDelivery]) -> PbResult<()> {
+        // Queued events carry the label
+        let sealed = pending
+            .iter()
+            .map(|delivery| {
+                Ok(PendingDelivery {
+                    body: self.sealer.seal(&delivery.body)?,
+                    ..delivery.clone()
+                })
+            })
+            .collect::<PbResult<Vec<_>>>()?;
+        self.inner.set_pending_deliveries(&sealed)
+    }
+}
+
+/// Storage error raised while sealing or opening labels
+fn encryption_error(reason: impl Into<String>) -> PbError {
+    PbError::storage("encryption", reason)
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{JsonStore, RunOutcome};
+    use chrono::NaiveDateTime;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn sealer() -> Sealer {
+        Sealer::from_hex(&"a5".repeat(32)).unwrap()
+    }
+
+    #[test]
+    fn test_sealer_rejects_bad_keys() {
+        for key in ["", "a5", &"zz".repeat(32), &"a5".repeat(33)] {
+            assert!(
+                Sealer::from_hex(key).is_err(),
+                "Expected {key:?} to be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_open_with_the_wrong_key_fails() {
+        let sealed = sealer().seal("HR: offer deadline").unwrap();
+        let other = Sealer::from_hex(&"5a".repeat(32)).unwrap();
+        let error = other.open(&sealed).unwrap_err();
+        assert!(error.to_string().contains(STATE_KEY_ENV), "{error}");
+        assert!(sealer().open("enc:v1:00ff").is_err());
+        // Each seal uses a fresh nonce
+        assert_ne!(sealed, sealer().seal("HR: offer deadline").unwrap());
+    }
+
+    #[test]
+    fn test_encrypted_store_keeps_labels_out_of_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+        let inner = JsonStore::open(path.clone()).unwrap();
+        let mut store = EncryptedStore::new(Box::new(inner), sealer());
+
+        let timer = TimerRecord {
+            name: "offer".to_string(),
+            label: Some("HR: offer deadline".to_string()),
+            start: create_test_datetime("2025-01-27 09:00:00"),
+            end: create_test_datetime("2025-01-27 17:00:00"),
+            pid: None,
+        };
+        let run = RunRecord {
+            name: Some("offer".to_string()),
+            label: timer.label.clone(),
+            start: timer.start,
+            end: timer.end,
+            finished_at: timer.end,
+            outcome: RunOutcome::Completed,
+            tags: Vec::new(),
+            notes: vec![Note {
+                at: timer.start,
+                text: "HR: counter-offer call".to_string(),
+            }],
+        };
+        let definition = TimerDefinition {
+            name: "offer".to_string(),
+            label: timer.label.clone(),
+            end: "17:00".to_string(),
+            ..TimerDefinition::default()
+        };
+        store.save_timer(&timer).unwrap();
+        store.record_run(&run).unwrap();
+        store.save_definition(&definition).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains("HR: offer"), "{contents}");
+        assert!(!contents.contains("counter-offer"), "{contents}");
+        assert!(contents.contains("\"offer\""));
+
+        let store = EncryptedStore::new(Box::new(JsonStore::open(path).unwrap()), sealer());
+        assert_eq!(store.timer("offer").unwrap(), Some(timer.clone()));
+        assert_eq!(store.timers().unwrap(), [timer]);
+        assert_eq!(store.history().unwrap(), [run]);
+        assert_eq!(store.definition("offer").unwrap(), Some(definition));
+    }
+}
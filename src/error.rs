@@ -27,9 +27,63 @@ pub enum PbError {
     #[error("Invalid relative time format: {input}")]
     InvalidRelativeTimeFormat { input: String },
 
+    /// A time no supported format could read, with each format's reason
+    /// for rejecting it
+    #[error("Invalid time format: {input}{}", list_attempts(.attempts))]
+    UnreadableTime {
+        input: String,
+        attempts: Vec<String>,
+    },
+
+    /// A time that could be read several ways, with the readings
+    #[error("Ambiguous time {input}: did you mean {readings}?")]
+    AmbiguousTime { input: String, readings: String },
+
     /// Required CLI options are missing (only --end is required now)
     #[error("--end option is required")]
     MissingRequiredOptions,
+
+    /// A CLI option received a value it cannot interpret
+    #[error("Invalid value for {option}: {input}")]
+    InvalidArgument { option: String, input: String },
+
+    /// A remote time source could not be queried
+    #[error("Time source {server} unavailable: {reason}")]
+    TimeSourceUnavailable { server: String, reason: String },
+
+    /// The configuration file could not be read or understood
+    #[error("Invalid configuration in {path}: {reason}")]
+    InvalidConfig { path: String, reason: String },
+
+    /// Saved timers or history could not be read or written
+    #[error("Storage error ({backend}): {reason}")]
+    Storage { backend: String, reason: String },
+
+    /// Data could not be sent to an external service
+    #[error("Could not deliver to {target}: {reason}")]
+    Delivery { target: String, reason: String },
+
+    /// Newer releases could not be checked for or installed
+    #[error("Self-update failed: {reason}")]
+    Update { reason: String },
+
+    /// A named timer is still running or scheduled under the same name
+    #[error(
+        "Timer '{name}' already exists until {until}; use --on-exists merge or replace, or --force"
+    )]
+    TimerExists { name: String, until: String },
+
+    /// A label could not be read from the shell context
+    #[error("Could not read the label from {origin}: {reason}")]
+    LabelSource { origin: String, reason: String },
+}
+
+/// The reasons of an UnreadableTime error, one indented line each
+fn list_attempts(attempts: &[String]) -> String {
+    attempts
+        .iter()
+        .map(|attempt| format!("\n  {attempt}"))
+        .collect()
 }
 
 /// Result type alias for operations that can fail with a PbError
@@ -49,6 +103,92 @@ impl PbError {
             input: input.into(),
         }
     }
+
+    /// Create an UnreadableTime error for `input`, with one
+    /// `format: reason` line per format tried
+    pub fn unreadable_time(input: impl Into<String>, attempts: Vec<String>) -> Self {
+        Self::UnreadableTime {
+            input: input.into(),
+            attempts,
+        }
+    }
+
+    /// Create an AmbiguousTime error listing what `input` could mean
+    pub fn ambiguous_time(input: impl Into<String>, readings: &[String]) -> Self {
+        let readings = match readings {
+            [] => String::new(),
+            [only] => only.clone(),
+            [first, second] => format!("{first} or {second}"),
+            [rest @ .., last] => format!("{}, or {last}", rest.join(", ")),
+        };
+        Self::AmbiguousTime {
+            input: input.into(),
+            readings,
+        }
+    }
+
+    /// Create an InvalidArgument error for the given option and input
+    pub fn invalid_argument(option: impl Into<String>, input: impl Into<String>) -> Self {
+        Self::InvalidArgument {
+            option: option.into(),
+            input: input.into(),
+        }
+    }
+
+    /// Create a TimeSourceUnavailable error for the given server and reason
+    pub fn time_source_unavailable(server: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self::TimeSourceUnavailable {
+            server: server.into(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Create an InvalidConfig error for the given file and reason
+    pub fn invalid_config(path: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self::InvalidConfig {
+            path: path.into(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Create a Storage error for the given backend and reason
+    pub fn storage(backend: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self::Storage {
+            backend: backend.into(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Create a Delivery error for the given target and reason
+    pub fn delivery(target: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self::Delivery {
+            target: target.into(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Create an Update error with the given reason
+    pub fn update(reason: impl Into<String>) -> Self {
+        Self::Update {
+            reason: reason.into(),
+        }
+    }
+
+    /// Create a TimerExists error for the timer `name` that lasts `until`
+    pub fn timer_exists(name: impl Into<String>, until: impl Into<String>) -> Self {
+        Self::TimerExists {
+            name: name.into(),
+            until: until.into(),
+        }
+    }
+
+    /// Create a LabelSource error for the given origin and reason
+    pub fn label_source(origin: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self::LabelSource {
+            origin: origin.into(),
+            reason: reason.into(),
+        }
+    }
 }
 
 // Note: anyhow automatically provides From<PbError> for anyhow::Error
@@ -93,12 +233,96 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_ambiguous_time_error_message() {
+        let readings = ["12:00:00".to_string(), "+20h".to_string()];
+        assert_eq!(
+            PbError::ambiguous_time("1200", &readings[..1]).to_string(),
+            "Ambiguous time 1200: did you mean 12:00:00?"
+        );
+        assert_eq!(
+            PbError::ambiguous_time("1200", &readings).to_string(),
+            "Ambiguous time 1200: did you mean 12:00:00 or +20h?"
+        );
+        let readings = [&readings[..], &["2025-01-01".to_string()]].concat();
+        assert_eq!(
+            PbError::ambiguous_time("1200", &readings).to_string(),
+            "Ambiguous time 1200: did you mean 12:00:00, +20h, or 2025-01-01?"
+        );
+    }
+
+    #[test]
+    fn test_timer_exists_error_message() {
+        let error = PbError::timer_exists("standup", "2025-01-27 09:15");
+        assert_eq!(
+            error.to_string(),
+            "Timer 'standup' already exists until 2025-01-27 09:15; use --on-exists merge or replace, or --force"
+        );
+    }
+
+    #[test]
+    fn test_label_source_error_message() {
+        let error = PbError::label_source("tmux", "not running inside tmux");
+        assert_eq!(
+            error.to_string(),
+            "Could not read the label from tmux: not running inside tmux"
+        );
+    }
+
     #[test]
     fn test_missing_required_options_error_message() {
         let error = PbError::MissingRequiredOptions;
         assert_eq!(error.to_string(), "--end option is required");
     }
 
+    #[test]
+    fn test_invalid_argument_error_message() {
+        let error = PbError::invalid_argument("--status-message", "50");
+        assert_eq!(error.to_string(), "Invalid value for --status-message: 50");
+    }
+
+    #[test]
+    fn test_time_source_unavailable_error_message() {
+        let error = PbError::time_source_unavailable("pool.ntp.org:123", "timed out");
+        assert_eq!(
+            error.to_string(),
+            "Time source pool.ntp.org:123 unavailable: timed out"
+        );
+    }
+
+    #[test]
+    fn test_invalid_config_error_message() {
+        let error = PbError::invalid_config("config.toml", "unknown field `colour`");
+        assert_eq!(
+            error.to_string(),
+            "Invalid configuration in config.toml: unknown field `colour`"
+        );
+    }
+
+    #[test]
+    fn test_storage_error_message() {
+        let error = PbError::storage("json", "permission denied");
+        assert_eq!(error.to_string(), "Storage error (json): permission denied");
+    }
+
+    #[test]
+    fn test_delivery_error_message() {
+        let error = PbError::delivery("http://localhost:4318/v1/traces", "connection refused");
+        assert_eq!(
+            error.to_string(),
+            "Could not deliver to http://localhost:4318/v1/traces: connection refused"
+        );
+    }
+
+    #[test]
+    fn test_update_error_message() {
+        let error = PbError::update("could not run curl: not found");
+        assert_eq!(
+            error.to_string(),
+            "Self-update failed: could not run curl: not found"
+        );
+    }
+
     #[test]
     fn test_error_conversion_to_anyhow() {
         let pb_error = PbError::StartAfterEnd;
@@ -0,0 +1,847 @@
+//! Key handling of the interactive progress loop
+//!
+//! Between two updates, the interactive loop watches the keyboard: `t`
+//! toggles durations and timestamps, `r` restarts the window, `+`/`-` shift
+//! it, `n` takes a note, `c` copies the bar to the clipboard, and Ctrl+C
+//! quits. With `--locked`, the keys that change the window do nothing and
+//! Ctrl+C asks for [`UNLOCK_PHRASE`] before quitting. [`EventLoop`] does
+//! that waiting
+//! against an [`EventSource`] and a [`Clock`] supplied by its owner, so the
+//! key bindings can be exercised with scripted events and a fake clock
+//! instead of a real terminal. Keys reach it as [`Key`]s, so only
+//! [`TerminalEvents`] needs crossterm; without the `interactive` feature it
+//! never reports a key.
+//!
+//! `--demo` swaps the system clock for a [`ScaledClock`], which plays the
+//! whole window back in a few seconds of real time.
+//!
+//! Updates follow a [`TickSchedule`] of deadlines one interval apart, so
+//! the time spent drawing and handling keys does not push later updates
+//! back. Deadlines are kept on the loop's clock, and the loop turns them
+//! back into real time when it waits. With `--align`, each wait instead ends on a round multiple of the
+//! interval on the wall clock, see [`until_aligned`], so every instance
+//! started with the same interval updates at the same moment.
+
+use crate::chain::Segment;
+use crate::progress_bar::TimeInfoStyle;
+use crate::time_parser::get_current_time;
+use chrono::{NaiveDateTime, Timelike};
+use std::io;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Minutes the window moves per `+` or `-` key press
+pub const SHIFT_STEP_MINUTES: i64 = 1;
+
+/// Phrase typed to quit a `--locked` timer
+pub const UNLOCK_PHRASE: &str = "stop the timer";
+
+/// How often the keyboard is checked while waiting for the next update
+pub const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Time from `now` until the next multiple of `interval` since midnight
+///
+/// A time already on a boundary waits a whole interval, so an update drawn
+/// right on the boundary is not repeated.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDateTime;
+/// use pmon::event_loop::until_aligned;
+/// use std::time::Duration;
+///
+/// let now = NaiveDateTime::parse_from_str("2025-01-27 10:00:12", "%Y-%m-%d %H:%M:%S").unwrap();
+/// assert_eq!(until_aligned(now, Duration::from_secs(30)), Duration::from_secs(18));
+/// assert_eq!(until_aligned(now, Duration::from_secs(60)), Duration::from_secs(48));
+/// ```
+pub fn until_aligned(now: NaiveDateTime, interval: Duration) -> Duration {
+    let interval_micros = interval.as_micros();
+    if interval_micros == 0 {
+        return interval;
+    }
+    // A leap second shows up as a nanosecond count past one second
+    let since_midnight = u128::from(now.num_seconds_from_midnight()) * 1_000_000
+        + u128::from(now.nanosecond() / 1_000);
+    let into_interval = since_midnight % interval_micros;
+    let left = interval_micros - into_interval;
+    Duration::from_micros(u64::try_from(left).unwrap_or(u64::MAX))
+}
+
+/// Deadlines for updates a fixed interval apart
+///
+/// Sleeping a whole interval after each update adds the time spent on the
+/// update to every interval, so an hourly loop drifts by seconds a day.
+/// Waiting until the next deadline instead keeps ticks on schedule.
+#[derive(Debug, Clone, Copy)]
+pub struct TickSchedule {
+    interval: Duration,
+    align: bool,
+    next: Instant,
+}
+
+impl TickSchedule {
+    /// Tick every `interval` from `start`, or on wall-clock multiples of
+    /// it when `align` is set
+    pub fn new(interval: Duration, align: bool, start: Instant) -> Self {
+        Self {
+            interval,
+            align,
+            next: start + interval,
+        }
+    }
+
+    /// Time left at `now`, or `wall` on the wall clock, until the next tick
+    pub fn remaining(&self, now: Instant, wall: NaiveDateTime) -> Duration {
+        if self.align {
+            until_aligned(wall, self.interval)
+        } else {
+            self.next.saturating_duration_since(now)
+        }
+    }
+
+    /// Space ticks `interval` apart from the last one on
+    pub fn set_interval(&mut self, interval: Duration) {
+        if interval != self.interval {
+            let last = self.next.checked_sub(self.interval).unwrap_or(self.next);
+            self.next = last + interval;
+            self.interval = interval;
+        }
+    }
+
+    /// Move past every tick that has come due by `now`
+    ///
+    /// Ticks missed while the process was stopped or the machine slept are
+    /// skipped rather than drawn in a burst. Before the next tick, such as
+    /// after a key press ends a wait early, this does nothing.
+    pub fn advance(&mut self, now: Instant) {
+        if now < self.next {
+            return;
+        }
+        if self.interval.is_zero() {
+            self.next = now;
+            return;
+        }
+        let missed = (now - self.next).as_nanos() / self.interval.as_nanos() + 1;
+        let missed = u32::try_from(missed).unwrap_or(u32::MAX);
+        self.next += self.interval.saturating_mul(missed);
+    }
+}
+
+/// A key press
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    /// A character typed without Ctrl
+    Char(char),
+    /// A character typed with Ctrl, such as Ctrl+C
+    Ctrl(char),
+    Enter,
+    Esc,
+    Backspace,
+    Up,
+    Down,
+    /// Any other key, such as a function key
+    Other,
+}
+
+/// Where key presses come from
+pub trait EventSource {
+    /// Wait up to `timeout`, or indefinitely when `None`, for the next key
+    ///
+    /// Returns `None` when the wait ends without a key press.
+    fn next_key(&mut self, timeout: Option<Duration>) -> io::Result<Option<Key>>;
+
+    /// Let `duration` pass without reading keys, when output is not a terminal
+    fn sleep(&mut self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// Key presses read from the terminal
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TerminalEvents;
+
+#[cfg(feature = "interactive")]
+impl EventSource for TerminalEvents {
+    fn next_key(&mut self, timeout: Option<Duration>) -> io::Result<Option<Key>> {
+        use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+
+        if let Some(timeout) = timeout {
+            if !event::poll(timeout)? {
+                return Ok(None);
+            }
+        }
+        let Event::Key(KeyEvent {
+            code, modifiers, ..
+        }) = event::read()?
+        else {
+            return Ok(None);
+        };
+        Ok(Some(match code {
+            KeyCode::Char(ch) if modifiers.contains(KeyModifiers::CONTROL) => Key::Ctrl(ch),
+            KeyCode::Char(ch) => Key::Char(ch),
+            KeyCode::Enter => Key::Enter,
+            KeyCode::Esc => Key::Esc,
+            KeyCode::Backspace => Key::Backspace,
+            KeyCode::Up => Key::Up,
+            KeyCode::Down => Key::Down,
+            _ => Key::Other,
+        }))
+    }
+}
+
+#[cfg(not(feature = "interactive"))]
+impl EventSource for TerminalEvents {
+    fn next_key(&mut self, timeout: Option<Duration>) -> io::Result<Option<Key>> {
+        match timeout {
+            Some(timeout) => {
+                std::thread::sleep(timeout);
+                Ok(None)
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "pmon was built without the `interactive` feature",
+            )),
+        }
+    }
+}
+
+/// Where the current time comes from
+pub trait Clock {
+    /// The current civil time
+    fn now(&self) -> NaiveDateTime;
+
+    /// Monotonic time advancing at the same rate as [`Clock::now`]
+    ///
+    /// Rate limits and clock-skew checks measure elapsed time with this.
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+
+    /// Seconds this clock advances per real second
+    fn scale(&self) -> f64 {
+        1.0
+    }
+}
+
+/// The clock used everywhere else, see [`get_current_time`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> NaiveDateTime {
+        get_current_time()
+    }
+}
+
+/// A clock running `scale` times faster than real time, for `--demo`
+///
+/// It reads `origin` when created and advances `scale` seconds for every
+/// real second after that.
+#[derive(Debug, Clone, Copy)]
+pub struct ScaledClock {
+    origin: NaiveDateTime,
+    started: Instant,
+    scale: f64,
+}
+
+impl ScaledClock {
+    /// Start at `origin`, running `scale` times faster than real time
+    pub fn new(origin: NaiveDateTime, scale: f64) -> Self {
+        Self {
+            origin,
+            started: Instant::now(),
+            scale: if scale.is_finite() {
+                scale.max(0.0)
+            } else {
+                0.0
+            },
+        }
+    }
+
+    /// Play `window` back from its start over `length` of real time
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::NaiveDateTime;
+    /// use pmon::chain::Segment;
+    /// use pmon::event_loop::ScaledClock;
+    /// use std::time::Duration;
+    ///
+    /// let at = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+    /// let window = Segment {
+    ///     label: None,
+    ///     start: at("2025-01-27 09:00:00"),
+    ///     end: at("2025-01-27 17:00:00"),
+    /// };
+    /// // Eight hours in 30 seconds
+    /// let clock = ScaledClock::compressing(&window, Duration::from_secs(30));
+    /// assert_eq!(clock.at(Duration::from_secs(15)), at("2025-01-27 13:00:00"));
+    /// ```
+    pub fn compressing(window: &Segment, length: Duration) -> Self {
+        let window_secs = window.duration().num_milliseconds() as f64 / 1000.0;
+        Self::new(window.start, window_secs / length.as_secs_f64())
+    }
+
+    /// Time shown once `elapsed` real time has passed since the clock started
+    pub fn at(&self, elapsed: Duration) -> NaiveDateTime {
+        chrono::Duration::from_std(self.scaled(elapsed))
+            .ok()
+            .and_then(|advance| self.origin.checked_add_signed(advance))
+            .unwrap_or(NaiveDateTime::MAX)
+    }
+
+    /// Real time stretched by the scale, saturating instead of overflowing
+    fn scaled(&self, elapsed: Duration) -> Duration {
+        Duration::try_from_secs_f64(elapsed.as_secs_f64() * self.scale).unwrap_or(Duration::MAX)
+    }
+}
+
+impl Clock for ScaledClock {
+    fn now(&self) -> NaiveDateTime {
+        self.at(self.started.elapsed())
+    }
+
+    fn instant(&self) -> Instant {
+        let elapsed = self.scaled(self.started.elapsed());
+        self.started
+            .checked_add(elapsed)
+            .unwrap_or_else(Instant::now)
+    }
+
+    fn scale(&self) -> f64 {
+        self.scale
+    }
+}
+
+/// What a key press asks the loop to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyAction {
+    /// Stop monitoring (Ctrl+C)
+    Quit,
+    /// Switch between durations and timestamps (`t`)
+    ToggleTimeStyle,
+    /// Read a note from the keyboard (`n`)
+    TakeNote,
+    /// Read the length of a sub-timer from the keyboard (`s`)
+    StartSubTimer,
+    /// Restart the window now, keeping its length (`r`)
+    Restart,
+    /// Move the window later or earlier (`+`, `-`)
+    Shift(chrono::Duration),
+    /// Copy a snapshot of the bar to the clipboard (`c`)
+    CopySnapshot,
+}
+
+impl KeyAction {
+    /// The action bound to `key`, if any
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pmon::event_loop::{Key, KeyAction};
+    ///
+    /// assert_eq!(KeyAction::from_key(Key::Ctrl('c')), Some(KeyAction::Quit));
+    /// assert_eq!(KeyAction::from_key(Key::Char('x')), None);
+    /// ```
+    pub fn from_key(key: Key) -> Option<Self> {
+        let step = chrono::Duration::minutes(SHIFT_STEP_MINUTES);
+        match key {
+            Key::Ctrl('c') => Some(Self::Quit),
+            Key::Char('t') => Some(Self::ToggleTimeStyle),
+            Key::Char('n') => Some(Self::TakeNote),
+            Key::Char('s') => Some(Self::StartSubTimer),
+            Key::Char('r') => Some(Self::Restart),
+            Key::Char('+') => Some(Self::Shift(step)),
+            Key::Char('-') => Some(Self::Shift(-step)),
+            Key::Char('c') => Some(Self::CopySnapshot),
+            _ => None,
+        }
+    }
+
+    /// Whether the action moves or ends the window, which `--locked` forbids
+    pub fn changes_window(self) -> bool {
+        matches!(self, Self::Quit | Self::Restart | Self::Shift(_))
+    }
+}
+
+/// What the keys can change while a window is shown
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoopControls {
+    /// The window, as restarted or shifted so far
+    pub window: Segment,
+    /// Whether durations or timestamps are shown
+    pub time_style: TimeInfoStyle,
+}
+
+impl LoopControls {
+    /// Controls starting from `window` shown with `time_style`
+    pub fn new(window: Segment, time_style: TimeInfoStyle) -> Self {
+        Self { window, time_style }
+    }
+
+    /// Apply `action` at `now`; returns false for actions left to the caller
+    pub fn apply(&mut self, action: KeyAction, now: NaiveDateTime) -> bool {
+        match action {
+            KeyAction::ToggleTimeStyle => self.time_style = self.time_style.toggle(),
+            KeyAction::Restart => self.window.reanchor(now),
+            KeyAction::Shift(by) => self.window.shift(by),
+            KeyAction::Quit
+            | KeyAction::TakeNote
+            | KeyAction::StartSubTimer
+            | KeyAction::CopySnapshot => return false,
+        }
+        true
+    }
+}
+
+/// Waits between updates while listening for key presses
+#[derive(Debug)]
+pub struct EventLoop<E, C> {
+    events: E,
+    clock: C,
+}
+
+impl EventLoop<TerminalEvents, SystemClock> {
+    /// Listen to the terminal and use the system clock
+    pub fn terminal() -> Self {
+        Self::new(TerminalEvents, SystemClock)
+    }
+}
+
+impl<E: EventSource, C: Clock> EventLoop<E, C> {
+    /// Listen to `events` and tell time with `clock`
+    pub fn new(events: E, clock: C) -> Self {
+        Self { events, clock }
+    }
+
+    /// The current time according to the loop's clock
+    pub fn now(&self) -> NaiveDateTime {
+        self.clock.now()
+    }
+
+    /// Monotonic time according to the loop's clock
+    pub fn instant(&self) -> Instant {
+        self.clock.instant()
+    }
+
+    /// How far the loop's clock advances during `real` time
+    pub fn clock_time(&self, real: Duration) -> Duration {
+        Duration::try_from_secs_f64(real.as_secs_f64() * self.clock.scale())
+            .unwrap_or(Duration::MAX)
+    }
+
+    /// Real time the loop's clock takes to advance by `clock_time`
+    ///
+    /// A stopped clock is waited on in real time rather than forever.
+    fn real_time(&self, clock_time: Duration) -> Duration {
+        let scale = self.clock.scale();
+        if scale > 0.0 {
+            Duration::try_from_secs_f64(clock_time.as_secs_f64() / scale).unwrap_or(Duration::MAX)
+        } else {
+            clock_time
+        }
+    }
+
+    /// Wait up to `interval` on the loop's clock for a bound key
+    ///
+    /// Returns the key's action as soon as it is pressed, or `None` once the
+    /// interval has passed. Unbound keys are ignored.
+    pub fn wait(&mut self, interval: Duration) -> io::Result<Option<KeyAction>> {
+        let mut remaining = self.real_time(interval);
+        while remaining > Duration::ZERO {
+            let chunk = remaining.min(POLL_INTERVAL);
+            if let Some(key) = self.next_key(Some(chunk))? {
+                let action = KeyAction::from_key(key);
+                debug!(?key, ?action, "key pressed");
+                if action.is_some() {
+                    return Ok(action);
+                }
+            }
+            remaining = remaining.saturating_sub(chunk);
+        }
+        Ok(None)
+    }
+
+    /// Let `interval` pass on the loop's clock without listening for keys
+    pub fn sleep(&mut self, interval: Duration) {
+        let real = self.real_time(interval);
+        self.events.sleep(real);
+    }
+
+    /// Read a line of at most `max_chars` characters
+    ///
+    /// `echo` is called with the text typed so far before each key. Enter
+    /// returns the text; Esc or Ctrl+C returns `None`.
+    pub fn read_line(
+        &mut self,
+        max_chars: usize,
+        mut echo: impl FnMut(&str) -> io::Result<()>,
+    ) -> io::Result<Option<String>> {
+        let mut text = String::new();
+        loop {
+            echo(&text)?;
+            match self.next_key(None)? {
+                Some(Key::Enter) => return Ok(Some(text)),
+                Some(Key::Esc | Key::Ctrl('c')) => return Ok(None),
+                Some(Key::Backspace) => {
+                    text.pop();
+                }
+                Some(Key::Char(ch)) if text.chars().count() < max_chars => text.push(ch),
+                _ => {}
+            }
+        }
+    }
+
+    /// Wait for a key press; returns whether it was `key`
+    pub fn confirm(&mut self, key: char) -> io::Result<bool> {
+        loop {
+            if let Some(pressed) = self.next_key(None)? {
+                debug!(key = ?pressed, expected = ?key, "key pressed");
+                return Ok(pressed == Key::Char(key));
+            }
+        }
+    }
+
+    /// Wait up to `timeout`, or indefinitely when `None`, for any key
+    pub fn next_key(&mut self, timeout: Option<Duration>) -> io::Result<Option<Key>> {
+        self.events.next_key(timeout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::collections::VecDeque;
+    use std::rc::Rc;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    /// A clock that only moves when the scripted events let time pass
+    #[derive(Clone)]
+    struct FakeClock(Rc<Cell<NaiveDateTime>>);
+
+    impl Clock for FakeClock {
+        fn now(&self) -> NaiveDateTime {
+            self.0.get()
+        }
+    }
+
+    /// Key presses delivered one per wait; `None` lets the wait time out
+    struct ScriptedEvents {
+        keys: VecDeque<Option<Key>>,
+        clock: FakeClock,
+        waits: usize,
+    }
+
+    impl EventSource for ScriptedEvents {
+        fn next_key(&mut self, timeout: Option<Duration>) -> io::Result<Option<Key>> {
+            self.waits += 1;
+            match (self.keys.pop_front(), timeout) {
+                (Some(Some(key)), _) => Ok(Some(key)),
+                (_, Some(timeout)) => {
+                    let clock = &self.clock.0;
+                    clock.set(clock.get() + chrono::Duration::from_std(timeout).unwrap());
+                    Ok(None)
+                }
+                (_, None) => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "script ended")),
+            }
+        }
+
+        fn sleep(&mut self, duration: Duration) {
+            let clock = &self.clock.0;
+            clock.set(clock.get() + chrono::Duration::from_std(duration).unwrap());
+        }
+    }
+
+    fn event_loop(keys: Vec<Option<Key>>) -> EventLoop<ScriptedEvents, FakeClock> {
+        let clock = FakeClock(Rc::new(Cell::new(create_test_datetime(
+            "2025-01-27 10:00:00",
+        ))));
+        let events = ScriptedEvents {
+            keys: keys.into(),
+            clock: clock.clone(),
+            waits: 0,
+        };
+        EventLoop::new(events, clock)
+    }
+
+    #[test]
+    fn test_key_bindings() {
+        let bindings = [
+            (Key::Char('t'), Some(KeyAction::ToggleTimeStyle)),
+            (Key::Char('n'), Some(KeyAction::TakeNote)),
+            (Key::Char('s'), Some(KeyAction::StartSubTimer)),
+            (Key::Char('r'), Some(KeyAction::Restart)),
+            (
+                Key::Char('+'),
+                Some(KeyAction::Shift(chrono::Duration::minutes(1))),
+            ),
+            (
+                Key::Char('-'),
+                Some(KeyAction::Shift(chrono::Duration::minutes(-1))),
+            ),
+            (Key::Ctrl('c'), Some(KeyAction::Quit)),
+            (Key::Char('c'), Some(KeyAction::CopySnapshot)),
+            (Key::Char('x'), None),
+            (Key::Enter, None),
+            (Key::Other, None),
+        ];
+        for (key, action) in bindings {
+            assert_eq!(KeyAction::from_key(key), action, "{key:?}");
+        }
+    }
+
+    #[test]
+    fn test_locked_keys() {
+        let changing: Vec<_> = ['t', 'n', 's', 'r', '+', '-']
+            .into_iter()
+            .filter_map(|ch| KeyAction::from_key(Key::Char(ch)))
+            .filter(|action| action.changes_window())
+            .collect();
+        assert_eq!(
+            changing,
+            [
+                KeyAction::Restart,
+                KeyAction::Shift(chrono::Duration::minutes(1)),
+                KeyAction::Shift(chrono::Duration::minutes(-1)),
+            ]
+        );
+        assert!(KeyAction::Quit.changes_window());
+    }
+
+    #[test]
+    fn test_wait_times_out_in_poll_steps() {
+        let mut event_loop = event_loop(Vec::new());
+        assert_eq!(event_loop.wait(Duration::from_secs(1)).unwrap(), None);
+        assert_eq!(event_loop.events.waits, 10);
+        assert_eq!(
+            event_loop.now(),
+            create_test_datetime("2025-01-27 10:00:01")
+        );
+    }
+
+    #[test]
+    fn test_until_aligned() {
+        let now = create_test_datetime("2025-01-27 10:00:30");
+        assert_eq!(
+            until_aligned(now, Duration::from_secs(30)),
+            Duration::from_secs(30)
+        );
+        assert_eq!(
+            until_aligned(now, Duration::from_secs(60)),
+            Duration::from_secs(30)
+        );
+        assert_eq!(
+            until_aligned(now, Duration::from_secs(3600)),
+            Duration::from_secs(3570)
+        );
+
+        let now = now + chrono::Duration::milliseconds(250);
+        assert_eq!(
+            until_aligned(now, Duration::from_secs(1)),
+            Duration::from_millis(750)
+        );
+        assert_eq!(until_aligned(now, Duration::ZERO), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_tick_schedule_absorbs_work_time() {
+        let start = Instant::now();
+        let wall = create_test_datetime("2025-01-27 10:00:00");
+        let second = Duration::from_secs(1);
+        let mut schedule = TickSchedule::new(second, false, start);
+
+        // 300ms spent drawing leaves 700ms to wait
+        let now = start + Duration::from_millis(300);
+        assert_eq!(schedule.remaining(now, wall), Duration::from_millis(700));
+        // A key press ending the wait early keeps the deadline
+        schedule.advance(now);
+        assert_eq!(schedule.remaining(now, wall), Duration::from_millis(700));
+
+        // Ticks stay a second apart from the start, not from each wake-up
+        schedule.advance(start + Duration::from_millis(1010));
+        let now = start + Duration::from_millis(1250);
+        assert_eq!(schedule.remaining(now, wall), Duration::from_millis(750));
+    }
+
+    #[test]
+    fn test_tick_schedule_interval_change() {
+        let start = Instant::now();
+        let wall = create_test_datetime("2025-01-27 10:00:00");
+        let mut schedule = TickSchedule::new(Duration::from_secs(10), false, start);
+        let now = start + Duration::from_secs(4);
+        schedule.set_interval(Duration::from_secs(40));
+        assert_eq!(schedule.remaining(now, wall), Duration::from_secs(36));
+        schedule.advance(start + Duration::from_secs(40));
+        assert_eq!(
+            schedule.remaining(start + Duration::from_secs(40), wall),
+            Duration::from_secs(40)
+        );
+    }
+
+    #[test]
+    fn test_tick_schedule_skips_missed_ticks() {
+        let start = Instant::now();
+        let wall = create_test_datetime("2025-01-27 10:00:00");
+        let mut schedule = TickSchedule::new(Duration::from_secs(10), false, start);
+        // Asleep for a minute and a half
+        let now = start + Duration::from_secs(95);
+        schedule.advance(now);
+        assert_eq!(schedule.remaining(now, wall), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_tick_schedule_aligned() {
+        let start = Instant::now();
+        let wall = create_test_datetime("2025-01-27 10:00:12");
+        let schedule = TickSchedule::new(Duration::from_secs(30), true, start);
+        assert_eq!(schedule.remaining(start, wall), Duration::from_secs(18));
+    }
+
+    #[test]
+    fn test_wait_returns_first_bound_key() {
+        let mut event_loop = event_loop(vec![None, Some(Key::Char('x')), Some(Key::Ctrl('c'))]);
+        assert_eq!(
+            event_loop.wait(Duration::from_secs(1)).unwrap(),
+            Some(KeyAction::Quit)
+        );
+        assert_eq!(event_loop.events.waits, 3);
+    }
+
+    #[test]
+    fn test_sleep_lets_clock_time_pass() {
+        let mut event_loop = event_loop(vec![Some(Key::Ctrl('c'))]);
+        event_loop.sleep(Duration::from_secs(90));
+        assert_eq!(
+            event_loop.now(),
+            create_test_datetime("2025-01-27 10:01:30")
+        );
+        // Keys are not read while sleeping
+        assert_eq!(event_loop.events.waits, 0);
+    }
+
+    #[test]
+    fn test_scaled_clock_time() {
+        let clock = ScaledClock::new(create_test_datetime("2025-01-27 09:00:00"), 60.0);
+        let event_loop = EventLoop::new(TerminalEvents, clock);
+        assert_eq!(
+            event_loop.clock_time(Duration::from_millis(100)),
+            Duration::from_secs(6)
+        );
+        assert_eq!(
+            event_loop.real_time(Duration::from_secs(6)),
+            Duration::from_millis(100)
+        );
+    }
+
+    #[test]
+    fn test_controls_toggle_and_move_window() {
+        let window = Segment {
+            label: None,
+            start: create_test_datetime("2025-01-27 09:00:00"),
+            end: create_test_datetime("2025-01-27 09:30:00"),
+        };
+        let mut controls = LoopControls::new(window, TimeInfoStyle::Durations);
+        let now = create_test_datetime("2025-01-27 09:10:00");
+
+        assert!(controls.apply(KeyAction::ToggleTimeStyle, now));
+        assert_eq!(controls.time_style, TimeInfoStyle::Timestamps);
+        assert!(controls.apply(KeyAction::ToggleTimeStyle, now));
+        assert_eq!(controls.time_style, TimeInfoStyle::Durations);
+
+        assert!(controls.apply(KeyAction::Shift(chrono::Duration::minutes(-1)), now));
+        assert_eq!(
+            controls.window.start,
+            create_test_datetime("2025-01-27 08:59:00")
+        );
+        assert!(controls.apply(KeyAction::Restart, now));
+        assert_eq!(controls.window.start, now);
+        assert_eq!(
+            controls.window.end,
+            create_test_datetime("2025-01-27 09:40:00")
+        );
+
+        assert!(!controls.apply(KeyAction::Quit, now));
+        assert!(!controls.apply(KeyAction::TakeNote, now));
+        assert!(!controls.apply(KeyAction::StartSubTimer, now));
+    }
+
+    #[test]
+    fn test_scaled_clock() {
+        let window = Segment {
+            label: None,
+            start: create_test_datetime("2025-01-27 09:00:00"),
+            end: create_test_datetime("2025-01-27 10:00:00"),
+        };
+        let clock = ScaledClock::compressing(&window, Duration::from_secs(6));
+        assert_eq!(clock.at(Duration::ZERO), window.start);
+        assert_eq!(
+            clock.at(Duration::from_millis(100)),
+            create_test_datetime("2025-01-27 09:01:00")
+        );
+        assert_eq!(clock.at(Duration::from_secs(6)), window.end);
+        // The clock keeps running past the end of the window
+        assert_eq!(
+            clock.at(Duration::from_secs(12)),
+            create_test_datetime("2025-01-27 11:00:00")
+        );
+        assert!(clock.now() >= window.start);
+        assert!(clock.instant() >= clock.started);
+    }
+
+    #[test]
+    fn test_scaled_clock_saturates() {
+        let start = create_test_datetime("2025-01-27 09:00:00");
+        let clock = ScaledClock::new(start, f64::MAX);
+        assert_eq!(clock.at(Duration::from_secs(1)), NaiveDateTime::MAX);
+
+        // A scale that is not a positive number stops the clock
+        let clock = ScaledClock::new(start, f64::NAN);
+        assert_eq!(clock.at(Duration::from_secs(60)), start);
+        let clock = ScaledClock::new(start, -1.0);
+        assert_eq!(clock.at(Duration::from_secs(60)), start);
+    }
+
+    #[test]
+    fn test_read_line() {
+        let typed = vec![
+            Some(Key::Char('h')),
+            Some(Key::Char('x')),
+            Some(Key::Backspace),
+            Some(Key::Char('i')),
+            Some(Key::Char('!')),
+            Some(Key::Enter),
+        ];
+        let mut echoed = Vec::new();
+        let line = event_loop(typed)
+            .read_line(2, |text| {
+                echoed.push(text.to_string());
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(line.as_deref(), Some("hi"));
+        assert_eq!(echoed, ["", "h", "hx", "h", "hi", "hi"]);
+
+        let cancelled = vec![Some(Key::Char('h')), Some(Key::Esc)];
+        assert_eq!(
+            event_loop(cancelled).read_line(10, |_| Ok(())).unwrap(),
+            None
+        );
+        let interrupted = vec![Some(Key::Char('h')), Some(Key::Ctrl('c'))];
+        assert_eq!(
+            event_loop(interrupted).read_line(10, |_| Ok(())).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_confirm() {
+        assert!(event_loop(vec![Some(Key::Char('s'))]).confirm('s').unwrap());
+        assert!(!event_loop(vec![Some(Key::Enter)]).confirm('s').unwrap());
+    }
+}
@@ -0,0 +1,328 @@
+//! ETA forecasts from externally reported progress
+//!
+//! The bar normally shows how much of the time window has elapsed. With
+//! `--progress-file FILE`, pmon also reads how much of the *work* is done
+//! from a file that another program keeps up to date, as a percent (`42`
+//! or `42%`) or as `DONE/TOTAL` (`120/300`). Like a download manager, it
+//! averages the pace of the recent updates to forecast when the work will
+//! be finished, and shows the spread of that pace as confidence bounds in
+//! verbose mode.
+
+use crate::color::Colorize;
+use crate::error::{PbError, PbResult};
+use chrono::{Duration, NaiveDateTime};
+use std::collections::VecDeque;
+use std::path::Path;
+
+/// Option name used in error messages
+const OPTION_NAME: &str = "--progress-file";
+
+/// Number of recent updates the pace is averaged over
+pub const ROLLING_SAMPLES: usize = 10;
+
+/// Parse reported work progress, as `42`, `42%`, or `120/300`, into a percent
+///
+/// # Examples
+///
+/// ```
+/// use pmon::forecast::parse_work_progress;
+///
+/// assert_eq!(parse_work_progress("42").unwrap(), 42.0);
+/// assert_eq!(parse_work_progress("12.5%\n").unwrap(), 12.5);
+/// assert_eq!(parse_work_progress("120/300").unwrap(), 40.0);
+/// assert!(parse_work_progress("half").is_err());
+/// ```
+pub fn parse_work_progress(input: &str) -> PbResult<f64> {
+    let invalid = || PbError::invalid_argument(OPTION_NAME, input.trim());
+    let number = |text: &str| {
+        text.trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite() && *value >= 0.0)
+    };
+
+    let input = input.trim();
+    let percent = match input.split_once('/') {
+        Some((done, total)) => {
+            let total = number(total)
+                .filter(|total| *total > 0.0)
+                .ok_or_else(invalid)?;
+            number(done).ok_or_else(invalid)? / total * 100.0
+        }
+        None => number(input.strip_suffix('%').unwrap_or(input)).ok_or_else(invalid)?,
+    };
+    Ok(percent.min(100.0))
+}
+
+/// Read the work progress from `path`, or `None` if it cannot be read yet
+pub fn read_work_progress(path: &Path) -> Option<f64> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| parse_work_progress(&contents).ok())
+}
+
+/// When the work is expected to be done
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Forecast {
+    /// Expected finish at the average recent pace
+    pub eta: NaiveDateTime,
+    /// Finish if the pace is one standard deviation faster
+    pub earliest: NaiveDateTime,
+    /// Finish if the pace is one standard deviation slower; `None` when
+    /// that pace would stall
+    pub latest: Option<NaiveDateTime>,
+    /// Average pace in percent per minute, times 1000 to keep `Eq`
+    rate_milli: i64,
+}
+
+impl Forecast {
+    /// Average recent pace in percent per minute
+    pub fn rate_per_minute(&self) -> f64 {
+        self.rate_milli as f64 / 1000.0
+    }
+}
+
+/// Recent work progress reports, used to forecast the finish
+#[derive(Debug, Clone, Default)]
+pub struct Forecaster {
+    samples: VecDeque<(NaiveDateTime, f64)>,
+}
+
+impl Forecaster {
+    /// Record the work progress reported at `time`
+    ///
+    /// A report lower than the previous one means the work was restarted,
+    /// so the earlier reports are forgotten.
+    pub fn observe(&mut self, time: NaiveDateTime, percent: f64) {
+        if let Some(&(last_time, last_percent)) = self.samples.back() {
+            if percent < last_percent {
+                self.samples.clear();
+            } else if time <= last_time {
+                self.samples.pop_back();
+            }
+        }
+        self.samples.push_back((time, percent));
+        while self.samples.len() > ROLLING_SAMPLES + 1 {
+            self.samples.pop_front();
+        }
+    }
+
+    /// The latest work progress reported
+    pub fn latest(&self) -> Option<f64> {
+        self.samples.back().map(|&(_, percent)| percent)
+    }
+
+    /// Forecast the finish from the pace of the recent reports
+    ///
+    /// Returns `None` until there are two reports, once the work is done,
+    /// and while the work is not advancing.
+    pub fn forecast(&self) -> Option<Forecast> {
+        let &(first_time, first_percent) = self.samples.front()?;
+        let &(last_time, last_percent) = self.samples.back()?;
+        let span = seconds(last_time - first_time);
+        if span <= 0.0 || last_percent >= 100.0 {
+            return None;
+        }
+        let rate = (last_percent - first_percent) / span;
+        if rate <= 0.0 {
+            return None;
+        }
+
+        // Spread of the pace between consecutive reports
+        let rates: Vec<f64> = self
+            .samples
+            .iter()
+            .zip(self.samples.iter().skip(1))
+            .map(|(&(t0, p0), &(t1, p1))| (p1 - p0) / seconds(t1 - t0))
+            .collect();
+        let variance = rates.iter().map(|r| (r - rate).powi(2)).sum::<f64>() / rates.len() as f64;
+        let spread = variance.sqrt();
+
+        let remaining = 100.0 - last_percent;
+        let finish = |rate: f64| last_time + Duration::seconds((remaining / rate).round() as i64);
+        Some(Forecast {
+            eta: finish(rate),
+            earliest: finish(rate + spread),
+            latest: (rate - spread > 0.0).then(|| finish(rate - spread)),
+            rate_milli: (rate * 60.0 * 1000.0).round() as i64,
+        })
+    }
+}
+
+fn seconds(duration: Duration) -> f64 {
+    duration.num_milliseconds() as f64 / 1000.0
+}
+
+/// Format a time as `HH:MM`, with the date when it is not on `today`'s day
+fn format_time(time: NaiveDateTime, today: NaiveDateTime) -> String {
+    if time.date() == today.date() {
+        time.format("%H:%M").to_string()
+    } else {
+        time.format("%m-%d %H:%M").to_string()
+    }
+}
+
+/// Format the work progress and its forecast finish for the stats line
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDateTime;
+/// use pmon::forecast::{format_forecast, Forecaster};
+///
+/// let at = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+/// let mut forecaster = Forecaster::default();
+/// forecaster.observe(at("2025-01-27 09:00:00"), 20.0);
+/// assert_eq!(format_forecast(&forecaster, at("2025-01-27 09:00:00")), "work 20.0% ETA --:--");
+/// forecaster.observe(at("2025-01-27 09:10:00"), 40.0);
+/// assert_eq!(format_forecast(&forecaster, at("2025-01-27 09:10:00")), "work 40.0% ETA 09:40");
+/// ```
+pub fn format_forecast(forecaster: &Forecaster, now: NaiveDateTime) -> String {
+    let work = forecaster.latest().unwrap_or_default();
+    let eta = match forecaster.forecast() {
+        Some(forecast) => format_time(forecast.eta, now),
+        None if work >= 100.0 => "done".to_string(),
+        None => "--:--".to_string(),
+    };
+    format!("work {work:.1}% ETA {eta}")
+}
+
+/// Format the forecast with green when it finishes by `end`, yellow when not
+pub fn format_colored_forecast(
+    forecaster: &Forecaster,
+    now: NaiveDateTime,
+    end: NaiveDateTime,
+) -> String {
+    let text = format_forecast(forecaster, now);
+    match forecaster.forecast() {
+        Some(forecast) if forecast.eta <= end => text.green().to_string(),
+        Some(_) => text.yellow().to_string(),
+        None => text,
+    }
+}
+
+/// Format the confidence bounds of the forecast for verbose mode
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDateTime;
+/// use pmon::forecast::{format_forecast_bounds, Forecaster};
+///
+/// let at = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+/// let mut forecaster = Forecaster::default();
+/// forecaster.observe(at("2025-01-27 09:00:00"), 0.0);
+/// forecaster.observe(at("2025-01-27 09:10:00"), 20.0);
+/// forecaster.observe(at("2025-01-27 09:20:00"), 30.0);
+/// assert_eq!(
+///     format_forecast_bounds(&forecaster, at("2025-01-27 09:20:00")).unwrap(),
+///     "Forecast: 09:55-10:30 at 1.5%/min"
+/// );
+/// ```
+pub fn format_forecast_bounds(forecaster: &Forecaster, now: NaiveDateTime) -> Option<String> {
+    let forecast = forecaster.forecast()?;
+    let latest = forecast
+        .latest
+        .map_or_else(|| "?".to_string(), |latest| format_time(latest, now));
+    Some(format!(
+        "Forecast: {}-{latest} at {:.1}%/min",
+        format_time(forecast.earliest, now),
+        forecast.rate_per_minute()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn at_minute(minute: i64) -> NaiveDateTime {
+        create_test_datetime("2025-01-27 09:00:00") + Duration::minutes(minute)
+    }
+
+    #[test]
+    fn test_parse_invalid_work_progress() {
+        for input in ["", "abc", "-5", "5/0", "/10", "NaN", "10/x"] {
+            assert!(
+                matches!(
+                    parse_work_progress(input),
+                    Err(PbError::InvalidArgument { .. })
+                ),
+                "Expected '{input}' to be rejected"
+            );
+        }
+        assert_eq!(parse_work_progress("400/300").unwrap(), 100.0);
+    }
+
+    #[test]
+    fn test_steady_pace_has_tight_bounds() {
+        let mut forecaster = Forecaster::default();
+        for minute in 0..5 {
+            forecaster.observe(at_minute(minute), minute as f64 * 5.0);
+        }
+        let forecast = forecaster.forecast().unwrap();
+        assert_eq!(forecast.eta, at_minute(20));
+        assert_eq!(forecast.earliest, forecast.eta);
+        assert_eq!(forecast.latest, Some(forecast.eta));
+        assert_eq!(forecast.rate_per_minute(), 5.0);
+    }
+
+    #[test]
+    fn test_pace_is_averaged_over_recent_reports() {
+        let mut forecaster = Forecaster::default();
+        // A slow start drops out of the rolling window
+        forecaster.observe(at_minute(0), 0.0);
+        forecaster.observe(at_minute(30), 1.0);
+        for minute in 1..=ROLLING_SAMPLES as i64 {
+            forecaster.observe(at_minute(30 + minute), 1.0 + minute as f64 * 2.0);
+        }
+        let forecast = forecaster.forecast().unwrap();
+        assert_eq!(forecast.rate_per_minute(), 2.0);
+        assert_eq!(forecast.eta, at_minute(40) + Duration::seconds(79 * 30));
+    }
+
+    #[test]
+    fn test_stalled_or_finished_work_has_no_forecast() {
+        let mut forecaster = Forecaster::default();
+        forecaster.observe(at_minute(0), 10.0);
+        assert!(forecaster.forecast().is_none());
+        forecaster.observe(at_minute(5), 10.0);
+        assert!(forecaster.forecast().is_none());
+        forecaster.observe(at_minute(10), 100.0);
+        assert!(forecaster.forecast().is_none());
+        assert_eq!(
+            format_forecast(&forecaster, at_minute(10)),
+            "work 100.0% ETA done"
+        );
+    }
+
+    #[test]
+    fn test_restarted_work_is_forecast_afresh() {
+        let mut forecaster = Forecaster::default();
+        forecaster.observe(at_minute(0), 50.0);
+        forecaster.observe(at_minute(10), 90.0);
+        forecaster.observe(at_minute(11), 0.0);
+        assert_eq!(forecaster.latest(), Some(0.0));
+        assert!(forecaster.forecast().is_none());
+    }
+
+    #[test]
+    fn test_erratic_pace_widens_bounds() {
+        let mut forecaster = Forecaster::default();
+        forecaster.observe(at_minute(0), 0.0);
+        forecaster.observe(at_minute(10), 30.0);
+        forecaster.observe(at_minute(20), 30.0);
+        forecaster.observe(at_minute(30), 30.0);
+        let forecast = forecaster.forecast().unwrap();
+        assert!(forecast.earliest < forecast.eta);
+        // A spread wider than the pace itself leaves no lower bound
+        assert_eq!(forecast.latest, None);
+        assert_eq!(
+            format_forecast_bounds(&forecaster, at_minute(30)).unwrap(),
+            "Forecast: 09:59-? at 1.0%/min"
+        );
+    }
+}
@@ -0,0 +1,278 @@
+//! GitHub Actions workflow command output
+//!
+//! With `--format gha` pmon stays quiet between updates and instead prints
+//! `::notice::`, `::warning::`, and `::error::` workflow commands when the
+//! window passes a milestone, reaches a checkpoint, or ends. Actions turns
+//! these into annotations on the run page, so a long CI step reports its
+//! time budget without scrolling through the log.
+
+use crate::checkpoint::Checkpoint;
+use crate::progress_bar::DurationFormat;
+use crate::progress_state::ProgressState;
+use chrono::NaiveDateTime;
+
+/// Severity of a workflow command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnotationLevel {
+    /// `::notice::`
+    Notice,
+    /// `::warning::`
+    Warning,
+    /// `::error::`
+    Error,
+}
+
+impl AnnotationLevel {
+    /// Workflow command name
+    pub fn command(self) -> &'static str {
+        match self {
+            AnnotationLevel::Notice => "notice",
+            AnnotationLevel::Warning => "warning",
+            AnnotationLevel::Error => "error",
+        }
+    }
+}
+
+/// Percentages announced as the window elapses, with their severity
+pub const MILESTONES: [(f64, AnnotationLevel); 4] = [
+    (25.0, AnnotationLevel::Notice),
+    (50.0, AnnotationLevel::Notice),
+    (75.0, AnnotationLevel::Warning),
+    (90.0, AnnotationLevel::Warning),
+];
+
+/// Escape a message for a workflow command
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a property value such as `title=` for a workflow command
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// Format a workflow command such as `::notice title=Build::Tests passed`
+///
+/// # Examples
+///
+/// ```
+/// use pmon::gha::{workflow_command, AnnotationLevel};
+///
+/// assert_eq!(
+///     workflow_command(AnnotationLevel::Warning, Some("Deploy: prod"), "75% elapsed"),
+///     "::warning title=Deploy%3A prod::75%25 elapsed"
+/// );
+/// assert_eq!(workflow_command(AnnotationLevel::Error, None, "a\nb"), "::error::a%0Ab");
+/// ```
+pub fn workflow_command(level: AnnotationLevel, title: Option<&str>, message: &str) -> String {
+    match title {
+        Some(title) => format!(
+            "::{} title={}::{}",
+            level.command(),
+            escape_property(title),
+            escape_data(message)
+        ),
+        None => format!("::{}::{}", level.command(), escape_data(message)),
+    }
+}
+
+/// Turns progress updates into workflow commands, announcing each event once
+#[derive(Debug, Clone)]
+pub struct GhaReporter {
+    title: Option<String>,
+    format: DurationFormat,
+    milestones_passed: usize,
+    checkpoints_passed: usize,
+}
+
+impl GhaReporter {
+    /// Report on a window shown with `label`, formatting durations with `format`
+    pub fn new(label: Option<&str>, format: DurationFormat) -> Self {
+        Self {
+            title: label.map(str::to_string),
+            format,
+            milestones_passed: 0,
+            checkpoints_passed: 0,
+        }
+    }
+
+    fn command(&self, level: AnnotationLevel, message: &str) -> String {
+        workflow_command(level, self.title.as_deref(), message)
+    }
+
+    /// Commands for milestones and checkpoints passed since the last update
+    ///
+    /// `checkpoints` must be sorted by time, as [`resolve_checkpoints`]
+    /// returns them. Completion is reported separately by
+    /// [`GhaReporter::completed`].
+    ///
+    /// [`resolve_checkpoints`]: crate::checkpoint::resolve_checkpoints
+    pub fn update(&mut self, state: &ProgressState, checkpoints: &[Checkpoint]) -> Vec<String> {
+        let mut commands = Vec::new();
+        while let Some(&(milestone, level)) = MILESTONES.get(self.milestones_passed) {
+            if state.is_complete() || state.percentage < milestone {
+                break;
+            }
+            commands.push(self.command(
+                level,
+                &format!(
+                    "{milestone}% of the time budget used, {} remaining",
+                    self.format.remaining(state.remaining())
+                ),
+            ));
+            self.milestones_passed += 1;
+        }
+        while let Some(checkpoint) = checkpoints.get(self.checkpoints_passed) {
+            if checkpoint.at > state.current {
+                break;
+            }
+            commands.push(self.command(
+                AnnotationLevel::Notice,
+                &format!("Checkpoint '{}' reached", checkpoint.name),
+            ));
+            self.checkpoints_passed += 1;
+        }
+        commands
+    }
+
+    /// Command reporting that the window elapsed
+    pub fn completed(&self, state: &ProgressState) -> String {
+        self.command(
+            AnnotationLevel::Notice,
+            &format!(
+                "Time window elapsed after {}",
+                self.format.elapsed(state.end - state.start)
+            ),
+        )
+    }
+
+    /// Command reporting how a wrapped command ended
+    ///
+    /// Running out of time or exiting unsuccessfully is an error.
+    pub fn command_finished(
+        &self,
+        program: &str,
+        code: i32,
+        timed_out: bool,
+        finished_at: NaiveDateTime,
+        state: &ProgressState,
+    ) -> String {
+        let used = self.format.elapsed(finished_at - state.start);
+        if timed_out {
+            self.command(
+                AnnotationLevel::Error,
+                &format!("{program} ran out of time after {used} (exit code {code})"),
+            )
+        } else if code != 0 {
+            self.command(
+                AnnotationLevel::Error,
+                &format!("{program} failed after {used} with exit code {code}"),
+            )
+        } else {
+            self.command(
+                AnnotationLevel::Notice,
+                &format!("{program} finished after {used} within its time budget"),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::progress_bar::DurationStyle;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn state_at(current: &str) -> ProgressState {
+        ProgressState::new(
+            create_test_datetime("2025-01-27 09:00:00"),
+            create_test_datetime("2025-01-27 10:40:00"),
+            create_test_datetime(current),
+        )
+    }
+
+    fn reporter() -> GhaReporter {
+        GhaReporter::new(Some("Build"), DurationStyle::Compact.into())
+    }
+
+    #[test]
+    fn test_milestones_are_announced_once() {
+        let mut reporter = reporter();
+        assert!(reporter
+            .update(&state_at("2025-01-27 09:10:00"), &[])
+            .is_empty());
+
+        let commands = reporter.update(&state_at("2025-01-27 09:25:00"), &[]);
+        assert_eq!(
+            commands,
+            ["::notice title=Build::25%25 of the time budget used, 1h 15m remaining"]
+        );
+        assert!(reporter
+            .update(&state_at("2025-01-27 09:26:00"), &[])
+            .is_empty());
+
+        // Milestones skipped between updates are all reported, in order
+        let commands = reporter.update(&state_at("2025-01-27 10:20:00"), &[]);
+        assert_eq!(commands.len(), 2);
+        assert!(commands[0].starts_with("::notice title=Build::50%25"));
+        assert!(commands[1].starts_with("::warning title=Build::75%25"));
+    }
+
+    #[test]
+    fn test_checkpoints_are_announced_when_reached() {
+        let checkpoints = [
+            Checkpoint {
+                name: "tests".to_string(),
+                at: create_test_datetime("2025-01-27 09:05:00"),
+            },
+            Checkpoint {
+                name: "deploy".to_string(),
+                at: create_test_datetime("2025-01-27 09:20:00"),
+            },
+        ];
+        let mut reporter = reporter();
+        let commands = reporter.update(&state_at("2025-01-27 09:06:00"), &checkpoints);
+        assert_eq!(
+            commands,
+            ["::notice title=Build::Checkpoint 'tests' reached"]
+        );
+        let commands = reporter.update(&state_at("2025-01-27 09:20:00"), &checkpoints);
+        assert_eq!(
+            commands,
+            ["::notice title=Build::Checkpoint 'deploy' reached"]
+        );
+    }
+
+    #[test]
+    fn test_completion_and_command_results() {
+        let reporter = reporter();
+        let state = state_at("2025-01-27 10:40:00");
+        assert_eq!(
+            reporter.completed(&state),
+            "::notice title=Build::Time window elapsed after 1h 40m"
+        );
+
+        let at = create_test_datetime("2025-01-27 09:30:00");
+        let finished =
+            |code, timed_out| reporter.command_finished("make", code, timed_out, at, &state);
+        assert!(finished(0, false).starts_with("::notice title=Build::make finished after 30m"));
+        assert!(finished(2, false).starts_with("::error title=Build::make failed after 30m"));
+        assert!(finished(143, true).starts_with("::error title=Build::make ran out of time"));
+    }
+
+    #[test]
+    fn test_untitled_commands_escape_messages() {
+        let reporter = GhaReporter::new(None, DurationFormat::default());
+        let state = state_at("2025-01-27 10:40:00");
+        let at = create_test_datetime("2025-01-27 10:40:00");
+        assert_eq!(
+            reporter.command_finished("100%\nsure", 0, false, at, &state),
+            "::notice::100%25%0Asure finished after 1h 40m within its time budget"
+        );
+    }
+}
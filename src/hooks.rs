@@ -0,0 +1,752 @@
+//! Actions run when a window passes a milestone, completes, or runs over
+//!
+//! Hooks are declared in the configuration file. Each one names the event
+//! it waits for and one or more actions: a command to run, a URL to POST
+//! the event to as JSON, a desktop notification, a spoken announcement, or
+//! the terminal bell.
+//!
+//! ```toml
+//! [[hooks]]
+//! on = "milestone"
+//! at = [50, 90]                # percent; defaults to 25, 50, 75, and 90
+//! run = ["blink1-tool", "--rgb", "orange"]
+//!
+//! [[hooks]]
+//! on = "complete"
+//! post = "http://localhost:8080/pmon"
+//! token = "keyring:relay"      # optional bearer token; see the secrets module
+//! bell = true
+//!
+//! [[hooks]]
+//! on = "overtime"
+//! after = "10m"                # how far past the end; defaults to 0s
+//! notify = "{label} is {overtime} over"
+//!
+//! [[hooks]]
+//! on = "milestone"
+//! at = [90]
+//! say = "{remaining} left"     # read aloud; see below
+//! ```
+//!
+//! `say` uses `say` on macOS, the speech synthesizer of PowerShell on
+//! Windows, and the first of `espeak-ng`, `espeak`, or `spd-say` installed
+//! elsewhere.
+//!
+//! Command arguments and notification and spoken texts may use the placeholders
+//! `{event}`, `{label}`, `{name}`, `{percent}`, `{elapsed}`, `{remaining}`,
+//! `{overtime}`, and `{end}`. Commands are started without a shell and also
+//! receive the values as `PMON_EVENT`, `PMON_LABEL`, `PMON_NAME`,
+//! `PMON_PERCENT`, `PMON_ELAPSED`, `PMON_REMAINING`, `PMON_OVERTIME`, and
+//! `PMON_END` environment variables.
+//!
+//! Each event fires once per window. Milestones already passed when pmon
+//! starts are skipped, so joining a window late does not replay them.
+
+use crate::deadline::parse_lead_time;
+use crate::doctor::find_program;
+use crate::error::{PbError, PbResult};
+#[cfg(feature = "net")]
+use crate::http::{HttpClient, HttpUrl};
+#[cfg(feature = "net")]
+use crate::outbox::deliver;
+use crate::progress_bar::{format_percentage, DurationFormat};
+use crate::progress_state::ProgressState;
+use crate::secrets::SecretRef;
+#[cfg(feature = "net")]
+use crate::store::PendingDelivery;
+use chrono::Duration;
+#[cfg(feature = "net")]
+use chrono::NaiveDateTime;
+use serde::Deserialize;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Milestones a `milestone` hook fires at when it lists none
+pub const DEFAULT_MILESTONES: [u16; 4] = [25, 50, 75, 90];
+
+/// Option name used in error messages
+const OPTION_NAME: &str = "hooks";
+
+/// Events a hook can wait for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HookTrigger {
+    /// The window passes a percentage
+    Milestone,
+    /// The window reaches its end
+    Complete,
+    /// The window runs past its end
+    Overtime,
+}
+
+/// A `[[hooks]]` table in the configuration file
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HookConfig {
+    /// Event the hook waits for
+    pub on: HookTrigger,
+    /// Percentages a milestone hook fires at
+    #[serde(default)]
+    pub at: Vec<u16>,
+    /// Overtime before an overtime hook fires, such as `10m`
+    #[serde(default)]
+    pub after: Option<String>,
+    /// Command and arguments to run
+    #[serde(default)]
+    pub run: Vec<String>,
+    /// URL to POST the event to as JSON
+    #[serde(default)]
+    pub post: Option<String>,
+    /// Where the bearer token sent with `post` is kept
+    #[serde(default)]
+    pub token: Option<SecretRef>,
+    /// Desktop notification text
+    #[serde(default)]
+    pub notify: Option<String>,
+    /// Text read aloud
+    #[serde(default)]
+    pub say: Option<String>,
+    /// Ring the terminal bell
+    #[serde(default)]
+    pub bell: bool,
+}
+
+/// Something that happened to a window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    /// The window passed this percentage
+    Milestone(u16),
+    /// The window reached its end
+    Complete,
+    /// The window ran past its end
+    Overtime,
+}
+
+impl HookEvent {
+    /// Name given to commands and webhooks, such as `milestone-50`
+    pub fn name(self) -> String {
+        match self {
+            HookEvent::Milestone(percent) => format!("milestone-{percent}"),
+            HookEvent::Complete => "complete".to_string(),
+            HookEvent::Overtime => "overtime".to_string(),
+        }
+    }
+}
+
+/// What a hook does when its event fires
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HookAction {
+    /// Run a command, given as a program and templated arguments
+    Run(Vec<String>),
+    /// POST the event as JSON, with a bearer token read when it is sent
+    #[cfg(feature = "net")]
+    Post(HttpUrl, Option<SecretRef>),
+    /// Show a desktop notification with templated text
+    Notify(String),
+    /// Read templated text aloud
+    Say(String),
+    /// Ring the terminal bell
+    Bell,
+}
+
+impl HookAction {
+    /// Channel name used in warnings
+    pub fn channel(&self) -> &'static str {
+        match self {
+            HookAction::Run(_) => "run",
+            #[cfg(feature = "net")]
+            HookAction::Post(..) => "post",
+            HookAction::Notify(_) => "notify",
+            HookAction::Say(_) => "say",
+            HookAction::Bell => "bell",
+        }
+    }
+}
+
+/// Condition a hook checks on every update
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Condition {
+    Milestones(Vec<u16>),
+    Complete,
+    Overtime(Duration),
+}
+
+/// A validated hook
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Hook {
+    condition: Condition,
+    actions: Vec<HookAction>,
+}
+
+impl Hook {
+    fn from_config(config: &HookConfig) -> PbResult<Self> {
+        let invalid = |reason: &str| PbError::invalid_argument(OPTION_NAME, reason);
+
+        let mut actions = Vec::new();
+        if !config.run.is_empty() {
+            actions.push(HookAction::Run(config.run.clone()));
+        }
+        if let Some(url) = &config.post {
+            #[cfg(feature = "net")]
+            {
+                let url: HttpUrl = url.parse()?;
+                if config.token.is_some() && !url.is_loopback() {
+                    return Err(invalid(&format!(
+                        "`token` would be sent in the clear to {url}; tokens only go to localhost"
+                    )));
+                }
+                actions.push(HookAction::Post(url, config.token.clone()));
+            }
+            #[cfg(not(feature = "net"))]
+            return Err(invalid(&format!(
+                "cannot post to {url}: pmon was built without the `net` feature"
+            )));
+        }
+        if config.token.is_some() && config.post.is_none() {
+            return Err(invalid("`token` only applies to post hooks"));
+        }
+        if let Some(text) = &config.notify {
+            if !cfg!(feature = "notifications") {
+                return Err(invalid(
+                    "pmon was built without the `notifications` feature",
+                ));
+            }
+            actions.push(HookAction::Notify(text.clone()));
+        }
+        if let Some(text) = &config.say {
+            actions.push(HookAction::Say(text.clone()));
+        }
+        if config.bell {
+            actions.push(HookAction::Bell);
+        }
+        if actions.is_empty() {
+            return Err(invalid("a hook needs run, post, notify, say, or bell"));
+        }
+
+        let condition = match config.on {
+            HookTrigger::Milestone => {
+                if config.after.is_some() {
+                    return Err(invalid("`after` only applies to overtime hooks"));
+                }
+                let mut at = if config.at.is_empty() {
+                    DEFAULT_MILESTONES.to_vec()
+                } else {
+                    config.at.clone()
+                };
+                if at.iter().any(|&percent| percent == 0 || percent > 100) {
+                    return Err(invalid("milestones must be between 1 and 100 percent"));
+                }
+                at.sort_unstable();
+                at.dedup();
+                Condition::Milestones(at)
+            }
+            trigger => {
+                if !config.at.is_empty() {
+                    return Err(invalid("`at` only applies to milestone hooks"));
+                }
+                match (trigger, &config.after) {
+                    (HookTrigger::Overtime, after) => Condition::Overtime(match after {
+                        Some(after) => parse_lead_time(after)?,
+                        None => Duration::zero(),
+                    }),
+                    (_, Some(_)) => {
+                        return Err(invalid("`after` only applies to overtime hooks"));
+                    }
+                    (_, None) => Condition::Complete,
+                }
+            }
+        };
+        Ok(Self { condition, actions })
+    }
+
+    /// Events of this hook that `state` has reached, oldest first
+    fn reached(&self, state: &ProgressState) -> Vec<HookEvent> {
+        match &self.condition {
+            Condition::Milestones(at) => at
+                .iter()
+                .filter(|&&percent| state.percentage >= f64::from(percent))
+                .map(|&percent| HookEvent::Milestone(percent))
+                .collect(),
+            Condition::Complete if state.is_complete() => vec![HookEvent::Complete],
+            Condition::Overtime(after)
+                if state.overtime() > Duration::zero() && state.overtime() >= *after =>
+            {
+                vec![HookEvent::Overtime]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// An action due to run for an event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Firing {
+    /// Event that fired
+    pub event: HookEvent,
+    /// Action to run for it
+    pub action: HookAction,
+}
+
+/// Timer details filled into hook templates
+#[derive(Debug, Clone, Copy)]
+pub struct HookContext<'a> {
+    /// Label of the window
+    pub label: Option<&'a str>,
+    /// Name of the timer, when started with `--name`
+    pub name: Option<&'a str>,
+    /// Format for the durations
+    pub format: DurationFormat,
+}
+
+impl Firing {
+    /// Placeholder names and values for this firing
+    fn variables(
+        &self,
+        state: &ProgressState,
+        context: &HookContext,
+    ) -> [(&'static str, String); 8] {
+        [
+            ("event", self.event.name()),
+            ("label", context.label.unwrap_or_default().to_string()),
+            ("name", context.name.unwrap_or_default().to_string()),
+            ("percent", format_percentage(state.percentage)),
+            ("elapsed", context.format.elapsed(state.elapsed())),
+            ("remaining", context.format.remaining(state.remaining())),
+            ("overtime", context.format.elapsed(state.overtime())),
+            ("end", state.end.format("%Y-%m-%d %H:%M").to_string()),
+        ]
+    }
+
+    /// Fill the placeholders in `template`
+    pub fn render(&self, template: &str, state: &ProgressState, context: &HookContext) -> String {
+        self.variables(state, context)
+            .iter()
+            .fold(template.to_string(), |text, (key, value)| {
+                text.replace(&format!("{{{key}}}"), value)
+            })
+    }
+
+    /// The webhook request of a `post` action, for sending or queueing
+    ///
+    /// The request names where its token is kept rather than holding the
+    /// token, so a queued request never saves the secret.
+    #[cfg(feature = "net")]
+    pub fn delivery(
+        &self,
+        state: &ProgressState,
+        context: &HookContext,
+        queued_at: NaiveDateTime,
+    ) -> Option<PendingDelivery> {
+        let HookAction::Post(url, token) = &self.action else {
+            return None;
+        };
+        let body: serde_json::Map<String, serde_json::Value> = self
+            .variables(state, context)
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value.into()))
+            .collect();
+        Some(PendingDelivery {
+            url: url.to_string(),
+            body: serde_json::Value::Object(body).to_string(),
+            token: token.as_ref().map(SecretRef::to_string),
+            queued_at,
+            attempts: 0,
+        })
+    }
+
+    /// Run the action, writing the bell to `out`
+    ///
+    /// Commands are started in the background; only a failure to start
+    /// them is reported.
+    pub fn execute(
+        &self,
+        state: &ProgressState,
+        context: &HookContext,
+        out: &mut dyn Write,
+    ) -> PbResult<()> {
+        match &self.action {
+            HookAction::Run(command) => {
+                let args: Vec<String> = command
+                    .iter()
+                    .map(|arg| self.render(arg, state, context))
+                    .collect();
+                let Some((program, args)) = args.split_first() else {
+                    return Err(PbError::delivery("command", "no program to run"));
+                };
+                let mut child = Command::new(program);
+                child.args(args).stdin(Stdio::null());
+                for (key, value) in self.variables(state, context) {
+                    child.env(format!("PMON_{}", key.to_uppercase()), value);
+                }
+                let mut child = child
+                    .spawn()
+                    .map_err(|e| PbError::delivery(program, e.to_string()))?;
+                // Reap the command when it exits without holding up the timer
+                std::thread::spawn(move || child.wait());
+                Ok(())
+            }
+            #[cfg(feature = "net")]
+            HookAction::Post(..) => match self.delivery(state, context, state.current) {
+                Some(delivery) => deliver(&delivery, &HttpClient::default()),
+                None => Ok(()),
+            },
+            HookAction::Notify(text) => {
+                let title = context.label.unwrap_or("pmon");
+                notify(title, &self.render(text, state, context))
+            }
+            HookAction::Say(text) => speak(&self.render(text, state, context)),
+            HookAction::Bell => write!(out, "\x07")
+                .and_then(|()| out.flush())
+                .map_err(|e| PbError::delivery("terminal bell", e.to_string())),
+        }
+    }
+}
+
+/// Show a desktop notification with the platform's notifier
+#[cfg(feature = "notifications")]
+fn notify(title: &str, text: &str) -> PbResult<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+        let mut command = Command::new("osascript");
+        command.arg("-e").arg(format!(
+            "display notification {} with title {}",
+            quote(text),
+            quote(title)
+        ));
+        command
+    } else {
+        let mut command = Command::new("notify-send");
+        command.args(["--app-name=pmon", "--", title, text]);
+        command
+    };
+    let program = command.get_program().to_string_lossy().into_owned();
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .spawn()
+        .map_err(|e| PbError::delivery(&program, e.to_string()))?;
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+/// Desktop notifications are left out of this build
+#[cfg(not(feature = "notifications"))]
+fn notify(_title: &str, _text: &str) -> PbResult<()> {
+    Err(PbError::delivery(
+        "desktop notification",
+        "pmon was built without the `notifications` feature",
+    ))
+}
+
+/// Speech synthesizers tried in order outside macOS and Windows
+const SPEECH_PROGRAMS: [&str; 3] = ["espeak-ng", "espeak", "spd-say"];
+
+/// Read `text` aloud with the platform's speech synthesizer
+fn speak(text: &str) -> PbResult<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("say");
+        command.arg(text);
+        command
+    } else if cfg!(windows) {
+        // The text is passed through the environment to spare quoting it
+        let mut command = Command::new("powershell");
+        command
+            .args([
+                "-NoProfile",
+                "-Command",
+                "Add-Type -AssemblyName System.Speech; \
+                 (New-Object System.Speech.Synthesis.SpeechSynthesizer).Speak($env:PMON_SAY)",
+            ])
+            .env("PMON_SAY", text);
+        command
+    } else {
+        let program = SPEECH_PROGRAMS
+            .into_iter()
+            .find(|program| find_program(program).is_some())
+            .ok_or_else(|| {
+                PbError::delivery("speech", "install espeak-ng, espeak, or speech-dispatcher")
+            })?;
+        let mut command = Command::new(program);
+        command.arg(text);
+        command
+    };
+    let program = command.get_program().to_string_lossy().into_owned();
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .spawn()
+        .map_err(|e| PbError::delivery(&program, e.to_string()))?;
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+/// The configured hooks and which of their events have fired in the current window
+#[derive(Debug, Clone, Default)]
+pub struct HookRegistry {
+    hooks: Vec<Hook>,
+    fired: Vec<Vec<HookEvent>>,
+    started: bool,
+}
+
+impl HookRegistry {
+    /// Validate the `[[hooks]]` tables of the configuration
+    pub fn from_config(configs: &[HookConfig]) -> PbResult<Self> {
+        let hooks = configs
+            .iter()
+            .map(Hook::from_config)
+            .collect::<PbResult<Vec<_>>>()?;
+        Ok(Self {
+            fired: vec![Vec::new(); hooks.len()],
+            hooks,
+            started: false,
+        })
+    }
+
+    /// Whether no hooks are configured
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    /// Forget fired events, for a new window
+    pub fn reset(&mut self) {
+        self.fired.iter_mut().for_each(Vec::clear);
+        self.started = false;
+    }
+
+    /// Actions due for events `state` has reached since the last update
+    ///
+    /// Milestones already passed on the first update of a window are marked
+    /// as fired without running anything.
+    pub fn update(&mut self, state: &ProgressState) -> Vec<Firing> {
+        let is_first = !self.started;
+        self.started = true;
+        let mut firings = Vec::new();
+        for (hook, fired) in self.hooks.iter().zip(&mut self.fired) {
+            for event in hook.reached(state) {
+                if fired.contains(&event) {
+                    continue;
+                }
+                fired.push(event);
+                if is_first && matches!(event, HookEvent::Milestone(_)) {
+                    continue;
+                }
+                firings.extend(hook.actions.iter().map(|action| Firing {
+                    event,
+                    action: action.clone(),
+                }));
+            }
+        }
+        firings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::progress_bar::DurationStyle;
+    use chrono::NaiveDateTime;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn state_at(current: &str) -> ProgressState {
+        ProgressState::new(
+            create_test_datetime("2025-01-27 09:00:00"),
+            create_test_datetime("2025-01-27 10:00:00"),
+            create_test_datetime(current),
+        )
+    }
+
+    fn hooks(toml: &str) -> PbResult<HookRegistry> {
+        #[derive(Deserialize)]
+        struct File {
+            hooks: Vec<HookConfig>,
+        }
+        let file: File = toml::from_str(toml).unwrap();
+        HookRegistry::from_config(&file.hooks)
+    }
+
+    fn events(firings: &[Firing]) -> Vec<HookEvent> {
+        firings.iter().map(|firing| firing.event).collect()
+    }
+
+    #[test]
+    fn test_milestones_fire_once_in_order() {
+        let mut registry =
+            hooks("[[hooks]]\non = \"milestone\"\nat = [90, 50]\nbell = true\n").unwrap();
+        assert!(registry.update(&state_at("2025-01-27 09:10:00")).is_empty());
+        assert_eq!(
+            events(&registry.update(&state_at("2025-01-27 09:30:00"))),
+            [HookEvent::Milestone(50)]
+        );
+        assert!(registry.update(&state_at("2025-01-27 09:31:00")).is_empty());
+        assert_eq!(
+            events(&registry.update(&state_at("2025-01-27 09:55:00"))),
+            [HookEvent::Milestone(90)]
+        );
+    }
+
+    #[test]
+    fn test_milestones_passed_before_start_are_skipped() {
+        let mut registry = hooks("[[hooks]]\non = \"milestone\"\nbell = true\n").unwrap();
+        assert!(registry.update(&state_at("2025-01-27 09:40:00")).is_empty());
+        assert_eq!(
+            events(&registry.update(&state_at("2025-01-27 09:45:00"))),
+            [HookEvent::Milestone(75)]
+        );
+
+        registry.reset();
+        assert!(registry.update(&state_at("2025-01-27 09:50:00")).is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "notifications")]
+    fn test_complete_and_overtime() {
+        let mut registry = hooks(
+            "[[hooks]]\non = \"complete\"\nbell = true\nnotify = \"done\"\n\n[[hooks]]\non = \"overtime\"\nafter = \"10m\"\nbell = true\n",
+        )
+        .unwrap();
+        assert!(registry.update(&state_at("2025-01-27 09:59:00")).is_empty());
+
+        let firings = registry.update(&state_at("2025-01-27 10:00:00"));
+        assert_eq!(events(&firings), [HookEvent::Complete, HookEvent::Complete]);
+        assert_eq!(firings[0].action, HookAction::Notify("done".to_string()));
+        assert_eq!(firings[1].action, HookAction::Bell);
+
+        assert!(registry.update(&state_at("2025-01-27 10:05:00")).is_empty());
+        assert_eq!(
+            events(&registry.update(&state_at("2025-01-27 10:10:00"))),
+            [HookEvent::Overtime]
+        );
+        assert!(registry.update(&state_at("2025-01-27 10:20:00")).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_hooks_are_rejected() {
+        let cases = [
+            "[[hooks]]\non = \"complete\"\n", // no action
+            "[[hooks]]\non = \"milestone\"\nat = [120]\nbell = true\n", // out of range
+            "[[hooks]]\non = \"complete\"\nat = [50]\nbell = true\n", // at on complete
+            "[[hooks]]\non = \"milestone\"\nafter = \"5m\"\nbell = true\n", // after on milestone
+            "[[hooks]]\non = \"overtime\"\nafter = \"soon\"\nbell = true\n", // bad duration
+            "[[hooks]]\non = \"complete\"\npost = \"https://example.com\"\n", // https
+            "[[hooks]]\non = \"complete\"\nbell = true\ntoken = \"env:T\"\n", // token without post
+            #[cfg(feature = "net")]
+            "[[hooks]]\non = \"complete\"\npost = \"http://relay.local\"\ntoken = \"env:T\"\n", // token off this machine
+        ];
+        for toml in cases {
+            assert!(hooks(toml).is_err(), "Expected {toml:?} to be rejected");
+        }
+    }
+
+    #[test]
+    #[cfg(not(all(feature = "net", feature = "notifications")))]
+    fn test_actions_left_out_of_the_build_are_rejected() {
+        let cases = [
+            #[cfg(not(feature = "net"))]
+            "[[hooks]]\non = \"complete\"\npost = \"http://localhost:8080\"\n",
+            #[cfg(not(feature = "notifications"))]
+            "[[hooks]]\non = \"complete\"\nnotify = \"done\"\n",
+        ];
+        for toml in cases {
+            let error = hooks(toml).unwrap_err().to_string();
+            assert!(error.contains("feature"), "{error}");
+        }
+    }
+
+    #[test]
+    fn test_say_hooks() {
+        let mut registry =
+            hooks("[[hooks]]\non = \"milestone\"\nat = [90]\nsay = \"{remaining} left\"\n")
+                .unwrap();
+        registry.update(&state_at("2025-01-27 09:10:00"));
+        let firings = registry.update(&state_at("2025-01-27 09:55:00"));
+        assert_eq!(
+            firings,
+            [Firing {
+                event: HookEvent::Milestone(90),
+                action: HookAction::Say("{remaining} left".to_string()),
+            }]
+        );
+        assert_eq!(firings[0].action.channel(), "say");
+    }
+
+    #[test]
+    fn test_templates_and_bell() {
+        let firing = Firing {
+            event: HookEvent::Milestone(75),
+            action: HookAction::Bell,
+        };
+        let context = HookContext {
+            label: Some("Review"),
+            name: None,
+            format: DurationStyle::Compact.into(),
+        };
+        let state = state_at("2025-01-27 09:45:00");
+        assert_eq!(
+            firing.render(
+                "{label}: {percent} ({event}), {remaining} left{name}",
+                &state,
+                &context
+            ),
+            "Review: 75.0% (milestone-75), 15m left"
+        );
+
+        let mut out = Vec::new();
+        firing.execute(&state, &context, &mut out).unwrap();
+        assert_eq!(out, b"\x07");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_action_receives_environment() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("event.txt");
+        let firing = Firing {
+            event: HookEvent::Complete,
+            action: HookAction::Run(vec![
+                "sh".to_string(),
+                "-c".to_string(),
+                format!("echo \"$PMON_EVENT {{label}}\" > {}", path.display()),
+            ]),
+        };
+        let context = HookContext {
+            label: Some("Retro"),
+            name: Some("retro"),
+            format: DurationFormat::default(),
+        };
+        let mut out = Vec::new();
+        firing
+            .execute(&state_at("2025-01-27 10:00:00"), &context, &mut out)
+            .unwrap();
+
+        let started = std::time::Instant::now();
+        while !path.exists() && started.elapsed() < std::time::Duration::from_secs(5) {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "complete Retro\n");
+    }
+
+    #[test]
+    fn test_missing_command_is_reported() {
+        let context = HookContext {
+            label: None,
+            name: None,
+            format: DurationFormat::default(),
+        };
+        for command in [
+            vec!["pmon-hook-that-does-not-exist".to_string()],
+            Vec::new(),
+        ] {
+            let firing = Firing {
+                event: HookEvent::Complete,
+                action: HookAction::Run(command),
+            };
+            let result =
+                firing.execute(&state_at("2025-01-27 10:00:00"), &context, &mut Vec::new());
+            assert!(matches!(result, Err(PbError::Delivery { .. })));
+        }
+    }
+}
@@ -0,0 +1,492 @@
+//! Minimal HTTP client for integrations
+//!
+//! Hooks and telemetry deliver small JSON documents to services on the local
+//! network, such as a webhook relay or an OpenTelemetry collector. Plain
+//! HTTP/1.1 over a TCP socket is enough for that, so pmon speaks it directly
+//! instead of pulling in a TLS stack and an async runtime. Only `http://`
+//! URLs are accepted, and bearer tokens are only sent to this machine's
+//! loopback addresses, where nobody on the network can read them.
+//!
+//! Every integration sends through [`HttpClient`], which retries failures
+//! that may pass, such as a relay restarting or rate limiting, with
+//! exponential backoff and jitter.
+
+use crate::error::{PbError, PbResult};
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::debug;
+
+/// Port used when a URL does not give one
+pub const DEFAULT_PORT: u16 = 80;
+
+/// How long to wait for the server before giving up on a request
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// An `http://host[:port][/path]` URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpUrl {
+    host: String,
+    port: u16,
+    path: String,
+}
+
+impl HttpUrl {
+    /// Parse a URL for `option`, using `default_port` when none is given
+    ///
+    /// A trailing `/` is dropped from the path, so paths can be appended
+    /// with [`HttpUrl::join`].
+    pub fn parse_with_default_port(input: &str, default_port: u16, option: &str) -> PbResult<Self> {
+        let invalid = || PbError::invalid_argument(option, input);
+        let rest = input.trim().strip_prefix("http://").ok_or_else(invalid)?;
+        let (authority, path) = match rest.find('/') {
+            Some(index) => rest.split_at(index),
+            None => (rest, ""),
+        };
+        // IPv6 addresses are bracketed, as in `http://[::1]:4318`
+        let (host, port) = match authority.strip_prefix('[') {
+            Some(bracketed) => {
+                let (host, port) = bracketed.split_once(']').ok_or_else(invalid)?;
+                (host, port.strip_prefix(':'))
+            }
+            None => match authority.split_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (authority, None),
+            },
+        };
+        let port = match port {
+            Some(port) => port.parse().map_err(|_| invalid())?,
+            None => default_port,
+        };
+        if host.is_empty() || port == 0 || path.contains(char::is_whitespace) {
+            return Err(invalid());
+        }
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            path: path.trim_end_matches('/').to_string(),
+        })
+    }
+
+    /// This URL with `path` appended, such as `/v1/traces`
+    pub fn join(&self, path: &str) -> Self {
+        Self {
+            path: format!("{}{path}", self.path),
+            ..self.clone()
+        }
+    }
+
+    /// Whether the URL points at this machine, such as `localhost` or `127.0.0.1`
+    pub fn is_loopback(&self) -> bool {
+        self.host.eq_ignore_ascii_case("localhost")
+            || self
+                .host
+                .parse::<std::net::IpAddr>()
+                .is_ok_and(|ip| ip.is_loopback())
+    }
+
+    /// Host and port as written in a URL
+    fn authority(&self) -> String {
+        if self.host.contains(':') {
+            format!("[{}]:{}", self.host, self.port)
+        } else {
+            format!("{}:{}", self.host, self.port)
+        }
+    }
+
+    /// Path sent in the request line
+    fn request_path(&self) -> &str {
+        if self.path.is_empty() {
+            "/"
+        } else {
+            &self.path
+        }
+    }
+}
+
+impl FromStr for HttpUrl {
+    type Err = PbError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Self::parse_with_default_port(input, DEFAULT_PORT, "URL")
+    }
+}
+
+impl fmt::Display for HttpUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "http://{}{}", self.authority(), self.path)
+    }
+}
+
+/// How a client retries requests that failed for a reason that may pass
+///
+/// The n-th retry waits `base_delay * 2^(n-1)`, capped at `max_delay`, with
+/// random jitter taking up to half of that away so that many pmon
+/// processes failing together do not retry in lockstep. A `Retry-After`
+/// answer is honored up to `max_delay`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Requests sent at most, including the first
+    pub attempts: u32,
+    /// Wait before the first retry
+    pub base_delay: Duration,
+    /// Longest wait between two requests
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(2),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Wait before retry number `retry` (from 1), given `jitter` in `[0, 1)`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pmon::http::RetryPolicy;
+    /// use std::time::Duration;
+    ///
+    /// let policy = RetryPolicy::default();
+    /// assert_eq!(policy.delay(1, 0.0), Duration::from_millis(250));
+    /// assert_eq!(policy.delay(2, 0.5), Duration::from_millis(375));
+    /// assert_eq!(policy.delay(10, 0.0), Duration::from_secs(2));
+    /// ```
+    pub fn delay(&self, retry: u32, jitter: f64) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        let delay = self.base_delay.saturating_mul(factor).min(self.max_delay);
+        delay.mul_f64(1.0 - jitter.clamp(0.0, 1.0) / 2.0)
+    }
+}
+
+/// The HTTP client every integration sends through
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct HttpClient {
+    /// When and how often failed requests are sent again
+    pub retry: RetryPolicy,
+}
+
+/// How one request went
+enum Attempt {
+    /// The server accepted the request
+    Delivered,
+    /// The request may succeed later, after the server's `Retry-After` if any
+    Retry(String, Option<Duration>),
+    /// Sending it again would not help
+    Failed(String),
+}
+
+impl HttpClient {
+    /// A client retrying with `retry`
+    pub fn new(retry: RetryPolicy) -> Self {
+        Self { retry }
+    }
+
+    /// POST a JSON document to `url`, succeeding on any 2xx answer
+    ///
+    /// Network errors, timeouts, and `408`, `429`, and `5xx` answers are
+    /// retried with backoff; other answers fail at once.
+    pub fn post_json(&self, url: &HttpUrl, body: &str) -> PbResult<()> {
+        self.post_json_authorized(url, body, None)
+    }
+
+    /// POST a JSON document to `url` with an optional bearer `token`
+    ///
+    /// A token is refused for URLs off this machine, since plain HTTP would
+    /// show it to anyone on the way.
+    pub fn post_json_authorized(
+        &self,
+        url: &HttpUrl,
+        body: &str,
+        token: Option<&str>,
+    ) -> PbResult<()> {
+        if token.is_some() && !url.is_loopback() {
+            return Err(PbError::delivery(
+                url.to_string(),
+                "refusing to send a token over plain HTTP to another machine",
+            ));
+        }
+        let attempts = self.retry.attempts.max(1);
+        for attempt in 1..=attempts {
+            let (reason, retry_after) = match send(url, body, token) {
+                Attempt::Delivered => return Ok(()),
+                Attempt::Failed(reason) => return Err(PbError::delivery(url.to_string(), reason)),
+                Attempt::Retry(reason, retry_after) => (reason, retry_after),
+            };
+            if attempt == attempts {
+                let reason = format!("{reason} (after {attempts} attempts)");
+                return Err(PbError::delivery(url.to_string(), reason));
+            }
+            let delay = retry_after
+                .map(|wait| wait.min(self.retry.max_delay))
+                .unwrap_or_else(|| self.retry.delay(attempt, jitter()));
+            debug!(%url, attempt, %reason, delay_ms = delay.as_millis() as u64, "retrying request");
+            std::thread::sleep(delay);
+        }
+        Ok(())
+    }
+}
+
+/// Send one request and read the answer's status and headers
+fn send(url: &HttpUrl, body: &str, token: Option<&str>) -> Attempt {
+    let address = match (url.host.as_str(), url.port).to_socket_addrs() {
+        Ok(mut addresses) => match addresses.next() {
+            Some(address) => address,
+            None => return Attempt::Failed("host not found".to_string()),
+        },
+        // Name lookups fail while offline too
+        Err(e) => return Attempt::Retry(e.to_string(), None),
+    };
+    let exchange = || -> std::io::Result<(String, Option<Duration>)> {
+        let mut stream = TcpStream::connect_timeout(&address, REQUEST_TIMEOUT)?;
+        stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+        stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+        let authorization = token
+            .map(|token| format!("Authorization: Bearer {token}\r\n"))
+            .unwrap_or_default();
+        write!(
+            stream,
+            "POST {} HTTP/1.1\r\nHost: {}\r\n{authorization}Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            url.request_path(),
+            url.authority(),
+            body.len()
+        )?;
+
+        let mut reader = BufReader::new(stream);
+        let mut status_line = String::new();
+        reader.read_line(&mut status_line)?;
+        let mut retry_after = None;
+        let mut header = String::new();
+        while reader.read_line(&mut header)? > 0 && !header.trim().is_empty() {
+            if let Some((name, value)) = header.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("retry-after") {
+                    retry_after = value.trim().parse().ok().map(Duration::from_secs);
+                }
+            }
+            header.clear();
+        }
+        Ok((status_line.trim().to_string(), retry_after))
+    };
+    let (status_line, retry_after) = match exchange() {
+        Ok(answer) => answer,
+        Err(e) => return Attempt::Retry(e.to_string(), None),
+    };
+    let status = status_line.split_whitespace().nth(1).unwrap_or_default();
+    let reason = format!("server answered '{status_line}'");
+    match status.parse::<u16>() {
+        Ok(200..=299) => Attempt::Delivered,
+        Ok(408 | 429 | 500..=599) => Attempt::Retry(reason, retry_after),
+        _ => Attempt::Failed(reason),
+    }
+}
+
+/// A random number in `[0, 1)` for spreading out retries
+fn jitter() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::net::TcpListener;
+
+    /// Accept one request on a local port, answer with `status_line`, and
+    /// return the port and a handle yielding the raw request
+    fn serve_once(status_line: &'static str) -> (u16, std::thread::JoinHandle<String>) {
+        let (port, server) = serve(&[status_line]);
+        (
+            port,
+            std::thread::spawn(move || server.join().unwrap().remove(0)),
+        )
+    }
+
+    /// Answer one request with each of `answers` in turn, returning the
+    /// port and a handle yielding the raw requests
+    fn serve(answers: &[&'static str]) -> (u16, std::thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let answers = answers.to_vec();
+        let server = std::thread::spawn(move || {
+            answers
+                .into_iter()
+                .map(|answer| {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let mut request = Vec::new();
+                    let mut buffer = [0u8; 1024];
+                    while !request.ends_with(b"}") {
+                        let read = stream.read(&mut buffer).unwrap();
+                        request.extend_from_slice(&buffer[..read]);
+                    }
+                    stream.write_all(answer.as_bytes()).unwrap();
+                    String::from_utf8(request).unwrap()
+                })
+                .collect()
+        });
+        (port, server)
+    }
+
+    /// A client that retries without waiting long
+    fn quick_client() -> HttpClient {
+        HttpClient::new(RetryPolicy {
+            attempts: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+        })
+    }
+
+    #[test]
+    fn test_parse_url() {
+        let url: HttpUrl = "http://hooks.local/pmon/".parse().unwrap();
+        assert_eq!(url.to_string(), "http://hooks.local:80/pmon");
+        assert_eq!(
+            url.join("/events").to_string(),
+            "http://hooks.local:80/pmon/events"
+        );
+
+        let url =
+            HttpUrl::parse_with_default_port("http://[::1]", 4318, "--otel-endpoint").unwrap();
+        assert_eq!(url.to_string(), "http://[::1]:4318");
+        assert_eq!(url.request_path(), "/");
+    }
+
+    #[test]
+    fn test_parse_invalid_url() {
+        for input in [
+            "",
+            "localhost:4318",
+            "https://collector",
+            "http://",
+            "http://host:port",
+            "http://host:0",
+            "http://[::1",
+            "http://host/a b",
+        ] {
+            assert!(
+                matches!(
+                    input.parse::<HttpUrl>(),
+                    Err(PbError::InvalidArgument { .. })
+                ),
+                "Expected '{input}' to be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_post_json() {
+        let (port, server) = serve_once("HTTP/1.1 204 No Content\r\n\r\n");
+        let url: HttpUrl = format!("http://127.0.0.1:{port}/hook").parse().unwrap();
+        HttpClient::default()
+            .post_json(&url, "{\"event\":\"complete\"}")
+            .unwrap();
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /hook HTTP/1.1\r\n"));
+        assert!(request.contains(&format!("Host: 127.0.0.1:{port}\r\n")));
+        assert!(request.contains("Content-Length: 20\r\n"));
+        assert!(request.ends_with("\r\n\r\n{\"event\":\"complete\"}"));
+        assert!(!request.contains("Authorization"));
+    }
+
+    #[test]
+    fn test_post_json_sends_bearer_token() {
+        let (port, server) = serve_once("HTTP/1.1 200 OK\r\n\r\n");
+        let url: HttpUrl = format!("http://127.0.0.1:{port}").parse().unwrap();
+        HttpClient::default()
+            .post_json_authorized(&url, "{}", Some("t0ken"))
+            .unwrap();
+        let request = server.join().unwrap();
+        assert!(request.contains("\r\nAuthorization: Bearer t0ken\r\n"));
+    }
+
+    #[test]
+    fn test_is_loopback() {
+        for url in [
+            "http://localhost:8080",
+            "http://127.0.0.1",
+            "http://[::1]:4318",
+        ] {
+            assert!(url.parse::<HttpUrl>().unwrap().is_loopback(), "{url}");
+        }
+        for url in [
+            "http://relay.local",
+            "http://192.168.1.10",
+            "http://localhost.evil.com",
+        ] {
+            assert!(!url.parse::<HttpUrl>().unwrap().is_loopback(), "{url}");
+        }
+    }
+
+    #[test]
+    fn test_post_json_refuses_token_for_other_hosts() {
+        let url: HttpUrl = "http://192.0.2.1:9".parse().unwrap();
+        let error = quick_client()
+            .post_json_authorized(&url, "{}", Some("t0ken"))
+            .unwrap_err();
+        assert!(matches!(error, PbError::Delivery { .. }));
+        assert!(error.to_string().contains("refusing to send a token"));
+    }
+
+    #[test]
+    fn test_post_json_reports_error_status() {
+        // Client errors are not retried
+        let (port, server) = serve_once("HTTP/1.1 400 Bad Request\r\n\r\n");
+        let url: HttpUrl = format!("http://127.0.0.1:{port}").parse().unwrap();
+        let error = quick_client().post_json(&url, "{}").unwrap_err();
+        server.join().unwrap();
+        assert!(matches!(error, PbError::Delivery { .. }));
+        assert!(error.to_string().contains("400 Bad Request"));
+    }
+
+    #[test]
+    fn test_post_json_retries_server_errors() {
+        let (port, server) = serve(&[
+            "HTTP/1.1 503 Service Unavailable\r\n\r\n",
+            "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 0\r\n\r\n",
+            "HTTP/1.1 204 No Content\r\n\r\n",
+        ]);
+        let url: HttpUrl = format!("http://127.0.0.1:{port}").parse().unwrap();
+        quick_client().post_json(&url, "{}").unwrap();
+        assert_eq!(server.join().unwrap().len(), 3);
+
+        let (port, server) = serve(&["HTTP/1.1 503 Service Unavailable\r\n\r\n"; 3]);
+        let url: HttpUrl = format!("http://127.0.0.1:{port}").parse().unwrap();
+        let error = quick_client().post_json(&url, "{}").unwrap_err();
+        server.join().unwrap();
+        assert!(
+            error.to_string().contains("503 Service Unavailable")
+                && error.to_string().contains("after 3 attempts"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off_with_jitter() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.delay(1, 0.0), Duration::from_millis(250));
+        assert_eq!(policy.delay(2, 0.0), Duration::from_millis(500));
+        assert_eq!(policy.delay(3, 0.0), Duration::from_secs(1));
+        assert_eq!(policy.delay(4, 0.0), Duration::from_secs(2));
+        assert_eq!(policy.delay(u32::MAX, 0.0), Duration::from_secs(2));
+        assert_eq!(policy.delay(3, 1.0), Duration::from_millis(500));
+        let jitter = jitter();
+        assert!((0.0..1.0).contains(&jitter), "{jitter}");
+    }
+}
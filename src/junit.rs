@@ -0,0 +1,273 @@
+//! JUnit XML reports of timed phases
+//!
+//! `--junit report.xml` writes one test case per phase when pmon exits:
+//! each window chained with `--then`, each checkpoint, and a wrapped
+//! command. A phase passes when it finished on time, fails when it overran
+//! (snoozed or shifted past its planned end, or a wrapped command ran out of
+//! time or failed), and is skipped when the run stopped before it ended. CI
+//! dashboards that understand JUnit can then chart how phases track their
+//! time budgets.
+
+use crate::checkpoint::Checkpoint;
+use crate::progress_bar::format_duration_compact;
+use chrono::{Duration, NaiveDateTime};
+use std::fmt::Write as _;
+use std::io;
+use std::path::Path;
+
+/// Outcome of one phase
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PhaseStatus {
+    /// Finished by its planned end
+    OnTime,
+    /// Did not finish within its budget, with the reason
+    Failed(String),
+    /// Did not run to completion, with the reason
+    Skipped(String),
+}
+
+/// A phase reported as one test case
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhaseResult {
+    /// Test case name
+    pub name: String,
+    /// Time the phase took
+    pub duration: Duration,
+    /// Whether the phase was on time
+    pub status: PhaseStatus,
+}
+
+impl PhaseResult {
+    /// Result of a window planned to end at `planned_end`
+    ///
+    /// `finished` is the window's actual end, after snoozes and shifts, when
+    /// it ran to completion, and `None` when it was stopped at `stopped_at`.
+    pub fn window(
+        name: &str,
+        start: NaiveDateTime,
+        planned_end: NaiveDateTime,
+        finished: Option<NaiveDateTime>,
+        stopped_at: NaiveDateTime,
+    ) -> Self {
+        let (duration, status) = match finished {
+            Some(end) if end > planned_end => (
+                end - start,
+                PhaseStatus::Failed(format!(
+                    "overran its planned end by {}",
+                    format_duration_compact(end - planned_end, false)
+                )),
+            ),
+            Some(end) => (end - start, PhaseStatus::OnTime),
+            None => (
+                (stopped_at - start).max(Duration::zero()),
+                PhaseStatus::Skipped("stopped before the window ended".to_string()),
+            ),
+        };
+        Self {
+            name: name.to_string(),
+            duration,
+            status,
+        }
+    }
+
+    /// Result of a checkpoint in a run that started at `start` and got as
+    /// far as `reached`
+    pub fn checkpoint(
+        checkpoint: &Checkpoint,
+        start: NaiveDateTime,
+        reached: NaiveDateTime,
+    ) -> Self {
+        let status = if checkpoint.at <= reached {
+            PhaseStatus::OnTime
+        } else {
+            PhaseStatus::Skipped("not reached before the run ended".to_string())
+        };
+        Self {
+            name: format!("checkpoint {}", checkpoint.name),
+            duration: (checkpoint.at - start).max(Duration::zero()),
+            status,
+        }
+    }
+}
+
+/// Escape text for an XML attribute
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#10;"),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Seconds with millisecond precision, as JUnit's `time` attribute expects
+fn seconds(duration: Duration) -> String {
+    format!("{:.3}", duration.num_milliseconds() as f64 / 1000.0)
+}
+
+/// Render a JUnit XML report with one test suite named `suite`
+///
+/// # Examples
+///
+/// ```
+/// use chrono::Duration;
+/// use pmon::junit::{render_junit, PhaseResult, PhaseStatus};
+///
+/// let phases = [PhaseResult {
+///     name: "build".to_string(),
+///     duration: Duration::seconds(90),
+///     status: PhaseStatus::OnTime,
+/// }];
+/// let xml = render_junit("release", &phases);
+/// assert!(xml.contains(r#"<testcase name="build" classname="release" time="90.000"/>"#));
+/// ```
+pub fn render_junit(suite: &str, phases: &[PhaseResult]) -> String {
+    let count = |f: fn(&PhaseStatus) -> bool| phases.iter().filter(|p| f(&p.status)).count();
+    let failures = count(|status| matches!(status, PhaseStatus::Failed(_)));
+    let skipped = count(|status| matches!(status, PhaseStatus::Skipped(_)));
+    let total = phases
+        .iter()
+        .fold(Duration::zero(), |total, phase| total + phase.duration);
+    let suite = escape_xml(suite);
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<testsuites name=\"pmon\" tests=\"{}\" failures=\"{failures}\" skipped=\"{skipped}\" time=\"{}\">",
+        phases.len(),
+        seconds(total)
+    );
+    let _ = writeln!(
+        xml,
+        "  <testsuite name=\"{suite}\" tests=\"{}\" failures=\"{failures}\" skipped=\"{skipped}\" time=\"{}\">",
+        phases.len(),
+        seconds(total)
+    );
+    for phase in phases {
+        let opening = format!(
+            "    <testcase name=\"{}\" classname=\"{suite}\" time=\"{}\"",
+            escape_xml(&phase.name),
+            seconds(phase.duration)
+        );
+        let _ = match &phase.status {
+            PhaseStatus::OnTime => writeln!(xml, "{opening}/>"),
+            PhaseStatus::Failed(message) => writeln!(
+                xml,
+                "{opening}>\n      <failure message=\"{}\"/>\n    </testcase>",
+                escape_xml(message)
+            ),
+            PhaseStatus::Skipped(message) => writeln!(
+                xml,
+                "{opening}>\n      <skipped message=\"{}\"/>\n    </testcase>",
+                escape_xml(message)
+            ),
+        };
+    }
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+/// Write a report rendered by [`render_junit`] to `path`
+pub fn write_junit(path: &Path, suite: &str, phases: &[PhaseResult]) -> io::Result<()> {
+    std::fs::write(path, render_junit(suite, phases))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_window_results() {
+        let start = create_test_datetime("2025-01-27 09:00:00");
+        let planned_end = create_test_datetime("2025-01-27 09:25:00");
+        let stopped_at = create_test_datetime("2025-01-27 09:40:00");
+
+        let on_time =
+            PhaseResult::window("focus", start, planned_end, Some(planned_end), stopped_at);
+        assert_eq!(on_time.status, PhaseStatus::OnTime);
+        assert_eq!(on_time.duration, Duration::minutes(25));
+
+        let snoozed = create_test_datetime("2025-01-27 09:35:00");
+        let late = PhaseResult::window("focus", start, planned_end, Some(snoozed), stopped_at);
+        assert_eq!(
+            late.status,
+            PhaseStatus::Failed("overran its planned end by 10m".to_string())
+        );
+        assert_eq!(late.duration, Duration::minutes(35));
+
+        let stopped_at = create_test_datetime("2025-01-27 09:10:00");
+        let stopped = PhaseResult::window("focus", start, planned_end, None, stopped_at);
+        assert!(matches!(stopped.status, PhaseStatus::Skipped(_)));
+        assert_eq!(stopped.duration, Duration::minutes(10));
+    }
+
+    #[test]
+    fn test_checkpoint_results() {
+        let start = create_test_datetime("2025-01-27 09:00:00");
+        let checkpoint = Checkpoint {
+            name: "demo".to_string(),
+            at: create_test_datetime("2025-01-27 09:30:00"),
+        };
+
+        let reached = PhaseResult::checkpoint(&checkpoint, start, checkpoint.at);
+        assert_eq!(reached.name, "checkpoint demo");
+        assert_eq!(reached.status, PhaseStatus::OnTime);
+        assert_eq!(reached.duration, Duration::minutes(30));
+
+        let early = create_test_datetime("2025-01-27 09:29:59");
+        let missed = PhaseResult::checkpoint(&checkpoint, start, early);
+        assert!(matches!(missed.status, PhaseStatus::Skipped(_)));
+    }
+
+    #[test]
+    fn test_render_counts_and_escapes() {
+        let phases = [
+            PhaseResult {
+                name: "plan & <design>".to_string(),
+                duration: Duration::milliseconds(1500),
+                status: PhaseStatus::OnTime,
+            },
+            PhaseResult {
+                name: "build".to_string(),
+                duration: Duration::minutes(2),
+                status: PhaseStatus::Failed("overran \"a lot\"".to_string()),
+            },
+            PhaseResult {
+                name: "ship".to_string(),
+                duration: Duration::zero(),
+                status: PhaseStatus::Skipped("stopped".to_string()),
+            },
+        ];
+        let xml = render_junit("Sprint's end", &phases);
+
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"));
+        assert!(xml.contains(
+            "<testsuite name=\"Sprint&apos;s end\" tests=\"3\" failures=\"1\" skipped=\"1\" time=\"121.500\">"
+        ));
+        assert!(xml.contains("<testcase name=\"plan &amp; &lt;design&gt;\""));
+        assert!(xml.contains("<failure message=\"overran &quot;a lot&quot;\"/>"));
+        assert!(xml.contains("<skipped message=\"stopped\"/>"));
+        assert!(xml.ends_with("</testsuites>\n"));
+    }
+
+    #[test]
+    fn test_write_junit() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.xml");
+        write_junit(&path, "pmon", &[]).unwrap();
+        let xml = std::fs::read_to_string(&path).unwrap();
+        assert!(xml.contains("tests=\"0\" failures=\"0\" skipped=\"0\""));
+    }
+}
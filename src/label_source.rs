@@ -0,0 +1,112 @@
+//! Labels read from the shell context
+//!
+//! Someone who starts many ad-hoc timers rarely stops to type a label.
+//! `--label-from-cmd "git branch --show-current"` runs the command once at
+//! startup and uses the first line it prints, and `--label-from-tmux` uses
+//! the name of the current tmux window. A `--label` given on the command
+//! line wins over both.
+
+use crate::error::{PbError, PbResult};
+use std::process::{Command, Stdio};
+
+/// Where a label is read from when none is given
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LabelSource {
+    /// The output of a shell command
+    Command(String),
+    /// The name of the current tmux window
+    Tmux,
+}
+
+impl LabelSource {
+    /// Read the label now
+    ///
+    /// Fails when the command cannot be run, exits unsuccessfully, or
+    /// prints nothing, and when pmon is not running inside tmux.
+    pub fn read(&self) -> PbResult<String> {
+        let mut command = match self {
+            Self::Command(command) => shell(command),
+            Self::Tmux => {
+                if std::env::var_os("TMUX").is_none() {
+                    return Err(self.error("not running inside tmux"));
+                }
+                let mut command = Command::new("tmux");
+                command.args(["display-message", "-p", "#W"]);
+                command
+            }
+        };
+        let output = command
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .map_err(|e| self.error(e.to_string()))?;
+        if !output.status.success() {
+            return Err(self.error(format!("exited with {}", output.status)));
+        }
+        label_from_output(&String::from_utf8_lossy(&output.stdout))
+            .ok_or_else(|| self.error("printed nothing"))
+    }
+
+    /// An error reading the label from this source
+    fn error(&self, reason: impl Into<String>) -> PbError {
+        let source = match self {
+            Self::Command(command) => format!("`{command}`"),
+            Self::Tmux => "tmux".to_string(),
+        };
+        PbError::label_source(source, reason)
+    }
+}
+
+/// `command` run by the platform's shell
+fn shell(command: &str) -> Command {
+    let (shell, flag) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+    let mut shell = Command::new(shell);
+    shell.args([flag, command]);
+    shell
+}
+
+/// The first line of `output` with text on it, trimmed
+fn label_from_output(output: &str) -> Option<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_label_from_output() {
+        assert_eq!(
+            label_from_output("feature/login\n").as_deref(),
+            Some("feature/login")
+        );
+        assert_eq!(
+            label_from_output("\n  締切 🚀  \nsecond\n").as_deref(),
+            Some("締切 🚀")
+        );
+        assert_eq!(label_from_output(" \n\n"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_command() {
+        let source = LabelSource::Command("printf 'Sprint 42\\nignored'".to_string());
+        assert_eq!(source.read().unwrap(), "Sprint 42");
+
+        let error = LabelSource::Command("exit 3".to_string())
+            .read()
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Could not read the label from `exit 3`"));
+        assert!(LabelSource::Command("true".to_string()).read().is_err());
+    }
+}
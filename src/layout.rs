@@ -0,0 +1,390 @@
+//! Display-width-aware layout helpers for the pmon CLI tool
+//!
+//! Terminal cells do not map one-to-one to bytes or `char`s: CJK characters
+//! and most emoji occupy two columns, while combining marks occupy none.
+//! This module measures and pads text by its rendered column width so that
+//! labels, headers, and multi-bar views stay aligned regardless of script.
+//!
+//! Labels longer than [`DEFAULT_MAX_LABEL_WIDTH`] columns, or the width set
+//! with `--max-label-width`, are cut short with `…` wherever they are drawn.
+//! JSON output and hooks still see the whole label.
+//!
+//! When a terminal is too narrow for a whole line, [`allocate`] shares its
+//! columns out between the parts of the line by priority, so every renderer
+//! gives up the same parts in the same order.
+
+use std::borrow::Cow;
+use std::sync::OnceLock;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Widest a label is drawn, in columns, unless configured otherwise
+pub const DEFAULT_MAX_LABEL_WIDTH: usize = 32;
+
+/// Marks where a label was cut short
+const ELLIPSIS: char = '…';
+
+/// Process-wide label width chosen with `--max-label-width`
+static MAX_LABEL_WIDTH: OnceLock<usize> = OnceLock::new();
+
+/// Return the number of terminal columns `text` occupies when rendered
+///
+/// # Examples
+///
+/// ```
+/// use pmon::layout::display_width;
+///
+/// assert_eq!(display_width("abc"), 3);
+/// assert_eq!(display_width("締切"), 4);
+/// assert_eq!(display_width("🚀"), 2);
+/// ```
+pub fn display_width(text: &str) -> usize {
+    UnicodeWidthStr::width(text)
+}
+
+/// Return the number of columns `text` occupies, leaving out the ANSI escape
+/// sequences that color it
+///
+/// # Examples
+///
+/// ```
+/// use pmon::layout::visible_width;
+///
+/// assert_eq!(visible_width("\x1b[31m110.0%\x1b[0m"), 6);
+/// assert_eq!(visible_width("締切"), 4);
+/// ```
+pub fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '\x1b' {
+            // A control sequence ends with a byte in @..=~
+            if chars.next() == Some('[') {
+                chars.by_ref().find(|ch| ('@'..='~').contains(ch));
+            }
+            continue;
+        }
+        width += ch.width().unwrap_or(0);
+    }
+    width
+}
+
+/// Pad `text` with trailing spaces until it occupies `width` columns
+///
+/// Text that is already `width` columns or wider is returned unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use pmon::layout::pad_end;
+///
+/// assert_eq!(pad_end("ab", 4), "ab  ");
+/// assert_eq!(pad_end("締切", 6), "締切  ");
+/// ```
+pub fn pad_end(text: &str, width: usize) -> String {
+    let padding = width.saturating_sub(display_width(text));
+    format!("{text}{}", " ".repeat(padding))
+}
+
+/// Pad `text` with leading spaces until it occupies `width` columns
+///
+/// Text that is already `width` columns or wider is returned unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use pmon::layout::pad_start;
+///
+/// assert_eq!(pad_start("ab", 4), "  ab");
+/// assert_eq!(pad_start("締切", 6), "  締切");
+/// ```
+pub fn pad_start(text: &str, width: usize) -> String {
+    let padding = width.saturating_sub(display_width(text));
+    format!("{}{text}", " ".repeat(padding))
+}
+
+/// Cut `text` so that it occupies at most `width` columns
+///
+/// Characters are never split: a double-width character that would straddle
+/// the limit is dropped entirely, so the result may be one column narrower
+/// than `width`.
+///
+/// # Examples
+///
+/// ```
+/// use pmon::layout::truncate_to_width;
+///
+/// assert_eq!(truncate_to_width("abcdef", 3), "abc");
+/// assert_eq!(truncate_to_width("締切日", 5), "締切");
+/// ```
+pub fn truncate_to_width(text: &str, width: usize) -> &str {
+    let mut used = 0;
+    for (index, ch) in text.char_indices() {
+        let ch_width = UnicodeWidthChar::width(ch).unwrap_or(0);
+        if used + ch_width > width {
+            return &text[..index];
+        }
+        used += ch_width;
+    }
+    text
+}
+
+/// Shorten `text` to at most `width` columns, ending it with `…` when cut
+///
+/// Text that fits is returned unchanged. Like [`truncate_to_width`], wide
+/// characters are never split.
+///
+/// # Examples
+///
+/// ```
+/// use pmon::layout::ellipsize;
+///
+/// assert_eq!(ellipsize("Sprint 42", 9), "Sprint 42");
+/// assert_eq!(ellipsize("Sprint 42", 6), "Sprin…");
+/// assert_eq!(ellipsize("締切日の確認", 6), "締切…");
+/// ```
+pub fn ellipsize(text: &str, width: usize) -> Cow<'_, str> {
+    if display_width(text) <= width {
+        return Cow::Borrowed(text);
+    }
+    match width {
+        0 => Cow::Borrowed(""),
+        _ => Cow::Owned(format!("{}{ELLIPSIS}", truncate_to_width(text, width - 1))),
+    }
+}
+
+/// Draw labels at most `width` columns wide in this process
+///
+/// Returns `false` if a width was already configured.
+pub fn set_max_label_width(width: usize) -> bool {
+    MAX_LABEL_WIDTH.set(width).is_ok()
+}
+
+/// Widest a label is drawn, in columns
+pub fn max_label_width() -> usize {
+    MAX_LABEL_WIDTH
+        .get()
+        .copied()
+        .unwrap_or(DEFAULT_MAX_LABEL_WIDTH)
+}
+
+/// A label as drawn, shortened to [`max_label_width`] columns
+///
+/// # Examples
+///
+/// ```
+/// use pmon::layout::fit_label;
+///
+/// assert_eq!(fit_label("Daily standup"), "Daily standup");
+/// assert_eq!(fit_label(&"a".repeat(40)), format!("{}…", "a".repeat(31)));
+/// ```
+pub fn fit_label(label: &str) -> Cow<'_, str> {
+    ellipsize(label, max_label_width())
+}
+
+/// Return the widest display width among `items`
+///
+/// Used to compute a shared column width before padding a set of labels.
+pub fn max_display_width<'a>(items: impl IntoIterator<Item = &'a str>) -> usize {
+    items.into_iter().map(display_width).max().unwrap_or(0)
+}
+
+/// Width of a column holding `labels` as [`fit_label`] draws them
+///
+/// # Examples
+///
+/// ```
+/// use pmon::layout::label_column_width;
+///
+/// assert_eq!(label_column_width(["Today", "Overall"]), 7);
+/// assert_eq!(label_column_width(["Today", &"x".repeat(50)]), 32);
+/// ```
+pub fn label_column_width<'a>(labels: impl IntoIterator<Item = &'a str>) -> usize {
+    labels
+        .into_iter()
+        .map(|label| display_width(&fit_label(label)))
+        .max()
+        .unwrap_or(0)
+}
+
+/// Room a part of a line asks for, see [`allocate`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Slot {
+    /// Fewest columns the part can be drawn in
+    pub min: usize,
+    /// Most columns the part can use
+    pub max: usize,
+}
+
+impl Slot {
+    /// A part drawn in `min` to `max` columns
+    pub fn new(min: usize, max: usize) -> Self {
+        Self {
+            min,
+            max: max.max(min),
+        }
+    }
+
+    /// A part drawn whole or not at all
+    pub fn fixed(width: usize) -> Self {
+        Self::new(width, width)
+    }
+}
+
+/// Share `columns` out between `slots`, given from the most to the least
+/// important
+///
+/// Each slot first gets its minimum, in order, while it fits; a slot whose
+/// minimum does not fit gets 0 columns and is left out, though less
+/// important ones may still fit. The columns left over then grow the slots
+/// toward their maximum, again in order. Room that should only be given once
+/// everything else is whole, such as a bar's cells beyond its shortest, goes
+/// last as a slot of its own.
+///
+/// # Examples
+///
+/// ```
+/// use pmon::layout::{allocate, Slot};
+///
+/// // The shortest bar, a label of 8 to 12 columns, the times, and more bar
+/// let slots = [Slot::fixed(10), Slot::new(8, 12), Slot::fixed(30), Slot::new(0, 30)];
+/// assert_eq!(allocate(&slots, 100), [10, 12, 30, 30]);
+/// assert_eq!(allocate(&slots, 60), [10, 12, 30, 8]);
+/// assert_eq!(allocate(&slots, 50), [10, 10, 30, 0]);
+/// // The times no longer fit, leaving their columns to the rest
+/// assert_eq!(allocate(&slots, 40), [10, 12, 0, 18]);
+/// ```
+pub fn allocate(slots: &[Slot], columns: usize) -> Vec<usize> {
+    let mut left = columns;
+    let mut widths: Vec<Option<usize>> = slots
+        .iter()
+        .map(|slot| {
+            (slot.min <= left).then(|| {
+                left -= slot.min;
+                slot.min
+            })
+        })
+        .collect();
+    for (width, slot) in widths.iter_mut().zip(slots) {
+        if let Some(width) = width {
+            let grow = (slot.max - *width).min(left);
+            *width += grow;
+            left -= grow;
+        }
+    }
+    widths.into_iter().map(|width| width.unwrap_or(0)).collect()
+}
+
+/// Lay out `key: value` rows so that all values start in the same column
+///
+/// Keys are padded by display width, so CJK or emoji keys line up with
+/// ASCII ones.
+///
+/// # Examples
+///
+/// ```
+/// use pmon::layout::align_pairs;
+///
+/// let rows = align_pairs(&[("Label", "締切"), ("Remaining", "2h 0m")]);
+/// assert_eq!(rows, vec!["Label:     締切", "Remaining: 2h 0m"]);
+/// ```
+pub fn align_pairs(rows: &[(&str, &str)]) -> Vec<String> {
+    let key_width = max_display_width(rows.iter().map(|(key, _)| *key)) + 1;
+    rows.iter()
+        .map(|(key, value)| format!("{} {value}", pad_end(&format!("{key}:"), key_width)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_width_ascii() {
+        assert_eq!(display_width(""), 0);
+        assert_eq!(display_width("Sprint 42"), 9);
+    }
+
+    #[test]
+    fn test_display_width_wide_characters() {
+        // CJK ideographs and emoji take two columns each
+        assert_eq!(display_width("リリース"), 8);
+        assert_eq!(display_width("発表🚀"), 6);
+        // Full-block bar characters are single width
+        assert_eq!(display_width("█░"), 2);
+    }
+
+    #[test]
+    fn test_display_width_combining_marks() {
+        // "e" followed by a combining acute accent renders as one column
+        assert_eq!(display_width("e\u{0301}"), 1);
+    }
+
+    #[test]
+    fn test_pad_end_and_start() {
+        assert_eq!(pad_end("abc", 5), "abc  ");
+        assert_eq!(pad_start("abc", 5), "  abc");
+        assert_eq!(pad_end("会議", 5), "会議 ");
+        assert_eq!(pad_start("会議", 5), " 会議");
+
+        // Never shrinks text that is already too wide
+        assert_eq!(pad_end("abcdef", 3), "abcdef");
+        assert_eq!(pad_start("会議", 1), "会議");
+    }
+
+    #[test]
+    fn test_truncate_to_width() {
+        assert_eq!(truncate_to_width("abc", 10), "abc");
+        assert_eq!(truncate_to_width("abc", 0), "");
+        assert_eq!(truncate_to_width("会議室", 4), "会議");
+        // A wide character straddling the limit is dropped
+        assert_eq!(truncate_to_width("会議室", 3), "会");
+        assert_eq!(truncate_to_width("a🚀b", 2), "a");
+    }
+
+    #[test]
+    fn test_ellipsize() {
+        assert_eq!(ellipsize("", 0), "");
+        assert_eq!(ellipsize("abc", 0), "");
+        assert_eq!(ellipsize("abc", 1), "…");
+        assert_eq!(ellipsize("abcd", 3), "ab…");
+        // The ellipsis takes one column, so a wide character may not fit
+        assert_eq!(ellipsize("会議室", 4), "会…");
+        assert_eq!(ellipsize("会議室", 5), "会議…");
+        assert_eq!(ellipsize("発表🚀準備", 7), "発表🚀…");
+        assert_eq!(ellipsize("発表🚀準備", 6), "発表…");
+        for width in 0..12 {
+            let text = ellipsize("リリース🚀 v2.1", width);
+            assert!(display_width(&text) <= width, "{width}: {text}");
+        }
+    }
+
+    #[test]
+    fn test_max_display_width() {
+        assert_eq!(max_display_width(Vec::<&str>::new()), 0);
+        assert_eq!(max_display_width(["demo", "レトロ"]), 6);
+    }
+
+    #[test]
+    fn test_align_pairs_mixed_scripts() {
+        let rows = align_pairs(&[("開始", "09:00"), ("End", "17:00")]);
+        assert_eq!(rows, vec!["開始: 09:00", "End:  17:00"]);
+
+        // Values start in the same display column
+        let columns: Vec<usize> = rows
+            .iter()
+            .map(|row| display_width(&row[..row.find(char::is_numeric).unwrap()]))
+            .collect();
+        assert_eq!(columns[0], columns[1]);
+    }
+
+    #[test]
+    fn test_allocate_skips_what_does_not_fit() {
+        let slots = [Slot::fixed(4), Slot::fixed(20), Slot::new(2, 6)];
+        // The second part is left out, but the third still fits after it
+        assert_eq!(allocate(&slots, 10), [4, 0, 6]);
+        assert_eq!(allocate(&slots, 3), [0, 0, 3]);
+        assert_eq!(allocate(&slots, 0), [0, 0, 0]);
+        assert_eq!(allocate(&[], 80), Vec::<usize>::new());
+        assert_eq!(Slot::new(5, 2), Slot::fixed(5));
+    }
+}
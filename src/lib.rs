@@ -3,20 +3,173 @@
 //! This library provides the core functionality for the pb CLI tool,
 //! including time parsing, progress calculation, and error handling.
 
+// Bad input must come back as a `PbError`, never as a panic
+#![cfg_attr(not(test), deny(clippy::unwrap_used, clippy::expect_used))]
+
+pub mod aggregate;
+pub mod alerts;
+pub mod atomic;
+pub mod badge;
+pub mod broadcast;
+pub mod burndown;
+pub mod bus;
+pub mod chain;
+pub mod checkpoint;
 pub mod cli;
+pub mod clipboard;
+pub mod clock_skew;
+pub mod color;
+pub mod compare;
+pub mod completion;
+pub mod config;
+#[cfg(feature = "dbus")]
+pub mod dbus;
+pub mod deadline;
+pub mod definition;
+pub mod diagnostics;
+pub mod doctor;
+#[cfg(feature = "encryption")]
+pub mod encryption;
 pub mod error;
+pub mod event_loop;
+pub mod forecast;
+pub mod gha;
+pub mod hooks;
+#[cfg(feature = "net")]
+pub mod http;
+pub mod junit;
+pub mod label_source;
+pub mod layout;
+pub mod list;
+pub mod migrate;
+pub mod milestones;
+pub mod notes;
+#[cfg(feature = "otel")]
+pub mod otel;
+pub mod outbox;
+pub mod output;
+pub mod pace;
+pub mod plan;
+pub mod power;
 pub mod progress_bar;
+pub mod progress_state;
+pub mod recording;
+#[cfg(feature = "remote")]
+pub mod remote;
+pub mod report;
+pub mod schedule;
+pub mod secrets;
+pub mod self_update;
+pub mod snapshot;
+pub mod solar;
+pub mod status_message;
+pub mod stopwatch;
+pub mod store;
+pub mod sub_timer;
+pub mod tags;
+pub mod terminal;
+pub mod themes;
+pub mod throttle;
 pub mod time_parser;
+pub mod time_source;
+pub mod timezone;
+pub mod today;
+pub mod warnings;
+pub mod watchdog;
+pub mod wrap;
+pub mod xbar;
 
 // Re-export commonly used types
+pub use aggregate::{aggregate_progress, in_group};
+pub use alerts::{AlertConfig, AlertManager};
 pub use anyhow::{Context, Result as AnyhowResult};
-pub use cli::Cli;
+pub use broadcast::{TimerName, TimerPublisher};
+pub use burndown::Burndown;
+pub use bus::{EventBus, ProgressEvent, ProgressUpdate, Subscriber};
+pub use chain::{resolve_chain, Segment, SegmentArgs};
+pub use checkpoint::{render_checkpoints, resolve_checkpoints, Checkpoint, CheckpointSpec};
+pub use cli::{
+    AttachArgs, Cli, Command, CompareArgs, DeadlineArgs, HistoryArgs, ImportPlanArgs, ListArgs,
+    ReportArgs, SecretArgs, SelfUpdateArgs, SnapshotArgs, StatusArgs, StopwatchArgs, ThemesArgs,
+};
+pub use clock_skew::{format_skew_warning, SkewDetector};
+pub use compare::{next_to_expire, sort_by_remaining, TimerEntry};
+pub use completion::{parse_language, render_complete_message, Language};
+pub use config::{Config, StoreBackend, StoreConfig};
+#[cfg(feature = "dbus")]
+pub use dbus::DbusService;
+pub use deadline::{format_deadline_status, DeadlineStatus};
+pub use definition::TimerDefinition;
+pub use diagnostics::init_debug_log;
+#[cfg(feature = "encryption")]
+pub use encryption::{EncryptedStore, Sealer};
 pub use error::{PbError, PbResult};
+pub use event_loop::{EventLoop, Key, KeyAction};
+pub use forecast::{format_colored_forecast, format_forecast, Forecast, Forecaster};
+pub use gha::{workflow_command, AnnotationLevel, GhaReporter};
+pub use hooks::{Firing, HookContext, HookEvent, HookRegistry};
+#[cfg(feature = "net")]
+pub use http::{HttpClient, HttpUrl, RetryPolicy};
+pub use junit::{render_junit, write_junit, PhaseResult, PhaseStatus};
+pub use label_source::LabelSource;
+pub use layout::{
+    align_pairs, ellipsize, fit_label, label_column_width, max_display_width, set_max_label_width,
+    visible_width,
+};
+pub use list::{filter_timers, TimerFilter, TimerState};
+pub use migrate::{migrate, Migration};
+pub use milestones::{Milestone, MilestoneConfig, MilestoneTrigger, Milestones};
+pub use notes::{format_note, render_recent_notes, Note, MAX_NOTE_LENGTH};
+#[cfg(feature = "otel")]
+pub use otel::{OtlpEndpoint, Telemetry};
+pub use outbox::Outbox;
+pub use output::{render_json, JsonUpdate, OutputFormat, OutputTarget, UpdateContext};
+pub use pace::{format_colored_pace, format_pace, Pace, PaceRate};
+pub use power::{BatterySaver, PowerMonitor};
 pub use progress_bar::{
-    calculate_progress, format_duration, render_colored_progress_bar,
-    render_colored_progress_bar_with_time, render_progress_bar, render_progress_bar_with_time,
+    calculate_day_progress, calculate_progress, fit_label_column, format_duration,
+    format_duration_compact, format_duration_with, format_overtime, format_percentage,
+    format_starts_in, format_timestamps, label_indent, quantize_percentage, render,
+    render_bar_state, render_fitted, render_indeterminate_bar, render_labeled_line,
+    render_labeled_lines, render_progress_bar, render_progress_bar_overlay, BarCells, BarState,
+    BarStyle, DurationFormat, DurationStyle, EmojiCells, RenderOptions, Rounding, TimeInfoStyle,
+};
+// Kept for compatibility; new code should use `render`
+#[allow(deprecated)]
+pub use progress_bar::{
+    render_colored_progress_bar, render_colored_progress_bar_overlay,
+    render_colored_progress_bar_overlay_with_time,
+    render_colored_progress_bar_overlay_with_timestamps,
+    render_colored_progress_bar_with_styled_time, render_colored_progress_bar_with_time,
+    render_colored_progress_bar_with_timestamps, render_progress_bar_with_styled_time,
+    render_progress_bar_with_time, render_progress_bar_with_timestamps,
+};
+pub use progress_state::{Granularity, ProgressState};
+#[cfg(feature = "remote")]
+pub use remote::RemoteHost;
+pub use report::{Report, ReportGroup, Since};
+pub use secrets::{delete_keyring_secret, set_keyring_secret, SecretAction, SecretRef};
+pub use solar::{DawnMethod, Location, Preset, SolarConfig, SolarEvent};
+pub use status_message::{select_status_message, StatusRule};
+pub use stopwatch::{format_lap, render_stopwatch_line, Lap, Stopwatch};
+pub use store::{
+    claim_timer, open_store, repair_json_store, OnExists, PendingDelivery, Repair, RunOutcome,
+    RunRecord, Store, TimerRecord, STORE_SCHEMA_VERSION,
 };
+pub use sub_timer::{parse_sub_timer, MAX_SUB_TIMER_INPUT};
+pub use tags::{format_tags, parse_tag};
+pub use terminal::TerminalSession;
+pub use themes::{discover_themes, find_theme, preview_lines, Theme, ThemesAction};
+pub use throttle::{Heartbeat, LineThrottle};
 pub use time_parser::{
-    determine_start_time_for_end, get_current_time, parse_date, parse_datetime,
-    parse_relative_time, parse_time, parse_time_with_base, validate_times,
+    ambiguous_readings, determine_start_time_for_end, get_current_time, parse_date, parse_datetime,
+    parse_relative_time, parse_time, parse_time_detailed, parse_time_strict, parse_time_with_base,
+    validate_times, ParsedTime, RelativeAnchor, TimeFormat,
 };
+pub use time_source::{drift_warning, set_clock_offset, TimeSource};
+pub use timezone::{civil_duration, parse_timezone, set_timezone};
+pub use today::{parse_day_hours, today_window, DayHours};
+pub use warnings::{clamp_interval, window_warnings, WindowWarning};
+pub use watchdog::{MaxRuntime, MAX_RUNTIME_EXIT_CODE};
+pub use wrap::{OnTimeout, Signal, TimeoutEscalation, TimeoutStage};
+pub use xbar::{render_xbar, render_xbar_idle};
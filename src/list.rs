@@ -0,0 +1,343 @@
+//! Listing and filtering of running named timers
+//!
+//! `pmon list` prints one row per named timer running on this machine.
+//! Installations with many timers can narrow the list down with
+//! `--filter FIELD~TEXT` (contains, ignoring case), `--filter FIELD=TEXT`
+//! (exact match) on the `name`, `label`, `message`, or any `tag` of a
+//! timer, and with
+//! `--state running|complete|overtime`. Timers publish their state files
+//! independently, so the filters are applied while the files are read
+//! rather than by a central process.
+
+use crate::compare::TimerEntry;
+use crate::error::PbError;
+use crate::layout::{fit_label, max_display_width, pad_end};
+use crate::progress_bar::format_percentage;
+use crate::tags::format_tags;
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use std::str::FromStr;
+
+/// Option name used in error messages
+const OPTION_NAME: &str = "--filter";
+
+/// Where a timer is in its window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum TimerState {
+    /// The window has not ended yet
+    Running,
+    /// The window has just ended
+    Complete,
+    /// The timer is still shown past the end of its window
+    Overtime,
+}
+
+impl TimerState {
+    /// State of a timer as of its latest update
+    pub fn of(entry: &TimerEntry) -> Self {
+        let state = entry.update.state();
+        if !state.is_complete() {
+            Self::Running
+        } else if state.overtime().is_zero() {
+            Self::Complete
+        } else {
+            Self::Overtime
+        }
+    }
+
+    /// Name shown in listings, as accepted by `--state`
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Complete => "complete",
+            Self::Overtime => "overtime",
+        }
+    }
+}
+
+/// Field of a timer a filter applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterField {
+    /// Name the timer was started with
+    Name,
+    /// Label of the timer, empty when it has none
+    Label,
+    /// Current status message, empty when there is none
+    Message,
+    /// Any one of the timer's tags
+    Tag,
+}
+
+/// How a filter compares the field with its text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterMatch {
+    /// The field contains the text, ignoring case (`~`)
+    Contains,
+    /// The field is exactly the text (`=`)
+    Equals,
+}
+
+/// A `--filter` condition such as `label~release`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimerFilter {
+    /// Field compared
+    pub field: FilterField,
+    /// Comparison used
+    pub matching: FilterMatch,
+    /// Text compared with the field
+    pub text: String,
+}
+
+impl FromStr for TimerFilter {
+    type Err = PbError;
+
+    /// Parse `FIELD~TEXT` or `FIELD=TEXT`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pmon::list::{FilterField, FilterMatch, TimerFilter};
+    ///
+    /// let filter: TimerFilter = "label~release".parse().unwrap();
+    /// assert_eq!(filter.field, FilterField::Label);
+    /// assert_eq!(filter.matching, FilterMatch::Contains);
+    /// assert_eq!(filter.text, "release");
+    /// assert!("owner=me".parse::<TimerFilter>().is_err());
+    /// ```
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let invalid = || PbError::invalid_argument(OPTION_NAME, input);
+        let split = input.find(['~', '=']).ok_or_else(invalid)?;
+        let (field, rest) = input.split_at(split);
+        let matching = if rest.starts_with('~') {
+            FilterMatch::Contains
+        } else {
+            FilterMatch::Equals
+        };
+        let field = match field.trim() {
+            "name" => FilterField::Name,
+            "label" => FilterField::Label,
+            "message" => FilterField::Message,
+            "tag" => FilterField::Tag,
+            _ => return Err(invalid()),
+        };
+        Ok(Self {
+            field,
+            matching,
+            text: rest[1..].to_string(),
+        })
+    }
+}
+
+impl TimerFilter {
+    /// Whether `entry` satisfies the condition
+    pub fn matches(&self, entry: &TimerEntry) -> bool {
+        let field = |value: Option<&str>| self.matches_value(value.unwrap_or_default());
+        match self.field {
+            FilterField::Name => field(Some(entry.name.as_str())),
+            FilterField::Label => field(entry.update.label.as_deref()),
+            FilterField::Message => field(entry.update.message.as_deref()),
+            FilterField::Tag => entry.update.tags.iter().any(|tag| self.matches_value(tag)),
+        }
+    }
+
+    fn matches_value(&self, value: &str) -> bool {
+        match self.matching {
+            FilterMatch::Contains => value.to_lowercase().contains(&self.text.to_lowercase()),
+            FilterMatch::Equals => value == self.text,
+        }
+    }
+}
+
+/// Keep the timers matching every filter and, when `states` is not empty,
+/// in one of `states`
+pub fn filter_timers(
+    entries: Vec<TimerEntry>,
+    filters: &[TimerFilter],
+    states: &[TimerState],
+) -> Vec<TimerEntry> {
+    entries
+        .into_iter()
+        .filter(|entry| filters.iter().all(|filter| filter.matches(entry)))
+        .filter(|entry| states.is_empty() || states.contains(&TimerState::of(entry)))
+        .collect()
+}
+
+/// One aligned row per timer: name, state, progress, end time, label, and
+/// tags
+pub fn render_list(entries: &[TimerEntry]) -> Vec<String> {
+    let name_width = max_display_width(entries.iter().map(|entry| entry.name.as_str()));
+    entries
+        .iter()
+        .map(|entry| {
+            let state = entry.update.state();
+            let row = format!(
+                "{}  {:<8}  {:>6}  ends {}",
+                pad_end(entry.name.as_str(), name_width),
+                TimerState::of(entry).name(),
+                format_percentage(state.percentage),
+                state.end.format("%Y-%m-%d %H:%M"),
+            );
+            let details = [
+                entry
+                    .update
+                    .label
+                    .as_deref()
+                    .map(|label| fit_label(label).into_owned())
+                    .unwrap_or_default(),
+                format_tags(&entry.update.tags),
+            ];
+            details
+                .iter()
+                .filter(|detail| !detail.is_empty())
+                .fold(row, |row, detail| format!("{row}  {detail}"))
+        })
+        .collect()
+}
+
+/// JSON representation of a listed timer
+#[derive(Debug, Serialize)]
+struct JsonTimer<'a> {
+    name: &'a str,
+    state: &'static str,
+    label: Option<&'a str>,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    current: NaiveDateTime,
+    percent: Option<f64>,
+    message: Option<&'a str>,
+    tags: &'a [String],
+}
+
+/// Render a listed timer as a single-line JSON object
+pub fn render_timer_json(entry: &TimerEntry) -> String {
+    let timer = JsonTimer {
+        name: entry.name.as_str(),
+        state: TimerState::of(entry).name(),
+        label: entry.update.label.as_deref(),
+        start: entry.update.start,
+        end: entry.update.end,
+        current: entry.update.current,
+        percent: entry.update.percent,
+        message: entry.update.message.as_deref(),
+        tags: &entry.update.tags,
+    };
+
+    // Serializing plain data with string keys cannot fail
+    serde_json::to_string(&timer).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::{render_json, JsonUpdate, UpdateContext};
+    use crate::progress_state::ProgressState;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn entry(name: &str, label: Option<&str>, end: &str) -> TimerEntry {
+        let state = ProgressState::new(
+            create_test_datetime("2025-01-27 09:00:00"),
+            create_test_datetime(end),
+            create_test_datetime("2025-01-27 09:30:00"),
+        );
+        let context = UpdateContext {
+            label,
+            ..UpdateContext::default()
+        };
+        TimerEntry {
+            name: name.parse().unwrap(),
+            update: JsonUpdate::parse(&render_json(&state, &context)).unwrap(),
+        }
+    }
+
+    fn names(entries: &[TimerEntry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.name.as_str()).collect()
+    }
+
+    fn timers() -> Vec<TimerEntry> {
+        let mut deploy = entry("deploy", Some("release deploy"), "2025-01-27 09:30:00");
+        deploy.update.tags = vec!["team:payments".to_string(), "sev2".to_string()];
+        let mut standup = entry("standup", None, "2025-01-27 09:15:00");
+        standup.update.tags = vec!["team:payments".to_string()];
+        vec![
+            entry("build", Some("Release build"), "2025-01-27 10:00:00"),
+            deploy,
+            standup,
+        ]
+    }
+
+    #[test]
+    fn test_parse_invalid_filter() {
+        for input in ["label", "owner~me", "~release", ""] {
+            assert!(
+                matches!(
+                    input.parse::<TimerFilter>(),
+                    Err(PbError::InvalidArgument { .. })
+                ),
+                "Expected '{input}' to be rejected"
+            );
+        }
+        let filter: TimerFilter = "message=a=b".parse().unwrap();
+        assert_eq!(filter.matching, FilterMatch::Equals);
+        assert_eq!(filter.text, "a=b");
+    }
+
+    #[test]
+    fn test_timer_states() {
+        let states: Vec<TimerState> = timers().iter().map(TimerState::of).collect();
+        assert_eq!(
+            states,
+            [
+                TimerState::Running,
+                TimerState::Complete,
+                TimerState::Overtime
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filter_by_field_and_state() {
+        let release: [TimerFilter; 1] = ["label~RELEASE".parse().unwrap()];
+        assert_eq!(
+            names(&filter_timers(timers(), &release, &[])),
+            ["build", "deploy"]
+        );
+        assert_eq!(
+            names(&filter_timers(timers(), &release, &[TimerState::Running])),
+            ["build"]
+        );
+
+        let sev2: TimerFilter = "tag=sev2".parse().unwrap();
+        assert_eq!(names(&filter_timers(timers(), &[sev2], &[])), ["deploy"]);
+        let team: TimerFilter = "tag~TEAM:".parse().unwrap();
+        assert_eq!(
+            names(&filter_timers(timers(), &[team], &[])),
+            ["deploy", "standup"]
+        );
+
+        let exact: TimerFilter = "name=standup".parse().unwrap();
+        assert_eq!(names(&filter_timers(timers(), &[exact], &[])), ["standup"]);
+        let states = [TimerState::Complete, TimerState::Overtime];
+        assert_eq!(
+            names(&filter_timers(timers(), &[], &states)),
+            ["deploy", "standup"]
+        );
+    }
+
+    #[test]
+    fn test_render_list() {
+        assert_eq!(
+            render_list(&timers()),
+            [
+                "build    running    50.0%  ends 2025-01-27 10:00  Release build",
+                "deploy   complete  100.0%  ends 2025-01-27 09:30  release deploy  [team:payments, sev2]",
+                "standup  overtime  200.0%  ends 2025-01-27 09:15  [team:payments]",
+            ]
+        );
+        let json = render_timer_json(&timers()[0]);
+        assert!(json.starts_with("{\"name\":\"build\",\"state\":\"running\""));
+        assert!(render_timer_json(&timers()[2]).ends_with("\"tags\":[\"team:payments\"]}"));
+    }
+}
@@ -1,11 +1,72 @@
-use anyhow::Result;
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use anyhow::{Context, Result};
+use pmon::badge::{render_badge_svg, render_badge_url};
+use pmon::broadcast::{read_update, running_timers};
+use pmon::chain::split_chain_args;
+use pmon::clipboard;
+use pmon::color::{self, Colorize};
+use pmon::compare::{next_to_expire, render_details, sort_by_remaining, NEXT_MARKER};
+use pmon::config::{config_path, themes_dir};
+use pmon::doctor::{
+    self, check_alternate_screen, check_clock, check_color, check_config, check_notifications,
+    check_unicode, find_program, render_report, TerminalProbe, WIDE_PROBE,
+};
+use pmon::event_loop::{
+    Clock, EventLoop, EventSource, Key, KeyAction, LoopControls, ScaledClock, SystemClock,
+    TerminalEvents, TickSchedule, UNLOCK_PHRASE,
+};
+use pmon::forecast::{format_forecast_bounds, read_work_progress};
+use pmon::list::{render_list, render_timer_json};
+#[cfg(feature = "net")]
+use pmon::outbox::deliver;
+use pmon::plan::read_plan;
+use pmon::recording::{CastRecorder, DEFAULT_SIZE};
+use pmon::schedule::{format_next_session, resolve_schedule};
+use pmon::self_update::{self, fetch_releases, format_check, latest_release, Version};
+use pmon::snapshot::{render_text, write_image};
+use pmon::solar::{self, DawnMethod, Location, SolarConfig, SolarEvent};
+use pmon::terminal::{
+    self, measure_width, move_up, restore_terminal, TerminalSession, CLEAR_BELOW, CLEAR_LINE,
+};
+use pmon::watchdog::POLL_INTERVAL;
+use pmon::wrap::{
+    exit_code, send_signal, TimeoutEscalation, TimeoutStage, SPAWN_FAILURE_EXIT_CODE,
+};
+#[cfg(feature = "dbus")]
+use pmon::DbusService;
+#[cfg(feature = "otel")]
+use pmon::Telemetry;
 use pmon::{
-    calculate_progress, determine_start_time_for_end, get_current_time, parse_time,
-    parse_time_with_base, render_colored_progress_bar_with_time, validate_times, Cli,
+    aggregate_progress, align_pairs, civil_duration, claim_timer, delete_keyring_secret,
+    determine_start_time_for_end, discover_themes, drift_warning, filter_timers, fit_label_column,
+    format_colored_forecast, format_colored_pace, format_deadline_status, format_duration_with,
+    format_lap, format_note, format_skew_warning, format_tags, get_current_time, in_group,
+    init_debug_log, label_column_width, label_indent, open_store, parse_sub_timer, parse_time,
+    parse_time_detailed, parse_time_strict, parse_time_with_base, preview_lines,
+    quantize_percentage, render, render_checkpoints, render_complete_message, render_fitted,
+    render_json, render_labeled_line, render_labeled_lines, render_recent_notes,
+    render_stopwatch_line, render_xbar, render_xbar_idle, repair_json_store, resolve_chain,
+    resolve_checkpoints, select_status_message, set_clock_offset, set_keyring_secret,
+    set_max_label_width, set_timezone, today_window, validate_times, visible_width,
+    window_warnings, write_junit, AlertManager, AttachArgs, BarCells, BarStyle, BatterySaver,
+    Burndown, Checkpoint, Cli, Command, CompareArgs, Config, DeadlineArgs, DeadlineStatus,
+    EventBus, Firing, Forecaster, GhaReporter, Heartbeat, HistoryArgs, HookContext, HookRegistry,
+    LineThrottle, ListArgs, MaxRuntime, Milestones, Note, OnExists, Outbox, OutputFormat,
+    OutputTarget, Pace, ParsedTime, PbError, PbResult, PhaseResult, PhaseStatus, PowerMonitor,
+    ProgressEvent, ProgressState, ProgressUpdate, RenderOptions, Repair, Report, ReportArgs,
+    RunOutcome, RunRecord, SecretAction, SecretArgs, Segment, SelfUpdateArgs, SkewDetector,
+    SnapshotArgs, StatusArgs, Stopwatch, StopwatchArgs, Store, StoreBackend, StoreConfig,
+    Subscriber, ThemesAction, ThemesArgs, TimeFormat, TimeInfoStyle, TimeSource, TimerDefinition,
+    TimerEntry, TimerName, TimerPublisher, TimerRecord, UpdateContext, WindowWarning,
+    MAX_NOTE_LENGTH, MAX_RUNTIME_EXIT_CODE, MAX_SUB_TIMER_INPUT,
 };
-use std::io::{self, Write};
-use std::time::Duration;
+#[cfg(feature = "remote")]
+use pmon::{remote::forwarded_args, JsonUpdate, RemoteHost};
+#[cfg(feature = "net")]
+use pmon::{HttpClient, RetryPolicy};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+use tracing::{debug, warn};
 
 fn main() -> Result<()> {
     // Parse command line arguments
@@ -17,85 +78,709 @@ fn main() -> Result<()> {
         }
     };
 
-    // Parse start and end times
-    let start_time = match cli.start() {
-        Some(start_str) => {
-            // Start time provided - parse it normally
-            match parse_time(start_str) {
+    // Diagnostics cover everything after parsing, including a saved timer's options
+    if cli.debug() {
+        if let Err(e) = init_debug_log(cli.debug_file()) {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
+        debug!(version = env!("CARGO_PKG_VERSION"), "pmon starting");
+    }
+
+    // A saved timer runs with the options it was defined with
+    let is_saved_run = matches!(cli.subcommand(), Some(Command::Run(_)));
+    let cli = match cli.subcommand() {
+        Some(Command::Run(args)) => match saved_timer_cli(&args.name) {
+            Ok(cli) => cli,
+            Err(e) => {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }
+        },
+        _ => cli,
+    };
+
+    // Take the label from the shell context unless one was given
+    let mut cli = cli;
+    if let (None, Some(source)) = (cli.label(), cli.label_source()) {
+        match source.read() {
+            Ok(label) => cli.label = Some(label),
+            Err(e) => eprintln!("Warning: {e}"),
+        }
+    }
+
+    // Read civil times in the requested zone before any times are resolved
+    if let Some(tz) = cli.timezone() {
+        set_timezone(tz);
+    }
+    // Every renderer shortens labels to the same width
+    if let Some(width) = cli.max_label_width() {
+        set_max_label_width(width);
+    }
+
+    // Forgotten instances end once the wall-clock bound has passed
+    if let Some(limit) = cli.max_runtime() {
+        let state_file = cli.name().map(TimerName::state_path);
+        start_watchdog(MaxRuntime::new(limit, SystemTime::now()), state_file);
+    }
+
+    // Progress goes to the chosen target; errors and warnings stay on stderr
+    let target = cli.output().clone();
+    let mut out = match target.open() {
+        Ok(out) => out,
+        Err(e) => {
+            eprintln!("Error opening output {target:?}: {e}");
+            std::process::exit(1);
+        }
+    };
+    if target != OutputTarget::Stdout {
+        // colored decides from stdout, which may not be where the bar goes
+        color::control::set_override(
+            target.is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+        );
+    }
+    // Every frame drawn from here on is copied to the recording
+    if let Some(path) = cli.record() {
+        let size = terminal::size().unwrap_or(DEFAULT_SIZE);
+        out = match CastRecorder::create(path, out, size) {
+            Ok(recorder) => Box::new(recorder),
+            Err(e) => {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }
+        };
+    }
+    let out = out.as_mut();
+
+    // Logs that nobody watches live still need signs of life
+    if let Some(every) = cli.heartbeat() {
+        if !target.is_terminal() {
+            let every = every.to_std().unwrap_or_default();
+            start_heartbeat(Heartbeat::new(every, Instant::now(), cli.duration_style()));
+        }
+    }
+
+    // Use an authoritative clock before any relative times are resolved
+    if cli.time_source() != &TimeSource::Local {
+        let server = cli.time_source().describe();
+        match cli.time_source().measure_offset(TIME_SOURCE_TIMEOUT) {
+            Ok(offset) => {
+                if let Some(warning) = drift_warning(&server, offset) {
+                    eprintln!("Warning: {warning}");
+                }
+                set_clock_offset(offset);
+            }
+            Err(e) => eprintln!("Warning: {e}; using the local clock"),
+        }
+    }
+
+    // Remote hosts compute their own progress; this process only displays it
+    #[cfg(feature = "remote")]
+    if !cli.remote().is_empty() {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        let code = run_remote(cli.remote(), &forwarded_args(&args), &cli, &target, out)?;
+        std::process::exit(code);
+    }
+
+    // Subcommands run their own loops instead of a start/end window
+    match cli.subcommand() {
+        Some(Command::Stopwatch(args)) => {
+            let power = PowerMonitor::new(cli.battery_saver());
+            return run_stopwatch(args, get_current_time(), power, &target, out);
+        }
+        Some(Command::Attach(args)) => {
+            let code = run_attach(args, &cli, &target, out)?;
+            std::process::exit(code);
+        }
+        Some(Command::Compare(args)) => {
+            let code = run_compare(args, &cli, &target, out)?;
+            std::process::exit(code);
+        }
+        Some(Command::List(args)) => {
+            if let Err(e) = run_list(args, &cli, out) {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Command::Status(args)) => {
+            if let Err(e) = run_status(args, &cli, out) {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Command::History(args)) => {
+            if let Err(e) = run_history(args, &cli, out) {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Command::Report(args)) => {
+            if let Err(e) = run_report(args, &cli, out) {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Command::Export(args)) => {
+            if let Err(e) = run_export(&args.name, out) {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Command::Import(args)) => {
+            if let Err(e) = run_import(&args.file, out) {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Command::ImportPlan(args)) => {
+            if let Err(e) = run_import_plan(&args.file, out) {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Command::Deadline(args)) => {
+            let code = run_deadline(args, out)?;
+            std::process::exit(code);
+        }
+        Some(Command::Doctor) => {
+            let code = run_doctor(&cli, &target, out)?;
+            std::process::exit(code);
+        }
+        Some(Command::RepairState) => {
+            if let Err(e) = run_repair_state(out) {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Command::Secret(args)) => {
+            if let Err(e) = run_secret(args, out) {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Command::Snapshot(args)) => {
+            if let Err(e) = run_snapshot(args, &cli, out) {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Command::Themes(args)) => {
+            run_themes(args, out)?;
+            return Ok(());
+        }
+        Some(Command::SelfUpdate(args)) => {
+            if let Err(e) = run_self_update(args, out) {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Command::Run(_)) | None => {}
+    }
+
+    // An unknown end has nothing to measure against, so count up like a stopwatch
+    if cli.end_is_unknown() {
+        let start_time = match cli.start() {
+            Some(start_str) => match parse_time(start_str) {
                 Ok(time) => time,
                 Err(e) => {
                     eprintln!("Error parsing start time '{start_str}': {e}");
                     std::process::exit(1);
                 }
+            },
+            None => get_current_time(),
+        };
+        if cli.verbose() {
+            writeln!(out, "pmon - Progress Monitor Tool")?;
+            let start = start_time.format("%Y-%m-%d %H:%M:%S").to_string();
+            for row in align_pairs(&[("Start time", &start), ("End time", "unknown")]) {
+                writeln!(out, "{row}")?;
             }
+            writeln!(out, "Press Ctrl+C to exit\n")?;
         }
-        None => {
-            // No start time provided - determine it based on end time format
-            determine_start_time_for_end(cli.end())
-        }
-    };
+        let args = StopwatchArgs {
+            label: cli.label().map(str::to_string),
+            interval: cli.interval(),
+            duration_style: cli.duration_style(),
+        };
+        let power = PowerMonitor::new(cli.battery_saver());
+        return run_stopwatch(&args, start_time, power, &target, out);
+    }
 
-    // Parse end time using start time as base for relative calculations
-    let end_time = match parse_time_with_base(cli.end(), Some(start_time)) {
-        Ok(time) => time,
+    // A schedule brings its own sessions in place of --start and --end, and
+    // so does a preset, with the solar window under way or the next one
+    let schedule = match (cli.schedule(), cli.preset()) {
+        (Some(path), _) => Some(load_schedule(path)),
+        (None, Some(_)) => Some(
+            next_preset_window(&cli, get_current_time())
+                .map(|window| vec![window])
+                .map_err(Into::into),
+        ),
+        (None, None) => None,
+    }
+    .map(|sessions| match sessions {
+        Ok(sessions) => sessions,
         Err(e) => {
-            eprintln!("Error parsing end time '{}': {e}", cli.end());
+            eprintln!("Error: {e}");
             std::process::exit(1);
         }
+    });
+
+    // Parse start and end times
+    let start_parsed = match cli.start() {
+        Some(start_str) => {
+            // Start time provided - parse it normally
+            match parse_cli_time(&cli, start_str, None) {
+                Ok(parsed) => Some(parsed),
+                Err(e) => {
+                    eprintln!("Error parsing start time '{start_str}': {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        None => None,
+    };
+    let start_time = match (start_parsed.as_ref(), schedule.as_ref()) {
+        (Some(parsed), _) => parsed.time,
+        (None, Some(sessions)) => sessions[0].start,
+        // No start time provided - determine it based on end time format
+        (None, None) => determine_start_time_for_end(cli.end()),
     };
 
+    // Parse end time, counting relative ends from the start unless
+    // --relative-anchor says otherwise
+    let end_base = cli.relative_anchor().base(start_time);
+    let end_parsed = match schedule {
+        Some(_) => None,
+        None => match parse_cli_time(&cli, cli.end(), end_base) {
+            Ok(parsed) => Some(parsed),
+            Err(e) => {
+                eprintln!("Error parsing end time '{}': {e}", cli.end());
+                std::process::exit(1);
+            }
+        },
+    };
+    let end_time = end_parsed.as_ref().map_or_else(
+        || {
+            schedule
+                .as_ref()
+                .map_or(start_time, |sessions| sessions[0].end)
+        },
+        |parsed| parsed.time,
+    );
+
     // Validate time relationship
     if let Err(e) = validate_times(start_time, end_time) {
         eprintln!("Error: {e}");
         std::process::exit(1);
     }
 
-    // Display header information only if verbose flag is set
-    if cli.verbose() {
-        println!("pmon - Progress Monitor Tool");
-        println!("Start time: {}", start_time.format("%Y-%m-%d %H:%M:%S"));
-        println!("End time: {}", end_time.format("%Y-%m-%d %H:%M:%S"));
-        println!("Update interval: {} seconds", cli.interval());
-        println!("Press Ctrl+C to exit\n");
+    // Suspicious windows run anyway, unless --strict says otherwise, and an
+    // interval longer than the window is clamped unless --no-clamp is given
+    let interval = cli
+        .demo()
+        .is_none()
+        .then(|| chrono::Duration::seconds(cli.interval() as i64));
+    let now = get_current_time();
+    // Only an interval given with --interval is worth a word; the default
+    // is clamped quietly
+    let interval_given = cli.interval_given();
+    for warning in window_warnings(start_time, end_time, interval, !cli.no_clamp(), now) {
+        match warning {
+            // Already fixed, so --strict has nothing to reject
+            WindowWarning::IntervalClamped { clamped, .. } => {
+                cli.interval = Some(clamped.num_seconds().unsigned_abs());
+                if !interval_given {
+                    continue;
+                }
+            }
+            WindowWarning::IntervalExceedsWindow { .. } if !interval_given => continue,
+            _ if cli.strict() => {
+                eprintln!("Error: {warning} (rejected by --strict)");
+                std::process::exit(1);
+            }
+            _ => {}
+        }
+        eprintln!("{}", format!("Warning: {warning}").yellow());
     }
 
-    // Check if we're in a TTY environment and if the environment is truly interactive
-    let is_tty = crossterm::tty::IsTty::is_tty(&std::io::stdout());
-    let is_interactive =
-        is_tty && std::env::var("CI").is_err() && std::env::var("GITHUB_ACTIONS").is_err();
+    // Resolve windows chained with --then, each starting where the previous ended
+    let first = Segment {
+        label: cli.label().map(str::to_string),
+        start: start_time,
+        end: end_time,
+    };
+    let mut chain = match schedule {
+        Some(sessions) => sessions,
+        None => match resolve_chain(first, cli.then()) {
+            Ok(chain) => chain,
+            Err(e) => {
+                eprintln!("Error in chained window: {e}");
+                std::process::exit(1);
+            }
+        },
+    };
 
-    // Enable raw mode for signal detection only if we're in an interactive TTY
-    if is_interactive {
-        crossterm::terminal::enable_raw_mode()?;
+    // Named timers are remembered in the store, along with how each run
+    // ends. One still running under the same name is not overwritten unless
+    // --on-exists or --force says so.
+    let mut whole_window = Segment {
+        label: cli.label().map(str::to_string),
+        start: start_time,
+        end: chain[chain.len() - 1].end,
+    };
+    let mut store = None;
+    if let Some(name) = cli.name().filter(|_| !cli.format().is_snapshot()) {
+        // Timers started with `pmon run` already have their definition saved
+        let definition = if is_saved_run {
+            None
+        } else {
+            let args = split_chain_args(std::env::args()).swap_remove(0);
+            TimerDefinition::from_args(name, args).ok()
+        };
+        store = match save_named_timer(
+            name,
+            &mut whole_window,
+            definition.as_ref(),
+            cli.on_exists(),
+        ) {
+            Ok(store) => store,
+            Err(e) => {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }
+        };
+        // A merged timer keeps the saved start and label
+        let last = chain.len() - 1;
+        chain[0].start = whole_window.start;
+        chain[0].label.clone_from(&whole_window.label);
+        chain[last].end = whole_window.end;
     }
+    let (start_time, end_time) = (chain[0].start, chain[0].end);
+    let overall = Segment {
+        label: Some("Overall".to_string()),
+        start: start_time,
+        end: chain[chain.len() - 1].end,
+    };
+    let overall = cli.overall().then_some(&overall);
 
-    // Ensure terminal cleanup on exit
-    let cleanup = move || {
-        if is_interactive {
-            let _ = crossterm::terminal::disable_raw_mode();
+    // Resolve checkpoints relative to the start time, like the end time
+    let checkpoints = match resolve_checkpoints(cli.checkpoints(), start_time) {
+        Ok(checkpoints) => checkpoints,
+        Err(e) => {
+            eprintln!("Error parsing checkpoint: {e}");
+            std::process::exit(1);
         }
-        println!(); // New line before exit
     };
 
-    // Set up panic hook for cleanup
+    // Menu-bar plugins and badges show one snapshot of the window running now
+    if cli.format().is_snapshot() {
+        let now = get_current_time();
+        let segment = chain
+            .iter()
+            .find(|segment| segment.end > now)
+            .unwrap_or(&chain[chain.len() - 1]);
+        let state =
+            ProgressState::with_granularity(segment.start, segment.end, now, cli.granularity());
+        let message = select_status_message(cli.status_messages(), &state, cli.duration_format());
+        writeln!(
+            out,
+            "{}",
+            render_snapshot(&cli, &state, segment.label.as_deref(), message.as_deref())
+        )?;
+        return Ok(());
+    }
+
+    // Hooks from the configuration file run at milestones, completion, and
+    // overtime, rate limited per channel
+    let (mut hooks, milestones, mut alerts, store_config) =
+        match Config::load().and_then(|config| {
+            Ok((
+                config.hook_registry()?,
+                config.milestone_set()?,
+                config.alert_manager()?,
+                config.store,
+            ))
+        }) {
+            Ok(hooks) => hooks,
+            Err(e) => {
+                eprintln!("Warning: hooks disabled: {e}");
+                (
+                    HookRegistry::default(),
+                    Milestones::default(),
+                    AlertManager::default(),
+                    StoreConfig::default(),
+                )
+            }
+        };
+    let mut outbox = open_outbox(&cli, &hooks, &store_config);
+
+    // JSON and workflow commands are meant for other programs, so keep the
+    // output free of prose
+    let is_machine = !cli.format().is_text();
+
+    // Display header information only if verbose flag is set
+    if cli.verbose() && !is_machine {
+        writeln!(out, "pmon - Progress Monitor Tool")?;
+        let mut rows = Vec::new();
+        if let Some(label) = cli.label() {
+            rows.push(("Label", label.to_string()));
+        }
+        rows.push((
+            "Start time",
+            format!(
+                "{}{}",
+                start_time.format("%Y-%m-%d %H:%M:%S"),
+                parse_note(start_parsed.as_ref())
+            ),
+        ));
+        rows.push((
+            "End time",
+            format!(
+                "{}{}",
+                end_time.format("%Y-%m-%d %H:%M:%S"),
+                parse_note(end_parsed.as_ref())
+            ),
+        ));
+        let now = get_current_time();
+        if cli.demo().is_none() && now < start_time {
+            rows.push((
+                "Starts in",
+                cli.duration_format()
+                    .remaining(civil_duration(now, start_time)),
+            ));
+        }
+        for segment in &chain[1..] {
+            rows.push((
+                "Then",
+                format!(
+                    "{} {} - {}",
+                    segment.label.as_deref().unwrap_or("-"),
+                    segment.start.format("%Y-%m-%d %H:%M:%S"),
+                    segment.end.format("%Y-%m-%d %H:%M:%S")
+                ),
+            ));
+        }
+        rows.push(match cli.demo() {
+            Some(length) => ("Demo length", format!("{}s", length.num_seconds())),
+            None => ("Update interval", format!("{} seconds", cli.interval())),
+        });
+        let rows: Vec<(&str, &str)> = rows
+            .iter()
+            .map(|(key, value)| (*key, value.as_str()))
+            .collect();
+        for row in align_pairs(&rows) {
+            writeln!(out, "{row}")?;
+        }
+        if cli.locked() {
+            writeln!(out, "Press Ctrl+C and type \"{UNLOCK_PHRASE}\" to exit\n")?;
+        } else {
+            writeln!(out, "Press Ctrl+C to exit\n")?;
+        }
+    }
+
+    // A wrapped command owns the keyboard, so its loop never enters raw mode
+    if !cli.wrap().is_empty() {
+        let code = run_wrapped(
+            &chain[0],
+            &cli,
+            &target,
+            &mut hooks,
+            &mut alerts,
+            &mut outbox,
+            out,
+        )?;
+        std::process::exit(code);
+    }
+
+    // Check if we're in a TTY environment and if the environment is truly interactive
+    let is_interactive = !is_machine && is_interactive_terminal(&target);
+    debug!(
+        output = ?target,
+        is_terminal = target.is_terminal(),
+        is_interactive,
+        term = ?std::env::var("TERM").ok(),
+        no_color = std::env::var_os("NO_COLOR").is_some(),
+        ci = std::env::var_os("CI").is_some(),
+        "terminal detected"
+    );
+
+    // Enable raw mode for signal detection only if we're in an interactive TTY
+    let session = TerminalSession::start(is_interactive)?;
+
+    // The panic message is printed before unwinding drops the session
     let original_hook = std::panic::take_hook();
+    let panic_target = target.clone();
     std::panic::set_hook(Box::new(move |panic_info| {
         if is_interactive {
-            let _ = crossterm::terminal::disable_raw_mode();
+            restore_terminal();
+        }
+        // New line before exit, on whichever stream carries the bar
+        match panic_target {
+            OutputTarget::Stdout => println!(),
+            OutputTarget::Stderr => eprintln!(),
+            OutputTarget::File(_) => {}
         }
-        println!(); // New line before exit
         original_hook(panic_info);
     }));
 
-    // Main application loop
-    let result = run_progress_loop(start_time, end_time, cli.interval(), is_interactive);
+    // Main application loop, run once per chained window
+    let mut result = Ok(LoopOutcome::Completed {
+        snoozes: 0,
+        end: end_time,
+    });
+    let mut total_snoozes = 0;
+    let mut finished_ends = Vec::new();
+    let mut notes = Vec::new();
+    let mut display = Display {
+        out,
+        is_interactive,
+        publisher: cli.name().map(TimerPublisher::new),
+        hooks,
+        milestones,
+        alerts,
+        outbox,
+        #[cfg(feature = "dbus")]
+        dbus: cli
+            .dbus()
+            .then(|| DbusService::start(cli.name(), cli.label()))
+            .and_then(|started| {
+                started
+                    .inspect_err(|e| eprintln!("Warning: could not publish on D-Bus: {e}"))
+                    .ok()
+            }),
+        #[cfg(feature = "otel")]
+        telemetry: cli.otel_endpoint().map(|endpoint| {
+            Telemetry::new(
+                endpoint.clone(),
+                cli.label(),
+                cli.name().map(TimerName::as_str),
+            )
+        }),
+    };
+    // A demo plays the whole chain back on one sped-up clock
+    let demo_clock = cli
+        .demo()
+        .and_then(|length| length.to_std().ok())
+        .map(|length| ScaledClock::compressing(&whole_window, length));
+    let mut index = 0;
+    while index < chain.len() {
+        // A daily preset lines up the next day's window before each one runs
+        if cli.daily() && index + 1 == chain.len() {
+            match next_preset_window(&cli, chain[index].end) {
+                Ok(window) => chain.push(window),
+                Err(error) => warn!(%error, "no preset window for the next day"),
+            }
+        }
+        let segment = &chain[index];
+        // Only a schedule or a daily preset previews the window to come
+        let next = (cli.schedule().is_some() || cli.daily())
+            .then(|| chain.get(index + 1))
+            .flatten();
+        result = match demo_clock {
+            Some(clock) => run_progress_loop(
+                segment,
+                next,
+                overall,
+                &cli,
+                &checkpoints,
+                &mut notes,
+                &mut display,
+                clock,
+            ),
+            None => run_progress_loop(
+                segment,
+                next,
+                overall,
+                &cli,
+                &checkpoints,
+                &mut notes,
+                &mut display,
+                SystemClock,
+            ),
+        };
+        match result {
+            Ok(LoopOutcome::Completed { snoozes, end }) => {
+                total_snoozes += snoozes;
+                finished_ends.push(end);
+            }
+            _ => break,
+        }
+        index += 1;
+    }
 
-    // Cleanup and handle result
-    cleanup();
+    // Restore the terminal before the final output, and end the shared timer
+    drop(display.publisher.take());
+    drop(session);
+    if !is_machine {
+        writeln!(display.out)?; // New line before exit
+    }
+    let outcome = match result {
+        Ok(LoopOutcome::Completed { .. }) => RunOutcome::Completed,
+        _ => RunOutcome::Interrupted,
+    };
+    if let (Some(store), Some(name)) = (store.as_deref_mut(), cli.name()) {
+        record_named_run(store, name, &whole_window, cli.tags(), &notes, outcome);
+    }
+    #[cfg(feature = "otel")]
+    if let Some(telemetry) = display.telemetry.as_mut() {
+        let finished = get_current_time();
+        if let Err(e) = telemetry.export_run(start_time, whole_window.end, finished, outcome) {
+            warn_delivery_failure(&cli, "telemetry export", &e);
+        }
+    }
+    if let Some(path) = cli.junit() {
+        let stopped_at = get_current_time();
+        let mut phases: Vec<PhaseResult> = chain
+            .iter()
+            .enumerate()
+            .map(|(i, segment)| {
+                let name = match &segment.label {
+                    Some(label) => label.clone(),
+                    None => format!("window {}", i + 1),
+                };
+                let finished = finished_ends.get(i).copied();
+                let mut phase =
+                    PhaseResult::window(&name, segment.start, segment.end, finished, stopped_at);
+                if finished.is_none() && i > finished_ends.len() {
+                    phase.duration = chrono::Duration::zero();
+                    phase.status = PhaseStatus::Skipped("not started".to_string());
+                }
+                phase
+            })
+            .collect();
+        phases.extend(
+            checkpoints
+                .iter()
+                .map(|checkpoint| PhaseResult::checkpoint(checkpoint, start_time, stopped_at)),
+        );
+        save_junit_report(path, &cli, &phases);
+    }
 
     match result {
         Ok(_) => {
-            println!("Progress monitoring completed successfully.");
+            if !is_machine {
+                writeln!(display.out, "Progress monitoring completed successfully.")?;
+                if total_snoozes > 0 {
+                    writeln!(display.out, "Snoozed {total_snoozes} time(s).")?;
+                }
+                if !notes.is_empty() {
+                    writeln!(display.out, "Notes:")?;
+                    for note in &notes {
+                        writeln!(display.out, "  {}", format_note(note))?;
+                    }
+                }
+            }
             Ok(())
         }
         Err(e) => {
@@ -105,72 +790,2162 @@ fn main() -> Result<()> {
     }
 }
 
-/// Run the main progress monitoring loop
-fn run_progress_loop(
-    start_time: chrono::NaiveDateTime,
-    end_time: chrono::NaiveDateTime,
-    interval_seconds: u64,
-    is_interactive: bool,
-) -> Result<()> {
-    let interval_duration = Duration::from_secs(interval_seconds);
-    let poll_duration = Duration::from_millis(100); // Check for Ctrl+C every 100ms
+/// Write the `--junit` report, warning rather than failing if it cannot be saved
+fn save_junit_report(path: &std::path::Path, cli: &Cli, phases: &[PhaseResult]) {
+    let suite = cli
+        .label()
+        .or(cli.name().map(TimerName::as_str))
+        .unwrap_or("pmon");
+    if let Err(e) = write_junit(path, suite, phases) {
+        eprintln!(
+            "Warning: could not write JUnit report to {}: {e}",
+            path.display()
+        );
+    }
+}
 
-    loop {
-        // Get current time and calculate progress (using centralized time function)
-        let current_time = get_current_time();
-        let progress = calculate_progress(start_time, end_time, current_time);
+/// Save a named timer's window, returning the store to record its run in
+///
+/// A timer still running under the same name is handled as `on_exists`
+/// says, and `window` becomes the window saved. The timer's definition is
+/// saved too, when given, so it can be exported or run again. Storage
+/// problems are reported as warnings so they never stop the timer; only a
+/// refused start is an error.
+fn save_named_timer(
+    name: &TimerName,
+    window: &mut Segment,
+    definition: Option<&TimerDefinition>,
+    on_exists: OnExists,
+) -> PbResult<Option<Box<dyn Store>>> {
+    let timer = TimerRecord {
+        name: name.as_str().to_string(),
+        label: window.label.clone(),
+        start: window.start,
+        end: window.end,
+        pid: Some(std::process::id()),
+    };
+    let saved = Config::load()
+        .and_then(|config| open_store(&config.store))
+        .and_then(|mut store| {
+            let claimed = claim_timer(store.as_mut(), &timer, get_current_time(), on_exists)?;
+            if let Some(definition) = definition {
+                store.save_definition(definition)?;
+            }
+            Ok((store, claimed))
+        });
+    match saved {
+        Ok((store, claimed)) => {
+            *window = Segment {
+                label: claimed.label,
+                start: claimed.start,
+                end: claimed.end,
+            };
+            Ok(Some(store))
+        }
+        Err(e @ PbError::TimerExists { .. }) => Err(e),
+        Err(e) => {
+            eprintln!("Warning: could not save timer '{}': {e}", name.as_str());
+            Ok(None)
+        }
+    }
+}
 
-        // Render progress bar with time information
-        let bar =
-            render_colored_progress_bar_with_time(progress, start_time, end_time, current_time);
+/// Append a finished run of a named timer to the history
+///
+/// A run stopped early also ends the saved timer, when this process still
+/// owns it, so the name can be started again right away.
+fn record_named_run(
+    store: &mut dyn Store,
+    name: &TimerName,
+    window: &Segment,
+    tags: &[String],
+    notes: &[Note],
+    outcome: RunOutcome,
+) {
+    let finished_at = get_current_time();
+    let run = RunRecord {
+        name: Some(name.as_str().to_string()),
+        label: window.label.clone(),
+        start: window.start,
+        end: window.end,
+        finished_at,
+        outcome,
+        tags: tags.to_vec(),
+        notes: notes.to_vec(),
+    };
+    if let Err(e) = store.record_run(&run) {
+        eprintln!("Warning: could not record run of '{}': {e}", name.as_str());
+    }
+    if outcome != RunOutcome::Interrupted {
+        return;
+    }
+    let ended = store.timer(name.as_str()).and_then(|saved| match saved {
+        Some(saved) if saved.pid == Some(std::process::id()) && saved.end > finished_at => store
+            .save_timer(&TimerRecord {
+                end: finished_at,
+                ..saved
+            }),
+        _ => Ok(()),
+    });
+    if let Err(e) = ended {
+        eprintln!("Warning: could not end timer '{}': {e}", name.as_str());
+    }
+}
 
-        // Update display
-        if is_interactive {
-            // In interactive TTY mode, use carriage return to overwrite the current line
-            print!("\r{bar}");
-            io::stdout().flush()?;
-        } else {
-            // In non-interactive mode, just print the progress bar
-            println!("{bar}");
+/// Print a deadline's status line, returning the exit code for its status
+fn run_deadline(args: &DeadlineArgs, out: &mut dyn Write) -> Result<i32> {
+    let now = get_current_time();
+    let end = match parse_time_with_base(&args.end, Some(now)) {
+        Ok(time) => time,
+        Err(e) => {
+            eprintln!("Error parsing end time '{}': {e}", args.end);
+            return Ok(1);
         }
+    };
+    let status = DeadlineStatus::evaluate(civil_duration(now, end), args.warn, args.fail);
+    writeln!(
+        out,
+        "{}",
+        format_deadline_status(status, args.label.as_deref(), now, end)
+    )?;
+    Ok(status.exit_code())
+}
 
-        // Check if we've completed (progress >= 100%)
-        if progress >= 100.0 {
-            if !is_interactive {
-                println!("Progress completed! Time range has elapsed.");
+/// Draw the window's progress now as an image
+fn run_snapshot(args: &SnapshotArgs, cli: &Cli, out: &mut dyn Write) -> Result<()> {
+    let start = match &args.start {
+        Some(start) => parse_time(start)
+            .map_err(|e| anyhow::anyhow!("could not parse start time '{start}': {e}"))?,
+        None => determine_start_time_for_end(&args.end),
+    };
+    let end = parse_time_with_base(&args.end, Some(start))
+        .map_err(|e| anyhow::anyhow!("could not parse end time '{}': {e}", args.end))?;
+    validate_times(start, end)?;
+    let state = ProgressState::new(start, end, get_current_time());
+    if args.copy {
+        let options = RenderOptions {
+            style: args.emoji.map_or(BarStyle::Classic, BarStyle::Emoji),
+            duration_format: cli.duration_format(),
+            ..RenderOptions::default()
+        };
+        let text = render_text(&state, args.label.as_deref(), &options);
+        clipboard::copy(&text)?;
+        writeln!(out, "{text}")?;
+        return Ok(());
+    }
+    let mut image = Vec::new();
+    write_image(
+        args.format,
+        &state,
+        args.label.as_deref(),
+        cli.duration_format(),
+        &mut image,
+    )?;
+    match &args.output {
+        Some(path) => std::fs::write(path, image)
+            .map_err(|e| anyhow::anyhow!("could not write {}: {e}", path.display()))?,
+        None => out.write_all(&image)?,
+    }
+    Ok(())
+}
+
+/// Print the `pmon doctor` report and return its exit code
+fn run_doctor(cli: &Cli, target: &OutputTarget, out: &mut dyn Write) -> Result<i32> {
+    let mut probe = TerminalProbe::from_env(is_interactive_terminal(target));
+    // The cursor position is only reported back for the terminal on stdout
+    if probe.is_interactive && target == &OutputTarget::Stdout {
+        probe.wide_char_columns = measure_width(&WIDE_PROBE.to_string()).ok();
+    }
+    let offset = cli.time_source().measure_offset(TIME_SOURCE_TIMEOUT);
+    let checks = [
+        check_color(&probe),
+        check_unicode(&probe),
+        check_alternate_screen(&probe),
+        check_notifications(find_program),
+        check_config(&config_path()),
+        check_clock(get_current_time(), cli.time_source(), offset),
+    ];
+    write!(out, "{}", render_report(&checks))?;
+    Ok(doctor::exit_code(&checks))
+}
+
+/// Check the configured store's file, recovering what it can from damage
+fn run_repair_state(out: &mut dyn Write) -> Result<()> {
+    let store = Config::load()?.store;
+    let path = store.resolved_path();
+    if store.backend != StoreBackend::Json {
+        writeln!(
+            out,
+            "The {} store at {} is kept consistent by SQLite; nothing to repair.",
+            store.backend.name(),
+            path.display()
+        )?;
+        return Ok(());
+    }
+    match repair_json_store(&path)? {
+        Repair::Missing => writeln!(out, "No state file at {}; nothing to repair.", path.display())?,
+        Repair::Healthy => writeln!(out, "{} is healthy; nothing to repair.", path.display())?,
+        Repair::Recovered {
+            timers,
+            runs,
+            definitions,
+            backup,
+        } => writeln!(
+            out,
+            "Recovered {timers} timer(s), {runs} run(s), and {definitions} definition(s) into {}; the damaged file was kept as {}.",
+            path.display(),
+            backup.display()
+        )?,
+    }
+    Ok(())
+}
+
+/// Save a secret read from standard input in the keyring, or remove one
+fn run_secret(args: &SecretArgs, out: &mut dyn Write) -> Result<()> {
+    match args.action {
+        SecretAction::Set => {
+            let mut secret = String::new();
+            std::io::stdin().read_line(&mut secret)?;
+            let secret = secret.trim();
+            if secret.is_empty() {
+                anyhow::bail!("no secret given on standard input");
+            }
+            set_keyring_secret(&args.name, secret).map_err(anyhow::Error::msg)?;
+            writeln!(
+                out,
+                "Saved; refer to it as \"keyring:{}\" in the configuration.",
+                args.name
+            )?;
+        }
+        SecretAction::Delete => {
+            if delete_keyring_secret(&args.name).map_err(anyhow::Error::msg)? {
+                writeln!(out, "Removed keyring:{}.", args.name)?;
             } else {
-                println!("\nProgress completed! Time range has elapsed.");
+                writeln!(out, "No secret named keyring:{}.", args.name)?;
             }
-            break;
         }
+    }
+    Ok(())
+}
 
-        // Sleep with periodic Ctrl+C checking (only in interactive mode)
-        if is_interactive {
-            let mut remaining_sleep = interval_duration;
-            while remaining_sleep > Duration::ZERO {
-                let sleep_chunk = remaining_sleep.min(poll_duration);
-
-                // Check for Ctrl+C
-                if event::poll(sleep_chunk)? {
-                    if let Event::Key(KeyEvent {
-                        code: KeyCode::Char('c'),
-                        modifiers: KeyModifiers::CONTROL,
-                        ..
-                    }) = event::read()?
-                    {
-                        println!("\nReceived Ctrl+C, exiting gracefully...");
-                        return Ok(());
-                    }
-                    // Ignore other key events
-                }
+/// Print sample bars of every style and color
+fn run_themes(args: &ThemesArgs, out: &mut dyn Write) -> Result<()> {
+    match args.action {
+        ThemesAction::Preview => {
+            for line in preview_lines(&discover_themes(&themes_dir())?) {
+                writeln!(out, "{line}")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Report whether a newer release exists, installing it unless only checking
+fn run_self_update(args: &SelfUpdateArgs, out: &mut dyn Write) -> Result<()> {
+    let current = Version::current()?;
+    let releases = fetch_releases()?;
+    let latest = latest_release(&releases, args.channel);
+    let newer = latest
+        .as_ref()
+        .filter(|(version, _)| *version > current)
+        .map(|(_, release)| *release);
+    writeln!(
+        out,
+        "{}",
+        format_check(&current, latest.as_ref().map(|(v, r)| (v, *r)))
+    )?;
+    let Some(release) = newer else {
+        return Ok(());
+    };
+    if args.check {
+        return Ok(());
+    }
+    install_release(release, out)
+}
+
+/// Put `release` in place of the running pmon
+#[cfg(feature = "self-update")]
+fn install_release(release: &self_update::Release, out: &mut dyn Write) -> Result<()> {
+    let path = self_update::install(release)?;
+    writeln!(out, "Installed {} at {}", release.tag_name, path.display())?;
+    Ok(())
+}
+
+/// Builds without the `self-update` feature leave installing to the user
+#[cfg(not(feature = "self-update"))]
+fn install_release(release: &self_update::Release, _out: &mut dyn Write) -> Result<()> {
+    let download = release
+        .asset_for_platform()
+        .map_or(release.html_url.as_str(), |asset| {
+            asset.browser_download_url.as_str()
+        });
+    anyhow::bail!("this build cannot replace itself; download {download}, or use --check")
+}
+
+/// Open the store selected in the configuration file
+fn open_configured_store() -> Result<Box<dyn Store>> {
+    Ok(open_store(&Config::load()?.store)?)
+}
+
+/// Look up a saved timer definition, failing when there is none
+fn saved_definition(name: &TimerName) -> Result<TimerDefinition> {
+    open_configured_store()?
+        .definition(name.as_str())?
+        .ok_or_else(|| anyhow::anyhow!("no saved timer named '{}'", name.as_str()))
+}
+
+/// Command line of a saved timer, as if its options had been typed
+fn saved_timer_cli(name: &TimerName) -> Result<Cli> {
+    let definition = saved_definition(name)?;
+    let args = std::iter::once("pmon".to_string()).chain(definition.to_args());
+    let cli = Cli::try_parse_chain_from(args)?;
+    cli.validate()?;
+    Ok(cli)
+}
+
+/// Print a saved timer's definition as TOML
+fn run_export(name: &TimerName, out: &mut dyn Write) -> Result<()> {
+    write!(out, "{}", saved_definition(name)?.to_toml())?;
+    Ok(())
+}
+
+/// Save the timer definition in `file` to the configured store
+fn run_import(file: &std::path::Path, out: &mut dyn Write) -> Result<()> {
+    let contents = std::fs::read_to_string(file)
+        .with_context(|| format!("could not read {}", file.display()))?;
+    let definition = TimerDefinition::from_toml(&contents, &file.display().to_string())?;
+    open_configured_store()?.save_definition(&definition)?;
+    writeln!(
+        out,
+        "Imported timer '{}'; start it with `pmon run {}`.",
+        definition.name, definition.name
+    )?;
+    Ok(())
+}
+
+/// Read the sessions of a `--schedule` plan that have not ended yet
+fn load_schedule(file: &std::path::Path) -> Result<Vec<Segment>> {
+    let contents = std::fs::read_to_string(file)
+        .with_context(|| format!("could not read {}", file.display()))?;
+    let path = file.display().to_string();
+    let plan = read_plan(&contents, &path)?;
+    Ok(resolve_schedule(&plan, get_current_time(), &path)?)
+}
+
+fn run_import_plan(file: &std::path::Path, out: &mut dyn Write) -> Result<()> {
+    let contents = std::fs::read_to_string(file)
+        .with_context(|| format!("could not read {}", file.display()))?;
+    let definitions = read_plan(&contents, &file.display().to_string())?;
+    let mut store = open_configured_store()?;
+    for definition in &definitions {
+        store.save_definition(definition)?;
+        writeln!(out, "Imported timer '{}'.", definition.name)?;
+    }
+    writeln!(
+        out,
+        "Imported {} timer(s); start one with `pmon run NAME`.",
+        definitions.len()
+    )?;
+    Ok(())
+}
+
+/// List the most recent runs from the configured store, oldest first
+fn run_list(args: &ListArgs, cli: &Cli, out: &mut dyn Write) -> Result<()> {
+    let entries: Vec<TimerEntry> = running_timers()?
+        .into_iter()
+        .map(|(name, update)| TimerEntry { name, update })
+        .collect();
+    let entries = filter_timers(entries, &args.filter, &args.state);
+
+    if cli.format() == OutputFormat::Json {
+        for entry in &entries {
+            writeln!(out, "{}", render_timer_json(entry))?;
+        }
+        return Ok(());
+    }
+    if entries.is_empty() {
+        writeln!(out, "No matching timers running.")?;
+    }
+    for line in render_list(&entries) {
+        writeln!(out, "{line}")?;
+    }
+    Ok(())
+}
 
-                remaining_sleep = remaining_sleep.saturating_sub(sleep_chunk);
+fn run_status(args: &StatusArgs, cli: &Cli, out: &mut dyn Write) -> Result<()> {
+    let entries: Vec<TimerEntry> = running_timers()?
+        .into_iter()
+        .map(|(name, update)| TimerEntry { name, update })
+        .collect();
+    let mut entries = in_group(entries, args.group.as_deref());
+    if entries.is_empty() {
+        match &args.group {
+            Some(group) => anyhow::bail!("no running timers tagged '{group}'"),
+            None => anyhow::bail!("no running named timers"),
+        }
+    }
+    sort_by_remaining(&mut entries);
+    let is_json = cli.format() == OutputFormat::Json;
+
+    if !args.aggregate {
+        if is_json {
+            for entry in &entries {
+                writeln!(out, "{}", render_timer_json(entry))?;
             }
         } else {
-            // In non-interactive mode, just sleep for the full interval
-            std::thread::sleep(interval_duration);
+            for line in render_comparison(&entries, None, cli) {
+                writeln!(out, "{line}")?;
+            }
         }
+        return Ok(());
+    }
+
+    let Some(state) = aggregate_progress(&entries) else {
+        anyhow::bail!("none of the timers has a known percentage");
+    };
+    let group = args.group.as_deref().unwrap_or("all timers");
+    if is_json {
+        let tags: Vec<String> = args.group.iter().cloned().collect();
+        let context = UpdateContext {
+            label: Some(group),
+            tags: &tags,
+            ..UpdateContext::default()
+        };
+        writeln!(out, "{}", render_json(&state, &context))?;
+    } else {
+        let label = format!("{group} ({} timers)", entries.len());
+        let bar = render_window_bar(cli, &state, cli.time_info_style(), None, None);
+        writeln!(out, "{}", render_labeled_line(Some(&label), 0, &bar))?;
     }
+    Ok(())
+}
 
+fn run_history(args: &HistoryArgs, cli: &Cli, out: &mut dyn Write) -> Result<()> {
+    let store = open_configured_store()?;
+    let history = store.history()?;
+    let recent = &history[history.len().saturating_sub(args.limit)..];
+
+    if cli.format() == OutputFormat::Json {
+        for run in recent {
+            writeln!(out, "{}", serde_json::to_string(run)?)?;
+        }
+        return Ok(());
+    }
+    if recent.is_empty() {
+        writeln!(out, "No runs recorded yet.")?;
+    }
+    for run in recent {
+        let name = run.name.as_deref().unwrap_or("-");
+        let label = run
+            .label
+            .as_deref()
+            .map(|label| format!(" ({label})"))
+            .unwrap_or_default();
+        let tags = match format_tags(&run.tags) {
+            tags if tags.is_empty() => tags,
+            tags => format!("  {tags}"),
+        };
+        writeln!(
+            out,
+            "{} - {}  {name}{label}  {}{tags}",
+            run.start.format("%Y-%m-%d %H:%M"),
+            run.end.format("%H:%M"),
+            run.outcome.name()
+        )?;
+        for note in &run.notes {
+            writeln!(out, "  {}", format_note(note))?;
+        }
+    }
     Ok(())
 }
+
+/// Print the `pmon report` totals of the runs finished since `--since`
+fn run_report(args: &ReportArgs, cli: &Cli, out: &mut dyn Write) -> Result<()> {
+    let store = open_configured_store()?;
+    let since = args.since.resolve(get_current_time());
+    let report = Report::build(&store.history()?, since, args.by);
+
+    if cli.format() == OutputFormat::Json {
+        writeln!(out, "{}", report.to_json())?;
+        return Ok(());
+    }
+    for line in report.render_table(cli.duration_format()) {
+        writeln!(out, "{line}")?;
+    }
+    Ok(())
+}
+
+/// Print a keepalive line to stderr on every beat until the process exits
+fn start_heartbeat(heartbeat: Heartbeat) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(heartbeat.every());
+        eprintln!("{}", heartbeat.line(Instant::now()));
+    });
+}
+
+/// End the process once `max_runtime` has passed, whatever it is doing
+fn start_watchdog(max_runtime: MaxRuntime, state_file: Option<PathBuf>) {
+    std::thread::spawn(move || {
+        while let Some(left) = max_runtime.remaining(SystemTime::now()) {
+            std::thread::sleep(left.min(POLL_INTERVAL));
+        }
+        // Exiting skips destructors, so undo what they would have done
+        restore_terminal();
+        if let Some(path) = state_file {
+            let _ = std::fs::remove_file(path);
+        }
+        eprintln!("\n{}", max_runtime.message());
+        std::process::exit(MAX_RUNTIME_EXIT_CODE);
+    });
+}
+
+/// How long to wait for a remote time source to answer
+const TIME_SOURCE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Whether the output target is a TTY outside of CI, so raw mode and redraws are safe
+///
+/// Always false without the `interactive` feature.
+fn is_interactive_terminal(target: &OutputTarget) -> bool {
+    let is_tty = cfg!(feature = "interactive") && target.is_terminal();
+    is_tty && std::env::var("CI").is_err() && std::env::var("GITHUB_ACTIONS").is_err()
+}
+
+/// Count up from `start` until Ctrl+C, marking laps with 'l' or space
+///
+/// Used by `pmon stopwatch` and by windows whose end is unknown.
+fn run_stopwatch(
+    args: &StopwatchArgs,
+    start: chrono::NaiveDateTime,
+    power: PowerMonitor,
+    target: &OutputTarget,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let session = TerminalSession::start(is_interactive_terminal(target))?;
+    let stopwatch = Stopwatch::new(start);
+    let result = run_stopwatch_loop(args, stopwatch, power, session.is_interactive(), out);
+    drop(session);
+    writeln!(out)?; // New line before exit
+
+    let stopwatch = result?;
+    writeln!(
+        out,
+        "Stopped after {} ({} laps).",
+        format_duration_with(stopwatch.elapsed(get_current_time()), args.duration_style),
+        stopwatch.lap_count()
+    )?;
+    Ok(())
+}
+
+/// Drive the stopwatch display until the user exits
+fn run_stopwatch_loop(
+    args: &StopwatchArgs,
+    mut stopwatch: Stopwatch,
+    mut power: PowerMonitor,
+    is_interactive: bool,
+    out: &mut dyn Write,
+) -> Result<Stopwatch> {
+    let interval_duration = Duration::from_secs(args.interval);
+    let frame_duration = Duration::from_millis(100); // Animation speed of the marquee
+    let mut frame = 0;
+    let mut events = EventLoop::terminal();
+
+    loop {
+        let elapsed = stopwatch.elapsed(get_current_time());
+        let line = render_stopwatch_line(frame, elapsed, args.duration_style);
+        let line = render_labeled_line(args.label.as_deref(), 0, &line);
+        let saving = power.is_saving(Instant::now());
+
+        if !is_interactive {
+            // Without a terminal there is no animation or lap key; just log the time
+            writeln!(out, "{line}")?;
+            std::thread::sleep(power.interval(interval_duration, Instant::now()));
+            continue;
+        }
+
+        write!(out, "\r{line}{}", CLEAR_LINE)?;
+        out.flush()?;
+
+        // On battery the marquee stands still and only the time moves
+        let wait = if saving {
+            SAVER_FRAME_DURATION
+        } else {
+            frame += 1;
+            frame_duration
+        };
+        if let Some(key) = events.next_key(Some(wait))? {
+            match key {
+                Key::Ctrl('c') => return Ok(stopwatch),
+                Key::Char('l' | ' ') => {
+                    // Print the lap above the live line, which is redrawn next frame
+                    let lap = stopwatch.lap(get_current_time());
+                    write!(
+                        out,
+                        "\r{}{}\r\n",
+                        format_lap(&lap, args.duration_style),
+                        CLEAR_LINE
+                    )?;
+                }
+                // Ignore other key events
+                _ => {}
+            }
+        }
+    }
+}
+
+/// How often the stopwatch is redrawn while saving battery
+const SAVER_FRAME_DURATION: Duration = Duration::from_secs(1);
+
+/// How often a wrapped command is checked for exit
+const CHILD_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Evaluate `window` at `now` with the granularity chosen on the command line
+fn window_state(cli: &Cli, window: &Segment, now: chrono::NaiveDateTime) -> ProgressState {
+    ProgressState::with_granularity(window.start, window.end, now, cli.granularity())
+}
+
+/// Run the wrapped command within `window` and return its exit code
+///
+/// The bar updates every interval until the command exits. When the window
+/// ends first, the command is signaled according to `--on-timeout`; while a
+/// grace period runs the bar turns yellow, and red once the final signal is
+/// sent.
+fn run_wrapped(
+    window: &Segment,
+    cli: &Cli,
+    target: &OutputTarget,
+    hooks: &mut HookRegistry,
+    alerts: &mut AlertManager,
+    outbox: &mut Outbox,
+    out: &mut dyn Write,
+) -> Result<i32> {
+    let Some((program, args)) = cli.wrap().split_first() else {
+        return Ok(0);
+    };
+    let mut child = match std::process::Command::new(program).args(args).spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            eprintln!("Error starting command '{program}': {e}");
+            return Ok(SPAWN_FAILURE_EXIT_CODE);
+        }
+    };
+
+    let is_text = cli.format().is_text();
+    let redraw = is_text && (target.is_terminal() || cli.inplace());
+    let label = window.label.as_deref();
+    let interval_duration = Duration::from_secs(cli.interval());
+    let started = Instant::now();
+    let mut escalation = TimeoutEscalation::new(cli.on_timeout());
+    let mut gha = GhaReporter::new(label, cli.duration_format());
+    let mut throttle = cli.min_change().map(LineThrottle::new);
+    let mut schedule = TickSchedule::new(interval_duration, cli.align(), started);
+
+    let status = loop {
+        let state = window_state(cli, window, get_current_time());
+        let message = select_status_message(cli.status_messages(), &state, cli.duration_format());
+
+        run_hooks(
+            hooks,
+            alerts,
+            outbox,
+            &state,
+            cli,
+            label,
+            false,
+            Instant::now(),
+            out,
+        );
+
+        if let Some(signal) = escalation.advance(state.is_complete(), Instant::now()) {
+            if is_text {
+                writeln!(
+                    out,
+                    "\nTime is up; sending SIG{} to {program}.",
+                    signal.name()
+                )?;
+            }
+            if let Err(e) = send_signal(&mut child, signal) {
+                eprintln!("Warning: could not signal {program}: {e}");
+            }
+        }
+
+        match cli.format() {
+            OutputFormat::Json => {
+                let context = UpdateContext {
+                    label,
+                    message: message.as_deref(),
+                    clock_skew: None,
+                    tags: cli.tags(),
+                };
+                writeln!(out, "{}", render_json(&state, &context))?;
+            }
+            OutputFormat::Gha => {
+                for command in gha.update(&state, &[]) {
+                    writeln!(out, "{command}")?;
+                }
+            }
+            // Rejected by validation: a snapshot cannot wait for a command
+            OutputFormat::Xbar | OutputFormat::Badge | OutputFormat::BadgeSvg => {}
+            OutputFormat::Text => {
+                let bar = render_wrapped_bar(cli, &state, escalation.stage(), message.as_deref());
+                let bar = render_labeled_line(label, 0, &bar);
+                if redraw {
+                    write!(out, "\r{bar}{}", CLEAR_LINE)?;
+                    out.flush()?;
+                } else if throttle
+                    .as_mut()
+                    .is_none_or(|throttle| throttle.should_emit(state.percentage, Instant::now()))
+                {
+                    writeln!(out, "{bar}")?;
+                }
+            }
+        }
+
+        // Wait for the next tick, but return as soon as the command exits or
+        // the grace period ends
+        let now = Instant::now();
+        let mut deadline = now + schedule.remaining(now, get_current_time());
+        if let Some(grace_deadline) = escalation.next_deadline() {
+            deadline = deadline.min(grace_deadline);
+        }
+        let exited = loop {
+            if let Some(status) = child.try_wait()? {
+                break Some(status);
+            }
+            if Instant::now() >= deadline {
+                break None;
+            }
+            std::thread::sleep(CHILD_POLL_INTERVAL);
+        };
+        if let Some(status) = exited {
+            break status;
+        }
+        schedule.advance(Instant::now());
+    };
+
+    let state = window_state(cli, window, get_current_time());
+    run_hooks(
+        hooks,
+        alerts,
+        outbox,
+        &state,
+        cli,
+        label,
+        true,
+        Instant::now(),
+        out,
+    );
+
+    let code = exit_code(status);
+    if let Some(path) = cli.junit() {
+        let now = get_current_time();
+        let status = if escalation.overran() {
+            PhaseStatus::Failed(format!("ran out of time (exit code {code})"))
+        } else if code != 0 {
+            PhaseStatus::Failed(format!("exited with code {code}"))
+        } else {
+            PhaseStatus::OnTime
+        };
+        let phase = PhaseResult {
+            name: program.clone(),
+            duration: (now - window.start).max(chrono::Duration::zero()),
+            status,
+        };
+        save_junit_report(path, cli, &[phase]);
+    }
+    if cli.format() == OutputFormat::Gha {
+        let now = get_current_time();
+        let state = window_state(cli, window, now);
+        let timed_out = escalation.overran();
+        writeln!(
+            out,
+            "{}",
+            gha.command_finished(program, code, timed_out, now, &state)
+        )?;
+    }
+    if is_text {
+        if redraw {
+            writeln!(out)?;
+        }
+        let elapsed = chrono::Duration::from_std(started.elapsed()).unwrap_or_default();
+        let outcome = match escalation.summary() {
+            Some(summary) => format!("ran out of time ({summary})"),
+            None => "finished".to_string(),
+        };
+        writeln!(
+            out,
+            "{program} {outcome} after {} with exit code {code}.",
+            format_duration_with(elapsed, cli.duration_style())
+        )?;
+    }
+    out.flush()?;
+    Ok(code)
+}
+
+/// Render a wrapped command's bar, colored by the timeout escalation stage
+fn render_wrapped_bar(
+    cli: &Cli,
+    state: &ProgressState,
+    stage: TimeoutStage,
+    message: Option<&str>,
+) -> String {
+    let plain = || {
+        render(
+            state,
+            &RenderOptions {
+                duration_format: cli.duration_format(),
+                step: cli.step(),
+                invert: cli.invert(),
+                drain: cli.drain(),
+                ..RenderOptions::default()
+            },
+        )
+    };
+    match stage {
+        TimeoutStage::Running => {
+            render_window_bar(cli, state, cli.time_info_style(), message, None)
+        }
+        TimeoutStage::Grace { signal, deadline } => {
+            let left = deadline.saturating_duration_since(Instant::now()).as_secs();
+            format!(
+                "{} · SIG{} sent, escalating in {left}s",
+                plain().yellow(),
+                signal.name()
+            )
+        }
+        TimeoutStage::Signaled(signal) => {
+            format!("{} · SIG{} sent", plain().red(), signal.name())
+        }
+    }
+}
+
+/// Show progress streamed from pmon on each remote host until all finish
+///
+/// Each host gets its own labeled bar. Returns the highest exit code
+/// reported by `ssh`.
+#[cfg(feature = "remote")]
+fn run_remote(
+    hosts: &[RemoteHost],
+    args: &[String],
+    cli: &Cli,
+    target: &OutputTarget,
+    out: &mut dyn Write,
+) -> Result<i32> {
+    use std::io::BufRead;
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let mut children: Vec<std::process::Child> = Vec::new();
+    for (index, host) in hosts.iter().enumerate() {
+        let mut child = match host.spawn(args) {
+            Ok(child) => child,
+            Err(e) => {
+                eprintln!("Error connecting to {}: {e}", host.destination());
+                for mut child in children {
+                    let _ = child.kill();
+                }
+                return Ok(SPAWN_FAILURE_EXIT_CODE);
+            }
+        };
+        if let Some(stdout) = child.stdout.take() {
+            let sender = sender.clone();
+            std::thread::spawn(move || {
+                for line in std::io::BufReader::new(stdout).lines() {
+                    let Ok(line) = line else { break };
+                    if sender.send((index, line)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        children.push(child);
+    }
+    drop(sender);
+
+    let labels: Vec<&str> = hosts.iter().map(RemoteHost::host).collect();
+    let label_width = label_column_width(labels.iter().copied());
+    let redraw = target.is_terminal();
+    let mut latest: Vec<Option<JsonUpdate>> = vec![None; hosts.len()];
+    let mut drawn_lines: u16 = 0;
+
+    // Ends once every host's stream has closed
+    for (index, line) in receiver {
+        match JsonUpdate::parse(&line) {
+            Ok(update) => latest[index] = Some(update),
+            Err(_) => {
+                eprintln!("Warning: unexpected output from {}: {line}", labels[index]);
+                continue;
+            }
+        }
+
+        let lines: Vec<String> = latest
+            .iter()
+            .zip(&labels)
+            .map(|(update, host)| {
+                let bar = match update {
+                    Some(update) => render_window_bar(
+                        cli,
+                        &update.state(),
+                        cli.time_info_style(),
+                        update.message.as_deref(),
+                        None,
+                    ),
+                    None => "waiting for the first update...".to_string(),
+                };
+                render_labeled_line(Some(host), label_width, &bar)
+            })
+            .collect();
+
+        if redraw {
+            // Redraw every host's bar in place
+            if drawn_lines > 0 {
+                write!(out, "{}", move_up(drawn_lines))?;
+            }
+            for (i, line) in lines.iter().enumerate() {
+                let separator = if i == 0 { "\r" } else { "\n" };
+                write!(out, "{separator}{line}{}", CLEAR_LINE)?;
+            }
+            out.flush()?;
+            drawn_lines = lines.len() as u16 - 1;
+        } else {
+            // Log only the host that just reported
+            writeln!(out, "{}", lines[index])?;
+        }
+    }
+
+    let mut code = 0;
+    for mut child in children {
+        code = code.max(exit_code(child.wait()?));
+    }
+    if redraw {
+        writeln!(out)?;
+    }
+    writeln!(out, "Remote monitoring finished.")?;
+    out.flush()?;
+    Ok(code)
+}
+
+/// Render the single snapshot printed by `--format xbar`, `badge`, or `badge-svg`
+fn render_snapshot(
+    cli: &Cli,
+    state: &ProgressState,
+    label: Option<&str>,
+    message: Option<&str>,
+) -> String {
+    let state = &ProgressState {
+        percentage: match cli.step() {
+            Some(step) => quantize_percentage(state.percentage, step),
+            None => state.percentage,
+        },
+        ..state.clone()
+    };
+    match cli.format() {
+        OutputFormat::Badge => render_badge_url(state, label),
+        OutputFormat::BadgeSvg => render_badge_svg(state, label),
+        _ => render_xbar(state, label, message, cli.duration_format()),
+    }
+}
+
+/// Display a named timer from its state file until it completes or ends
+///
+/// Returns 1 when no timer with that name is running.
+fn run_attach(
+    args: &AttachArgs,
+    cli: &Cli,
+    target: &OutputTarget,
+    out: &mut dyn Write,
+) -> Result<i32> {
+    let path = args.name.state_path();
+    if cli.format().is_snapshot() {
+        let output = match read_update(&path)? {
+            Some(update) => render_snapshot(
+                cli,
+                &update.state(),
+                update.label.as_deref(),
+                update.message.as_deref(),
+            ),
+            None if cli.format() == OutputFormat::Xbar => {
+                render_xbar_idle(&format!("No running timer named '{}'", args.name.as_str()))
+            }
+            None => {
+                eprintln!("Error: no running timer named '{}'", args.name.as_str());
+                return Ok(1);
+            }
+        };
+        writeln!(out, "{output}")?;
+        return Ok(0);
+    }
+    let is_json = cli.format() == OutputFormat::Json;
+    let redraw = !is_json && (target.is_terminal() || cli.inplace());
+    let interval_duration = Duration::from_secs(args.interval);
+    let mut schedule = TickSchedule::new(interval_duration, cli.align(), Instant::now());
+    let mut last_seen = None;
+
+    loop {
+        let Some(update) = read_update(&path)? else {
+            if last_seen.is_none() {
+                eprintln!("Error: no running timer named '{}'", args.name.as_str());
+                return Ok(1);
+            }
+            if redraw {
+                writeln!(out)?;
+            }
+            writeln!(out, "Timer '{}' ended.", args.name.as_str())?;
+            return Ok(0);
+        };
+
+        // Only redraw when the timer has published something new
+        if last_seen.as_ref() != Some(&update) {
+            let state = update.state();
+            if is_json {
+                let context = UpdateContext {
+                    label: update.label.as_deref(),
+                    message: update.message.as_deref(),
+                    clock_skew: None,
+                    tags: &update.tags,
+                };
+                writeln!(out, "{}", render_json(&state, &context))?;
+            } else {
+                let bar = render_window_bar(
+                    cli,
+                    &state,
+                    cli.time_info_style(),
+                    update.message.as_deref(),
+                    None,
+                );
+                let bar = render_labeled_line(update.label.as_deref(), 0, &bar);
+                if redraw {
+                    write!(out, "\r{bar}{}", CLEAR_LINE)?;
+                    out.flush()?;
+                } else {
+                    writeln!(out, "{bar}")?;
+                }
+            }
+            if state.is_complete() {
+                if !is_json {
+                    if redraw {
+                        writeln!(out)?;
+                    }
+                    let message = complete_message(cli, &state, update.label.as_deref());
+                    if !message.is_empty() {
+                        writeln!(out, "{message}")?;
+                    }
+                }
+                return Ok(0);
+            }
+            last_seen = Some(update);
+        }
+
+        std::thread::sleep(schedule.remaining(Instant::now(), get_current_time()));
+        schedule.advance(Instant::now());
+    }
+}
+
+/// Compare running named timers until they have all ended or the user quits
+///
+/// Returns 1 when none of the timers are running.
+fn run_compare(
+    args: &CompareArgs,
+    cli: &Cli,
+    target: &OutputTarget,
+    out: &mut dyn Write,
+) -> Result<i32> {
+    let session = TerminalSession::start(is_interactive_terminal(target))?;
+    let result = run_compare_loop(args, cli, session.is_interactive(), out);
+
+    if session.is_interactive() {
+        drop(session);
+        writeln!(out)?; // New line before exit
+    }
+    result
+}
+
+/// Latest updates of the compared timers, sorted by time left
+fn read_compared_timers(args: &CompareArgs) -> Result<Vec<TimerEntry>> {
+    let timers = if args.names.is_empty() {
+        running_timers()?
+    } else {
+        let mut timers = Vec::new();
+        for name in &args.names {
+            if let Some(update) = read_update(&name.state_path())? {
+                timers.push((name.clone(), update));
+            }
+        }
+        timers
+    };
+    let mut entries: Vec<TimerEntry> = timers
+        .into_iter()
+        .map(|(name, update)| TimerEntry { name, update })
+        .collect();
+    sort_by_remaining(&mut entries);
+    Ok(entries)
+}
+
+/// One labeled bar per timer, with the selected one marked when `selected`
+/// is given and the next to expire flagged
+fn render_comparison(entries: &[TimerEntry], selected: Option<usize>, cli: &Cli) -> Vec<String> {
+    let label_width = label_column_width(entries.iter().map(TimerEntry::label));
+    let next = next_to_expire(entries);
+    entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            let bar = render_window_bar(
+                cli,
+                &entry.update.state(),
+                cli.time_info_style(),
+                entry.update.message.as_deref(),
+                None,
+            );
+            let line = render_labeled_line(Some(entry.label()), label_width, &bar);
+            let cursor = match selected {
+                Some(selected) if selected == index => "› ",
+                Some(_) => "  ",
+                None => "",
+            };
+            if next == Some(index) {
+                format!("{cursor}{line} {}", NEXT_MARKER.bold())
+            } else {
+                format!("{cursor}{line}")
+            }
+        })
+        .collect()
+}
+
+/// Drive the comparison view, handling selection and focus keys in a terminal
+fn run_compare_loop(
+    args: &CompareArgs,
+    cli: &Cli,
+    is_interactive: bool,
+    out: &mut dyn Write,
+) -> Result<i32> {
+    let interval_duration = Duration::from_secs(args.interval);
+    let poll_duration = Duration::from_millis(100);
+    let mut events = EventLoop::terminal();
+    let mut entries: Vec<TimerEntry> = Vec::new();
+    let mut last_check: Option<Instant> = None;
+    // The selection follows the timer, not its position, as the order changes
+    let mut selected: Option<TimerName> = None;
+    let mut is_focused = false;
+    let mut needs_redraw = false;
+    let mut drawn_lines: u16 = 0;
+
+    loop {
+        if last_check.is_none_or(|checked| checked.elapsed() >= interval_duration) {
+            last_check = Some(Instant::now());
+            let latest = read_compared_timers(args)?;
+            if latest.is_empty() {
+                if entries.is_empty() {
+                    eprintln!("Error: no running named timers to compare");
+                    return Ok(1);
+                }
+                if is_interactive {
+                    writeln!(out, "\r")?;
+                }
+                writeln!(out, "All timers ended.")?;
+                return Ok(0);
+            }
+            if latest != entries {
+                entries = latest;
+                needs_redraw = true;
+            }
+        }
+
+        let index = selected
+            .as_ref()
+            .and_then(|name| entries.iter().position(|entry| &entry.name == name))
+            .unwrap_or(0);
+        if needs_redraw {
+            needs_redraw = false;
+            if !is_interactive {
+                // Log the whole table on every change, separated by a blank line
+                for line in render_comparison(&entries, None, cli) {
+                    writeln!(out, "{line}")?;
+                }
+                writeln!(out)?;
+                continue;
+            }
+            let mut lines = if is_focused {
+                render_details(&entries[index], cli.duration_format())
+            } else {
+                render_comparison(&entries, Some(index), cli)
+            };
+            let help = if is_focused {
+                "Enter/Esc back · q quit"
+            } else {
+                "↑/↓ select · Enter details · q quit"
+            };
+            lines.push(help.dimmed().to_string());
+
+            // Raw mode does not translate "\n", so return the carriage explicitly
+            if drawn_lines > 0 {
+                write!(out, "{}", move_up(drawn_lines))?;
+            }
+            write!(out, "\r{}", lines.join("\r\n"))?;
+            write!(out, "{}", CLEAR_BELOW)?;
+            out.flush()?;
+            drawn_lines = lines.len() as u16 - 1;
+        }
+
+        if !is_interactive {
+            std::thread::sleep(interval_duration);
+            continue;
+        }
+        let Some(key) = events.next_key(Some(poll_duration))? else {
+            continue;
+        };
+        match key {
+            Key::Ctrl('c') | Key::Char('q') => return Ok(0),
+            Key::Up | Key::Char('k') if !is_focused => {
+                selected = Some(entries[index.saturating_sub(1)].name.clone());
+            }
+            Key::Down | Key::Char('j') if !is_focused => {
+                selected = Some(entries[(index + 1).min(entries.len() - 1)].name.clone());
+            }
+            Key::Enter => {
+                selected = Some(entries[index].name.clone());
+                is_focused = !is_focused;
+            }
+            Key::Esc => is_focused = false,
+            _ => continue,
+        }
+        needs_redraw = true;
+    }
+}
+
+/// Run the hooks whose events `state` has reached, warning about failures
+///
+/// Alerts held back by the rate limits are delivered when they are due, or
+/// right away on the `is_final` update of a run. The bell goes to stderr
+/// when the output is meant for other programs. Rate limits are measured
+/// at `now`, which a `--demo` speeds up along with the window.
+///
+/// The queue of webhook events in `outbox` is sent again when it is due.
+#[allow(clippy::too_many_arguments)]
+fn run_hooks(
+    hooks: &mut HookRegistry,
+    alerts: &mut AlertManager,
+    outbox: &mut Outbox,
+    state: &ProgressState,
+    cli: &Cli,
+    label: Option<&str>,
+    is_final: bool,
+    now: Instant,
+    out: &mut dyn Write,
+) {
+    retry_webhooks(outbox, cli, now);
+    let mut firings = alerts.admit(hooks.update(state), now);
+    if is_final {
+        firings.extend(alerts.flush(now));
+    }
+    run_firings(firings, outbox, state, cli, label, now, out);
+}
+
+/// Run hook and milestone actions admitted by the rate limits
+///
+/// Webhook events that cannot be sent are queued in `outbox`.
+fn run_firings(
+    firings: Vec<Firing>,
+    outbox: &mut Outbox,
+    state: &ProgressState,
+    cli: &Cli,
+    label: Option<&str>,
+    now: Instant,
+    out: &mut dyn Write,
+) {
+    let context = HookContext {
+        label,
+        name: cli.name().map(TimerName::as_str),
+        format: cli.duration_format(),
+    };
+    for firing in firings {
+        debug!(event = %firing.event.name(), channel = firing.action.channel(), "hook firing");
+        let result = if cli.format().is_text() {
+            firing.execute(state, &context, out)
+        } else {
+            firing.execute(state, &context, &mut std::io::stderr())
+        };
+        match result {
+            Ok(()) => {}
+            // The client has already retried; the service is down for now
+            Err(e @ PbError::Delivery { .. }) if firing.action.channel() == "post" => {
+                warn_delivery_failure(cli, "post hook", &e);
+                queue_delivery(outbox, &firing, state, &context, cli, now);
+            }
+            Err(e) => eprintln!("\rWarning: {} hook failed: {e}", firing.action.channel()),
+        }
+    }
+}
+
+/// Runs the configured hooks and milestones as the bus raises events
+///
+/// The hooks check their own milestones and overtime on every tick; alerts
+/// held back by the rate limits go out once the window completes.
+struct HookRunner<'a> {
+    hooks: &'a mut HookRegistry,
+    milestones: &'a Milestones,
+    alerts: &'a mut AlertManager,
+    outbox: &'a mut Outbox,
+    cli: &'a Cli,
+}
+
+impl Subscriber for HookRunner<'_> {
+    fn on_event(
+        &mut self,
+        event: ProgressEvent,
+        update: &ProgressUpdate,
+        out: &mut dyn Write,
+    ) -> std::io::Result<()> {
+        let is_final = match event {
+            ProgressEvent::Tick => false,
+            ProgressEvent::Completed => true,
+            ProgressEvent::Reached(index) => {
+                let firings = self.milestones.firings(index, update.state);
+                run_firings(
+                    self.alerts.admit(firings, update.now),
+                    self.outbox,
+                    update.state,
+                    self.cli,
+                    update.context.label,
+                    update.now,
+                    out,
+                );
+                return Ok(());
+            }
+            _ => return Ok(()),
+        };
+        run_hooks(
+            self.hooks,
+            self.alerts,
+            self.outbox,
+            update.state,
+            self.cli,
+            update.context.label,
+            is_final,
+            update.now,
+            out,
+        );
+        Ok(())
+    }
+}
+
+/// Exports each update to the OpenTelemetry collector
+#[cfg(feature = "otel")]
+struct TelemetryExport<'a> {
+    telemetry: &'a mut Telemetry,
+    cli: &'a Cli,
+}
+
+#[cfg(feature = "otel")]
+impl Subscriber for TelemetryExport<'_> {
+    fn on_event(
+        &mut self,
+        event: ProgressEvent,
+        update: &ProgressUpdate,
+        _out: &mut dyn Write,
+    ) -> std::io::Result<()> {
+        if event == ProgressEvent::Tick {
+            if let Err(e) = self.telemetry.export_progress(update.state) {
+                warn_delivery_failure(self.cli, "telemetry export", &e);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes the updates meant for other programs, for `--format json` and `gha`
+struct MachineOutput<'a> {
+    format: OutputFormat,
+    gha: GhaReporter,
+    checkpoints: &'a [Checkpoint],
+}
+
+impl Subscriber for MachineOutput<'_> {
+    fn on_event(
+        &mut self,
+        event: ProgressEvent,
+        update: &ProgressUpdate,
+        out: &mut dyn Write,
+    ) -> std::io::Result<()> {
+        match (self.format, event) {
+            (OutputFormat::Json, ProgressEvent::Tick) => {
+                writeln!(out, "{}", render_json(update.state, update.context))
+            }
+            (OutputFormat::Gha, ProgressEvent::Tick) => {
+                for command in self.gha.update(update.state, self.checkpoints) {
+                    writeln!(out, "{command}")?;
+                }
+                Ok(())
+            }
+            (OutputFormat::Gha, ProgressEvent::Completed) => {
+                writeln!(out, "{}", self.gha.completed(update.state))
+            }
+            // Text is drawn by the loop; snapshots are printed before it starts
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Queue the webhook event of a `post` firing that could not be sent
+#[cfg(feature = "net")]
+fn queue_delivery(
+    outbox: &mut Outbox,
+    firing: &Firing,
+    state: &ProgressState,
+    context: &HookContext,
+    cli: &Cli,
+    now: Instant,
+) {
+    if let Some(delivery) = firing.delivery(state, context, get_current_time()) {
+        if let Err(e) = outbox.push(delivery, now) {
+            warn_delivery_failure(cli, "webhook queue", &e);
+        }
+    }
+}
+
+/// This build sends no webhooks, so there is nothing to queue
+#[cfg(not(feature = "net"))]
+fn queue_delivery(
+    _outbox: &mut Outbox,
+    _firing: &Firing,
+    _state: &ProgressState,
+    _context: &HookContext,
+    _cli: &Cli,
+    _now: Instant,
+) {
+}
+
+/// Send queued webhook events again if they are due at `now`
+#[cfg(feature = "net")]
+fn retry_webhooks(outbox: &mut Outbox, cli: &Cli, now: Instant) {
+    // Each retry tries once; the queue then waits for the next interval
+    let client = HttpClient::new(RetryPolicy {
+        attempts: 1,
+        ..RetryPolicy::default()
+    });
+    match outbox.retry_due(now, get_current_time(), |delivery| {
+        deliver(delivery, &client)
+    }) {
+        Ok(0) => {}
+        Ok(sent) => debug!(sent, "queued webhook events delivered"),
+        Err(e) => warn_delivery_failure(cli, "webhook queue", &e),
+    }
+}
+
+/// This build sends no webhooks, so nothing is ever queued
+#[cfg(not(feature = "net"))]
+fn retry_webhooks(_outbox: &mut Outbox, _cli: &Cli, _now: Instant) {}
+
+/// Queue for webhook events, kept in the store when hooks are configured
+///
+/// Without a usable store the queue lasts only as long as this run.
+fn open_outbox(cli: &Cli, hooks: &HookRegistry, config: &StoreConfig) -> Outbox {
+    if hooks.is_empty() || !cfg!(feature = "net") {
+        return Outbox::default();
+    }
+    open_store(config)
+        .and_then(|store| Outbox::new(Some(store)))
+        .unwrap_or_else(|e| {
+            warn_delivery_failure(cli, "opening the webhook queue", &e);
+            Outbox::default()
+        })
+}
+
+/// Report an integration that could not be reached after retrying
+///
+/// A webhook relay or collector being down should not clutter the display,
+/// so the failure is shown only with --verbose; --debug always logs it.
+fn warn_delivery_failure(cli: &Cli, what: &str, error: &PbError) {
+    warn!(%error, "{what} failed");
+    if cli.verbose() {
+        eprintln!("\rWarning: {what} failed: {error}");
+    }
+}
+
+/// Number of notes listed beneath the bar in verbose mode
+const RECENT_NOTES: usize = 3;
+
+/// Time between updates of a `--demo`
+const DEMO_FRAME_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Minutes added to the end time per snooze
+const SNOOZE_MINUTES: i64 = 5;
+
+/// How a progress loop ended
+enum LoopOutcome {
+    /// The window elapsed at `end`, after being snoozed `snoozes` times
+    Completed {
+        snoozes: usize,
+        end: chrono::NaiveDateTime,
+    },
+    /// The user pressed Ctrl+C
+    Interrupted,
+}
+
+/// Where a progress loop draws its updates
+struct Display<'a> {
+    /// Stream receiving the bar
+    out: &'a mut dyn Write,
+    /// Whether `out` is a terminal in raw mode that can be redrawn
+    is_interactive: bool,
+    /// State file mirrored for `pmon attach` when the timer is named
+    publisher: Option<TimerPublisher>,
+    /// Hooks run as the window passes milestones and ends
+    hooks: HookRegistry,
+    /// Milestones from the configuration file, with their bar colors
+    milestones: Milestones,
+    /// Rate limits applied to the hooks' alerts
+    alerts: AlertManager,
+    /// Webhook events waiting to be sent again
+    outbox: Outbox,
+    /// Service on the session bus when `--dbus` is given
+    #[cfg(feature = "dbus")]
+    dbus: Option<DbusService>,
+    /// OpenTelemetry exporter when `--otel-endpoint` is given
+    #[cfg(feature = "otel")]
+    telemetry: Option<Telemetry>,
+}
+
+/// Run the main progress monitoring loop for one window
+///
+/// When `overall` is given, a combined bar for the whole chain is drawn
+/// beneath the window's own bar. A `next` session of a schedule is
+/// previewed beneath it, and the loop hands over to it at the end of the
+/// window without announcing completion.
+#[allow(clippy::too_many_arguments)]
+fn run_progress_loop(
+    segment: &Segment,
+    next: Option<&Segment>,
+    overall: Option<&Segment>,
+    cli: &Cli,
+    checkpoints: &[Checkpoint],
+    notes: &mut Vec<Note>,
+    display: &mut Display,
+    clock: impl Clock,
+) -> Result<LoopOutcome> {
+    let is_interactive = display.is_interactive;
+    let out = &mut *display.out;
+    // The window can be re-anchored or shifted with keys, so keep a local copy
+    let mut controls = LoopControls::new(segment.clone(), cli.time_info_style());
+    let mut events = EventLoop::new(TerminalEvents, clock);
+    let label = segment.label.as_deref();
+    // Intervals are measured on the loop's clock, which a demo speeds up
+    let interval_duration = match cli.demo() {
+        Some(_) => events.clock_time(DEMO_FRAME_INTERVAL),
+        None => Duration::from_secs(cli.interval()),
+    };
+    let mut schedule = TickSchedule::new(interval_duration, cli.align(), events.instant());
+    // A demo's frames are already few, and a saver would slow the playback
+    let mut power = PowerMonitor::new(match cli.demo() {
+        Some(_) => BatterySaver::Off,
+        None => cli.battery_saver(),
+    });
+    let mut drawn_lines: u16 = 0; // Lines printed beneath the bar on the previous update
+    let mut snoozes = 0;
+    let mut skew_detector = SkewDetector::new(events.now(), events.instant());
+    let mut forecaster = cli.progress_file().map(|_| Forecaster::default());
+    let mut burndown = cli.burndown().then(Burndown::default);
+    let mut throttle = cli.min_change().map(LineThrottle::new);
+    let mut sub_timer: Option<Segment> = None;
+    // Shown in place of the status message until the next update
+    let mut notice: Option<String> = None;
+    let mut last_tick: Option<Instant> = None;
+    display.hooks.reset();
+
+    // Everything that follows the window hears of its updates from the bus
+    let mut machine_output = MachineOutput {
+        format: cli.format(),
+        gha: GhaReporter::new(label, cli.duration_format()),
+        checkpoints,
+    };
+    let mut hook_runner = HookRunner {
+        hooks: &mut display.hooks,
+        milestones: &display.milestones,
+        alerts: &mut display.alerts,
+        outbox: &mut display.outbox,
+        cli,
+    };
+    #[cfg(feature = "otel")]
+    let mut exporter = display
+        .telemetry
+        .as_mut()
+        .map(|telemetry| TelemetryExport { telemetry, cli });
+    let mut bus = EventBus::new();
+    bus.watch(display.milestones.triggers());
+    if let Some(publisher) = display.publisher.as_mut() {
+        bus.subscribe(publisher);
+    }
+    bus.subscribe(&mut hook_runner);
+    #[cfg(feature = "dbus")]
+    if let Some(dbus) = display.dbus.as_mut() {
+        bus.subscribe(dbus);
+    }
+    #[cfg(feature = "otel")]
+    if let Some(exporter) = exporter.as_mut() {
+        bus.subscribe(exporter);
+    }
+    bus.subscribe(&mut machine_output);
+
+    loop {
+        // Get current time and calculate progress (using centralized time function)
+        let current_time = events.now();
+        let tick = events.instant();
+        schedule.set_interval(power.interval(interval_duration, tick));
+        skew_detector.observe(current_time, events.instant());
+        let (start_time, end_time) = (controls.window.start, controls.window.end);
+        let state =
+            ProgressState::with_granularity(start_time, end_time, current_time, cli.granularity());
+        let progress = state.percentage;
+        debug!(
+            percentage = progress,
+            since_last_ms = last_tick.map(|last| tick.duration_since(last).as_millis() as u64),
+            "update"
+        );
+        last_tick = Some(tick);
+        let message = select_status_message(cli.status_messages(), &state, cli.duration_format());
+        if let (Some(path), Some(forecaster)) = (cli.progress_file(), forecaster.as_mut()) {
+            // The file may not exist yet; keep the last report until it does
+            if let Some(work) = read_work_progress(path) {
+                forecaster.observe(current_time, work);
+            }
+        }
+        if let Some(burndown) = burndown.as_mut() {
+            let remaining = match forecaster.as_ref().and_then(Forecaster::latest) {
+                Some(work) => 100.0 - work,
+                None => 100.0 - progress,
+            };
+            burndown.record(progress, remaining);
+        }
+
+        let context = UpdateContext {
+            label,
+            message: message.as_deref(),
+            clock_skew: skew_detector.has_skew().then(|| skew_detector.total_skew()),
+            tags: cli.tags(),
+        };
+        let update = ProgressUpdate {
+            state: &state,
+            context: &context,
+            now: events.instant(),
+        };
+        bus.publish(&update, out)?;
+        if !cli.format().is_text() {
+            if state.is_complete() {
+                break;
+            }
+            events.sleep(schedule.remaining(events.instant(), events.now()));
+            schedule.advance(events.instant());
+            continue;
+        }
+
+        // Align the window, sub-timer, overall, and day bars when more than one is shown
+        let today = cli
+            .today()
+            .then(|| today_window(current_time, cli.day_hours()));
+        // Name the top bar so it does not pass for the day's
+        let top_label = label.or((today.is_some() && overall.is_none()).then_some("Overall"));
+        let label_width = label_column_width(
+            top_label
+                .into_iter()
+                .chain(sub_timer.as_ref().and_then(|timer| timer.label.as_deref()))
+                .chain(overall.and_then(|overall| overall.label.as_deref()))
+                .chain(today.as_ref().and_then(|today| today.label.as_deref())),
+        );
+        // Lines wider than the terminal are wrapped here rather than by it
+        let columns = is_interactive
+            .then(terminal::size)
+            .flatten()
+            .map_or(usize::MAX, |(width, _)| usize::from(width));
+        // On a narrow terminal the label column gives way to the bar
+        let label_width = fit_label_column(
+            &state,
+            &render_options(cli, controls.time_style),
+            label_width,
+            columns,
+        );
+        let top_label = top_label.filter(|_| label_width > 0);
+        let sub_label = sub_timer
+            .as_ref()
+            .and_then(|timer| timer.label.as_deref())
+            .filter(|_| label_width > 0);
+        let overall_label = overall
+            .and_then(|overall| overall.label.as_deref())
+            .filter(|_| label_width > 0);
+        let today_label = today
+            .as_ref()
+            .and_then(|today| today.label.as_deref())
+            .filter(|_| label_width > 0);
+        let fit = |label: Option<&str>| columns.saturating_sub(label_indent(label, label_width));
+
+        let lines = render_window_lines(
+            cli,
+            &state,
+            controls.time_style,
+            notice.take().as_deref().or(message.as_deref()),
+            forecaster.as_ref(),
+            fit(top_label),
+        );
+        // A milestone's or theme's color holds until overtime turns the bar red
+        let color = display
+            .milestones
+            .color(&state)
+            .or_else(|| cli.theme().and_then(|theme| theme.color(state.percentage)));
+        let lines: Vec<_> = match color {
+            Some(color) if state.percentage <= 100.0 => {
+                lines.iter().map(|line| color.paint(line)).collect()
+            }
+            _ => lines,
+        };
+        let mut lines = render_labeled_lines(top_label, label_width, &lines).into_iter();
+        let bar = lines.next().unwrap_or_default();
+        let mut extra_lines: Vec<String> = lines.collect();
+
+        if let Some(timer) = sub_timer.as_ref() {
+            let timer_bar = render_fitted(
+                &ProgressState::new(timer.start, timer.end, current_time),
+                &RenderOptions {
+                    // Zen keeps its own bar so the sub-timer shows no numbers either
+                    style: if cli.zen() {
+                        BarStyle::Zen
+                    } else {
+                        BarStyle::Thin
+                    },
+                    ..render_options(cli, TimeInfoStyle::Durations)
+                },
+                fit(sub_label),
+            );
+            extra_lines.extend(render_labeled_lines(sub_label, label_width, &timer_bar));
+        }
+        if let Some(overall) = overall {
+            let overall_bar = render_fitted(
+                &ProgressState::with_granularity(
+                    overall.start,
+                    overall.end,
+                    current_time,
+                    cli.granularity(),
+                ),
+                &render_options(cli, TimeInfoStyle::Durations),
+                fit(overall_label),
+            );
+            extra_lines.extend(render_labeled_lines(
+                overall_label,
+                label_width,
+                &overall_bar,
+            ));
+        }
+        // The day bar shares the time information on the top line
+        if let Some(today) = today.as_ref() {
+            let today_bar = render_fitted(
+                &ProgressState::new(today.start, today.end, current_time),
+                &RenderOptions {
+                    time: None,
+                    ..render_options(cli, TimeInfoStyle::Durations)
+                },
+                fit(today_label),
+            );
+            extra_lines.extend(render_labeled_lines(today_label, label_width, &today_bar));
+        }
+
+        if let Some(next) = next {
+            extra_lines.push(format!("  {}", format_next_session(next, current_time)));
+        }
+
+        // Piped output only gets the chart once, when the window is over
+        if let Some(burndown) = burndown.as_ref() {
+            if is_interactive || state.is_complete() {
+                let legend = match forecaster.as_ref().and_then(Forecaster::latest) {
+                    Some(_) => "work left",
+                    None => "time left",
+                };
+                extra_lines.extend(burndown.render(legend));
+            }
+        }
+
+        // Checkpoint countdowns and recent notes are listed beneath the bar in verbose mode
+        if cli.verbose() {
+            extra_lines.extend(render_checkpoints(
+                checkpoints,
+                current_time,
+                cli.duration_format(),
+            ));
+            extra_lines.extend(render_recent_notes(notes, RECENT_NOTES));
+            if let Some(bounds) = forecaster
+                .as_ref()
+                .and_then(|forecaster| format_forecast_bounds(forecaster, current_time))
+            {
+                extra_lines.push(format!("  {bounds}"));
+            }
+            if skew_detector.has_skew() {
+                extra_lines.push(format!(
+                    "  {}",
+                    format_skew_warning(skew_detector.total_skew()).yellow()
+                ));
+            }
+        }
+
+        // Update display
+        if is_interactive {
+            // In interactive TTY mode, move back to the bar line and overwrite it
+            if drawn_lines > 0 {
+                write!(out, "{}", move_up(drawn_lines))?;
+            }
+            write!(out, "\r{bar}")?;
+            // Raw mode does not translate "\n", so return the carriage explicitly
+            for line in &extra_lines {
+                write!(out, "{}\r\n{line}", CLEAR_LINE)?;
+            }
+            write!(out, "{}", CLEAR_LINE)?;
+            out.flush()?;
+            drawn_lines = extra_lines.len() as u16;
+        } else if cli.inplace() {
+            // Rewrite the bar's line; the lines beneath it only appear once it ends
+            write!(out, "\r{bar}{}", CLEAR_LINE)?;
+            if progress >= 100.0 {
+                writeln!(out)?;
+                for line in &extra_lines {
+                    writeln!(out, "{line}")?;
+                }
+            }
+            out.flush()?;
+        } else if throttle
+            .as_mut()
+            .is_none_or(|throttle| throttle.should_emit(progress, events.instant()))
+        {
+            // In non-interactive mode, just print the progress bar
+            writeln!(out, "{bar}")?;
+            for line in &extra_lines {
+                writeln!(out, "{line}")?;
+            }
+        }
+
+        // Check if we've completed (progress >= 100%)
+        if progress >= 100.0 && next.is_some() {
+            // Switch to the next session on the same lines of the terminal
+            if is_interactive {
+                if drawn_lines > 0 {
+                    write!(out, "{}", move_up(drawn_lines))?;
+                }
+                write!(out, "\r{CLEAR_BELOW}")?;
+            }
+            break;
+        }
+        if progress >= 100.0 {
+            let message = complete_message(cli, &state, label);
+            if !is_interactive {
+                if !message.is_empty() {
+                    writeln!(out, "{message}")?;
+                }
+            } else {
+                writeln!(out)?;
+                if !message.is_empty() {
+                    writeln!(out, "{message}")?;
+                }
+                bus.pause(&update, out)?;
+                // A locked window cannot be extended
+                if !cli.locked() && prompt_snooze(&mut events, out)? {
+                    // Extend the end and resume, like an alarm clock
+                    let now = events.now();
+                    controls
+                        .window
+                        .snooze(now, chrono::Duration::minutes(SNOOZE_MINUTES));
+                    snoozes += 1;
+                    drawn_lines = 0;
+                    continue;
+                }
+            }
+            break;
+        }
+
+        // Sleep while listening for key presses (only in interactive mode)
+        if is_interactive {
+            match events.wait(schedule.remaining(events.instant(), events.now()))? {
+                None => {}
+                Some(KeyAction::Quit) if cli.locked() => {
+                    bus.pause(&update, out)?;
+                    if read_unlock(&mut events, out)? {
+                        writeln!(out, "\nUnlocked, exiting...")?;
+                        return Ok(LoopOutcome::Interrupted);
+                    }
+                }
+                // Restarting and shifting are ignored while locked
+                Some(action) if cli.locked() && action.changes_window() => {}
+                Some(KeyAction::Quit) => {
+                    writeln!(out, "\nReceived Ctrl+C, exiting gracefully...")?;
+                    return Ok(LoopOutcome::Interrupted);
+                }
+                Some(KeyAction::TakeNote) => {
+                    // Take a timestamped note, then redraw
+                    bus.pause(&update, out)?;
+                    if let Some(text) = read_note(&mut events, out)? {
+                        notes.extend(Note::new(events.now(), &text));
+                    }
+                }
+                Some(KeyAction::StartSubTimer) => {
+                    bus.pause(&update, out)?;
+                    if let Some(timer) = read_sub_timer(&mut events, out)? {
+                        sub_timer = Some(timer);
+                    }
+                }
+                Some(KeyAction::CopySnapshot) => {
+                    let options = render_options(cli, controls.time_style);
+                    notice = Some(
+                        match clipboard::copy(&render_text(&state, label, &options)) {
+                            Ok(()) => "Copied to the clipboard".to_string(),
+                            Err(e) => e.to_string(),
+                        },
+                    );
+                }
+                // Toggle durations/timestamps, restart, or shift, and redraw immediately
+                Some(action) => {
+                    controls.apply(action, events.now());
+                    write!(out, "\r{}", CLEAR_LINE)?;
+                }
+            }
+        } else {
+            // In non-interactive mode, just sleep until the next tick
+            events.sleep(schedule.remaining(events.instant(), events.now()));
+        }
+        // A key press ends the wait early without using up the tick
+        schedule.advance(events.instant());
+    }
+
+    Ok(LoopOutcome::Completed {
+        snoozes,
+        end: controls.window.end,
+    })
+}
+
+/// The message announcing that a window has completed, possibly empty
+fn complete_message(cli: &Cli, state: &ProgressState, label: Option<&str>) -> String {
+    let template = cli
+        .complete_message()
+        .unwrap_or_else(|| cli.language().complete_message());
+    render_complete_message(template, state, label, cli.duration_format())
+}
+
+/// Parse a time given on the command line, strictly with `--strict`
+fn parse_cli_time(
+    cli: &Cli,
+    input: &str,
+    base_time: Option<chrono::NaiveDateTime>,
+) -> PbResult<ParsedTime> {
+    // Solar events fall on today's date, or follow the start for an end
+    if let Ok(event) = input.parse::<SolarEvent>() {
+        let (location, _) = solar_settings(cli)?;
+        let time = match base_time {
+            Some(base) => solar::event_after(event, location, base)?,
+            None => solar::event_on(event, location, get_current_time().date())?,
+        };
+        return Ok(ParsedTime {
+            time,
+            format: TimeFormat::Keyword,
+            base: None,
+        });
+    }
+    if cli.strict() {
+        parse_time_strict(input, base_time)
+    } else {
+        parse_time_detailed(input, base_time)
+    }
+}
+
+/// Place and dawn method for solar events: `--location` and `--method`,
+/// falling back to the `[solar]` table of the configuration file
+fn solar_settings(cli: &Cli) -> PbResult<(Location, DawnMethod)> {
+    let config = match (cli.location(), cli.method()) {
+        (Some(_), Some(_)) => None,
+        _ => Some(Config::load()?.solar),
+    };
+    let configured = config.as_ref().map(SolarConfig::location).transpose()?;
+    let location = cli
+        .location()
+        .or(configured.flatten())
+        .ok_or_else(|| {
+            PbError::invalid_argument(
+                "--location",
+                "solar events need LAT,LON here or a location in the [solar] table of the configuration file",
+            )
+        })?;
+    let method = cli
+        .method()
+        .or(config.and_then(|config| config.method))
+        .unwrap_or_default();
+    Ok((location, method))
+}
+
+/// The `--preset` window ending first after `after`, labeled with
+/// `--label` or the preset's name
+fn next_preset_window(cli: &Cli, after: chrono::NaiveDateTime) -> PbResult<Segment> {
+    let preset = cli
+        .preset()
+        .ok_or_else(|| PbError::invalid_argument("--preset", "missing"))?;
+    let (location, method) = solar_settings(cli)?;
+    let mut window = preset.next_window(location, method, after)?;
+    if let Some(label) = cli.label() {
+        window.label = Some(label.to_string());
+    }
+    Ok(window)
+}
+
+/// How a time given on the command line was read, as ` (relative to ...)`,
+/// or nothing when it was absolute
+fn parse_note(parsed: Option<&ParsedTime>) -> String {
+    parsed
+        .and_then(ParsedTime::describe)
+        .map(|note| format!(" ({note})"))
+        .unwrap_or_default()
+}
+
+/// Options for the colored text bar chosen on the command line
+fn render_options(cli: &Cli, time_style: TimeInfoStyle) -> RenderOptions {
+    RenderOptions {
+        color: true,
+        style: if cli.zen() {
+            BarStyle::Zen
+        } else if cli.overlay_percent() {
+            BarStyle::Overlay
+        } else if let Some(cells) = cli.emoji() {
+            BarStyle::Emoji(cells)
+        } else {
+            BarStyle::Classic
+        },
+        time: Some(time_style),
+        duration_format: cli.duration_format(),
+        step: cli.step(),
+        invert: cli.invert(),
+        drain: cli.drain(),
+        cells: cli
+            .theme()
+            .map_or_else(BarCells::default, |theme| theme.cells),
+        ..RenderOptions::default()
+    }
+}
+
+/// Render a window's bar with its time information, pace, work forecast,
+/// and status message
+fn render_window_bar(
+    cli: &Cli,
+    state: &ProgressState,
+    time_style: TimeInfoStyle,
+    message: Option<&str>,
+    forecaster: Option<&Forecaster>,
+) -> String {
+    // Without a column limit everything stays on one line
+    render_window_lines(cli, state, time_style, message, forecaster, usize::MAX).concat()
+}
+
+/// Render a window's bar as [`render_window_bar`] does, in lines at most
+/// `columns` wide
+///
+/// The pace, forecast, and message follow the bar while shrinking it makes
+/// room for them, and get lines of their own once it does not.
+fn render_window_lines(
+    cli: &Cli,
+    state: &ProgressState,
+    time_style: TimeInfoStyle,
+    message: Option<&str>,
+    forecaster: Option<&Forecaster>,
+    columns: usize,
+) -> Vec<String> {
+    let mut details = Vec::new();
+    let mut after = String::new();
+    if let Some(rate) = cli.pace() {
+        let pace = format_colored_pace(&Pace::evaluate(&rate, state));
+        after.push_str(&format!(" {pace}"));
+        details.push(pace);
+    }
+    if let Some(forecaster) = forecaster {
+        let forecast = format_colored_forecast(forecaster, state.current, state.end);
+        after.push_str(&format!(" {forecast}"));
+        details.push(forecast);
+    }
+    if let Some(message) = message {
+        after.push_str(&format!(" · {message}"));
+        details.push(message.to_string());
+    }
+
+    let options = render_options(cli, time_style);
+    let mut lines = render_fitted(
+        state,
+        &options,
+        columns.saturating_sub(visible_width(&after)),
+    );
+    if let [line] = lines.as_mut_slice() {
+        line.push_str(&after);
+        return lines;
+    }
+    let mut lines = render_fitted(state, &options, columns);
+    lines.extend(details);
+    lines
+}
+
+/// Read a one-line note typed on the bar line; Enter saves, Esc cancels
+fn read_note(
+    events: &mut EventLoop<impl EventSource, impl Clock>,
+    out: &mut dyn Write,
+) -> Result<Option<String>> {
+    let note = events.read_line(MAX_NOTE_LENGTH, |text| {
+        write!(out, "\rNote: {text}{}", CLEAR_LINE)?;
+        out.flush()
+    })?;
+    write!(out, "\r{}", CLEAR_LINE)?;
+    Ok(note)
+}
+
+/// Ask for [`UNLOCK_PHRASE`] on the bar line; returns whether it was typed
+fn read_unlock(
+    events: &mut EventLoop<impl EventSource, impl Clock>,
+    out: &mut dyn Write,
+) -> Result<bool> {
+    let phrase = events.read_line(UNLOCK_PHRASE.len(), |text| {
+        write!(
+            out,
+            "\rType \"{UNLOCK_PHRASE}\" to quit: {text}{}",
+            CLEAR_LINE
+        )?;
+        out.flush()
+    })?;
+    write!(out, "\r{}", CLEAR_LINE)?;
+    Ok(phrase.is_some_and(|phrase| phrase.trim().eq_ignore_ascii_case(UNLOCK_PHRASE)))
+}
+
+/// Read a sub-timer typed on the bar line, asking again until its length
+/// parses; Esc cancels
+fn read_sub_timer(
+    events: &mut EventLoop<impl EventSource, impl Clock>,
+    out: &mut dyn Write,
+) -> Result<Option<Segment>> {
+    let mut prompt = "Sub-timer (e.g. 15m Q&A): ".to_string();
+    loop {
+        let input = events.read_line(MAX_SUB_TIMER_INPUT, |text| {
+            write!(out, "\r{prompt}{text}{}", CLEAR_LINE)?;
+            out.flush()
+        })?;
+        let Some(input) = input else {
+            write!(out, "\r{}", CLEAR_LINE)?;
+            return Ok(None);
+        };
+        match parse_sub_timer(&input, events.now()) {
+            Ok(timer) => {
+                write!(out, "\r{}", CLEAR_LINE)?;
+                return Ok(Some(timer));
+            }
+            Err(e) => prompt = format!("{e}. Sub-timer: "),
+        }
+    }
+}
+
+/// Ask whether to snooze a completed window; returns true for 's'
+fn prompt_snooze(
+    events: &mut EventLoop<impl EventSource, impl Clock>,
+    out: &mut dyn Write,
+) -> Result<bool> {
+    write!(
+        out,
+        "\rPress 's' to snooze {SNOOZE_MINUTES}m, any other key to finish"
+    )?;
+    out.flush()?;
+
+    let snooze = events.confirm('s')?;
+    write!(out, "\r{}", CLEAR_LINE)?;
+    out.flush()?;
+    Ok(snooze)
+}
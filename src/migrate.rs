@@ -0,0 +1,133 @@
+//! Upgrading files written by older releases
+//!
+//! Every file pmon keeps between invocations records the version of its
+//! layout in `schema_version` (SQLite databases use `PRAGMA user_version`).
+//! When a release changes a layout it bumps that version and registers a
+//! [`Migration`] from the old one, so files written by any earlier release
+//! are upgraded step by step when they are read. Files from a newer release
+//! are refused rather than misread.
+
+/// One step upgrading data from version `from` to `from + 1`
+pub struct Migration<T: ?Sized> {
+    /// Version this step upgrades from
+    pub from: u32,
+    /// What the step changes, for error messages
+    pub description: &'static str,
+    /// Rewrite the data in place
+    pub apply: fn(&mut T) -> Result<(), String>,
+}
+
+impl<T: ?Sized> std::fmt::Debug for Migration<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Migration")
+            .field("from", &self.from)
+            .field("description", &self.description)
+            .finish()
+    }
+}
+
+/// Upgrade `data` written at `version` to `current` with `migrations`
+///
+/// Returns how many steps were applied. Fails when `version` is newer than
+/// `current`, when a step is missing from the registry, or when a step
+/// fails.
+///
+/// # Examples
+///
+/// ```
+/// use pmon::migrate::{migrate, Migration};
+///
+/// let migrations: &[Migration<Vec<u32>>] = &[Migration {
+///     from: 1,
+///     description: "add a second value",
+///     apply: |data| {
+///         data.push(2);
+///         Ok(())
+///     },
+/// }];
+/// let mut data = vec![1];
+/// assert_eq!(migrate(&mut data, 1, 2, migrations), Ok(1));
+/// assert_eq!(data, [1, 2]);
+/// assert!(migrate(&mut data, 3, 2, migrations).is_err());
+/// ```
+pub fn migrate<T: ?Sized>(
+    data: &mut T,
+    version: u32,
+    current: u32,
+    migrations: &[Migration<T>],
+) -> Result<usize, String> {
+    if version > current {
+        return Err(format!(
+            "written by a newer pmon (schema version {version}, this release reads up to {current})"
+        ));
+    }
+    for from in version..current {
+        let step = migrations
+            .iter()
+            .find(|m| m.from == from)
+            .ok_or_else(|| format!("no upgrade from schema version {from}"))?;
+        (step.apply)(data).map_err(|e| {
+            format!(
+                "upgrading from schema version {from} ({}): {e}",
+                step.description
+            )
+        })?;
+    }
+    Ok((current - version) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rename(data: &mut String) -> Result<(), String> {
+        *data = data.replace("old", "new");
+        Ok(())
+    }
+
+    fn uppercase(data: &mut String) -> Result<(), String> {
+        if data.is_empty() {
+            return Err("nothing to upgrade".to_string());
+        }
+        *data = data.to_uppercase();
+        Ok(())
+    }
+
+    const MIGRATIONS: &[Migration<String>] = &[
+        Migration {
+            from: 2,
+            description: "uppercase names",
+            apply: uppercase,
+        },
+        Migration {
+            from: 1,
+            description: "rename old names",
+            apply: rename,
+        },
+    ];
+
+    #[test]
+    fn test_migrate_applies_steps_in_version_order() {
+        let mut data = "old-timer".to_string();
+        assert_eq!(migrate(&mut data, 1, 3, MIGRATIONS), Ok(2));
+        assert_eq!(data, "NEW-TIMER");
+
+        // Data already at the current version is left alone
+        assert_eq!(migrate(&mut data, 3, 3, MIGRATIONS), Ok(0));
+        assert_eq!(data, "NEW-TIMER");
+    }
+
+    #[test]
+    fn test_migrate_reports_what_failed() {
+        let mut data = String::new();
+        let error = migrate(&mut data, 2, 3, MIGRATIONS).unwrap_err();
+        assert!(error.contains("uppercase names"), "{error}");
+        assert!(error.contains("nothing to upgrade"), "{error}");
+
+        let error = migrate(&mut data, 0, 3, MIGRATIONS).unwrap_err();
+        assert_eq!(error, "no upgrade from schema version 0");
+
+        let error = migrate(&mut data, 4, 3, MIGRATIONS).unwrap_err();
+        assert!(error.contains("newer pmon"), "{error}");
+    }
+}
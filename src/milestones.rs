@@ -0,0 +1,356 @@
+//! Milestones declared in the configuration file
+//!
+//! A milestone is reached at a percentage of the window, at a time of day,
+//! or at a distance from the start or end of the window. It can run a
+//! command, show a desktop notification, speak, and change the color of
+//! the bar from then on:
+//!
+//! ```toml
+//! [[milestones]]
+//! at = "50%"
+//! notify = "Halfway through {label}"
+//!
+//! [[milestones]]
+//! at = "-15m"                  # before the end; "+20m" is after the start
+//! run = ["blink1-tool", "--rgb", "orange"]
+//! color = "yellow"
+//!
+//! [[milestones]]
+//! at = "12:30"                 # a time of day within the window
+//! say = "Lunch in {remaining}" # read aloud, as with hooks
+//! color = "red"
+//! ```
+//!
+//! Commands, notifications, and spoken texts take the placeholders of the hooks module,
+//! with `{event}` naming the percentage the milestone fell on, such as
+//! `milestone-75`. Like hooks, milestones already passed when pmon starts
+//! do not run their actions, but their colors still apply.
+
+use crate::color::BarColor;
+use crate::deadline::parse_lead_time;
+use crate::error::{PbError, PbResult};
+use crate::hooks::{Firing, HookAction, HookEvent};
+use crate::progress_state::ProgressState;
+use chrono::{Duration, NaiveTime};
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// Option name used in error messages
+const OPTION_NAME: &str = "milestones";
+
+/// A `[[milestones]]` table in the configuration file
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MilestoneConfig {
+    /// When the milestone is reached, such as `50%`, `12:30`, or `-15m`
+    pub at: String,
+    /// Command and arguments to run
+    #[serde(default)]
+    pub run: Vec<String>,
+    /// Desktop notification text
+    #[serde(default)]
+    pub notify: Option<String>,
+    /// Text read aloud
+    #[serde(default)]
+    pub say: Option<String>,
+    /// Color of the bar once the milestone is reached
+    #[serde(default)]
+    pub color: Option<BarColor>,
+}
+
+/// When a milestone is reached
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MilestoneTrigger {
+    /// `50%`, a percentage of the window
+    Percent(f64),
+    /// `12:30`, the first such time of day from the start of the window
+    TimeOfDay(NaiveTime),
+    /// `+20m`, a duration after the start
+    AfterStart(Duration),
+    /// `-15m`, a duration before the end
+    BeforeEnd(Duration),
+}
+
+impl MilestoneTrigger {
+    /// Percentage of the window at which the trigger is reached
+    ///
+    /// Times outside the window give percentages below 0 or above 100.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::NaiveDateTime;
+    /// use pmon::milestones::MilestoneTrigger;
+    /// use pmon::ProgressState;
+    ///
+    /// let at = |time| NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M").unwrap();
+    /// let state = ProgressState::new(at("2025-01-27 09:00"), at("2025-01-27 10:00"), at("2025-01-27 09:10"));
+    /// let trigger: MilestoneTrigger = "-15m".parse().unwrap();
+    /// assert_eq!(trigger.percentage(&state), 75.0);
+    /// let trigger: MilestoneTrigger = "9:30".parse().unwrap();
+    /// assert_eq!(trigger.percentage(&state), 50.0);
+    /// ```
+    pub fn percentage(&self, state: &ProgressState) -> f64 {
+        let at = match *self {
+            MilestoneTrigger::Percent(percent) => return percent,
+            MilestoneTrigger::TimeOfDay(time) => {
+                let at = state.start.date().and_time(time);
+                if at < state.start {
+                    at + Duration::days(1)
+                } else {
+                    at
+                }
+            }
+            MilestoneTrigger::AfterStart(offset) => state.start + offset,
+            MilestoneTrigger::BeforeEnd(offset) => state.end - offset,
+        };
+        ProgressState::new(state.start, state.end, at).percentage
+    }
+
+    /// Whether the window in `state` has reached the trigger
+    pub fn is_reached(&self, state: &ProgressState) -> bool {
+        state.percentage >= self.percentage(state)
+    }
+}
+
+impl FromStr for MilestoneTrigger {
+    type Err = PbError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+        let invalid = || PbError::invalid_argument(OPTION_NAME, input);
+        if let Some(percent) = input.strip_suffix('%') {
+            let percent: f64 = percent.trim().parse().map_err(|_| invalid())?;
+            if !(percent > 0.0 && percent <= 100.0) {
+                return Err(invalid());
+            }
+            return Ok(MilestoneTrigger::Percent(percent));
+        }
+        if let Some(offset) = input.strip_prefix('-') {
+            return Ok(MilestoneTrigger::BeforeEnd(
+                parse_lead_time(offset).map_err(|_| invalid())?,
+            ));
+        }
+        if let Some(offset) = input.strip_prefix('+') {
+            return Ok(MilestoneTrigger::AfterStart(
+                parse_lead_time(offset).map_err(|_| invalid())?,
+            ));
+        }
+        NaiveTime::parse_from_str(input, "%H:%M")
+            .or_else(|_| NaiveTime::parse_from_str(input, "%H:%M:%S"))
+            .map(MilestoneTrigger::TimeOfDay)
+            .map_err(|_| invalid())
+    }
+}
+
+/// A validated milestone
+#[derive(Debug, Clone, PartialEq)]
+pub struct Milestone {
+    /// When the milestone is reached
+    pub trigger: MilestoneTrigger,
+    /// Actions run when it is reached
+    pub actions: Vec<HookAction>,
+    /// Color of the bar from then on
+    pub color: Option<BarColor>,
+}
+
+impl Milestone {
+    fn from_config(config: &MilestoneConfig) -> PbResult<Self> {
+        let invalid = |reason: &str| PbError::invalid_argument(OPTION_NAME, reason);
+        let trigger = config.at.parse()?;
+        let mut actions = Vec::new();
+        if !config.run.is_empty() {
+            actions.push(HookAction::Run(config.run.clone()));
+        }
+        if let Some(text) = &config.notify {
+            if !cfg!(feature = "notifications") {
+                return Err(invalid(
+                    "pmon was built without the `notifications` feature",
+                ));
+            }
+            actions.push(HookAction::Notify(text.clone()));
+        }
+        if let Some(text) = &config.say {
+            actions.push(HookAction::Say(text.clone()));
+        }
+        if actions.is_empty() && config.color.is_none() {
+            return Err(invalid("a milestone needs run, notify, say, or color"));
+        }
+        Ok(Self {
+            trigger,
+            actions,
+            color: config.color,
+        })
+    }
+}
+
+/// The configured milestones, in the order of the configuration file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Milestones {
+    milestones: Vec<Milestone>,
+}
+
+impl Milestones {
+    /// Validate the `[[milestones]]` tables of the configuration
+    pub fn from_config(configs: &[MilestoneConfig]) -> PbResult<Self> {
+        let milestones = configs
+            .iter()
+            .map(Milestone::from_config)
+            .collect::<PbResult<Vec<_>>>()?;
+        Ok(Self { milestones })
+    }
+
+    /// Whether no milestones are configured
+    pub fn is_empty(&self) -> bool {
+        self.milestones.is_empty()
+    }
+
+    /// Triggers of the milestones, for the event bus to watch
+    pub fn triggers(&self) -> Vec<MilestoneTrigger> {
+        self.milestones
+            .iter()
+            .map(|milestone| milestone.trigger)
+            .collect()
+    }
+
+    /// Actions due when the window in `state` reaches the milestone at `index`
+    pub fn firings(&self, index: usize, state: &ProgressState) -> Vec<Firing> {
+        let Some(milestone) = self.milestones.get(index) else {
+            return Vec::new();
+        };
+        let percent = milestone.trigger.percentage(state).clamp(0.0, 100.0) as u16;
+        milestone
+            .actions
+            .iter()
+            .map(|action| Firing {
+                event: HookEvent::Milestone(percent),
+                action: action.clone(),
+            })
+            .collect()
+    }
+
+    /// Color of the bar in `state`, set by the latest milestone reached
+    pub fn color(&self, state: &ProgressState) -> Option<BarColor> {
+        self.milestones
+            .iter()
+            .filter_map(|milestone| {
+                let at = milestone.trigger.percentage(state);
+                milestone
+                    .color
+                    .filter(|_| state.percentage >= at)
+                    .map(|color| (at, color))
+            })
+            .max_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, color)| color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn state_at(current: &str) -> ProgressState {
+        ProgressState::new(
+            create_test_datetime("2025-01-27 09:00:00"),
+            create_test_datetime("2025-01-27 10:00:00"),
+            create_test_datetime(current),
+        )
+    }
+
+    fn milestones(toml: &str) -> PbResult<Milestones> {
+        #[derive(Deserialize)]
+        struct File {
+            milestones: Vec<MilestoneConfig>,
+        }
+        let file: File = toml::from_str(toml).unwrap();
+        Milestones::from_config(&file.milestones)
+    }
+
+    fn trigger(input: &str) -> MilestoneTrigger {
+        input.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_triggers() {
+        assert_eq!(trigger("50%"), MilestoneTrigger::Percent(50.0));
+        assert_eq!(
+            trigger("-15m"),
+            MilestoneTrigger::BeforeEnd(Duration::minutes(15))
+        );
+        assert_eq!(
+            trigger("+1h"),
+            MilestoneTrigger::AfterStart(Duration::hours(1))
+        );
+        assert_eq!(
+            trigger("12:30"),
+            MilestoneTrigger::TimeOfDay(NaiveTime::from_hms_opt(12, 30, 0).unwrap())
+        );
+        for input in ["", "0%", "101%", "half%", "-15", "+soon", "25:00", "noon"] {
+            assert!(
+                input.parse::<MilestoneTrigger>().is_err(),
+                "Expected {input:?} to be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_time_of_day_after_midnight() {
+        let state = ProgressState::new(
+            create_test_datetime("2025-01-27 22:00:00"),
+            create_test_datetime("2025-01-28 02:00:00"),
+            create_test_datetime("2025-01-27 23:00:00"),
+        );
+        assert_eq!(trigger("01:00").percentage(&state), 75.0);
+        assert!(!trigger("01:00").is_reached(&state));
+    }
+
+    #[test]
+    fn test_color_follows_the_latest_milestone() {
+        let milestones = milestones(
+            "[[milestones]]\nat = \"-10m\"\ncolor = \"red\"\n\n\
+             [[milestones]]\nat = \"50%\"\ncolor = \"yellow\"\n",
+        )
+        .unwrap();
+        assert_eq!(milestones.color(&state_at("2025-01-27 09:10:00")), None);
+        assert_eq!(
+            milestones.color(&state_at("2025-01-27 09:30:00")),
+            Some(BarColor::Yellow)
+        );
+        assert_eq!(
+            milestones.color(&state_at("2025-01-27 09:55:00")),
+            Some(BarColor::Red)
+        );
+    }
+
+    #[test]
+    fn test_firings_name_the_percentage() {
+        let milestones =
+            milestones("[[milestones]]\nat = \"-15m\"\nrun = [\"echo\", \"{event}\"]\n").unwrap();
+        let firings = milestones.firings(0, &state_at("2025-01-27 09:45:00"));
+        assert_eq!(
+            firings,
+            [Firing {
+                event: HookEvent::Milestone(75),
+                action: HookAction::Run(vec!["echo".to_string(), "{event}".to_string()]),
+            }]
+        );
+        assert!(milestones
+            .firings(1, &state_at("2025-01-27 09:45:00"))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_milestone_needs_an_action() {
+        let error = milestones("[[milestones]]\nat = \"50%\"\n").unwrap_err();
+        assert!(
+            error.to_string().contains("run, notify, say, or color"),
+            "{error}"
+        );
+        assert!(milestones("[[milestones]]\nat = \"soon\"\ncolor = \"red\"\n").is_err());
+    }
+}
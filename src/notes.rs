@@ -0,0 +1,113 @@
+//! Timestamped notes taken while a window runs
+//!
+//! Pressing `n` in the interactive view lets the user type a short note.
+//! Notes are stamped with the time they were taken, listed beneath the bar in
+//! verbose mode, and repeated in the completion summary so a run doubles as
+//! a lightweight time log. The notes of a named timer are kept with its run
+//! in the history, and listed by `pmon history`.
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+/// Longest note accepted, in characters
+pub const MAX_NOTE_LENGTH: usize = 200;
+
+/// A note taken at a point in time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Note {
+    /// Moment the note was taken
+    pub at: NaiveDateTime,
+    /// Note text
+    pub text: String,
+}
+
+impl Note {
+    /// Create a note, trimming whitespace and capping its length
+    ///
+    /// Returns `None` for notes that are empty after trimming.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::NaiveDateTime;
+    /// use pmon::notes::Note;
+    ///
+    /// let at = NaiveDateTime::parse_from_str("2025-01-27 10:15:00", "%Y-%m-%d %H:%M:%S").unwrap();
+    /// assert_eq!(Note::new(at, "  started late  ").unwrap().text, "started late");
+    /// assert!(Note::new(at, "   ").is_none());
+    /// ```
+    pub fn new(at: NaiveDateTime, text: &str) -> Option<Self> {
+        let text: String = text.trim().chars().take(MAX_NOTE_LENGTH).collect();
+        if text.is_empty() {
+            return None;
+        }
+        Some(Self { at, text })
+    }
+}
+
+/// Format a note as `HH:MM  text`
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDateTime;
+/// use pmon::notes::{format_note, Note};
+///
+/// let at = NaiveDateTime::parse_from_str("2025-01-27 10:15:00", "%Y-%m-%d %H:%M:%S").unwrap();
+/// let note = Note::new(at, "started late").unwrap();
+/// assert_eq!(format_note(&note), "10:15  started late");
+/// ```
+pub fn format_note(note: &Note) -> String {
+    format!("{}  {}", note.at.format("%H:%M"), note.text)
+}
+
+/// Render the most recent `limit` notes, oldest first, indented for display
+/// beneath the progress bar
+pub fn render_recent_notes(notes: &[Note], limit: usize) -> Vec<String> {
+    let skip = notes.len().saturating_sub(limit);
+    notes[skip..]
+        .iter()
+        .map(|note| format!("  ✎ {}", format_note(note)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn note(time: &str, text: &str) -> Note {
+        Note::new(create_test_datetime(time), text).unwrap()
+    }
+
+    #[test]
+    fn test_new_caps_length() {
+        let long = "x".repeat(MAX_NOTE_LENGTH + 50);
+        let note = Note::new(create_test_datetime("2025-01-27 10:00:00"), &long).unwrap();
+        assert_eq!(note.text.chars().count(), MAX_NOTE_LENGTH);
+
+        // Multi-byte text is capped by characters, not bytes
+        let long = "締".repeat(MAX_NOTE_LENGTH + 1);
+        let note = Note::new(create_test_datetime("2025-01-27 10:00:00"), &long).unwrap();
+        assert_eq!(note.text.chars().count(), MAX_NOTE_LENGTH);
+    }
+
+    #[test]
+    fn test_render_recent_notes() {
+        let notes = vec![
+            note("2025-01-27 10:00:00", "kickoff"),
+            note("2025-01-27 10:20:00", "blocked on review"),
+            note("2025-01-27 10:45:00", "unblocked"),
+        ];
+
+        assert_eq!(
+            render_recent_notes(&notes, 2),
+            vec!["  ✎ 10:20  blocked on review", "  ✎ 10:45  unblocked"]
+        );
+        assert_eq!(render_recent_notes(&notes, 10).len(), 3);
+        assert!(render_recent_notes(&[], 3).is_empty());
+    }
+}
@@ -0,0 +1,416 @@
+//! OpenTelemetry export over OTLP/HTTP
+//!
+//! With the `otel` feature, `--otel-endpoint http://localhost:4318` sends
+//! the run to an OpenTelemetry collector: gauges for the percent elapsed
+//! and the seconds remaining as the window progresses, and one span
+//! covering the whole run when pmon exits. Both carry the timer's label and
+//! name, so platform teams can line pmon windows up with the rest of their
+//! telemetry. Data is posted as OTLP JSON over plain HTTP; export failures
+//! never stop the timer.
+
+use crate::error::{PbError, PbResult};
+use crate::http::{HttpClient, HttpUrl};
+use crate::progress_state::ProgressState;
+use crate::store::RunOutcome;
+use crate::timezone::{civil_to_utc, configured_timezone};
+use chrono::{Local, NaiveDateTime, TimeZone};
+use serde_json::{json, Value};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// Option name used in error messages
+const OPTION_NAME: &str = "--otel-endpoint";
+
+/// Port OTLP/HTTP collectors listen on by default
+const DEFAULT_PORT: u16 = 4318;
+
+/// Service name reported in the resource attributes
+const SERVICE_NAME: &str = "pmon";
+
+/// Least time between two metric exports
+pub const METRIC_INTERVAL: Duration = Duration::from_secs(10);
+
+/// An OTLP/HTTP collector such as `http://localhost:4318`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OtlpEndpoint {
+    base: HttpUrl,
+}
+
+impl OtlpEndpoint {
+    /// URL of the collector path for one signal, such as `/v1/traces`
+    pub fn url(&self, signal_path: &str) -> String {
+        self.base.join(signal_path).to_string()
+    }
+
+    /// POST a JSON document to the collector path for one signal
+    pub fn post(&self, signal_path: &str, body: &str) -> PbResult<()> {
+        HttpClient::default().post_json(&self.base.join(signal_path), body)
+    }
+}
+
+impl FromStr for OtlpEndpoint {
+    type Err = PbError;
+
+    /// Parse `http://host[:port][/path]`, defaulting to port 4318
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Ok(Self {
+            base: HttpUrl::parse_with_default_port(input, DEFAULT_PORT, OPTION_NAME)?,
+        })
+    }
+}
+
+/// Nanoseconds since the Unix epoch of a civil time
+fn unix_nanos(civil: NaiveDateTime) -> String {
+    let utc = match configured_timezone() {
+        Some(tz) => civil_to_utc(tz, civil),
+        None => Local
+            .from_local_datetime(&civil)
+            .earliest()
+            .map_or(civil, |time| time.naive_utc()),
+    };
+    utc.and_utc()
+        .timestamp_nanos_opt()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// A random identifier of `bytes` bytes, as lowercase hex
+fn random_id(bytes: usize) -> String {
+    let mut id = String::new();
+    while id.len() < bytes * 2 {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u128(
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos(),
+        );
+        hasher.write_u32(std::process::id());
+        id.push_str(&format!("{:016x}", hasher.finish()));
+    }
+    id.truncate(bytes * 2);
+    id
+}
+
+/// Key/value attribute in OTLP JSON
+fn attribute(key: &str, value: &str) -> Value {
+    json!({ "key": key, "value": { "stringValue": value } })
+}
+
+/// Exports one pmon run to an OTLP collector
+#[derive(Debug)]
+pub struct Telemetry {
+    endpoint: OtlpEndpoint,
+    label: Option<String>,
+    name: Option<String>,
+    trace_id: String,
+    span_id: String,
+    last_export: Option<Instant>,
+    failed: bool,
+}
+
+impl Telemetry {
+    /// Export a run shown with `label`, shared as `name` if it is named
+    pub fn new(endpoint: OtlpEndpoint, label: Option<&str>, name: Option<&str>) -> Self {
+        Self {
+            endpoint,
+            label: label.map(str::to_string),
+            name: name.map(str::to_string),
+            trace_id: random_id(16),
+            span_id: random_id(8),
+            last_export: None,
+            failed: false,
+        }
+    }
+
+    /// Attributes identifying the timer on every span and data point
+    fn attributes(&self) -> Vec<Value> {
+        let mut attributes = Vec::new();
+        if let Some(label) = &self.label {
+            attributes.push(attribute("pmon.label", label));
+        }
+        if let Some(name) = &self.name {
+            attributes.push(attribute("pmon.name", name));
+        }
+        attributes
+    }
+
+    /// Resource and instrumentation scope wrapped around every export
+    fn envelope(
+        &self,
+        resource_key: &str,
+        scope_key: &str,
+        items_key: &str,
+        items: Value,
+    ) -> Value {
+        json!({
+            resource_key: [{
+                "resource": { "attributes": [attribute("service.name", SERVICE_NAME)] },
+                scope_key: [{
+                    "scope": { "name": SERVICE_NAME, "version": env!("CARGO_PKG_VERSION") },
+                    items_key: items,
+                }],
+            }]
+        })
+    }
+
+    /// OTLP metrics document with the percent and remaining gauges for `state`
+    pub fn metrics_payload(&self, state: &ProgressState) -> Value {
+        let time = unix_nanos(state.current);
+        let attributes = self.attributes();
+        let percent = if state.percentage.is_finite() {
+            state.percentage
+        } else {
+            0.0
+        };
+        let metrics = json!([
+            {
+                "name": "pmon.progress",
+                "description": "Percent of the time window elapsed",
+                "unit": "%",
+                "gauge": { "dataPoints": [{
+                    "timeUnixNano": time,
+                    "asDouble": percent,
+                    "attributes": attributes,
+                }] },
+            },
+            {
+                "name": "pmon.remaining",
+                "description": "Time left until the end of the window",
+                "unit": "s",
+                "gauge": { "dataPoints": [{
+                    "timeUnixNano": time,
+                    "asInt": state.remaining().num_seconds().to_string(),
+                    "attributes": attributes,
+                }] },
+            },
+        ]);
+        self.envelope("resourceMetrics", "scopeMetrics", "metrics", metrics)
+    }
+
+    /// OTLP traces document with one span from `start` to `finished`
+    pub fn span_payload(
+        &self,
+        start: NaiveDateTime,
+        planned_end: NaiveDateTime,
+        finished: NaiveDateTime,
+        outcome: RunOutcome,
+    ) -> Value {
+        let mut attributes = self.attributes();
+        attributes.push(attribute(
+            "pmon.planned_end",
+            &planned_end.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        ));
+        attributes.push(attribute("pmon.outcome", outcome.name()));
+        // STATUS_CODE_OK for a window that ran out, UNSET when it was stopped
+        let status_code = match outcome {
+            RunOutcome::Completed => 1,
+            RunOutcome::Interrupted => 0,
+        };
+        let span = json!([{
+            "traceId": self.trace_id,
+            "spanId": self.span_id,
+            "name": self.label.as_deref().unwrap_or("pmon window"),
+            "kind": 1,
+            "startTimeUnixNano": unix_nanos(start),
+            "endTimeUnixNano": unix_nanos(finished),
+            "attributes": attributes,
+            "status": { "code": status_code },
+        }]);
+        self.envelope("resourceSpans", "scopeSpans", "spans", span)
+    }
+
+    /// Export the gauges for `state`, at most once per [`METRIC_INTERVAL`]
+    ///
+    /// The final update of a window is always exported. Only the first
+    /// failure is returned, so an unreachable collector warns once.
+    pub fn export_progress(&mut self, state: &ProgressState) -> PbResult<()> {
+        let now = Instant::now();
+        let is_due = self
+            .last_export
+            .is_none_or(|last| now.duration_since(last) >= METRIC_INTERVAL);
+        if !is_due && !state.is_complete() {
+            return Ok(());
+        }
+        self.last_export = Some(now);
+        let result = self
+            .endpoint
+            .post("/v1/metrics", &self.metrics_payload(state).to_string());
+        self.first_failure(result)
+    }
+
+    /// Export the span covering the whole run
+    pub fn export_run(
+        &mut self,
+        start: NaiveDateTime,
+        planned_end: NaiveDateTime,
+        finished: NaiveDateTime,
+        outcome: RunOutcome,
+    ) -> PbResult<()> {
+        let payload = self.span_payload(start, planned_end, finished, outcome);
+        let result = self.endpoint.post("/v1/traces", &payload.to_string());
+        self.first_failure(result)
+    }
+
+    fn first_failure(&mut self, result: PbResult<()>) -> PbResult<()> {
+        match result {
+            Err(e) if !self.failed => {
+                self.failed = true;
+                Err(e)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn telemetry(endpoint: &str) -> Telemetry {
+        Telemetry::new(endpoint.parse().unwrap(), Some("Sprint"), Some("sprint"))
+    }
+
+    #[test]
+    fn test_parse_endpoint() {
+        let endpoint: OtlpEndpoint = "http://localhost:4318".parse().unwrap();
+        assert_eq!(
+            endpoint.url("/v1/traces"),
+            "http://localhost:4318/v1/traces"
+        );
+
+        let endpoint: OtlpEndpoint = "http://collector/otlp/".parse().unwrap();
+        assert_eq!(
+            endpoint.url("/v1/metrics"),
+            "http://collector:4318/otlp/v1/metrics"
+        );
+
+        let endpoint: OtlpEndpoint = "http://[::1]:9000".parse().unwrap();
+        assert_eq!(endpoint.url("/v1/traces"), "http://[::1]:9000/v1/traces");
+    }
+
+    #[test]
+    fn test_parse_invalid_endpoint() {
+        for input in [
+            "",
+            "localhost:4318",
+            "https://collector",
+            "http://",
+            "http://host:port",
+            "http://host:0",
+            "http://[::1",
+        ] {
+            assert!(
+                matches!(
+                    input.parse::<OtlpEndpoint>(),
+                    Err(PbError::InvalidArgument { .. })
+                ),
+                "Expected '{input}' to be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_random_ids() {
+        let id = random_id(16);
+        assert_eq!(id.len(), 32);
+        assert!(id.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(random_id(8), random_id(8));
+    }
+
+    #[test]
+    fn test_metrics_payload() {
+        let state = ProgressState::new(
+            create_test_datetime("2025-01-27 09:00:00"),
+            create_test_datetime("2025-01-27 10:00:00"),
+            create_test_datetime("2025-01-27 09:15:00"),
+        );
+        let payload = telemetry("http://localhost").metrics_payload(&state);
+        let scope = &payload["resourceMetrics"][0]["scopeMetrics"][0];
+        assert_eq!(scope["scope"]["name"], "pmon");
+
+        let metrics = scope["metrics"].as_array().unwrap();
+        assert_eq!(metrics[0]["name"], "pmon.progress");
+        let point = &metrics[0]["gauge"]["dataPoints"][0];
+        assert_eq!(point["asDouble"], 25.0);
+        assert_eq!(point["attributes"][0]["key"], "pmon.label");
+        assert_eq!(point["attributes"][1]["value"]["stringValue"], "sprint");
+        assert_eq!(metrics[1]["name"], "pmon.remaining");
+        assert_eq!(metrics[1]["gauge"]["dataPoints"][0]["asInt"], "2700");
+    }
+
+    #[test]
+    fn test_span_payload() {
+        let telemetry = telemetry("http://localhost");
+        let payload = telemetry.span_payload(
+            create_test_datetime("2025-01-27 09:00:00"),
+            create_test_datetime("2025-01-27 10:00:00"),
+            create_test_datetime("2025-01-27 09:30:00"),
+            RunOutcome::Interrupted,
+        );
+        let span = &payload["resourceSpans"][0]["scopeSpans"][0]["spans"][0];
+        assert_eq!(span["name"], "Sprint");
+        assert_eq!(span["traceId"].as_str().unwrap().len(), 32);
+        assert_eq!(span["spanId"].as_str().unwrap().len(), 16);
+        assert_eq!(span["status"]["code"], 0);
+        let start: i64 = span["startTimeUnixNano"].as_str().unwrap().parse().unwrap();
+        let end: i64 = span["endTimeUnixNano"].as_str().unwrap().parse().unwrap();
+        assert_eq!(end - start, 30 * 60 * 1_000_000_000);
+        assert!(span["attributes"]
+            .as_array()
+            .unwrap()
+            .contains(&attribute("pmon.outcome", "interrupted")));
+    }
+
+    #[test]
+    fn test_post_to_collector() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 1024];
+            while !request.ends_with(b"{}") {
+                let read = stream.read(&mut buffer).unwrap();
+                request.extend_from_slice(&buffer[..read]);
+            }
+            stream.write_all(b"HTTP/1.1 200 OK\r\n\r\n").unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        let endpoint: OtlpEndpoint = format!("http://127.0.0.1:{port}/otlp").parse().unwrap();
+        endpoint.post("/v1/traces", "{}").unwrap();
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /otlp/v1/traces HTTP/1.1\r\n"));
+        assert!(request.contains("Content-Type: application/json\r\n"));
+    }
+
+    #[test]
+    fn test_unreachable_collector_fails_once() {
+        // Bind and drop a listener to find a port nobody is listening on
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let mut telemetry = telemetry(&format!("http://127.0.0.1:{port}"));
+        let state = ProgressState::new(
+            create_test_datetime("2025-01-27 09:00:00"),
+            create_test_datetime("2025-01-27 10:00:00"),
+            create_test_datetime("2025-01-27 10:00:00"),
+        );
+        assert!(matches!(
+            telemetry.export_progress(&state),
+            Err(PbError::Delivery { .. })
+        ));
+        assert!(telemetry.export_progress(&state).is_ok());
+    }
+}
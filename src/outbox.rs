@@ -0,0 +1,214 @@
+//! Webhook events kept until they can be delivered
+//!
+//! A `post` hook whose service cannot be reached, for example while the
+//! laptop is offline, does not lose its event. The request is queued in the
+//! store and sent again every [`RETRY_INTERVAL`] while pmon runs, and as soon
+//! as the next pmon with hooks starts. Events older than [`MAX_AGE_HOURS`]
+//! are dropped, as are the oldest ones when more than [`MAX_PENDING`] wait.
+
+#[cfg(feature = "net")]
+use crate::error::PbError;
+use crate::error::PbResult;
+#[cfg(feature = "net")]
+use crate::http::{HttpClient, HttpUrl};
+#[cfg(feature = "net")]
+use crate::secrets::SecretRef;
+use crate::store::{PendingDelivery, Store};
+use chrono::NaiveDateTime;
+use std::time::{Duration, Instant};
+
+/// Time between two attempts to send the queue
+pub const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Most deliveries kept waiting
+pub const MAX_PENDING: usize = 100;
+
+/// Hours after which a waiting delivery is no longer worth sending
+pub const MAX_AGE_HOURS: i64 = 24;
+
+/// Send a webhook request, reading its token from where it is kept
+#[cfg(feature = "net")]
+pub fn deliver(delivery: &PendingDelivery, client: &HttpClient) -> PbResult<()> {
+    let url: HttpUrl = delivery.url.parse()?;
+    let token = delivery
+        .token
+        .as_deref()
+        .map(|token| token.parse::<SecretRef>()?.resolve())
+        .transpose()
+        .map_err(|e| PbError::delivery(&delivery.url, e))?;
+    client.post_json_authorized(&url, &delivery.body, token.as_deref())
+}
+
+/// Deliveries waiting to be sent again, saved in the store when there is one
+#[derive(Default)]
+pub struct Outbox {
+    store: Option<Box<dyn Store>>,
+    pending: Vec<PendingDelivery>,
+    next_retry: Option<Instant>,
+}
+
+impl Outbox {
+    /// A queue kept in `store`, starting with what earlier runs left there
+    ///
+    /// Without a store the queue only lasts as long as this process.
+    pub fn new(store: Option<Box<dyn Store>>) -> PbResult<Self> {
+        let pending = match &store {
+            Some(store) => store.pending_deliveries()?,
+            None => Vec::new(),
+        };
+        Ok(Self {
+            store,
+            pending,
+            next_retry: None,
+        })
+    }
+
+    /// Deliveries waiting, oldest first
+    pub fn pending(&self) -> &[PendingDelivery] {
+        &self.pending
+    }
+
+    /// Queue a delivery whose attempt at `now` failed
+    pub fn push(&mut self, delivery: PendingDelivery, now: Instant) -> PbResult<()> {
+        self.pending.push(PendingDelivery {
+            attempts: delivery.attempts + 1,
+            ..delivery
+        });
+        let excess = self.pending.len().saturating_sub(MAX_PENDING);
+        self.pending.drain(..excess);
+        self.next_retry.get_or_insert(now + RETRY_INTERVAL);
+        self.save()
+    }
+
+    /// Send the waiting deliveries with `send` if a retry is due at `now`
+    ///
+    /// `at` is the wall-clock time, against which old deliveries expire.
+    /// Returns how many were delivered; the rest keep waiting.
+    pub fn retry_due(
+        &mut self,
+        now: Instant,
+        at: NaiveDateTime,
+        mut send: impl FnMut(&PendingDelivery) -> PbResult<()>,
+    ) -> PbResult<usize> {
+        if self.pending.is_empty() || self.next_retry.is_some_and(|due| now < due) {
+            return Ok(0);
+        }
+        self.next_retry = Some(now + RETRY_INTERVAL);
+        let max_age = chrono::Duration::hours(MAX_AGE_HOURS);
+        let mut delivered = 0;
+        let mut waiting = Vec::new();
+        for delivery in std::mem::take(&mut self.pending) {
+            if at - delivery.queued_at > max_age {
+                continue;
+            }
+            match send(&delivery) {
+                Ok(()) => delivered += 1,
+                Err(_) => waiting.push(PendingDelivery {
+                    attempts: delivery.attempts + 1,
+                    ..delivery
+                }),
+            }
+        }
+        self.pending = waiting;
+        self.save()?;
+        Ok(delivered)
+    }
+
+    /// Keep the queue in the store
+    fn save(&mut self) -> PbResult<()> {
+        match self.store.as_mut() {
+            Some(store) => store.set_pending_deliveries(&self.pending),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::PbError;
+    use crate::store::JsonStore;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn delivery(event: &str, queued_at: &str) -> PendingDelivery {
+        PendingDelivery {
+            url: "http://relay.local:80/pmon".to_string(),
+            body: format!("{{\"event\":\"{event}\"}}"),
+            token: None,
+            queued_at: create_test_datetime(queued_at),
+            attempts: 0,
+        }
+    }
+
+    fn offline(delivery: &PendingDelivery) -> PbResult<()> {
+        Err(PbError::delivery(&delivery.url, "network is unreachable"))
+    }
+
+    #[test]
+    fn test_queue_survives_until_delivered() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+        let open = || Outbox::new(Some(Box::new(JsonStore::open(path.clone()).unwrap()))).unwrap();
+        let start = Instant::now();
+        let at = create_test_datetime("2025-01-27 09:30:00");
+
+        let mut outbox = open();
+        outbox
+            .push(delivery("milestone-50", "2025-01-27 09:30:00"), start)
+            .unwrap();
+        // Not due until the retry interval has passed
+        assert_eq!(outbox.retry_due(start, at, offline).unwrap(), 0);
+        assert_eq!(
+            outbox
+                .retry_due(start + RETRY_INTERVAL, at, offline)
+                .unwrap(),
+            0
+        );
+        assert_eq!(outbox.pending()[0].attempts, 2);
+
+        // The next run sends what this one could not, right away
+        let mut sent = Vec::new();
+        let mut outbox = open();
+        let delivered = outbox
+            .retry_due(start, at, |delivery| {
+                sent.push(delivery.body.clone());
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(delivered, 1);
+        assert_eq!(sent, ["{\"event\":\"milestone-50\"}"]);
+        assert!(open().pending().is_empty());
+    }
+
+    #[test]
+    fn test_old_and_excess_deliveries_are_dropped() {
+        let mut outbox = Outbox::new(None).unwrap();
+        let start = Instant::now();
+        outbox
+            .push(delivery("complete", "2025-01-26 09:00:00"), start)
+            .unwrap();
+        outbox
+            .push(delivery("overtime", "2025-01-27 09:00:00"), start)
+            .unwrap();
+        let at = create_test_datetime("2025-01-27 10:00:00");
+        assert_eq!(
+            outbox
+                .retry_due(start + RETRY_INTERVAL, at, offline)
+                .unwrap(),
+            0
+        );
+        assert_eq!(outbox.pending().len(), 1);
+        assert_eq!(outbox.pending()[0].body, "{\"event\":\"overtime\"}");
+
+        for _ in 0..MAX_PENDING {
+            outbox
+                .push(delivery("milestone-25", "2025-01-27 09:00:00"), start)
+                .unwrap();
+        }
+        assert_eq!(outbox.pending().len(), MAX_PENDING);
+        assert!(outbox.pending()[0].body.contains("milestone-25"));
+    }
+}
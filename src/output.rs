@@ -0,0 +1,383 @@
+//! Machine-readable output formats for the pmon CLI tool
+//!
+//! Besides the human-oriented progress bar, pmon can emit each update as a
+//! structured record so scripts and status bars can consume it. Every format
+//! is rendered from the same [`ProgressState`] snapshot as the text bar.
+
+use crate::error::PbError;
+use crate::progress_state::ProgressState;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, IsTerminal, LineWriter, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Option name used in error messages
+const OPTION_NAME: &str = "--output";
+
+/// Output format for each progress update
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable progress bar
+    #[default]
+    Text,
+    /// One JSON object per update (newline-delimited JSON)
+    Json,
+    /// GitHub Actions workflow commands at milestones and completion
+    Gha,
+    /// A single xbar/SwiftBar menu-bar plugin snapshot
+    Xbar,
+    /// The URL of a shields.io badge, printed once
+    Badge,
+    /// A badge drawn as an SVG image, printed once
+    BadgeSvg,
+}
+
+impl OutputFormat {
+    /// Whether the output is meant for people, with bars and prose
+    pub fn is_text(self) -> bool {
+        self == OutputFormat::Text
+    }
+
+    /// Whether one snapshot is printed instead of following the window
+    pub fn is_snapshot(self) -> bool {
+        matches!(
+            self,
+            OutputFormat::Xbar | OutputFormat::Badge | OutputFormat::BadgeSvg
+        )
+    }
+}
+
+/// Where progress output is written
+///
+/// Errors and warnings always go to stderr; this only moves the bar, the
+/// header, and the summary. Routing them to stderr or a file keeps stdout
+/// free for other data, such as a wrapped command's output.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum OutputTarget {
+    /// Standard output
+    #[default]
+    Stdout,
+    /// Standard error
+    Stderr,
+    /// A file, truncated when opened
+    File(PathBuf),
+}
+
+impl OutputTarget {
+    /// Open the target for writing
+    ///
+    /// Files are line-buffered so each non-interactive update lands as soon
+    /// as it is written.
+    pub fn open(&self) -> io::Result<Box<dyn Write>> {
+        Ok(match self {
+            Self::Stdout => Box::new(io::stdout()),
+            Self::Stderr => Box::new(io::stderr()),
+            Self::File(path) => Box::new(LineWriter::new(File::create(path)?)),
+        })
+    }
+
+    /// Whether the target is a terminal, so redraws and colors make sense
+    pub fn is_terminal(&self) -> bool {
+        match self {
+            Self::Stdout => io::stdout().is_terminal(),
+            Self::Stderr => io::stderr().is_terminal(),
+            Self::File(_) => false,
+        }
+    }
+}
+
+impl FromStr for OutputTarget {
+    type Err = PbError;
+
+    /// Parse `stdout`, `stderr`, or `file:<path>`
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let trimmed = input.trim();
+        if let Some(path) = trimmed.strip_prefix("file:") {
+            if path.is_empty() {
+                return Err(PbError::invalid_argument(OPTION_NAME, input));
+            }
+            return Ok(Self::File(PathBuf::from(path)));
+        }
+        match trimmed.to_ascii_lowercase().as_str() {
+            "stdout" | "-" => Ok(Self::Stdout),
+            "stderr" => Ok(Self::Stderr),
+            _ => Err(PbError::invalid_argument(OPTION_NAME, input)),
+        }
+    }
+}
+
+/// Context for a progress update beyond the window itself
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct UpdateContext<'a> {
+    /// Label of the window
+    pub label: Option<&'a str>,
+    /// Status message selected for the current percentage
+    pub message: Option<&'a str>,
+    /// Net system clock adjustment observed during the run, if any
+    pub clock_skew: Option<chrono::Duration>,
+    /// Tags attached to the timer
+    pub tags: &'a [String],
+}
+
+/// JSON representation of a progress update
+#[derive(Debug, Serialize)]
+struct JsonStatus<'a> {
+    label: Option<&'a str>,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    current: NaiveDateTime,
+    percent: Option<f64>,
+    elapsed_seconds: i64,
+    remaining_seconds: i64,
+    overtime_seconds: i64,
+    complete: bool,
+    message: Option<&'a str>,
+    clock_skew_seconds: Option<i64>,
+    #[serde(skip_serializing_if = "has_no_tags")]
+    tags: &'a [String],
+}
+
+fn has_no_tags(tags: &&[String]) -> bool {
+    tags.is_empty()
+}
+
+/// Render a progress update as a single-line JSON object
+///
+/// Timestamps use ISO 8601 local time; durations are whole seconds and
+/// `remaining_seconds` becomes negative once the window has passed, and
+/// `overtime_seconds` counts the time past the end.
+/// A percentage that is not finite is reported as `null`.
+/// `clock_skew_seconds` is set once the system clock has been adjusted, and
+/// `tags` is only present when the timer has tags.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDateTime;
+/// use pmon::output::{render_json, UpdateContext};
+/// use pmon::progress_state::ProgressState;
+///
+/// let start = NaiveDateTime::parse_from_str("2025-01-27 09:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+/// let end = NaiveDateTime::parse_from_str("2025-01-27 17:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+/// let current = NaiveDateTime::parse_from_str("2025-01-27 13:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+///
+/// let json = render_json(&ProgressState::new(start, end, current), &UpdateContext::default());
+/// assert!(json.contains("\"percent\":50.0"));
+/// ```
+pub fn render_json(state: &ProgressState, context: &UpdateContext) -> String {
+    let status = JsonStatus {
+        label: context.label,
+        start: state.start,
+        end: state.end,
+        current: state.current,
+        percent: Some(state.percentage).filter(|percent| percent.is_finite()),
+        elapsed_seconds: state.elapsed().num_seconds(),
+        remaining_seconds: state.remaining().num_seconds(),
+        overtime_seconds: state.overtime().num_seconds(),
+        complete: state.is_complete(),
+        message: context.message,
+        clock_skew_seconds: context.clock_skew.map(|skew| skew.num_seconds()),
+        tags: context.tags,
+    };
+
+    // Serializing plain data with string keys cannot fail
+    serde_json::to_string(&status).unwrap_or_default()
+}
+
+/// A progress update read back from [`render_json`] output
+///
+/// Used to display updates produced by another pmon process, such as one
+/// running on a remote host.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct JsonUpdate {
+    /// Label of the window
+    pub label: Option<String>,
+    /// Start of the window
+    pub start: NaiveDateTime,
+    /// End of the window
+    pub end: NaiveDateTime,
+    /// Moment the update was taken
+    pub current: NaiveDateTime,
+    /// Progress percentage, `None` when it was not finite
+    pub percent: Option<f64>,
+    /// Status message, if any
+    pub message: Option<String>,
+    /// Tags attached to the timer
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Id of the process publishing a named timer's state file
+    #[serde(default)]
+    pub pid: Option<u32>,
+}
+
+impl JsonUpdate {
+    /// Parse one line of JSON output
+    pub fn parse(line: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(line)
+    }
+
+    /// Snapshot as computed by the producing process
+    ///
+    /// The reported percentage is kept rather than recomputed, so the
+    /// producer's clock stays authoritative.
+    pub fn state(&self) -> ProgressState {
+        ProgressState {
+            start: self.start,
+            end: self.end,
+            current: self.current,
+            percentage: self.percent.unwrap_or(f64::NAN),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn sample_state(current: &str) -> ProgressState {
+        ProgressState::new(
+            create_test_datetime("2025-01-27 09:00:00"),
+            create_test_datetime("2025-01-27 17:00:00"),
+            create_test_datetime(current),
+        )
+    }
+
+    #[test]
+    fn test_parse_output_target() {
+        assert_eq!(
+            "stdout".parse::<OutputTarget>().unwrap(),
+            OutputTarget::Stdout
+        );
+        assert_eq!("-".parse::<OutputTarget>().unwrap(), OutputTarget::Stdout);
+        assert_eq!(
+            "STDERR".parse::<OutputTarget>().unwrap(),
+            OutputTarget::Stderr
+        );
+        assert_eq!(
+            "file:/tmp/pmon.log".parse::<OutputTarget>().unwrap(),
+            OutputTarget::File(PathBuf::from("/tmp/pmon.log"))
+        );
+
+        for input in ["", "file:", "tty", "/tmp/pmon.log"] {
+            assert!(
+                matches!(
+                    input.parse::<OutputTarget>(),
+                    Err(PbError::InvalidArgument { .. })
+                ),
+                "Expected '{input}' to be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_file_target_writes_lines() {
+        let path = std::env::temp_dir().join(format!("pmon-output-{}.log", std::process::id()));
+        let target = OutputTarget::File(path.clone());
+        assert!(!target.is_terminal());
+
+        {
+            let mut out = target.open().unwrap();
+            writeln!(out, "first").unwrap();
+            writeln!(out, "second").unwrap();
+        }
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "first\nsecond\n");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_render_json_fields() {
+        let context = UpdateContext {
+            label: Some("Work day"),
+            message: Some("on track"),
+            clock_skew: Some(chrono::Duration::seconds(-30)),
+            tags: &["sev2".to_string()],
+        };
+        let json = render_json(&sample_state("2025-01-27 11:00:00"), &context);
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(value["label"], "Work day");
+        assert_eq!(value["start"], "2025-01-27T09:00:00");
+        assert_eq!(value["end"], "2025-01-27T17:00:00");
+        assert_eq!(value["current"], "2025-01-27T11:00:00");
+        assert_eq!(value["percent"], 25.0);
+        assert_eq!(value["elapsed_seconds"], 7200);
+        assert_eq!(value["remaining_seconds"], 21600);
+        assert_eq!(value["complete"], false);
+        assert_eq!(value["message"], "on track");
+        assert_eq!(value["clock_skew_seconds"], -30);
+        assert_eq!(value["tags"], serde_json::json!(["sev2"]));
+    }
+
+    #[test]
+    fn test_render_json_optional_fields_are_null() {
+        let json = render_json(
+            &sample_state("2025-01-27 11:00:00"),
+            &UpdateContext::default(),
+        );
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert!(value["label"].is_null());
+        assert!(value["message"].is_null());
+        assert!(value["clock_skew_seconds"].is_null());
+        assert!(value.get("tags").is_none());
+    }
+
+    #[test]
+    fn test_render_json_overtime() {
+        let json = render_json(
+            &sample_state("2025-01-27 18:00:00"),
+            &UpdateContext::default(),
+        );
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(value["percent"], 112.5);
+        assert_eq!(value["remaining_seconds"], -3600);
+        assert_eq!(value["overtime_seconds"], 3600);
+        assert_eq!(value["complete"], true);
+    }
+
+    #[test]
+    fn test_render_json_non_finite_percent_is_null() {
+        let mut state = sample_state("2025-01-27 11:00:00");
+        state.percentage = f64::NAN;
+        let json = render_json(&state, &UpdateContext::default());
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert!(value["percent"].is_null());
+        assert_eq!(value["complete"], false);
+    }
+
+    #[test]
+    fn test_json_update_round_trip() {
+        let state = sample_state("2025-01-27 11:00:00");
+        let context = UpdateContext {
+            label: Some("Build"),
+            message: Some("compiling"),
+            clock_skew: None,
+            tags: &["team:payments".to_string()],
+        };
+        let update = JsonUpdate::parse(&render_json(&state, &context)).unwrap();
+
+        assert_eq!(update.label.as_deref(), Some("Build"));
+        assert_eq!(update.tags, ["team:payments"]);
+        assert_eq!(update.message.as_deref(), Some("compiling"));
+        assert_eq!(update.state(), state);
+        assert!(JsonUpdate::parse("not json").is_err());
+    }
+
+    #[test]
+    fn test_render_json_is_single_line() {
+        let context = UpdateContext {
+            label: Some("a\nb"),
+            ..UpdateContext::default()
+        };
+        let json = render_json(&sample_state("2025-01-27 11:00:00"), &context);
+        assert!(!json.contains('\n'));
+    }
+}
@@ -0,0 +1,233 @@
+//! Pace tracking against a reference schedule
+//!
+//! A reference schedule describes how fast progress is expected to advance,
+//! such as `60%/day` for a sprint burndown. Comparing the wall-clock progress
+//! with the percentage the schedule expects at the same moment shows whether
+//! the window is running ahead of or behind that pace.
+
+use crate::color::Colorize;
+use crate::error::PbError;
+use crate::progress_state::ProgressState;
+use chrono::Duration;
+use std::str::FromStr;
+
+/// Option name used in error messages
+const OPTION_NAME: &str = "--pace";
+
+/// Deltas smaller than this (in percentage points) count as on pace
+const ON_PACE_TOLERANCE: f64 = 0.05;
+
+/// Expected rate of progress, in percent per unit of time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PaceRate {
+    /// Percentage expected to be consumed per `per`
+    pub percent: f64,
+    /// Unit of time the percentage applies to
+    pub per: Duration,
+}
+
+impl PaceRate {
+    /// Percentage the schedule expects after `elapsed`, capped to 0-100%
+    pub fn expected_after(&self, elapsed: Duration) -> f64 {
+        let ratio = elapsed.num_milliseconds() as f64 / self.per.num_milliseconds() as f64;
+        (self.percent * ratio).clamp(0.0, 100.0)
+    }
+}
+
+impl FromStr for PaceRate {
+    type Err = PbError;
+
+    /// Parse a `PERCENT/UNIT` rate such as `60%/day` or `12.5/h`
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let invalid = || PbError::invalid_argument(OPTION_NAME, input);
+
+        let (percent, unit) = input.split_once('/').ok_or_else(invalid)?;
+        let percent = percent.trim();
+        let percent = percent
+            .strip_suffix('%')
+            .unwrap_or(percent)
+            .parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite() && *value > 0.0)
+            .ok_or_else(invalid)?;
+
+        let per = match unit.trim().to_lowercase().as_str() {
+            "s" | "sec" | "second" => Duration::seconds(1),
+            "m" | "min" | "minute" => Duration::minutes(1),
+            "h" | "hour" => Duration::hours(1),
+            "d" | "day" => Duration::days(1),
+            "w" | "week" => Duration::weeks(1),
+            _ => return Err(invalid()),
+        };
+
+        Ok(Self { percent, per })
+    }
+}
+
+/// Comparison between actual progress and the reference schedule
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pace {
+    /// Percentage the schedule expects at the current moment
+    pub expected: f64,
+    /// Actual minus expected, in percentage points
+    pub delta: f64,
+}
+
+impl Pace {
+    /// Compare `state` with the progress `rate` expects at the same moment
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::NaiveDateTime;
+    /// use pmon::pace::Pace;
+    /// use pmon::progress_state::ProgressState;
+    ///
+    /// let start = NaiveDateTime::parse_from_str("2025-01-27 00:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+    /// let end = NaiveDateTime::parse_from_str("2025-01-29 00:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+    /// let current = NaiveDateTime::parse_from_str("2025-01-28 00:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+    ///
+    /// let state = ProgressState::new(start, end, current);
+    /// let pace = Pace::evaluate(&"60%/day".parse().unwrap(), &state);
+    /// assert_eq!(pace.expected, 60.0);
+    /// assert_eq!(pace.delta, -10.0);
+    /// ```
+    pub fn evaluate(rate: &PaceRate, state: &ProgressState) -> Self {
+        let expected = rate.expected_after(state.elapsed());
+        Self {
+            expected,
+            delta: state.percentage - expected,
+        }
+    }
+
+    /// Whether progress is within tolerance of the schedule
+    pub fn is_on_pace(&self) -> bool {
+        self.delta.abs() < ON_PACE_TOLERANCE
+    }
+}
+
+/// Format a pace comparison as an arrow and signed delta
+///
+/// # Examples
+///
+/// ```
+/// use pmon::pace::{format_pace, Pace};
+///
+/// assert_eq!(format_pace(&Pace { expected: 40.0, delta: 5.0 }), "▲ +5.0% ahead");
+/// assert_eq!(format_pace(&Pace { expected: 60.0, delta: -2.5 }), "▼ -2.5% behind");
+/// assert_eq!(format_pace(&Pace { expected: 50.0, delta: 0.0 }), "= on pace");
+/// ```
+pub fn format_pace(pace: &Pace) -> String {
+    if pace.is_on_pace() {
+        "= on pace".to_string()
+    } else if pace.delta > 0.0 {
+        format!("▲ {:+.1}% ahead", pace.delta)
+    } else {
+        format!("▼ {:+.1}% behind", pace.delta)
+    }
+}
+
+/// Format a pace comparison with green for ahead and yellow for behind
+pub fn format_colored_pace(pace: &Pace) -> String {
+    let text = format_pace(pace);
+    if pace.is_on_pace() {
+        text
+    } else if pace.delta > 0.0 {
+        text.green().to_string()
+    } else {
+        text.yellow().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn state_at(current: &str) -> ProgressState {
+        ProgressState::new(
+            create_test_datetime("2025-01-27 09:00:00"),
+            create_test_datetime("2025-01-27 17:00:00"),
+            create_test_datetime(current),
+        )
+    }
+
+    #[test]
+    fn test_parse_rates() {
+        assert_eq!(
+            "60%/day".parse::<PaceRate>().unwrap(),
+            PaceRate {
+                percent: 60.0,
+                per: Duration::days(1)
+            }
+        );
+        assert_eq!(
+            "12.5/h".parse::<PaceRate>().unwrap(),
+            PaceRate {
+                percent: 12.5,
+                per: Duration::hours(1)
+            }
+        );
+        assert_eq!(
+            " 20% / Week ".parse::<PaceRate>().unwrap().per,
+            Duration::weeks(1)
+        );
+        assert_eq!(
+            "1/min".parse::<PaceRate>().unwrap().per,
+            Duration::minutes(1)
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_rates() {
+        for input in [
+            "",
+            "60",
+            "60%",
+            "/day",
+            "abc/day",
+            "0/day",
+            "-5/h",
+            "10/fortnight",
+            "NaN/h",
+        ] {
+            let result = input.parse::<PaceRate>();
+            assert!(
+                matches!(result, Err(PbError::InvalidArgument { .. })),
+                "Expected '{input}' to be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_expected_is_capped() {
+        let rate: PaceRate = "10%/h".parse().unwrap();
+        assert_eq!(rate.expected_after(Duration::minutes(90)), 15.0);
+        assert_eq!(rate.expected_after(Duration::hours(20)), 100.0);
+        assert_eq!(rate.expected_after(Duration::hours(-1)), 0.0);
+    }
+
+    #[test]
+    fn test_evaluate_ahead_and_behind() {
+        // 8h window runs at 12.5%/h, so a 10%/h schedule falls behind it
+        let slow: PaceRate = "10%/h".parse().unwrap();
+        let pace = Pace::evaluate(&slow, &state_at("2025-01-27 11:00:00"));
+        assert_eq!(pace.expected, 20.0);
+        assert_eq!(pace.delta, 5.0);
+        assert_eq!(format_pace(&pace), "▲ +5.0% ahead");
+
+        let fast: PaceRate = "25%/h".parse().unwrap();
+        let pace = Pace::evaluate(&fast, &state_at("2025-01-27 11:00:00"));
+        assert_eq!(pace.delta, -25.0);
+        assert_eq!(format_pace(&pace), "▼ -25.0% behind");
+
+        let matching: PaceRate = "12.5%/h".parse().unwrap();
+        let pace = Pace::evaluate(&matching, &state_at("2025-01-27 13:00:00"));
+        assert!(pace.is_on_pace());
+        assert_eq!(format_pace(&pace), "= on pace");
+    }
+}
@@ -0,0 +1,207 @@
+//! Planned windows imported in bulk from CSV
+//!
+//! `pmon import-plan plan.csv` saves one named timer per row of a CSV file,
+//! such as the talks of a conference or the dates of a release calendar,
+//! ready to be started with `pmon run NAME`:
+//!
+//! ```csv
+//! label,start,end,tags
+//! Opening keynote,2025-03-10 09:00,2025-03-10 10:00,track:main
+//! Rust in production,2025-03-10 10:30,2025-03-10 11:15,track:main;rust
+//! ```
+//!
+//! The `label`, `start`, and `end` columns are required, and `tags` holds
+//! any number of tags separated by `;`. A `name` column sets the names of
+//! the timers; without one, each is named after its label, as
+//! `opening-keynote`, with a number appended when labels repeat. Every row
+//! is checked before any timer is saved.
+
+use crate::broadcast::{TimerName, MAX_NAME_LENGTH};
+use crate::definition::TimerDefinition;
+use crate::error::{PbError, PbResult};
+use crate::tags::parse_tag;
+use serde::Deserialize;
+use std::collections::HashSet;
+
+/// Separator between the tags of a row
+pub const TAG_SEPARATOR: char = ';';
+
+/// A row of the plan as written
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PlanRow {
+    #[serde(default)]
+    name: Option<String>,
+    label: String,
+    start: String,
+    end: String,
+    #[serde(default)]
+    tags: Option<String>,
+}
+
+/// Name for a timer labeled `label`: lowercase letters and digits joined by `-`
+///
+/// # Examples
+///
+/// ```
+/// use pmon::plan::name_from_label;
+///
+/// assert_eq!(name_from_label("Opening keynote"), "opening-keynote");
+/// assert_eq!(name_from_label("Q&A: Rust 2025!"), "q-a-rust-2025");
+/// assert_eq!(name_from_label("基調講演"), "timer");
+/// ```
+pub fn name_from_label(label: &str) -> String {
+    let name = label
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let name = name[..name.len().min(MAX_NAME_LENGTH)].trim_end_matches('-');
+    if name.is_empty() {
+        "timer".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Read the timer definitions of a CSV plan from `contents`
+///
+/// `path` names the file in error messages, along with the line of the row
+/// at fault.
+///
+/// # Examples
+///
+/// ```
+/// use pmon::plan::read_plan;
+///
+/// let plan = "label,start,end,tags\n\
+///             Keynote,2025-03-10 09:00,2025-03-10 10:00,track:main\n\
+///             Keynote,2025-03-11 09:00,2025-03-11 10:00,\n";
+/// let definitions = read_plan(plan, "plan.csv").unwrap();
+/// assert_eq!(definitions[0].name, "keynote");
+/// assert_eq!(definitions[0].tags, ["track:main"]);
+/// assert_eq!(definitions[1].name, "keynote-2");
+/// ```
+pub fn read_plan(contents: &str, path: &str) -> PbResult<Vec<TimerDefinition>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(contents.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|e| PbError::invalid_config(path, csv_reason(&e)))?
+        .clone();
+    let mut names = HashSet::new();
+    let mut definitions = Vec::new();
+    for record in reader.records() {
+        let line = match &record {
+            Ok(record) => record.position(),
+            Err(e) => e.position(),
+        }
+        .map_or(0, csv::Position::line);
+        let invalid =
+            |reason: String| PbError::invalid_config(path, format!("line {line}: {reason}"));
+        let row: PlanRow = record
+            .and_then(|record| record.deserialize(Some(&headers)))
+            .map_err(|e| invalid(csv_reason(&e)))?;
+        let name = match row.name.as_deref().filter(|name| !name.is_empty()) {
+            Some(name) => name
+                .parse::<TimerName>()
+                .map_err(|e| invalid(e.to_string()))?
+                .as_str()
+                .to_string(),
+            None => unique_name(&name_from_label(&row.label), &names),
+        };
+        if !names.insert(name.clone()) {
+            return Err(invalid(format!("the name '{name}' is used twice")));
+        }
+        let tags = row
+            .tags
+            .iter()
+            .flat_map(|tags| tags.split(TAG_SEPARATOR))
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(parse_tag)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| invalid(e.to_string()))?;
+        let definition = TimerDefinition {
+            name,
+            label: Some(row.label).filter(|label| !label.is_empty()),
+            start: Some(row.start).filter(|start| !start.is_empty()),
+            end: row.end,
+            tags,
+            ..TimerDefinition::default()
+        };
+        definition.validate().map_err(|e| invalid(e.to_string()))?;
+        definitions.push(definition);
+    }
+    if definitions.is_empty() {
+        return Err(PbError::invalid_config(path, "the plan has no rows"));
+    }
+    Ok(definitions)
+}
+
+/// `base`, or `base-2`, `base-3`, ... when it is taken
+fn unique_name(base: &str, taken: &HashSet<String>) -> String {
+    (1..)
+        .map(|n| match n {
+            1 => base.to_string(),
+            n => format!("{base}-{n}"),
+        })
+        .find(|name| !taken.contains(name))
+        .unwrap_or_default()
+}
+
+/// What a CSV error says, without the position the caller reports itself
+fn csv_reason(error: &csv::Error) -> String {
+    match error.kind() {
+        csv::ErrorKind::Deserialize { err, .. } => err.to_string(),
+        csv::ErrorKind::UnequalLengths {
+            expected_len, len, ..
+        } => format!("expected {expected_len} fields, found {len}"),
+        _ => error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_plan_with_names() {
+        let plan = "name,label,start,end,tags\n\
+                    keynote,Opening keynote,2025-03-10 09:00,2025-03-10 10:00,track:main ; rust\n\
+                    ,Lunch,,2025-03-10 13:00,\n";
+        let definitions = read_plan(plan, "plan.csv").unwrap();
+        assert_eq!(definitions.len(), 2);
+        assert_eq!(definitions[0].name, "keynote");
+        assert_eq!(definitions[0].label.as_deref(), Some("Opening keynote"));
+        assert_eq!(definitions[0].start.as_deref(), Some("2025-03-10 09:00"));
+        assert_eq!(definitions[0].tags, ["track:main", "rust"]);
+        assert_eq!(definitions[1].name, "lunch");
+        assert_eq!(definitions[1].start, None);
+        assert!(definitions[1].tags.is_empty());
+    }
+
+    #[test]
+    fn test_read_plan_reports_the_line() {
+        let error = |plan: &str| read_plan(plan, "plan.csv").unwrap_err().to_string();
+        let header = "label,start,end,tags\n";
+        let valid = "Keynote,2025-03-10 09:00,2025-03-10 10:00,\n";
+
+        let message = error(&format!("{header}{valid}Talk,2025-03-10 11:00,,\n"));
+        assert!(message.contains("line 3"), "{message}");
+        let message = error(&format!("{header}{valid}Talk,2025-03-10 11:00\n"));
+        assert!(
+            message.contains("line 3: expected 4 fields, found 2"),
+            "{message}"
+        );
+        let message = error("label,start,end,room\nTalk,,+1h,A\n");
+        assert!(message.contains("line 2"), "{message}");
+        let message = error(&format!("{header}Talk,,+1h,bad tag!\n"));
+        assert!(message.contains("line 2"), "{message}");
+        let message = error("name,label,start,end\nkeynote,A,,+1h\nkeynote,B,,+2h\n");
+        assert!(message.contains("used twice"), "{message}");
+        assert!(error(header).contains("no rows"));
+    }
+}
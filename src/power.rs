@@ -0,0 +1,254 @@
+//! Saving battery on laptops
+//!
+//! A timer left running for hours on a laptop should cost as little power
+//! as possible. On battery, pmon stretches its refresh interval by
+//! [`BATTERY_INTERVAL_FACTOR`] and stops animating the stopwatch marquee.
+//! `--battery-saver auto`, the default, checks the power source every
+//! [`POWER_CHECK_INTERVAL`] so plugging in or out takes effect mid-run;
+//! `on` and `off` override it.
+//!
+//! The power source is read from `/sys/class/power_supply` on Linux and
+//! from `pmset` on macOS. Elsewhere, or in builds without the `battery`
+//! feature, it is unknown and `auto` never saves.
+
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// How many times longer the refresh interval is on battery
+pub const BATTERY_INTERVAL_FACTOR: u32 = 4;
+
+/// How often `auto` checks whether the machine is on battery
+pub const POWER_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Whether refreshes are slowed down to save battery
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum BatterySaver {
+    /// Save while the machine runs on battery
+    #[default]
+    Auto,
+    /// Always save
+    On,
+    /// Never save
+    Off,
+}
+
+/// Where the machine draws its power from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerSource {
+    /// Plugged in
+    Ac,
+    /// Running on its battery
+    Battery,
+}
+
+/// Tracks whether to save battery, re-checking the power source now and then
+#[derive(Debug, Clone)]
+pub struct PowerMonitor {
+    mode: BatterySaver,
+    checked: Option<(Instant, bool)>,
+}
+
+impl PowerMonitor {
+    /// Save battery according to `mode`
+    pub fn new(mode: BatterySaver) -> Self {
+        Self {
+            mode,
+            checked: None,
+        }
+    }
+
+    /// Whether to save battery at `now`
+    pub fn is_saving(&mut self, now: Instant) -> bool {
+        match self.mode {
+            BatterySaver::On => true,
+            BatterySaver::Off => false,
+            BatterySaver::Auto => match self.checked {
+                Some((at, saving)) if now.saturating_duration_since(at) < POWER_CHECK_INTERVAL => {
+                    saving
+                }
+                _ => {
+                    let source = power_source();
+                    let saving = source == Some(PowerSource::Battery);
+                    debug!(?source, saving, "power source checked");
+                    self.checked = Some((now, saving));
+                    saving
+                }
+            },
+        }
+    }
+
+    /// The refresh interval to use at `now` instead of `interval`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pmon::power::{BatterySaver, PowerMonitor};
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let mut power = PowerMonitor::new(BatterySaver::On);
+    /// let interval = Duration::from_secs(60);
+    /// assert_eq!(power.interval(interval, Instant::now()), Duration::from_secs(240));
+    /// ```
+    pub fn interval(&mut self, interval: Duration, now: Instant) -> Duration {
+        if self.is_saving(now) {
+            interval.saturating_mul(BATTERY_INTERVAL_FACTOR)
+        } else {
+            interval
+        }
+    }
+}
+
+/// The machine's current power source, if it can be told
+#[cfg(all(feature = "battery", target_os = "linux"))]
+pub fn power_source() -> Option<PowerSource> {
+    let entries = std::fs::read_dir("/sys/class/power_supply").ok()?;
+    let read = |dir: &std::path::Path, name: &str| {
+        std::fs::read_to_string(dir.join(name))
+            .map(|value| value.trim().to_string())
+            .ok()
+    };
+    let supplies: Vec<Supply> = entries
+        .flatten()
+        .map(|entry| {
+            let dir = entry.path();
+            Supply {
+                kind: read(&dir, "type").unwrap_or_default(),
+                online: read(&dir, "online").map(|online| online == "1"),
+                status: read(&dir, "status"),
+            }
+        })
+        .collect();
+    source_from_supplies(&supplies)
+}
+
+/// The machine's current power source, if it can be told
+#[cfg(all(feature = "battery", target_os = "macos"))]
+pub fn power_source() -> Option<PowerSource> {
+    let output = std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    source_from_pmset(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// The power source is unknown on this platform or build
+#[cfg(not(all(feature = "battery", any(target_os = "linux", target_os = "macos"))))]
+pub fn power_source() -> Option<PowerSource> {
+    None
+}
+
+/// One entry of `/sys/class/power_supply`
+#[cfg_attr(not(all(feature = "battery", target_os = "linux")), allow(dead_code))]
+#[derive(Debug, Clone, Default)]
+struct Supply {
+    /// `Mains`, `USB`, `Battery`, ...
+    kind: String,
+    /// Whether a charger is connected, for chargers
+    online: Option<bool>,
+    /// `Charging`, `Discharging`, `Full`, ..., for batteries
+    status: Option<String>,
+}
+
+/// Any connected charger means AC; otherwise a discharging battery means
+/// battery. Desktops without supplies are left unknown.
+#[cfg_attr(not(all(feature = "battery", target_os = "linux")), allow(dead_code))]
+fn source_from_supplies(supplies: &[Supply]) -> Option<PowerSource> {
+    let is_charger = |supply: &&Supply| supply.kind != "Battery";
+    if supplies
+        .iter()
+        .filter(is_charger)
+        .any(|supply| supply.online == Some(true))
+    {
+        return Some(PowerSource::Ac);
+    }
+    supplies
+        .iter()
+        .any(|supply| supply.kind == "Battery" && supply.status.as_deref() == Some("Discharging"))
+        .then_some(PowerSource::Battery)
+}
+
+/// Read the source from `pmset -g batt`, whose first line is
+/// `Now drawing from 'Battery Power'` or `'AC Power'`
+#[cfg_attr(not(all(feature = "battery", target_os = "macos")), allow(dead_code))]
+fn source_from_pmset(output: &str) -> Option<PowerSource> {
+    let first = output.lines().next()?;
+    if first.contains("'Battery Power'") {
+        Some(PowerSource::Battery)
+    } else if first.contains("'AC Power'") {
+        Some(PowerSource::Ac)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn supply(kind: &str, online: Option<bool>, status: Option<&str>) -> Supply {
+        Supply {
+            kind: kind.to_string(),
+            online,
+            status: status.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_source_from_supplies() {
+        let battery = supply("Battery", None, Some("Discharging"));
+        let charging = supply("Battery", None, Some("Charging"));
+        let mains = |online| supply("Mains", Some(online), None);
+
+        assert_eq!(
+            source_from_supplies(&[mains(false), battery.clone()]),
+            Some(PowerSource::Battery)
+        );
+        assert_eq!(
+            source_from_supplies(&[mains(true), charging]),
+            Some(PowerSource::Ac)
+        );
+        // A USB-C charger is not always reported as mains
+        assert_eq!(
+            source_from_supplies(&[supply("USB", Some(true), None), battery]),
+            Some(PowerSource::Ac)
+        );
+        assert_eq!(source_from_supplies(&[]), None);
+        assert_eq!(source_from_supplies(&[mains(false)]), None);
+    }
+
+    #[test]
+    fn test_source_from_pmset() {
+        let on_battery = "Now drawing from 'Battery Power'\n -InternalBattery-0 (id=1234)\t87%; discharging; 5:12 remaining present: true\n";
+        assert_eq!(source_from_pmset(on_battery), Some(PowerSource::Battery));
+        let plugged_in = "Now drawing from 'AC Power'\n -InternalBattery-0 (id=1234)\t100%; charged; 0:00 remaining present: true\n";
+        assert_eq!(source_from_pmset(plugged_in), Some(PowerSource::Ac));
+        assert_eq!(source_from_pmset(""), None);
+    }
+
+    #[test]
+    fn test_override_modes() {
+        let now = Instant::now();
+        let interval = Duration::from_secs(30);
+        assert!(PowerMonitor::new(BatterySaver::On).is_saving(now));
+        assert!(!PowerMonitor::new(BatterySaver::Off).is_saving(now));
+        assert_eq!(
+            PowerMonitor::new(BatterySaver::On).interval(interval, now),
+            Duration::from_secs(120)
+        );
+        assert_eq!(
+            PowerMonitor::new(BatterySaver::Off).interval(interval, now),
+            interval
+        );
+    }
+
+    #[test]
+    fn test_auto_caches_the_power_source() {
+        let now = Instant::now();
+        let mut power = PowerMonitor::new(BatterySaver::Auto);
+        let saving = power.is_saving(now);
+        assert_eq!(power.checked, Some((now, saving)));
+        power.is_saving(now + Duration::from_secs(30));
+        assert_eq!(power.checked, Some((now, saving)));
+    }
+}
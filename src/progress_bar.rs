@@ -3,12 +3,24 @@
 //! This module provides progress calculation and rendering functionality
 //! for time-based progress visualization with color support.
 
+use crate::color::Colorize;
+use crate::error::PbError;
+use crate::layout;
+use crate::progress_state::ProgressState;
+use crate::timezone::civil_duration;
 use chrono::{Duration, NaiveDateTime};
-use colored::*;
+use std::str::FromStr;
 
 /// Fixed width for the progress bar display
 const BAR_WIDTH: usize = 40;
 
+/// Fewest cells a bar is shrunk to before its time information moves to a
+/// line of its own, see [`render_fitted`]
+pub const MIN_BAR_WIDTH: usize = 10;
+
+/// Fewest columns a label column is squeezed to, see [`fit_label_column`]
+pub const MIN_LABEL_WIDTH: usize = 8;
+
 /// Format a duration as human-readable time (e.g., "2h 36m", "45m", "1h")
 ///
 /// This function converts a chrono::Duration into a human-readable format
@@ -59,6 +71,205 @@ pub fn format_duration(duration: Duration) -> String {
     }
 }
 
+/// Formatting strategy for durations shown in the time information
+///
+/// Selected with `--duration-style` and applied through
+/// [`format_duration_with`] everywhere a duration is displayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum DurationStyle {
+    /// Largest two units, e.g. `2h 36m`, `3d 4h`, `11mo 3w`
+    #[default]
+    Compact,
+    /// Like `Compact` but never uses the approximate month/year units
+    CompactExact,
+    /// Zero-padded clock notation with seconds, e.g. `02:36:10`
+    Colon,
+    /// Spelled-out units, e.g. `2 hours 36 minutes`
+    Words,
+}
+
+/// Rounding direction applied to a duration before it is formatted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Rounding {
+    /// Truncate partial units (59.9 minutes shows as `59m`)
+    #[default]
+    Down,
+    /// Round partial units up (59.1 minutes shows as `1h 0m`)
+    Up,
+}
+
+/// Duration formatting settings used by the time-information renderers
+///
+/// Elapsed time is always truncated; only the remaining time honours
+/// `remaining_rounding`, so deadline users can opt into seeing a remaining
+/// value that never understates the displayed unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DurationFormat {
+    /// Formatting strategy for both elapsed and remaining time
+    pub style: DurationStyle,
+    /// Rounding direction for the remaining time
+    pub remaining_rounding: Rounding,
+}
+
+impl From<DurationStyle> for DurationFormat {
+    fn from(style: DurationStyle) -> Self {
+        Self {
+            style,
+            ..Self::default()
+        }
+    }
+}
+
+impl DurationFormat {
+    /// Format an elapsed duration (always truncated)
+    pub fn elapsed(&self, duration: Duration) -> String {
+        format_duration_with(duration, self.style)
+    }
+
+    /// Format a remaining duration using the configured rounding
+    pub fn remaining(&self, duration: Duration) -> String {
+        let duration = match self.remaining_rounding {
+            Rounding::Down => duration,
+            Rounding::Up => round_up_to(duration, self.style.precision()),
+        };
+        format_duration_with(duration, self.style)
+    }
+}
+
+impl DurationStyle {
+    /// Smallest unit the style displays for durations under a day
+    fn precision(self) -> Duration {
+        match self {
+            Self::Colon => Duration::seconds(1),
+            Self::Compact | Self::CompactExact | Self::Words => Duration::minutes(1),
+        }
+    }
+}
+
+/// Round a positive duration up to the next multiple of `unit`
+fn round_up_to(duration: Duration, unit: Duration) -> Duration {
+    let unit_ms = unit.num_milliseconds();
+    let duration_ms = duration.num_milliseconds();
+    if duration_ms <= 0 || unit_ms <= 0 {
+        return duration;
+    }
+
+    let units = duration_ms / unit_ms + i64::from(duration_ms % unit_ms != 0);
+    Duration::milliseconds(units.saturating_mul(unit_ms))
+}
+
+/// Format a duration using the given [`DurationStyle`]
+///
+/// Negative durations are treated as zero in every style.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::Duration;
+/// use pmon::progress_bar::{format_duration_with, DurationStyle};
+///
+/// let duration = Duration::hours(2) + Duration::minutes(36) + Duration::seconds(10);
+/// assert_eq!(format_duration_with(duration, DurationStyle::Compact), "2h 36m");
+/// assert_eq!(format_duration_with(duration, DurationStyle::Colon), "02:36:10");
+/// assert_eq!(format_duration_with(duration, DurationStyle::Words), "2 hours 36 minutes");
+/// ```
+pub fn format_duration_with(duration: Duration, style: DurationStyle) -> String {
+    match style {
+        DurationStyle::Compact => format_duration_compact(duration, true),
+        DurationStyle::CompactExact => format_duration_compact(duration, false),
+        DurationStyle::Colon => format_duration_colon(duration),
+        DurationStyle::Words => format_duration_words(duration),
+    }
+}
+
+/// Days in a week, used for the exact `w` tier
+const DAYS_PER_WEEK: i64 = 7;
+/// Days in an approximate month, used for the `mo` tier
+const DAYS_PER_MONTH: i64 = 30;
+/// Days in an approximate year, used for the `y` tier
+const DAYS_PER_YEAR: i64 = 365;
+
+/// Format a duration using its two largest units
+///
+/// Durations under a day are formatted exactly like [`format_duration`].
+/// Longer durations step up through days, weeks, months, and years, always
+/// showing the two largest units (`3d 4h`, `2w 3d`, `11mo 3w`, `1y 2mo`).
+///
+/// # Approximation Policy
+///
+/// Days and weeks are exact. Months and years are calendar-independent
+/// approximations: a month is 30 days and a year is 365 days, so the
+/// displayed value may differ from a calendar count by a few days. Pass
+/// `approximate = false` to stop at weeks (a year-long range then shows
+/// `52w 1d`).
+///
+/// # Examples
+///
+/// ```
+/// use chrono::Duration;
+/// use pmon::progress_bar::format_duration_compact;
+///
+/// assert_eq!(format_duration_compact(Duration::minutes(156), true), "2h 36m");
+/// assert_eq!(format_duration_compact(Duration::hours(76), true), "3d 4h");
+/// assert_eq!(format_duration_compact(Duration::days(351), true), "11mo 3w");
+/// assert_eq!(format_duration_compact(Duration::days(425), true), "1y 2mo");
+/// assert_eq!(format_duration_compact(Duration::days(365), false), "52w 1d");
+/// ```
+pub fn format_duration_compact(duration: Duration, approximate: bool) -> String {
+    if duration.num_days() < 1 {
+        return format_duration(duration);
+    }
+
+    let days = duration.num_days();
+    if approximate && days >= DAYS_PER_YEAR {
+        let years = days / DAYS_PER_YEAR;
+        let months = (days % DAYS_PER_YEAR) / DAYS_PER_MONTH;
+        format!("{years}y {months}mo")
+    } else if approximate && days >= DAYS_PER_MONTH {
+        let months = days / DAYS_PER_MONTH;
+        let weeks = (days % DAYS_PER_MONTH) / DAYS_PER_WEEK;
+        format!("{months}mo {weeks}w")
+    } else if days >= DAYS_PER_WEEK {
+        let weeks = days / DAYS_PER_WEEK;
+        let remaining_days = days % DAYS_PER_WEEK;
+        format!("{weeks}w {remaining_days}d")
+    } else {
+        let hours = duration.num_hours() % 24;
+        format!("{days}d {hours}h")
+    }
+}
+
+/// Format a duration as `HH:MM:SS`; hours grow beyond two digits when needed
+fn format_duration_colon(duration: Duration) -> String {
+    let total_seconds = duration.num_seconds().max(0);
+    let hours = total_seconds / 3600;
+    let minutes = (total_seconds % 3600) / 60;
+    let seconds = total_seconds % 60;
+
+    format!("{hours:02}:{minutes:02}:{seconds:02}")
+}
+
+/// Format a duration as spelled-out hours and minutes
+fn format_duration_words(duration: Duration) -> String {
+    let total_minutes = duration.num_minutes().max(0);
+    let hours = total_minutes / 60;
+    let minutes = total_minutes % 60;
+
+    let unit = |amount: i64, singular: &str| {
+        if amount == 1 {
+            format!("{amount} {singular}")
+        } else {
+            format!("{amount} {singular}s")
+        }
+    };
+
+    match (hours, minutes) {
+        (0, minutes) => unit(minutes, "minute"),
+        (hours, 0) => unit(hours, "hour"),
+        (hours, minutes) => format!("{} {}", unit(hours, "hour"), unit(minutes, "minute")),
+    }
+}
+
 /// Calculate progress percentage based on elapsed time
 ///
 /// This function calculates the progress percentage between start and end times
@@ -112,24 +323,83 @@ pub fn format_duration(duration: Duration) -> String {
 /// assert_eq!(progress, 50.0); // 50% progress
 /// ```
 pub fn calculate_progress(start: NaiveDateTime, end: NaiveDateTime, current: NaiveDateTime) -> f64 {
-    let total_duration = end - start;
-    let elapsed_duration = current - start;
+    progress_from_durations(civil_duration(start, end), civil_duration(start, current))
+}
+
+/// Calculate progress counting whole calendar days
+///
+/// Each midnight since the start date completes one day, so the percentage
+/// steps once a day instead of moving continuously: a 30-day window is 40%
+/// done once 12 days have passed. An end after midnight counts its last,
+/// partial day as a whole one. Before the start and from the end on, the
+/// result is the same as [`calculate_progress`], so windows still complete
+/// on time.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDateTime;
+/// use pmon::progress_bar::calculate_day_progress;
+///
+/// let at = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+/// let (start, end) = (at("2025-07-01 00:00:00"), at("2025-07-31 00:00:00"));
+///
+/// assert_eq!(calculate_day_progress(start, end, at("2025-07-13 08:00:00")), 40.0);
+/// assert_eq!(calculate_day_progress(start, end, at("2025-07-13 23:59:59")), 40.0);
+/// ```
+pub fn calculate_day_progress(
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    current: NaiveDateTime,
+) -> f64 {
+    if current < start || current >= end {
+        return calculate_progress(start, end, current);
+    }
+    let partial_last_day = i64::from(end.time() != chrono::NaiveTime::MIN);
+    let total_days = (end.date() - start.date()).num_days() + partial_last_day;
+    let elapsed_days = (current.date() - start.date()).num_days();
+    if total_days <= 0 {
+        return 0.0;
+    }
+    elapsed_days as f64 / total_days as f64 * 100.0
+}
+
+/// Progress percentage for `elapsed_duration` out of `total_duration`
+///
+/// Split out of [`calculate_progress`] so time zone aware callers can measure
+/// the durations themselves.
+pub(crate) fn progress_from_durations(total_duration: Duration, elapsed_duration: Duration) -> f64 {
+    let total_microseconds = duration_microseconds(total_duration);
+    let elapsed_microseconds = duration_microseconds(elapsed_duration);
 
     // Handle zero duration edge case (use microseconds for higher precision)
-    if total_duration.num_microseconds().unwrap_or(0) == 0 {
+    if total_microseconds == 0 {
         return 100.0;
     }
 
     // Calculate progress percentage using microseconds for better precision
-    let total_microseconds = total_duration.num_microseconds().unwrap_or(0) as f64;
-    let elapsed_microseconds = elapsed_duration.num_microseconds().unwrap_or(0) as f64;
-
-    let progress = (elapsed_microseconds / total_microseconds) * 100.0;
+    let progress = (elapsed_microseconds as f64 / total_microseconds as f64) * 100.0;
+    debug_assert!(progress.is_finite(), "progress must be finite: {progress}");
 
     // Ensure non-negative progress (clamp negative values to 0.0)
     progress.max(0.0)
 }
 
+/// Whole microseconds in `duration`, without overflow
+///
+/// `Duration::num_microseconds` returns `None` beyond roughly 292,000 years,
+/// which previously turned absurdly long windows into a zero total and a
+/// constant 100%. Such durations fall back to i128 arithmetic instead.
+fn duration_microseconds(duration: Duration) -> i128 {
+    duration
+        .num_microseconds()
+        .map(i128::from)
+        .unwrap_or_else(|| {
+            i128::from(duration.num_seconds()) * 1_000_000
+                + i128::from(duration.subsec_nanos() / 1_000)
+        })
+}
+
 /// Render a visual progress bar with fixed 40-character width
 ///
 /// This function creates a visual progress bar representation using Unicode
@@ -152,6 +422,8 @@ pub fn calculate_progress(start: NaiveDateTime, end: NaiveDateTime, current: Nai
 /// - **>100% Progress**: Shows full bar with actual percentage: `[████████████████████████████████████████] 150.0%`
 /// - **Negative Progress**: Clamped to 0% (same as 0% case)
 /// - **Fractional Progress**: Rounds to nearest character position
+/// - **NaN / Infinite Progress**: Shows `--%`; NaN and negative infinity leave
+///   the bar empty, positive infinity fills it
 ///
 /// # Arguments
 ///
@@ -187,21 +459,98 @@ pub fn calculate_progress(start: NaiveDateTime, end: NaiveDateTime, current: Nai
 /// assert_eq!(render_progress_bar(150.0), "[████████████████████████████████████████] 150.0%");
 /// ```
 pub fn render_progress_bar(percentage: f64) -> String {
-    // Clamp negative percentages to 0 for visual display
-    let display_percentage = percentage.max(0.0);
-
-    // Calculate filled characters (round to nearest)
-    let filled_chars = ((display_percentage / 100.0) * BAR_WIDTH as f64).round() as usize;
+    render_bar(percentage, percentage, BAR_WIDTH, BarCells::default())
+}
 
-    // Ensure we don't exceed the bar width (for >100% cases)
-    let filled_chars = filled_chars.min(BAR_WIDTH);
+/// Bar of `width` cells filled to `fill`, followed by the `shown` percentage
+fn render_bar(fill: f64, shown: f64, width: usize, cells: BarCells) -> String {
+    let filled_chars = filled_cells(fill, width);
 
     // Create filled and empty portions
-    let filled = "█".repeat(filled_chars);
-    let empty = "░".repeat(BAR_WIDTH - filled_chars);
+    let filled = cells.filled.to_string().repeat(filled_chars);
+    let empty = cells.empty.to_string().repeat(width - filled_chars);
+
+    format!("[{filled}{empty}] {}", format_percentage(shown))
+}
+
+/// Number of filled cells for `percentage`, always within `0..=width`
+///
+/// Negative values and NaN leave the bar empty; values above 100 (including
+/// positive infinity) fill it.
+fn filled_cells(percentage: f64, width: usize) -> usize {
+    if percentage.is_nan() {
+        return 0;
+    }
+    // Clamp before scaling so the cast below can never saturate oddly
+    let display_percentage = percentage.clamp(0.0, 100.0);
+    ((display_percentage / 100.0) * width as f64).round() as usize
+}
+
+/// Format a percentage to one decimal place, or `--%` when it is not finite
+///
+/// # Examples
+///
+/// ```
+/// use pmon::progress_bar::format_percentage;
+///
+/// assert_eq!(format_percentage(42.25), "42.2%");
+/// assert_eq!(format_percentage(f64::NAN), "--%");
+/// assert_eq!(format_percentage(f64::INFINITY), "--%");
+/// ```
+pub fn format_percentage(percentage: f64) -> String {
+    if percentage.is_finite() {
+        format!("{percentage:.1}%")
+    } else {
+        "--%".to_string()
+    }
+}
 
-    // Format with percentage to one decimal place
-    format!("[{filled}{empty}] {percentage:.1}%")
+/// A coarse word for how far a window has come, for `--zen`
+///
+/// # Examples
+///
+/// ```
+/// use pmon::progress_bar::format_phase;
+///
+/// assert_eq!(format_phase(10.0), "early");
+/// assert_eq!(format_phase(50.0), "midway");
+/// assert_eq!(format_phase(80.0), "wrapping up");
+/// assert_eq!(format_phase(100.0), "time's up");
+/// assert_eq!(format_phase(120.0), "overtime");
+/// ```
+pub fn format_phase(percentage: f64) -> &'static str {
+    if percentage > 100.0 {
+        "overtime"
+    } else if percentage >= 100.0 {
+        "time's up"
+    } else if percentage >= 75.0 {
+        "wrapping up"
+    } else if percentage >= 30.0 {
+        "midway"
+    } else {
+        "early"
+    }
+}
+
+/// Snap a percentage down to a multiple of `step` for display
+///
+/// Rounding down keeps a window from showing 100% before it ends; overtime
+/// (>100%) and non-finite values are left as they are.
+///
+/// # Examples
+///
+/// ```
+/// use pmon::progress_bar::quantize_percentage;
+///
+/// assert_eq!(quantize_percentage(42.7, 5.0), 40.0);
+/// assert_eq!(quantize_percentage(99.9, 5.0), 95.0);
+/// assert_eq!(quantize_percentage(112.3, 5.0), 112.3);
+/// ```
+pub fn quantize_percentage(percentage: f64, step: f64) -> f64 {
+    if !(percentage.is_finite() && step.is_finite() && step > 0.0) || percentage >= 100.0 {
+        return percentage;
+    }
+    (percentage / step).floor() * step
 }
 
 /// Render a visual progress bar with color support
@@ -255,15 +604,21 @@ pub fn render_progress_bar(percentage: f64) -> String {
 /// let overtime = render_colored_progress_bar(150.0);
 /// // Contains red-colored: "[████████████████████████████████████████] 150.0%"
 /// ```
+#[deprecated(note = "use `render` with `RenderOptions`")]
 pub fn render_colored_progress_bar(percentage: f64) -> String {
-    let bar = render_progress_bar(percentage);
-
-    // Apply red color for overtime (>100%)
-    if percentage > 100.0 {
-        bar.red().to_string()
-    } else {
-        bar
-    }
+    render(
+        &state_at(
+            percentage,
+            NaiveDateTime::default(),
+            NaiveDateTime::default(),
+            NaiveDateTime::default(),
+        ),
+        &RenderOptions {
+            color: true,
+            time: None,
+            ..RenderOptions::default()
+        },
+    )
 }
 
 /// Render a visual progress bar with time information
@@ -295,22 +650,229 @@ pub fn render_colored_progress_bar(percentage: f64) -> String {
 /// let result = render_progress_bar_with_time(32.5, start, end, current);
 /// // Contains: "[████████████░░░░░░░░░░░░░░░░░░░░░░░░░░░░] 32.5% (2h 36m elapsed, 5h 24m remaining)"
 /// ```
+#[deprecated(note = "use `render` with `RenderOptions`")]
 pub fn render_progress_bar_with_time(
     percentage: f64,
     start: NaiveDateTime,
     end: NaiveDateTime,
     current: NaiveDateTime,
 ) -> String {
-    let base_bar = render_progress_bar(percentage);
+    render(
+        &state_at(percentage, start, end, current),
+        &RenderOptions {
+            ..RenderOptions::default()
+        },
+    )
+}
+
+/// Render a visual progress bar with time information in the given style
+///
+/// Same as [`render_progress_bar_with_time`], with durations formatted by
+/// [`format_duration_with`]. Accepts either a bare [`DurationStyle`] or a
+/// full [`DurationFormat`] that also controls rounding.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDateTime;
+/// use pmon::progress_bar::{render_progress_bar_with_styled_time, DurationStyle};
+///
+/// let start = NaiveDateTime::parse_from_str("2025-01-27 09:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+/// let end = NaiveDateTime::parse_from_str("2025-01-27 17:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+/// let current = NaiveDateTime::parse_from_str("2025-01-27 11:36:00", "%Y-%m-%d %H:%M:%S").unwrap();
+///
+/// let result = render_progress_bar_with_styled_time(32.5, start, end, current, DurationStyle::Colon);
+/// assert!(result.ends_with("(02:36:00 elapsed, 05:24:00 remaining)"));
+/// ```
+///
+/// Before the start there is no progress to show, so the empty bar is
+/// followed by the time until the window opens, such as `(starts in 25m)`.
+#[deprecated(note = "use `render` with `RenderOptions`")]
+pub fn render_progress_bar_with_styled_time(
+    percentage: f64,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    current: NaiveDateTime,
+    format: impl Into<DurationFormat>,
+) -> String {
+    render(
+        &state_at(percentage, start, end, current),
+        &RenderOptions {
+            duration_format: format.into(),
+            ..RenderOptions::default()
+        },
+    )
+}
 
+/// Format the elapsed/remaining suffix shown after the bar
+///
+/// Produces `(2h 36m elapsed, 5h 24m remaining)`, or once the end has passed
+/// `(9h 12m elapsed, +1h 12m over)`. Shared by every renderer that appends
+/// time information so the wording stays consistent.
+fn format_time_info(
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    current: NaiveDateTime,
+    format: DurationFormat,
+) -> String {
     // Calculate elapsed and remaining time
-    let elapsed_duration = current - start;
-    let remaining_duration = end - current;
+    let elapsed_duration = civil_duration(start, current);
+    let remaining_duration = civil_duration(current, end);
+
+    let elapsed_str = format.elapsed(elapsed_duration);
+    if remaining_duration < Duration::zero() {
+        let overtime_str = format_overtime(-remaining_duration, format);
+        return format!("({elapsed_str} elapsed, {overtime_str})");
+    }
+    let remaining_str = format.remaining(remaining_duration);
+
+    format!("({elapsed_str} elapsed, {remaining_str} remaining)")
+}
+
+/// Format the time left before the start as `(starts in 25m)`
+///
+/// # Examples
+///
+/// ```
+/// use chrono::Duration;
+/// use pmon::progress_bar::{format_starts_in, DurationStyle};
+///
+/// assert_eq!(format_starts_in(Duration::minutes(25), DurationStyle::Compact), "(starts in 25m)");
+/// ```
+pub fn format_starts_in(until: Duration, format: impl Into<DurationFormat>) -> String {
+    format!("(starts in {})", format.into().remaining(until))
+}
 
-    let elapsed_str = format_duration(elapsed_duration);
-    let remaining_str = format_duration(remaining_duration);
+/// Bar filled to `fill` without a percentage, shown before the start
+fn render_bare_bar(fill: f64, width: usize, cells: BarCells) -> String {
+    let filled_chars = filled_cells(fill, width);
+    format!(
+        "[{}{}]",
+        cells.filled.to_string().repeat(filled_chars),
+        cells.empty.to_string().repeat(width - filled_chars)
+    )
+}
 
-    format!("{base_bar} ({elapsed_str} elapsed, {remaining_str} remaining)")
+/// Format time spent past the end as `+1h 12m over`
+///
+/// # Examples
+///
+/// ```
+/// use chrono::Duration;
+/// use pmon::progress_bar::{format_overtime, DurationStyle};
+///
+/// assert_eq!(format_overtime(Duration::minutes(72), DurationStyle::Compact), "+1h 12m over");
+/// assert_eq!(format_overtime(Duration::minutes(72), DurationStyle::Colon), "+01:12:00 over");
+/// ```
+pub fn format_overtime(overtime: Duration, format: impl Into<DurationFormat>) -> String {
+    format!("+{} over", format.into().elapsed(overtime))
+}
+
+/// How the time information after the bar is expressed
+///
+/// `Durations` shows elapsed/remaining amounts (`2h 36m elapsed`), while
+/// `Timestamps` shows wall-clock times (`started 09:00 · now 11:36 · ends 17:00`)
+/// for users who think in clock terms. Interactive mode toggles between them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeInfoStyle {
+    /// Elapsed and remaining durations
+    #[default]
+    Durations,
+    /// Start, current, and end wall-clock timestamps
+    Timestamps,
+}
+
+impl TimeInfoStyle {
+    /// Return the other style
+    pub fn toggle(self) -> Self {
+        match self {
+            Self::Durations => Self::Timestamps,
+            Self::Timestamps => Self::Durations,
+        }
+    }
+}
+
+/// Format start, current, and end as wall-clock timestamps
+///
+/// Times on the same calendar day as `current` are shown as `HH:MM`; times on
+/// other days include the date so multi-day windows stay unambiguous.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDateTime;
+/// use pmon::progress_bar::format_timestamps;
+///
+/// let start = NaiveDateTime::parse_from_str("2025-01-27 09:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+/// let end = NaiveDateTime::parse_from_str("2025-01-27 17:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+/// let current = NaiveDateTime::parse_from_str("2025-01-27 11:36:00", "%Y-%m-%d %H:%M:%S").unwrap();
+///
+/// assert_eq!(
+///     format_timestamps(start, end, current),
+///     "(started 09:00 · now 11:36 · ends 17:00)"
+/// );
+/// ```
+pub fn format_timestamps(
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    current: NaiveDateTime,
+) -> String {
+    let clock = |time: NaiveDateTime| {
+        if time.date() == current.date() {
+            time.format("%H:%M").to_string()
+        } else {
+            time.format("%Y-%m-%d %H:%M").to_string()
+        }
+    };
+
+    let started = if current < start { "starts" } else { "started" };
+    format!(
+        "({started} {} · now {} · ends {})",
+        clock(start),
+        current.format("%H:%M"),
+        clock(end)
+    )
+}
+
+/// Render a visual progress bar with wall-clock timestamps
+///
+/// Same as [`render_progress_bar_with_time`] but the suffix is produced by
+/// [`format_timestamps`].
+#[deprecated(note = "use `render` with `RenderOptions`")]
+pub fn render_progress_bar_with_timestamps(
+    percentage: f64,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    current: NaiveDateTime,
+) -> String {
+    render(
+        &state_at(percentage, start, end, current),
+        &RenderOptions {
+            time: Some(TimeInfoStyle::Timestamps),
+            ..RenderOptions::default()
+        },
+    )
+}
+
+/// Render a visual progress bar with color support and wall-clock timestamps
+///
+/// Overtime (>100%) lines are red, matching
+/// [`render_colored_progress_bar_with_time`].
+#[deprecated(note = "use `render` with `RenderOptions`")]
+pub fn render_colored_progress_bar_with_timestamps(
+    percentage: f64,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    current: NaiveDateTime,
+) -> String {
+    render(
+        &state_at(percentage, start, end, current),
+        &RenderOptions {
+            color: true,
+            time: Some(TimeInfoStyle::Timestamps),
+            ..RenderOptions::default()
+        },
+    )
 }
 
 /// Render a visual progress bar with color support and time information
@@ -329,22 +891,770 @@ pub fn render_progress_bar_with_time(
 /// # Returns
 ///
 /// Returns a formatted string with colored progress bar and time information
+#[deprecated(note = "use `render` with `RenderOptions`")]
 pub fn render_colored_progress_bar_with_time(
     percentage: f64,
     start: NaiveDateTime,
     end: NaiveDateTime,
     current: NaiveDateTime,
 ) -> String {
-    let bar = render_progress_bar_with_time(percentage, start, end, current);
+    render(
+        &state_at(percentage, start, end, current),
+        &RenderOptions {
+            color: true,
+            ..RenderOptions::default()
+        },
+    )
+}
 
-    // Apply red color for overtime (>100%)
-    if percentage > 100.0 {
-        bar.red().to_string()
+/// Render a visual progress bar with color support and styled time information
+///
+/// Same as [`render_colored_progress_bar_with_time`], with durations
+/// formatted by [`format_duration_with`].
+#[deprecated(note = "use `render` with `RenderOptions`")]
+pub fn render_colored_progress_bar_with_styled_time(
+    percentage: f64,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    current: NaiveDateTime,
+    format: impl Into<DurationFormat>,
+) -> String {
+    render(
+        &state_at(percentage, start, end, current),
+        &RenderOptions {
+            color: true,
+            duration_format: format.into(),
+            ..RenderOptions::default()
+        },
+    )
+}
+
+/// Split an overlay bar into runs of cells that share the same styling
+///
+/// Each run is `(text, on_fill)` where `on_fill` marks percentage text that
+/// sits on top of filled cells and therefore needs contrasting styling.
+fn overlay_runs(fill: f64, shown: f64, width: usize, cells: BarCells) -> Vec<(String, bool)> {
+    let filled_chars = filled_cells(fill, width);
+
+    let text: Vec<char> = format_percentage(shown).chars().collect();
+    let text_start = width.saturating_sub(text.len()) / 2;
+    let text_end = (text_start + text.len()).min(width);
+
+    let mut runs: Vec<(String, bool)> = Vec::new();
+    for cell in 0..width {
+        let is_filled = cell < filled_chars;
+        let (ch, on_fill) = if (text_start..text_end).contains(&cell) {
+            (text[cell - text_start], is_filled)
+        } else if is_filled {
+            (cells.filled, false)
+        } else {
+            (cells.empty, false)
+        };
+
+        match runs.last_mut() {
+            Some((run, run_on_fill)) if *run_on_fill == on_fill => run.push(ch),
+            _ => runs.push((ch.to_string(), on_fill)),
+        }
+    }
+    runs
+}
+
+/// Render a progress bar with the percentage centered inside the bar
+///
+/// The percentage text overwrites the fill characters in the middle of the
+/// bar instead of following it, which frees horizontal space for the time
+/// information in narrow terminals. The bar keeps its fixed 40-character
+/// width.
+///
+/// # Examples
+///
+/// ```
+/// use pmon::progress_bar::render_progress_bar_overlay;
+///
+/// assert_eq!(
+///     render_progress_bar_overlay(50.0),
+///     "[█████████████████50.0%░░░░░░░░░░░░░░░░░░]"
+/// );
+/// ```
+pub fn render_progress_bar_overlay(percentage: f64) -> String {
+    render_overlay(
+        percentage,
+        percentage,
+        BAR_WIDTH,
+        false,
+        false,
+        BarCells::default(),
+    )
+}
+
+/// Render an overlay progress bar with color support
+///
+/// Percentage text that sits on filled cells is shown in reverse video so it
+/// stays readable against the solid blocks. Overtime (>100%) bars are red,
+/// matching [`render_colored_progress_bar`].
+#[deprecated(note = "use `render` with `RenderOptions`")]
+pub fn render_colored_progress_bar_overlay(percentage: f64) -> String {
+    render_overlay(
+        percentage,
+        percentage,
+        BAR_WIDTH,
+        true,
+        percentage > 100.0,
+        BarCells::default(),
+    )
+}
+
+/// Overlay bar of `width` cells filled to `fill` around the `shown`
+/// percentage, styled when `color` is set
+fn render_overlay(
+    fill: f64,
+    shown: f64,
+    width: usize,
+    color: bool,
+    overtime: bool,
+    cells: BarCells,
+) -> String {
+    let style = |text: &str, on_fill: bool| -> String {
+        match (color, overtime, on_fill) {
+            (false, _, _) | (true, false, false) => text.to_string(),
+            (true, true, true) => text.red().reversed().to_string(),
+            (true, true, false) => text.red().to_string(),
+            (true, false, true) => text.reversed().to_string(),
+        }
+    };
+
+    let runs: String = overlay_runs(fill, shown, width, cells)
+        .iter()
+        .map(|(run, on_fill)| style(run, *on_fill))
+        .collect();
+    format!("{}{runs}{}", style("[", false), style("]", false))
+}
+
+/// Render an overlay progress bar with color support and time information
+///
+/// Produces `[███42.5%░░░] (2h 36m elapsed, 5h 24m remaining)`, using the
+/// same time suffix as [`render_progress_bar_with_styled_time`].
+#[deprecated(note = "use `render` with `RenderOptions`")]
+pub fn render_colored_progress_bar_overlay_with_time(
+    percentage: f64,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    current: NaiveDateTime,
+    format: impl Into<DurationFormat>,
+) -> String {
+    render(
+        &state_at(percentage, start, end, current),
+        &RenderOptions {
+            color: true,
+            style: BarStyle::Overlay,
+            duration_format: format.into(),
+            ..RenderOptions::default()
+        },
+    )
+}
+
+/// Render an overlay progress bar with color support and wall-clock timestamps
+#[deprecated(note = "use `render` with `RenderOptions`")]
+pub fn render_colored_progress_bar_overlay_with_timestamps(
+    percentage: f64,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    current: NaiveDateTime,
+) -> String {
+    render(
+        &state_at(percentage, start, end, current),
+        &RenderOptions {
+            color: true,
+            style: BarStyle::Overlay,
+            time: Some(TimeInfoStyle::Timestamps),
+            ..RenderOptions::default()
+        },
+    )
+}
+
+/// Where the percentage goes relative to the bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BarStyle {
+    /// After the bar: `[████░░░░] 50.0%`
+    #[default]
+    Classic,
+    /// Centered inside the bar: `[██50.0%░░]`
+    Overlay,
+    /// A thin line for secondary bars, with the percentage after it:
+    /// ` ━━━━────  50.0%`
+    Thin,
+    /// The bar and a coarse phase word, with no numbers:
+    /// `[████░░░░] midway`
+    Zen,
+    /// Double-width emoji cells, as shared on social media, with the
+    /// percentage after them: `🟩🟩⬜⬜ 50.0%`
+    Emoji(EmojiCells),
+}
+
+impl BarStyle {
+    /// Columns taken by the cells of a bar `width` columns wide
+    fn cell_columns(self, width: usize) -> usize {
+        match self {
+            BarStyle::Emoji(_) => width / 2 * 2,
+            _ => width,
+        }
+    }
+}
+
+/// The filled and empty cells of a bracketed bar, `█` and `░` by default
+///
+/// Themes may choose others, but each must take a single column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BarCells {
+    /// Cell for elapsed time
+    pub filled: char,
+    /// Cell for remaining time
+    pub empty: char,
+}
+
+impl Default for BarCells {
+    fn default() -> Self {
+        Self {
+            filled: '█',
+            empty: '░',
+        }
+    }
+}
+
+/// The filled and empty cells of an emoji bar
+///
+/// Given as the two emoji, filled first, such as `🟦⬛`. Both must be
+/// double-width so the bar keeps its length in columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmojiCells {
+    /// Cell for elapsed time
+    pub filled: char,
+    /// Cell for remaining time
+    pub empty: char,
+}
+
+impl Default for EmojiCells {
+    fn default() -> Self {
+        Self {
+            filled: '🟩',
+            empty: '⬜',
+        }
+    }
+}
+
+impl EmojiCells {
+    /// Cells filled to `fill` in `width` columns, two columns per cell
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pmon::progress_bar::EmojiCells;
+    ///
+    /// assert_eq!(EmojiCells::default().render(50.0, 8), "🟩🟩⬜⬜");
+    /// assert_eq!(EmojiCells::default().render(50.0, 9), "🟩🟩⬜⬜");
+    /// ```
+    pub fn render(self, fill: f64, width: usize) -> String {
+        let cells = width / 2;
+        let filled_chars = filled_cells(fill, cells);
+        format!(
+            "{}{}",
+            self.filled.to_string().repeat(filled_chars),
+            self.empty.to_string().repeat(cells - filled_chars)
+        )
+    }
+}
+
+impl FromStr for EmojiCells {
+    type Err = PbError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let invalid = || PbError::invalid_argument("--emoji", input);
+        let cells: Vec<char> = input.chars().filter(|ch| !ch.is_whitespace()).collect();
+        let [filled, empty] = cells[..] else {
+            return Err(invalid());
+        };
+        let double_width = |ch: char| layout::display_width(ch.encode_utf8(&mut [0; 4])) == 2;
+        if !(double_width(filled) && double_width(empty)) {
+            return Err(invalid());
+        }
+        Ok(Self { filled, empty })
+    }
+}
+
+/// How [`render`] draws a bar
+///
+/// The defaults give the plain 40-cell bar followed by the elapsed and
+/// remaining time, as in `[████░░░░] 32.5% (2h 36m elapsed, 5h 24m remaining)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderOptions {
+    /// Color overtime lines red and reverse the overlay text on filled cells
+    pub color: bool,
+    /// Number of cells between the brackets; emoji cells take two each
+    pub width: usize,
+    /// Where the percentage is shown
+    pub style: BarStyle,
+    /// Time information after the bar, or `None` for the bar alone
+    pub time: Option<TimeInfoStyle>,
+    /// How durations in the time information are formatted
+    pub duration_format: DurationFormat,
+    /// Snap the percentage and bar to multiples of this many points
+    pub step: Option<f64>,
+    /// Show the percentage remaining, counting down to 0%, instead of the
+    /// percentage elapsed
+    pub invert: bool,
+    /// Empty the bar as time passes instead of filling it
+    pub drain: bool,
+    /// Characters of the filled and empty cells between the brackets
+    pub cells: BarCells,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            color: false,
+            width: BAR_WIDTH,
+            style: BarStyle::default(),
+            time: Some(TimeInfoStyle::default()),
+            duration_format: DurationFormat::default(),
+            step: None,
+            invert: false,
+            drain: false,
+            cells: BarCells::default(),
+        }
+    }
+}
+
+/// Render a progress line for `state`
+///
+/// Every bar pmon prints comes from here. Before the start, a line with time
+/// information shows the bar as it stands at the start and the time until the
+/// window opens instead of `0.0%`; past the end, colored lines turn red.
+/// `invert` shows the percentage remaining and `drain` empties the bar, as
+/// exam and presentation timers usually do.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDateTime;
+/// use pmon::progress_bar::{render, BarStyle, RenderOptions};
+/// use pmon::progress_state::ProgressState;
+///
+/// let at = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+/// let state = ProgressState::new(
+///     at("2025-01-27 09:00:00"),
+///     at("2025-01-27 17:00:00"),
+///     at("2025-01-27 13:00:00"),
+/// );
+///
+/// assert_eq!(
+///     render(&state, &RenderOptions { width: 10, ..RenderOptions::default() }),
+///     "[█████░░░░░] 50.0% (4h 0m elapsed, 4h 0m remaining)"
+/// );
+/// assert_eq!(
+///     render(&state, &RenderOptions { width: 10, style: BarStyle::Overlay, time: None, ..RenderOptions::default() }),
+///     "[██50.0%░░░]"
+/// );
+/// ```
+pub fn render(state: &ProgressState, options: &RenderOptions) -> String {
+    let ProgressState {
+        start,
+        current,
+        percentage,
+        ..
+    } = *state;
+    let percentage = match options.step {
+        Some(step) => quantize_percentage(percentage, step),
+        None => percentage,
+    };
+    // Taken from the snapped percentage so the remainder only reaches 0% at
+    // the end; overtime leaves nothing rather than a negative remainder
+    let remaining = if percentage.is_nan() {
+        percentage
+    } else {
+        (100.0 - percentage).max(0.0)
+    };
+    let shown = if options.invert {
+        remaining
+    } else {
+        percentage
+    };
+    let fill = if options.drain { remaining } else { percentage };
+    let pending = options.time.is_some() && current < start;
+    let pending_fill = if options.drain { 100.0 } else { 0.0 };
+    let bar = match options.style {
+        // Zen shows no times, so it names the phase even before the start
+        BarStyle::Zen if current < start => {
+            format!(
+                "{} not started",
+                render_bare_bar(pending_fill, options.width, options.cells)
+            )
+        }
+        BarStyle::Zen => format!(
+            "{} {}",
+            render_bare_bar(fill, options.width, options.cells),
+            format_phase(percentage)
+        ),
+        BarStyle::Thin if pending => format!(" {}", thin_cells(pending_fill, options.width)),
+        BarStyle::Emoji(cells) if pending => cells.render(pending_fill, options.width),
+        _ if pending => render_bare_bar(pending_fill, options.width, options.cells),
+        BarStyle::Classic => render_bar(fill, shown, options.width, options.cells),
+        // Lined up with the percentage of a bracketed bar above it
+        BarStyle::Thin => format!(
+            " {}  {}",
+            thin_cells(fill, options.width),
+            format_percentage(shown)
+        ),
+        BarStyle::Overlay => render_overlay(
+            fill,
+            shown,
+            options.width,
+            options.color,
+            percentage > 100.0,
+            options.cells,
+        ),
+        BarStyle::Emoji(cells) => format!(
+            "{} {}",
+            cells.render(fill, options.width),
+            format_percentage(shown)
+        ),
+    };
+    let time_info = render_time_info(state, options);
+
+    match (options.style, time_info) {
+        (BarStyle::Zen, _) => color_overtime(bar, percentage, options),
+        (BarStyle::Classic | BarStyle::Thin | BarStyle::Emoji(_), None) => {
+            color_overtime(bar, percentage, options)
+        }
+        (BarStyle::Classic | BarStyle::Thin | BarStyle::Emoji(_), Some(time_info)) => {
+            color_overtime(format!("{bar} {time_info}"), percentage, options)
+        }
+        // The overlay bar is already styled cell by cell
+        (BarStyle::Overlay, None) => bar,
+        (BarStyle::Overlay, Some(time_info)) => {
+            format!("{bar} {}", color_overtime(time_info, percentage, options))
+        }
+    }
+}
+
+/// The time information shown after the bar, if `options` asks for it
+fn render_time_info(state: &ProgressState, options: &RenderOptions) -> Option<String> {
+    let ProgressState {
+        start,
+        end,
+        current,
+        ..
+    } = *state;
+    options.time.map(|time_style| match time_style {
+        TimeInfoStyle::Durations if current < start => {
+            format_starts_in(civil_duration(current, start), options.duration_format)
+        }
+        TimeInfoStyle::Durations => format_time_info(start, end, current, options.duration_format),
+        TimeInfoStyle::Timestamps => format_timestamps(start, end, current),
+    })
+}
+
+/// Render a progress line for `state` as lines at most `columns` wide
+///
+/// The columns are shared out with [`layout::allocate`]. A line too wide for
+/// the terminal first gets a shorter bar, down to [`MIN_BAR_WIDTH`] cells.
+/// Past that, the time information moves to a second line and the bar
+/// shrinks further to fit beside its percentage, so the terminal never wraps
+/// a line in the middle of a color code.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDateTime;
+/// use pmon::progress_bar::{render_fitted, RenderOptions};
+/// use pmon::ProgressState;
+///
+/// let at = |time| NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M").unwrap();
+/// let state = ProgressState::new(at("2025-01-27 09:00"), at("2025-01-27 17:00"), at("2025-01-27 13:00"));
+/// let options = RenderOptions::default();
+/// assert_eq!(render_fitted(&state, &options, 80).len(), 1);
+/// let lines = render_fitted(&state, &options, 40);
+/// assert_eq!(lines[0], format!("[{}{}] 50.0%", "█".repeat(16), "░".repeat(16)));
+/// assert_eq!(lines[1], "(4h 0m elapsed, 4h 0m remaining)");
+/// ```
+pub fn render_fitted(
+    state: &ProgressState,
+    options: &RenderOptions,
+    columns: usize,
+) -> Vec<String> {
+    let measure = LineMeasure::of(state, options);
+    let widths = layout::allocate(&measure.slots(options.width), columns);
+    // Narrower than the shortest bar, the bar takes whatever is left
+    let width = match widths[0] {
+        0 => columns.saturating_sub(measure.around),
+        shortest => shortest + widths[2] - measure.around,
+    }
+    .min(options.width);
+    let time_info = render_time_info(state, options).filter(|_| measure.time.is_some());
+    match time_info {
+        Some(time_info) if widths[1] == 0 => vec![
+            render(
+                state,
+                &RenderOptions {
+                    width,
+                    time: None,
+                    ..*options
+                },
+            ),
+            color_overtime(time_info, state.percentage, options),
+        ],
+        _ => vec![render(state, &RenderOptions { width, ..*options })],
+    }
+}
+
+/// Width of the label column in front of `state`'s bar in `columns`
+///
+/// Once the bar is down to [`MIN_BAR_WIDTH`] cells, the column gives way
+/// to [`MIN_LABEL_WIDTH`] columns before the time information moves below
+/// the bar. Without room for the shortest bar and label, labels are left
+/// out and 0 is returned.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDateTime;
+/// use pmon::progress_bar::{fit_label_column, RenderOptions};
+/// use pmon::ProgressState;
+///
+/// let at = |time| NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M").unwrap();
+/// let state = ProgressState::new(at("2025-01-27 09:00"), at("2025-01-27 17:00"), at("2025-01-27 13:00"));
+/// let options = RenderOptions::default();
+/// assert_eq!(fit_label_column(&state, &options, 20, 120), 20);
+/// assert_eq!(fit_label_column(&state, &options, 20, 60), 8);
+/// assert_eq!(fit_label_column(&state, &options, 20, 20), 0);
+/// ```
+pub fn fit_label_column(
+    state: &ProgressState,
+    options: &RenderOptions,
+    label_width: usize,
+    columns: usize,
+) -> usize {
+    let label_width = label_width.min(layout::max_label_width());
+    if label_width == 0 {
+        return 0;
+    }
+    let [bar, time, rest] = LineMeasure::of(state, options).slots(options.width);
+    // The label and the space after it
+    let label = layout::Slot::new(MIN_LABEL_WIDTH.min(label_width) + 1, label_width + 1);
+    layout::allocate(&[bar, label, time, rest], columns)[1].saturating_sub(1)
+}
+
+/// Columns a progress line takes besides the cells of its bar
+struct LineMeasure {
+    /// Brackets and percentage around the cells
+    around: usize,
+    /// Time information after the bar, with the space before it
+    time: Option<usize>,
+}
+
+impl LineMeasure {
+    fn of(state: &ProgressState, options: &RenderOptions) -> Self {
+        let bar_only = layout::visible_width(&render(
+            state,
+            &RenderOptions {
+                time: None,
+                ..*options
+            },
+        ));
+        // Zen shows no time information to move
+        let time = match options.style {
+            BarStyle::Zen => None,
+            _ => options
+                .time
+                .map(|_| layout::visible_width(&render(state, options)) - bar_only),
+        };
+        Self {
+            around: bar_only.saturating_sub(options.style.cell_columns(options.width)),
+            time,
+        }
+    }
+
+    /// The parts of the line sharing the columns, from the most to the least
+    /// important: the shortest bar with its percentage, the time
+    /// information, and the rest of a bar `width` cells long
+    fn slots(&self, width: usize) -> [layout::Slot; 3] {
+        let shortest = width.min(MIN_BAR_WIDTH);
+        [
+            layout::Slot::fixed(self.around + shortest),
+            layout::Slot::fixed(self.time.unwrap_or(0)),
+            layout::Slot::new(0, width - shortest),
+        ]
+    }
+}
+
+/// Cells of a thin bar filled to `fill`
+fn thin_cells(fill: f64, width: usize) -> String {
+    let filled_chars = filled_cells(fill, width);
+    format!(
+        "{}{}",
+        "━".repeat(filled_chars),
+        "─".repeat(width - filled_chars)
+    )
+}
+
+/// Color `text` red when it belongs to an overtime (>100%) colored line
+fn color_overtime(text: String, percentage: f64, options: &RenderOptions) -> String {
+    if options.color && percentage > 100.0 {
+        text.red().to_string()
     } else {
-        bar
+        text
     }
 }
 
+/// State for the deprecated renderers, which take a percentage of their own
+fn state_at(
+    percentage: f64,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    current: NaiveDateTime,
+) -> ProgressState {
+    ProgressState {
+        start,
+        end,
+        current,
+        percentage,
+    }
+}
+
+/// Width of the moving block in the indeterminate bar
+const INDETERMINATE_BLOCK_WIDTH: usize = 8;
+
+/// Render an indeterminate (marquee) bar for windows without a known end
+///
+/// A block of `█` cells bounces between the edges of the 40-character bar;
+/// passing successive `frame` numbers animates it. No percentage is shown
+/// because there is nothing to measure progress against.
+///
+/// # Examples
+///
+/// ```
+/// use pmon::progress_bar::render_indeterminate_bar;
+///
+/// assert_eq!(
+///     render_indeterminate_bar(0),
+///     format!("[{}{}]", "█".repeat(8), "░".repeat(32))
+/// );
+/// assert_eq!(
+///     render_indeterminate_bar(3),
+///     format!("[{}{}{}]", "░".repeat(3), "█".repeat(8), "░".repeat(29))
+/// );
+/// ```
+pub fn render_indeterminate_bar(frame: usize) -> String {
+    let travel = BAR_WIDTH - INDETERMINATE_BLOCK_WIDTH;
+    let step = frame % (2 * travel);
+    // Move right for the first half of the cycle, then back left
+    let offset = if step <= travel {
+        step
+    } else {
+        2 * travel - step
+    };
+
+    format!(
+        "[{}{}{}]",
+        "░".repeat(offset),
+        "█".repeat(INDETERMINATE_BLOCK_WIDTH),
+        "░".repeat(travel - offset)
+    )
+}
+
+/// What a progress bar shows for a window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BarState {
+    /// Known end: a filled bar with the given percentage
+    Determinate(f64),
+    /// Unknown end: a bouncing marquee at the given animation frame
+    Indeterminate(usize),
+}
+
+/// Render a bar for either a determinate or an indeterminate window
+///
+/// # Examples
+///
+/// ```
+/// use pmon::progress_bar::{render_bar_state, render_indeterminate_bar, render_progress_bar, BarState};
+///
+/// assert_eq!(render_bar_state(BarState::Determinate(50.0)), render_progress_bar(50.0));
+/// assert_eq!(render_bar_state(BarState::Indeterminate(7)), render_indeterminate_bar(7));
+/// ```
+pub fn render_bar_state(state: BarState) -> String {
+    match state {
+        BarState::Determinate(percentage) => render_progress_bar(percentage),
+        BarState::Indeterminate(frame) => render_indeterminate_bar(frame),
+    }
+}
+
+/// Prefix a rendered progress line with a label column
+///
+/// The label is padded by display width (not bytes or chars) to
+/// `label_width` columns, so several bars with CJK or emoji labels line up
+/// when stacked. Pass `0` for `label_width` when rendering a single bar.
+/// Labels wider than [`layout::max_label_width`] or the column are cut
+/// short with `…`, and the column never grows past it. Lines without a
+/// label are returned unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use pmon::progress_bar::render_labeled_line;
+///
+/// assert_eq!(render_labeled_line(None, 0, "[█░] 50.0%"), "[█░] 50.0%");
+/// assert_eq!(render_labeled_line(Some("デモ"), 6, "[█░] 50.0%"), "デモ   [█░] 50.0%");
+/// ```
+pub fn render_labeled_line(label: Option<&str>, label_width: usize, line: &str) -> String {
+    match label {
+        Some(label) => {
+            let label_width = label_width.min(layout::max_label_width());
+            let label = layout::fit_label(label);
+            // A column squeezed narrower than the label cuts it shorter
+            let label = match label_width {
+                0 => label,
+                width => layout::ellipsize(&label, width).into_owned().into(),
+            };
+            format!("{} {line}", layout::pad_end(&label, label_width))
+        }
+        None => line.to_string(),
+    }
+}
+
+/// Columns [`render_labeled_line`] puts before the line of `label`
+pub fn label_indent(label: Option<&str>, label_width: usize) -> usize {
+    label.map_or(0, |_| label_width.min(layout::max_label_width()) + 1)
+}
+
+/// Label the first of `lines` as [`render_labeled_line`] does and indent the
+/// rest to line up beneath it
+///
+/// # Examples
+///
+/// ```
+/// use pmon::progress_bar::render_labeled_lines;
+///
+/// let lines = ["[█░] 50.0%".to_string(), "(1h 0m elapsed, 1h 0m remaining)".to_string()];
+/// assert_eq!(
+///     render_labeled_lines(Some("Demo"), 4, &lines),
+///     ["Demo [█░] 50.0%", "     (1h 0m elapsed, 1h 0m remaining)"]
+/// );
+/// ```
+pub fn render_labeled_lines(
+    label: Option<&str>,
+    label_width: usize,
+    lines: &[String],
+) -> Vec<String> {
+    let indent = " ".repeat(label_indent(label, label_width));
+    lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| match index {
+            0 => render_labeled_line(label, label_width, line),
+            _ => format!("{indent}{line}"),
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod format_duration_tests {
     use super::*;
@@ -377,26 +1687,221 @@ mod format_duration_tests {
         // Test zero duration
         assert_eq!(format_duration(Duration::zero()), "0m");
 
-        // Test negative duration
-        assert_eq!(format_duration(Duration::minutes(-10)), "0m");
+        // Test negative duration
+        assert_eq!(format_duration(Duration::minutes(-10)), "0m");
+    }
+
+    #[test]
+    fn test_format_duration_edge_cases() {
+        // Large durations
+        assert_eq!(
+            format_duration(Duration::hours(100) + Duration::minutes(30)),
+            "100h 30m"
+        );
+        assert_eq!(format_duration(Duration::hours(24)), "24h 0m");
+
+        // Exactly at boundaries
+        assert_eq!(format_duration(Duration::minutes(60)), "1h 0m"); // 1 hour
+        assert_eq!(format_duration(Duration::hours(1)), "1h 0m"); // 1 hour as hours
+    }
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod duration_style_tests {
+    use super::*;
+
+    #[test]
+    fn test_compact_matches_format_duration_under_a_day() {
+        let cases = [
+            Duration::zero(),
+            Duration::seconds(59),
+            Duration::minutes(45),
+            Duration::hours(1),
+            Duration::hours(23) + Duration::minutes(59),
+            Duration::minutes(-10),
+        ];
+        for duration in cases {
+            assert_eq!(
+                format_duration_with(duration, DurationStyle::Compact),
+                format_duration(duration)
+            );
+            assert_eq!(
+                format_duration_with(duration, DurationStyle::CompactExact),
+                format_duration(duration)
+            );
+        }
+    }
+
+    #[test]
+    fn test_compact_day_and_week_tiers() {
+        let compact = |d| format_duration_compact(d, true);
+        assert_eq!(compact(Duration::hours(24)), "1d 0h");
+        assert_eq!(
+            compact(Duration::hours(100) + Duration::minutes(30)),
+            "4d 4h"
+        );
+        assert_eq!(compact(Duration::days(6) + Duration::hours(23)), "6d 23h");
+        assert_eq!(compact(Duration::days(7)), "1w 0d");
+        assert_eq!(compact(Duration::days(17)), "2w 3d");
+        assert_eq!(compact(Duration::days(29)), "4w 1d");
+    }
+
+    #[test]
+    fn test_compact_approximate_month_and_year_tiers() {
+        let compact = |d| format_duration_compact(d, true);
+        assert_eq!(compact(Duration::days(30)), "1mo 0w");
+        assert_eq!(compact(Duration::days(351)), "11mo 3w");
+        assert_eq!(compact(Duration::days(364)), "12mo 0w");
+        assert_eq!(compact(Duration::days(365)), "1y 0mo");
+        assert_eq!(compact(Duration::days(425)), "1y 2mo");
+        assert_eq!(compact(Duration::days(36525)), "100y 0mo");
+    }
+
+    #[test]
+    fn test_compact_exact_stops_at_weeks() {
+        let exact = |d| format_duration_with(d, DurationStyle::CompactExact);
+        assert_eq!(exact(Duration::days(30)), "4w 2d");
+        assert_eq!(exact(Duration::days(365)), "52w 1d");
+        assert_eq!(exact(Duration::days(3)), "3d 0h");
+    }
+
+    #[test]
+    fn test_colon_style() {
+        let colon = |d| format_duration_with(d, DurationStyle::Colon);
+        assert_eq!(colon(Duration::zero()), "00:00:00");
+        assert_eq!(colon(Duration::seconds(59)), "00:00:59");
+        assert_eq!(colon(Duration::minutes(45)), "00:45:00");
+        assert_eq!(
+            colon(Duration::hours(2) + Duration::minutes(36) + Duration::seconds(10)),
+            "02:36:10"
+        );
+        assert_eq!(colon(Duration::hours(123)), "123:00:00");
+        assert_eq!(colon(Duration::minutes(-10)), "00:00:00");
+    }
+
+    #[test]
+    fn test_words_style() {
+        let words = |d| format_duration_with(d, DurationStyle::Words);
+        assert_eq!(words(Duration::zero()), "0 minutes");
+        assert_eq!(words(Duration::seconds(30)), "0 minutes");
+        assert_eq!(words(Duration::minutes(1)), "1 minute");
+        assert_eq!(words(Duration::minutes(45)), "45 minutes");
+        assert_eq!(words(Duration::hours(1)), "1 hour");
+        assert_eq!(
+            words(Duration::hours(1) + Duration::minutes(1)),
+            "1 hour 1 minute"
+        );
+        assert_eq!(
+            words(Duration::hours(2) + Duration::minutes(36)),
+            "2 hours 36 minutes"
+        );
+        assert_eq!(words(Duration::minutes(-10)), "0 minutes");
+    }
+
+    #[test]
+    fn test_styled_time_rendering() {
+        let start =
+            NaiveDateTime::parse_from_str("2025-01-27 09:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let end =
+            NaiveDateTime::parse_from_str("2025-01-27 17:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let current =
+            NaiveDateTime::parse_from_str("2025-01-27 11:36:00", "%Y-%m-%d %H:%M:%S").unwrap();
+
+        let result =
+            render_progress_bar_with_styled_time(32.5, start, end, current, DurationStyle::Words);
+        assert!(result.ends_with("(2 hours 36 minutes elapsed, 5 hours 24 minutes remaining)"));
+
+        // The default renderer keeps the compact style
+        assert_eq!(
+            render_progress_bar_with_time(32.5, start, end, current),
+            render_progress_bar_with_styled_time(32.5, start, end, current, DurationStyle::Compact)
+        );
+    }
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod rounding_tests {
+    use super::*;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_round_up_to() {
+        let minute = Duration::minutes(1);
+        assert_eq!(
+            round_up_to(Duration::seconds(59 * 60 + 54), minute),
+            Duration::minutes(60)
+        );
+        assert_eq!(
+            round_up_to(Duration::minutes(59), minute),
+            Duration::minutes(59)
+        );
+        assert_eq!(
+            round_up_to(Duration::seconds(1), minute),
+            Duration::minutes(1)
+        );
+        assert_eq!(round_up_to(Duration::zero(), minute), Duration::zero());
+        assert_eq!(
+            round_up_to(Duration::seconds(-30), minute),
+            Duration::seconds(-30)
+        );
+        assert_eq!(
+            round_up_to(Duration::milliseconds(1500), Duration::seconds(1)),
+            Duration::seconds(2)
+        );
+    }
+
+    #[test]
+    fn test_remaining_rounding_directions() {
+        let almost_hour = Duration::seconds(59 * 60 + 54);
+        let down = DurationFormat::default();
+        let up = DurationFormat {
+            remaining_rounding: Rounding::Up,
+            ..DurationFormat::default()
+        };
+
+        assert_eq!(down.remaining(almost_hour), "59m");
+        assert_eq!(up.remaining(almost_hour), "1h 0m");
+
+        // Elapsed time is always truncated
+        assert_eq!(up.elapsed(almost_hour), "59m");
     }
 
     #[test]
-    fn test_format_duration_edge_cases() {
-        // Large durations
-        assert_eq!(
-            format_duration(Duration::hours(100) + Duration::minutes(30)),
-            "100h 30m"
-        );
-        assert_eq!(format_duration(Duration::hours(24)), "24h 0m");
+    fn test_remaining_rounding_colon_precision() {
+        let up = DurationFormat {
+            style: DurationStyle::Colon,
+            remaining_rounding: Rounding::Up,
+        };
+        assert_eq!(up.remaining(Duration::milliseconds(59_400)), "00:01:00");
+        assert_eq!(up.remaining(Duration::seconds(59)), "00:00:59");
+    }
 
-        // Exactly at boundaries
-        assert_eq!(format_duration(Duration::minutes(60)), "1h 0m"); // 1 hour
-        assert_eq!(format_duration(Duration::hours(1)), "1h 0m"); // 1 hour as hours
+    #[test]
+    fn test_rendered_remaining_rounds_up() {
+        let start = create_test_datetime("2025-01-27 09:00:00");
+        let end = create_test_datetime("2025-01-27 10:00:00");
+        let current = create_test_datetime("2025-01-27 09:00:06");
+        let up = DurationFormat {
+            remaining_rounding: Rounding::Up,
+            ..DurationFormat::default()
+        };
+
+        let truncated =
+            render_progress_bar_with_styled_time(0.2, start, end, current, DurationStyle::Compact);
+        assert!(truncated.ends_with("(0m elapsed, 59m remaining)"));
+
+        let rounded = render_progress_bar_with_styled_time(0.2, start, end, current, up);
+        assert!(rounded.ends_with("(0m elapsed, 1h 0m remaining)"));
     }
 }
 
 #[cfg(test)]
+#[allow(deprecated)]
 mod render_with_time_tests {
     use super::*;
     use chrono::NaiveDateTime;
@@ -422,6 +1927,7 @@ mod render_with_time_tests {
     }
 
     #[test]
+    #[cfg(feature = "color")]
     fn test_render_colored_progress_bar_with_time_normal() {
         use colored::control;
 
@@ -453,6 +1959,45 @@ mod render_with_time_tests {
     }
 
     #[test]
+    fn test_time_info_overtime() {
+        let start = create_test_datetime("2025-01-27 09:00:00");
+        let end = create_test_datetime("2025-01-27 17:00:00");
+        let format = DurationFormat::from(DurationStyle::Compact);
+
+        let at_end = format_time_info(start, end, end, format);
+        assert_eq!(at_end, "(8h 0m elapsed, 0m remaining)");
+
+        let late = create_test_datetime("2025-01-27 18:12:00");
+        let result = render_progress_bar_with_time(115.0, start, end, late);
+        assert!(result.ends_with("(9h 12m elapsed, +1h 12m over)"));
+    }
+
+    #[test]
+    fn test_before_the_start() {
+        let start = create_test_datetime("2025-01-27 09:00:00");
+        let end = create_test_datetime("2025-01-27 17:00:00");
+        let early = create_test_datetime("2025-01-27 08:35:00");
+        let empty = format!("[{}]", "░".repeat(BAR_WIDTH));
+
+        assert_eq!(
+            render_progress_bar_with_time(0.0, start, end, early),
+            format!("{empty} (starts in 25m)")
+        );
+        assert_eq!(
+            render_progress_bar_with_styled_time(0.0, start, end, early, DurationStyle::Colon),
+            format!("{empty} (starts in 00:25:00)")
+        );
+        assert_eq!(
+            render_progress_bar_with_timestamps(0.0, start, end, early),
+            format!("{empty} (starts 09:00 · now 08:35 · ends 17:00)")
+        );
+        // Exactly at the start the window is open
+        assert!(render_progress_bar_with_time(0.0, start, end, start)
+            .ends_with("0.0% (0m elapsed, 8h 0m remaining)"));
+    }
+
+    #[test]
+    #[cfg(feature = "color")]
     fn test_render_colored_progress_bar_with_time_overtime() {
         use colored::control;
 
@@ -472,7 +2017,8 @@ mod render_with_time_tests {
         assert!(result.contains("125.0%"));
         // Should contain time information
         assert!(result.contains("10h 0m elapsed"));
-        assert!(result.contains("0m remaining")); // Negative remaining shows as 0m
+        assert!(result.contains("+2h 0m over")); // Past the end, overtime replaces remaining
+        assert!(!result.contains("remaining"));
 
         // When colors are forced on, overtime should potentially contain color codes
         // In some CI environments, colors may still be disabled, so we check the function doesn't panic
@@ -505,6 +2051,266 @@ mod render_with_time_tests {
         assert!(result.contains("1h 15m remaining"));
     }
 }
+#[cfg(test)]
+#[allow(deprecated)]
+mod timestamp_tests {
+    use super::*;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_format_timestamps_same_day() {
+        let start = create_test_datetime("2025-01-27 09:00:00");
+        let end = create_test_datetime("2025-01-27 17:00:00");
+        let current = create_test_datetime("2025-01-27 11:36:42");
+
+        assert_eq!(
+            format_timestamps(start, end, current),
+            "(started 09:00 · now 11:36 · ends 17:00)"
+        );
+    }
+
+    #[test]
+    fn test_format_timestamps_multi_day() {
+        let start = create_test_datetime("2025-01-20 00:00:00");
+        let end = create_test_datetime("2025-02-15 00:00:00");
+        let current = create_test_datetime("2025-01-27 11:36:00");
+
+        assert_eq!(
+            format_timestamps(start, end, current),
+            "(started 2025-01-20 00:00 · now 11:36 · ends 2025-02-15 00:00)"
+        );
+    }
+
+    #[test]
+    fn test_render_progress_bar_with_timestamps() {
+        let start = create_test_datetime("2025-01-27 09:00:00");
+        let end = create_test_datetime("2025-01-27 17:00:00");
+        let current = create_test_datetime("2025-01-27 13:00:00");
+
+        let result = render_progress_bar_with_timestamps(50.0, start, end, current);
+        assert_eq!(
+            result,
+            format!(
+                "{} (started 09:00 · now 13:00 · ends 17:00)",
+                render_progress_bar(50.0)
+            )
+        );
+    }
+
+    #[test]
+    fn test_colored_timestamps_normal_matches_plain() {
+        let start = create_test_datetime("2025-01-27 09:00:00");
+        let end = create_test_datetime("2025-01-27 17:00:00");
+        let current = create_test_datetime("2025-01-27 10:00:00");
+
+        assert_eq!(
+            render_colored_progress_bar_with_timestamps(12.5, start, end, current),
+            render_progress_bar_with_timestamps(12.5, start, end, current)
+        );
+    }
+
+    #[test]
+    fn test_overlay_with_timestamps() {
+        let start = create_test_datetime("2025-01-27 09:00:00");
+        let end = create_test_datetime("2025-01-27 17:00:00");
+        let current = create_test_datetime("2025-01-27 10:00:00");
+
+        let result = render_colored_progress_bar_overlay_with_timestamps(12.5, start, end, current);
+        assert!(result.contains("12.5%"));
+        assert!(result.ends_with("(started 09:00 · now 10:00 · ends 17:00)"));
+    }
+
+    #[test]
+    fn test_time_info_style_toggle() {
+        assert_eq!(TimeInfoStyle::default(), TimeInfoStyle::Durations);
+        assert_eq!(TimeInfoStyle::Durations.toggle(), TimeInfoStyle::Timestamps);
+        assert_eq!(TimeInfoStyle::Timestamps.toggle(), TimeInfoStyle::Durations);
+    }
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod overlay_tests {
+    use super::*;
+    #[cfg(feature = "color")]
+    use colored::control;
+
+    fn bar_cells(result: &str) -> &str {
+        let bar_start = result.find('[').unwrap() + 1;
+        let bar_end = result.rfind(']').unwrap();
+        &result[bar_start..bar_end]
+    }
+
+    #[test]
+    fn test_overlay_keeps_fixed_width() {
+        for percentage in [0.0, 2.5, 42.5, 99.9, 100.0, 150.0, 1234.5] {
+            let result = render_progress_bar_overlay(percentage);
+            assert_eq!(
+                bar_cells(&result).chars().count(),
+                BAR_WIDTH,
+                "Overlay bar for {percentage}% should be {BAR_WIDTH} cells: {result}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_overlay_text_is_centered() {
+        let result = render_progress_bar_overlay(42.5);
+        let cells: Vec<char> = bar_cells(&result).chars().collect();
+        let text: String = cells[17..22].iter().collect();
+        assert_eq!(text, "42.5%");
+        assert!(!result.ends_with('%'));
+    }
+
+    #[test]
+    fn test_overlay_exact_format() {
+        assert_eq!(
+            render_progress_bar_overlay(0.0),
+            format!("[{}0.0%{}]", "░".repeat(18), "░".repeat(18))
+        );
+        assert_eq!(
+            render_progress_bar_overlay(100.0),
+            format!("[{}100.0%{}]", "█".repeat(17), "█".repeat(17))
+        );
+    }
+
+    #[test]
+    fn test_overlay_preserves_fill_outside_text() {
+        // 75% fills 30 cells; the text replaces some of them in the middle
+        let result = render_progress_bar_overlay(75.0);
+        let cells = bar_cells(&result);
+        let filled = cells.chars().filter(|&c| c == '█').count();
+        let text_len = "75.0%".len();
+        assert_eq!(filled, 30 - text_len);
+        assert!(cells.ends_with("░░░░░░░░░░"));
+    }
+
+    #[test]
+    fn test_overlay_negative_percentage() {
+        let result = render_progress_bar_overlay(-10.0);
+        assert!(result.contains("-10.0%"));
+        assert!(!result.contains('█'));
+    }
+
+    #[test]
+    #[cfg(feature = "color")]
+    fn test_colored_overlay_uses_contrast_on_fill() {
+        let original_should_colorize = control::SHOULD_COLORIZE.should_colorize();
+        control::set_override(true);
+
+        // Text on filled cells is styled
+        let on_fill = render_colored_progress_bar_overlay(80.0);
+        assert!(on_fill.contains('\x1b'));
+        assert!(on_fill.contains("80.0%"));
+
+        // Text on empty cells is left plain
+        let on_empty = render_colored_progress_bar_overlay(5.0);
+        assert_eq!(on_empty, render_progress_bar_overlay(5.0));
+
+        if original_should_colorize {
+            control::set_override(true);
+        } else {
+            control::unset_override();
+        }
+    }
+
+    #[test]
+    fn test_colored_overlay_with_time() {
+        let start =
+            NaiveDateTime::parse_from_str("2025-01-27 09:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let end =
+            NaiveDateTime::parse_from_str("2025-01-27 17:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let current =
+            NaiveDateTime::parse_from_str("2025-01-27 09:48:00", "%Y-%m-%d %H:%M:%S").unwrap();
+
+        let result = render_colored_progress_bar_overlay_with_time(
+            10.0,
+            start,
+            end,
+            current,
+            DurationStyle::Compact,
+        );
+        assert!(result.contains("10.0%"));
+        assert!(result.ends_with("(48m elapsed, 7h 12m remaining)"));
+    }
+}
+
+#[cfg(test)]
+mod label_tests {
+    use super::*;
+
+    #[test]
+    fn test_render_labeled_line_without_label() {
+        let bar = render_progress_bar(50.0);
+        assert_eq!(render_labeled_line(None, 10, &bar), bar);
+    }
+
+    #[test]
+    fn test_render_labeled_line_single_bar() {
+        let bar = render_progress_bar(25.0);
+        assert_eq!(
+            render_labeled_line(Some("Sprint"), 0, &bar),
+            format!("Sprint {bar}")
+        );
+    }
+
+    #[test]
+    fn test_render_labeled_line_squeezed_column() {
+        let bar = render_progress_bar(25.0);
+        assert_eq!(
+            render_labeled_line(Some("Quarterly planning"), 8, &bar),
+            format!("Quarter… {bar}")
+        );
+    }
+
+    #[test]
+    fn test_render_labeled_line_aligns_wide_labels() {
+        let bar = render_progress_bar(50.0);
+        let labels = ["demo", "レトロ", "🚀"];
+        let width = layout::max_display_width(labels);
+
+        let lines: Vec<String> = labels
+            .iter()
+            .map(|label| render_labeled_line(Some(label), width, &bar))
+            .collect();
+
+        // Every bar starts in the same terminal column
+        for line in &lines {
+            let prefix = &line[..line.find('[').unwrap()];
+            assert_eq!(layout::display_width(prefix), width + 1, "line: {line}");
+        }
+    }
+
+    #[test]
+    fn test_render_labeled_line_shortens_long_labels() {
+        let bar = render_progress_bar(50.0);
+        let long = "締切".repeat(20);
+        let labels = ["demo", long.as_str()];
+        let width = layout::max_display_width(labels);
+
+        let lines: Vec<String> = labels
+            .iter()
+            .map(|label| render_labeled_line(Some(label), width, &bar))
+            .collect();
+
+        // A wide character would not fit next to the ellipsis, so the label
+        // is padded by a column
+        assert!(lines[1].starts_with(&format!("{}締…  [", "締切".repeat(7))));
+        // The column shrinks with the label, so the bars still line up
+        for line in &lines {
+            let prefix = &line[..line.find('[').unwrap()];
+            assert_eq!(
+                layout::display_width(prefix),
+                layout::DEFAULT_MAX_LABEL_WIDTH + 1,
+                "line: {line}"
+            );
+        }
+    }
+}
+
 #[cfg(test)]
 mod progress_calculation_tests {
     use super::*;
@@ -666,6 +2472,27 @@ mod progress_calculation_tests {
         assert!((result - 50.0).abs() < 1.0); // Within 1% tolerance for leap year variations
     }
 
+    #[test]
+    fn test_ranges_beyond_microsecond_limits() {
+        // Far more microseconds than fit in an i64
+        let start = NaiveDateTime::MIN;
+        let end = NaiveDateTime::MAX;
+        assert!((end - start).num_microseconds().is_none());
+
+        assert_eq!(calculate_progress(start, end, start), 0.0);
+        assert_eq!(calculate_progress(start, end, end), 100.0);
+
+        let midpoint = start + (end - start) / 2;
+        let result = calculate_progress(start, end, midpoint);
+        assert!((result - 50.0).abs() < 1e-6, "got {result}");
+
+        // Elapsed overflows while the total does not
+        let start = create_test_datetime("2025-01-01 00:00:00");
+        let end = start + Duration::days(365);
+        assert!(calculate_progress(start, end, NaiveDateTime::MAX) > 100.0);
+        assert_eq!(calculate_progress(start, end, NaiveDateTime::MIN), 0.0);
+    }
+
     #[cfg(test)]
     mod performance_tests {
         use super::*;
@@ -706,6 +2533,260 @@ mod progress_calculation_tests {
 mod render_tests {
     use super::*;
 
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn state_at(current: &str) -> ProgressState {
+        ProgressState::new(
+            create_test_datetime("2025-01-27 09:00:00"),
+            create_test_datetime("2025-01-27 17:00:00"),
+            create_test_datetime(current),
+        )
+    }
+
+    #[test]
+    fn test_render_options_default_matches_plain_bar() {
+        let state = state_at("2025-01-27 11:36:00");
+        let options = RenderOptions {
+            time: None,
+            ..RenderOptions::default()
+        };
+        assert_eq!(render(&state, &options), render_progress_bar(32.5));
+        assert_eq!(
+            render(&state, &RenderOptions::default()),
+            format!(
+                "{} (2h 36m elapsed, 5h 24m remaining)",
+                render_progress_bar(32.5)
+            )
+        );
+    }
+
+    #[test]
+    fn test_render_width() {
+        let state = state_at("2025-01-27 13:00:00");
+        for width in [0, 1, 10, 80] {
+            for style in [BarStyle::Classic, BarStyle::Overlay] {
+                let options = RenderOptions {
+                    width,
+                    style,
+                    time: None,
+                    ..RenderOptions::default()
+                };
+                let line = render(&state, &options);
+                let cells = line[1..line.find(']').unwrap()].chars().count();
+                assert_eq!(cells, width, "{style:?}: {line}");
+            }
+        }
+        let options = RenderOptions {
+            width: 4,
+            time: Some(TimeInfoStyle::Timestamps),
+            ..RenderOptions::default()
+        };
+        assert_eq!(
+            render(&state, &options),
+            "[██░░] 50.0% (started 09:00 · now 13:00 · ends 17:00)"
+        );
+    }
+
+    #[test]
+    fn test_render_step() {
+        let state = state_at("2025-01-27 11:36:00");
+        let options = RenderOptions {
+            step: Some(5.0),
+            time: None,
+            ..RenderOptions::default()
+        };
+        assert_eq!(render(&state, &options), render_progress_bar(30.0));
+    }
+
+    #[test]
+    fn test_quantize_percentage() {
+        assert_eq!(quantize_percentage(0.0, 5.0), 0.0);
+        assert_eq!(quantize_percentage(4.99, 5.0), 0.0);
+        assert_eq!(quantize_percentage(5.0, 5.0), 5.0);
+        assert_eq!(quantize_percentage(33.3, 12.5), 25.0);
+        assert_eq!(quantize_percentage(100.0, 30.0), 100.0);
+        assert_eq!(quantize_percentage(42.0, 0.0), 42.0);
+        assert!(quantize_percentage(f64::NAN, 5.0).is_nan());
+    }
+
+    #[test]
+    fn test_render_before_the_start() {
+        let state = state_at("2025-01-27 08:00:00");
+        let options = RenderOptions {
+            width: 4,
+            style: BarStyle::Overlay,
+            ..RenderOptions::default()
+        };
+        assert_eq!(render(&state, &options), "[░░░░] (starts in 1h 0m)");
+        // Without time information there is nothing else to show
+        let options = RenderOptions {
+            width: 4,
+            time: None,
+            ..RenderOptions::default()
+        };
+        assert_eq!(render(&state, &options), "[░░░░] 0.0%");
+    }
+
+    #[test]
+    fn test_render_fitted_shrinks_then_stacks() {
+        let state = state_at("2025-01-27 13:00:00");
+        let options = RenderOptions::default();
+        let full = render(&state, &options);
+        assert_eq!(render_fitted(&state, &options, usize::MAX), [full.as_str()]);
+        // The bar gives up cells before anything moves
+        let columns = layout::display_width(&full) - 20;
+        let lines = render_fitted(&state, &options, columns);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(layout::display_width(&lines[0]), columns);
+        // Then the time information gets a line of its own
+        let lines = render_fitted(&state, &options, 30);
+        assert_eq!(
+            lines,
+            [
+                format!("[{}{}] 50.0%", "█".repeat(11), "░".repeat(11)),
+                "(4h 0m elapsed, 4h 0m remaining)".to_string(),
+            ]
+        );
+        // Zen has nothing to move, so only its bar shrinks
+        let zen = RenderOptions {
+            style: BarStyle::Zen,
+            ..options
+        };
+        assert_eq!(render_fitted(&state, &zen, 13), ["[██░░] midway"]);
+    }
+
+    #[test]
+    fn test_render_emoji_counts_double_width() {
+        let options = RenderOptions {
+            width: 8,
+            style: BarStyle::Emoji(EmojiCells::default()),
+            time: None,
+            ..RenderOptions::default()
+        };
+        assert_eq!(
+            render(&state_at("2025-01-27 13:00:00"), &options),
+            "🟩🟩⬜⬜ 50.0%"
+        );
+        assert_eq!(
+            render(&state_at("2025-01-27 08:00:00"), &options),
+            "⬜⬜⬜⬜ 0.0%"
+        );
+        // The cells fill the same columns as a plain bar would
+        let options = RenderOptions {
+            width: 20,
+            time: Some(TimeInfoStyle::Durations),
+            ..options
+        };
+        let state = state_at("2025-01-27 13:00:00");
+        let line = render(&state, &options);
+        assert_eq!(layout::display_width(&line), 59);
+        assert_eq!(render_fitted(&state, &options, 59), [line]);
+        // An odd column left over stays empty rather than splitting a cell
+        let lines = render_fitted(&state, &options, 58);
+        assert_eq!(layout::display_width(&lines[0]), 57);
+    }
+
+    #[test]
+    fn test_render_zen_hides_numbers() {
+        let options = RenderOptions {
+            width: 4,
+            style: BarStyle::Zen,
+            ..RenderOptions::default()
+        };
+        for (current, expected) in [
+            ("2025-01-27 08:00:00", "[░░░░] not started"),
+            ("2025-01-27 09:30:00", "[░░░░] early"),
+            ("2025-01-27 13:00:00", "[██░░] midway"),
+            ("2025-01-27 16:00:00", "[████] wrapping up"),
+            ("2025-01-27 17:00:00", "[████] time's up"),
+            ("2025-01-27 18:00:00", "[████] overtime"),
+        ] {
+            assert_eq!(render(&state_at(current), &options), expected);
+        }
+    }
+
+    #[test]
+    fn test_render_thin() {
+        let options = RenderOptions {
+            width: 8,
+            style: BarStyle::Thin,
+            ..RenderOptions::default()
+        };
+        assert_eq!(
+            render(&state_at("2025-01-27 11:00:00"), &options),
+            " ━━──────  25.0% (2h 0m elapsed, 6h 0m remaining)"
+        );
+        assert_eq!(
+            render(&state_at("2025-01-27 08:00:00"), &options),
+            " ──────── (starts in 1h 0m)"
+        );
+        // The percentage lines up with a bracketed bar of the same width
+        let classic = render(
+            &state_at("2025-01-27 11:00:00"),
+            &RenderOptions {
+                width: 8,
+                ..RenderOptions::default()
+            },
+        );
+        let thin = render(&state_at("2025-01-27 11:00:00"), &options);
+        assert_eq!(classic.find('%'), thin.find('%'));
+    }
+
+    #[test]
+    fn test_render_invert() {
+        let state = state_at("2025-01-27 11:00:00");
+        let invert = RenderOptions {
+            width: 8,
+            time: None,
+            invert: true,
+            ..RenderOptions::default()
+        };
+        assert_eq!(render(&state, &invert), "[██░░░░░░] 75.0%");
+        let drain = RenderOptions {
+            drain: true,
+            ..invert
+        };
+        assert_eq!(render(&state, &drain), "[██████░░] 75.0%");
+        assert_eq!(
+            render(
+                &state,
+                &RenderOptions {
+                    style: BarStyle::Overlay,
+                    ..drain
+                }
+            ),
+            "[█75.0%░░]"
+        );
+        // The remainder is counted from the snapped percentage
+        assert_eq!(
+            render(
+                &state_at("2025-01-27 16:45:00"),
+                &RenderOptions {
+                    step: Some(5.0),
+                    ..invert
+                }
+            ),
+            "[████████] 5.0%"
+        );
+        assert_eq!(
+            render(&state_at("2025-01-27 18:00:00"), &drain),
+            "[░░░░░░░░] 0.0%"
+        );
+        // A draining bar starts out full
+        assert_eq!(
+            render(
+                &state_at("2025-01-27 08:00:00"),
+                &RenderOptions {
+                    time: Some(TimeInfoStyle::Durations),
+                    ..drain
+                }
+            ),
+            "[████████] (starts in 1h 0m)"
+        );
+    }
+
     #[test]
     fn test_basic_rendering() {
         // Test 0%
@@ -750,6 +2831,39 @@ mod render_tests {
         }
     }
 
+    #[test]
+    fn test_non_finite_percentages() {
+        let empty = format!("[{}] --%", "░".repeat(BAR_WIDTH));
+        let full = format!("[{}] --%", "█".repeat(BAR_WIDTH));
+
+        assert_eq!(render_progress_bar(f64::NAN), empty);
+        assert_eq!(render_progress_bar(f64::NEG_INFINITY), empty);
+        assert_eq!(render_progress_bar(f64::INFINITY), full);
+
+        for percentage in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            let overlay = render_progress_bar_overlay(percentage);
+            assert_eq!(overlay.chars().count(), BAR_WIDTH + 2, "overlay: {overlay}");
+            assert!(overlay.contains("--%"));
+        }
+    }
+
+    #[test]
+    fn test_extreme_finite_percentages_keep_bar_shape() {
+        for percentage in [f64::MAX, f64::MIN, f64::MIN_POSITIVE, -0.0, 1e300] {
+            let result = render_progress_bar(percentage);
+            let bar: String = result
+                .trim_start_matches('[')
+                .chars()
+                .take_while(|&c| c != ']')
+                .collect();
+            assert_eq!(
+                bar.chars().count(),
+                BAR_WIDTH,
+                "bar for {percentage}: {result}"
+            );
+        }
+    }
+
     #[test]
     fn test_exact_format_requirements() {
         // Test the exact format specified in the issue
@@ -821,7 +2935,8 @@ mod render_tests {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "color"))]
+#[allow(deprecated)]
 mod color_tests {
     use super::*;
     use colored::control;
@@ -1084,3 +3199,38 @@ mod color_tests {
         }
     }
 }
+
+#[cfg(test)]
+mod indeterminate_tests {
+    use super::*;
+
+    fn block_offset(bar: &str) -> usize {
+        bar.chars().skip(1).take_while(|&c| c == '░').count()
+    }
+
+    #[test]
+    fn test_indeterminate_bar_width() {
+        for frame in 0..100 {
+            let bar = render_indeterminate_bar(frame);
+            assert_eq!(bar.chars().count(), BAR_WIDTH + 2);
+            assert_eq!(bar.chars().filter(|&c| c == '█').count(), 8);
+        }
+    }
+
+    #[test]
+    fn test_indeterminate_bar_bounces() {
+        assert_eq!(block_offset(&render_indeterminate_bar(0)), 0);
+        assert_eq!(block_offset(&render_indeterminate_bar(32)), 32);
+        // Reverses at the right edge and returns to the start
+        assert_eq!(block_offset(&render_indeterminate_bar(33)), 31);
+        assert_eq!(block_offset(&render_indeterminate_bar(64)), 0);
+        assert_eq!(render_indeterminate_bar(5), render_indeterminate_bar(69));
+    }
+
+    #[test]
+    fn test_bar_state_indeterminate_has_no_percentage() {
+        let bar = render_bar_state(BarState::Indeterminate(10));
+        assert!(!bar.contains('%'));
+        assert!(render_bar_state(BarState::Determinate(25.0)).ends_with("25.0%"));
+    }
+}
@@ -0,0 +1,183 @@
+//! Progress snapshot shared by the pmon renderers
+//!
+//! A [`ProgressState`] captures one evaluation of the time window: the start,
+//! end, and current times along with the derived percentage. Text, JSON, and
+//! other output formats are all rendered from the same snapshot so they never
+//! disagree about the numbers they show.
+
+use crate::progress_bar::{calculate_day_progress, calculate_progress};
+use crate::timezone::civil_duration;
+use chrono::{Duration, NaiveDateTime};
+
+/// How finely progress through a window is measured
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Granularity {
+    /// Continuously, to the microsecond
+    #[default]
+    Continuous,
+    /// In whole calendar days, for "day 12 of 30" countdowns
+    Day,
+}
+
+/// A single evaluation of a time window at a given moment
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProgressState {
+    /// Start of the window
+    pub start: NaiveDateTime,
+    /// End of the window
+    pub end: NaiveDateTime,
+    /// Moment the snapshot was taken
+    pub current: NaiveDateTime,
+    /// Progress percentage as computed by [`calculate_progress`]
+    pub percentage: f64,
+}
+
+impl ProgressState {
+    /// Evaluate the window `start..end` at `current`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::NaiveDateTime;
+    /// use pmon::progress_state::ProgressState;
+    ///
+    /// let start = NaiveDateTime::parse_from_str("2025-01-27 09:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+    /// let end = NaiveDateTime::parse_from_str("2025-01-27 17:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+    /// let current = NaiveDateTime::parse_from_str("2025-01-27 13:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+    ///
+    /// let state = ProgressState::new(start, end, current);
+    /// assert_eq!(state.percentage, 50.0);
+    /// ```
+    pub fn new(start: NaiveDateTime, end: NaiveDateTime, current: NaiveDateTime) -> Self {
+        Self {
+            start,
+            end,
+            current,
+            percentage: calculate_progress(start, end, current),
+        }
+    }
+
+    /// Evaluate the window `start..end` at `current`, measuring progress with
+    /// the given granularity
+    pub fn with_granularity(
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+        current: NaiveDateTime,
+        granularity: Granularity,
+    ) -> Self {
+        let percentage = match granularity {
+            Granularity::Continuous => calculate_progress(start, end, current),
+            Granularity::Day => calculate_day_progress(start, end, current),
+        };
+        Self {
+            start,
+            end,
+            current,
+            percentage,
+        }
+    }
+
+    /// Time elapsed since the start (negative before the window opens)
+    pub fn elapsed(&self) -> Duration {
+        civil_duration(self.start, self.current)
+    }
+
+    /// Time left until the end (negative once the window has passed)
+    pub fn remaining(&self) -> Duration {
+        civil_duration(self.current, self.end)
+    }
+
+    /// Time spent past the end, or zero while the window is still open
+    pub fn overtime(&self) -> Duration {
+        civil_duration(self.end, self.current).max(Duration::zero())
+    }
+
+    /// Whether the window has been fully consumed (progress >= 100%)
+    pub fn is_complete(&self) -> bool {
+        self.percentage >= 100.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_new_computes_percentage() {
+        let state = ProgressState::new(
+            create_test_datetime("2025-01-27 09:00:00"),
+            create_test_datetime("2025-01-27 17:00:00"),
+            create_test_datetime("2025-01-27 11:00:00"),
+        );
+        assert_eq!(state.percentage, 25.0);
+        assert!(!state.is_complete());
+    }
+
+    #[test]
+    fn test_day_granularity() {
+        let state_at = |current| {
+            ProgressState::with_granularity(
+                create_test_datetime("2025-07-01 09:00:00"),
+                create_test_datetime("2025-07-04 18:00:00"),
+                create_test_datetime(current),
+                Granularity::Day,
+            )
+        };
+        // Four calendar days, the last one partial
+        assert_eq!(state_at("2025-07-01 23:59:59").percentage, 0.0);
+        assert_eq!(state_at("2025-07-02 00:00:00").percentage, 25.0);
+        assert_eq!(state_at("2025-07-04 17:59:59").percentage, 75.0);
+        assert!(!state_at("2025-07-04 17:59:59").is_complete());
+        assert_eq!(state_at("2025-07-04 18:00:00").percentage, 100.0);
+        assert!(state_at("2025-07-05 06:00:00").is_complete());
+        assert_eq!(state_at("2025-06-30 12:00:00").percentage, 0.0);
+    }
+
+    #[test]
+    fn test_day_granularity_within_one_day() {
+        let start = create_test_datetime("2025-07-01 09:00:00");
+        let end = create_test_datetime("2025-07-01 17:00:00");
+        let state = ProgressState::with_granularity(
+            start,
+            end,
+            create_test_datetime("2025-07-01 16:00:00"),
+            Granularity::Day,
+        );
+        assert_eq!(state.percentage, 0.0);
+        let continuous = ProgressState::with_granularity(
+            start,
+            end,
+            create_test_datetime("2025-07-01 16:00:00"),
+            Granularity::Continuous,
+        );
+        assert_eq!(
+            continuous,
+            ProgressState::new(start, end, continuous.current)
+        );
+    }
+
+    #[test]
+    fn test_elapsed_remaining_overtime() {
+        let state = ProgressState::new(
+            create_test_datetime("2025-01-27 09:00:00"),
+            create_test_datetime("2025-01-27 17:00:00"),
+            create_test_datetime("2025-01-27 11:00:00"),
+        );
+        assert_eq!(state.elapsed(), Duration::hours(2));
+        assert_eq!(state.remaining(), Duration::hours(6));
+        assert_eq!(state.overtime(), Duration::zero());
+
+        let late = ProgressState::new(
+            create_test_datetime("2025-01-27 09:00:00"),
+            create_test_datetime("2025-01-27 17:00:00"),
+            create_test_datetime("2025-01-27 18:15:00"),
+        );
+        assert_eq!(late.remaining(), -Duration::minutes(75));
+        assert_eq!(late.overtime(), Duration::minutes(75));
+        assert!(late.is_complete());
+    }
+}
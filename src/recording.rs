@@ -0,0 +1,241 @@
+//! Recording sessions as asciinema casts
+//!
+//! `--record session.cast` keeps a copy of everything pmon draws, with the
+//! time each frame appeared, in the [asciicast v2] format. The file can be
+//! replayed with `asciinema play` or embedded in documentation with the
+//! asciinema player.
+//!
+//! [`CastRecorder`] sits between the renderers and the output: it passes
+//! every write through and turns each finished frame into an output event.
+//! A frame is finished when the renderer flushes or ends a line, so
+//! interactive redraws become one event each and piped output one per
+//! line. Events are written as they happen, so a run ended by Ctrl+C or
+//! `--max-runtime` still leaves a playable file.
+//!
+//! [asciicast v2]: https://docs.asciinema.org/manual/asciicast/v2/
+
+use crate::error::{PbError, PbResult};
+use serde_json::json;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Option name used in error messages
+const OPTION_NAME: &str = "--record";
+
+/// Terminal size written to the header when the real one is unknown
+pub const DEFAULT_SIZE: (u16, u16) = (80, 24);
+
+/// Copies output to an asciicast file, one event per frame
+#[derive(Debug)]
+pub struct CastRecorder<W: Write, C: Write> {
+    inner: W,
+    cast: C,
+    started: Instant,
+    pending: Vec<u8>,
+}
+
+impl<W: Write> CastRecorder<W, File> {
+    /// Record `inner`'s output to a new cast file at `path`
+    ///
+    /// `size` is the terminal's width and height in characters.
+    pub fn create(path: &Path, inner: W, size: (u16, u16)) -> PbResult<Self> {
+        let invalid = |e: io::Error| {
+            PbError::invalid_argument(OPTION_NAME, format!("{}: {e}", path.display()))
+        };
+        let cast = File::create(path).map_err(invalid)?;
+        Self::new(inner, cast, size).map_err(invalid)
+    }
+}
+
+impl<W: Write, C: Write> CastRecorder<W, C> {
+    /// Pass output through to `inner`, recording it to `cast`
+    ///
+    /// Writes the cast's header right away.
+    pub fn new(inner: W, mut cast: C, (width, height): (u16, u16)) -> io::Result<Self> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        let mut env = serde_json::Map::new();
+        if let Ok(term) = std::env::var("TERM") {
+            env.insert("TERM".to_string(), term.into());
+        }
+        let header = json!({
+            "version": 2,
+            "width": width,
+            "height": height,
+            "timestamp": timestamp,
+            "env": env,
+        });
+        writeln!(cast, "{header}")?;
+        Ok(Self {
+            inner,
+            cast,
+            started: Instant::now(),
+            pending: Vec::new(),
+        })
+    }
+
+    /// Write the output gathered since the last event as a new event
+    fn record_frame(&mut self) -> io::Result<()> {
+        // A character split across two writes waits for its remaining bytes
+        let complete = match std::str::from_utf8(&self.pending) {
+            Ok(_) => self.pending.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => self.pending.len(),
+        };
+        if complete == 0 {
+            return Ok(());
+        }
+        let rest = self.pending.split_off(complete);
+        let text = String::from_utf8_lossy(&self.pending);
+        let elapsed = self.started.elapsed().as_micros() as f64 / 1e6;
+        let event = json!([elapsed, "o", to_terminal_newlines(&text)]);
+        writeln!(self.cast, "{event}")?;
+        self.cast.flush()?;
+        self.pending = rest;
+        Ok(())
+    }
+}
+
+impl<W: Write, C: Write> Write for CastRecorder<W, C> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.pending.extend_from_slice(&buf[..written]);
+        if self.pending.ends_with(b"\n") {
+            self.record_frame()?;
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()?;
+        self.record_frame()
+    }
+}
+
+impl<W: Write, C: Write> Drop for CastRecorder<W, C> {
+    fn drop(&mut self) {
+        // Nothing can report a failure here; the events so far are kept
+        let _ = self.record_frame();
+    }
+}
+
+/// Return the carriage at each bare line feed, as a terminal not in raw
+/// mode would, so players start every line at the left edge
+fn to_terminal_newlines(text: &str) -> String {
+    let mut converted = String::with_capacity(text.len());
+    let mut previous = None;
+    for ch in text.chars() {
+        if ch == '\n' && previous != Some('\r') {
+            converted.push('\r');
+        }
+        converted.push(ch);
+        previous = Some(ch);
+    }
+    converted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn lines(cast: &[u8]) -> Vec<Value> {
+        String::from_utf8(cast.to_vec())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    fn recorded_text(events: &[Value]) -> Vec<&str> {
+        events
+            .iter()
+            .map(|event| event[2].as_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_header() {
+        let mut cast = Vec::new();
+        let recorder = CastRecorder::new(Vec::new(), &mut cast, (120, 40)).unwrap();
+        drop(recorder);
+
+        let lines = lines(&cast);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0]["version"], 2);
+        assert_eq!(lines[0]["width"], 120);
+        assert_eq!(lines[0]["height"], 40);
+        assert!(lines[0]["timestamp"].as_u64().unwrap() > 0);
+    }
+
+    #[test]
+    fn test_frames_end_at_flushes_and_line_ends() {
+        let mut out = Vec::new();
+        let mut cast = Vec::new();
+        let mut recorder = CastRecorder::new(&mut out, &mut cast, DEFAULT_SIZE).unwrap();
+        // An interactive redraw, flushed once it is complete
+        write!(recorder, "\r[###   ] 50.0%").unwrap();
+        write!(recorder, "\x1b[K").unwrap();
+        recorder.flush().unwrap();
+        // Piped output, one line per update
+        writeln!(recorder, "[######] 100.0%").unwrap();
+        writeln!(recorder, "Progress completed!").unwrap();
+        write!(recorder, "unfinished").unwrap();
+        drop(recorder);
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\r[###   ] 50.0%\x1b[K[######] 100.0%\nProgress completed!\nunfinished"
+        );
+        let lines = lines(&cast);
+        let events = &lines[1..];
+        assert_eq!(
+            recorded_text(events),
+            [
+                "\r[###   ] 50.0%\x1b[K",
+                "[######] 100.0%\r\n",
+                "Progress completed!\r\n",
+                "unfinished"
+            ]
+        );
+        for event in events {
+            assert_eq!(event[1], "o");
+        }
+        let times: Vec<f64> = events.iter().map(|e| e[0].as_f64().unwrap()).collect();
+        assert!(times.windows(2).all(|pair| pair[0] <= pair[1]), "{times:?}");
+    }
+
+    #[test]
+    fn test_split_characters_wait_for_their_remaining_bytes() {
+        let mut cast = Vec::new();
+        let mut recorder = CastRecorder::new(Vec::new(), &mut cast, DEFAULT_SIZE).unwrap();
+        let bar = "█".as_bytes();
+        recorder.write_all(&bar[..1]).unwrap();
+        recorder.flush().unwrap();
+        recorder.write_all(&bar[1..]).unwrap();
+        recorder.flush().unwrap();
+        drop(recorder);
+
+        assert_eq!(recorded_text(&lines(&cast)[1..]), ["█"]);
+    }
+
+    #[test]
+    fn test_to_terminal_newlines() {
+        assert_eq!(to_terminal_newlines("a\nb\r\nc"), "a\r\nb\r\nc");
+        assert_eq!(to_terminal_newlines("\n\n"), "\r\n\r\n");
+        assert_eq!(to_terminal_newlines("no newline"), "no newline");
+    }
+
+    #[test]
+    fn test_create_reports_unwritable_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing").join("session.cast");
+        assert!(matches!(
+            CastRecorder::create(&path, Vec::new(), DEFAULT_SIZE),
+            Err(PbError::InvalidArgument { .. })
+        ));
+    }
+}
@@ -0,0 +1,334 @@
+//! Progress from other machines over SSH
+//!
+//! With the `remote` feature, `pmon --remote user@host --end 17:00` runs
+//! pmon on each host through `ssh`, asks it for JSON output, and renders the
+//! updates locally, one labeled bar per host. Repeating `--remote` turns a
+//! single terminal into a dashboard for several machines. Progress is
+//! computed on the remote side, so each host's own clock is authoritative.
+
+use crate::cli::Cli;
+use crate::error::PbError;
+use clap::CommandFactory;
+use std::process::{Child, Command, Stdio};
+use std::str::FromStr;
+
+/// Option name used in error messages
+const OPTION_NAME: &str = "--remote";
+
+/// Program started on the remote host
+pub const REMOTE_PROGRAM: &str = "pmon";
+
+/// Ids of the options that only make sense locally and are not forwarded
+///
+/// They choose where this process writes, what it records, or the name it
+/// claims, none of which the remote pmon should do on its host.
+const LOCAL_ONLY_OPTIONS: [&str; 11] = [
+    "remote",
+    "format",
+    "output",
+    "record",
+    "junit",
+    "debug_file",
+    "progress_file",
+    "name",
+    "on_exists",
+    "force",
+    "copy",
+];
+
+/// An SSH destination such as `user@host` or `host`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteHost {
+    destination: String,
+}
+
+impl RemoteHost {
+    /// Destination as passed to `ssh`
+    pub fn destination(&self) -> &str {
+        &self.destination
+    }
+
+    /// Host name without the user, used to label its bar
+    pub fn host(&self) -> &str {
+        self.destination
+            .rsplit_once('@')
+            .map_or(self.destination.as_str(), |(_, host)| host)
+    }
+
+    /// Start pmon on this host with `args`, streaming JSON on stdout
+    pub fn spawn(&self, args: &[String]) -> std::io::Result<Child> {
+        let remote_command = std::iter::once(REMOTE_PROGRAM.to_string())
+            .chain(args.iter().map(|arg| shell_quote(arg)))
+            .collect::<Vec<_>>()
+            .join(" ");
+        Command::new("ssh")
+            .args(["-T", "-o", "BatchMode=yes", "--"])
+            .arg(&self.destination)
+            .arg(remote_command)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+    }
+}
+
+impl FromStr for RemoteHost {
+    type Err = PbError;
+
+    /// Parse `[user@]host`
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let destination = input.trim();
+        let (user, host) = destination.rsplit_once('@').unwrap_or(("", destination));
+        let is_valid = !host.is_empty()
+            && !destination.starts_with('-')
+            && user.is_empty() != destination.contains('@')
+            && destination
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "@.-_:[]".contains(c));
+        if !is_valid {
+            return Err(PbError::invalid_argument(OPTION_NAME, input));
+        }
+        Ok(Self {
+            destination: destination.to_string(),
+        })
+    }
+}
+
+/// Arguments to forward to the remote pmon
+///
+/// `args` are this process's arguments without the program name. Local-only
+/// options are dropped and `--format json` is added so the output can be
+/// parsed. Arguments of a wrapped command after `--` are kept untouched.
+///
+/// # Examples
+///
+/// ```
+/// use pmon::remote::forwarded_args;
+///
+/// let args: Vec<String> = ["--remote", "ci@build1", "--end", "17:00", "--format=text"]
+///     .map(String::from)
+///     .to_vec();
+/// assert_eq!(forwarded_args(&args), ["--format", "json", "--end", "17:00"]);
+/// ```
+pub fn forwarded_args(args: &[String]) -> Vec<String> {
+    let local = LocalOptions::new();
+    let mut forwarded = vec!["--format".to_string(), "json".to_string()];
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--" {
+            forwarded.push(arg.clone());
+            forwarded.extend(iter.cloned());
+            break;
+        }
+        match local.find(arg) {
+            Some(takes_value) => {
+                if takes_value {
+                    iter.next(); // Skip the option's value
+                }
+            }
+            None => forwarded.push(arg.clone()),
+        }
+    }
+    forwarded
+}
+
+/// Spellings of the local-only options, read from the command line parser
+struct LocalOptions {
+    /// Long names and aliases, with whether the option takes a value
+    longs: Vec<(String, bool)>,
+    /// Short names and aliases, with whether the option takes a value
+    shorts: Vec<(char, bool)>,
+}
+
+impl LocalOptions {
+    fn new() -> Self {
+        let command = Cli::command();
+        let mut local = Self {
+            longs: Vec::new(),
+            shorts: Vec::new(),
+        };
+        let arguments = std::iter::once(&command)
+            .chain(command.get_subcommands())
+            .flat_map(|command| command.get_arguments())
+            .filter(|arg| LOCAL_ONLY_OPTIONS.contains(&arg.get_id().as_str()));
+        for arg in arguments {
+            let takes_value = arg.get_action().takes_values();
+            let longs = arg
+                .get_long()
+                .into_iter()
+                .chain(arg.get_all_aliases().unwrap_or_default());
+            local
+                .longs
+                .extend(longs.map(|long| (long.to_string(), takes_value)));
+            let shorts = arg
+                .get_short()
+                .into_iter()
+                .chain(arg.get_all_short_aliases().unwrap_or_default());
+            local
+                .shorts
+                .extend(shorts.map(|short| (short, takes_value)));
+        }
+        local
+    }
+
+    /// Whether `arg` is a local-only option, and if so whether its value
+    /// follows as the next argument
+    fn find(&self, arg: &str) -> Option<bool> {
+        if let Some(long) = arg.strip_prefix("--") {
+            let (name, value) = match long.split_once('=') {
+                Some((name, _)) => (name, true),
+                None => (long, false),
+            };
+            let (_, takes_value) = self.longs.iter().find(|(long, _)| long == name)?;
+            return Some(*takes_value && !value);
+        }
+        let mut chars = arg.strip_prefix('-')?.chars();
+        let short = chars.next()?;
+        let (_, takes_value) = self.shorts.iter().find(|(s, _)| *s == short)?;
+        // `-oFILE` carries its value; other flags may not be bundled with it
+        match chars.as_str() {
+            "" => Some(*takes_value),
+            _ if *takes_value => Some(false),
+            _ => None,
+        }
+    }
+}
+
+/// Quote `arg` for the POSIX shell that runs the remote command
+///
+/// # Examples
+///
+/// ```
+/// use pmon::remote::shell_quote;
+///
+/// assert_eq!(shell_quote("17:00"), "17:00");
+/// assert_eq!(shell_quote("Work day"), "'Work day'");
+/// assert_eq!(shell_quote("it's"), r"'it'\''s'");
+/// ```
+pub fn shell_quote(arg: &str) -> String {
+    let is_plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "+-_./:=@%,".contains(c));
+    if is_plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_remote_host() {
+        let remote: RemoteHost = "ci@build1.example.com".parse().unwrap();
+        assert_eq!(remote.destination(), "ci@build1.example.com");
+        assert_eq!(remote.host(), "build1.example.com");
+
+        let remote: RemoteHost = "kiosk".parse().unwrap();
+        assert_eq!(remote.host(), "kiosk");
+
+        for input in ["", "@host", "user@", "-oProxyCommand=x", "host;rm", "a b"] {
+            assert!(
+                matches!(
+                    input.parse::<RemoteHost>(),
+                    Err(PbError::InvalidArgument { .. })
+                ),
+                "Expected '{input}' to be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_forwarded_args_drop_local_options() {
+        let args = strings(&[
+            "--remote",
+            "a",
+            "--remote=b",
+            "--end",
+            "+1h",
+            "--output",
+            "stderr",
+            "--label",
+            "Deploy",
+        ]);
+        assert_eq!(
+            forwarded_args(&args),
+            strings(&["--format", "json", "--end", "+1h", "--label", "Deploy"])
+        );
+    }
+
+    #[test]
+    fn test_forwarded_args_drop_every_local_option() {
+        let cases: [&[&str]; 17] = [
+            &["--format", "json"],
+            &["--format=text"],
+            &["--output", "stderr"],
+            &["--record", "run.cast"],
+            &["--record=run.cast"],
+            &["--junit", "report.xml"],
+            &["--debug-file", "debug.log"],
+            &["--progress-file", "work.txt"],
+            &["--name", "standup"],
+            &["--name=standup"],
+            &["--name", "standup", "--force"],
+            &["--name", "standup", "--on-exists", "merge"],
+            &["--copy"],
+            &["-o", "bar.svg"],
+            &["-obar.svg"],
+            &["--remote", "a"],
+            &["--remote=b"],
+        ];
+        for local in cases {
+            let mut args = strings(&["--end", "+1h"]);
+            args.extend(strings(local));
+            args.extend(strings(&["--label", "Deploy"]));
+            assert_eq!(
+                forwarded_args(&args),
+                strings(&["--format", "json", "--end", "+1h", "--label", "Deploy"]),
+                "{local:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_forwarded_args_keep_other_options() {
+        let args = strings(&["-s", "09:00", "-e", "17:00", "-l", "Work", "--verbose"]);
+        assert_eq!(
+            forwarded_args(&args),
+            strings(&[
+                "--format",
+                "json",
+                "-s",
+                "09:00",
+                "-e",
+                "17:00",
+                "-l",
+                "Work",
+                "--verbose"
+            ])
+        );
+    }
+
+    #[test]
+    fn test_forwarded_args_keep_wrapped_command() {
+        let args = strings(&["--end", "+5m", "--", "grep", "--format", "x"]);
+        assert_eq!(
+            forwarded_args(&args),
+            strings(&["--format", "json", "--end", "+5m", "--", "grep", "--format", "x"])
+        );
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote(""), "''");
+        assert_eq!(shell_quote("$(reboot)"), "'$(reboot)'");
+        assert_eq!(shell_quote("2025-01-27 09:00"), "'2025-01-27 09:00'");
+    }
+}
@@ -0,0 +1,488 @@
+//! Summaries of the run history
+//!
+//! `pmon report --since monday` totals the runs that finished since then by
+//! tag, or by label with `--by label`: how many ran, the time tracked, the
+//! overtime incurred, and the share that ran to completion rather than
+//! being stopped early. A run with several tags counts towards each of
+//! them, so the total line counts every run once rather than adding up the
+//! rows. With `--format json` the same report is printed as one JSON
+//! document.
+
+use crate::error::PbError;
+use crate::layout::{fit_label, max_display_width, pad_end};
+use crate::progress_bar::DurationFormat;
+use crate::store::{RunOutcome, RunRecord};
+use crate::timezone::civil_duration;
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// Option name used in error messages
+const OPTION_NAME: &str = "--since";
+
+/// Group of runs without tags when grouping by tag
+pub const UNTAGGED: &str = "(untagged)";
+
+/// Group of runs with neither label nor name when grouping by label
+pub const UNLABELED: &str = "(unlabeled)";
+
+/// Where a report starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Since {
+    /// `today`, from midnight
+    Today,
+    /// `yesterday`, from the previous midnight
+    Yesterday,
+    /// `monday` or `mon`, from the latest such day, today included
+    Weekday(Weekday),
+    /// `2025-01-27`, from that date
+    Date(NaiveDate),
+    /// `7d`, that long ago
+    Ago(Duration),
+}
+
+impl FromStr for Since {
+    type Err = PbError;
+
+    /// Parse `today`, `yesterday`, a weekday, a date, or a duration
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::{Duration, Weekday};
+    /// use pmon::report::Since;
+    ///
+    /// assert_eq!("Monday".parse::<Since>().unwrap(), Since::Weekday(Weekday::Mon));
+    /// assert_eq!("2w".parse::<Since>().unwrap(), Since::Ago(Duration::weeks(2)));
+    /// assert!("last week".parse::<Since>().is_err());
+    /// ```
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let text = input.trim().to_lowercase();
+        match text.as_str() {
+            "today" => return Ok(Self::Today),
+            "yesterday" => return Ok(Self::Yesterday),
+            _ => {}
+        }
+        if let Ok(weekday) = text.parse() {
+            return Ok(Self::Weekday(weekday));
+        }
+        if let Ok(date) = NaiveDate::parse_from_str(&text, "%Y-%m-%d") {
+            return Ok(Self::Date(date));
+        }
+        crate::deadline::parse_lead_time(&text)
+            .map(Self::Ago)
+            .map_err(|_| PbError::invalid_argument(OPTION_NAME, input))
+    }
+}
+
+impl Since {
+    /// The moment the report starts, as seen at `now`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::NaiveDateTime;
+    /// use pmon::report::Since;
+    ///
+    /// let at = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+    /// // 2025-01-29 is a Wednesday
+    /// let now = at("2025-01-29 15:00:00");
+    /// let since: Since = "monday".parse().unwrap();
+    /// assert_eq!(since.resolve(now), at("2025-01-27 00:00:00"));
+    /// let since: Since = "wed".parse().unwrap();
+    /// assert_eq!(since.resolve(now), at("2025-01-29 00:00:00"));
+    /// ```
+    pub fn resolve(self, now: NaiveDateTime) -> NaiveDateTime {
+        let today = now.date();
+        let day = match self {
+            Self::Today => today,
+            Self::Yesterday => today - Duration::days(1),
+            Self::Weekday(weekday) => {
+                let back = (7 + today.weekday().num_days_from_monday()
+                    - weekday.num_days_from_monday())
+                    % 7;
+                today - Duration::days(i64::from(back))
+            }
+            Self::Date(date) => date,
+            Self::Ago(duration) => return now - duration,
+        };
+        day.and_time(NaiveTime::MIN)
+    }
+}
+
+/// What the rows of a report total
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ReportGroup {
+    /// One row per tag
+    #[default]
+    Tag,
+    /// One row per label, or name for runs without a label
+    Label,
+}
+
+impl ReportGroup {
+    /// Heading of the group column
+    fn heading(self) -> &'static str {
+        match self {
+            Self::Tag => "TAG",
+            Self::Label => "LABEL",
+        }
+    }
+
+    /// Groups `run` counts towards
+    fn keys(self, run: &RunRecord) -> Vec<String> {
+        match self {
+            Self::Tag if run.tags.is_empty() => vec![UNTAGGED.to_string()],
+            Self::Tag => run.tags.clone(),
+            Self::Label => vec![run
+                .label
+                .as_deref()
+                .or(run.name.as_deref())
+                .unwrap_or(UNLABELED)
+                .to_string()],
+        }
+    }
+}
+
+/// Totals of the runs in one group
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportRow {
+    /// Tag or label the row totals
+    pub group: String,
+    /// Runs counted
+    pub runs: usize,
+    /// Runs whose window elapsed
+    pub completed: usize,
+    /// Time from the start of each run to when it ended
+    pub tracked: Duration,
+    /// Time runs went on past the end of their windows
+    pub overtime: Duration,
+}
+
+impl ReportRow {
+    fn new(group: &str) -> Self {
+        Self {
+            group: group.to_string(),
+            runs: 0,
+            completed: 0,
+            tracked: Duration::zero(),
+            overtime: Duration::zero(),
+        }
+    }
+
+    fn add(&mut self, run: &RunRecord) {
+        self.runs += 1;
+        if run.outcome == RunOutcome::Completed {
+            self.completed += 1;
+        }
+        self.tracked += civil_duration(run.start, run.finished_at).max(Duration::zero());
+        self.overtime += civil_duration(run.end, run.finished_at).max(Duration::zero());
+    }
+
+    /// Percentage of the runs that ran to completion
+    pub fn completion_rate(&self) -> f64 {
+        if self.runs == 0 {
+            return 0.0;
+        }
+        self.completed as f64 * 100.0 / self.runs as f64
+    }
+}
+
+/// Totals of the runs that finished since a moment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    /// Start of the report
+    pub since: NaiveDateTime,
+    /// What the rows total
+    pub by: ReportGroup,
+    /// One row per group, the most time tracked first
+    pub rows: Vec<ReportRow>,
+    /// Every run counted once
+    pub total: ReportRow,
+}
+
+impl Report {
+    /// Total the runs of `history` that finished at or after `since`
+    pub fn build(history: &[RunRecord], since: NaiveDateTime, by: ReportGroup) -> Self {
+        let mut groups: BTreeMap<String, ReportRow> = BTreeMap::new();
+        let mut total = ReportRow::new("Total");
+        for run in history.iter().filter(|run| run.finished_at >= since) {
+            total.add(run);
+            for key in by.keys(run) {
+                groups
+                    .entry(key)
+                    .or_insert_with_key(|key| ReportRow::new(key))
+                    .add(run);
+            }
+        }
+        let mut rows: Vec<ReportRow> = groups.into_values().collect();
+        // Ties keep the alphabetical order of the map
+        rows.sort_by_key(|row| std::cmp::Reverse(row.tracked));
+        Self {
+            since,
+            by,
+            rows,
+            total,
+        }
+    }
+
+    /// Aligned table with a heading, one line per group, and the total
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::NaiveDateTime;
+    /// use pmon::progress_bar::DurationFormat;
+    /// use pmon::report::{Report, ReportGroup};
+    /// use pmon::store::{RunOutcome, RunRecord};
+    ///
+    /// let at = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+    /// let run = RunRecord {
+    ///     name: Some("focus".to_string()),
+    ///     label: None,
+    ///     start: at("2025-01-27 09:00:00"),
+    ///     end: at("2025-01-27 10:00:00"),
+    ///     finished_at: at("2025-01-27 10:15:00"),
+    ///     outcome: RunOutcome::Completed,
+    ///     tags: vec!["deep-work".to_string()],
+    ///     notes: Vec::new(),
+    /// };
+    /// let report = Report::build(&[run], at("2025-01-27 00:00:00"), ReportGroup::Tag);
+    /// assert_eq!(
+    ///     report.render_table(DurationFormat::default()),
+    ///     [
+    ///         "Since Mon 2025-01-27 00:00",
+    ///         "TAG        RUNS  TRACKED  OVERTIME  COMPLETED",
+    ///         "deep-work     1   1h 15m       15m       100%",
+    ///         "Total         1   1h 15m       15m       100%",
+    ///     ]
+    /// );
+    /// ```
+    pub fn render_table(&self, format: DurationFormat) -> Vec<String> {
+        let mut lines = vec![format!("Since {}", self.since.format("%a %Y-%m-%d %H:%M"))];
+        if self.rows.is_empty() {
+            lines.push("No runs finished since then.".to_string());
+            return lines;
+        }
+        let groups: Vec<_> = self.rows.iter().map(|row| fit_label(&row.group)).collect();
+        let width = max_display_width(
+            groups
+                .iter()
+                .map(|group| group.as_ref())
+                .chain([self.by.heading(), self.total.group.as_str()]),
+        );
+        lines.push(format!(
+            "{}  RUNS  TRACKED  OVERTIME  COMPLETED",
+            pad_end(self.by.heading(), width)
+        ));
+        for (group, row) in groups
+            .iter()
+            .zip(&self.rows)
+            .chain([(&fit_label(&self.total.group), &self.total)])
+        {
+            lines.push(format!(
+                "{}  {:>4}  {:>7}  {:>8}  {:>9}",
+                pad_end(group, width),
+                row.runs,
+                format.elapsed(row.tracked),
+                format.elapsed(row.overtime),
+                format!("{:.0}%", row.completion_rate()),
+            ));
+        }
+        lines
+    }
+
+    /// The report as a single-line JSON document
+    pub fn to_json(&self) -> String {
+        let report = JsonReport {
+            since: self.since,
+            by: self.by.heading().to_lowercase(),
+            groups: self.rows.iter().map(JsonRow::from).collect(),
+            total: JsonRow::from(&self.total),
+        };
+
+        // Serializing plain data with string keys cannot fail
+        serde_json::to_string(&report).unwrap_or_default()
+    }
+}
+
+/// JSON representation of a report
+#[derive(Debug, Serialize)]
+struct JsonReport<'a> {
+    since: NaiveDateTime,
+    by: String,
+    groups: Vec<JsonRow<'a>>,
+    total: JsonRow<'a>,
+}
+
+/// JSON representation of a row; durations are whole seconds
+#[derive(Debug, Serialize)]
+struct JsonRow<'a> {
+    group: &'a str,
+    runs: usize,
+    completed: usize,
+    tracked_seconds: i64,
+    overtime_seconds: i64,
+    completion_rate: f64,
+}
+
+impl<'a> From<&'a ReportRow> for JsonRow<'a> {
+    fn from(row: &'a ReportRow) -> Self {
+        Self {
+            group: &row.group,
+            runs: row.runs,
+            completed: row.completed,
+            tracked_seconds: row.tracked.num_seconds(),
+            overtime_seconds: row.overtime.num_seconds(),
+            completion_rate: row.completion_rate(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn run(
+        label: Option<&str>,
+        tags: &[&str],
+        start: &str,
+        end: &str,
+        finished_at: &str,
+        outcome: RunOutcome,
+    ) -> RunRecord {
+        RunRecord {
+            name: None,
+            label: label.map(str::to_string),
+            start: create_test_datetime(start),
+            end: create_test_datetime(end),
+            finished_at: create_test_datetime(finished_at),
+            outcome,
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            notes: Vec::new(),
+        }
+    }
+
+    fn history() -> Vec<RunRecord> {
+        vec![
+            // Last week, left out
+            run(
+                Some("Focus"),
+                &["deep-work"],
+                "2025-01-24 09:00:00",
+                "2025-01-24 11:00:00",
+                "2025-01-24 11:00:00",
+                RunOutcome::Completed,
+            ),
+            run(
+                Some("Focus"),
+                &["deep-work", "team:payments"],
+                "2025-01-27 09:00:00",
+                "2025-01-27 11:00:00",
+                "2025-01-27 11:30:00",
+                RunOutcome::Completed,
+            ),
+            run(
+                Some("Review"),
+                &["team:payments"],
+                "2025-01-28 14:00:00",
+                "2025-01-28 15:00:00",
+                "2025-01-28 14:20:00",
+                RunOutcome::Interrupted,
+            ),
+            run(
+                None,
+                &[],
+                "2025-01-28 16:00:00",
+                "2025-01-28 16:10:00",
+                "2025-01-28 16:10:00",
+                RunOutcome::Completed,
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_parse_since() {
+        assert_eq!("today".parse::<Since>().unwrap(), Since::Today);
+        assert_eq!(
+            "2025-01-27".parse::<Since>().unwrap(),
+            Since::Date(NaiveDate::from_ymd_opt(2025, 1, 27).unwrap())
+        );
+        let now = create_test_datetime("2025-01-27 15:00:00");
+        assert_eq!(
+            "yesterday".parse::<Since>().unwrap().resolve(now),
+            create_test_datetime("2025-01-26 00:00:00")
+        );
+        assert_eq!(
+            "tue".parse::<Since>().unwrap().resolve(now),
+            create_test_datetime("2025-01-21 00:00:00")
+        );
+        assert_eq!(
+            "36h".parse::<Since>().unwrap().resolve(now),
+            create_test_datetime("2025-01-26 03:00:00")
+        );
+        for input in ["", "next monday", "-7d", "2025-02-30"] {
+            assert!(
+                input.parse::<Since>().is_err(),
+                "Expected {input:?} to be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_report_by_tag_counts_runs_once_in_total() {
+        let report = Report::build(
+            &history(),
+            create_test_datetime("2025-01-27 00:00:00"),
+            ReportGroup::Tag,
+        );
+        let groups: Vec<_> = report.rows.iter().map(|row| row.group.as_str()).collect();
+        assert_eq!(groups, ["team:payments", "deep-work", UNTAGGED]);
+        let payments = &report.rows[0];
+        assert_eq!(payments.runs, 2);
+        assert_eq!(payments.tracked, Duration::minutes(170));
+        assert_eq!(payments.overtime, Duration::minutes(30));
+        assert_eq!(payments.completion_rate(), 50.0);
+        assert_eq!(report.total.runs, 3);
+        assert_eq!(report.total.tracked, Duration::minutes(180));
+        assert_eq!(report.total.completed, 2);
+    }
+
+    #[test]
+    fn test_report_by_label() {
+        let report = Report::build(
+            &history(),
+            create_test_datetime("2025-01-20 00:00:00"),
+            ReportGroup::Label,
+        );
+        let groups: Vec<_> = report
+            .rows
+            .iter()
+            .map(|row| (row.group.as_str(), row.runs))
+            .collect();
+        assert_eq!(groups, [("Focus", 2), ("Review", 1), (UNLABELED, 1)]);
+        let json = report.to_json();
+        assert!(json.starts_with(r#"{"since":"2025-01-20T00:00:00","by":"label","#));
+        assert!(json.contains(
+            r#"{"group":"Focus","runs":2,"completed":2,"tracked_seconds":16200,"overtime_seconds":1800,"completion_rate":100.0}"#
+        ));
+    }
+
+    #[test]
+    fn test_empty_report() {
+        let report = Report::build(
+            &history(),
+            create_test_datetime("2025-02-01 00:00:00"),
+            ReportGroup::Tag,
+        );
+        assert_eq!(
+            report.render_table(DurationFormat::default()),
+            ["Since Sat 2025-02-01 00:00", "No runs finished since then."]
+        );
+    }
+}
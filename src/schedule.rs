@@ -0,0 +1,164 @@
+//! Back-to-back sessions followed from a schedule
+//!
+//! `pmon --schedule talks.csv` reads a plan in the format of
+//! `pmon import-plan` and shows the session running now. When it ends, the
+//! bar switches to the next session by itself, without the snooze prompt,
+//! and a line beneath the bar previews the next session's title and start
+//! time. Sessions that have already ended are skipped, so a schedule can be
+//! opened halfway through the day; a gap between two sessions shows the
+//! countdown to the next one.
+
+use crate::chain::Segment;
+use crate::definition::TimerDefinition;
+use crate::error::{PbError, PbResult};
+use crate::time_parser::{parse_time, parse_time_with_base, validate_times};
+use chrono::NaiveDateTime;
+
+/// Sessions of `plan` that have not ended by `now`, in order
+///
+/// Every session needs a start, and sessions may follow each other
+/// directly but not overlap. `path` names the file in error messages.
+pub fn resolve_schedule(
+    plan: &[TimerDefinition],
+    now: NaiveDateTime,
+    path: &str,
+) -> PbResult<Vec<Segment>> {
+    let invalid = |name: &str, reason: String| {
+        PbError::invalid_config(path, format!("session '{name}': {reason}"))
+    };
+    let mut sessions = Vec::new();
+    for definition in plan {
+        let name = definition.name.as_str();
+        let Some(start) = definition.start.as_deref() else {
+            return Err(invalid(name, "every session needs a start".to_string()));
+        };
+        let start = parse_time(start).map_err(|e| invalid(name, e.to_string()))?;
+        let end = parse_time_with_base(&definition.end, Some(start))
+            .map_err(|e| invalid(name, e.to_string()))?;
+        validate_times(start, end).map_err(|e| invalid(name, e.to_string()))?;
+        sessions.push((
+            name,
+            Segment {
+                label: definition.label.clone(),
+                start,
+                end,
+            },
+        ));
+    }
+    sessions.sort_by_key(|(_, session)| session.start);
+    for pair in sessions.windows(2) {
+        let ((before, earlier), (after, later)) = (&pair[0], &pair[1]);
+        if later.start < earlier.end {
+            return Err(invalid(after, format!("overlaps with '{before}'")));
+        }
+    }
+    let upcoming: Vec<Segment> = sessions
+        .into_iter()
+        .map(|(_, session)| session)
+        .filter(|session| session.end > now)
+        .collect();
+    if upcoming.is_empty() {
+        return Err(PbError::invalid_config(
+            path,
+            "every session of the schedule has ended",
+        ));
+    }
+    Ok(upcoming)
+}
+
+/// Preview of the session after the current one, as seen at `now`
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDateTime;
+/// use pmon::chain::Segment;
+/// use pmon::schedule::format_next_session;
+///
+/// let at = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+/// let next = Segment {
+///     label: Some("Rust in production".to_string()),
+///     start: at("2025-03-10 10:30:00"),
+///     end: at("2025-03-10 11:15:00"),
+/// };
+/// let now = at("2025-03-10 09:40:00");
+/// assert_eq!(format_next_session(&next, now), "Next: Rust in production at 10:30");
+/// let evening = at("2025-03-09 18:00:00");
+/// assert_eq!(format_next_session(&next, evening), "Next: Rust in production at Mon 10:30");
+/// ```
+pub fn format_next_session(next: &Segment, now: NaiveDateTime) -> String {
+    let title = next.label.as_deref().unwrap_or("session");
+    let start = if next.start.date() == now.date() {
+        next.start.format("%H:%M")
+    } else {
+        next.start.format("%a %H:%M")
+    };
+    format!("Next: {title} at {start}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn session(name: &str, start: Option<&str>, end: &str) -> TimerDefinition {
+        TimerDefinition {
+            name: name.to_string(),
+            label: Some(name.to_string()),
+            start: start.map(str::to_string),
+            end: end.to_string(),
+            ..TimerDefinition::default()
+        }
+    }
+
+    #[test]
+    fn test_schedule_starts_at_the_current_session() {
+        let plan = [
+            session("lunch", Some("2025-03-10 12:00:00"), "+1h"),
+            session(
+                "keynote",
+                Some("2025-03-10 09:00:00"),
+                "2025-03-10 10:00:00",
+            ),
+            session("talk", Some("2025-03-10 10:00:00"), "+45m"),
+        ];
+        let sessions = resolve_schedule(
+            &plan,
+            create_test_datetime("2025-03-10 10:20:00"),
+            "talks.csv",
+        )
+        .unwrap();
+        let labels: Vec<_> = sessions
+            .iter()
+            .map(|session| session.label.as_deref().unwrap())
+            .collect();
+        assert_eq!(labels, ["talk", "lunch"]);
+        assert_eq!(sessions[0].end, create_test_datetime("2025-03-10 10:45:00"));
+        assert_eq!(sessions[1].end, create_test_datetime("2025-03-10 13:00:00"));
+    }
+
+    #[test]
+    fn test_schedule_rejects_overlaps_and_missing_starts() {
+        let now = create_test_datetime("2025-03-10 08:00:00");
+        let error = |plan: &[TimerDefinition]| {
+            resolve_schedule(plan, now, "talks.csv")
+                .unwrap_err()
+                .to_string()
+        };
+        let message = error(&[
+            session("keynote", Some("2025-03-10 09:00:00"), "+1h"),
+            session("talk", Some("2025-03-10 09:30:00"), "+1h"),
+        ]);
+        assert!(
+            message.contains("'talk': overlaps with 'keynote'"),
+            "{message}"
+        );
+        let message = error(&[session("keynote", None, "+1h")]);
+        assert!(message.contains("needs a start"), "{message}");
+        let message = error(&[session("keynote", Some("2025-03-09 09:00:00"), "+1h")]);
+        assert!(message.contains("has ended"), "{message}");
+    }
+}
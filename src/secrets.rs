@@ -0,0 +1,221 @@
+//! Secrets kept out of plain-text files
+//!
+//! A secret is read from an environment variable when it is set and,
+//! when pmon is built with the `keyring` feature, from the OS keyring
+//! (Keychain on macOS, Credential Manager on Windows, the kernel keyring on
+//! Linux) under the service `pmon` otherwise.
+//!
+//! The configuration file never holds secrets itself. Settings such as a
+//! webhook token name where the secret lives instead, as a [`SecretRef`]:
+//!
+//! ```toml
+//! [[hooks]]
+//! on = "complete"
+//! post = "http://localhost:8080/pmon"
+//! token = "keyring:relay"      # or "env:RELAY_TOKEN"
+//! ```
+//!
+//! pmon only speaks plain HTTP, so a token would cross the network in the
+//! clear. Hooks with a `token` must therefore post to this machine
+//! (`localhost` or a loopback address), such as a relay that forwards the
+//! event over TLS; other URLs are rejected when the configuration is read.
+//!
+//! `pmon secret set relay` saves a secret read from standard input in the
+//! keyring, so it does not end up in the shell history either.
+
+use serde::Deserialize;
+use std::fmt;
+use std::str::FromStr;
+
+/// Keyring service under which pmon's secrets are stored
+pub const KEYRING_SERVICE: &str = "pmon";
+
+/// Read the secret `account` from the OS keyring
+///
+/// Returns `Ok(None)` when the keyring holds no such secret.
+#[cfg(feature = "keyring")]
+pub fn keyring_secret(account: &str) -> Result<Option<String>, String> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, account).map_err(|e| e.to_string())?;
+    match entry.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("keyring entry {KEYRING_SERVICE}/{account}: {e}")),
+    }
+}
+
+/// This build has no keyring to read from
+#[cfg(not(feature = "keyring"))]
+pub fn keyring_secret(_account: &str) -> Result<Option<String>, String> {
+    Ok(None)
+}
+
+/// Save `secret` in the OS keyring as `account`, replacing any saved one
+#[cfg(feature = "keyring")]
+pub fn set_keyring_secret(account: &str, secret: &str) -> Result<(), String> {
+    keyring::Entry::new(KEYRING_SERVICE, account)
+        .and_then(|entry| entry.set_password(secret))
+        .map_err(|e| format!("keyring entry {KEYRING_SERVICE}/{account}: {e}"))
+}
+
+/// Remove the secret `account` from the OS keyring, returning whether it
+/// was there
+#[cfg(feature = "keyring")]
+pub fn delete_keyring_secret(account: &str) -> Result<bool, String> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, account).map_err(|e| e.to_string())?;
+    match entry.delete_credential() {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(format!("keyring entry {KEYRING_SERVICE}/{account}: {e}")),
+    }
+}
+
+/// This build cannot save to the keyring
+#[cfg(not(feature = "keyring"))]
+pub fn set_keyring_secret(_account: &str, _secret: &str) -> Result<(), String> {
+    Err(NO_KEYRING.to_string())
+}
+
+/// This build cannot remove from the keyring
+#[cfg(not(feature = "keyring"))]
+pub fn delete_keyring_secret(_account: &str) -> Result<bool, String> {
+    Err(NO_KEYRING.to_string())
+}
+
+/// Reason given when the keyring is needed but left out of the build
+#[cfg(not(feature = "keyring"))]
+const NO_KEYRING: &str = "this build of pmon does not include the `keyring` feature";
+
+/// What `pmon secret` does with a keyring entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SecretAction {
+    /// Save a secret read from standard input
+    Set,
+    /// Remove the secret
+    Delete,
+}
+
+/// Where a secret named in the configuration file is kept
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum SecretRef {
+    /// `env:VARIABLE`, an environment variable
+    Env(String),
+    /// `keyring:ACCOUNT`, an entry of the OS keyring
+    Keyring(String),
+}
+
+impl SecretRef {
+    /// Read the secret
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pmon::secrets::SecretRef;
+    ///
+    /// std::env::set_var("PMON_DOC_TOKEN", "t0ken");
+    /// let token: SecretRef = "env:PMON_DOC_TOKEN".parse().unwrap();
+    /// assert_eq!(token.resolve().unwrap(), "t0ken");
+    /// assert!("t0ken".parse::<SecretRef>().is_err());
+    /// ```
+    pub fn resolve(&self) -> Result<String, String> {
+        #[cfg(not(feature = "keyring"))]
+        if let SecretRef::Keyring(_) = self {
+            return Err(NO_KEYRING.to_string());
+        }
+        let secret = match self {
+            SecretRef::Env(variable) => std::env::var(variable)
+                .ok()
+                .filter(|secret| !secret.trim().is_empty()),
+            SecretRef::Keyring(account) => keyring_secret(account)?,
+        };
+        secret
+            .map(|secret| secret.trim().to_string())
+            .ok_or_else(|| format!("secret {self} is not set"))
+    }
+}
+
+impl FromStr for SecretRef {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let reference = match input.split_once(':') {
+            Some(("env", variable)) if !variable.is_empty() => SecretRef::Env(variable.to_string()),
+            Some(("keyring", account)) if !account.is_empty() => {
+                SecretRef::Keyring(account.to_string())
+            }
+            _ => return Err(
+                "secrets are given as env:VARIABLE or keyring:ACCOUNT, never written into the file"
+                    .to_string(),
+            ),
+        };
+        Ok(reference)
+    }
+}
+
+impl TryFrom<String> for SecretRef {
+    type Error = String;
+
+    fn try_from(input: String) -> Result<Self, Self::Error> {
+        input.parse()
+    }
+}
+
+impl fmt::Display for SecretRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SecretRef::Env(variable) => write!(f, "env:{variable}"),
+            SecretRef::Keyring(account) => write!(f, "keyring:{account}"),
+        }
+    }
+}
+
+/// Read a secret from `variable`, falling back to the keyring `account`
+///
+/// Empty values count as unset.
+pub fn lookup_secret(variable: &str, account: &str) -> Result<Option<String>, String> {
+    match std::env::var(variable) {
+        Ok(secret) if !secret.trim().is_empty() => Ok(Some(secret.trim().to_string())),
+        _ => keyring_secret(account),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_secret_prefers_the_environment() {
+        std::env::set_var("PMON_TEST_SECRET", "  s3cret \n");
+        assert_eq!(
+            lookup_secret("PMON_TEST_SECRET", "pmon-test-missing").unwrap(),
+            Some("s3cret".to_string())
+        );
+        std::env::remove_var("PMON_TEST_SECRET");
+    }
+
+    #[test]
+    fn test_parse_secret_ref() {
+        assert_eq!(
+            "keyring:relay".parse(),
+            Ok(SecretRef::Keyring("relay".to_string()))
+        );
+        assert_eq!(
+            "env:RELAY_TOKEN".parse(),
+            Ok(SecretRef::Env("RELAY_TOKEN".to_string()))
+        );
+        for input in ["", "hunter2", "env:", "keyring:", "file:/etc/token"] {
+            assert!(
+                input.parse::<SecretRef>().is_err(),
+                "Expected {input:?} to be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_resolve_missing_secret() {
+        let error = SecretRef::Env("PMON_TEST_UNSET_SECRET".to_string())
+            .resolve()
+            .unwrap_err();
+        assert_eq!(error, "secret env:PMON_TEST_UNSET_SECRET is not set");
+    }
+}
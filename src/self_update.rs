@@ -0,0 +1,639 @@
+//! Checking for and installing newer releases
+//!
+//! `pmon self-update --check` asks GitHub which releases exist and compares
+//! the newest one on the chosen channel with the running version. pmon never
+//! checks on its own; nothing is sent unless the command is run. Releases
+//! are fetched with `curl`, the way notifications go through the desktop's
+//! notifier, so no TLS stack is compiled into pmon.
+//!
+//! Builds with the `self-update` feature can also download the release
+//! binary for this platform and put it in place of the running executable.
+//! Only binaries under pmon's GitHub release downloads are fetched, over
+//! HTTPS, and each must match the SHA-256 published beside it as
+//! `<binary>.sha256`; a release without one is not installed.
+
+use crate::error::{PbError, PbResult};
+use serde::Deserialize;
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Releases of pmon, newest first
+pub const RELEASES_URL: &str = "https://api.github.com/repos/matsuokashuhei/pmon/releases";
+
+/// Version of the running pmon
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Where the binaries of pmon's releases are downloaded from
+pub const DOWNLOAD_URL_PREFIX: &str = "https://github.com/matsuokashuhei/pmon/releases/download/";
+
+/// Seconds to wait for GitHub before giving up
+#[cfg(feature = "net")]
+const FETCH_TIMEOUT_SECS: &str = "10";
+
+/// Seconds a release binary may take to download
+#[cfg(feature = "self-update")]
+const DOWNLOAD_TIMEOUT_SECS: &str = "300";
+
+/// Which releases count as updates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Channel {
+    /// Final releases only
+    #[default]
+    Stable,
+    /// Final releases and release candidates
+    Prerelease,
+}
+
+/// A release as listed by the GitHub API
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    #[serde(default)]
+    pub prerelease: bool,
+    #[serde(default)]
+    pub draft: bool,
+    pub html_url: String,
+    #[serde(default)]
+    pub assets: Vec<Asset>,
+}
+
+/// A file attached to a release
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Asset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+impl Release {
+    /// The binary built for this platform, if the release has one
+    pub fn asset_for_platform(&self) -> Option<&Asset> {
+        let name = platform_asset_name()?;
+        self.assets.iter().find(|asset| asset.name == name)
+    }
+
+    /// The `<name>.sha256` file published with `asset`, if any
+    pub fn checksum_for(&self, asset: &Asset) -> Option<&Asset> {
+        let name = format!("{}.sha256", asset.name);
+        self.assets.iter().find(|candidate| candidate.name == name)
+    }
+}
+
+/// Refuse to download from anywhere but pmon's release downloads
+///
+/// # Examples
+///
+/// ```
+/// use pmon::self_update::check_download_url;
+///
+/// assert!(check_download_url(
+///     "https://github.com/matsuokashuhei/pmon/releases/download/v2.1.0/pmon-linux-x86_64"
+/// )
+/// .is_ok());
+/// assert!(check_download_url("file:///etc/passwd").is_err());
+/// assert!(check_download_url("http://github.com/matsuokashuhei/pmon/releases/download/v2.1.0/pmon").is_err());
+/// ```
+pub fn check_download_url(url: &str) -> PbResult<()> {
+    let path = url.strip_prefix(DOWNLOAD_URL_PREFIX).unwrap_or_default();
+    let mut segments = path.split('/');
+    let plain = |segment: Option<&str>| {
+        segment.is_some_and(|segment| {
+            !segment.is_empty()
+                && segment != ".."
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "._-+".contains(c))
+        })
+    };
+    // `<tag>/<file>` and nothing else
+    if plain(segments.next()) && plain(segments.next()) && segments.next().is_none() {
+        Ok(())
+    } else {
+        Err(PbError::update(format!(
+            "refusing to download {url}: not a pmon release on GitHub"
+        )))
+    }
+}
+
+/// Read the digest from a `sha256sum`-style checksum file
+///
+/// # Examples
+///
+/// ```
+/// use pmon::self_update::parse_checksum;
+///
+/// let digest = "a".repeat(64);
+/// assert_eq!(parse_checksum(&format!("{digest}  pmon-linux-x86_64\n")), Some(digest));
+/// assert_eq!(parse_checksum("not a digest"), None);
+/// ```
+pub fn parse_checksum(text: &str) -> Option<String> {
+    let digest = text.split_whitespace().next()?.to_ascii_lowercase();
+    (digest.len() == 64 && digest.chars().all(|c| c.is_ascii_hexdigit())).then_some(digest)
+}
+
+/// A semantic version such as `2.1.0` or `2.1.0-rc.1`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    pub pre: Option<String>,
+}
+
+impl Version {
+    /// Parse a version or release tag, with or without a leading `v`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pmon::self_update::Version;
+    ///
+    /// let version = Version::parse("v2.1.0-rc.1").unwrap();
+    /// assert_eq!(version.to_string(), "2.1.0-rc.1");
+    /// assert!(version < Version::parse("2.1.0").unwrap());
+    /// ```
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        let input = input.strip_prefix('v').unwrap_or(input);
+        let (numbers, pre) = match input.split_once('-') {
+            Some((numbers, pre)) if !pre.is_empty() => (numbers, Some(pre.to_string())),
+            Some(_) => return None,
+            None => (input, None),
+        };
+        let mut parts = numbers.split('.').map(|part| part.parse::<u64>().ok());
+        let (Some(Some(major)), Some(Some(minor)), Some(Some(patch)), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return None;
+        };
+        Some(Self {
+            major,
+            minor,
+            patch,
+            pre,
+        })
+    }
+
+    /// The running pmon's version
+    pub fn current() -> PbResult<Self> {
+        Self::parse(CURRENT_VERSION).ok_or_else(|| {
+            PbError::update(format!(
+                "cannot read this build's version {CURRENT_VERSION}"
+            ))
+        })
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (&self.pre, &other.pre) {
+                // A release candidate comes before its release
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(a), Some(b)) => compare_pre_release(a, b),
+            })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(pre) = &self.pre {
+            write!(f, "-{pre}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Order pre-release labels field by field, numbers numerically
+///
+/// `rc.2` comes before `rc.10`, and a number before a word.
+fn compare_pre_release(a: &str, b: &str) -> Ordering {
+    let mut a_fields = a.split('.');
+    let mut b_fields = b.split('.');
+    loop {
+        let ordering = match (a_fields.next(), b_fields.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a), Some(b)) => match (a.parse::<u64>(), b.parse::<u64>()) {
+                (Ok(a), Ok(b)) => a.cmp(&b),
+                (Ok(_), Err(_)) => Ordering::Less,
+                (Err(_), Ok(_)) => Ordering::Greater,
+                (Err(_), Err(_)) => a.cmp(b),
+            },
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+/// Read the release list returned by the GitHub API
+pub fn parse_releases(json: &str) -> PbResult<Vec<Release>> {
+    serde_json::from_str(json)
+        .map_err(|e| PbError::update(format!("unexpected reply from GitHub: {e}")))
+}
+
+/// Newest published release on `channel`, with its version
+///
+/// Drafts and tags that are not versions are skipped. The stable channel
+/// also skips releases marked as pre-releases or tagged like one.
+pub fn latest_release(releases: &[Release], channel: Channel) -> Option<(Version, &Release)> {
+    releases
+        .iter()
+        .filter(|release| !release.draft)
+        .filter_map(|release| Some((Version::parse(&release.tag_name)?, release)))
+        .filter(|(version, release)| {
+            channel == Channel::Prerelease || (!release.prerelease && version.pre.is_none())
+        })
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+}
+
+/// Report whether `latest` is newer than `current`
+pub fn format_check(current: &Version, latest: Option<(&Version, &Release)>) -> String {
+    match latest {
+        Some((version, release)) if version > current => format!(
+            "pmon {version} is available (you have {current}): {}",
+            release.html_url
+        ),
+        _ => format!("pmon {current} is up to date"),
+    }
+}
+
+/// Name of the release asset built for this platform
+///
+/// Returns `None` on platforms no binary is published for.
+pub fn platform_asset_name() -> Option<&'static str> {
+    if cfg!(all(target_os = "linux", target_arch = "x86_64")) {
+        Some("pmon-linux-x86_64")
+    } else if cfg!(all(target_os = "linux", target_arch = "aarch64")) {
+        Some("pmon-linux-aarch64")
+    } else if cfg!(all(target_os = "macos", target_arch = "x86_64")) {
+        Some("pmon-macos-x86_64")
+    } else if cfg!(all(target_os = "macos", target_arch = "aarch64")) {
+        Some("pmon-macos-aarch64")
+    } else if cfg!(all(target_os = "windows", target_arch = "x86_64")) {
+        Some("pmon-windows-x86_64.exe")
+    } else {
+        None
+    }
+}
+
+/// Ask GitHub for pmon's releases
+#[cfg(feature = "net")]
+pub fn fetch_releases() -> PbResult<Vec<Release>> {
+    let output = curl(FETCH_TIMEOUT_SECS)
+        .args(["-H", "Accept: application/vnd.github+json", RELEASES_URL])
+        .output()
+        .map_err(|e| PbError::update(format!("could not run curl: {e}")))?;
+    if !output.status.success() {
+        return Err(PbError::update(format!(
+            "could not reach {RELEASES_URL}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    parse_releases(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Release checks need network access, which is left out of this build
+#[cfg(not(feature = "net"))]
+pub fn fetch_releases() -> PbResult<Vec<Release>> {
+    Err(PbError::update("pmon was built without the `net` feature"))
+}
+
+/// Replace the running executable with `release`'s binary for this platform
+///
+/// The binary is downloaded next to the executable, checked against the
+/// release's checksum, and renamed over it, so a failed or tampered
+/// download leaves the installed pmon untouched. Returns the path that was
+/// replaced.
+#[cfg(feature = "self-update")]
+pub fn install(release: &Release) -> PbResult<std::path::PathBuf> {
+    let asset = release.asset_for_platform().ok_or_else(|| {
+        PbError::update(format!(
+            "{} has no binary for this platform; see {}",
+            release.tag_name, release.html_url
+        ))
+    })?;
+    let checksum = release.checksum_for(asset).ok_or_else(|| {
+        PbError::update(format!(
+            "{} publishes no checksum for {}; see {}",
+            release.tag_name, asset.name, release.html_url
+        ))
+    })?;
+    check_download_url(&asset.browser_download_url)?;
+    check_download_url(&checksum.browser_download_url)?;
+    let expected = fetch_checksum(checksum)?;
+
+    let exe = std::env::current_exe()
+        .and_then(|path| path.canonicalize())
+        .map_err(|e| PbError::update(format!("cannot locate the running pmon: {e}")))?;
+    let download = exe.with_file_name(format!(".{}.download", asset.name));
+    let discard = |error: PbError| {
+        let _ = std::fs::remove_file(&download);
+        error
+    };
+    let status = curl(DOWNLOAD_TIMEOUT_SECS)
+        .arg("-o")
+        .arg(&download)
+        .arg(&asset.browser_download_url)
+        .status()
+        .map_err(|e| PbError::update(format!("could not run curl: {e}")))?;
+    if !status.success() {
+        return Err(discard(PbError::update(format!(
+            "could not download {}",
+            asset.browser_download_url
+        ))));
+    }
+    let actual = std::fs::read(&download)
+        .map(|bytes| sha256_hex(&bytes))
+        .map_err(|e| discard(PbError::update(format!("{}: {e}", download.display()))))?;
+    if actual != expected {
+        return Err(discard(PbError::update(format!(
+            "{} does not match its checksum {}",
+            asset.name, checksum.browser_download_url
+        ))));
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&download, std::fs::Permissions::from_mode(0o755))
+            .map_err(|e| discard(PbError::update(format!("{}: {e}", download.display()))))?;
+    }
+    // Windows keeps a running executable's name, but lets it be renamed
+    #[cfg(windows)]
+    std::fs::rename(&exe, exe.with_extension("old.exe"))
+        .map_err(|e| discard(PbError::update(format!("{}: {e}", exe.display()))))?;
+    std::fs::rename(&download, &exe)
+        .map_err(|e| discard(PbError::update(format!("{}: {e}", exe.display()))))?;
+    Ok(exe)
+}
+
+/// Download and read a release's checksum file
+#[cfg(feature = "self-update")]
+fn fetch_checksum(checksum: &Asset) -> PbResult<String> {
+    let output = curl(FETCH_TIMEOUT_SECS)
+        .arg(&checksum.browser_download_url)
+        .output()
+        .map_err(|e| PbError::update(format!("could not run curl: {e}")))?;
+    if !output.status.success() {
+        return Err(PbError::update(format!(
+            "could not download {}",
+            checksum.browser_download_url
+        )));
+    }
+    parse_checksum(&String::from_utf8_lossy(&output.stdout)).ok_or_else(|| {
+        PbError::update(format!(
+            "{} does not hold a SHA-256 digest",
+            checksum.browser_download_url
+        ))
+    })
+}
+
+/// Lowercase hex SHA-256 of `bytes`
+#[cfg(feature = "self-update")]
+fn sha256_hex(bytes: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// `curl` over HTTPS only, failing on HTTP errors, quiet except for errors
+#[cfg(feature = "net")]
+fn curl(timeout_secs: &str) -> std::process::Command {
+    let mut command = std::process::Command::new("curl");
+    command
+        .args(["-fsSL", "--proto", "=https", "--proto-redir", "=https"])
+        .args(["--max-time", timeout_secs])
+        .stdin(std::process::Stdio::null());
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(tag: &str, prerelease: bool) -> Release {
+        Release {
+            tag_name: tag.to_string(),
+            prerelease,
+            draft: false,
+            html_url: format!("https://github.com/matsuokashuhei/pmon/releases/tag/{tag}"),
+            assets: Vec::new(),
+        }
+    }
+
+    fn version(input: &str) -> Version {
+        Version::parse(input).unwrap()
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(
+            version("v2.1.3"),
+            Version {
+                major: 2,
+                minor: 1,
+                patch: 3,
+                pre: None
+            }
+        );
+        assert_eq!(version("2.1.0-rc.1").pre.as_deref(), Some("rc.1"));
+        assert_eq!(version(" v10.0.0 ").to_string(), "10.0.0");
+
+        for input in ["", "v", "2.1", "2.1.0.4", "2.x.0", "2.1.0-", "latest"] {
+            assert_eq!(Version::parse(input), None, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_current_version_parses() {
+        assert_eq!(Version::current().unwrap().to_string(), CURRENT_VERSION);
+    }
+
+    #[test]
+    fn test_version_order() {
+        assert!(version("2.0.1") > version("2.0.0"));
+        assert!(version("2.10.0") > version("2.9.9"));
+        assert!(version("3.0.0") > version("2.99.99"));
+        assert!(version("2.1.0-rc.1") < version("2.1.0"));
+        assert!(version("2.1.0-rc.1") > version("2.0.0"));
+        assert!(version("2.1.0-rc.10") > version("2.1.0-rc.2"));
+        assert!(version("2.1.0-beta") < version("2.1.0-rc.1"));
+        assert!(version("2.1.0-rc") < version("2.1.0-rc.1"));
+        assert_eq!(version("v2.0.0").cmp(&version("2.0.0")), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_latest_release_by_channel() {
+        let releases = vec![
+            release("v2.2.0-rc.1", true),
+            release("v2.1.0", false),
+            release("v2.0.0", false),
+            release("nightly", true),
+        ];
+
+        let (stable, _) = latest_release(&releases, Channel::Stable).unwrap();
+        assert_eq!(stable.to_string(), "2.1.0");
+        let (pre, _) = latest_release(&releases, Channel::Prerelease).unwrap();
+        assert_eq!(pre.to_string(), "2.2.0-rc.1");
+    }
+
+    #[test]
+    fn test_latest_release_skips_drafts_and_unmarked_candidates() {
+        let mut draft = release("v3.0.0", false);
+        draft.draft = true;
+        // Tagged as a candidate but not marked as a pre-release on GitHub
+        let candidate = release("v2.2.0-rc.1", false);
+        let releases = vec![draft, candidate, release("v2.1.0", false)];
+
+        let (latest, _) = latest_release(&releases, Channel::Stable).unwrap();
+        assert_eq!(latest.to_string(), "2.1.0");
+        assert_eq!(latest_release(&[], Channel::Prerelease), None);
+    }
+
+    #[test]
+    fn test_format_check() {
+        let newer = release("v2.1.0", false);
+        let current = version("2.0.0");
+
+        assert_eq!(
+            format_check(&current, Some((&version("2.1.0"), &newer))),
+            "pmon 2.1.0 is available (you have 2.0.0): \
+             https://github.com/matsuokashuhei/pmon/releases/tag/v2.1.0"
+        );
+        assert_eq!(
+            format_check(&version("2.1.0"), Some((&version("2.1.0"), &newer))),
+            "pmon 2.1.0 is up to date"
+        );
+        // A build newer than any release, such as one from main
+        assert_eq!(
+            format_check(&version("2.2.0"), Some((&version("2.1.0"), &newer))),
+            "pmon 2.2.0 is up to date"
+        );
+        assert_eq!(format_check(&current, None), "pmon 2.0.0 is up to date");
+    }
+
+    #[test]
+    fn test_parse_releases() {
+        let json = r#"[
+            {
+                "tag_name": "v2.1.0",
+                "name": "pmon 2.1.0",
+                "prerelease": false,
+                "draft": false,
+                "html_url": "https://github.com/matsuokashuhei/pmon/releases/tag/v2.1.0",
+                "assets": [
+                    {
+                        "name": "pmon-linux-x86_64",
+                        "size": 1024,
+                        "browser_download_url": "https://github.com/matsuokashuhei/pmon/releases/download/v2.1.0/pmon-linux-x86_64"
+                    }
+                ]
+            }
+        ]"#;
+        let releases = parse_releases(json).unwrap();
+        assert_eq!(releases.len(), 1);
+        assert_eq!(releases[0].tag_name, "v2.1.0");
+        assert_eq!(releases[0].assets[0].name, "pmon-linux-x86_64");
+
+        assert!(matches!(
+            parse_releases(r#"{"message": "API rate limit exceeded"}"#),
+            Err(PbError::Update { .. })
+        ));
+    }
+
+    #[test]
+    fn test_asset_for_platform() {
+        let mut release = release("v2.1.0", false);
+        assert_eq!(release.asset_for_platform(), None);
+
+        let Some(name) = platform_asset_name() else {
+            return;
+        };
+        release.assets = ["pmon-linux-x86_64", "pmon-macos-aarch64", name]
+            .iter()
+            .map(|name| Asset {
+                name: name.to_string(),
+                browser_download_url: format!("https://example.com/{name}"),
+            })
+            .collect();
+        assert_eq!(release.asset_for_platform().unwrap().name, name);
+    }
+
+    #[test]
+    fn test_checksum_for() {
+        let mut release = release("v2.1.0", false);
+        let asset = |name: &str| Asset {
+            name: name.to_string(),
+            browser_download_url: format!("{DOWNLOAD_URL_PREFIX}v2.1.0/{name}"),
+        };
+        release.assets = vec![asset("pmon-linux-x86_64"), asset("pmon-macos-aarch64")];
+        assert_eq!(release.checksum_for(&release.assets[0]), None);
+
+        release.assets.push(asset("pmon-linux-x86_64.sha256"));
+        assert_eq!(
+            release.checksum_for(&release.assets[0]).unwrap().name,
+            "pmon-linux-x86_64.sha256"
+        );
+        assert_eq!(release.checksum_for(&release.assets[1]), None);
+    }
+
+    #[test]
+    fn test_check_download_url() {
+        assert!(check_download_url(&format!(
+            "{DOWNLOAD_URL_PREFIX}v2.1.0-rc.1/pmon-linux-x86_64.sha256"
+        ))
+        .is_ok());
+
+        for url in [
+            "file:///usr/bin/pmon",
+            "http://github.com/matsuokashuhei/pmon/releases/download/v2.1.0/pmon",
+            "https://example.com/matsuokashuhei/pmon/releases/download/v2.1.0/pmon",
+            "https://github.com/someone/pmon/releases/download/v2.1.0/pmon",
+            "https://github.com/matsuokashuhei/pmon/releases/download/v2.1.0",
+            "https://github.com/matsuokashuhei/pmon/releases/download/../../x/pmon",
+            "https://github.com/matsuokashuhei/pmon/releases/download/v2.1.0/a/pmon",
+            "https://github.com/matsuokashuhei/pmon/releases/download/v2.1.0/pmon?x=1",
+            "https://github.com/matsuokashuhei/pmon/releases/download.evil.com/v2/pmon",
+        ] {
+            assert!(
+                matches!(check_download_url(url), Err(PbError::Update { .. })),
+                "{url}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_checksum() {
+        let digest = "0123456789abcdef".repeat(4);
+        assert_eq!(parse_checksum(&digest), Some(digest.clone()));
+        assert_eq!(
+            parse_checksum(&format!("{}  pmon-linux-x86_64\n", digest.to_uppercase())),
+            Some(digest.clone())
+        );
+        assert_eq!(parse_checksum(""), None);
+        assert_eq!(parse_checksum(&digest[1..]), None);
+        assert_eq!(parse_checksum(&format!("{}g", &digest[1..])), None);
+    }
+
+    #[cfg(feature = "self-update")]
+    #[test]
+    fn test_sha256_hex() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
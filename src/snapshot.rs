@@ -0,0 +1,473 @@
+//! Image snapshots of the bar for wikis and status pages
+//!
+//! `pmon snapshot` draws the progress of a window as it stands now and
+//! exits. SVG output is plain text and always available: the label above
+//! the bar, and the percentage with the elapsed and remaining time below
+//! it. PNG output needs the `png` feature and, having no fonts to draw
+//! with, shows only the bar and the percentage, in a small built-in digit
+//! font. With `--copy`, the bar is instead copied as plain text, or emoji
+//! with `--emoji`, for pasting into chat.
+
+use crate::layout::fit_label;
+use crate::progress_bar::{format_percentage, render, DurationFormat, RenderOptions};
+use crate::progress_state::ProgressState;
+use std::io::{self, Write};
+
+/// Width of the image in pixels
+pub const IMAGE_WIDTH: u32 = 480;
+
+/// Height of the bar in pixels
+pub const BAR_HEIGHT: u32 = 16;
+
+/// An RGB color
+type Rgb = (u8, u8, u8);
+
+/// Unfilled part of the bar
+const TRACK_COLOR: Rgb = (0xe1, 0xe4, 0xe8);
+
+/// Filled part of the bar while the window is open
+const FILL_COLOR: Rgb = (0x2d, 0xa4, 0x4e);
+
+/// Filled bar once the window has elapsed, the red of the xbar title
+const OVERDUE_COLOR: Rgb = (0xd7, 0x3a, 0x49);
+
+/// Text color
+const TEXT_COLOR: Rgb = (0x24, 0x29, 0x2f);
+
+/// Line height of SVG text in pixels
+const LINE_HEIGHT: u32 = 20;
+
+/// Image formats `pmon snapshot` can write
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ImageFormat {
+    /// Scalable vector graphics with the label and times as text
+    #[default]
+    Svg,
+    /// A bitmap of the bar and percentage (needs the `png` feature)
+    Png,
+}
+
+/// Write a snapshot of `state` to `out` in `format`
+pub fn write_image(
+    format: ImageFormat,
+    state: &ProgressState,
+    label: Option<&str>,
+    duration_format: DurationFormat,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    match format {
+        ImageFormat::Svg => out.write_all(render_svg(state, label, duration_format).as_bytes()),
+        ImageFormat::Png => write_png(state, out),
+    }
+}
+
+/// Render `state` as plain text for pasting into chat
+///
+/// The bar is drawn as `options` asks, without color, below the label.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDateTime;
+/// use pmon::progress_bar::{BarStyle, EmojiCells, RenderOptions};
+/// use pmon::snapshot::render_text;
+/// use pmon::ProgressState;
+///
+/// let at = |time| NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M").unwrap();
+/// let state = ProgressState::new(at("2025-01-27 09:00"), at("2025-01-27 10:00"), at("2025-01-27 09:30"));
+/// let options = RenderOptions {
+///     width: 20,
+///     style: BarStyle::Emoji(EmojiCells::default()),
+///     time: None,
+///     ..RenderOptions::default()
+/// };
+/// assert_eq!(
+///     render_text(&state, Some("Standup"), &options),
+///     "Standup\n🟩🟩🟩🟩🟩⬜⬜⬜⬜⬜ 50.0%"
+/// );
+/// ```
+pub fn render_text(state: &ProgressState, label: Option<&str>, options: &RenderOptions) -> String {
+    let bar = render(
+        state,
+        &RenderOptions {
+            color: false,
+            ..*options
+        },
+    );
+    match label {
+        Some(label) => format!("{}\n{bar}", fit_label(label)),
+        None => bar,
+    }
+}
+
+/// Render `state` as an SVG image
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDateTime;
+/// use pmon::progress_bar::DurationFormat;
+/// use pmon::progress_state::ProgressState;
+/// use pmon::snapshot::render_svg;
+///
+/// let at = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+/// let state = ProgressState::new(
+///     at("2025-01-27 09:00:00"),
+///     at("2025-01-27 17:00:00"),
+///     at("2025-01-27 13:00:00"),
+/// );
+/// let svg = render_svg(&state, Some("Workday"), DurationFormat::default());
+/// assert!(svg.starts_with("<svg"));
+/// assert!(svg.contains("50.0% · 4h 0m elapsed, 4h 0m remaining"));
+/// ```
+pub fn render_svg(state: &ProgressState, label: Option<&str>, format: DurationFormat) -> String {
+    let percent = format_percentage(state.percentage);
+    let times = if state.is_complete() {
+        format!(
+            "{} elapsed, {} overtime",
+            format.elapsed(state.elapsed()),
+            format.elapsed(state.overtime())
+        )
+    } else {
+        format!(
+            "{} elapsed, {} remaining",
+            format.elapsed(state.elapsed()),
+            format.remaining(state.remaining())
+        )
+    };
+    let summary = format!("{percent} · {times}");
+    let title = match label {
+        Some(label) => format!("{label}: {summary}"),
+        None => summary.clone(),
+    };
+
+    let mut body = Vec::new();
+    let mut y = 0;
+    if let Some(label) = label {
+        y += LINE_HEIGHT;
+        body.push(format!(
+            r#"  <text x="0" y="{}">{}</text>"#,
+            y - 6,
+            escape_xml(&fit_label(label))
+        ));
+    }
+    body.push(format!(
+        r#"  <rect x="0" y="{y}" width="{IMAGE_WIDTH}" height="{BAR_HEIGHT}" rx="3" fill="{}"/>"#,
+        hex(TRACK_COLOR)
+    ));
+    let filled = filled_width(state.percentage);
+    if filled > 0 {
+        body.push(format!(
+            r#"  <rect x="0" y="{y}" width="{filled}" height="{BAR_HEIGHT}" rx="3" fill="{}"/>"#,
+            hex(bar_color(state))
+        ));
+    }
+    y += BAR_HEIGHT + LINE_HEIGHT;
+    body.push(format!(
+        r#"  <text x="0" y="{}">{}</text>"#,
+        y - 4,
+        escape_xml(&summary)
+    ));
+
+    format!(
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" role="img" aria-label="{title}">"#,
+            "\n  <title>{title}</title>\n",
+            "  <style>text {{ font-family: ui-monospace, Menlo, Consolas, monospace; font-size: 13px; fill: {text}; }}</style>\n",
+            "{body}\n</svg>\n"
+        ),
+        width = IMAGE_WIDTH,
+        height = y,
+        title = escape_xml(&title),
+        text = hex(TEXT_COLOR),
+        body = body.join("\n"),
+    )
+}
+
+/// Pixels of the bar filled at `percentage`, full once the window has elapsed
+fn filled_width(percentage: f64) -> u32 {
+    if !percentage.is_finite() {
+        return 0;
+    }
+    (percentage.clamp(0.0, 100.0) / 100.0 * f64::from(IMAGE_WIDTH)).round() as u32
+}
+
+/// Color of the filled part of the bar
+fn bar_color(state: &ProgressState) -> Rgb {
+    if state.is_complete() {
+        OVERDUE_COLOR
+    } else {
+        FILL_COLOR
+    }
+}
+
+/// CSS notation of a color, such as `#2da44e`
+fn hex((r, g, b): Rgb) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// Make text safe inside an SVG element or attribute
+pub(crate) fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Pixels per dot of the built-in font
+#[cfg(feature = "png")]
+const GLYPH_SCALE: u32 = 3;
+
+/// Dots per row and rows per glyph of the built-in font
+#[cfg(feature = "png")]
+const GLYPH_SIZE: (u32, u32) = (3, 5);
+
+/// Rows of a glyph in the built-in font, top first, three dots per row
+///
+/// Covers what [`format_percentage`] prints.
+#[cfg(any(feature = "png", test))]
+fn glyph(ch: char) -> Option<[u8; 5]> {
+    Some(match ch {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '%' => [0b101, 0b001, 0b010, 0b100, 0b101],
+        _ => return None,
+    })
+}
+
+/// An RGBA image being drawn, transparent where nothing is drawn
+#[cfg(feature = "png")]
+struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+#[cfg(feature = "png")]
+impl Canvas {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; (width * height * 4) as usize],
+        }
+    }
+
+    fn fill_rect(&mut self, x: u32, y: u32, width: u32, height: u32, (r, g, b): Rgb) {
+        for row in y..(y + height).min(self.height) {
+            for column in x..(x + width).min(self.width) {
+                let at = ((row * self.width + column) * 4) as usize;
+                self.pixels[at..at + 4].copy_from_slice(&[r, g, b, 0xff]);
+            }
+        }
+    }
+
+    fn draw_text(&mut self, text: &str, x: u32, y: u32, color: Rgb) {
+        let (columns, _) = GLYPH_SIZE;
+        let advance = (columns + 1) * GLYPH_SCALE;
+        for (index, rows) in text.chars().filter_map(glyph).enumerate() {
+            let left = x + index as u32 * advance;
+            for (row, dots) in (0..).zip(rows) {
+                for column in 0..columns {
+                    if dots & (1 << (columns - 1 - column)) != 0 {
+                        self.fill_rect(
+                            left + column * GLYPH_SCALE,
+                            y + row * GLYPH_SCALE,
+                            GLYPH_SCALE,
+                            GLYPH_SCALE,
+                            color,
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Write `state` as a PNG image of the bar with the percentage below it
+#[cfg(feature = "png")]
+fn write_png(state: &ProgressState, out: &mut dyn Write) -> io::Result<()> {
+    let (_, rows) = GLYPH_SIZE;
+    let text_top = BAR_HEIGHT + 6;
+    let height = text_top + rows * GLYPH_SCALE;
+    let mut canvas = Canvas::new(IMAGE_WIDTH, height);
+    canvas.fill_rect(0, 0, IMAGE_WIDTH, BAR_HEIGHT, TRACK_COLOR);
+    canvas.fill_rect(
+        0,
+        0,
+        filled_width(state.percentage),
+        BAR_HEIGHT,
+        bar_color(state),
+    );
+    canvas.draw_text(
+        &format_percentage(state.percentage),
+        0,
+        text_top,
+        TEXT_COLOR,
+    );
+
+    let mut encoder = png::Encoder::new(out, canvas.width, canvas.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer
+        .write_image_data(&canvas.pixels)
+        .map_err(io::Error::other)?;
+    writer.finish().map_err(io::Error::other)
+}
+
+/// PNG images are left out of this build
+#[cfg(not(feature = "png"))]
+fn write_png(_state: &ProgressState, _out: &mut dyn Write) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "pmon was built without the `png` feature",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn state_at(current: &str) -> ProgressState {
+        ProgressState::new(
+            create_test_datetime("2025-01-27 09:00:00"),
+            create_test_datetime("2025-01-27 17:00:00"),
+            create_test_datetime(current),
+        )
+    }
+
+    #[test]
+    fn test_svg_draws_the_filled_share_of_the_bar() {
+        let svg = render_svg(
+            &state_at("2025-01-27 11:00:00"),
+            None,
+            DurationFormat::default(),
+        );
+        assert!(
+            svg.contains(r##"<rect x="0" y="0" width="480" height="16" rx="3" fill="#e1e4e8"/>"##)
+        );
+        assert!(
+            svg.contains(r##"<rect x="0" y="0" width="120" height="16" rx="3" fill="#2da44e"/>"##)
+        );
+        assert!(svg.contains("<title>25.0% · 2h 0m elapsed, 6h 0m remaining</title>"));
+        assert!(svg.contains(r#"height="36""#));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+
+    #[test]
+    fn test_text_drops_color() {
+        let options = RenderOptions {
+            color: true,
+            width: 8,
+            ..RenderOptions::default()
+        };
+        assert_eq!(
+            render_text(&state_at("2025-01-27 19:00:00"), None, &options),
+            "[████████] 125.0% (10h 0m elapsed, +2h 0m over)"
+        );
+    }
+
+    #[test]
+    fn test_svg_label_is_escaped() {
+        let svg = render_svg(
+            &state_at("2025-01-27 13:00:00"),
+            Some("R&D <launch>"),
+            DurationFormat::default(),
+        );
+        assert!(svg.contains(r#"<text x="0" y="14">R&amp;D &lt;launch&gt;</text>"#));
+        assert!(svg.contains(r#"aria-label="R&amp;D &lt;launch&gt;: 50.0%"#));
+        assert!(!svg.contains("<launch>"));
+        // The bar moves down to make room for the label
+        assert!(svg.contains(r#"<rect x="0" y="20" width="240""#));
+        assert!(svg.contains(r#"height="56""#));
+    }
+
+    #[test]
+    fn test_svg_after_the_end() {
+        let svg = render_svg(
+            &state_at("2025-01-27 18:30:00"),
+            None,
+            DurationFormat::default(),
+        );
+        assert!(svg.contains(r##"width="480" height="16" rx="3" fill="#d73a49""##));
+        assert!(svg.contains("9h 30m elapsed, 1h 30m overtime"));
+    }
+
+    #[test]
+    fn test_svg_before_the_start_has_no_fill() {
+        let svg = render_svg(
+            &state_at("2025-01-27 08:00:00"),
+            None,
+            DurationFormat::default(),
+        );
+        assert_eq!(svg.matches("<rect").count(), 1);
+    }
+
+    #[test]
+    fn test_filled_width() {
+        assert_eq!(filled_width(0.0), 0);
+        assert_eq!(filled_width(50.0), 240);
+        assert_eq!(filled_width(150.0), IMAGE_WIDTH);
+        assert_eq!(filled_width(-5.0), 0);
+        assert_eq!(filled_width(f64::NAN), 0);
+    }
+
+    #[test]
+    fn test_font_covers_percentages() {
+        for percentage in [0.0, 42.25, 100.0, 1234.5, f64::NAN] {
+            let text = format_percentage(percentage);
+            assert!(text.chars().all(|ch| glyph(ch).is_some()), "{text}");
+        }
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn test_png_image() {
+        let mut image = Vec::new();
+        let state = state_at("2025-01-27 13:00:00");
+        write_image(
+            ImageFormat::Png,
+            &state,
+            Some("ignored"),
+            DurationFormat::default(),
+            &mut image,
+        )
+        .unwrap();
+
+        let decoder = png::Decoder::new(image.as_slice());
+        let reader = decoder.read_info().unwrap();
+        let info = reader.info();
+        assert_eq!(info.width, IMAGE_WIDTH);
+        assert_eq!(info.height, BAR_HEIGHT + 6 + 15);
+    }
+
+    #[cfg(not(feature = "png"))]
+    #[test]
+    fn test_png_needs_the_feature() {
+        let state = state_at("2025-01-27 13:00:00");
+        let result = write_image(
+            ImageFormat::Png,
+            &state,
+            None,
+            DurationFormat::default(),
+            &mut Vec::new(),
+        );
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::Unsupported);
+    }
+}
@@ -0,0 +1,515 @@
+//! Windows anchored to the sun
+//!
+//! `pmon --start sunrise --end sunset --location 35.68,139.69` follows the
+//! daylight at a place. The events are worked out with the sunrise
+//! equation used by NOAA's solar calculator, which is good to about a
+//! minute away from the poles: `sunrise` and `sunset` when the top of the
+//! sun crosses the horizon, `dawn` and `dusk` at the ends of civil
+//! twilight, and `solar-noon` when the sun is highest. Times are shown in
+//! the `--timezone` zone, or the local one. The calculations need the
+//! `solar` feature.
+//!
+//! `--preset daylight`, `twilight`, or `fast` picks a window between two
+//! events of the day. `fast` runs from dawn, reckoned by `--method` as
+//! prayer-time calculators reckon Fajr, to sunset. With `--daily` the
+//! window starts over each day, recomputed for that day's sun. The place
+//! and method can be set once in the configuration file:
+//!
+//! ```toml
+//! [solar]
+//! location = "21.42,39.83"
+//! method = "umm-al-qura"
+//! ```
+
+use crate::chain::Segment;
+use crate::error::{PbError, PbResult};
+use chrono::{NaiveDate, NaiveDateTime};
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// Name used in error messages about solar events
+const OPTION_NAME: &str = "solar event";
+
+/// A moment in the sun's day
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolarEvent {
+    /// Start of civil twilight in the morning
+    Dawn,
+    /// Top of the sun rising above the horizon
+    Sunrise,
+    /// Sun at its highest
+    SolarNoon,
+    /// Top of the sun setting below the horizon
+    Sunset,
+    /// End of civil twilight in the evening
+    Dusk,
+}
+
+impl SolarEvent {
+    /// Word for the event on the command line
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Dawn => "dawn",
+            Self::Sunrise => "sunrise",
+            Self::SolarNoon => "solar-noon",
+            Self::Sunset => "sunset",
+            Self::Dusk => "dusk",
+        }
+    }
+}
+
+impl FromStr for SolarEvent {
+    type Err = PbError;
+
+    /// Parse `dawn`, `sunrise`, `solar-noon`, `sunset`, or `dusk`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "dawn" => Ok(Self::Dawn),
+            "sunrise" => Ok(Self::Sunrise),
+            "solar-noon" => Ok(Self::SolarNoon),
+            "sunset" => Ok(Self::Sunset),
+            "dusk" => Ok(Self::Dusk),
+            _ => Err(PbError::invalid_argument(OPTION_NAME, s)),
+        }
+    }
+}
+
+impl std::fmt::Display for SolarEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A place on Earth, in degrees north and east
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Location {
+    /// Degrees north of the equator, negative to the south
+    pub latitude: f64,
+    /// Degrees east of Greenwich, negative to the west
+    pub longitude: f64,
+}
+
+impl FromStr for Location {
+    type Err = PbError;
+
+    /// Parse `LAT,LON` in decimal degrees
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pmon::solar::Location;
+    ///
+    /// let tokyo: Location = "35.68,139.69".parse().unwrap();
+    /// assert_eq!((tokyo.latitude, tokyo.longitude), (35.68, 139.69));
+    /// assert!("35.68".parse::<Location>().is_err());
+    /// assert!("91,0".parse::<Location>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || PbError::invalid_argument("--location", s);
+        let (latitude, longitude) = s.split_once(',').ok_or_else(invalid)?;
+        let latitude: f64 = latitude.trim().parse().map_err(|_| invalid())?;
+        let longitude: f64 = longitude.trim().parse().map_err(|_| invalid())?;
+        if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+            return Err(invalid());
+        }
+        Ok(Self {
+            latitude,
+            longitude,
+        })
+    }
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{},{}", self.latitude, self.longitude)
+    }
+}
+
+/// Altitude of the sun's center at sunrise and sunset, allowing for
+/// refraction and the sun's radius
+const HORIZON: f64 = -0.833;
+
+/// Altitude of the sun's center at the ends of civil twilight
+const CIVIL_TWILIGHT: f64 = -6.0;
+
+/// Where the sun is at an event
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(not(feature = "solar"), allow(dead_code))]
+enum Crossing {
+    /// Rising past this altitude of its center, in degrees
+    Rising(f64),
+    /// Highest in the sky
+    Transit,
+    /// Setting past this altitude
+    Setting(f64),
+}
+
+impl SolarEvent {
+    /// Where the sun is at the event
+    fn crossing(self) -> Crossing {
+        match self {
+            Self::Dawn => Crossing::Rising(CIVIL_TWILIGHT),
+            Self::Sunrise => Crossing::Rising(HORIZON),
+            Self::SolarNoon => Crossing::Transit,
+            Self::Sunset => Crossing::Setting(HORIZON),
+            Self::Dusk => Crossing::Setting(CIVIL_TWILIGHT),
+        }
+    }
+}
+
+/// Instant in UTC of `event` at `location` during the solar day of `date`
+///
+/// `None` when the sun stays above or below the event's altitude all day,
+/// as in polar summer and winter.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use pmon::solar::{event_utc, SolarEvent};
+///
+/// let tokyo = "35.68,139.69".parse().unwrap();
+/// let equinox = NaiveDate::from_ymd_opt(2025, 3, 20).unwrap();
+/// let sunrise = event_utc(SolarEvent::Sunrise, tokyo, equinox).unwrap();
+/// assert_eq!(sunrise.format("%Y-%m-%d %H").to_string(), "2025-03-19 20");
+/// ```
+#[cfg(feature = "solar")]
+pub fn event_utc(event: SolarEvent, location: Location, date: NaiveDate) -> Option<NaiveDateTime> {
+    crossing_utc(event.crossing(), location, date)
+}
+
+/// Instant in UTC of `crossing` at `location` during the solar day of `date`
+#[cfg(feature = "solar")]
+fn crossing_utc(crossing: Crossing, location: Location, date: NaiveDate) -> Option<NaiveDateTime> {
+    const J2000: f64 = 2_451_545.0;
+    const UNIX_EPOCH_JD: f64 = 2_440_587.5;
+    const OBLIQUITY: f64 = 23.4397;
+
+    let epoch = NaiveDate::from_ymd_opt(2000, 1, 1)?;
+    let day = (date - epoch).num_days() as f64;
+    // Mean solar noon, then the sun's position along the ecliptic
+    let mean_noon = day - location.longitude / 360.0;
+    let anomaly = (357.5291 + 0.985_600_28 * mean_noon).rem_euclid(360.0);
+    let m = anomaly.to_radians();
+    let center = 1.9148 * m.sin() + 0.0200 * (2.0 * m).sin() + 0.0003 * (3.0 * m).sin();
+    let ecliptic = (anomaly + center + 180.0 + 102.9372)
+        .rem_euclid(360.0)
+        .to_radians();
+    let transit = J2000 + mean_noon + 0.0053 * m.sin() - 0.0069 * (2.0 * ecliptic).sin();
+    let declination = (ecliptic.sin() * OBLIQUITY.to_radians().sin()).asin();
+
+    let half_day = |altitude: f64| {
+        let latitude = location.latitude.to_radians();
+        let cos_hour_angle = (altitude.to_radians().sin() - latitude.sin() * declination.sin())
+            / (latitude.cos() * declination.cos());
+        (-1.0..=1.0)
+            .contains(&cos_hour_angle)
+            .then(|| cos_hour_angle.acos().to_degrees() / 360.0)
+    };
+    let julian = match crossing {
+        Crossing::Rising(altitude) => transit - half_day(altitude)?,
+        Crossing::Transit => transit,
+        Crossing::Setting(altitude) => transit + half_day(altitude)?,
+    };
+    let seconds = ((julian - UNIX_EPOCH_JD) * 86_400.0).round() as i64;
+    chrono::DateTime::from_timestamp(seconds, 0).map(|time| time.naive_utc())
+}
+
+/// Civil time of `crossing` at `location` on `date`, in the `--timezone`
+/// zone or the local one; `name` names the event in errors
+#[cfg(feature = "solar")]
+fn crossing_on(
+    crossing: Crossing,
+    name: &str,
+    location: Location,
+    date: NaiveDate,
+) -> PbResult<NaiveDateTime> {
+    use chrono::{Local, TimeZone, Utc};
+
+    let utc = crossing_utc(crossing, location, date).ok_or_else(|| {
+        PbError::invalid_time_format(format!(
+            "There is no {name} at {location} on {date}: the sun never gets that high or low"
+        ))
+    })?;
+    let utc = Utc.from_utc_datetime(&utc);
+    Ok(match crate::timezone::configured_timezone() {
+        Some(tz) => utc.with_timezone(&tz).naive_local(),
+        None => utc.with_timezone(&Local).naive_local(),
+    })
+}
+
+/// Solar events cannot be computed without the `solar` feature
+#[cfg(not(feature = "solar"))]
+fn crossing_on(
+    _crossing: Crossing,
+    name: &str,
+    _location: Location,
+    _date: NaiveDate,
+) -> PbResult<NaiveDateTime> {
+    Err(PbError::invalid_argument(
+        OPTION_NAME,
+        format!("{name}: this build of pmon does not include the `solar` feature"),
+    ))
+}
+
+/// Civil time of `event` at `location` on `date`, in the `--timezone`
+/// zone or the local one
+pub fn event_on(event: SolarEvent, location: Location, date: NaiveDate) -> PbResult<NaiveDateTime> {
+    crossing_on(event.crossing(), event.as_str(), location, date)
+}
+
+/// First `event` at `location` after `after`, looking up to a day ahead
+pub fn event_after(
+    event: SolarEvent,
+    location: Location,
+    after: NaiveDateTime,
+) -> PbResult<NaiveDateTime> {
+    let today = event_on(event, location, after.date());
+    match today {
+        Ok(time) if time > after => Ok(time),
+        _ => match after.date().succ_opt() {
+            Some(tomorrow) => event_on(event, location, tomorrow),
+            None => today,
+        },
+    }
+}
+
+/// How far below the horizon the sun is at dawn, for the `fast` preset
+///
+/// The methods are those of the common prayer-time calculators, which
+/// differ in the angle taken for Fajr.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DawnMethod {
+    /// 6°, the start of civil twilight
+    Civil,
+    /// 18°, Muslim World League
+    #[default]
+    Mwl,
+    /// 15°, Islamic Society of North America
+    Isna,
+    /// 19.5°, Egyptian General Authority of Survey
+    Egypt,
+    /// 18.5°, Umm al-Qura University, Makkah
+    UmmAlQura,
+    /// 18°, University of Islamic Sciences, Karachi
+    Karachi,
+}
+
+impl DawnMethod {
+    /// Degrees of the sun below the horizon at dawn
+    pub fn depression(self) -> f64 {
+        match self {
+            Self::Civil => -CIVIL_TWILIGHT,
+            Self::Mwl | Self::Karachi => 18.0,
+            Self::Isna => 15.0,
+            Self::Egypt => 19.5,
+            Self::UmmAlQura => 18.5,
+        }
+    }
+}
+
+/// Daily windows between two solar events, for `--preset`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Preset {
+    /// Sunrise to sunset
+    Daylight,
+    /// Civil dawn to civil dusk
+    Twilight,
+    /// Dawn by the chosen method (Fajr) to sunset (Maghrib), as kept in Ramadan
+    Fast,
+}
+
+impl Preset {
+    /// Label of the window when none is given
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Daylight => "Daylight",
+            Self::Twilight => "Twilight",
+            Self::Fast => "Fast",
+        }
+    }
+
+    /// The window on `date` at `location`
+    pub fn window_on(
+        self,
+        location: Location,
+        method: DawnMethod,
+        date: NaiveDate,
+    ) -> PbResult<Segment> {
+        let (start, end) = match self {
+            Self::Daylight => (SolarEvent::Sunrise, SolarEvent::Sunset),
+            Self::Twilight => (SolarEvent::Dawn, SolarEvent::Dusk),
+            Self::Fast => {
+                let dawn = Crossing::Rising(-method.depression());
+                return Ok(Segment {
+                    label: Some(self.label().to_string()),
+                    start: crossing_on(dawn, "dawn", location, date)?,
+                    end: event_on(SolarEvent::Sunset, location, date)?,
+                });
+            }
+        };
+        Ok(Segment {
+            label: Some(self.label().to_string()),
+            start: event_on(start, location, date)?,
+            end: event_on(end, location, date)?,
+        })
+    }
+
+    /// The first window at `location` that ends after `after`: the one
+    /// under way or the next day's
+    pub fn next_window(
+        self,
+        location: Location,
+        method: DawnMethod,
+        after: NaiveDateTime,
+    ) -> PbResult<Segment> {
+        let today = self.window_on(location, method, after.date())?;
+        if today.end > after {
+            return Ok(today);
+        }
+        match after.date().succ_opt() {
+            Some(tomorrow) => self.window_on(location, method, tomorrow),
+            None => Ok(today),
+        }
+    }
+}
+
+/// The `[solar]` table in the configuration file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SolarConfig {
+    /// Place used without `--location`, as `LAT,LON`
+    pub location: Option<String>,
+    /// How dawn is reckoned for the `fast` preset without `--method`
+    pub method: Option<DawnMethod>,
+}
+
+impl SolarConfig {
+    /// The configured place, if any
+    pub fn location(&self) -> PbResult<Option<Location>> {
+        self.location.as_deref().map(str::parse).transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_solar_event_and_location() {
+        assert_eq!(
+            "Sunrise".parse::<SolarEvent>().unwrap(),
+            SolarEvent::Sunrise
+        );
+        assert_eq!(
+            "solar-noon".parse::<SolarEvent>().unwrap(),
+            SolarEvent::SolarNoon
+        );
+        assert!(matches!(
+            "noon".parse::<SolarEvent>(),
+            Err(PbError::InvalidArgument { .. })
+        ));
+
+        let sydney: Location = " -33.87 , 151.21 ".parse().unwrap();
+        assert_eq!(sydney.to_string(), "-33.87,151.21");
+        assert!("0,181".parse::<Location>().is_err());
+        assert!("north,east".parse::<Location>().is_err());
+    }
+
+    #[cfg(not(feature = "solar"))]
+    #[test]
+    fn test_events_need_the_solar_feature() {
+        let tokyo: Location = "35.68,139.69".parse().unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 6, 21).unwrap();
+        let error = event_on(SolarEvent::Sunrise, tokyo, date).unwrap_err();
+        assert!(matches!(error, PbError::InvalidArgument { .. }));
+        assert!(error
+            .to_string()
+            .contains("does not include the `solar` feature"));
+    }
+
+    #[cfg(feature = "solar")]
+    #[test]
+    fn test_sun_events_in_tokyo() {
+        let tokyo: Location = "35.68,139.69".parse().unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 6, 21).unwrap();
+        let at = |event| {
+            let time = event_utc(event, tokyo, date).unwrap();
+            // Japan Standard Time, in minutes after midnight
+            let jst = time + chrono::Duration::hours(9);
+            jst.format("%H:%M").to_string()
+        };
+        let close_to = |actual: String, expected: &str| {
+            let minutes = |s: &str| {
+                let (h, m) = s.split_once(':').unwrap();
+                h.parse::<i32>().unwrap() * 60 + m.parse::<i32>().unwrap()
+            };
+            assert!(
+                (minutes(&actual) - minutes(expected)).abs() <= 2,
+                "{actual} is not close to {expected}"
+            );
+        };
+        // Published times for the solstice
+        close_to(at(SolarEvent::Dawn), "03:55");
+        close_to(at(SolarEvent::Sunrise), "04:25");
+        close_to(at(SolarEvent::SolarNoon), "11:43");
+        close_to(at(SolarEvent::Sunset), "19:00");
+        close_to(at(SolarEvent::Dusk), "19:30");
+    }
+
+    #[cfg(feature = "solar")]
+    #[test]
+    fn test_no_sunrise_in_polar_night() {
+        let tromso: Location = "69.65,18.96".parse().unwrap();
+        let midwinter = NaiveDate::from_ymd_opt(2025, 12, 21).unwrap();
+        assert_eq!(event_utc(SolarEvent::Sunrise, tromso, midwinter), None);
+        assert!(event_utc(SolarEvent::SolarNoon, tromso, midwinter).is_some());
+        let error = event_on(SolarEvent::Sunrise, tromso, midwinter).unwrap_err();
+        assert!(error.to_string().contains("no sunrise"), "{error}");
+    }
+
+    #[cfg(feature = "solar")]
+    #[test]
+    fn test_preset_windows() {
+        let tokyo: Location = "35.68,139.69".parse().unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 6, 21).unwrap();
+        let window = |preset: Preset, method| preset.window_on(tokyo, method, date).unwrap();
+
+        let daylight = window(Preset::Daylight, DawnMethod::default());
+        assert_eq!(daylight.label.as_deref(), Some("Daylight"));
+        let minutes = (daylight.end - daylight.start).num_minutes();
+        assert!(
+            (14 * 60 + 33..=14 * 60 + 37).contains(&minutes),
+            "{minutes}"
+        );
+
+        // A deeper dawn starts the fast earlier, and every fast ends at sunset
+        let mwl = window(Preset::Fast, DawnMethod::Mwl);
+        let isna = window(Preset::Fast, DawnMethod::Isna);
+        assert!(mwl.start < isna.start);
+        assert_eq!(mwl.end, daylight.end);
+        let civil = window(Preset::Twilight, DawnMethod::Mwl);
+        assert_eq!(window(Preset::Fast, DawnMethod::Civil).start, civil.start);
+
+        // Once a window ends, the next is the following day's
+        let next = Preset::Daylight
+            .next_window(tokyo, DawnMethod::default(), daylight.end)
+            .unwrap();
+        assert!(next.start > daylight.end);
+        assert_eq!((next.start - daylight.start).num_hours(), 24);
+        let current = Preset::Daylight
+            .next_window(tokyo, DawnMethod::default(), daylight.start)
+            .unwrap();
+        assert_eq!(current, daylight);
+    }
+
+    #[cfg(not(feature = "solar"))]
+    #[test]
+    fn test_solar_events_need_the_feature() {
+        let tokyo: Location = "35.68,139.69".parse().unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 6, 21).unwrap();
+        assert!(event_on(SolarEvent::Sunrise, tokyo, date).is_err());
+    }
+}
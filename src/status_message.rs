@@ -0,0 +1,269 @@
+//! Percent-threshold status messages for the pmon stats line
+//!
+//! Users can attach short status messages to ranges of progress, such as
+//! "on track" below 50% or "overtime by {overtime}" past 100%. Rules are given
+//! on the command line as `RANGE=TEXT` and the first rule whose range
+//! contains the current percentage supplies the message.
+//!
+//! # Range Syntax
+//!
+//! - `<50` - below 50%
+//! - `>100` - above 100%
+//! - `90-100` - from 90% to 100% inclusive
+//!
+//! A trailing `%` on any number is accepted (`90%-100%`).
+//!
+//! # Placeholders
+//!
+//! - `{percent}` - current percentage with one decimal place
+//! - `{elapsed}` - elapsed time
+//! - `{remaining}` - remaining time
+//! - `{overtime}` - time spent past the end
+
+use crate::error::PbError;
+use crate::progress_bar::{format_percentage, DurationFormat};
+use crate::progress_state::ProgressState;
+use std::str::FromStr;
+
+/// Option name used in error messages
+const OPTION_NAME: &str = "--status-message";
+
+/// Range of percentages a status rule applies to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PercentRange {
+    /// Strictly below the given percentage
+    Below(f64),
+    /// Strictly above the given percentage
+    Above(f64),
+    /// Between the two percentages, inclusive
+    Between(f64, f64),
+}
+
+impl PercentRange {
+    /// Whether `percentage` falls inside this range
+    pub fn contains(&self, percentage: f64) -> bool {
+        match *self {
+            Self::Below(limit) => percentage < limit,
+            Self::Above(limit) => percentage > limit,
+            Self::Between(low, high) => (low..=high).contains(&percentage),
+        }
+    }
+}
+
+/// A status message shown while progress is within a range
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusRule {
+    /// Percentages the rule applies to
+    pub range: PercentRange,
+    /// Message template, possibly containing placeholders
+    pub template: String,
+}
+
+impl StatusRule {
+    /// Fill the template placeholders from `state`
+    pub fn render(&self, state: &ProgressState, format: DurationFormat) -> String {
+        self.template
+            .replace("{percent}", &format_percentage(state.percentage))
+            .replace("{elapsed}", &format.elapsed(state.elapsed()))
+            .replace("{remaining}", &format.remaining(state.remaining()))
+            .replace("{overtime}", &format.elapsed(state.overtime()))
+    }
+}
+
+impl FromStr for StatusRule {
+    type Err = PbError;
+
+    /// Parse a `RANGE=TEXT` rule such as `90-100=wrap up`
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let invalid = || PbError::invalid_argument(OPTION_NAME, input);
+
+        let (range, template) = input.split_once('=').ok_or_else(invalid)?;
+        if template.trim().is_empty() {
+            return Err(invalid());
+        }
+
+        let range = range.trim();
+        let range = if let Some(limit) = range.strip_prefix('<') {
+            PercentRange::Below(parse_percent(limit).ok_or_else(invalid)?)
+        } else if let Some(limit) = range.strip_prefix('>') {
+            PercentRange::Above(parse_percent(limit).ok_or_else(invalid)?)
+        } else {
+            let (low, high) = range.split_once('-').ok_or_else(invalid)?;
+            let low = parse_percent(low).ok_or_else(invalid)?;
+            let high = parse_percent(high).ok_or_else(invalid)?;
+            if low > high {
+                return Err(invalid());
+            }
+            PercentRange::Between(low, high)
+        };
+
+        Ok(Self {
+            range,
+            template: template.trim().to_string(),
+        })
+    }
+}
+
+/// Parse a finite percentage, allowing an optional trailing `%`
+fn parse_percent(input: &str) -> Option<f64> {
+    let input = input.trim();
+    let number = input.strip_suffix('%').unwrap_or(input);
+    number.parse::<f64>().ok().filter(|value| value.is_finite())
+}
+
+/// Pick the first rule matching the current progress and render its message
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDateTime;
+/// use pmon::progress_bar::DurationFormat;
+/// use pmon::progress_state::ProgressState;
+/// use pmon::status_message::{select_status_message, StatusRule};
+///
+/// let rules: Vec<StatusRule> = ["<50=on track", "90-100=wrap up", ">100=overtime by {overtime}"]
+///     .iter()
+///     .map(|rule| rule.parse().unwrap())
+///     .collect();
+///
+/// let start = NaiveDateTime::parse_from_str("2025-01-27 09:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+/// let end = NaiveDateTime::parse_from_str("2025-01-27 10:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+/// let current = NaiveDateTime::parse_from_str("2025-01-27 10:12:00", "%Y-%m-%d %H:%M:%S").unwrap();
+///
+/// let state = ProgressState::new(start, end, current);
+/// let message = select_status_message(&rules, &state, DurationFormat::default());
+/// assert_eq!(message.as_deref(), Some("overtime by 12m"));
+/// ```
+pub fn select_status_message(
+    rules: &[StatusRule],
+    state: &ProgressState,
+    format: DurationFormat,
+) -> Option<String> {
+    rules
+        .iter()
+        .find(|rule| rule.range.contains(state.percentage))
+        .map(|rule| rule.render(state, format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn state_at(current: &str) -> ProgressState {
+        ProgressState::new(
+            create_test_datetime("2025-01-27 10:00:00"),
+            create_test_datetime("2025-01-27 12:00:00"),
+            create_test_datetime(current),
+        )
+    }
+
+    fn rules(specs: &[&str]) -> Vec<StatusRule> {
+        specs.iter().map(|spec| spec.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_parse_ranges() {
+        let rule: StatusRule = "<50=on track".parse().unwrap();
+        assert_eq!(rule.range, PercentRange::Below(50.0));
+        assert_eq!(rule.template, "on track");
+
+        let rule: StatusRule = ">100=overtime".parse().unwrap();
+        assert_eq!(rule.range, PercentRange::Above(100.0));
+
+        let rule: StatusRule = "90%-100%= wrap up ".parse().unwrap();
+        assert_eq!(rule.range, PercentRange::Between(90.0, 100.0));
+        assert_eq!(rule.template, "wrap up");
+    }
+
+    #[test]
+    fn test_parse_invalid_rules() {
+        let invalid = [
+            "",
+            "50",
+            "50=",
+            "=message",
+            "abc=message",
+            "<=message",
+            "100-90=reversed",
+            "10-NaN=not a number",
+            ">inf=infinite",
+        ];
+        for input in invalid {
+            let result = input.parse::<StatusRule>();
+            assert!(result.is_err(), "Expected '{input}' to be rejected");
+            assert!(matches!(result, Err(PbError::InvalidArgument { .. })));
+        }
+    }
+
+    #[test]
+    fn test_range_boundaries() {
+        assert!(PercentRange::Below(50.0).contains(49.9));
+        assert!(!PercentRange::Below(50.0).contains(50.0));
+        assert!(PercentRange::Above(100.0).contains(100.1));
+        assert!(!PercentRange::Above(100.0).contains(100.0));
+        assert!(PercentRange::Between(90.0, 100.0).contains(90.0));
+        assert!(PercentRange::Between(90.0, 100.0).contains(100.0));
+        assert!(!PercentRange::Between(90.0, 100.0).contains(89.9));
+    }
+
+    #[test]
+    fn test_first_matching_rule_wins() {
+        let rules = rules(&["<50=on track", "0-100=in progress", ">100=overtime"]);
+        let format = DurationFormat::default();
+
+        assert_eq!(
+            select_status_message(&rules, &state_at("2025-01-27 10:30:00"), format).as_deref(),
+            Some("on track")
+        );
+        assert_eq!(
+            select_status_message(&rules, &state_at("2025-01-27 11:30:00"), format).as_deref(),
+            Some("in progress")
+        );
+        assert_eq!(
+            select_status_message(&rules, &state_at("2025-01-27 12:30:00"), format).as_deref(),
+            Some("overtime")
+        );
+    }
+
+    #[test]
+    fn test_no_matching_rule() {
+        let rules = rules(&["90-100=wrap up"]);
+        let state = state_at("2025-01-27 11:00:00");
+        assert_eq!(
+            select_status_message(&rules, &state, DurationFormat::default()),
+            None
+        );
+        assert_eq!(
+            select_status_message(&[], &state, DurationFormat::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let summary_rules =
+            rules(&["0-200={percent} done, {elapsed} in, {remaining} left, {overtime} over"]);
+        let message = select_status_message(
+            &summary_rules,
+            &state_at("2025-01-27 11:30:00"),
+            DurationFormat::default(),
+        );
+        assert_eq!(
+            message.as_deref(),
+            Some("75.0% done, 1h 30m in, 30m left, 0m over")
+        );
+
+        let overtime_rules = rules(&[">100=overtime by {overtime}"]);
+        let message = select_status_message(
+            &overtime_rules,
+            &state_at("2025-01-27 13:12:00"),
+            DurationFormat::default(),
+        );
+        assert_eq!(message.as_deref(), Some("overtime by 1h 12m"));
+    }
+}
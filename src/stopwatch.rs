@@ -0,0 +1,166 @@
+//! Open-ended stopwatch mode for the pmon CLI tool
+//!
+//! `pmon stopwatch` counts up from the moment it starts with no end time.
+//! Because there is nothing to measure progress against, the bar is an
+//! animated indeterminate marquee and the stats show the elapsed time.
+//! Laps can be marked at any point and are reported with their split times.
+
+use crate::progress_bar::{format_duration_with, render_bar_state, BarState, DurationStyle};
+use chrono::{Duration, NaiveDateTime};
+
+/// A count-up timer with lap marks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stopwatch {
+    start: NaiveDateTime,
+    laps: Vec<NaiveDateTime>,
+}
+
+/// A lap marked on a [`Stopwatch`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lap {
+    /// Lap number, starting at 1
+    pub number: usize,
+    /// Time since the stopwatch started
+    pub total: Duration,
+    /// Time since the previous lap (or the start for the first lap)
+    pub split: Duration,
+}
+
+impl Stopwatch {
+    /// Start a stopwatch at `start`
+    pub fn new(start: NaiveDateTime) -> Self {
+        Self {
+            start,
+            laps: Vec::new(),
+        }
+    }
+
+    /// Moment the stopwatch started
+    pub fn start(&self) -> NaiveDateTime {
+        self.start
+    }
+
+    /// Time elapsed since the start
+    pub fn elapsed(&self, now: NaiveDateTime) -> Duration {
+        now - self.start
+    }
+
+    /// Number of laps marked so far
+    pub fn lap_count(&self) -> usize {
+        self.laps.len()
+    }
+
+    /// Mark a lap at `now` and return its times
+    pub fn lap(&mut self, now: NaiveDateTime) -> Lap {
+        let previous = self.laps.last().copied().unwrap_or(self.start);
+        self.laps.push(now);
+        Lap {
+            number: self.laps.len(),
+            total: now - self.start,
+            split: now - previous,
+        }
+    }
+}
+
+/// Format a lap as `Lap N  TOTAL  (+SPLIT)`
+///
+/// # Examples
+///
+/// ```
+/// use chrono::Duration;
+/// use pmon::progress_bar::DurationStyle;
+/// use pmon::stopwatch::{format_lap, Lap};
+///
+/// let lap = Lap { number: 2, total: Duration::seconds(95), split: Duration::seconds(40) };
+/// assert_eq!(format_lap(&lap, DurationStyle::Colon), "Lap 2  00:01:35  (+00:00:40)");
+/// ```
+pub fn format_lap(lap: &Lap, style: DurationStyle) -> String {
+    format!(
+        "Lap {}  {}  (+{})",
+        lap.number,
+        format_duration_with(lap.total, style),
+        format_duration_with(lap.split, style)
+    )
+}
+
+/// Render the stopwatch line: an indeterminate bar and the elapsed time
+///
+/// # Examples
+///
+/// ```
+/// use chrono::Duration;
+/// use pmon::progress_bar::DurationStyle;
+/// use pmon::stopwatch::render_stopwatch_line;
+///
+/// let line = render_stopwatch_line(0, Duration::seconds(75), DurationStyle::Colon);
+/// assert!(line.ends_with("] 00:01:15 elapsed"));
+/// ```
+pub fn render_stopwatch_line(frame: usize, elapsed: Duration, style: DurationStyle) -> String {
+    format!(
+        "{} {} elapsed",
+        render_bar_state(BarState::Indeterminate(frame)),
+        format_duration_with(elapsed, style)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_elapsed() {
+        let stopwatch = Stopwatch::new(create_test_datetime("2025-01-27 09:00:00"));
+        assert_eq!(
+            stopwatch.elapsed(create_test_datetime("2025-01-27 09:02:30")),
+            Duration::seconds(150)
+        );
+        assert_eq!(stopwatch.lap_count(), 0);
+    }
+
+    #[test]
+    fn test_laps_track_splits() {
+        let mut stopwatch = Stopwatch::new(create_test_datetime("2025-01-27 09:00:00"));
+
+        let first = stopwatch.lap(create_test_datetime("2025-01-27 09:01:00"));
+        assert_eq!(first.number, 1);
+        assert_eq!(first.total, Duration::minutes(1));
+        assert_eq!(first.split, Duration::minutes(1));
+
+        let second = stopwatch.lap(create_test_datetime("2025-01-27 09:03:30"));
+        assert_eq!(second.number, 2);
+        assert_eq!(second.total, Duration::seconds(210));
+        assert_eq!(second.split, Duration::seconds(150));
+        assert_eq!(stopwatch.lap_count(), 2);
+    }
+
+    #[test]
+    fn test_format_lap_styles() {
+        let lap = Lap {
+            number: 1,
+            total: Duration::minutes(90),
+            split: Duration::minutes(90),
+        };
+        assert_eq!(
+            format_lap(&lap, DurationStyle::Compact),
+            "Lap 1  1h 30m  (+1h 30m)"
+        );
+        assert_eq!(
+            format_lap(&lap, DurationStyle::Words),
+            "Lap 1  1 hour 30 minutes  (+1 hour 30 minutes)"
+        );
+    }
+
+    #[test]
+    fn test_render_stopwatch_line_animates() {
+        let style = DurationStyle::Colon;
+        let first = render_stopwatch_line(0, Duration::seconds(5), style);
+        let next = render_stopwatch_line(1, Duration::seconds(5), style);
+        assert_ne!(first, next);
+        assert!(first.contains("00:00:05 elapsed"));
+        assert!(!first.contains('%'));
+    }
+}
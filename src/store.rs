@@ -0,0 +1,1334 @@
+//! Persistence for named timers and run history
+//!
+//! Everything pmon remembers between invocations goes through the [`Store`]
+//! trait. The backend is chosen in the `[store]` table of the configuration
+//! file: a single JSON file (the default, with no extra dependencies) or an
+//! SQLite database when pmon is built with the `sqlite` feature, for users
+//! who prefer transactional writes and large histories.
+//!
+//! The JSON file is replaced atomically on every change and records the
+//! version of its layout; files written by older releases are upgraded when
+//! they are opened (see the [`migrate`](crate::migrate) module). A file
+//! damaged anyway, for example truncated by an older release or another
+//! program, is refused on open; `pmon repair-state` recovers what it can from
+//! it and keeps the damaged file aside.
+
+use crate::atomic::{temp_path, write_atomic};
+use crate::broadcast::process_is_alive;
+use crate::config::{StoreBackend, StoreConfig};
+use crate::definition::TimerDefinition;
+#[cfg(feature = "encryption")]
+use crate::encryption::{EncryptedStore, Sealer};
+use crate::error::{PbError, PbResult};
+use crate::migrate::{migrate, Migration};
+use crate::notes::Note;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A named timer's window, saved when it starts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimerRecord {
+    /// Name given with `--name`
+    pub name: String,
+    /// Label shown before the bar
+    pub label: Option<String>,
+    /// Start of the window
+    pub start: NaiveDateTime,
+    /// End of the window
+    pub end: NaiveDateTime,
+    /// Process running the timer; once it has exited the timer no longer
+    /// holds its name, even before the end of its window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+}
+
+/// How a run ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunOutcome {
+    /// The window elapsed
+    Completed,
+    /// The user stopped the timer early
+    Interrupted,
+}
+
+impl RunOutcome {
+    /// Lowercase name used when storing and listing runs
+    pub fn name(self) -> &'static str {
+        match self {
+            RunOutcome::Completed => "completed",
+            RunOutcome::Interrupted => "interrupted",
+        }
+    }
+
+    /// Parse a name produced by [`RunOutcome::name`]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "completed" => Some(RunOutcome::Completed),
+            "interrupted" => Some(RunOutcome::Interrupted),
+            _ => None,
+        }
+    }
+}
+
+/// One finished run, appended to the history
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunRecord {
+    /// Timer name, if the run was named
+    pub name: Option<String>,
+    /// Label shown before the bar
+    pub label: Option<String>,
+    /// Start of the window
+    pub start: NaiveDateTime,
+    /// End of the window
+    pub end: NaiveDateTime,
+    /// Moment the run ended
+    pub finished_at: NaiveDateTime,
+    /// How the run ended
+    pub outcome: RunOutcome,
+    /// Tags the timer was started with
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Notes taken during the run
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<Note>,
+}
+
+/// A webhook delivery that failed and waits to be sent again
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingDelivery {
+    /// URL the event is posted to
+    pub url: String,
+    /// JSON document to post
+    pub body: String,
+    /// Where the bearer token is kept, such as `keyring:relay`; never the
+    /// token itself
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Moment the first attempt failed
+    pub queued_at: NaiveDateTime,
+    /// Attempts made so far
+    pub attempts: u32,
+}
+
+/// What to do when a named timer starts while one with the same name has
+/// not ended yet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum OnExists {
+    /// Refuse to start
+    #[default]
+    Abort,
+    /// Start over, forgetting the saved window
+    Replace,
+    /// Continue the saved timer: keep its start and label, and end at the
+    /// later of the two ends
+    Merge,
+}
+
+/// Saved timers and run history
+pub trait Store {
+    /// Backend holding the data
+    fn backend(&self) -> StoreBackend;
+
+    /// Save a timer, replacing any timer with the same name
+    fn save_timer(&mut self, timer: &TimerRecord) -> PbResult<()>;
+
+    /// Look up a timer by name
+    fn timer(&self, name: &str) -> PbResult<Option<TimerRecord>>;
+
+    /// All saved timers, ordered by name
+    fn timers(&self) -> PbResult<Vec<TimerRecord>>;
+
+    /// Remove a timer, returning whether it existed
+    fn remove_timer(&mut self, name: &str) -> PbResult<bool>;
+
+    /// Append a finished run to the history
+    fn record_run(&mut self, run: &RunRecord) -> PbResult<()>;
+
+    /// All recorded runs, oldest first
+    fn history(&self) -> PbResult<Vec<RunRecord>>;
+
+    /// Save a timer definition, replacing any with the same name
+    fn save_definition(&mut self, definition: &TimerDefinition) -> PbResult<()>;
+
+    /// Look up a timer definition by name
+    fn definition(&self, name: &str) -> PbResult<Option<TimerDefinition>>;
+
+    /// Deliveries waiting to be sent again, oldest first
+    fn pending_deliveries(&self) -> PbResult<Vec<PendingDelivery>>;
+
+    /// Replace the deliveries waiting to be sent again
+    fn set_pending_deliveries(&mut self, pending: &[PendingDelivery]) -> PbResult<()>;
+}
+
+/// Open the store selected by `config`
+pub fn open_store(config: &StoreConfig) -> PbResult<Box<dyn Store>> {
+    let store = open_backend(config)?;
+    if !config.encrypt {
+        return Ok(store);
+    }
+    encrypt_store(store)
+}
+
+/// Seal the labels kept in `store` with the configured key
+#[cfg(feature = "encryption")]
+fn encrypt_store(store: Box<dyn Store>) -> PbResult<Box<dyn Store>> {
+    Ok(Box::new(EncryptedStore::new(store, Sealer::load()?)))
+}
+
+/// Encryption was requested from a build without it
+#[cfg(not(feature = "encryption"))]
+fn encrypt_store(_store: Box<dyn Store>) -> PbResult<Box<dyn Store>> {
+    Err(PbError::storage(
+        "encryption",
+        "this build of pmon does not include the `encryption` feature",
+    ))
+}
+
+/// Open the backend selected by `config`, without encryption
+fn open_backend(config: &StoreConfig) -> PbResult<Box<dyn Store>> {
+    let path = config.resolved_path();
+    match config.backend {
+        StoreBackend::Json => Ok(Box::new(JsonStore::open(path)?)),
+        #[cfg(feature = "sqlite")]
+        StoreBackend::Sqlite => Ok(Box::new(SqliteStore::open(&path)?)),
+        #[cfg(not(feature = "sqlite"))]
+        StoreBackend::Sqlite => Err(PbError::storage(
+            StoreBackend::Sqlite.name(),
+            "this build of pmon does not include the `sqlite` feature",
+        )),
+    }
+}
+
+/// Save `timer` unless a timer with the same name is still running at `now`
+///
+/// A saved timer is running until the end of its window, as long as the
+/// process that started it has not exited. `on_exists` decides what happens
+/// when one is: the start is refused with [`PbError::TimerExists`], the saved
+/// window is replaced, or the two are merged. Returns the timer as saved.
+pub fn claim_timer(
+    store: &mut dyn Store,
+    timer: &TimerRecord,
+    now: NaiveDateTime,
+    on_exists: OnExists,
+) -> PbResult<TimerRecord> {
+    let claimed = match store.timer(&timer.name)? {
+        Some(saved) if saved.end > now && saved.pid.is_none_or(process_is_alive) => match on_exists
+        {
+            OnExists::Abort => {
+                return Err(PbError::timer_exists(
+                    &saved.name,
+                    saved.end.format("%Y-%m-%d %H:%M").to_string(),
+                ))
+            }
+            OnExists::Replace => timer.clone(),
+            OnExists::Merge => TimerRecord {
+                name: timer.name.clone(),
+                label: saved.label.or(timer.label.clone()),
+                start: saved.start.min(timer.start),
+                end: saved.end.max(timer.end),
+                pid: timer.pid,
+            },
+        },
+        _ => timer.clone(),
+    };
+    store.save_timer(&claimed)?;
+    Ok(claimed)
+}
+
+/// Version of the JSON store layout written by this release
+pub const STORE_SCHEMA_VERSION: u32 = 1;
+
+/// Contents of the JSON store file
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+struct JsonData {
+    /// Version of the layout, upgraded by [`JSON_MIGRATIONS`]
+    schema_version: u32,
+    timers: Vec<TimerRecord>,
+    history: Vec<RunRecord>,
+    definitions: Vec<TimerDefinition>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pending_deliveries: Vec<PendingDelivery>,
+}
+
+impl Default for JsonData {
+    fn default() -> Self {
+        Self {
+            schema_version: STORE_SCHEMA_VERSION,
+            timers: Vec::new(),
+            history: Vec::new(),
+            definitions: Vec::new(),
+            pending_deliveries: Vec::new(),
+        }
+    }
+}
+
+/// Steps upgrading JSON store files written by earlier releases
+///
+/// Files written before the version was recorded have the first layout.
+const JSON_MIGRATIONS: &[Migration<serde_json::Value>] = &[];
+
+impl JsonData {
+    /// Parse a store file, upgrading older layouts and refusing layouts
+    /// newer than this release's
+    fn parse(path: &Path, contents: &str) -> PbResult<Self> {
+        let mut value: serde_json::Value = serde_json::from_str(contents)
+            .map_err(|e| json_error(path, format!("{e}; run `pmon repair-state` to recover it")))?;
+        upgrade_json(&mut value, STORE_SCHEMA_VERSION, JSON_MIGRATIONS)
+            .map_err(|e| json_error(path, e))?;
+        serde_json::from_value(value)
+            .map_err(|e| json_error(path, format!("{e}; run `pmon repair-state` to recover it")))
+    }
+}
+
+/// Upgrade a parsed JSON store to `current` with `migrations`
+fn upgrade_json(
+    value: &mut serde_json::Value,
+    current: u32,
+    migrations: &[Migration<serde_json::Value>],
+) -> Result<(), String> {
+    let version = match value.get("schema_version") {
+        None => 1,
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| format!("invalid schema version {version}"))?,
+    };
+    migrate(value, version, current, migrations)?;
+    if let Some(object) = value.as_object_mut() {
+        object.insert("schema_version".to_string(), current.into());
+    }
+    Ok(())
+}
+
+/// Store kept in a single JSON file, rewritten on every change
+#[derive(Debug)]
+pub struct JsonStore {
+    path: PathBuf,
+    data: JsonData,
+}
+
+impl JsonStore {
+    /// Open the store at `path`; a missing file is an empty store
+    pub fn open(path: PathBuf) -> PbResult<Self> {
+        let data = match fs::read_to_string(&path) {
+            Ok(contents) => JsonData::parse(&path, &contents)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => JsonData::default(),
+            Err(e) => return Err(json_error(&path, e)),
+        };
+        Ok(Self { path, data })
+    }
+
+    /// Read the file again, so a change does not drop what other store
+    /// handles saved since this one was opened
+    fn reload(&mut self) -> PbResult<()> {
+        *self = Self::open(self.path.clone())?;
+        Ok(())
+    }
+
+    /// Write the whole store back to its file
+    fn save(&self) -> PbResult<()> {
+        save_json(&self.path, &self.data)
+    }
+}
+
+/// Replace the JSON store file at `path` with `data`
+fn save_json(path: &Path, data: &JsonData) -> PbResult<()> {
+    let contents = serde_json::to_string_pretty(data).map_err(|e| json_error(path, e))?;
+    write_atomic(path, (contents + "\n").as_bytes()).map_err(|e| json_error(path, e))
+}
+
+/// What [`repair_json_store`] found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Repair {
+    /// There is no store file yet
+    Missing,
+    /// The file was readable and was left as it was
+    Healthy,
+    /// The file was damaged: what could be read from it was saved in its
+    /// place, and the damaged file was kept at `backup`
+    Recovered {
+        timers: usize,
+        runs: usize,
+        definitions: usize,
+        backup: PathBuf,
+    },
+}
+
+/// Check the JSON store file at `path`, recovering a damaged one
+///
+/// Temporary files left beside it by interrupted writes are removed. A file
+/// that cannot be read whole is cut back to the last complete record, so
+/// the timers, runs, and definitions written before the damage survive.
+pub fn repair_json_store(path: &Path) -> PbResult<Repair> {
+    remove_stale_temp_files(path).map_err(|e| json_error(path, e))?;
+    let contents = match fs::read(path) {
+        Ok(contents) => String::from_utf8_lossy(&contents).into_owned(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Repair::Missing),
+        Err(e) => return Err(json_error(path, e)),
+    };
+    match JsonData::parse(path, &contents) {
+        Ok(_) => return Ok(Repair::Healthy),
+        // A newer release's file is not damage to cut away
+        Err(e) if newer_schema(&contents) => return Err(e),
+        Err(_) => {}
+    }
+    let data = recover_truncated(&contents).unwrap_or_default();
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let backup = path.with_file_name(format!("{name}.corrupt"));
+    fs::rename(path, &backup).map_err(|e| json_error(path, e))?;
+    save_json(path, &data)?;
+    Ok(Repair::Recovered {
+        timers: data.timers.len(),
+        runs: data.history.len(),
+        definitions: data.definitions.len(),
+        backup,
+    })
+}
+
+/// Whether readable JSON claims a layout newer than this release's
+fn newer_schema(contents: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(contents)
+        .ok()
+        .and_then(|value| value.get("schema_version")?.as_u64())
+        .is_some_and(|version| version > u64::from(STORE_SCHEMA_VERSION))
+}
+
+/// Remove temporary files that writes to `path` left behind
+fn remove_stale_temp_files(path: &Path) -> io::Result<()> {
+    let Some(dir) = path.parent().filter(|dir| dir.is_dir()) else {
+        return Ok(());
+    };
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let ours = temp_path(path);
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file = entry.file_name().to_string_lossy().into_owned();
+        if file.starts_with(&format!(".{name}.")) && file.ends_with(".tmp") && entry.path() != ours
+        {
+            fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+/// The store saved in the longest prefix of `contents` that reads as one
+///
+/// A truncated file is cut back after its last complete value and the
+/// objects and arrays still open are closed again.
+fn recover_truncated(contents: &str) -> Option<JsonData> {
+    let mut open = Vec::new();
+    let mut cuts = Vec::new();
+    let (mut in_string, mut escaped) = (false, false);
+    for (index, c) in contents.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => open.push('}'),
+            '[' => open.push(']'),
+            '}' | ']' => {
+                open.pop();
+                cuts.push((index + 1, open.clone()));
+            }
+            ',' => cuts.push((index, open.clone())),
+            _ => {}
+        }
+    }
+    cuts.into_iter().rev().find_map(|(end, open)| {
+        let closed: String = contents[..end]
+            .chars()
+            .chain(open.into_iter().rev())
+            .collect();
+        let mut value = serde_json::from_str(&closed).ok()?;
+        upgrade_json(&mut value, STORE_SCHEMA_VERSION, JSON_MIGRATIONS).ok()?;
+        serde_json::from_value(value).ok()
+    })
+}
+
+/// Storage error for the JSON file at `path`
+fn json_error(path: &Path, error: impl std::fmt::Display) -> PbError {
+    PbError::storage(
+        StoreBackend::Json.name(),
+        format!("{}: {error}", path.display()),
+    )
+}
+
+impl Store for JsonStore {
+    fn backend(&self) -> StoreBackend {
+        StoreBackend::Json
+    }
+
+    fn save_timer(&mut self, timer: &TimerRecord) -> PbResult<()> {
+        self.reload()?;
+        self.data.timers.retain(|saved| saved.name != timer.name);
+        self.data.timers.push(timer.clone());
+        self.data.timers.sort_by(|a, b| a.name.cmp(&b.name));
+        self.save()
+    }
+
+    fn timer(&self, name: &str) -> PbResult<Option<TimerRecord>> {
+        Ok(self.data.timers.iter().find(|t| t.name == name).cloned())
+    }
+
+    fn timers(&self) -> PbResult<Vec<TimerRecord>> {
+        Ok(self.data.timers.clone())
+    }
+
+    fn remove_timer(&mut self, name: &str) -> PbResult<bool> {
+        self.reload()?;
+        let before = self.data.timers.len();
+        self.data.timers.retain(|saved| saved.name != name);
+        if self.data.timers.len() == before {
+            return Ok(false);
+        }
+        self.save().map(|()| true)
+    }
+
+    fn record_run(&mut self, run: &RunRecord) -> PbResult<()> {
+        self.reload()?;
+        self.data.history.push(run.clone());
+        self.save()
+    }
+
+    fn history(&self) -> PbResult<Vec<RunRecord>> {
+        Ok(self.data.history.clone())
+    }
+
+    fn save_definition(&mut self, definition: &TimerDefinition) -> PbResult<()> {
+        self.reload()?;
+        self.data
+            .definitions
+            .retain(|saved| saved.name != definition.name);
+        self.data.definitions.push(definition.clone());
+        self.data.definitions.sort_by(|a, b| a.name.cmp(&b.name));
+        self.save()
+    }
+
+    fn definition(&self, name: &str) -> PbResult<Option<TimerDefinition>> {
+        Ok(self
+            .data
+            .definitions
+            .iter()
+            .find(|d| d.name == name)
+            .cloned())
+    }
+
+    fn pending_deliveries(&self) -> PbResult<Vec<PendingDelivery>> {
+        Ok(self.data.pending_deliveries.clone())
+    }
+
+    fn set_pending_deliveries(&mut self, pending: &[PendingDelivery]) -> PbResult<()> {
+        self.reload()?;
+        self.data.pending_deliveries = pending.to_vec();
+        self.save()
+    }
+}
+
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStore;
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use super::*;
+    use rusqlite::{params, Connection, OptionalExtension, Row};
+
+    /// Format of times stored in SQLite text columns
+    const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f";
+
+    /// Tables created in a new database
+    const SCHEMA: &str = "
+        CREATE TABLE IF NOT EXISTS timers (
+            name TEXT PRIMARY KEY,
+            label TEXT,
+            start TEXT NOT NULL,
+            end TEXT NOT NULL,
+            pid INTEGER
+        );
+        CREATE TABLE IF NOT EXISTS history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT,
+            label TEXT,
+            start TEXT NOT NULL,
+            end TEXT NOT NULL,
+            finished_at TEXT NOT NULL,
+            outcome TEXT NOT NULL,
+            tags TEXT NOT NULL DEFAULT '',
+            notes TEXT NOT NULL DEFAULT '[]'
+        );
+        CREATE TABLE IF NOT EXISTS definitions (
+            name TEXT PRIMARY KEY,
+            definition TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS pending_deliveries (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            url TEXT NOT NULL,
+            body TEXT NOT NULL,
+            token TEXT,
+            queued_at TEXT NOT NULL,
+            attempts INTEGER NOT NULL
+        );";
+
+    /// Store kept in an SQLite database
+    #[derive(Debug)]
+    pub struct SqliteStore {
+        connection: Connection,
+    }
+
+    impl SqliteStore {
+        /// Open or create the database at `path`
+        pub fn open(path: &Path) -> PbResult<Self> {
+            create_parent_dir(path).map_err(sqlite_error)?;
+            let mut connection = Connection::open(path).map_err(sqlite_error)?;
+            let version: u32 = connection
+                .query_row("PRAGMA user_version", [], |row| row.get(0))
+                .map_err(sqlite_error)?;
+            // Databases created before the version was recorded have tables
+            // and are upgraded from 0; new ones start at the current version
+            let is_new = connection
+                .prepare("SELECT name FROM sqlite_master WHERE name = 'history'")
+                .and_then(|mut statement| statement.exists([]))
+                .map(|exists| !exists)
+                .map_err(sqlite_error)?;
+            connection.execute_batch(SCHEMA).map_err(sqlite_error)?;
+            if !is_new {
+                migrate(
+                    &mut connection,
+                    version,
+                    SQLITE_SCHEMA_VERSION,
+                    SQLITE_MIGRATIONS,
+                )
+                .map_err(sqlite_error)?;
+            }
+            connection
+                .pragma_update(None, "user_version", SQLITE_SCHEMA_VERSION)
+                .map_err(sqlite_error)?;
+            Ok(Self { connection })
+        }
+    }
+
+    /// Version of the database layout written by this release
+    const SQLITE_SCHEMA_VERSION: u32 = 3;
+
+    /// Steps upgrading databases created by earlier releases
+    const SQLITE_MIGRATIONS: &[Migration<Connection>] = &[
+        Migration {
+            from: 0,
+            description: "keep the tags of each run",
+            apply: add_history_tags,
+        },
+        Migration {
+            from: 1,
+            description: "remember the process running each timer",
+            apply: add_timer_pid,
+        },
+        Migration {
+            from: 2,
+            description: "keep the notes of each run",
+            apply: add_history_notes,
+        },
+    ];
+
+    fn add_history_tags(connection: &mut Connection) -> Result<(), String> {
+        // Some unversioned databases already have the column
+        if connection.prepare("SELECT tags FROM history").is_ok() {
+            return Ok(());
+        }
+        connection
+            .execute_batch("ALTER TABLE history ADD COLUMN tags TEXT NOT NULL DEFAULT ''")
+            .map_err(|e| e.to_string())
+    }
+
+    fn add_timer_pid(connection: &mut Connection) -> Result<(), String> {
+        // A timers table created along with the upgrade already has it
+        if connection.prepare("SELECT pid FROM timers").is_ok() {
+            return Ok(());
+        }
+        connection
+            .execute_batch("ALTER TABLE timers ADD COLUMN pid INTEGER")
+            .map_err(|e| e.to_string())
+    }
+
+    fn add_history_notes(connection: &mut Connection) -> Result<(), String> {
+        // A history table created along with the upgrade already has it
+        if connection.prepare("SELECT notes FROM history").is_ok() {
+            return Ok(());
+        }
+        connection
+            .execute_batch("ALTER TABLE history ADD COLUMN notes TEXT NOT NULL DEFAULT '[]'")
+            .map_err(|e| e.to_string())
+    }
+
+    /// Create the directory that will hold `path`
+    fn create_parent_dir(path: &Path) -> std::io::Result<()> {
+        match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => fs::create_dir_all(dir),
+            _ => Ok(()),
+        }
+    }
+
+    /// Storage error for the SQLite backend
+    fn sqlite_error(error: impl std::fmt::Display) -> PbError {
+        PbError::storage(StoreBackend::Sqlite.name(), error.to_string())
+    }
+
+    fn format_time(time: NaiveDateTime) -> String {
+        time.format(TIME_FORMAT).to_string()
+    }
+
+    /// Read a time column written by [`format_time`]
+    fn time_column(row: &Row, index: usize) -> rusqlite::Result<NaiveDateTime> {
+        let text: String = row.get(index)?;
+        NaiveDateTime::parse_from_str(&text, TIME_FORMAT).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(
+                index,
+                rusqlite::types::Type::Text,
+                Box::new(e),
+            )
+        })
+    }
+
+    fn timer_from_row(row: &Row) -> rusqlite::Result<TimerRecord> {
+        Ok(TimerRecord {
+            name: row.get(0)?,
+            label: row.get(1)?,
+            start: time_column(row, 2)?,
+            end: time_column(row, 3)?,
+            pid: row.get(4)?,
+        })
+    }
+
+    fn run_from_row(row: &Row) -> rusqlite::Result<RunRecord> {
+        let outcome: String = row.get(5)?;
+        Ok(RunRecord {
+            name: row.get(0)?,
+            label: row.get(1)?,
+            start: time_column(row, 2)?,
+            end: time_column(row, 3)?,
+            finished_at: time_column(row, 4)?,
+            outcome: RunOutcome::from_name(&outcome).ok_or_else(|| {
+                rusqlite::Error::InvalidColumnType(
+                    5,
+                    "outcome".to_string(),
+                    rusqlite::types::Type::Text,
+                )
+            })?,
+            tags: row
+                .get::<_, String>(6)?
+                .split_whitespace()
+                .map(str::to_string)
+                .collect(),
+            notes: serde_json::from_str(&row.get::<_, String>(7)?).map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    7,
+                    rusqlite::types::Type::Text,
+                    Box::new(e),
+                )
+            })?,
+        })
+    }
+
+    impl Store for SqliteStore {
+        fn backend(&self) -> StoreBackend {
+            StoreBackend::Sqlite
+        }
+
+        fn save_timer(&mut self, timer: &TimerRecord) -> PbResult<()> {
+            self.connection
+                .execute(
+                    "INSERT OR REPLACE INTO timers (name, label, start, end, pid)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![
+                        timer.name,
+                        timer.label,
+                        format_time(timer.start),
+                        format_time(timer.end),
+                        timer.pid
+                    ],
+                )
+                .map(|_| ())
+                .map_err(sqlite_error)
+        }
+
+        fn timer(&self, name: &str) -> PbResult<Option<TimerRecord>> {
+            self.connection
+                .query_row(
+                    "SELECT name, label, start, end, pid FROM timers WHERE name = ?1",
+                    params![name],
+                    timer_from_row,
+                )
+                .optional()
+                .map_err(sqlite_error)
+        }
+
+        fn timers(&self) -> PbResult<Vec<TimerRecord>> {
+            let mut statement = self
+                .connection
+                .prepare("SELECT name, label, start, end, pid FROM timers ORDER BY name")
+                .map_err(sqlite_error)?;
+            let rows = statement
+                .query_map([], timer_from_row)
+                .map_err(sqlite_error)?;
+            rows.collect::<rusqlite::Result<_>>().map_err(sqlite_error)
+        }
+
+        fn remove_timer(&mut self, name: &str) -> PbResult<bool> {
+            self.connection
+                .execute("DELETE FROM timers WHERE name = ?1", params![name])
+                .map(|removed| removed > 0)
+                .map_err(sqlite_error)
+        }
+
+        fn record_run(&mut self, run: &RunRecord) -> PbResult<()> {
+            self.connection
+                .execute(
+                    "INSERT INTO history (name, label, start, end, finished_at, outcome, tags, notes)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    params![
+                        run.name,
+                        run.label,
+                        format_time(run.start),
+                        format_time(run.end),
+                        format_time(run.finished_at),
+                        run.outcome.name(),
+                        // Tags never contain spaces
+                        run.tags.join(" "),
+                        serde_json::to_string(&run.notes).map_err(sqlite_error)?
+                    ],
+                )
+                .map(|_| ())
+                .map_err(sqlite_error)
+        }
+
+        fn history(&self) -> PbResult<Vec<RunRecord>> {
+            let mut statement = self
+                .connection
+                .prepare(
+                    "SELECT name, label, start, end, finished_at, outcome, tags, notes
+                     FROM history ORDER BY id",
+                )
+                .map_err(sqlite_error)?;
+            let rows = statement
+                .query_map([], run_from_row)
+                .map_err(sqlite_error)?;
+            rows.collect::<rusqlite::Result<_>>().map_err(sqlite_error)
+        }
+
+        fn save_definition(&mut self, definition: &TimerDefinition) -> PbResult<()> {
+            // Definitions are kept as JSON so new options need no schema change
+            let json = serde_json::to_string(definition).map_err(sqlite_error)?;
+            self.connection
+                .execute(
+                    "INSERT OR REPLACE INTO definitions (name, definition) VALUES (?1, ?2)",
+                    params![definition.name, json],
+                )
+                .map(|_| ())
+                .map_err(sqlite_error)
+        }
+
+        fn definition(&self, name: &str) -> PbResult<Option<TimerDefinition>> {
+            let json: Option<String> = self
+                .connection
+                .query_row(
+                    "SELECT definition FROM definitions WHERE name = ?1",
+                    params![name],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(sqlite_error)?;
+            json.map(|json| serde_json::from_str(&json).map_err(sqlite_error))
+                .transpose()
+        }
+
+        fn pending_deliveries(&self) -> PbResult<Vec<PendingDelivery>> {
+            let mut statement = self
+                .connection
+                .prepare(
+                    "SELECT url, body, token, queued_at, attempts
+                     FROM pending_deliveries ORDER BY id",
+                )
+                .map_err(sqlite_error)?;
+            let rows = statement
+                .query_map([], |row| {
+                    Ok(PendingDelivery {
+                        url: row.get(0)?,
+                        body: row.get(1)?,
+                        token: row.get(2)?,
+                        queued_at: time_column(row, 3)?,
+                        attempts: row.get(4)?,
+                    })
+                })
+                .map_err(sqlite_error)?;
+            rows.collect::<rusqlite::Result<_>>().map_err(sqlite_error)
+        }
+
+        fn set_pending_deliveries(&mut self, pending: &[PendingDelivery]) -> PbResult<()> {
+            let transaction = self.connection.transaction().map_err(sqlite_error)?;
+            transaction
+                .execute("DELETE FROM pending_deliveries", [])
+                .map_err(sqlite_error)?;
+            for delivery in pending {
+                transaction
+                    .execute(
+                        "INSERT INTO pending_deliveries (url, body, token, queued_at, attempts)
+                         VALUES (?1, ?2, ?3, ?4, ?5)",
+                        params![
+                            delivery.url,
+                            delivery.body,
+                            delivery.token,
+                            format_time(delivery.queued_at),
+                            delivery.attempts
+                        ],
+                    )
+                    .map_err(sqlite_error)?;
+            }
+            transaction.commit().map_err(sqlite_error)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn timer(name: &str, end: &str) -> TimerRecord {
+        TimerRecord {
+            name: name.to_string(),
+            label: Some(format!("{name} label")),
+            start: create_test_datetime("2025-01-27 09:00:00"),
+            end: create_test_datetime(end),
+            pid: None,
+        }
+    }
+
+    fn run(outcome: RunOutcome) -> RunRecord {
+        RunRecord {
+            name: Some("standup".to_string()),
+            label: None,
+            start: create_test_datetime("2025-01-27 09:00:00"),
+            end: create_test_datetime("2025-01-27 09:15:00"),
+            finished_at: create_test_datetime("2025-01-27 09:12:30"),
+            outcome,
+            tags: vec!["team:payments".to_string(), "sev2".to_string()],
+            notes: vec![Note {
+                at: create_test_datetime("2025-01-27 09:05:00"),
+                text: "Blocked on review".to_string(),
+            }],
+        }
+    }
+
+    /// Behavior every backend must share
+    fn exercise_store(store: &mut dyn Store) {
+        assert!(store.timers().unwrap().is_empty());
+        assert!(store.timer("standup").unwrap().is_none());
+
+        store
+            .save_timer(&timer("standup", "2025-01-27 09:15:00"))
+            .unwrap();
+        store
+            .save_timer(&timer("deploy", "2025-01-27 17:00:00"))
+            .unwrap();
+        store
+            .save_timer(&timer("standup", "2025-01-27 09:30:00"))
+            .unwrap();
+
+        let names: Vec<_> = store
+            .timers()
+            .unwrap()
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(names, ["deploy", "standup"]);
+        assert_eq!(
+            store.timer("standup").unwrap(),
+            Some(timer("standup", "2025-01-27 09:30:00"))
+        );
+
+        assert!(store.remove_timer("deploy").unwrap());
+        assert!(!store.remove_timer("deploy").unwrap());
+
+        store.record_run(&run(RunOutcome::Completed)).unwrap();
+        store.record_run(&run(RunOutcome::Interrupted)).unwrap();
+        assert_eq!(
+            store.history().unwrap(),
+            [run(RunOutcome::Completed), run(RunOutcome::Interrupted)]
+        );
+
+        let mut definition = TimerDefinition {
+            name: "standup".to_string(),
+            end: "+15m".to_string(),
+            ..TimerDefinition::default()
+        };
+        assert!(store.definition("standup").unwrap().is_none());
+        store.save_definition(&definition).unwrap();
+        definition.end = "+20m".to_string();
+        store.save_definition(&definition).unwrap();
+        assert_eq!(store.definition("standup").unwrap(), Some(definition));
+
+        let delivery = PendingDelivery {
+            url: "http://relay.local:80/pmon".to_string(),
+            body: "{\"event\":\"complete\"}".to_string(),
+            token: Some("keyring:relay".to_string()),
+            queued_at: create_test_datetime("2025-01-27 09:15:00"),
+            attempts: 2,
+        };
+        assert!(store.pending_deliveries().unwrap().is_empty());
+        store
+            .set_pending_deliveries(&[delivery.clone(), delivery.clone()])
+            .unwrap();
+        store
+            .set_pending_deliveries(std::slice::from_ref(&delivery))
+            .unwrap();
+        assert_eq!(store.pending_deliveries().unwrap(), [delivery]);
+    }
+
+    #[test]
+    fn test_json_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("store.json");
+        let mut store = JsonStore::open(path.clone()).unwrap();
+        assert_eq!(store.backend(), StoreBackend::Json);
+        exercise_store(&mut store);
+
+        // Changes survive reopening the file
+        let reopened = JsonStore::open(path).unwrap();
+        assert_eq!(reopened.timers().unwrap().len(), 1);
+        assert_eq!(reopened.history().unwrap().len(), 2);
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_store_adds_tags_to_old_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.sqlite3");
+        rusqlite::Connection::open(&path)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE history (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    name TEXT,
+                    label TEXT,
+                    start TEXT NOT NULL,
+                    end TEXT NOT NULL,
+                    finished_at TEXT NOT NULL,
+                    outcome TEXT NOT NULL
+                );
+                INSERT INTO history (name, label, start, end, finished_at, outcome)
+                VALUES ('standup', NULL, '2025-01-27 09:00:00', '2025-01-27 09:15:00',
+                        '2025-01-27 09:12:30', 'completed');",
+            )
+            .unwrap();
+
+        let mut store = SqliteStore::open(&path).unwrap();
+        assert!(store.history().unwrap()[0].tags.is_empty());
+        let old = RunRecord {
+            tags: Vec::new(),
+            notes: Vec::new(),
+            ..run(RunOutcome::Completed)
+        };
+        store.record_run(&run(RunOutcome::Completed)).unwrap();
+        assert_eq!(store.history().unwrap(), [old, run(RunOutcome::Completed)]);
+
+        let version: u32 = rusqlite::Connection::open(&path)
+            .unwrap()
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, 3);
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_store_adds_pid_to_old_timers() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.sqlite3");
+        let old = SqliteStore::open(&path).unwrap();
+        drop(old);
+        let connection = rusqlite::Connection::open(&path).unwrap();
+        connection
+            .execute_batch(
+                "DROP TABLE timers;
+                CREATE TABLE timers (
+                    name TEXT PRIMARY KEY,
+                    label TEXT,
+                    start TEXT NOT NULL,
+                    end TEXT NOT NULL
+                );
+                INSERT INTO timers (name, label, start, end)
+                VALUES ('standup', NULL, '2025-01-27 09:00:00', '2025-01-27 09:15:00');
+                PRAGMA user_version = 1;",
+            )
+            .unwrap();
+        drop(connection);
+
+        let mut store = SqliteStore::open(&path).unwrap();
+        assert_eq!(store.timer("standup").unwrap().unwrap().pid, None);
+        let running = TimerRecord {
+            pid: Some(42),
+            ..timer("review", "2025-01-27 10:00:00")
+        };
+        store.save_timer(&running).unwrap();
+        assert_eq!(store.timer("review").unwrap(), Some(running));
+    }
+
+    #[test]
+    fn test_json_store_keeps_changes_from_other_handles() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+        let mut first = JsonStore::open(path.clone()).unwrap();
+        let mut second = JsonStore::open(path.clone()).unwrap();
+        first
+            .save_timer(&timer("standup", "2025-01-27 09:15:00"))
+            .unwrap();
+        second.record_run(&run(RunOutcome::Completed)).unwrap();
+
+        let store = JsonStore::open(path).unwrap();
+        assert_eq!(store.timers().unwrap().len(), 1);
+        assert_eq!(store.history().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_claim_timer() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = JsonStore::open(dir.path().join("store.json")).unwrap();
+        let saved = timer("standup", "2025-01-27 09:15:00");
+        store.save_timer(&saved).unwrap();
+        let again = TimerRecord {
+            label: Some("Retro".to_string()),
+            start: create_test_datetime("2025-01-27 09:10:00"),
+            ..timer("standup", "2025-01-27 09:20:00")
+        };
+        let during = create_test_datetime("2025-01-27 09:12:00");
+
+        let error = claim_timer(&mut store, &again, during, OnExists::Abort).unwrap_err();
+        assert!(matches!(error, PbError::TimerExists { .. }), "{error}");
+        assert_eq!(store.timer("standup").unwrap(), Some(saved.clone()));
+
+        let merged = claim_timer(&mut store, &again, during, OnExists::Merge).unwrap();
+        assert_eq!(merged.start, saved.start);
+        assert_eq!(merged.end, again.end);
+        assert_eq!(merged.label, saved.label);
+        assert_eq!(store.timer("standup").unwrap(), Some(merged));
+
+        let replaced = claim_timer(&mut store, &again, during, OnExists::Replace).unwrap();
+        assert_eq!(replaced, again);
+
+        // A timer that has ended is simply started again
+        let later = create_test_datetime("2025-01-27 10:00:00");
+        assert_eq!(
+            claim_timer(&mut store, &saved, later, OnExists::Abort).unwrap(),
+            saved
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_claim_timer_of_exited_process() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = JsonStore::open(dir.path().join("store.json")).unwrap();
+        let during = create_test_datetime("2025-01-27 09:12:00");
+        let again = timer("standup", "2025-01-27 09:20:00");
+
+        let running = TimerRecord {
+            pid: Some(std::process::id()),
+            ..timer("standup", "2025-01-27 09:15:00")
+        };
+        store.save_timer(&running).unwrap();
+        assert!(claim_timer(&mut store, &again, during, OnExists::Abort).is_err());
+
+        // A timer killed before the end of its window no longer holds its name
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let exited = child.id();
+        child.wait().unwrap();
+        store
+            .save_timer(&TimerRecord {
+                pid: Some(exited),
+                ..running
+            })
+            .unwrap();
+        assert_eq!(
+            claim_timer(&mut store, &again, during, OnExists::Abort).unwrap(),
+            again
+        );
+    }
+
+    #[test]
+    fn test_json_store_rejects_corrupt_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+        fs::write(&path, "{\"timers\": [").unwrap();
+        assert!(matches!(
+            JsonStore::open(path),
+            Err(PbError::Storage { .. })
+        ));
+    }
+
+    #[test]
+    fn test_json_store_refuses_newer_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+        fs::write(&path, "{\"schema_version\": 99, \"timers\": []}").unwrap();
+        let error = JsonStore::open(path.clone()).unwrap_err();
+        assert!(error.to_string().contains("newer pmon"), "{error}");
+        // Repairing leaves it alone too
+        assert!(repair_json_store(&path).is_err());
+        assert!(!dir.path().join("store.json.corrupt").exists());
+    }
+
+    #[test]
+    fn test_json_store_upgrades_older_layouts() {
+        // A later layout that keeps runs under `runs` instead of `history`
+        fn rename_history(value: &mut serde_json::Value) -> Result<(), String> {
+            let object = value.as_object_mut().ok_or("not an object")?;
+            let history = object.remove("history").unwrap_or_default();
+            object.insert("runs".to_string(), history);
+            Ok(())
+        }
+        let migrations = [Migration {
+            from: 1,
+            description: "rename history to runs",
+            apply: rename_history,
+        }];
+
+        let mut value = serde_json::json!({ "timers": [], "history": [1] });
+        upgrade_json(&mut value, 2, &migrations).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({ "schema_version": 2, "timers": [], "runs": [1] })
+        );
+
+        let mut value = serde_json::json!({ "schema_version": 0 });
+        let error = upgrade_json(&mut value, 2, &migrations).unwrap_err();
+        assert!(error.contains("schema version 0"), "{error}");
+        let mut value = serde_json::json!({ "schema_version": "1" });
+        assert!(upgrade_json(&mut value, 2, &migrations).is_err());
+
+        // Files written before the version was recorded open as the first layout
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+        fs::write(&path, "{\"timers\": []}").unwrap();
+        let mut store = JsonStore::open(path.clone()).unwrap();
+        store.record_run(&run(RunOutcome::Completed)).unwrap();
+        let saved: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(saved["schema_version"], STORE_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_repair_json_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+        assert_eq!(repair_json_store(&path).unwrap(), Repair::Missing);
+
+        let mut store = JsonStore::open(path.clone()).unwrap();
+        store
+            .save_timer(&timer("deploy", "2025-01-27 10:00:00"))
+            .unwrap();
+        store
+            .save_timer(&timer("standup", "2025-01-27 09:15:00"))
+            .unwrap();
+        store.record_run(&run(RunOutcome::Completed)).unwrap();
+        store.record_run(&run(RunOutcome::Interrupted)).unwrap();
+        assert_eq!(repair_json_store(&path).unwrap(), Repair::Healthy);
+
+        // Cut the file in the middle of the last run
+        let contents = fs::read_to_string(&path).unwrap();
+        let cut = contents.rfind("interrupted").unwrap();
+        fs::write(&path, &contents[..cut]).unwrap();
+        fs::write(dir.path().join(".store.json.1.tmp"), "{").unwrap();
+        assert!(JsonStore::open(path.clone())
+            .unwrap_err()
+            .to_string()
+            .contains("pmon repair-state"));
+
+        let backup = dir.path().join("store.json.corrupt");
+        assert_eq!(
+            repair_json_store(&path).unwrap(),
+            Repair::Recovered {
+                timers: 2,
+                runs: 1,
+                definitions: 0,
+                backup: backup.clone(),
+            }
+        );
+        assert_eq!(fs::read_to_string(backup).unwrap(), contents[..cut]);
+        assert!(!dir.path().join(".store.json.1.tmp").exists());
+        let store = JsonStore::open(path).unwrap();
+        assert_eq!(store.history().unwrap(), [run(RunOutcome::Completed)]);
+    }
+
+    #[test]
+    fn test_recover_truncated() {
+        let data = recover_truncated("{\"timers\": [], \"history\": [{\"na").unwrap();
+        assert!(data.timers.is_empty() && data.history.is_empty());
+        // Brackets inside strings do not count
+        let data = recover_truncated(
+            "{\"definitions\": [{\"name\": \"a]\\\"}\", \"end\": \"+1h\"}, {\"name\":",
+        )
+        .unwrap();
+        assert_eq!(data.definitions[0].name, "a]\"}");
+        assert!(recover_truncated("").is_none());
+        assert!(recover_truncated("not json").is_none());
+    }
+
+    #[test]
+    fn test_open_store_uses_configured_backend() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = StoreConfig {
+            backend: StoreBackend::Json,
+            path: Some(dir.path().join("store.json")),
+            ..StoreConfig::default()
+        };
+        assert_eq!(open_store(&config).unwrap().backend(), StoreBackend::Json);
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.sqlite3");
+        let mut store = SqliteStore::open(&path).unwrap();
+        assert_eq!(store.backend(), StoreBackend::Sqlite);
+        exercise_store(&mut store);
+
+        let reopened = SqliteStore::open(&path).unwrap();
+        assert_eq!(reopened.timers().unwrap().len(), 1);
+        assert_eq!(reopened.history().unwrap().len(), 2);
+    }
+
+    #[cfg(not(feature = "encryption"))]
+    #[test]
+    fn test_encryption_requires_feature() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = StoreConfig {
+            path: Some(dir.path().join("store.json")),
+            encrypt: true,
+            ..StoreConfig::default()
+        };
+        assert!(matches!(open_store(&config), Err(PbError::Storage { .. })));
+    }
+
+    #[cfg(not(feature = "sqlite"))]
+    #[test]
+    fn test_sqlite_requires_feature() {
+        let config = StoreConfig {
+            backend: StoreBackend::Sqlite,
+            ..StoreConfig::default()
+        };
+        assert!(matches!(open_store(&config), Err(PbError::Storage { .. })));
+    }
+}
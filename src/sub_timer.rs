@@ -0,0 +1,74 @@
+//! Short timers started from inside a running window
+//!
+//! Pressing `s` while a window is shown asks for a length and an optional
+//! label, such as `15m Q&A slot`, and draws a thin bar for that timer beneath
+//! the window's. Starting another replaces it.
+
+use crate::chain::Segment;
+use crate::error::PbResult;
+use crate::time_parser::parse_relative_time;
+use chrono::NaiveDateTime;
+
+/// Label of a sub-timer started without one
+pub const SUB_TIMER_LABEL: &str = "Sub-timer";
+
+/// Longest input accepted at the sub-timer prompt
+pub const MAX_SUB_TIMER_INPUT: usize = 80;
+
+/// Start a sub-timer at `now` from input such as `15m` or `15m Q&A slot`
+///
+/// The length is a relative time, optionally written with a leading `+`.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDateTime;
+/// use pmon::sub_timer::parse_sub_timer;
+///
+/// let at = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+/// let now = at("2025-07-21 10:00:00");
+///
+/// let timer = parse_sub_timer("15m Q&A slot", now).unwrap();
+/// assert_eq!(timer.label.as_deref(), Some("Q&A slot"));
+/// assert_eq!(timer.end, at("2025-07-21 10:15:00"));
+/// assert!(parse_sub_timer("soon", now).is_err());
+/// ```
+pub fn parse_sub_timer(input: &str, now: NaiveDateTime) -> PbResult<Segment> {
+    let input = input.trim();
+    let (length, label) = input.split_once(' ').unwrap_or((input, ""));
+    let end = parse_relative_time(length.strip_prefix('+').unwrap_or(length), now)?;
+    let label = match label.trim() {
+        "" => SUB_TIMER_LABEL,
+        label => label,
+    };
+    Ok(Segment {
+        label: Some(label.to_string()),
+        start: now,
+        end,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_parse_sub_timer() {
+        let now = create_test_datetime("2025-07-21 10:00:00");
+        let timer = parse_sub_timer(" +90s ", now).unwrap();
+        assert_eq!(timer.label.as_deref(), Some(SUB_TIMER_LABEL));
+        assert_eq!(timer.start, now);
+        assert_eq!(timer.end, create_test_datetime("2025-07-21 10:01:30"));
+
+        let timer = parse_sub_timer("1h  Closing remarks ", now).unwrap();
+        assert_eq!(timer.label.as_deref(), Some("Closing remarks"));
+
+        for input in ["", "   ", "15", "0m", "Q&A 15m"] {
+            assert!(parse_sub_timer(input, now).is_err(), "{input:?}");
+        }
+    }
+}
@@ -0,0 +1,75 @@
+//! Tags for grouping related timers
+//!
+//! `--tag team:payments --tag sev2` attaches free-form tags to a timer. They
+//! are published with its updates, listed by `pmon list`, and kept in the
+//! run history, so runs can later be grouped, for example to total the
+//! overtime of every `sev2` timer in a month.
+
+use crate::error::{PbError, PbResult};
+
+/// Option name used in error messages
+const OPTION_NAME: &str = "--tag";
+
+/// Longest accepted tag
+pub const MAX_TAG_LENGTH: usize = 64;
+
+/// Parse a `--tag` value
+///
+/// Tags are made of letters, digits, and `-`, `_`, `.`, `:`, or `/`, so they
+/// can be joined with spaces and given on any command line unquoted.
+///
+/// # Examples
+///
+/// ```
+/// use pmon::tags::parse_tag;
+///
+/// assert_eq!(parse_tag("team:payments").unwrap(), "team:payments");
+/// assert_eq!(parse_tag(" sev2 ").unwrap(), "sev2");
+/// assert!(parse_tag("two words").is_err());
+/// ```
+pub fn parse_tag(input: &str) -> PbResult<String> {
+    let tag = input.trim();
+    let is_valid = !tag.is_empty()
+        && tag.len() <= MAX_TAG_LENGTH
+        && tag
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_.:/".contains(c));
+    if !is_valid {
+        return Err(PbError::invalid_argument(OPTION_NAME, input));
+    }
+    Ok(tag.to_string())
+}
+
+/// Format tags for display, as `[team:payments, sev2]`
+///
+/// Returns an empty string when there are no tags.
+pub fn format_tags(tags: &[String]) -> String {
+    if tags.is_empty() {
+        String::new()
+    } else {
+        format!("[{}]", tags.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_invalid_tag() {
+        let too_long = "x".repeat(MAX_TAG_LENGTH + 1);
+        for input in ["", "  ", "a,b", "sev 2", "é", too_long.as_str()] {
+            assert!(
+                matches!(parse_tag(input), Err(PbError::InvalidArgument { .. })),
+                "Expected '{input}' to be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_format_tags() {
+        assert_eq!(format_tags(&[]), "");
+        let tags = ["team:payments".to_string(), "sev2".to_string()];
+        assert_eq!(format_tags(&tags), "[team:payments, sev2]");
+    }
+}
@@ -0,0 +1,117 @@
+//! Raw-mode terminal sessions
+//!
+//! Interactive modes put the terminal in raw mode to read single key
+//! presses. A [`TerminalSession`] owns that state: it enters raw mode when
+//! started and leaves it when dropped, so the terminal is restored on every
+//! return path, including early returns through `?`, without each caller
+//! remembering to clean up. pmon draws in the main screen, so leaving raw
+//! mode is all there is to restore.
+//!
+//! Without the `interactive` feature there is no raw mode: sessions never
+//! become interactive and pmon only prints plain lines.
+
+use std::io;
+
+/// Clears from the cursor to the end of the line
+pub const CLEAR_LINE: &str = "\x1b[K";
+
+/// Clears from the cursor to the end of the screen
+pub const CLEAR_BELOW: &str = "\x1b[J";
+
+/// Escape sequence moving the cursor up `lines` lines
+pub fn move_up(lines: u16) -> String {
+    format!("\x1b[{lines}A")
+}
+
+/// Leave raw mode, ignoring a terminal that is already restored or gone
+///
+/// Also used where destructors do not run, such as a panic hook or a
+/// process about to exit.
+pub fn restore_terminal() {
+    #[cfg(feature = "interactive")]
+    let _ = crossterm::terminal::disable_raw_mode();
+}
+
+/// Columns the cursor advances when `text` is drawn on stdout
+///
+/// Asks the terminal where the cursor is before and after drawing, so stdout
+/// must be an interactive terminal. The line is cleared afterwards.
+#[cfg(feature = "interactive")]
+pub fn measure_width(text: &str) -> io::Result<u16> {
+    use crossterm::cursor::position;
+    use std::io::Write;
+
+    let _session = TerminalSession::start(true)?;
+    let mut stdout = io::stdout();
+    write!(stdout, "\r")?;
+    stdout.flush()?;
+    let (before, _) = position()?;
+    write!(stdout, "{text}")?;
+    stdout.flush()?;
+    let (after, _) = position()?;
+    write!(stdout, "\r{CLEAR_LINE}")?;
+    stdout.flush()?;
+    Ok(after.saturating_sub(before))
+}
+
+/// Widths cannot be measured without raw mode
+#[cfg(not(feature = "interactive"))]
+pub fn measure_width(_text: &str) -> io::Result<u16> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "pmon was built without the `interactive` feature",
+    ))
+}
+
+/// Width and height of the terminal in characters, if there is one
+#[cfg(feature = "interactive")]
+pub fn size() -> Option<(u16, u16)> {
+    crossterm::terminal::size().ok()
+}
+
+/// The terminal cannot be asked for its size without the `interactive` feature
+#[cfg(not(feature = "interactive"))]
+pub fn size() -> Option<(u16, u16)> {
+    None
+}
+
+/// Raw mode held for as long as the session lives
+#[derive(Debug)]
+pub struct TerminalSession {
+    raw_mode: bool,
+}
+
+impl TerminalSession {
+    /// Enter raw mode when `interactive`; otherwise leave the terminal alone
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pmon::terminal::TerminalSession;
+    ///
+    /// // Piped output never touches the terminal
+    /// let session = TerminalSession::start(false).unwrap();
+    /// assert!(!session.is_interactive());
+    /// ```
+    pub fn start(interactive: bool) -> io::Result<Self> {
+        let raw_mode = interactive && cfg!(feature = "interactive");
+        #[cfg(feature = "interactive")]
+        if raw_mode {
+            crossterm::terminal::enable_raw_mode()?;
+        }
+        Ok(Self { raw_mode })
+    }
+
+    /// Whether the session put the terminal in raw mode
+    pub fn is_interactive(&self) -> bool {
+        self.raw_mode
+    }
+}
+
+impl Drop for TerminalSession {
+    fn drop(&mut self) {
+        if self.raw_mode {
+            restore_terminal();
+        }
+    }
+}
@@ -0,0 +1,468 @@
+//! Sample bars for choosing how pmon looks
+//!
+//! `pmon themes preview` prints every bar style, bar color, and installed
+//! theme with sample bars at a few percentages side by side, including one
+//! past the end, so an appearance can be picked without starting a real
+//! timer. It begins:
+//!
+//! ```text
+//!          0%                       25%                      50%
+//! classic  [░░░░░░░░░░] 0.0%        [███░░░░░░░] 25.0%       [█████░░░░░] 50.0%
+//! overlay  [░░░0.0%░░░]             [██25.0%░░░]             [██50.0%░░░]
+//! ```
+//!
+//! Themes are TOML files in the `themes` directory beside the configuration
+//! file, such as `~/.config/pmon/themes/ocean.toml`, and are selected by
+//! file name with `--theme ocean`. A theme sets the characters of the bar,
+//! its color, and colors from given percentages on; every key is optional:
+//!
+//! ```toml
+//! filled = "▓"        # a single-column character for elapsed time
+//! empty = "·"         # and one for the time remaining
+//! color = "cyan"      # any color milestones take
+//!
+//! [[thresholds]]
+//! at = "75%"
+//! color = "yellow"
+//!
+//! [[thresholds]]
+//! at = "90%"
+//! color = "red"
+//! ```
+//!
+//! A milestone's color takes precedence over the theme's, and overtime turns
+//! the bar red whatever the theme.
+
+use crate::color::BarColor;
+use crate::config::themes_dir;
+use crate::error::{PbError, PbResult};
+use crate::layout::{display_width, pad_end, visible_width};
+use crate::progress_bar::{render, BarCells, BarStyle, EmojiCells, RenderOptions};
+use crate::progress_state::ProgressState;
+use chrono::NaiveDateTime;
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Percentages every sample bar is drawn at
+pub const PREVIEW_PERCENTAGES: [f64; 6] = [0.0, 25.0, 50.0, 75.0, 100.0, 125.0];
+
+/// Cells in each sample bar
+pub const PREVIEW_WIDTH: usize = 10;
+
+/// What `pmon themes` does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ThemesAction {
+    /// Print sample bars of every style and color
+    Preview,
+}
+
+/// Extension of theme files
+pub const THEME_EXTENSION: &str = "toml";
+
+/// A theme read from the themes directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    /// Name the theme is selected by, its file name without the extension
+    pub name: String,
+    /// Cells of bracketed bars
+    pub cells: BarCells,
+    /// Color of the bar below the first threshold
+    pub color: Option<BarColor>,
+    /// Colors from a percentage of the window on, lowest first
+    pub thresholds: Vec<(f64, BarColor)>,
+}
+
+/// A theme file as written
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThemeFile {
+    #[serde(default)]
+    filled: Option<String>,
+    #[serde(default)]
+    empty: Option<String>,
+    #[serde(default)]
+    color: Option<BarColor>,
+    #[serde(default)]
+    thresholds: Vec<ThresholdFile>,
+}
+
+/// A `[[thresholds]]` table of a theme file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThresholdFile {
+    at: String,
+    color: BarColor,
+}
+
+impl Theme {
+    /// Parse the contents of the theme file for `name`
+    ///
+    /// `path` is only used in error messages.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pmon::color::BarColor;
+    /// use pmon::themes::Theme;
+    ///
+    /// let theme = Theme::parse("ocean", "filled = \"▓\"\ncolor = \"cyan\"\n", "ocean.toml").unwrap();
+    /// assert_eq!(theme.cells.filled, '▓');
+    /// assert_eq!(theme.cells.empty, '░');
+    /// assert_eq!(theme.color(50.0), Some(BarColor::Cyan));
+    /// assert!(Theme::parse("wide", "filled = \"🟦\"\n", "wide.toml").is_err());
+    /// ```
+    pub fn parse(name: &str, contents: &str, path: &str) -> PbResult<Self> {
+        let invalid = |reason: String| PbError::invalid_config(path, reason);
+        let file: ThemeFile =
+            toml::from_str(contents).map_err(|e| invalid(e.message().to_string()))?;
+        let cell = |key: &str, value: Option<String>, default: char| match value {
+            None => Ok(default),
+            Some(value) => match value.chars().collect::<Vec<_>>()[..] {
+                [ch] if display_width(&value) == 1 => Ok(ch),
+                _ => Err(invalid(format!(
+                    "{key} must be a single character one column wide, not '{value}'"
+                ))),
+            },
+        };
+        let defaults = BarCells::default();
+        let cells = BarCells {
+            filled: cell("filled", file.filled, defaults.filled)?,
+            empty: cell("empty", file.empty, defaults.empty)?,
+        };
+        let mut thresholds = file
+            .thresholds
+            .iter()
+            .map(|threshold| {
+                let percent = threshold
+                    .at
+                    .trim()
+                    .strip_suffix('%')
+                    .and_then(|percent| percent.trim().parse::<f64>().ok())
+                    .filter(|percent| *percent > 0.0 && *percent <= 100.0)
+                    .ok_or_else(|| {
+                        invalid(format!(
+                            "threshold '{}' is not a percentage such as 75%",
+                            threshold.at
+                        ))
+                    })?;
+                Ok((percent, threshold.color))
+            })
+            .collect::<PbResult<Vec<_>>>()?;
+        thresholds.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(Self {
+            name: name.to_string(),
+            cells,
+            color: file.color,
+            thresholds,
+        })
+    }
+
+    /// Color of the bar at `percentage`, from the highest threshold reached
+    pub fn color(&self, percentage: f64) -> Option<BarColor> {
+        self.thresholds
+            .iter()
+            .rev()
+            .find(|(at, _)| percentage >= *at)
+            .map(|&(_, color)| color)
+            .or(self.color)
+    }
+}
+
+/// Whether `name` can name a theme: letters, digits, `-`, and `_`
+pub fn is_theme_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_')
+}
+
+/// Names of the themes in `dir`, sorted; a missing directory has none
+fn theme_names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == THEME_EXTENSION))
+        .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+        .filter(|name| is_theme_name(name))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Load the theme `name` from `dir`
+///
+/// A theme that does not exist is reported with the names of those that do.
+pub fn load_theme(dir: &Path, name: &str) -> PbResult<Theme> {
+    if !is_theme_name(name) {
+        return Err(PbError::invalid_argument("--theme", name));
+    }
+    let path = dir.join(format!("{name}.{THEME_EXTENSION}"));
+    match fs::read_to_string(&path) {
+        Ok(contents) => Theme::parse(name, &contents, &path.display().to_string()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            let names = theme_names(dir);
+            let found = if names.is_empty() {
+                "none".to_string()
+            } else {
+                names.join(", ")
+            };
+            Err(PbError::invalid_config(
+                dir.display().to_string(),
+                format!("there is no theme named '{name}'; themes found: {found}"),
+            ))
+        }
+        Err(e) => Err(PbError::invalid_config(
+            path.display().to_string(),
+            e.to_string(),
+        )),
+    }
+}
+
+/// Load the theme `name` from the user's [`themes_dir`], for `--theme`
+pub fn find_theme(name: &str) -> PbResult<Theme> {
+    load_theme(&themes_dir(), name)
+}
+
+/// Every theme in `dir`, sorted by name
+///
+/// A theme file that cannot be read fails the whole discovery, naming the
+/// file, so a broken theme is noticed rather than quietly left out.
+pub fn discover_themes(dir: &Path) -> PbResult<Vec<Theme>> {
+    theme_names(dir)
+        .iter()
+        .map(|name| load_theme(dir, name))
+        .collect()
+}
+
+/// Bar styles by the name they are shown under
+pub fn preview_styles() -> [(&'static str, BarStyle); 5] {
+    [
+        ("classic", BarStyle::Classic),
+        ("overlay", BarStyle::Overlay),
+        ("thin", BarStyle::Thin),
+        ("zen", BarStyle::Zen),
+        ("emoji", BarStyle::Emoji(EmojiCells::default())),
+    ]
+}
+
+/// Bar colors by the name the configuration gives them
+pub fn preview_colors() -> [(&'static str, BarColor); 6] {
+    [
+        ("red", BarColor::Red),
+        ("yellow", BarColor::Yellow),
+        ("green", BarColor::Green),
+        ("blue", BarColor::Blue),
+        ("magenta", BarColor::Magenta),
+        ("cyan", BarColor::Cyan),
+    ]
+}
+
+/// Lines of `pmon themes preview`: a header of percentages, then one row of
+/// sample bars per style, per color, and per theme in `themes`
+///
+/// Colored styles and themes turn red past the end as real bars do; colored
+/// rows show the classic bar in their color throughout.
+///
+/// # Examples
+///
+/// ```
+/// use pmon::themes::preview_lines;
+///
+/// let lines = preview_lines(&[]);
+/// assert!(lines[0].trim_start().starts_with("0%"));
+/// assert!(lines[1].starts_with("classic  [░░░░░░░░░░] 0.0%"));
+/// ```
+pub fn preview_lines(themes: &[Theme]) -> Vec<String> {
+    let styled = preview_styles().map(|(name, style)| {
+        let options = RenderOptions {
+            color: true,
+            width: PREVIEW_WIDTH,
+            style,
+            time: None,
+            ..RenderOptions::default()
+        };
+        (name, samples(|state| render(state, &options)))
+    });
+    let plain = RenderOptions {
+        width: PREVIEW_WIDTH,
+        time: None,
+        ..RenderOptions::default()
+    };
+    let colored = preview_colors()
+        .map(|(name, color)| (name, samples(|state| color.paint(&render(state, &plain)))));
+    let themed = themes.iter().map(|theme| {
+        let options = RenderOptions {
+            color: true,
+            cells: theme.cells,
+            ..plain
+        };
+        let draw = |state: &ProgressState| {
+            let line = render(state, &options);
+            match theme.color(state.percentage) {
+                Some(color) if state.percentage <= 100.0 => color.paint(&line),
+                _ => line,
+            }
+        };
+        (theme.name.as_str(), samples(draw))
+    });
+    let rows: Vec<(&str, Vec<String>)> = styled.into_iter().chain(colored).chain(themed).collect();
+
+    let name_width = rows
+        .iter()
+        .map(|(name, _)| display_width(name))
+        .max()
+        .unwrap_or(0);
+    let column_width = rows
+        .iter()
+        .flat_map(|(_, samples)| samples.iter().map(|sample| visible_width(sample)))
+        .max()
+        .unwrap_or(0);
+    let header = PREVIEW_PERCENTAGES.map(|percentage| format!("{percentage}%"));
+    std::iter::once((pad_end("", name_width), header.to_vec()))
+        .chain(
+            rows.into_iter()
+                .map(|(name, samples)| (pad_end(name, name_width), samples)),
+        )
+        .map(|(name, cells)| {
+            let cells: Vec<String> = cells
+                .iter()
+                .map(|cell| pad_visible(cell, column_width))
+                .collect();
+            format!("{name}  {}", cells.join(" "))
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+/// One sample drawn by `draw` at each of [`PREVIEW_PERCENTAGES`]
+fn samples(draw: impl Fn(&ProgressState) -> String) -> Vec<String> {
+    let at = NaiveDateTime::default();
+    PREVIEW_PERCENTAGES
+        .iter()
+        .map(|&percentage| {
+            draw(&ProgressState {
+                start: at,
+                end: at,
+                current: at,
+                percentage,
+            })
+        })
+        .collect()
+}
+
+/// Pad `text` to `width` columns, not counting the codes that color it
+fn pad_visible(text: &str, width: usize) -> String {
+    let padding = width.saturating_sub(visible_width(text));
+    format!("{text}{}", " ".repeat(padding))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `text` without the codes that color it, whether or not colors are on
+    fn strip_colors(text: &str) -> String {
+        let mut plain = String::new();
+        let mut chars = text.chars();
+        while let Some(ch) = chars.next() {
+            if ch == '\x1b' {
+                chars.by_ref().find(|&ch| ch == 'm');
+            } else {
+                plain.push(ch);
+            }
+        }
+        plain
+    }
+
+    #[test]
+    fn test_preview_lines() {
+        let lines: Vec<String> = preview_lines(&[])
+            .iter()
+            .map(|line| strip_colors(line))
+            .collect();
+        assert_eq!(
+            lines.len(),
+            1 + preview_styles().len() + preview_colors().len()
+        );
+        assert_eq!(
+            lines[0],
+            "         0%                       25%                      50%                      75%                      100%                     125%"
+        );
+        assert_eq!(
+            lines[1],
+            "classic  [░░░░░░░░░░] 0.0%        [███░░░░░░░] 25.0%       [█████░░░░░] 50.0%       [████████░░] 75.0%       [██████████] 100.0%      [██████████] 125.0%"
+        );
+        assert!(lines[2].starts_with("overlay  [░░░0.0%░░░]"));
+        assert!(lines[4].starts_with("zen      [░░░░░░░░░░] early"));
+        assert!(lines[5].starts_with("emoji    ⬜⬜⬜⬜⬜ 0.0%"));
+        assert!(lines[6].starts_with("red      [░░░░░░░░░░] 0.0%"));
+        // The widest sample, zen's "wrapping up", sets the column width
+        assert!(lines[4].contains("[████████░░] wrapping up [██████████] time's up"));
+    }
+
+    #[test]
+    fn test_theme_thresholds() {
+        let contents = "color = \"green\"\n\
+                        [[thresholds]]\nat = \"90%\"\ncolor = \"red\"\n\
+                        [[thresholds]]\nat = \"75%\"\ncolor = \"yellow\"\n";
+        let theme = Theme::parse("traffic", contents, "traffic.toml").unwrap();
+        assert_eq!(theme.cells, BarCells::default());
+        assert_eq!(theme.color(10.0), Some(BarColor::Green));
+        assert_eq!(theme.color(75.0), Some(BarColor::Yellow));
+        assert_eq!(theme.color(95.0), Some(BarColor::Red));
+
+        let error = |contents: &str| {
+            Theme::parse("broken", contents, "broken.toml")
+                .unwrap_err()
+                .to_string()
+        };
+        let message = error("[[thresholds]]\nat = \"120%\"\ncolor = \"red\"\n");
+        assert!(message.contains("threshold '120%'"), "{message}");
+        let message = error("empty = \"--\"\n");
+        assert!(
+            message.contains("empty must be a single character"),
+            "{message}"
+        );
+        let message = error("color = \"pink\"\n");
+        assert!(message.contains("broken.toml"), "{message}");
+        assert!(error("glyph = \"x\"\n").contains("unknown field"));
+    }
+
+    #[test]
+    fn test_load_theme() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(discover_themes(&dir.path().join("missing"))
+            .unwrap()
+            .is_empty());
+        fs::write(dir.path().join("ocean.toml"), "filled = \"▓\"\n").unwrap();
+        fs::write(dir.path().join("forest.toml"), "color = \"green\"\n").unwrap();
+        fs::write(dir.path().join("notes.txt"), "not a theme").unwrap();
+
+        let theme = load_theme(dir.path(), "ocean").unwrap();
+        assert_eq!(theme.name, "ocean");
+        assert_eq!(theme.cells.filled, '▓');
+        let names: Vec<String> = discover_themes(dir.path())
+            .unwrap()
+            .into_iter()
+            .map(|theme| theme.name)
+            .collect();
+        assert_eq!(names, ["forest", "ocean"]);
+
+        let message = load_theme(dir.path(), "desert").unwrap_err().to_string();
+        assert!(
+            message.contains("no theme named 'desert'; themes found: forest, ocean"),
+            "{message}"
+        );
+        assert!(load_theme(dir.path(), "../ocean").is_err());
+
+        let lines = preview_lines(&discover_themes(dir.path()).unwrap());
+        let ocean = strip_colors(lines.last().unwrap());
+        assert!(ocean.starts_with("ocean    [░░░░░░░░░░] 0.0%"), "{ocean}");
+        assert!(ocean.contains("[▓▓▓▓▓░░░░░] 50.0%"), "{ocean}");
+    }
+}
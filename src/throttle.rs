@@ -0,0 +1,147 @@
+//! Thinning out piped progress lines
+//!
+//! A long window piped to a log prints the same bar over and over: a
+//! one-day window updated every minute adds 1,440 lines that mostly differ
+//! in the last digit. `--min-change 0.5` keeps a line only once the
+//! percentage has moved by at least half a point since the last one kept,
+//! and repeats the bar every [`THROTTLE_HEARTBEAT`] so a quiet log still
+//! shows the monitor is alive. The first and final lines are always kept.
+//!
+//! Some CI systems end a job whose log has been silent for a while, often
+//! ten minutes, which a long wait or a sparse `--format gha` run can easily
+//! hit. `--heartbeat 9m` prints a short [`Heartbeat`] line to stderr on
+//! that schedule whatever else is happening.
+
+use crate::progress_bar::{format_duration_with, DurationStyle};
+use std::time::{Duration, Instant};
+
+/// Longest a throttled log goes without a line
+pub const THROTTLE_HEARTBEAT: Duration = Duration::from_secs(5 * 60);
+
+/// Decides which updates are worth a new line
+#[derive(Debug, Clone)]
+pub struct LineThrottle {
+    min_change: f64,
+    heartbeat: Duration,
+    last: Option<(f64, Instant)>,
+}
+
+impl LineThrottle {
+    /// Keep lines that move at least `min_change` percentage points
+    pub fn new(min_change: f64) -> Self {
+        Self::with_heartbeat(min_change, THROTTLE_HEARTBEAT)
+    }
+
+    /// Keep lines that move at least `min_change` points, or come
+    /// `heartbeat` after the last line kept
+    pub fn with_heartbeat(min_change: f64, heartbeat: Duration) -> Self {
+        Self {
+            min_change,
+            heartbeat,
+            last: None,
+        }
+    }
+
+    /// Whether the update at `percentage`, seen at `now`, gets a line
+    ///
+    /// Kept updates become the reference for the next ones.
+    pub fn should_emit(&mut self, percentage: f64, now: Instant) -> bool {
+        let emit = match self.last {
+            None => true,
+            Some((last, at)) => {
+                percentage >= 100.0
+                    || (percentage - last).abs() >= self.min_change
+                    || now.saturating_duration_since(at) >= self.heartbeat
+            }
+        };
+        if emit {
+            self.last = Some((percentage, now));
+        }
+        emit
+    }
+}
+
+/// Keepalive lines printed on a fixed schedule
+#[derive(Debug, Clone, Copy)]
+pub struct Heartbeat {
+    every: Duration,
+    started: Instant,
+    style: DurationStyle,
+}
+
+impl Heartbeat {
+    /// Beat `every` so long, counting from `started`
+    pub fn new(every: Duration, started: Instant, style: DurationStyle) -> Self {
+        Self {
+            every,
+            started,
+            style,
+        }
+    }
+
+    /// Time between keepalive lines
+    pub fn every(&self) -> Duration {
+        self.every
+    }
+
+    /// Keepalive line printed at `now`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pmon::progress_bar::DurationStyle;
+    /// use pmon::throttle::Heartbeat;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let started = Instant::now();
+    /// let heartbeat = Heartbeat::new(Duration::from_secs(540), started, DurationStyle::Compact);
+    /// assert_eq!(
+    ///     heartbeat.line(started + Duration::from_secs(1080)),
+    ///     "pmon: still running (18m)"
+    /// );
+    /// ```
+    pub fn line(&self, now: Instant) -> String {
+        let running = now.saturating_duration_since(self.started);
+        let running = chrono::Duration::from_std(running).unwrap_or_default();
+        format!(
+            "pmon: still running ({})",
+            format_duration_with(running, self.style)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_small_changes_are_dropped() {
+        let start = Instant::now();
+        let mut throttle = LineThrottle::new(0.5);
+        assert!(throttle.should_emit(10.0, start));
+        assert!(!throttle.should_emit(10.2, start + Duration::from_secs(60)));
+        assert!(!throttle.should_emit(10.4, start + Duration::from_secs(120)));
+        // Measured from the last line kept, not the last update seen
+        assert!(throttle.should_emit(10.5, start + Duration::from_secs(180)));
+        assert!(!throttle.should_emit(10.9, start + Duration::from_secs(240)));
+    }
+
+    #[test]
+    fn test_heartbeat_repeats_quiet_lines() {
+        let start = Instant::now();
+        let mut throttle = LineThrottle::with_heartbeat(5.0, Duration::from_secs(600));
+        assert!(throttle.should_emit(1.0, start));
+        assert!(!throttle.should_emit(1.1, start + Duration::from_secs(599)));
+        assert!(throttle.should_emit(1.2, start + Duration::from_secs(600)));
+        assert!(!throttle.should_emit(1.3, start + Duration::from_secs(900)));
+    }
+
+    #[test]
+    fn test_completion_is_always_kept() {
+        let start = Instant::now();
+        let mut throttle = LineThrottle::new(10.0);
+        assert!(throttle.should_emit(95.0, start));
+        assert!(throttle.should_emit(100.0, start));
+        assert!(throttle.should_emit(100.0, start));
+    }
+}
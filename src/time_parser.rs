@@ -4,8 +4,14 @@
 //! `NaiveDateTime` objects for use in progress bar calculations.
 
 use crate::error::PbError;
-use chrono::{Duration, Local, NaiveDate, NaiveDateTime};
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime, NaiveTime};
 use regex::Regex;
+use std::sync::LazyLock;
+use tracing::debug;
+
+/// Relative times such as `30m`, compiled once for all parses
+#[allow(clippy::unwrap_used)] // A literal pattern, compiled by every test
+static RELATIVE_TIME: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(\d+)([smhd])$").unwrap());
 
 /// Get current time consistently across the application
 ///
@@ -17,14 +23,20 @@ use regex::Regex;
 /// # Returns
 ///
 /// Returns the current local time as a `NaiveDateTime`, which matches
-/// the format used for parsed absolute timestamps.
+/// the format used for parsed absolute timestamps. When a remote time
+/// source is configured, its measured offset is applied, and with
+/// `--timezone` the civil time is read in that zone.
 ///
 /// # Usage
 ///
 /// This function should be used everywhere in the application where
 /// we need to get the current time, to ensure timezone consistency.
 pub fn get_current_time() -> NaiveDateTime {
-    Local::now().naive_local()
+    let now = match crate::timezone::configured_timezone() {
+        Some(tz) => crate::timezone::now_in(tz),
+        None => Local::now().naive_local(),
+    };
+    now + crate::time_source::clock_offset()
 }
 
 /// Parse a date string in YYYY-MM-DD format
@@ -64,46 +76,37 @@ pub fn get_current_time() -> NaiveDateTime {
 /// assert!(result.is_err());
 /// ```
 pub fn parse_date(input: &str) -> Result<NaiveDateTime, PbError> {
-    // First, validate the basic format using regex to ensure 4-digit year
+    read_date(input).map_err(|_| PbError::invalid_time_format(input))
+}
+
+/// Read a `YYYY-MM-DD` date, or say why `input` is not one
+fn read_date(input: &str) -> Result<NaiveDateTime, String> {
+    // First, validate the basic format to ensure 4-digit year
     if !input.chars().all(|c| c.is_ascii_digit() || c == '-') {
-        return Err(PbError::InvalidTimeFormat {
-            input: input.to_string(),
-        });
+        return Err("only digits and '-' are allowed".to_string());
     }
 
     // Split by hyphens and validate format
     let parts: Vec<&str> = input.split('-').collect();
     if parts.len() != 3 {
-        return Err(PbError::InvalidTimeFormat {
-            input: input.to_string(),
-        });
+        return Err("expected a year, month, and day separated by '-'".to_string());
     }
 
     // Validate year is exactly 4 digits
     if parts[0].len() != 4 {
-        return Err(PbError::InvalidTimeFormat {
-            input: input.to_string(),
-        });
+        return Err(format!("the year {} does not have four digits", parts[0]));
     }
 
     // Validate month and day are 1-2 digits
     if parts[1].is_empty() || parts[1].len() > 2 || parts[2].is_empty() || parts[2].len() > 2 {
-        return Err(PbError::InvalidTimeFormat {
-            input: input.to_string(),
-        });
+        return Err("the month and day need one or two digits".to_string());
     }
 
     // Parse the date string using chrono's built-in parser
     NaiveDate::parse_from_str(input, "%Y-%m-%d")
-        .map(|date| {
-            // Convert to NaiveDateTime with time 00:00:00
-            // Using and_hms_opt(0, 0, 0).unwrap() is safe here because
-            // 00:00:00 is always a valid time
-            date.and_hms_opt(0, 0, 0).unwrap()
-        })
-        .map_err(|_| PbError::InvalidTimeFormat {
-            input: input.to_string(),
-        })
+        // Convert to NaiveDateTime with time 00:00:00
+        .map(|date| date.and_time(NaiveTime::MIN))
+        .map_err(|_| format!("{input} is not a day of the calendar"))
 }
 
 /// Parse a datetime string in YYYY-MM-DD HH:MM:SS format
@@ -144,53 +147,50 @@ pub fn parse_date(input: &str) -> Result<NaiveDateTime, PbError> {
 /// assert!(result.is_err());
 /// ```
 pub fn parse_datetime(input: &str) -> Result<NaiveDateTime, PbError> {
+    read_datetime(input).map_err(|_| PbError::invalid_time_format(input))
+}
+
+/// Read a `YYYY-MM-DD HH:MM:SS` date and time, or say why `input` is not one
+fn read_datetime(input: &str) -> Result<NaiveDateTime, String> {
     // Validate that input contains only ASCII characters, spaces, hyphens, and colons
     if !input
         .chars()
         .all(|c| c.is_ascii() && (c.is_ascii_digit() || c == '-' || c == ' ' || c == ':'))
     {
-        return Err(PbError::InvalidTimeFormat {
-            input: input.to_string(),
-        });
+        return Err("only digits, '-', ':', and a space are allowed".to_string());
     }
 
     // Check for double spaces or other formatting issues
     if input.contains("  ") {
-        return Err(PbError::InvalidTimeFormat {
-            input: input.to_string(),
-        });
+        return Err("the date and time are separated by more than one space".to_string());
     }
 
     // Validate seconds are not >= 60 before parsing
     // Split by space to get date and time parts
     let parts: Vec<&str> = input.split(' ').collect();
     if parts.len() != 2 {
-        return Err(PbError::InvalidTimeFormat {
-            input: input.to_string(),
-        });
+        return Err("expected a date and a time separated by a space".to_string());
     }
 
     // Split time part by colons to check seconds
     let time_parts: Vec<&str> = parts[1].split(':').collect();
     if time_parts.len() != 3 {
-        return Err(PbError::InvalidTimeFormat {
-            input: input.to_string(),
-        });
+        return Err("the time needs hours, minutes, and seconds".to_string());
     }
 
     // Check if seconds >= 60
     if let Ok(seconds) = time_parts[2].parse::<u32>() {
         if seconds >= 60 {
-            return Err(PbError::InvalidTimeFormat {
-                input: input.to_string(),
-            });
+            return Err(format!("{seconds} seconds is not below 60"));
         }
     }
 
     chrono::NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M:%S").map_err(|_| {
-        PbError::InvalidTimeFormat {
-            input: input.to_string(),
-        }
+        // Name the part at fault
+        read_date(parts[0])
+            .and_then(|date| read_time_only(parts[1], date.date()))
+            .err()
+            .unwrap_or_else(|| format!("{input} is not a valid date and time"))
     })
 }
 
@@ -253,108 +253,257 @@ pub fn parse_relative_time(
     input: &str,
     base_time: NaiveDateTime,
 ) -> Result<NaiveDateTime, PbError> {
-    // Create regex pattern for relative time formats: ^(\d+)([smhd])$
-    let re = Regex::new(r"^(\d+)([smhd])$").unwrap();
-
-    if let Some(captures) = re.captures(input) {
-        // Parse the numeric amount
-        let amount: i64 = captures[1]
-            .parse()
-            .map_err(|_| PbError::InvalidRelativeTimeFormat {
-                input: input.to_string(),
-            })?;
-
-        let unit = &captures[2];
-
-        // Validate range based on unit
-        let max_value = match unit {
-            "s" => 86400,        // Max 1 day worth of seconds
-            "m" => 999,          // Max 999 minutes
-            "h" => 999,          // Max 999 hours
-            "d" => 999,          // Max 999 days
-            _ => unreachable!(), // Regex ensures only valid units
-        };
-
-        if !(1..=max_value).contains(&amount) {
-            return Err(PbError::InvalidRelativeTimeFormat {
-                input: input.to_string(),
-            });
-        }
-
-        // Convert to seconds based on unit
-        let seconds = match unit {
-            "s" => amount,         // seconds
-            "m" => amount * 60,    // minutes to seconds
-            "h" => amount * 3600,  // hours to seconds
-            "d" => amount * 86400, // days to seconds
-            _ => {
-                return Err(PbError::InvalidRelativeTimeFormat {
-                    input: input.to_string(),
-                })
-            }
-        };
+    read_relative_time(input, base_time).map_err(|_| PbError::invalid_relative_time_format(input))
+}
 
-        // Add duration to base time with overflow checking
-        base_time
-            .checked_add_signed(Duration::seconds(seconds))
-            .ok_or_else(|| PbError::InvalidRelativeTimeFormat {
-                input: input.to_string(),
-            })
-    } else {
-        Err(PbError::InvalidRelativeTimeFormat {
-            input: input.to_string(),
-        })
-    }
+/// Read an offset such as `30m` from `base_time`, or say why `input` is not one
+fn read_relative_time(input: &str, base_time: NaiveDateTime) -> Result<NaiveDateTime, String> {
+    // Anything but digits followed by a unit is rejected without the regex
+    let bytes = input.as_bytes();
+    let looks_relative = bytes.first().is_some_and(u8::is_ascii_digit)
+        && matches!(bytes.last(), Some(b's' | b'm' | b'h' | b'd'));
+    let Some(captures) = looks_relative
+        .then(|| RELATIVE_TIME.captures(input))
+        .flatten()
+    else {
+        return Err("expected a number followed by s, m, h, or d".to_string());
+    };
+
+    let unit = &captures[2];
+
+    // Validate range based on unit
+    let (max_value, unit_seconds, unit_name) = match unit {
+        "s" => (86400, 1, "seconds"), // Max 1 day worth of seconds
+        "m" => (999, 60, "minutes"),  // Max 999 minutes
+        "h" => (999, 3600, "hours"),  // Max 999 hours
+        "d" => (999, 86400, "days"),  // Max 999 days
+        _ => return Err(format!("{unit} is not a unit")),
+    };
+
+    // Parse the numeric amount
+    let amount = captures[1]
+        .parse::<i64>()
+        .ok()
+        .filter(|amount| (1..=max_value).contains(amount))
+        .ok_or_else(|| format!("{unit_name} must be from 1 to {max_value}"))?;
+
+    // Add duration to base time with overflow checking
+    base_time
+        .checked_add_signed(Duration::seconds(amount * unit_seconds))
+        .ok_or_else(|| "the time it ends at is out of range".to_string())
 }
 
-/// Parse a time-only string in HH:MM:SS format
+/// Read a time-only string in HH:MM:SS format
 ///
-/// This function parses time strings in the format `HH:MM:SS` and converts
-/// them to `NaiveDateTime` using today's date as the date component.
+/// This function reads time strings in the format `HH:MM:SS` and converts
+/// them to `NaiveDateTime` on the given day.
 ///
 /// # Arguments
 ///
 /// * `input` - A string slice containing the time in HH:MM:SS format
+/// * `day` - The date the time of day falls on
 ///
 /// # Returns
 ///
-/// * `Ok(NaiveDateTime)` - Successfully parsed time with today's date
-/// * `Err(PbError)` - Invalid time format
-fn parse_time_only(input: &str) -> Result<NaiveDateTime, PbError> {
+/// * `Ok(NaiveDateTime)` - Successfully parsed time on `day`
+/// * `Err(String)` - Why `input` is not a time of day
+fn read_time_only(input: &str, day: NaiveDate) -> Result<NaiveDateTime, String> {
     // Validate that input contains only ASCII digits and colons
     if !input.chars().all(|c| c.is_ascii_digit() || c == ':') {
-        return Err(PbError::InvalidTimeFormat {
-            input: input.to_string(),
-        });
+        return Err("only digits and ':' are allowed".to_string());
     }
 
     // Validate seconds are not >= 60 before parsing
     let time_parts: Vec<&str> = input.split(':').collect();
     if time_parts.len() != 3 {
-        return Err(PbError::InvalidTimeFormat {
-            input: input.to_string(),
-        });
+        return Err("the time needs hours, minutes, and seconds".to_string());
     }
 
     // Check if seconds >= 60
     if let Ok(seconds) = time_parts[2].parse::<u32>() {
         if seconds >= 60 {
-            return Err(PbError::InvalidTimeFormat {
-                input: input.to_string(),
-            });
+            return Err(format!("{seconds} seconds is not below 60"));
         }
     }
 
     // Try to parse as time
     let time = chrono::NaiveTime::parse_from_str(input, "%H:%M:%S").map_err(|_| {
-        PbError::InvalidTimeFormat {
-            input: input.to_string(),
+        match (time_parts[0].parse::<u32>(), time_parts[1].parse::<u32>()) {
+            (Ok(hours), _) if hours >= 24 => format!("{hours} hours is not below 24"),
+            (_, Ok(minutes)) if minutes >= 60 => format!("{minutes} minutes is not below 60"),
+            _ => format!("{input} is not a valid time of day"),
         }
     })?;
 
-    // Use today's date (consistent with get_current_time)
-    let today = get_current_time().date();
-    Ok(today.and_time(time))
+    Ok(day.and_time(time))
+}
+
+/// What a relative end time such as `+2h` counts from
+///
+/// Relative ends count from the start by default, so
+/// `--start 09:00:00 --end 2h` ends at 11:00. With `Now` they count from the
+/// moment pmon is run, whatever the start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum RelativeAnchor {
+    /// Count from the start time
+    #[default]
+    Start,
+    /// Count from the current time
+    Now,
+}
+
+impl RelativeAnchor {
+    /// Base for a relative end in a window beginning at `start`, or `None`
+    /// for the current time
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::NaiveDateTime;
+    /// use pmon::time_parser::{parse_time_with_base, RelativeAnchor};
+    ///
+    /// let start = NaiveDateTime::parse_from_str("2025-01-27 09:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+    /// let end = parse_time_with_base("2h", RelativeAnchor::Start.base(start)).unwrap();
+    /// assert_eq!(end.to_string(), "2025-01-27 11:00:00");
+    /// assert_eq!(RelativeAnchor::Now.base(start), None);
+    /// ```
+    pub fn base(self, start: NaiveDateTime) -> Option<NaiveDateTime> {
+        match self {
+            Self::Start => Some(start),
+            Self::Now => None,
+        }
+    }
+}
+
+/// Formats tried in turn when an input is not a keyword, in the order
+/// their reasons for rejecting it are listed
+const FALLBACK_FORMATS: [TimeFormat; 4] = [
+    TimeFormat::Date,
+    TimeFormat::DateTime,
+    TimeFormat::TimeOnly,
+    TimeFormat::Relative,
+];
+
+/// Words that stand for a moment, resolved against the current time
+const KEYWORDS: &[&str] = &["now"];
+
+/// The format a time was written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeFormat {
+    /// `YYYY-MM-DD`, midnight at the start of that day
+    Date,
+    /// `YYYY-MM-DD HH:MM:SS`
+    DateTime,
+    /// `HH:MM:SS` today
+    TimeOnly,
+    /// An offset such as `+2h` or `30m`
+    Relative,
+    /// A word such as `now`
+    Keyword,
+}
+
+impl TimeFormat {
+    /// How inputs in the format are written, for error messages
+    pub fn pattern(self) -> &'static str {
+        match self {
+            Self::Date => "YYYY-MM-DD",
+            Self::DateTime => "YYYY-MM-DD HH:MM:SS",
+            Self::TimeOnly => "HH:MM:SS",
+            Self::Relative => "+30m, 2h, 1d",
+            Self::Keyword => "now",
+        }
+    }
+
+    /// Short name of the format in debug logs
+    fn log_name(self) -> &'static str {
+        match self {
+            Self::Date => "date",
+            Self::DateTime => "datetime",
+            Self::TimeOnly => "time",
+            Self::Relative => "relative",
+            Self::Keyword => "keyword",
+        }
+    }
+}
+
+impl std::fmt::Display for TimeFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Date => "date",
+            Self::DateTime => "date and time",
+            Self::TimeOnly => "time of day",
+            Self::Relative => "relative",
+            Self::Keyword => "keyword",
+        })
+    }
+}
+
+/// A parsed time along with how it was read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParsedTime {
+    /// The moment the input stands for
+    pub time: NaiveDateTime,
+    /// The format that matched
+    pub format: TimeFormat,
+    /// What the input was resolved against: the base of a relative time,
+    /// midnight of the day a time of day falls on, or the current time for a
+    /// keyword. Absolute dates and datetimes have none.
+    pub base: Option<NaiveDateTime>,
+}
+
+impl ParsedTime {
+    /// Short note on how the input was read, for confirmations, or `None`
+    /// when it was absolute
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::NaiveDateTime;
+    /// use pmon::time_parser::parse_time_detailed;
+    ///
+    /// let start = NaiveDateTime::parse_from_str("2025-01-27 14:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+    /// let parsed = parse_time_detailed("+2h", Some(start)).unwrap();
+    /// assert_eq!(parsed.describe().as_deref(), Some("relative to 2025-01-27 14:00:00"));
+    /// assert_eq!(parse_time_detailed("2025-01-27", None).unwrap().describe(), None);
+    /// ```
+    pub fn describe(&self) -> Option<String> {
+        let base = self.base?;
+        Some(match self.format {
+            TimeFormat::TimeOnly => format!("time of day on {}", base.date()),
+            TimeFormat::Keyword => format!("keyword, at {}", base.format("%Y-%m-%d %H:%M:%S")),
+            _ => format!("relative to {}", base.format("%Y-%m-%d %H:%M:%S")),
+        })
+    }
+}
+
+/// Which separators an input contains, found in one pass over its bytes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Shape {
+    space: bool,
+    colon: bool,
+    hyphen: bool,
+}
+
+impl Shape {
+    fn of(input: &str) -> Self {
+        input
+            .bytes()
+            .fold(Self::default(), |shape, byte| match byte {
+                b' ' => Self {
+                    space: true,
+                    ..shape
+                },
+                b':' => Self {
+                    colon: true,
+                    ..shape
+                },
+                b'-' => Self {
+                    hyphen: true,
+                    ..shape
+                },
+                _ => shape,
+            })
+    }
 }
 
 /// Parse a time string in any supported format
@@ -366,6 +515,9 @@ fn parse_time_only(input: &str) -> Result<NaiveDateTime, PbError> {
 /// - Date: "YYYY-MM-DD" (e.g., "2025-07-21")
 /// - DateTime: "YYYY-MM-DD HH:MM:SS" (e.g., "2025-07-21 10:30:00")
 /// - Relative: "+NNu" where NN is number and u is unit (s/m/h/d) (e.g., "+2h", "+30m")
+/// - Keyword: "now"
+///
+/// Use [`parse_time_detailed`] to also learn which format matched.
 ///
 /// # Arguments
 ///
@@ -430,41 +582,225 @@ pub fn parse_time_with_base(
     input: &str,
     base_time: Option<NaiveDateTime>,
 ) -> Result<NaiveDateTime, PbError> {
+    parse_time_detailed(input, base_time).map(|parsed| parsed.time)
+}
+
+/// Parse a time string and report which format matched and what it was
+/// resolved against
+///
+/// Accepts the same inputs as [`parse_time_with_base`]. The format the
+/// input looks like is tried first, then the others; when none reads it,
+/// the error lists why each format rejected it.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDateTime;
+/// use pmon::time_parser::{parse_time_detailed, TimeFormat};
+///
+/// let start = NaiveDateTime::parse_from_str("2025-01-27 14:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+///
+/// let parsed = parse_time_detailed("2h", Some(start)).unwrap();
+/// assert_eq!(parsed.format, TimeFormat::Relative);
+/// assert_eq!(parsed.base, Some(start));
+/// assert_eq!(parsed.time.to_string(), "2025-01-27 16:00:00");
+///
+/// let parsed = parse_time_detailed("2025-01-27 17:00:00", Some(start)).unwrap();
+/// assert_eq!(parsed.format, TimeFormat::DateTime);
+/// assert_eq!(parsed.base, None);
+/// ```
+pub fn parse_time_detailed(
+    input: &str,
+    base_time: Option<NaiveDateTime>,
+) -> Result<ParsedTime, PbError> {
     let trimmed_input = input.trim();
 
     if trimmed_input.is_empty() {
         return Err(PbError::invalid_time_format("Time cannot be empty"));
     }
 
-    // Check for relative time format (starts with + or -)
-    if trimmed_input.starts_with('+') || trimmed_input.starts_with('-') {
-        let base = base_time.unwrap_or_else(get_current_time);
-        let relative_input = if let Some(stripped) = trimmed_input.strip_prefix('+') {
-            stripped // Remove the '+' prefix
-        } else {
-            trimmed_input // Keep the '-' prefix for negative relative times
-        };
-        return parse_relative_time(relative_input, base);
+    if KEYWORDS.contains(&trimmed_input.to_ascii_lowercase().as_str()) {
+        debug!(input = trimmed_input, format = "keyword", "parsing time");
+        let now = get_current_time();
+        return Ok(ParsedTime {
+            time: now,
+            format: TimeFormat::Keyword,
+            base: Some(now),
+        });
     }
 
-    // Check if it looks like a datetime (contains space and colon)
-    if trimmed_input.contains(' ') && trimmed_input.contains(':') {
-        return parse_datetime(trimmed_input);
+    // Try the format the input looks like first, then the others
+    let shape = Shape::of(trimmed_input);
+    let likely = if matches!(trimmed_input.as_bytes().first(), Some(b'+' | b'-')) {
+        TimeFormat::Relative
+    } else if shape.space && shape.colon {
+        TimeFormat::DateTime
+    } else if shape.hyphen && !shape.space && !shape.colon {
+        TimeFormat::Date
+    } else if shape.colon && !shape.space && !shape.hyphen {
+        TimeFormat::TimeOnly
+    } else {
+        TimeFormat::Relative
+    };
+    let mut attempts = Vec::new();
+    let others = FALLBACK_FORMATS
+        .into_iter()
+        .filter(|&format| format != likely);
+    for format in std::iter::once(likely).chain(others) {
+        debug!(
+            input = trimmed_input,
+            format = format.log_name(),
+            "parsing time"
+        );
+        match read_as(format, trimmed_input, base_time) {
+            Ok(parsed) => return Ok(parsed),
+            Err(reason) => attempts.push((format, reason)),
+        }
     }
 
-    // Check if it looks like a date (contains hyphens but no space/colon)
-    if trimmed_input.contains('-') && !trimmed_input.contains(' ') && !trimmed_input.contains(':') {
-        return parse_date(trimmed_input);
+    // Every format rejected the input, so say why each did
+    attempts.sort_by_key(|(format, _)| FALLBACK_FORMATS.iter().position(|f| f == format));
+    let attempts = attempts
+        .into_iter()
+        .map(|(format, reason)| format!("{format} ({}): {reason}", format.pattern()))
+        .collect();
+    Err(PbError::unreadable_time(trimmed_input, attempts))
+}
+
+/// Read `input` in one `format`, or say why it is not written that way
+fn read_as(
+    format: TimeFormat,
+    input: &str,
+    base_time: Option<NaiveDateTime>,
+) -> Result<ParsedTime, String> {
+    let absolute = |time| ParsedTime {
+        time,
+        format,
+        base: None,
+    };
+    match format {
+        TimeFormat::Date => read_date(input).map(absolute),
+        TimeFormat::DateTime => read_datetime(input).map(absolute),
+        TimeFormat::TimeOnly => {
+            // Use today's date (consistent with get_current_time)
+            let today = get_current_time().date();
+            read_time_only(input, today).map(|time| ParsedTime {
+                time,
+                format,
+                base: Some(today.and_time(NaiveTime::MIN)),
+            })
+        }
+        TimeFormat::Relative => {
+            let base = base_time.unwrap_or_else(get_current_time);
+            // Remove the '+' prefix, keeping a '-' to be rejected
+            let offset = input.strip_prefix('+').unwrap_or(input);
+            read_relative_time(offset, base).map(|time| ParsedTime {
+                time,
+                format,
+                base: Some(base),
+            })
+        }
+        TimeFormat::Keyword => Err("only now is a keyword".to_string()),
     }
+}
 
-    // Check if it looks like a time-only format (contains colons but no space or hyphens)
-    if trimmed_input.contains(':') && !trimmed_input.contains(' ') && !trimmed_input.contains('-') {
-        return parse_time_only(trimmed_input);
+/// Parse a time string, refusing inputs that could mean several things
+///
+/// Bare numbers such as `1200` or `2025` match no format, and a typo for a
+/// time of day, an offset, or a date is easy to make. Strict parsing names
+/// the readings an input with several has instead of only rejecting it. An
+/// input with a single reading is rejected as by [`parse_time_detailed`],
+/// ending with that reading as a hint; other inputs are parsed as usual.
+///
+/// # Examples
+///
+/// ```
+/// use pmon::time_parser::parse_time_strict;
+///
+/// let error = parse_time_strict("2025", None).unwrap_err();
+/// assert_eq!(
+///     error.to_string(),
+///     "Ambiguous time 2025: did you mean 20:25:00 or 2025-01-01?"
+/// );
+/// let error = parse_time_strict("60", None).unwrap_err();
+/// assert!(error.to_string().ends_with("\n  did you mean +60m?"));
+/// assert!(parse_time_strict("+2h", None).is_ok());
+/// ```
+pub fn parse_time_strict(
+    input: &str,
+    base_time: Option<NaiveDateTime>,
+) -> Result<ParsedTime, PbError> {
+    match &ambiguous_readings(input)[..] {
+        [] => parse_time_detailed(input, base_time),
+        [reading] => match parse_time_detailed(input, base_time) {
+            Err(PbError::UnreadableTime {
+                input,
+                mut attempts,
+            }) => {
+                attempts.push(format!("did you mean {reading}?"));
+                Err(PbError::unreadable_time(input, attempts))
+            }
+            other => other,
+        },
+        readings => Err(PbError::ambiguous_time(input.trim(), readings)),
     }
+}
 
-    // If none of the above, try relative time without prefix (like "2h", "30m")
-    let base = base_time.unwrap_or_else(get_current_time);
-    parse_relative_time(trimmed_input, base)
+/// Plausible readings of a bare number, each written as an input that parses
+///
+/// A number of up to four digits can be a time of day (`1015` as
+/// `10:15:00`), any number can be an offset in minutes, four digits can be
+/// a year, and eight a compact date. Offsets take a single unit of at most
+/// 999, so minutes past 999 are only offered when they come to whole
+/// hours: `1200` reads as `+20h`, but `1015` has no offset reading. Anything
+/// but a bare number has no readings.
+///
+/// # Examples
+///
+/// ```
+/// use pmon::time_parser::ambiguous_readings;
+///
+/// assert_eq!(ambiguous_readings("1200"), ["12:00:00", "+20h"]);
+/// assert_eq!(ambiguous_readings("1015"), ["10:15:00"]);
+/// assert_eq!(ambiguous_readings("45"), ["+45m"]);
+/// assert!(ambiguous_readings("45m").is_empty());
+/// ```
+pub fn ambiguous_readings(input: &str) -> Vec<String> {
+    let digits = input.trim();
+    if digits.is_empty() || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Vec::new();
+    }
+    let Ok(number) = digits.parse::<u32>() else {
+        return Vec::new();
+    };
+
+    let mut readings = Vec::new();
+    // `9` and `17` as whole hours, `930` and `1015` as hours and minutes
+    let clock = match digits.len() {
+        1 | 2 => Some((number, 0)),
+        3 | 4 => Some((number / 100, number % 100)),
+        _ => None,
+    };
+    if let Some((hour, minute)) = clock.filter(|&(hour, minute)| hour < 24 && minute < 60) {
+        readings.push(format!("{hour:02}:{minute:02}:00"));
+    }
+    // Minutes, written as hours where minutes would pass what the relative
+    // parser allows
+    if (1..=999).contains(&number) {
+        readings.push(format!("+{number}m"));
+    } else if number % 60 == 0 && (1..=999).contains(&(number / 60)) {
+        readings.push(format!("+{}h", number / 60));
+    }
+    if digits.len() == 4 && (1970..=2999).contains(&number) {
+        readings.push(format!("{number}-01-01"));
+    }
+    if digits.len() == 8 {
+        let date = format!("{}-{}-{}", &digits[..4], &digits[4..6], &digits[6..]);
+        if parse_date(&date).is_ok() {
+            readings.push(date);
+        }
+    }
+    readings
 }
 
 /// Determine appropriate start time based on the end time format
@@ -507,7 +843,7 @@ pub fn determine_start_time_for_end(end_time_input: &str) -> NaiveDateTime {
     {
         // Looks like date-only format - use today at 00:00:00
         let today = get_current_time().date();
-        today.and_hms_opt(0, 0, 0).unwrap()
+        today.and_time(NaiveTime::MIN)
     } else {
         // For all other formats (datetime, time-only, relative), use current time
         get_current_time()
@@ -1357,4 +1693,179 @@ mod tests {
             "Relative time parsing took too long: {duration:?}"
         );
     }
+
+    #[test]
+    fn test_parse_time_detailed_formats() {
+        let base = parse_datetime("2025-01-27 14:00:00").unwrap();
+        let cases = [
+            ("2025-01-27", TimeFormat::Date, None),
+            ("2025-01-27 17:00:00", TimeFormat::DateTime, None),
+            ("+30m", TimeFormat::Relative, Some(base)),
+            ("1d", TimeFormat::Relative, Some(base)),
+        ];
+        for (input, format, expected_base) in cases {
+            let parsed = parse_time_detailed(input, Some(base)).unwrap();
+            assert_eq!(parsed.format, format, "{input}");
+            assert_eq!(parsed.base, expected_base, "{input}");
+            assert_eq!(
+                parsed.time,
+                parse_time_with_base(input, Some(base)).unwrap()
+            );
+        }
+
+        let parsed = parse_time_detailed("09:30:00", Some(base)).unwrap();
+        assert_eq!(parsed.format, TimeFormat::TimeOnly);
+        assert_eq!(
+            parsed.base,
+            Some(parsed.time.date().and_time(NaiveTime::MIN))
+        );
+        assert_eq!(
+            parsed.describe(),
+            Some(format!("time of day on {}", parsed.time.date()))
+        );
+
+        assert!(parse_time_detailed("soon", Some(base)).is_err());
+    }
+
+    #[test]
+    fn test_now_keyword() {
+        let before = get_current_time();
+        let parsed = parse_time_detailed(" NOW ", None).unwrap();
+        assert_eq!(parsed.format, TimeFormat::Keyword);
+        assert_eq!(parsed.base, Some(parsed.time));
+        assert!(parsed.time >= before);
+        // The current time, even when a base is given for relative inputs
+        let base = parse_datetime("2000-01-01 00:00:00").unwrap();
+        assert!(parse_time_with_base("now", Some(base)).unwrap() >= before);
+    }
+
+    #[test]
+    fn test_ambiguous_readings() {
+        assert_eq!(ambiguous_readings("9"), ["09:00:00", "+9m"]);
+        assert_eq!(ambiguous_readings("930"), ["09:30:00", "+930m"]);
+        assert_eq!(ambiguous_readings("2025"), ["20:25:00", "2025-01-01"]);
+        assert_eq!(ambiguous_readings("1970"), ["1970-01-01"]);
+        assert_eq!(ambiguous_readings("1260"), ["+21h"]);
+        assert_eq!(ambiguous_readings("1015"), ["10:15:00"]);
+        assert_eq!(ambiguous_readings("20250721"), ["2025-07-21"]);
+        assert!(ambiguous_readings("20251321").is_empty());
+        assert!(ambiguous_readings("99999999999").is_empty());
+        assert_eq!(ambiguous_readings("0"), ["00:00:00"]);
+        for input in ["", "+15", "10:15", "2025-01-01", "100000"] {
+            assert!(ambiguous_readings(input).is_empty(), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_strict_parsing() {
+        let error = parse_time_strict(" 1200 ", None).unwrap_err();
+        assert!(matches!(error, PbError::AmbiguousTime { .. }));
+        assert_eq!(
+            error.to_string(),
+            "Ambiguous time 1200: did you mean 12:00:00 or +20h?"
+        );
+        // A single reading is a hint, not an ambiguity
+        for (input, hint) in [("1015", "10:15:00"), ("60", "+60m"), ("0", "00:00:00")] {
+            let error = parse_time_strict(input, None).unwrap_err();
+            assert!(
+                matches!(error, PbError::UnreadableTime { .. }),
+                "{input}: {error:?}"
+            );
+            let message = error.to_string();
+            assert!(message.starts_with(&format!("Invalid time format: {input}\n")));
+            assert!(
+                message.ends_with(&format!("\n  did you mean {hint}?")),
+                "{message}"
+            );
+        }
+        // Without readings the usual error remains
+        assert!(matches!(
+            parse_time_strict("100000", None),
+            Err(PbError::UnreadableTime { .. })
+        ));
+        let base = parse_datetime("2025-01-27 14:00:00").unwrap();
+        assert_eq!(
+            parse_time_strict("90m", Some(base)).unwrap(),
+            parse_time_detailed("90m", Some(base)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_unreadable_time_explains_each_format() {
+        let error = parse_time_detailed(" 10:61:00 ", None).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid time format: 10:61:00\n  \
+             date (YYYY-MM-DD): only digits and '-' are allowed\n  \
+             date and time (YYYY-MM-DD HH:MM:SS): expected a date and a time separated by a space\n  \
+             time of day (HH:MM:SS): 61 minutes is not below 60\n  \
+             relative (+30m, 2h, 1d): expected a number followed by s, m, h, or d"
+        );
+
+        let reasons = |input| match parse_time_detailed(input, None) {
+            Err(PbError::UnreadableTime { attempts, .. }) => attempts,
+            other => panic!("Expected UnreadableTime for {input}, got {other:?}"),
+        };
+        assert_eq!(
+            reasons("2025-02-30")[0],
+            "date (YYYY-MM-DD): 2025-02-30 is not a day of the calendar"
+        );
+        assert_eq!(
+            reasons("25-07-21")[0],
+            "date (YYYY-MM-DD): the year 25 does not have four digits"
+        );
+        assert_eq!(
+            reasons("2025-07-21 24:00:00")[1],
+            "date and time (YYYY-MM-DD HH:MM:SS): 24 hours is not below 24"
+        );
+        assert_eq!(
+            reasons("+1000m")[3],
+            "relative (+30m, 2h, 1d): minutes must be from 1 to 999"
+        );
+    }
+
+    #[test]
+    fn test_relative_anchor() {
+        let start = parse_datetime("2000-01-01 09:00:00").unwrap();
+        let from_start = parse_time_with_base("+2h", RelativeAnchor::Start.base(start)).unwrap();
+        assert_eq!(from_start, parse_datetime("2000-01-01 11:00:00").unwrap());
+
+        let before = get_current_time();
+        let from_now = parse_time_detailed("+2h", RelativeAnchor::Now.base(start)).unwrap();
+        assert!(from_now.base.is_some_and(|base| base >= before));
+        assert!(from_now.time >= before + Duration::hours(2));
+        // Absolute ends are the same whatever the anchor
+        assert_eq!(
+            parse_time_with_base("2000-01-01 17:00:00", RelativeAnchor::Now.base(start)).unwrap(),
+            parse_time_with_base("2000-01-01 17:00:00", RelativeAnchor::Start.base(start)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_shape_of_input() {
+        let shape = |space, colon, hyphen| Shape {
+            space,
+            colon,
+            hyphen,
+        };
+        assert_eq!(Shape::of("2025-07-21 10:00:00"), shape(true, true, true));
+        assert_eq!(Shape::of("2025-07-21"), shape(false, false, true));
+        assert_eq!(Shape::of("10:00:00"), shape(false, true, false));
+        assert_eq!(Shape::of("30m"), Shape::default());
+    }
+
+    #[test]
+    fn test_relative_time_rejected_before_regex() {
+        let base_time =
+            NaiveDateTime::parse_from_str("2025-07-21 10:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        for input in ["", "m", "m30", "-5m", "30", "30x", " 30m"] {
+            assert!(
+                matches!(
+                    parse_relative_time(input, base_time),
+                    Err(PbError::InvalidRelativeTimeFormat { .. })
+                ),
+                "Expected '{input}' to be rejected"
+            );
+        }
+    }
 }
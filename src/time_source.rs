@@ -0,0 +1,276 @@
+//! Authoritative time sources for the pmon CLI tool
+//!
+//! By default progress is computed from the local clock. With
+//! `--time-source ntp://pool.ntp.org` pmon asks an NTP server for the time
+//! once at startup and applies the measured offset to every reading of
+//! [`get_current_time`](crate::time_parser::get_current_time), so a skewed
+//! local clock does not skew the progress bar.
+
+use crate::error::{PbError, PbResult};
+use chrono::Duration;
+#[cfg(feature = "net")]
+use chrono::{DateTime, Utc};
+#[cfg(feature = "net")]
+use std::net::UdpSocket;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicI64, Ordering};
+
+/// Option name used in error messages
+const OPTION_NAME: &str = "--time-source";
+
+/// Default NTP port
+const NTP_PORT: u16 = 123;
+
+/// Seconds between the NTP epoch (1900) and the Unix epoch (1970)
+#[cfg(feature = "net")]
+const NTP_UNIX_OFFSET_SECONDS: i64 = 2_208_988_800;
+
+/// Size of an NTP packet without extensions
+#[cfg(feature = "net")]
+const NTP_PACKET_SIZE: usize = 48;
+
+/// Offsets larger than this trigger a divergence warning
+pub const DRIFT_WARNING_THRESHOLD: Duration = Duration::seconds(1);
+
+/// Offset applied to the local clock, in milliseconds
+static CLOCK_OFFSET_MS: AtomicI64 = AtomicI64::new(0);
+
+/// Where the current time comes from
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum TimeSource {
+    /// The local system clock
+    #[default]
+    Local,
+    /// An NTP server, queried once at startup
+    Ntp { host: String, port: u16 },
+}
+
+impl TimeSource {
+    /// `host:port` of the server, or `local` for the system clock
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Local => "local".to_string(),
+            Self::Ntp { host, port } => format!("{host}:{port}"),
+        }
+    }
+
+    /// Measure how far the local clock is behind this source
+    ///
+    /// The result is added to local readings to get the source's time; the
+    /// local clock has a zero offset by definition.
+    pub fn measure_offset(&self, timeout: std::time::Duration) -> PbResult<Duration> {
+        match self {
+            Self::Local => Ok(Duration::zero()),
+            #[cfg(feature = "net")]
+            Self::Ntp { .. } => query_ntp_offset(&self.describe(), timeout),
+            #[cfg(not(feature = "net"))]
+            Self::Ntp { .. } => {
+                let _ = timeout;
+                Err(PbError::time_source_unavailable(
+                    self.describe(),
+                    "pmon was built without the `net` feature",
+                ))
+            }
+        }
+    }
+}
+
+impl FromStr for TimeSource {
+    type Err = PbError;
+
+    /// Parse `local` or `ntp://host[:port]`
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let invalid = || PbError::invalid_argument(OPTION_NAME, input);
+
+        if input.trim().eq_ignore_ascii_case("local") {
+            return Ok(Self::Local);
+        }
+
+        let address = input.trim().strip_prefix("ntp://").ok_or_else(invalid)?;
+        let address = address.trim_end_matches('/');
+        let (host, port) = match address.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse::<u16>().map_err(|_| invalid())?),
+            None => (address, NTP_PORT),
+        };
+        if host.is_empty() || host.contains('/') {
+            return Err(invalid());
+        }
+
+        Ok(Self::Ntp {
+            host: host.to_string(),
+            port,
+        })
+    }
+}
+
+/// Offset currently applied to the local clock
+pub fn clock_offset() -> Duration {
+    Duration::milliseconds(CLOCK_OFFSET_MS.load(Ordering::Relaxed))
+}
+
+/// Apply `offset` to every subsequent reading of the current time
+pub fn set_clock_offset(offset: Duration) {
+    CLOCK_OFFSET_MS.store(offset.num_milliseconds(), Ordering::Relaxed);
+}
+
+/// Query an NTP server and return the offset of its clock from the local one
+#[cfg(feature = "net")]
+fn query_ntp_offset(server: &str, timeout: std::time::Duration) -> PbResult<Duration> {
+    let unavailable =
+        |reason: std::io::Error| PbError::time_source_unavailable(server, reason.to_string());
+
+    let socket = UdpSocket::bind("0.0.0.0:0").map_err(unavailable)?;
+    socket
+        .set_read_timeout(Some(timeout))
+        .map_err(unavailable)?;
+    socket.connect(server).map_err(unavailable)?;
+
+    // LI = 0 (no warning), VN = 4, Mode = 3 (client)
+    let mut request = [0u8; NTP_PACKET_SIZE];
+    request[0] = 0b00_100_011;
+
+    let sent = Utc::now();
+    socket.send(&request).map_err(unavailable)?;
+    let mut response = [0u8; NTP_PACKET_SIZE];
+    let received_len = socket.recv(&mut response).map_err(unavailable)?;
+    let received = Utc::now();
+
+    if received_len < NTP_PACKET_SIZE {
+        return Err(PbError::time_source_unavailable(
+            server,
+            "short NTP response",
+        ));
+    }
+    let server_time = parse_ntp_timestamp(&response[40..48])
+        .ok_or_else(|| PbError::time_source_unavailable(server, "invalid NTP timestamp"))?;
+
+    // Assume a symmetric network path: the server read its clock halfway through
+    let midpoint = sent + (received - sent) / 2;
+    Ok(server_time - midpoint)
+}
+
+/// Decode a 64-bit NTP timestamp (seconds since 1900 plus a 32-bit fraction)
+#[cfg(feature = "net")]
+fn parse_ntp_timestamp(bytes: &[u8]) -> Option<DateTime<Utc>> {
+    let seconds = i64::from(u32::from_be_bytes(bytes.get(0..4)?.try_into().ok()?));
+    let fraction = u64::from(u32::from_be_bytes(bytes.get(4..8)?.try_into().ok()?));
+    if seconds == 0 {
+        return None;
+    }
+    let nanos = ((fraction * 1_000_000_000) >> 32) as u32;
+    DateTime::from_timestamp(seconds - NTP_UNIX_OFFSET_SECONDS, nanos)
+}
+
+/// Describe a clock offset for the divergence warning, or `None` when it is
+/// within [`DRIFT_WARNING_THRESHOLD`]
+///
+/// # Examples
+///
+/// ```
+/// use chrono::Duration;
+/// use pmon::time_source::drift_warning;
+///
+/// assert_eq!(drift_warning("pool.ntp.org:123", Duration::milliseconds(300)), None);
+/// assert_eq!(
+///     drift_warning("pool.ntp.org:123", Duration::milliseconds(-2500)),
+///     Some("Local clock is 2.5s ahead of pool.ntp.org:123".to_string())
+/// );
+/// ```
+pub fn drift_warning(server: &str, offset: Duration) -> Option<String> {
+    if offset.abs() <= DRIFT_WARNING_THRESHOLD {
+        return None;
+    }
+    let seconds = offset.num_milliseconds().abs() as f64 / 1000.0;
+    let direction = if offset > Duration::zero() {
+        "behind"
+    } else {
+        "ahead of"
+    };
+    Some(format!("Local clock is {seconds:.1}s {direction} {server}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_time_source() {
+        assert_eq!("local".parse::<TimeSource>().unwrap(), TimeSource::Local);
+        assert_eq!(
+            "ntp://pool.ntp.org".parse::<TimeSource>().unwrap(),
+            TimeSource::Ntp {
+                host: "pool.ntp.org".to_string(),
+                port: 123
+            }
+        );
+        assert_eq!(
+            "ntp://time.example.com:1123/"
+                .parse::<TimeSource>()
+                .unwrap(),
+            TimeSource::Ntp {
+                host: "time.example.com".to_string(),
+                port: 1123
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_time_source() {
+        for input in [
+            "",
+            "pool.ntp.org",
+            "http://pool.ntp.org",
+            "ntp://",
+            "ntp://host:port",
+            "ntp://a/b",
+        ] {
+            assert!(
+                matches!(
+                    input.parse::<TimeSource>(),
+                    Err(PbError::InvalidArgument { .. })
+                ),
+                "Expected '{input}' to be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(TimeSource::Local.describe(), "local");
+        let source: TimeSource = "ntp://pool.ntp.org".parse().unwrap();
+        assert_eq!(source.describe(), "pool.ntp.org:123");
+    }
+
+    #[test]
+    #[cfg(feature = "net")]
+    fn test_parse_ntp_timestamp() {
+        // 2025-01-27 00:00:00 UTC plus half a second
+        let seconds = (1_737_936_000 + NTP_UNIX_OFFSET_SECONDS) as u32;
+        let mut bytes = seconds.to_be_bytes().to_vec();
+        bytes.extend_from_slice(&0x8000_0000u32.to_be_bytes());
+
+        let time = parse_ntp_timestamp(&bytes).unwrap();
+        assert_eq!(time.timestamp(), 1_737_936_000);
+        assert_eq!(time.timestamp_subsec_millis(), 500);
+
+        assert!(parse_ntp_timestamp(&[0; 8]).is_none());
+        assert!(parse_ntp_timestamp(&[1; 4]).is_none());
+    }
+
+    #[test]
+    fn test_local_source_has_no_offset() {
+        let offset = TimeSource::Local
+            .measure_offset(std::time::Duration::from_millis(10))
+            .unwrap();
+        assert_eq!(offset, Duration::zero());
+    }
+
+    #[test]
+    fn test_drift_warning_direction() {
+        assert_eq!(drift_warning("ntp", Duration::seconds(1)), None);
+        assert_eq!(
+            drift_warning("ntp", Duration::seconds(3)),
+            Some("Local clock is 3.0s behind ntp".to_string())
+        );
+    }
+}
@@ -0,0 +1,207 @@
+//! Time zone aware durations for windows given in civil time
+//!
+//! Times are parsed as naive local ("civil") times. Subtracting two civil
+//! times is wrong across a daylight saving transition: a window from 00:00
+//! to 06:00 on a spring-forward night really lasts five hours, and on a
+//! fall-back night seven. With `--timezone Area/City` pmon maps civil times
+//! to UTC in that zone before measuring durations, so elapsed and total
+//! times account for 23- and 25-hour days.
+
+use crate::error::{PbError, PbResult};
+use chrono::{Duration, LocalResult, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::sync::OnceLock;
+
+/// Longest DST gap searched backwards when a civil time does not exist
+const MAX_GAP: Duration = Duration::hours(3);
+
+/// Zone configured with `--timezone`, if any
+static TIMEZONE: OnceLock<Tz> = OnceLock::new();
+
+/// Parse an IANA zone name such as `Europe/Berlin`
+///
+/// # Examples
+///
+/// ```
+/// use pmon::timezone::parse_timezone;
+///
+/// assert_eq!(parse_timezone("America/New_York").unwrap().name(), "America/New_York");
+/// assert!(parse_timezone("Mars/Olympus_Mons").is_err());
+/// ```
+pub fn parse_timezone(input: &str) -> PbResult<Tz> {
+    input
+        .trim()
+        .parse::<Tz>()
+        .map_err(|_| PbError::invalid_argument("--timezone", input))
+}
+
+/// Use `tz` for all civil time calculations in this process
+///
+/// Returns `false` if a zone was already configured.
+pub fn set_timezone(tz: Tz) -> bool {
+    TIMEZONE.set(tz).is_ok()
+}
+
+/// Zone configured with [`set_timezone`], if any
+pub fn configured_timezone() -> Option<Tz> {
+    TIMEZONE.get().copied()
+}
+
+/// Current civil time in `tz`
+pub fn now_in(tz: Tz) -> NaiveDateTime {
+    Utc::now().with_timezone(&tz).naive_local()
+}
+
+/// Convert a civil time in `tz` to UTC
+///
+/// Ambiguous times (repeated during a fall-back transition) resolve to the
+/// earlier instant. Times skipped by a spring-forward transition keep the
+/// offset in effect before the gap, so `02:30` in a one-hour gap maps to
+/// the same instant as `03:30` after it.
+pub fn civil_to_utc(tz: Tz, civil: NaiveDateTime) -> NaiveDateTime {
+    match tz.from_local_datetime(&civil) {
+        LocalResult::Single(time) | LocalResult::Ambiguous(time, _) => time.naive_utc(),
+        LocalResult::None => {
+            let offset = tz
+                .from_local_datetime(&(civil - MAX_GAP))
+                .earliest()
+                .map(|before| before.offset().fix().local_minus_utc())
+                .unwrap_or(0);
+            civil - Duration::seconds(i64::from(offset))
+        }
+    }
+}
+
+/// Real time between two civil times in `tz`
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{Duration, NaiveDateTime};
+/// use pmon::timezone::{civil_duration_in, parse_timezone};
+///
+/// let at = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+/// let berlin = parse_timezone("Europe/Berlin").unwrap();
+///
+/// // Clocks sprang forward at 02:00 on 2025-03-30
+/// let night = civil_duration_in(berlin, at("2025-03-30 00:00:00"), at("2025-03-30 06:00:00"));
+/// assert_eq!(night, Duration::hours(5));
+/// ```
+pub fn civil_duration_in(tz: Tz, from: NaiveDateTime, to: NaiveDateTime) -> Duration {
+    civil_to_utc(tz, to) - civil_to_utc(tz, from)
+}
+
+/// Time between two civil times, honoring the configured zone if any
+///
+/// Without `--timezone` this is plain subtraction, as before.
+pub fn civil_duration(from: NaiveDateTime, to: NaiveDateTime) -> Duration {
+    match configured_timezone() {
+        Some(tz) => civil_duration_in(tz, from, to),
+        None => to - from,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::progress_bar::progress_from_durations;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn new_york() -> Tz {
+        parse_timezone("America/New_York").unwrap()
+    }
+
+    #[test]
+    fn test_parse_timezone() {
+        assert!(parse_timezone(" Asia/Tokyo ").is_ok());
+        assert!(matches!(
+            parse_timezone("Nowhere/Special"),
+            Err(PbError::InvalidArgument { .. })
+        ));
+    }
+
+    #[test]
+    fn test_spring_forward_day_is_23_hours() {
+        // US clocks sprang forward at 02:00 on 2025-03-09
+        let day = civil_duration_in(
+            new_york(),
+            create_test_datetime("2025-03-09 00:00:00"),
+            create_test_datetime("2025-03-10 00:00:00"),
+        );
+        assert_eq!(day, Duration::hours(23));
+    }
+
+    #[test]
+    fn test_fall_back_day_is_25_hours() {
+        // US clocks fell back at 02:00 on 2025-11-02
+        let day = civil_duration_in(
+            new_york(),
+            create_test_datetime("2025-11-02 00:00:00"),
+            create_test_datetime("2025-11-03 00:00:00"),
+        );
+        assert_eq!(day, Duration::hours(25));
+    }
+
+    #[test]
+    fn test_progress_across_spring_forward() {
+        let tz = new_york();
+        let start = create_test_datetime("2025-03-09 00:00:00");
+        let end = create_test_datetime("2025-03-09 06:00:00");
+        let current = create_test_datetime("2025-03-09 04:00:00");
+
+        // 3 real hours of a 5-hour window, not 4 of 6
+        let progress = progress_from_durations(
+            civil_duration_in(tz, start, end),
+            civil_duration_in(tz, start, current),
+        );
+        assert!((progress - 60.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_progress_across_fall_back() {
+        let tz = new_york();
+        let start = create_test_datetime("2025-11-02 00:00:00");
+        let end = create_test_datetime("2025-11-02 06:00:00");
+        let current = create_test_datetime("2025-11-02 03:30:00");
+
+        // 4.5 real hours of a 7-hour window
+        let progress = progress_from_durations(
+            civil_duration_in(tz, start, end),
+            civil_duration_in(tz, start, current),
+        );
+        assert!((progress - 4.5 / 7.0 * 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_nonexistent_time_uses_offset_before_gap() {
+        let tz = new_york();
+        // 02:30 does not exist on 2025-03-09; it lines up with 03:30 EDT
+        assert_eq!(
+            civil_to_utc(tz, create_test_datetime("2025-03-09 02:30:00")),
+            civil_to_utc(tz, create_test_datetime("2025-03-09 03:30:00"))
+        );
+    }
+
+    #[test]
+    fn test_ambiguous_time_uses_earlier_instant() {
+        let tz = new_york();
+        // 01:30 happens twice on 2025-11-02; the first is EDT (UTC-4)
+        assert_eq!(
+            civil_to_utc(tz, create_test_datetime("2025-11-02 01:30:00")),
+            create_test_datetime("2025-11-02 05:30:00")
+        );
+    }
+
+    #[test]
+    fn test_ordinary_day_is_unchanged() {
+        let duration = civil_duration_in(
+            new_york(),
+            create_test_datetime("2025-06-01 09:00:00"),
+            create_test_datetime("2025-06-01 17:00:00"),
+        );
+        assert_eq!(duration, Duration::hours(8));
+    }
+}
@@ -0,0 +1,122 @@
+//! A bar for the current day beneath multi-day windows
+//!
+//! A sprint or semester bar barely moves from one hour to the next. With
+//! `--today`, pmon draws a second bar beneath the window's showing how far
+//! the current day has come, and `--day-hours 09:00-17:30` narrows that day
+//! to a working block. The window's time information stays on the top line
+//! and serves both bars.
+
+use crate::chain::Segment;
+use crate::error::{PbError, PbResult};
+use chrono::{Duration, NaiveDateTime, NaiveTime};
+
+/// Label of the day bar
+pub const TODAY_LABEL: &str = "Today";
+
+/// Option name used in error messages
+const OPTION_NAME: &str = "day hours";
+
+/// The block of each day the day bar measures, such as `09:00-17:30`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DayHours {
+    /// When the block starts
+    pub start: NaiveTime,
+    /// When the block ends, later the same day
+    pub end: NaiveTime,
+}
+
+/// Parse a `--day-hours` value written as `HH:MM-HH:MM`
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveTime;
+/// use pmon::today::parse_day_hours;
+///
+/// let hours = parse_day_hours("09:00-17:30").unwrap();
+/// assert_eq!(hours.start, NaiveTime::from_hms_opt(9, 0, 0).unwrap());
+/// assert_eq!(hours.end, NaiveTime::from_hms_opt(17, 30, 0).unwrap());
+/// assert!(parse_day_hours("17:00-09:00").is_err());
+/// ```
+pub fn parse_day_hours(input: &str) -> PbResult<DayHours> {
+    let invalid = || PbError::invalid_argument(OPTION_NAME, input);
+    let (start, end) = input.trim().split_once('-').ok_or_else(invalid)?;
+    let time = |text: &str| NaiveTime::parse_from_str(text.trim(), "%H:%M").map_err(|_| invalid());
+    let (start, end) = (time(start)?, time(end)?);
+    // Blocks past midnight would belong to two days
+    if start >= end {
+        return Err(invalid());
+    }
+    Ok(DayHours { start, end })
+}
+
+/// The day, or the block of it given by `hours`, that `now` falls on
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDateTime;
+/// use pmon::today::{parse_day_hours, today_window};
+///
+/// let at = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+/// let now = at("2025-07-14 13:00:00");
+///
+/// let day = today_window(now, None);
+/// assert_eq!((day.start, day.end), (at("2025-07-14 00:00:00"), at("2025-07-15 00:00:00")));
+///
+/// let block = today_window(now, Some(parse_day_hours("09:00-17:00").unwrap()));
+/// assert_eq!((block.start, block.end), (at("2025-07-14 09:00:00"), at("2025-07-14 17:00:00")));
+/// ```
+pub fn today_window(now: NaiveDateTime, hours: Option<DayHours>) -> Segment {
+    let day = now.date();
+    let (start, end) = match hours {
+        Some(hours) => (day.and_time(hours.start), day.and_time(hours.end)),
+        None => {
+            let midnight = day.and_time(NaiveTime::MIN);
+            (midnight, midnight + Duration::days(1))
+        }
+    };
+    Segment {
+        label: Some(TODAY_LABEL.to_string()),
+        start,
+        end,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_parse_day_hours() {
+        let hours = parse_day_hours(" 08:30 - 12:00 ").unwrap();
+        assert_eq!(hours.start, NaiveTime::from_hms_opt(8, 30, 0).unwrap());
+        assert_eq!(hours.end, NaiveTime::from_hms_opt(12, 0, 0).unwrap());
+
+        for input in [
+            "",
+            "09:00",
+            "09:00-09:00",
+            "22:00-06:00",
+            "9am-5pm",
+            "25:00-26:00",
+        ] {
+            assert!(parse_day_hours(input).is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_today_window_follows_the_clock() {
+        let hours = parse_day_hours("09:00-17:00").unwrap();
+        // Outside the block the bar is empty or full, like any other window
+        let early = today_window(create_test_datetime("2025-07-14 07:00:00"), Some(hours));
+        assert_eq!(early.start, create_test_datetime("2025-07-14 09:00:00"));
+        let late = today_window(create_test_datetime("2025-07-14 23:59:59"), None);
+        assert_eq!(late.end, create_test_datetime("2025-07-15 00:00:00"));
+        assert_eq!(late.label.as_deref(), Some(TODAY_LABEL));
+    }
+}
@@ -0,0 +1,188 @@
+//! Warnings about windows that are probably not what was meant
+//!
+//! Some windows parse fine but look like mistakes: an end more than a year
+//! away, an update interval longer than the whole window, or a start equal
+//! to the end. pmon prints these in yellow on stderr and runs anyway, unless
+//! `--strict` turns them into errors.
+//!
+//! An interval longer than the window would leave the bar standing still,
+//! so it is clamped to a [`CLAMPED_REDRAWS`]th of the window, and a 30s
+//! window with the 60s default interval still animates. `--no-clamp` keeps
+//! the interval as given. Interval warnings only concern an interval given
+//! with `--interval`, and `--strict` never rejects a clamped one, as there
+//! is nothing left to fix.
+
+use chrono::{Duration, NaiveDateTime};
+use std::fmt;
+
+/// Ends further away than this are likely a typo in the year
+pub const FAR_END: Duration = Duration::days(365);
+
+/// Redraws a clamped interval allows over the window
+pub const CLAMPED_REDRAWS: i32 = 30;
+
+/// Something suspicious about a window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowWarning {
+    /// The end is more than [`FAR_END`] from now
+    FarEnd { days: i64 },
+    /// The update interval is longer than the whole window
+    IntervalExceedsWindow {
+        interval: Duration,
+        window: Duration,
+    },
+    /// The update interval was longer than the whole window and is clamped
+    IntervalClamped {
+        interval: Duration,
+        window: Duration,
+        clamped: Duration,
+    },
+    /// The window has no length
+    StartEqualsEnd,
+}
+
+impl fmt::Display for WindowWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FarEnd { days } => write!(f, "The end is {days} days away; check the year"),
+            Self::IntervalExceedsWindow { interval, window } => write!(
+                f,
+                "The update interval ({}s) is longer than the whole window ({}s), so the bar will barely move",
+                interval.num_seconds(),
+                window.num_seconds()
+            ),
+            Self::IntervalClamped {
+                interval,
+                window,
+                clamped,
+            } => write!(
+                f,
+                "The update interval ({}s) is longer than the whole window ({}s), so the bar is redrawn every {}s instead",
+                interval.num_seconds(),
+                window.num_seconds(),
+                clamped.num_seconds()
+            ),
+            Self::StartEqualsEnd => {
+                write!(f, "The start equals the end, so the window is already over")
+            }
+        }
+    }
+}
+
+/// Warnings for a window from `start` to `end` seen at `now`, redrawn every
+/// `interval` if it is redrawn on a schedule
+///
+/// The interval only matters while the window is still running. With
+/// `clamp`, one longer than the window is reported with the interval to
+/// use instead, from [`clamp_interval`].
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{Duration, NaiveDateTime};
+/// use pmon::warnings::{window_warnings, WindowWarning};
+///
+/// let at = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+/// let now = at("2025-03-10 09:00:00");
+/// let minute = Some(Duration::seconds(60));
+/// let warnings = window_warnings(now, at("2026-03-10 09:00:00"), minute, true, now);
+/// assert!(warnings.is_empty());
+/// let warnings = window_warnings(now, at("2027-03-10 09:00:00"), None, true, now);
+/// assert_eq!(warnings, [WindowWarning::FarEnd { days: 730 }]);
+/// ```
+pub fn window_warnings(
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    interval: Option<Duration>,
+    clamp: bool,
+    now: NaiveDateTime,
+) -> Vec<WindowWarning> {
+    let mut warnings = Vec::new();
+    if end - now > FAR_END {
+        warnings.push(WindowWarning::FarEnd {
+            days: (end - now).num_days(),
+        });
+    }
+    let window = end - start;
+    if window.is_zero() {
+        warnings.push(WindowWarning::StartEqualsEnd);
+    } else if let Some(interval) = interval.filter(|&interval| interval > window && end > now) {
+        warnings.push(if clamp {
+            WindowWarning::IntervalClamped {
+                interval,
+                window,
+                clamped: clamp_interval(window),
+            }
+        } else {
+            WindowWarning::IntervalExceedsWindow { interval, window }
+        });
+    }
+    warnings
+}
+
+/// Interval redrawing a window of length `window` [`CLAMPED_REDRAWS`]
+/// times, in whole seconds and at least one
+///
+/// # Examples
+///
+/// ```
+/// use chrono::Duration;
+/// use pmon::warnings::clamp_interval;
+///
+/// assert_eq!(clamp_interval(Duration::seconds(30)), Duration::seconds(1));
+/// assert_eq!(clamp_interval(Duration::minutes(10)), Duration::seconds(20));
+/// ```
+pub fn clamp_interval(window: Duration) -> Duration {
+    Duration::seconds((window / CLAMPED_REDRAWS).num_seconds().max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_window_warnings() {
+        let now = create_test_datetime("2025-03-10 09:00:00");
+        let minute = Some(Duration::seconds(60));
+
+        let end = create_test_datetime("2025-03-10 09:00:30");
+        let warnings = window_warnings(now, end, minute, false, now);
+        assert_eq!(
+            warnings,
+            [WindowWarning::IntervalExceedsWindow {
+                interval: Duration::seconds(60),
+                window: Duration::seconds(30),
+            }]
+        );
+        assert_eq!(
+            warnings[0].to_string(),
+            "The update interval (60s) is longer than the whole window (30s), so the bar will barely move"
+        );
+        assert!(window_warnings(now, end, None, false, now).is_empty());
+        // Nothing is left to redraw once the window is over
+        let later = create_test_datetime("2025-03-10 10:00:00");
+        assert!(window_warnings(now, end, minute, false, later).is_empty());
+
+        let warnings = window_warnings(now, end, minute, true, now);
+        assert_eq!(
+            warnings[0].to_string(),
+            "The update interval (60s) is longer than the whole window (30s), so the bar is redrawn every 1s instead"
+        );
+
+        // An empty window is reported once, not also as shorter than the interval
+        let warnings = window_warnings(now, now, minute, false, now);
+        assert_eq!(warnings, [WindowWarning::StartEqualsEnd]);
+
+        let end = create_test_datetime("2035-03-10 09:00:00");
+        let warnings = window_warnings(now, end, minute, false, now);
+        assert_eq!(warnings, [WindowWarning::FarEnd { days: 3652 }]);
+        assert_eq!(
+            warnings[0].to_string(),
+            "The end is 3652 days away; check the year"
+        );
+    }
+}
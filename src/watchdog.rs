@@ -0,0 +1,105 @@
+//! Safety limit on how long pmon may run
+//!
+//! A pmon left running on a server, say watching a window that ended weeks
+//! ago or a stopwatch nobody stopped, never exits on its own. With
+//! `--max-runtime 48h`, a watchdog ends the process once that much
+//! wall-clock time has passed, whatever the progress, logs why on stderr,
+//! and exits with [`MAX_RUNTIME_EXIT_CODE`] so supervisors can tell it apart
+//! from a normal exit. A command given after `--` is not signaled; use
+//! `--on-timeout` to bound it.
+
+use crate::progress_bar::{format_duration_with, DurationStyle};
+use chrono::Duration;
+use std::time::SystemTime;
+
+/// Exit code used when `--max-runtime` ends the process
+pub const MAX_RUNTIME_EXIT_CODE: i32 = 124;
+
+/// Longest the watchdog sleeps between checks of the wall clock
+///
+/// Checking often keeps the limit accurate across a suspended machine,
+/// during which the monotonic clock behind `sleep` stands still.
+pub const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// A wall-clock bound on the lifetime of the process
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaxRuntime {
+    limit: Duration,
+    started: SystemTime,
+}
+
+impl MaxRuntime {
+    /// Allow `limit` from `started`
+    pub fn new(limit: Duration, started: SystemTime) -> Self {
+        Self { limit, started }
+    }
+
+    /// Time left before the limit, or `None` once it has been reached
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::Duration;
+    /// use pmon::watchdog::MaxRuntime;
+    /// use std::time::SystemTime;
+    ///
+    /// let started = SystemTime::now();
+    /// let max_runtime = MaxRuntime::new(Duration::hours(48), started);
+    /// let later = started + std::time::Duration::from_secs(47 * 3600);
+    /// assert_eq!(max_runtime.remaining(later), Some(std::time::Duration::from_secs(3600)));
+    /// ```
+    pub fn remaining(&self, now: SystemTime) -> Option<std::time::Duration> {
+        let limit = self.limit.to_std().unwrap_or_default();
+        // A clock set back before the start counts as no time passed
+        let elapsed = now.duration_since(self.started).unwrap_or_default();
+        limit.checked_sub(elapsed).filter(|left| !left.is_zero())
+    }
+
+    /// Line logged when the limit ends the process
+    pub fn message(&self) -> String {
+        format!(
+            "pmon: maximum runtime of {} reached; exiting with code {MAX_RUNTIME_EXIT_CODE}",
+            format_duration_with(self.limit, DurationStyle::Colon)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limit_is_reached() {
+        let started = SystemTime::now();
+        let max_runtime = MaxRuntime::new(Duration::minutes(5), started);
+        let after = |seconds| started + std::time::Duration::from_secs(seconds);
+
+        assert_eq!(
+            max_runtime.remaining(started),
+            Some(std::time::Duration::from_secs(300))
+        );
+        assert!(max_runtime.remaining(after(299)).is_some());
+        assert_eq!(max_runtime.remaining(after(300)), None);
+        assert_eq!(max_runtime.remaining(after(3600)), None);
+    }
+
+    #[test]
+    fn test_clock_set_back_keeps_limit() {
+        let started = SystemTime::now();
+        let max_runtime = MaxRuntime::new(Duration::minutes(5), started);
+        let earlier = started - std::time::Duration::from_secs(60);
+        assert_eq!(
+            max_runtime.remaining(earlier),
+            Some(std::time::Duration::from_secs(300))
+        );
+    }
+
+    #[test]
+    fn test_message() {
+        let max_runtime = MaxRuntime::new(Duration::minutes(90), SystemTime::now());
+        assert_eq!(
+            max_runtime.message(),
+            "pmon: maximum runtime of 01:30:00 reached; exiting with code 124"
+        );
+    }
+}
@@ -0,0 +1,458 @@
+//! Wrapping a child command in a time budget
+//!
+//! `pmon --end +10m -- make test` runs `make test` while showing how much of
+//! the ten-minute budget it has used. When the budget runs out the child is
+//! signaled according to `--on-timeout`, and pmon exits with the child's
+//! status, which turns pmon into a visual `timeout`.
+//!
+//! `--on-timeout signal=TERM,grace=30s,then=KILL` asks politely first and
+//! escalates if the command is still running once the grace period ends.
+
+use crate::error::PbError;
+use crate::time_parser::parse_relative_time;
+use chrono::{DateTime, Duration};
+use std::process::{Child, ExitStatus};
+use std::str::FromStr;
+use std::time::Instant;
+
+/// Option name used in error messages
+const OPTION_NAME: &str = "--on-timeout";
+
+/// Grace period used when `then=` is given without `grace=`
+pub const DEFAULT_GRACE: Duration = Duration::seconds(10);
+
+/// Exit code used when the wrapped command cannot be started
+pub const SPAWN_FAILURE_EXIT_CODE: i32 = 127;
+
+/// Signals that can be sent to a wrapped command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    /// SIGHUP
+    Hup,
+    /// SIGINT
+    Int,
+    /// SIGQUIT
+    Quit,
+    /// SIGKILL
+    Kill,
+    /// SIGTERM
+    Term,
+    /// SIGUSR1
+    Usr1,
+    /// SIGUSR2
+    Usr2,
+}
+
+impl Signal {
+    /// Conventional name without the `SIG` prefix, e.g. `TERM`
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Hup => "HUP",
+            Self::Int => "INT",
+            Self::Quit => "QUIT",
+            Self::Kill => "KILL",
+            Self::Term => "TERM",
+            Self::Usr1 => "USR1",
+            Self::Usr2 => "USR2",
+        }
+    }
+
+    /// Platform signal number
+    #[cfg(unix)]
+    fn number(self) -> libc::c_int {
+        match self {
+            Self::Hup => libc::SIGHUP,
+            Self::Int => libc::SIGINT,
+            Self::Quit => libc::SIGQUIT,
+            Self::Kill => libc::SIGKILL,
+            Self::Term => libc::SIGTERM,
+            Self::Usr1 => libc::SIGUSR1,
+            Self::Usr2 => libc::SIGUSR2,
+        }
+    }
+}
+
+impl FromStr for Signal {
+    type Err = PbError;
+
+    /// Parse a signal name such as `TERM`, `SIGTERM`, or `term`
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let upper = input.trim().to_ascii_uppercase();
+        let name = upper.strip_prefix("SIG").unwrap_or(&upper);
+        match name {
+            "HUP" => Ok(Self::Hup),
+            "INT" => Ok(Self::Int),
+            "QUIT" => Ok(Self::Quit),
+            "KILL" => Ok(Self::Kill),
+            "TERM" => Ok(Self::Term),
+            "USR1" => Ok(Self::Usr1),
+            "USR2" => Ok(Self::Usr2),
+            _ => Err(PbError::invalid_argument(OPTION_NAME, input)),
+        }
+    }
+}
+
+/// What to do with a wrapped command when its time budget runs out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnTimeout {
+    /// Send a signal to the command
+    Signal(Signal),
+    /// Send `signal`, then `then` if the command outlives the grace period
+    Escalate {
+        signal: Signal,
+        grace: Duration,
+        then: Signal,
+    },
+    /// Let the command keep running and show the overtime
+    Ignore,
+}
+
+impl Default for OnTimeout {
+    /// SIGTERM, like `timeout(1)`
+    fn default() -> Self {
+        Self::Signal(Signal::Term)
+    }
+}
+
+impl FromStr for OnTimeout {
+    type Err = PbError;
+
+    /// Parse a signal name, `none`, or `signal=SIG,grace=DURATION,then=SIG`
+    ///
+    /// In the key/value form every key is optional: `signal` defaults to
+    /// TERM, and giving either `grace` or `then` enables escalation with
+    /// [`DEFAULT_GRACE`] and KILL filling in the other.
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let invalid = || PbError::invalid_argument(OPTION_NAME, input);
+
+        if input.trim().eq_ignore_ascii_case("none") {
+            return Ok(Self::Ignore);
+        }
+        if !input.contains('=') {
+            return input.parse().map(Self::Signal);
+        }
+
+        let mut signal = Signal::Term;
+        let mut grace = None;
+        let mut then = None;
+        for pair in input.split(',') {
+            let (key, value) = pair.split_once('=').ok_or_else(invalid)?;
+            match key.trim().to_ascii_lowercase().as_str() {
+                "signal" => signal = value.parse().map_err(|_| invalid())?,
+                "grace" => grace = Some(parse_grace(value).ok_or_else(invalid)?),
+                "then" => then = Some(value.parse().map_err(|_| invalid())?),
+                _ => return Err(invalid()),
+            }
+        }
+
+        Ok(match (grace, then) {
+            (None, None) => Self::Signal(signal),
+            (grace, then) => Self::Escalate {
+                signal,
+                grace: grace.unwrap_or(DEFAULT_GRACE),
+                then: then.unwrap_or(Signal::Kill),
+            },
+        })
+    }
+}
+
+/// Parse a grace period such as `30s` or `2m`
+fn parse_grace(input: &str) -> Option<Duration> {
+    let base = DateTime::UNIX_EPOCH.naive_utc();
+    parse_relative_time(input.trim(), base)
+        .ok()
+        .map(|end| end - base)
+}
+
+/// Where a wrapped command is in the timeout escalation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeoutStage {
+    /// The window has not ended, or `--on-timeout none` is in effect
+    Running,
+    /// `signal` was sent and the command has until `deadline` to exit
+    Grace { signal: Signal, deadline: Instant },
+    /// The final signal was sent
+    Signaled(Signal),
+}
+
+/// Applies an [`OnTimeout`] policy to a wrapped command over time
+#[derive(Debug, Clone)]
+pub struct TimeoutEscalation {
+    policy: OnTimeout,
+    stage: TimeoutStage,
+    sent: Vec<Signal>,
+    overran: bool,
+}
+
+impl TimeoutEscalation {
+    /// Start tracking a command governed by `policy`
+    pub fn new(policy: OnTimeout) -> Self {
+        Self {
+            policy,
+            stage: TimeoutStage::Running,
+            sent: Vec::new(),
+            overran: false,
+        }
+    }
+
+    /// Current stage of the escalation
+    pub fn stage(&self) -> TimeoutStage {
+        self.stage
+    }
+
+    /// Signals sent so far, in order
+    pub fn sent(&self) -> &[Signal] {
+        &self.sent
+    }
+
+    /// Whether the command was still running when its time was up, signaled
+    /// or not
+    pub fn overran(&self) -> bool {
+        self.overran
+    }
+
+    /// Advance the escalation and return the signal to send now, if any
+    ///
+    /// `window_complete` reports whether the time budget is exhausted.
+    pub fn advance(&mut self, window_complete: bool, now: Instant) -> Option<Signal> {
+        self.overran |= window_complete;
+        let signal = match (self.stage, self.policy) {
+            (TimeoutStage::Running, _) if !window_complete => return None,
+            (TimeoutStage::Running, OnTimeout::Signal(signal)) => {
+                self.stage = TimeoutStage::Signaled(signal);
+                signal
+            }
+            (TimeoutStage::Running, OnTimeout::Escalate { signal, grace, .. }) => {
+                let grace = grace.to_std().unwrap_or_default();
+                self.stage = TimeoutStage::Grace {
+                    signal,
+                    deadline: now + grace,
+                };
+                signal
+            }
+            (TimeoutStage::Grace { deadline, .. }, OnTimeout::Escalate { then, .. })
+                if now >= deadline =>
+            {
+                self.stage = TimeoutStage::Signaled(then);
+                then
+            }
+            _ => return None,
+        };
+        self.sent.push(signal);
+        Some(signal)
+    }
+
+    /// Earliest moment [`advance`](Self::advance) may need to run again
+    pub fn next_deadline(&self) -> Option<Instant> {
+        match self.stage {
+            TimeoutStage::Grace { deadline, .. } => Some(deadline),
+            _ => None,
+        }
+    }
+
+    /// Describe the escalation for the completion summary, e.g.
+    /// `sent SIGTERM, then SIGKILL after a 30s grace period`, or
+    /// `not signaled` for an overrun under `--on-timeout none`
+    ///
+    /// Returns `None` while the command has not run out of time.
+    pub fn summary(&self) -> Option<String> {
+        let names: Vec<String> = self
+            .sent
+            .iter()
+            .map(|signal| format!("SIG{}", signal.name()))
+            .collect();
+        match (names.as_slice(), self.policy) {
+            ([], _) if self.overran => Some("not signaled".to_string()),
+            ([], _) => None,
+            ([first, then], OnTimeout::Escalate { grace, .. }) => Some(format!(
+                "sent {first}, then {then} after a {}s grace period",
+                grace.num_seconds()
+            )),
+            (names, _) => Some(format!("sent {}", names.join(", "))),
+        }
+    }
+}
+
+/// Send `signal` to a running child
+///
+/// On platforms without signals every signal terminates the child.
+pub fn send_signal(child: &mut Child, signal: Signal) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        let pid = libc::pid_t::try_from(child.id())
+            .map_err(|_| std::io::Error::other("process id out of range"))?;
+        // SAFETY: kill(2) has no memory-safety requirements; the pid belongs
+        // to a child we have not yet reaped, so it cannot have been reused.
+        if unsafe { libc::kill(pid, signal.number()) } == 0 {
+            Ok(())
+        } else {
+            Err(std::io::Error::last_os_error())
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = signal;
+        child.kill()
+    }
+}
+
+/// Exit code to propagate for a finished child
+///
+/// A child killed by a signal reports `128 + N`, the same as a shell.
+pub fn exit_code(status: ExitStatus) -> i32 {
+    if let Some(code) = status.code() {
+        return code;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_signal() {
+        assert_eq!("TERM".parse::<Signal>().unwrap(), Signal::Term);
+        assert_eq!("sigkill".parse::<Signal>().unwrap(), Signal::Kill);
+        assert_eq!(" usr1 ".parse::<Signal>().unwrap(), Signal::Usr1);
+        assert!(matches!(
+            "STOP".parse::<Signal>(),
+            Err(PbError::InvalidArgument { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_on_timeout() {
+        assert_eq!(OnTimeout::default(), OnTimeout::Signal(Signal::Term));
+        assert_eq!(
+            "KILL".parse::<OnTimeout>().unwrap(),
+            OnTimeout::Signal(Signal::Kill)
+        );
+        assert_eq!("none".parse::<OnTimeout>().unwrap(), OnTimeout::Ignore);
+        assert!("".parse::<OnTimeout>().is_err());
+    }
+
+    #[test]
+    fn test_parse_escalating_on_timeout() {
+        assert_eq!(
+            "signal=TERM,grace=30s,then=KILL"
+                .parse::<OnTimeout>()
+                .unwrap(),
+            OnTimeout::Escalate {
+                signal: Signal::Term,
+                grace: Duration::seconds(30),
+                then: Signal::Kill
+            }
+        );
+        assert_eq!(
+            "signal=INT".parse::<OnTimeout>().unwrap(),
+            OnTimeout::Signal(Signal::Int)
+        );
+        assert_eq!(
+            "grace=2m".parse::<OnTimeout>().unwrap(),
+            OnTimeout::Escalate {
+                signal: Signal::Term,
+                grace: Duration::minutes(2),
+                then: Signal::Kill
+            }
+        );
+        assert_eq!(
+            "then=QUIT".parse::<OnTimeout>().unwrap(),
+            OnTimeout::Escalate {
+                signal: Signal::Term,
+                grace: DEFAULT_GRACE,
+                then: Signal::Quit
+            }
+        );
+
+        for input in ["grace=soon", "signal=STOP", "delay=5s", "signal"] {
+            assert!(
+                matches!(
+                    input.parse::<OnTimeout>(),
+                    Err(PbError::InvalidArgument { .. })
+                ),
+                "Expected '{input}' to be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_escalation_sequence() {
+        let policy: OnTimeout = "signal=TERM,grace=30s,then=KILL".parse().unwrap();
+        let mut escalation = TimeoutEscalation::new(policy);
+        let now = Instant::now();
+
+        assert_eq!(escalation.advance(false, now), None);
+        assert_eq!(escalation.stage(), TimeoutStage::Running);
+
+        assert_eq!(escalation.advance(true, now), Some(Signal::Term));
+        let deadline = now + std::time::Duration::from_secs(30);
+        assert_eq!(
+            escalation.stage(),
+            TimeoutStage::Grace {
+                signal: Signal::Term,
+                deadline
+            }
+        );
+        assert_eq!(escalation.next_deadline(), Some(deadline));
+
+        // Still within the grace period
+        let later = now + std::time::Duration::from_secs(10);
+        assert_eq!(escalation.advance(true, later), None);
+
+        assert_eq!(escalation.advance(true, deadline), Some(Signal::Kill));
+        assert_eq!(escalation.stage(), TimeoutStage::Signaled(Signal::Kill));
+        assert_eq!(escalation.advance(true, deadline), None);
+        assert_eq!(escalation.sent(), [Signal::Term, Signal::Kill]);
+        assert_eq!(
+            escalation.summary().unwrap(),
+            "sent SIGTERM, then SIGKILL after a 30s grace period"
+        );
+    }
+
+    #[test]
+    fn test_single_signal_and_ignore() {
+        let now = Instant::now();
+
+        let mut escalation = TimeoutEscalation::new(OnTimeout::Signal(Signal::Int));
+        assert_eq!(escalation.advance(true, now), Some(Signal::Int));
+        assert_eq!(escalation.advance(true, now), None);
+        assert_eq!(escalation.summary().unwrap(), "sent SIGINT");
+
+        let mut escalation = TimeoutEscalation::new(OnTimeout::Ignore);
+        assert_eq!(escalation.advance(false, now), None);
+        assert!(!escalation.overran());
+        assert_eq!(escalation.summary(), None);
+        // The overrun is kept even though nothing is sent
+        assert_eq!(escalation.advance(true, now), None);
+        assert_eq!(escalation.stage(), TimeoutStage::Running);
+        assert!(escalation.overran());
+        assert_eq!(escalation.sent(), []);
+        assert_eq!(escalation.summary().unwrap(), "not signaled");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_signal_and_exit_code() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        send_signal(&mut child, Signal::Term).unwrap();
+        let status = child.wait().unwrap();
+        assert_eq!(exit_code(status), 128 + libc::SIGTERM);
+
+        let status = std::process::Command::new("sh")
+            .args(["-c", "exit 3"])
+            .status()
+            .unwrap();
+        assert_eq!(exit_code(status), 3);
+    }
+}
@@ -0,0 +1,146 @@
+//! Menu-bar plugin output for xbar and SwiftBar
+//!
+//! xbar and SwiftBar run a plugin script on a schedule and show the first
+//! line it prints in the macOS menu bar, with the lines after `---` in its
+//! dropdown. With `--format xbar` pmon prints one such snapshot and exits,
+//! so a one-line plugin keeps a timer in the menu bar:
+//!
+//! ```sh
+//! #!/bin/sh
+//! # pmon.30s.sh: the "30s" in the file name is the refresh interval
+//! exec /usr/local/bin/pmon --format xbar attach standup
+//! ```
+//!
+//! The dropdown lists the elapsed and remaining time, the end, and the
+//! status message, and ends with a Refresh item that reruns the plugin.
+
+use crate::layout::fit_label;
+use crate::progress_bar::{format_percentage, DurationFormat};
+use crate::progress_state::ProgressState;
+
+/// Color of the menu-bar title once the window has elapsed
+pub const OVERDUE_COLOR: &str = "#d73a49";
+
+/// Dropdown item that reruns the plugin when clicked
+const REFRESH_ITEM: &str = "Refresh | refresh=true";
+
+/// Make text safe for one xbar line, where `|` starts the item's parameters
+fn escape_line(text: &str) -> String {
+    text.replace('|', "¦").replace(['\r', '\n'], " ")
+}
+
+/// Render a progress snapshot as xbar plugin output
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDateTime;
+/// use pmon::progress_bar::DurationFormat;
+/// use pmon::progress_state::ProgressState;
+/// use pmon::xbar::render_xbar;
+///
+/// let at = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+/// let state = ProgressState::new(
+///     at("2025-01-27 09:00:00"),
+///     at("2025-01-27 09:20:00"),
+///     at("2025-01-27 09:05:00"),
+/// );
+/// let output = render_xbar(&state, Some("Standup"), None, DurationFormat::default());
+/// assert_eq!(output.lines().next(), Some("25.0% Standup"));
+/// ```
+pub fn render_xbar(
+    state: &ProgressState,
+    label: Option<&str>,
+    message: Option<&str>,
+    format: DurationFormat,
+) -> String {
+    let percent = format_percentage(state.percentage);
+    let mut title = match label {
+        Some(label) => format!("{percent} {}", escape_line(&fit_label(label))),
+        None => percent,
+    };
+    if state.is_complete() {
+        title.push_str(&format!(" | color={OVERDUE_COLOR}"));
+    }
+
+    let mut lines = vec![
+        title,
+        "---".to_string(),
+        format!("Elapsed: {}", format.elapsed(state.elapsed())),
+    ];
+    if state.is_complete() {
+        lines.push(format!("Overtime: {}", format.elapsed(state.overtime())));
+    } else {
+        lines.push(format!(
+            "Remaining: {}",
+            format.remaining(state.remaining())
+        ));
+    }
+    lines.push(format!("Ends: {}", state.end.format("%Y-%m-%d %H:%M")));
+    if let Some(message) = message {
+        lines.push(escape_line(message));
+    }
+    lines.push("---".to_string());
+    lines.push(REFRESH_ITEM.to_string());
+    lines.join("\n")
+}
+
+/// Render xbar plugin output for when there is no timer to show
+pub fn render_xbar_idle(reason: &str) -> String {
+    format!("pmon\n---\n{}\n---\n{REFRESH_ITEM}", escape_line(reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::progress_bar::DurationStyle;
+    use chrono::NaiveDateTime;
+
+    fn create_test_datetime(time_str: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time_str, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn state_at(current: &str) -> ProgressState {
+        ProgressState::new(
+            create_test_datetime("2025-01-27 09:00:00"),
+            create_test_datetime("2025-01-27 10:00:00"),
+            create_test_datetime(current),
+        )
+    }
+
+    #[test]
+    fn test_render_running_window() {
+        let output = render_xbar(
+            &state_at("2025-01-27 09:45:00"),
+            Some("Deep work | focus"),
+            Some("wrap up"),
+            DurationStyle::Compact.into(),
+        );
+        assert_eq!(
+            output,
+            "75.0% Deep work ¦ focus\n---\nElapsed: 45m\nRemaining: 15m\nEnds: 2025-01-27 10:00\nwrap up\n---\nRefresh | refresh=true"
+        );
+    }
+
+    #[test]
+    fn test_render_elapsed_window() {
+        let output = render_xbar(
+            &state_at("2025-01-27 10:30:00"),
+            None,
+            None,
+            DurationStyle::Compact.into(),
+        );
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0], "150.0% | color=#d73a49");
+        assert!(lines.contains(&"Overtime: 30m"));
+        assert!(!output.contains("Remaining"));
+    }
+
+    #[test]
+    fn test_render_idle() {
+        assert_eq!(
+            render_xbar_idle("No running timer named 'standup'"),
+            "pmon\n---\nNo running timer named 'standup'\n---\nRefresh | refresh=true"
+        );
+    }
+}
@@ -36,7 +36,7 @@ mod cli_parsing_tests {
                 let cli = Cli::try_parse_from(args.clone()).unwrap();
                 assert_eq!(cli.start, Some(args[2].to_string()));
                 assert_eq!(cli.end, args[4]);
-                assert_eq!(cli.interval, 60); // default value
+                assert_eq!(cli.interval(), 60); // default value
             } else {
                 assert!(result.is_err(), "Expected parsing to fail for: {args:?}");
             }
@@ -64,7 +64,7 @@ mod cli_parsing_tests {
 
         for (args, expected_interval) in test_cases {
             let cli = Cli::try_parse_from(args).unwrap();
-            assert_eq!(cli.interval, expected_interval);
+            assert_eq!(cli.interval(), expected_interval);
         }
     }
 
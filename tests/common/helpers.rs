@@ -106,8 +106,10 @@ impl TimeTestData {
 }
 
 /// Progress calculation test utilities
+#[allow(dead_code)]
 pub struct ProgressTestUtils;
 
+#[allow(dead_code)]
 impl ProgressTestUtils {
     /// Create test datetime from a simple string format for testing
     pub fn parse_test_datetime(datetime_str: &str) -> NaiveDateTime {
@@ -188,8 +190,10 @@ impl ProgressTestUtils {
 }
 
 /// CLI test utilities
+#[allow(dead_code)]
 pub struct CliTestUtils;
 
+#[allow(dead_code)]
 impl CliTestUtils {
     /// Create a command with pb binary
     pub fn pb_command() -> Command {
@@ -237,8 +241,10 @@ impl CliTestUtils {
 }
 
 /// Progress bar rendering test utilities
+#[allow(dead_code)]
 pub struct ProgressBarTestUtils;
 
+#[allow(dead_code)]
 impl ProgressBarTestUtils {
     /// Test cases for progress bar rendering
     pub fn rendering_cases() -> Vec<(f64, &'static str)> {
@@ -294,8 +300,10 @@ impl ProgressBarTestUtils {
 }
 
 /// Performance test utilities
+#[allow(dead_code)]
 pub struct PerformanceTestUtils;
 
+#[allow(dead_code)]
 impl PerformanceTestUtils {
     /// Measure the execution time of a function
     pub fn measure_time<F, R>(f: F) -> (R, std::time::Duration)
@@ -335,6 +343,7 @@ impl PerformanceTestUtils {
 }
 
 /// Performance expectations for various operations
+#[allow(dead_code)]
 pub struct PerformanceExpectations {
     pub parse_time_max: std::time::Duration,
     pub calculate_progress_max: std::time::Duration,
@@ -342,8 +351,10 @@ pub struct PerformanceExpectations {
 }
 
 /// Custom assertion helpers
+#[allow(dead_code)]
 pub struct AssertionHelpers;
 
+#[allow(dead_code)]
 impl AssertionHelpers {
     /// Assert that two floating point numbers are approximately equal
     pub fn assert_approx_eq(a: f64, b: f64, tolerance: f64) {
@@ -375,8 +386,10 @@ impl AssertionHelpers {
 }
 
 /// Test fixtures for generating consistent test data
+#[allow(dead_code)]
 pub struct TestFixtures;
 
+#[allow(dead_code)]
 impl TestFixtures {
     /// Generate a test datetime relative to a base time
     pub fn datetime_relative_to_now(offset_minutes: i64) -> NaiveDateTime {
@@ -163,7 +163,7 @@ mod pb_result_tests {
     fn test_pb_result_success() {
         let success: PbResult<i32> = Ok(42);
         assert!(success.is_ok());
-        assert_eq!(success.unwrap(), 42);
+        assert!(matches!(success, Ok(42)));
     }
 
     #[test]
@@ -171,10 +171,9 @@ mod pb_result_tests {
         let error: PbResult<i32> = Err(PbError::StartAfterEnd);
         assert!(error.is_err());
 
-        let err = error.unwrap_err();
         assert_eq!(
-            err.to_string(),
-            "Start time must be before or equal to end time"
+            error.map_err(|err| err.to_string()),
+            Err("Start time must be before or equal to end time".to_string())
         );
     }
 
@@ -361,6 +360,12 @@ mod error_message_tests {
             PbError::EndTimeAlreadyPassed,
             PbError::invalid_relative_time_format("test"),
             PbError::MissingRequiredOptions,
+            PbError::invalid_argument("--test", "test"),
+            PbError::time_source_unavailable("test", "test"),
+            PbError::invalid_config("test", "test"),
+            PbError::storage("test", "test"),
+            PbError::delivery("test", "test"),
+            PbError::update("test"),
         ];
 
         for error in test_cases {
@@ -398,6 +403,11 @@ mod error_trait_implementation_tests {
             PbError::EndTimeAlreadyPassed,
             PbError::invalid_relative_time_format("test"),
             PbError::MissingRequiredOptions,
+            PbError::invalid_argument("--test", "test"),
+            PbError::time_source_unavailable("test", "test"),
+            PbError::invalid_config("test", "test"),
+            PbError::storage("test", "test"),
+            PbError::delivery("test", "test"),
         ];
 
         for error in errors {
@@ -435,6 +445,15 @@ mod error_pattern_matching_tests {
             PbError::EndTimeAlreadyPassed,
             PbError::invalid_relative_time_format("test"),
             PbError::MissingRequiredOptions,
+            PbError::invalid_argument("--test", "test"),
+            PbError::time_source_unavailable("test", "test"),
+            PbError::invalid_config("test", "test"),
+            PbError::storage("test", "test"),
+            PbError::delivery("test", "test"),
+            PbError::ambiguous_time("test", &["test".to_string()]),
+            PbError::unreadable_time("test", vec!["test".to_string()]),
+            PbError::label_source("test", "test"),
+            PbError::timer_exists("test", "test"),
         ];
 
         for error in errors {
@@ -460,6 +479,45 @@ mod error_pattern_matching_tests {
                 PbError::MissingRequiredOptions => {
                     assert_eq!(error.to_string(), "--end option is required");
                 }
+                PbError::InvalidArgument { option, input } => {
+                    assert_eq!(option, "--test");
+                    assert_eq!(input, "test");
+                }
+                PbError::TimeSourceUnavailable { server, reason } => {
+                    assert_eq!(server, "test");
+                    assert_eq!(reason, "test");
+                }
+                PbError::InvalidConfig { path, reason } => {
+                    assert_eq!(path, "test");
+                    assert_eq!(reason, "test");
+                }
+                PbError::Storage { backend, reason } => {
+                    assert_eq!(backend, "test");
+                    assert_eq!(reason, "test");
+                }
+                PbError::Delivery { target, reason } => {
+                    assert_eq!(target, "test");
+                    assert_eq!(reason, "test");
+                }
+                PbError::Update { reason } => {
+                    assert_eq!(reason, "test");
+                }
+                PbError::AmbiguousTime { input, readings } => {
+                    assert_eq!(input, "test");
+                    assert_eq!(readings, "test");
+                }
+                PbError::UnreadableTime { input, attempts } => {
+                    assert_eq!(input, "test");
+                    assert_eq!(attempts, ["test"]);
+                }
+                PbError::LabelSource { origin, reason } => {
+                    assert_eq!(origin, "test");
+                    assert_eq!(reason, "test");
+                }
+                PbError::TimerExists { name, until } => {
+                    assert_eq!(name, "test");
+                    assert_eq!(until, "test");
+                }
             }
         }
     }
@@ -475,6 +533,16 @@ mod error_pattern_matching_tests {
             PbError::EndTimeAlreadyPassed => "end_time_already_passed",
             PbError::InvalidRelativeTimeFormat { .. } => "invalid_relative_time_format",
             PbError::MissingRequiredOptions => "missing_required_options",
+            PbError::InvalidArgument { .. } => "invalid_argument",
+            PbError::TimeSourceUnavailable { .. } => "time_source_unavailable",
+            PbError::InvalidConfig { .. } => "invalid_config",
+            PbError::Storage { .. } => "storage",
+            PbError::Delivery { .. } => "delivery",
+            PbError::Update { .. } => "update",
+            PbError::AmbiguousTime { .. } => "ambiguous_time",
+            PbError::UnreadableTime { .. } => "unreadable_time",
+            PbError::LabelSource { .. } => "label_source",
+            PbError::TimerExists { .. } => "timer_exists",
         };
 
         assert_eq!(result, "start_after_end");
@@ -86,8 +86,8 @@ fn test_completed_progress() {
 
     let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
     assert!(stdout.contains("pmon - Progress Monitor Tool"));
-    assert!(stdout.contains("Start time: 2025-07-21 10:00:00"));
-    assert!(stdout.contains("End time: 2025-07-21 11:00:00"));
+    assert!(stdout.contains("Start time:      2025-07-21 10:00:00"));
+    assert!(stdout.contains("End time:        2025-07-21 11:00:00"));
     assert!(stdout.contains("Progress completed!"));
 }
 
@@ -107,12 +107,71 @@ fn test_date_format_parsing() {
     let output = cmd.timeout(Duration::from_secs(5)).assert().success();
 
     let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
-    assert!(stdout.contains("Start time: 2025-07-20 00:00:00"));
-    assert!(stdout.contains("End time: 2025-07-21 00:00:00"));
+    assert!(stdout.contains("Start time:      2025-07-20 00:00:00"));
+    assert!(stdout.contains("End time:        2025-07-21 00:00:00"));
     // Should complete immediately since this is a past time range
     assert!(stdout.contains("Progress completed!"));
 }
 
+#[test]
+fn test_verbose_explains_relative_times() {
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args([
+        "--start",
+        "2025-07-20 09:00:00",
+        "--end",
+        "90m",
+        "--verbose",
+    ]);
+
+    let output = cmd.timeout(Duration::from_secs(5)).assert().success();
+
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("Start time:      2025-07-20 09:00:00\n"));
+    assert!(
+        stdout.contains("End time:        2025-07-20 10:30:00 (relative to 2025-07-20 09:00:00)")
+    );
+}
+
+#[test]
+fn test_strict_lists_readings_of_ambiguous_times() {
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args(["--end", "2025", "--strict"]);
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "Error parsing end time '2025': Ambiguous time 2025: did you mean 20:25:00 or 2025-01-01?",
+    ));
+
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args(["--end", "2025"]);
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "Invalid time format: 2025\n  date (YYYY-MM-DD): expected a year, month, and day separated by '-'",
+    ));
+}
+
+#[test]
+fn test_relative_anchor_now_counts_from_the_current_time() {
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args([
+        "--start",
+        "2025-07-20 09:00:00",
+        "--end",
+        "1s",
+        "--relative-anchor",
+        "now",
+        "--interval",
+        "1",
+        "--verbose",
+    ]);
+
+    let output = cmd.timeout(Duration::from_secs(10)).assert().success();
+
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    assert!(!stdout.contains("End time:        2025-07-20 09:00:01"));
+    assert!(stdout.contains(&format!("(relative to {today}")));
+    assert!(stdout.contains("Progress completed!"));
+}
+
 #[test]
 fn test_relative_time_parsing() {
     let mut cmd = Command::cargo_bin("pmon").unwrap();
@@ -131,7 +190,9 @@ fn test_relative_time_parsing() {
 
     let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
     assert!(stdout.contains("pmon - Progress Monitor Tool"));
-    assert!(stdout.contains("[░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░] 0.0%"));
+    assert!(stdout.contains("Starts in:       "));
+    assert!(stdout.contains("[░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░] (starts in "));
+    assert!(!stdout.contains("0.0%"));
 }
 
 #[test]
@@ -209,6 +270,116 @@ fn test_equal_start_end_times() {
     assert!(stdout.contains("Progress completed!"));
 }
 
+#[test]
+fn test_suspicious_windows_warn_unless_strict() {
+    let args = [
+        "--start",
+        "2025-07-21 10:00:00",
+        "--end",
+        "2025-07-21 10:00:00",
+        "--interval",
+        "1",
+    ];
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args(args);
+    cmd.timeout(Duration::from_secs(5))
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Warning: The start equals the end, so the window is already over",
+        ));
+
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args(args).arg("--strict");
+    cmd.timeout(Duration::from_secs(5))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Error: The start equals the end, so the window is already over (rejected by --strict)",
+        ));
+}
+
+#[test]
+fn test_interval_longer_than_the_window_is_clamped() {
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args(["--end", "+2s", "--interval", "60"]);
+    cmd.timeout(Duration::from_secs(10))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Progress completed!"))
+        .stderr(predicate::str::contains(
+            "Warning: The update interval (60s) is longer than the whole window (2s), so the bar is redrawn every 1s instead",
+        ));
+
+    // The default interval is clamped without a word, even with --strict
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args(["--end", "+30s", "--strict"]);
+    cmd.timeout(Duration::from_secs(45))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Progress completed!"))
+        .stderr(predicate::str::contains("update interval").not());
+
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args(["--end", "+2s", "--interval", "60", "--no-clamp", "--strict"]);
+    cmd.timeout(Duration::from_secs(10))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "so the bar will barely move (rejected by --strict)",
+        ));
+}
+
+#[test]
+fn test_themes_preview_shows_every_style() {
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args(["themes", "preview"]);
+    cmd.timeout(Duration::from_secs(10))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("classic  [░░░░░░░░░░] 0.0%"))
+        .stdout(predicate::str::contains("[██50.0%░░░]"))
+        .stdout(predicate::str::contains("🟩🟩🟩🟩🟩 125.0%"))
+        .stdout(predicate::str::contains("magenta"));
+}
+
+#[test]
+fn test_theme_from_the_themes_directory() {
+    let config_dir = tempfile::tempdir().unwrap();
+    let themes = config_dir.path().join("themes");
+    std::fs::create_dir(&themes).unwrap();
+    std::fs::write(themes.join("ocean.toml"), "filled = \"▓\"\nempty = \"·\"\n").unwrap();
+    let pmon = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("pmon").unwrap();
+        cmd.env("PMON_CONFIG", config_dir.path().join("config.toml"))
+            .args(args)
+            .timeout(Duration::from_secs(10));
+        cmd
+    };
+
+    pmon(&["themes", "preview"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ocean    [··········] 0.0%"));
+    pmon(&[
+        "--theme",
+        "ocean",
+        "--start",
+        "2025-01-27 09:00:00",
+        "--end",
+        "2025-01-27 10:00:00",
+    ])
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(format!("[{}]", "▓".repeat(40))));
+    pmon(&["--theme", "desert", "--end", "+1h"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "there is no theme named 'desert'; themes found: ocean",
+        ));
+}
+
 #[cfg(test)]
 mod comprehensive_cli_integration_tests {
     use super::*;
@@ -858,3 +1029,1406 @@ mod regression_tests {
         }
     }
 }
+
+#[cfg(unix)]
+#[test]
+fn test_wrapped_command_exit_code_is_propagated() {
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args(["--end", "+1h", "--", "sh", "-c", "exit 3"]);
+
+    let output = cmd.timeout(Duration::from_secs(5)).assert().code(3);
+
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("sh finished after"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_wrapped_command_is_signaled_on_timeout() {
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args([
+        "--end",
+        "+1s",
+        "--interval",
+        "1",
+        "--on-timeout",
+        "KILL",
+        "--",
+        "sleep",
+        "30",
+    ]);
+
+    // SIGKILL is signal 9, reported as 128 + 9 like a shell would
+    let output = cmd.timeout(Duration::from_secs(10)).assert().code(137);
+
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("sending SIGKILL to sleep"));
+    assert!(stdout.contains("ran out of time"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_wrapped_command_escalates_after_grace() {
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args([
+        "--end",
+        "+1s",
+        "--interval",
+        "1",
+        "--on-timeout",
+        "signal=USR1,grace=1s,then=KILL",
+        "--",
+        "sh",
+        "-c",
+        "trap '' USR1; exec sleep 30",
+    ]);
+
+    // SIGUSR1 stays ignored across exec, so only SIGKILL (9) stops sleep
+    let output = cmd.timeout(Duration::from_secs(10)).assert().code(137);
+
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("sending SIGUSR1 to sh"));
+    assert!(stdout.contains("sending SIGKILL to sh"));
+    assert!(stdout.contains("sent SIGUSR1, then SIGKILL after a 1s grace period"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_wrapped_command_overrun_without_signal() {
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args([
+        "--end",
+        "+1s",
+        "--interval",
+        "1",
+        "--on-timeout",
+        "none",
+        "--",
+        "sleep",
+        "3",
+    ]);
+
+    let output = cmd.timeout(Duration::from_secs(10)).assert().success();
+
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    assert!(!stdout.contains("sending"), "{stdout}");
+    assert!(
+        stdout.contains("sleep ran out of time (not signaled) after"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn test_attach_without_running_timer() {
+    let runtime_dir = tempfile::tempdir().unwrap();
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.env("XDG_RUNTIME_DIR", runtime_dir.path())
+        .args(["attach", "nobody-home"]);
+
+    cmd.assert().code(1).stderr(predicate::str::contains(
+        "no running timer named 'nobody-home'",
+    ));
+}
+
+#[test]
+fn test_attach_mirrors_named_timer() {
+    let runtime_dir = tempfile::tempdir().unwrap();
+    let mut timer = std::process::Command::new(env!("CARGO_BIN_EXE_pmon"))
+        .env("XDG_RUNTIME_DIR", runtime_dir.path())
+        .env("XDG_DATA_HOME", runtime_dir.path())
+        .args(["--name", "demo", "--end", "+3s", "--interval", "1"])
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+
+    // Wait for the first update to be published
+    let state_file = runtime_dir.path().join("pmon").join("demo.json");
+    for _ in 0..50 {
+        if state_file.exists() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.env("XDG_RUNTIME_DIR", runtime_dir.path())
+        .args(["--format", "json", "attach", "demo"]);
+    let output = cmd.timeout(Duration::from_secs(10)).assert().success();
+    timer.wait().unwrap();
+
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    assert!(stdout.lines().count() >= 1);
+    assert!(!state_file.exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_attach_drops_state_of_killed_timer() {
+    let runtime_dir = tempfile::tempdir().unwrap();
+    let mut timer = std::process::Command::new(env!("CARGO_BIN_EXE_pmon"))
+        .env("XDG_RUNTIME_DIR", runtime_dir.path())
+        .env("XDG_DATA_HOME", runtime_dir.path())
+        .args(["--name", "standup", "--end", "+10m", "--interval", "1"])
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+
+    let state_file = runtime_dir.path().join("pmon").join("standup.json");
+    for _ in 0..50 {
+        if state_file.exists() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    // Killed outright, the timer cannot remove its state file
+    timer.kill().unwrap();
+    timer.wait().unwrap();
+    assert!(state_file.exists());
+
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.env("XDG_RUNTIME_DIR", runtime_dir.path())
+        .args(["attach", "standup"]);
+    cmd.assert()
+        .code(1)
+        .stderr(predicate::str::contains("no running timer named 'standup'"));
+    assert!(!state_file.exists());
+}
+
+#[test]
+fn test_compare_without_running_timers() {
+    let runtime_dir = tempfile::tempdir().unwrap();
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.env("XDG_RUNTIME_DIR", runtime_dir.path())
+        .arg("compare");
+
+    cmd.assert().code(1).stderr(predicate::str::contains(
+        "no running named timers to compare",
+    ));
+}
+
+#[test]
+fn test_compare_sorts_timers_by_time_left() {
+    let runtime_dir = tempfile::tempdir().unwrap();
+    let start_timer = |name: &str, end: &str| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_pmon"))
+            .env("XDG_RUNTIME_DIR", runtime_dir.path())
+            .env("XDG_DATA_HOME", runtime_dir.path())
+            .args(["--name", name, "--end", end, "--interval", "1"])
+            .stdout(std::process::Stdio::null())
+            .spawn()
+            .unwrap()
+    };
+    let mut timers = [start_timer("later", "+4s"), start_timer("sooner", "+2s")];
+
+    // Wait for both timers to publish their first update
+    let state_dir = runtime_dir.path().join("pmon");
+    for _ in 0..50 {
+        if state_dir.join("later.json").exists() && state_dir.join("sooner.json").exists() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.env("XDG_RUNTIME_DIR", runtime_dir.path())
+        .arg("compare");
+    let output = cmd.timeout(Duration::from_secs(10)).assert().success();
+    for timer in &mut timers {
+        timer.wait().unwrap();
+    }
+
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    let first_table: Vec<&str> = stdout.lines().take_while(|line| !line.is_empty()).collect();
+    assert_eq!(first_table.len(), 2, "{stdout}");
+    assert!(first_table[0].starts_with("sooner "), "{stdout}");
+    assert!(first_table[0].ends_with("← next"), "{stdout}");
+    assert!(first_table[1].starts_with("later "), "{stdout}");
+    assert!(stdout.ends_with("All timers ended.\n"));
+}
+
+#[test]
+fn test_named_runs_are_recorded_in_history() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let pmon = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("pmon").unwrap();
+        cmd.env("XDG_RUNTIME_DIR", data_dir.path())
+            .env("XDG_DATA_HOME", data_dir.path())
+            .env("PMON_CONFIG", data_dir.path().join("config.toml"))
+            .args(args);
+        cmd
+    };
+
+    pmon(&["history"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No runs recorded yet."));
+
+    pmon(&[
+        "--name",
+        "standup",
+        "--label",
+        "Standup",
+        "--tag",
+        "team:payments",
+        "--end",
+        "+1s",
+        "--interval",
+        "1",
+    ])
+    .timeout(Duration::from_secs(10))
+    .assert()
+    .success();
+    assert!(data_dir.path().join("pmon").join("store.json").exists());
+
+    pmon(&["history"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "standup (Standup)  completed  [team:payments]",
+        ));
+    pmon(&["--format", "json", "history"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"tags\":[\"team:payments\"]"));
+}
+
+#[test]
+fn test_history_lists_notes_of_each_run() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let pmon = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("pmon").unwrap();
+        cmd.env("XDG_DATA_HOME", data_dir.path())
+            .env("PMON_CONFIG", data_dir.path().join("config.toml"))
+            .args(args);
+        cmd
+    };
+    let store = data_dir.path().join("pmon").join("store.json");
+    std::fs::create_dir_all(store.parent().unwrap()).unwrap();
+    std::fs::write(
+        &store,
+        r#"{"schema_version": 1, "history": [
+            {"name": "focus", "label": null, "start": "2025-01-27T09:00:00", "end": "2025-01-27T11:00:00", "finished_at": "2025-01-27T11:00:00", "outcome": "completed", "notes": [{"at": "2025-01-27T10:15:00", "text": "started late"}]}
+        ]}"#,
+    )
+    .unwrap();
+
+    pmon(&["history"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "2025-01-27 09:00 - 11:00  focus  completed\n  10:15  started late\n",
+        ));
+    pmon(&["--format", "json", "history"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            r#""notes":[{"at":"2025-01-27T10:15:00","text":"started late"}]"#,
+        ));
+}
+
+#[test]
+fn test_report_totals_history_by_tag() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let pmon = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("pmon").unwrap();
+        cmd.env("XDG_DATA_HOME", data_dir.path())
+            .env("PMON_CONFIG", data_dir.path().join("config.toml"))
+            .args(args);
+        cmd
+    };
+    let store = data_dir.path().join("pmon").join("store.json");
+    std::fs::create_dir_all(store.parent().unwrap()).unwrap();
+    std::fs::write(
+        &store,
+        r#"{"schema_version": 1, "history": [
+            {"name": "focus", "label": "Focus", "start": "2025-01-27T09:00:00", "end": "2025-01-27T11:00:00", "finished_at": "2025-01-27T11:30:00", "outcome": "completed", "tags": ["deep-work"]},
+            {"name": "review", "label": null, "start": "2025-01-28T14:00:00", "end": "2025-01-28T15:00:00", "finished_at": "2025-01-28T14:20:00", "outcome": "interrupted"}
+        ]}"#,
+    )
+    .unwrap();
+
+    pmon(&["report", "--since", "2025-01-27"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Since Mon 2025-01-27 00:00"))
+        .stdout(predicate::str::contains(
+            "deep-work      1   2h 30m       30m       100%",
+        ))
+        .stdout(predicate::str::contains(
+            "Total          2   2h 50m       30m        50%",
+        ));
+    pmon(&[
+        "--format",
+        "json",
+        "report",
+        "--since",
+        "2025-01-28",
+        "--by",
+        "label",
+    ])
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(
+        r#""groups":[{"group":"review","runs":1,"completed":0,"tracked_seconds":1200"#,
+    ));
+}
+
+#[test]
+fn test_repair_state_recovers_truncated_store() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let pmon = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("pmon").unwrap();
+        cmd.env("XDG_DATA_HOME", data_dir.path())
+            .env("PMON_CONFIG", data_dir.path().join("config.toml"))
+            .args(args);
+        cmd
+    };
+    let store = data_dir.path().join("pmon").join("store.json");
+
+    pmon(&["repair-state"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("nothing to repair"));
+
+    std::fs::create_dir_all(store.parent().unwrap()).unwrap();
+    std::fs::write(
+        &store,
+        r#"{"schema_version": 1, "history": [{"name": "standup", "label": null, "start": "2025-01-27T09:00:00", "end": "2025-01-27T09:15:00", "finished_at": "2025-01-27T09:15:00", "outcome": "completed"}, {"name": "rev"#,
+    )
+    .unwrap();
+    pmon(&["history"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("pmon repair-state"));
+
+    pmon(&["repair-state"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Recovered 0 timer(s), 1 run(s)"))
+        .stdout(predicate::str::contains("store.json.corrupt"));
+    pmon(&["history"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("standup  completed"));
+    pmon(&["repair-state"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("is healthy"));
+}
+
+#[test]
+fn test_secret_set_needs_a_secret_on_stdin() {
+    Command::cargo_bin("pmon")
+        .unwrap()
+        .args(["secret", "set", "relay"])
+        .write_stdin("\n")
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "no secret given on standard input",
+        ));
+}
+
+#[test]
+fn test_invalid_store_config_is_reported() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let config = data_dir.path().join("config.toml");
+    std::fs::write(&config, "[store]\nbackend = \"csv\"\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.env("XDG_DATA_HOME", data_dir.path())
+        .env("PMON_CONFIG", &config)
+        .arg("history");
+    cmd.assert()
+        .code(1)
+        .stderr(predicate::str::contains("Invalid configuration in"));
+}
+
+#[test]
+fn test_export_import_and_run_timer_definition() {
+    let laptop = tempfile::tempdir().unwrap();
+    let desktop = tempfile::tempdir().unwrap();
+    let pmon = |home: &std::path::Path, args: &[&str]| {
+        let mut cmd = Command::cargo_bin("pmon").unwrap();
+        cmd.env("XDG_RUNTIME_DIR", home)
+            .env("XDG_DATA_HOME", home)
+            .env("PMON_CONFIG", home.join("config.toml"))
+            .args(args)
+            .timeout(Duration::from_secs(10));
+        cmd
+    };
+
+    pmon(
+        laptop.path(),
+        &[
+            "--name",
+            "focus",
+            "--label",
+            "Deep work",
+            "--end",
+            "+1s",
+            "--interval",
+            "1",
+        ],
+    )
+    .assert()
+    .success();
+
+    let output = pmon(laptop.path(), &["export", "focus"]).assert().success();
+    let toml = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    assert!(toml.contains("name = \"focus\""));
+    assert!(toml.contains("label = \"Deep work\""));
+    assert!(toml.contains("end = \"+1s\""));
+
+    pmon(desktop.path(), &["export", "focus"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("no saved timer named 'focus'"));
+
+    let file = desktop.path().join("timer.toml");
+    std::fs::write(&file, &toml).unwrap();
+    pmon(desktop.path(), &["import", file.to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Imported timer 'focus'"));
+
+    pmon(desktop.path(), &["run", "focus"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Deep work"));
+    pmon(desktop.path(), &["history"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("focus (Deep work)  completed"));
+}
+
+#[test]
+fn test_import_plan_saves_a_timer_per_row() {
+    let home = tempfile::tempdir().unwrap();
+    let pmon = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("pmon").unwrap();
+        cmd.env("XDG_RUNTIME_DIR", home.path())
+            .env("XDG_DATA_HOME", home.path())
+            .env("PMON_CONFIG", home.path().join("config.toml"))
+            .args(args)
+            .timeout(Duration::from_secs(10));
+        cmd
+    };
+    let plan = home.path().join("plan.csv");
+    std::fs::write(
+        &plan,
+        "label,start,end,tags\n\
+         Opening keynote,,+1s,track:main\n\
+         \"Rust, in production\",2030-03-10 10:30,2030-03-10 11:15,track:main;rust\n",
+    )
+    .unwrap();
+
+    pmon(&["import-plan", plan.to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Imported timer 'opening-keynote'"))
+        .stdout(predicate::str::contains(
+            "Imported timer 'rust-in-production'",
+        ));
+    pmon(&["export", "rust-in-production"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "tags = [\"track:main\", \"rust\"]",
+        ));
+    pmon(&["export", "opening-keynote"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("label = \"Opening keynote\""));
+
+    std::fs::write(&plan, "label,start,end\nBroken,,\n").unwrap();
+    pmon(&["import-plan", plan.to_str().unwrap()])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("line 2"));
+}
+
+#[test]
+fn test_schedule_switches_to_the_next_session() {
+    let dir = tempfile::tempdir().unwrap();
+    let plan = dir.path().join("talks.csv");
+    let now = chrono::Local::now().naive_local();
+    let at = |seconds| {
+        (now + chrono::Duration::seconds(seconds))
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string()
+    };
+    std::fs::write(
+        &plan,
+        format!(
+            "label,start,end\nEarlier,{},{}\nKeynote,{},+3s\nQ&A,{},+1s\n",
+            at(-60),
+            at(-30),
+            at(-1),
+            at(2)
+        ),
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("pmon")
+        .unwrap()
+        .args(["--schedule", plan.to_str().unwrap(), "--interval", "1"])
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    assert!(!stdout.contains("Earlier"), "{stdout}");
+    assert!(stdout.starts_with("Keynote "), "{stdout}");
+    assert!(stdout.contains("  Next: Q&A at "), "{stdout}");
+    assert!(stdout.contains("\nQ&A "), "{stdout}");
+}
+
+#[test]
+fn test_deadline_exit_codes() {
+    let deadline = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("pmon").unwrap();
+        cmd.arg("deadline").args(args);
+        cmd
+    };
+
+    deadline(&["--end", "+30d", "--warn", "7d", "--label", "Release"])
+        .assert()
+        .code(0)
+        .stdout(predicate::str::starts_with("OK Release: "));
+    deadline(&["--end", "+3d", "--warn", "7d"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::starts_with("WARN Deadline: "));
+    deadline(&["--end", "+3d", "--warn", "7d", "--fail", "5d"])
+        .assert()
+        .code(2)
+        .stdout(predicate::str::starts_with("FAIL "));
+    deadline(&["--end", "2020-01-01"])
+        .assert()
+        .code(2)
+        .stdout(predicate::str::contains("overdue (due 2020-01-01 00:00)"));
+}
+
+#[test]
+fn test_gha_format_emits_workflow_commands() {
+    // A one-minute window that is almost over
+    let now = chrono::Local::now().naive_local();
+    let start = (now - chrono::Duration::seconds(59)).format("%Y-%m-%d %H:%M:%S");
+    let end = (now + chrono::Duration::seconds(1)).format("%Y-%m-%d %H:%M:%S");
+    let (start, end) = (start.to_string(), end.to_string());
+
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args([
+        "--start",
+        &start,
+        "--end",
+        &end,
+        "--interval",
+        "1",
+        "--label",
+        "CI: build",
+        "--format",
+        "gha",
+    ]);
+    let output = cmd.timeout(Duration::from_secs(10)).assert().success();
+
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    assert!(
+        stdout.lines().all(|line| line.starts_with("::")),
+        "{stdout}"
+    );
+    assert!(stdout.contains("::warning title=CI%3A build::90%25 of the time budget used"));
+    assert!(stdout.contains("::notice title=CI%3A build::Time window elapsed after 1m"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_gha_format_reports_failed_wrapped_command() {
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args([
+        "--end", "+1h", "--format", "gha", "--", "sh", "-c", "exit 4",
+    ]);
+    cmd.timeout(Duration::from_secs(10))
+        .assert()
+        .code(4)
+        .stdout(predicate::str::contains("::error::sh failed after"));
+}
+
+#[test]
+fn test_junit_report_of_chained_windows() {
+    let dir = tempfile::tempdir().unwrap();
+    let report = dir.path().join("phases.xml");
+
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args(["--end", "+1s", "--label", "plan", "--interval", "1"])
+        .args(["--checkpoint", "kickoff@+1s", "--junit"])
+        .arg(&report)
+        .args(["--then", "--end", "+1s", "--label", "build"]);
+    cmd.timeout(Duration::from_secs(10)).assert().success();
+
+    let xml = std::fs::read_to_string(&report).unwrap();
+    assert!(
+        xml.contains("<testsuite name=\"plan\" tests=\"3\""),
+        "{xml}"
+    );
+    assert!(xml.contains("<testcase name=\"plan\" classname=\"plan\" time=\"1.000\"/>"));
+    assert!(xml.contains("<testcase name=\"build\" classname=\"plan\" time=\"1.000\"/>"));
+    assert!(xml.contains("<testcase name=\"checkpoint kickoff\""));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_junit_report_of_failed_wrapped_command() {
+    let dir = tempfile::tempdir().unwrap();
+    let report = dir.path().join("command.xml");
+
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args(["--end", "+1h", "--format", "json", "--junit"])
+        .arg(&report)
+        .args(["--", "sh", "-c", "exit 3"]);
+    cmd.timeout(Duration::from_secs(10)).assert().code(3);
+
+    let xml = std::fs::read_to_string(&report).unwrap();
+    assert!(xml.contains("failures=\"1\""), "{xml}");
+    assert!(xml.contains("<failure message=\"exited with code 3\"/>"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_complete_hook_runs_command() {
+    let dir = tempfile::tempdir().unwrap();
+    let marker = dir.path().join("complete.txt");
+    let config = dir.path().join("config.toml");
+    std::fs::write(
+        &config,
+        format!(
+            "[[hooks]]\non = \"complete\"\nrun = [\"sh\", \"-c\", \"echo $PMON_EVENT {{label}} > '{}'\"]\n",
+            marker.display()
+        ),
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.env("XDG_DATA_HOME", dir.path())
+        .env("PMON_CONFIG", &config)
+        .args(["--end", "+1s", "--label", "Tea", "--interval", "1"]);
+    cmd.timeout(Duration::from_secs(10)).assert().success();
+
+    // The hook runs in the background, so give it a moment to finish
+    for _ in 0..50 {
+        if std::fs::read_to_string(&marker).is_ok_and(|text| text.ends_with('\n')) {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    assert_eq!(std::fs::read_to_string(&marker).unwrap(), "complete Tea\n");
+}
+
+#[test]
+fn test_progress_file_shows_work_forecast() {
+    let dir = tempfile::tempdir().unwrap();
+    let progress = dir.path().join("build.progress");
+    std::fs::write(&progress, "120/300\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args(["--end", "+1s", "--interval", "1", "--progress-file"])
+        .arg(&progress);
+    cmd.timeout(Duration::from_secs(10))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("work 40.0% ETA --:--"));
+}
+
+#[test]
+fn test_burndown_chart_is_printed_when_window_ends() {
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args(["--end", "+2s", "--interval", "1", "--burndown"]);
+    let output = cmd.timeout(Duration::from_secs(10)).assert().success();
+
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    assert_eq!(
+        stdout.matches("· expected  █ time left").count(),
+        1,
+        "{stdout}"
+    );
+    assert!(stdout.contains("100% │"));
+    assert!(stdout.contains("  0% │"));
+}
+
+#[test]
+fn test_xbar_format_prints_one_snapshot() {
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args(["--format", "xbar", "--end", "+1h", "--label", "Focus"]);
+    let output = cmd.timeout(Duration::from_secs(10)).assert().success();
+
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert!(lines[0].ends_with("% Focus"), "{stdout}");
+    assert_eq!(lines[1], "---");
+    assert!(lines.iter().any(|line| line.starts_with("Remaining: ")));
+    assert_eq!(lines.last(), Some(&"Refresh | refresh=true"));
+}
+
+#[test]
+fn test_xbar_attach_without_running_timer() {
+    let runtime_dir = tempfile::tempdir().unwrap();
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.env("XDG_RUNTIME_DIR", runtime_dir.path())
+        .args(["--format", "xbar", "attach", "standup"]);
+    cmd.timeout(Duration::from_secs(10))
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "pmon\n---\nNo running timer named 'standup'",
+        ));
+}
+
+#[test]
+fn test_inplace_rewrites_one_line_when_piped() {
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args(["--end", "+2s", "--interval", "1", "--inplace"]);
+    let output = cmd.timeout(Duration::from_secs(10)).assert().success();
+
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    let (bar, rest) = stdout.split_once('\n').unwrap();
+    // Every update rewrites the same line, erasing what the last one left
+    assert!(bar.matches('\r').count() >= 2, "{stdout:?}");
+    assert!(
+        bar.starts_with('\r') && bar.ends_with("\x1b[K"),
+        "{stdout:?}"
+    );
+    assert!(rest.starts_with("Progress completed!"), "{stdout:?}");
+}
+
+#[test]
+fn test_min_change_thins_out_piped_lines() {
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args(["--end", "+4s", "--interval", "1", "--min-change", "99"]);
+    let output = cmd.timeout(Duration::from_secs(10)).assert().success();
+
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    let bars: Vec<&str> = stdout
+        .lines()
+        .filter(|line| line.starts_with('['))
+        .collect();
+    // The first line, then nothing until the window completes
+    assert_eq!(bars.len(), 2, "{stdout}");
+    assert!(bars[1].contains("100."), "{stdout}");
+}
+
+#[test]
+fn test_heartbeat_prints_keepalive_lines_to_stderr() {
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args([
+        "--end",
+        "+3s",
+        "--interval",
+        "4",
+        "--format",
+        "gha",
+        "--heartbeat",
+        "1s",
+    ]);
+    let output = cmd.timeout(Duration::from_secs(20)).assert().success();
+
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    let stderr = String::from_utf8(output.get_output().stderr.clone()).unwrap();
+    assert!(!stdout.contains("still running"), "{stdout}");
+    assert!(stderr.contains("pmon: still running ("), "{stderr}");
+}
+
+#[test]
+fn test_complete_message_template() {
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args([
+        "--start",
+        "2025-01-27 09:00:00",
+        "--end",
+        "2025-01-27 10:00:00",
+        "--label",
+        "Deploy",
+        "--complete-message",
+        "{label} ended at {end}",
+    ]);
+    cmd.timeout(Duration::from_secs(10))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "\nDeploy ended at 2025-01-27 10:00\n",
+        ))
+        .stdout(predicate::str::contains("Progress completed!").not());
+}
+
+#[test]
+fn test_complete_message_is_english_whatever_the_locale() {
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.env("LC_ALL", "fr_FR.UTF-8").args([
+        "--start",
+        "2025-01-27 09:00:00",
+        "--end",
+        "2025-01-27 10:00:00",
+    ]);
+    cmd.timeout(Duration::from_secs(10))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Progress completed! Time range has elapsed.",
+        ));
+}
+
+#[test]
+fn test_complete_message_follows_the_locale_with_lang_auto() {
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.env("LC_ALL", "fr_FR.UTF-8").args([
+        "--start",
+        "2025-01-27 09:00:00",
+        "--end",
+        "2025-01-27 10:00:00",
+        "--lang",
+        "auto",
+    ]);
+    cmd.timeout(Duration::from_secs(10))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Progression terminée ! La période est écoulée.",
+        ));
+}
+
+#[test]
+fn test_badge_format_prints_shields_url() {
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args([
+        "--format",
+        "badge",
+        "--start",
+        "2025-01-27 09:00:00",
+        "--end",
+        "2025-01-27 17:00:00",
+        "--label",
+        "Sprint 42",
+    ]);
+    cmd.timeout(Duration::from_secs(10))
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::is_match(r"^https://img\.shields\.io/badge/Sprint_42-\d+%25-red\n$")
+                .unwrap(),
+        );
+}
+
+#[test]
+fn test_day_granularity_counts_whole_days() {
+    let today = chrono::Local::now().date_naive();
+    let day = |offset: i64| (today + chrono::Duration::days(offset)).to_string();
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args([
+        "--format",
+        "badge",
+        "--start",
+        &day(-2),
+        "--end",
+        &day(2),
+        "--granularity",
+        "day",
+    ]);
+    // Two of four days have passed, whatever the time of day
+    cmd.timeout(Duration::from_secs(10))
+        .assert()
+        .success()
+        .stdout("https://img.shields.io/badge/progress-50%25-green\n");
+}
+
+#[test]
+fn test_step_snaps_the_displayed_percentage() {
+    let today = chrono::Local::now().date_naive();
+    let day = |offset: i64| (today + chrono::Duration::days(offset)).to_string();
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args([
+        "--format",
+        "badge",
+        "--start",
+        &day(-1),
+        "--end",
+        &day(99),
+        "--step",
+        "25",
+    ]);
+    // Somewhere past 1% of the window, snapped down to 0%
+    cmd.timeout(Duration::from_secs(10))
+        .assert()
+        .success()
+        .stdout("https://img.shields.io/badge/progress-0%25-green\n");
+}
+
+#[test]
+fn test_invert_counts_down_to_zero() {
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args([
+        "--start",
+        "2025-07-21 10:00:00",
+        "--end",
+        "2025-07-21 11:00:00",
+        "--invert",
+        "--drain",
+    ]);
+
+    let output = cmd.timeout(Duration::from_secs(5)).assert().success();
+
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    assert!(
+        stdout.contains(&format!("[{}] 0.0%", "░".repeat(40))),
+        "{stdout:?}"
+    );
+    assert!(!stdout.contains("100.0%"), "{stdout:?}");
+    assert!(stdout.contains("Progress completed!"));
+}
+
+#[test]
+fn test_today_draws_a_day_bar_beneath_the_window() {
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args([
+        "--start",
+        "2025-07-01",
+        "--end",
+        "2025-07-08",
+        "--today",
+        "--day-hours",
+        "09:00-17:00",
+    ]);
+
+    let output = cmd.timeout(Duration::from_secs(5)).assert().success();
+
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert!(lines[0].starts_with("Overall ["), "{stdout:?}");
+    assert!(lines[0].contains("elapsed"), "{stdout:?}");
+    // The day bar has no time information of its own
+    assert!(lines[1].starts_with("Today   ["), "{stdout:?}");
+    assert!(lines[1].ends_with('%'), "{stdout:?}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_label_from_cmd() {
+    let past = [
+        "--start",
+        "2025-07-21 10:00:00",
+        "--end",
+        "2025-07-21 11:00:00",
+    ];
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args(past)
+        .args(["--label-from-cmd", "echo feature/login"]);
+    cmd.timeout(Duration::from_secs(5))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("feature/login ["));
+
+    // --label wins, and a failing command only costs the label
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args(past)
+        .args(["--label", "Deploy", "--label-from-cmd", "echo ignored"]);
+    cmd.timeout(Duration::from_secs(5))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Deploy ["));
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args(past).args(["--label-from-cmd", "exit 1"]);
+    cmd.timeout(Duration::from_secs(5))
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("["))
+        .stderr(predicate::str::contains(
+            "Warning: Could not read the label from `exit 1`",
+        ));
+}
+
+#[test]
+fn test_long_labels_are_cut_short() {
+    let past = [
+        "--start",
+        "2025-07-21 10:00:00",
+        "--end",
+        "2025-07-21 11:00:00",
+    ];
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args(past).args([
+        "--label",
+        "🚀リリース準備の最終確認",
+        "--max-label-width",
+        "8",
+    ]);
+    cmd.timeout(Duration::from_secs(5))
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("🚀リリ… ["));
+
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args(past).args(["--label", &"x".repeat(40)]);
+    cmd.timeout(Duration::from_secs(5))
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(format!(
+            "{}… [",
+            "x".repeat(31)
+        )));
+}
+
+#[test]
+fn test_badge_svg_format_prints_an_image() {
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args(["--format", "badge-svg", "--end", "+1h"]);
+    cmd.timeout(Duration::from_secs(10))
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("<svg"))
+        .stdout(predicate::str::contains("<title>progress: 0%</title>"));
+}
+
+#[test]
+fn test_badge_attach_without_running_timer() {
+    let runtime_dir = tempfile::tempdir().unwrap();
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.env("XDG_RUNTIME_DIR", runtime_dir.path())
+        .args(["--format", "badge", "attach", "standup"]);
+    cmd.timeout(Duration::from_secs(10))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no running timer named 'standup'"));
+}
+
+#[test]
+fn test_list_filters_running_timers() {
+    let runtime_dir = tempfile::tempdir().unwrap();
+    let state_dir = runtime_dir.path().join("pmon");
+    std::fs::create_dir_all(&state_dir).unwrap();
+    let update = |label: &str, end: &str, percent: f64| {
+        format!(
+            "{{\"label\":\"{label}\",\"start\":\"2025-01-27T09:00:00\",\"end\":\"{end}\",\
+             \"current\":\"2025-01-27T09:30:00\",\"percent\":{percent:.1},\"message\":null}}"
+        )
+    };
+    for (name, label, end, percent) in [
+        ("build", "Release build", "2025-01-27T10:00:00", 50.0),
+        ("deploy", "Release deploy", "2025-01-27T09:15:00", 200.0),
+        ("standup", "Standup", "2025-01-27T10:00:00", 50.0),
+    ] {
+        let json = update(label, end, percent);
+        std::fs::write(state_dir.join(format!("{name}.json")), json).unwrap();
+    }
+
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.env("XDG_RUNTIME_DIR", runtime_dir.path()).args([
+        "list",
+        "--filter",
+        "label~release",
+        "--state",
+        "running",
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::starts_with("build  running"))
+        .stdout(predicate::str::contains("deploy").not())
+        .stdout(predicate::str::contains("standup").not());
+
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.env("XDG_RUNTIME_DIR", runtime_dir.path())
+        .args(["list", "--filter", "name=missing"]);
+    cmd.assert()
+        .success()
+        .stdout("No matching timers running.\n");
+}
+
+#[test]
+fn test_status_aggregates_group() {
+    let runtime_dir = tempfile::tempdir().unwrap();
+    let state_dir = runtime_dir.path().join("pmon");
+    std::fs::create_dir_all(&state_dir).unwrap();
+    for (name, end, percent, tags) in [
+        ("api", "2025-01-27T13:00:00", 25.0, "[\"team:payments\"]"),
+        (
+            "ledger",
+            "2025-01-27T11:00:00",
+            100.0,
+            "[\"team:payments\"]",
+        ),
+        ("search", "2025-01-27T11:00:00", 50.0, "[\"team:search\"]"),
+    ] {
+        let json = format!(
+            "{{\"label\":null,\"start\":\"2025-01-27T09:00:00\",\"end\":\"{end}\",\
+             \"current\":\"2025-01-27T10:00:00\",\"percent\":{percent:.1},\
+             \"message\":null,\"tags\":{tags}}}"
+        );
+        std::fs::write(state_dir.join(format!("{name}.json")), json).unwrap();
+    }
+    let pmon = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("pmon").unwrap();
+        cmd.env("XDG_RUNTIME_DIR", runtime_dir.path()).args(args);
+        cmd
+    };
+
+    // 1h of 4h and 2h of 2h used
+    pmon(&[
+        "--format",
+        "json",
+        "status",
+        "--group",
+        "team:payments",
+        "--aggregate",
+    ])
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("\"percent\":50.0"))
+    .stdout(predicate::str::contains("\"end\":\"2025-01-27T13:00:00\""));
+    pmon(&["status", "--group", "team:payments", "--aggregate"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("team:payments (2 timers) "));
+    pmon(&["status", "--group", "team:payments"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("search").not());
+    pmon(&["status", "--group", "team:billing", "--aggregate"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "no running timers tagged 'team:billing'",
+        ));
+}
+
+#[test]
+fn test_max_runtime_ends_process() {
+    let runtime_dir = tempfile::tempdir().unwrap();
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.env("XDG_RUNTIME_DIR", runtime_dir.path())
+        .env("XDG_DATA_HOME", runtime_dir.path())
+        .env("PMON_CONFIG", runtime_dir.path().join("config.toml"))
+        .args(["--end", "+1h", "--name", "forgotten", "--max-runtime", "1s"]);
+    cmd.timeout(Duration::from_secs(10))
+        .assert()
+        .code(124)
+        .stderr(predicate::str::contains(
+            "pmon: maximum runtime of 00:00:01 reached",
+        ));
+    assert!(!runtime_dir.path().join("pmon/forgotten.json").exists());
+}
+
+#[test]
+fn test_running_named_timer_is_not_overwritten() {
+    let dir = tempfile::tempdir().unwrap();
+    let pmon = || {
+        let mut cmd = std::process::Command::new(env!("CARGO_BIN_EXE_pmon"));
+        cmd.env("XDG_RUNTIME_DIR", dir.path())
+            .env("XDG_DATA_HOME", dir.path())
+            .env("PMON_CONFIG", dir.path().join("config.toml"))
+            .args(["--end", "+1h", "--name", "review"]);
+        cmd
+    };
+    let mut running = pmon().stdout(std::process::Stdio::null()).spawn().unwrap();
+    let state_file = dir.path().join("pmon").join("review.json");
+    for _ in 0..50 {
+        if state_file.exists() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    let run = |extra: &[&str]| {
+        Command::from_std(pmon())
+            .args(["--max-runtime", "1s"])
+            .args(extra)
+            .timeout(Duration::from_secs(10))
+            .assert()
+    };
+
+    run(&[]).code(1).stderr(predicate::str::contains(
+        "Timer 'review' already exists until",
+    ));
+    run(&["--force"]).code(124);
+    run(&["--on-exists", "merge"]).code(124);
+    running.kill().unwrap();
+    running.wait().unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_named_timer_restarts_after_interrupted_run() {
+    let dir = tempfile::tempdir().unwrap();
+    let pmon = || {
+        let mut cmd = std::process::Command::new(env!("CARGO_BIN_EXE_pmon"));
+        cmd.env("XDG_RUNTIME_DIR", dir.path())
+            .env("XDG_DATA_HOME", dir.path())
+            .env("PMON_CONFIG", dir.path().join("config.toml"))
+            .args(["--name", "standup"]);
+        cmd
+    };
+    let mut interrupted = pmon()
+        .args(["--end", "+10m"])
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    let state_file = dir.path().join("pmon").join("standup.json");
+    for _ in 0..50 {
+        if state_file.exists() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    interrupted.kill().unwrap();
+    interrupted.wait().unwrap();
+
+    Command::from_std(pmon())
+        .args(["--end", "+1s", "--interval", "1"])
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Progress completed!"));
+}
+
+#[test]
+fn test_debug_log() {
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args(["--debug", "deadline", "--end", "+30d"]);
+    cmd.assert()
+        .code(0)
+        .stderr(predicate::str::contains("pmon starting"))
+        .stderr(predicate::str::contains(
+            "input=\"+30d\" format=\"relative\"",
+        ));
+
+    let dir = tempfile::tempdir().unwrap();
+    let log = dir.path().join("pmon.log");
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.arg("--debug-file")
+        .arg(&log)
+        .args(["--end", "+1h", "--max-runtime", "1s"]);
+    cmd.timeout(Duration::from_secs(10))
+        .assert()
+        .code(124)
+        .stderr(predicate::str::contains("parsing time").not());
+    let log = std::fs::read_to_string(log).unwrap();
+    assert!(log.contains("terminal detected"), "{log}");
+    assert!(log.contains("percentage="), "{log}");
+}
+
+#[test]
+fn test_demo_plays_back_window() {
+    // A past workday, played back in a second
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args([
+        "--start",
+        "2025-01-27 09:00:00",
+        "--end",
+        "2025-01-27 17:00:00",
+        "--demo",
+        "1s",
+    ]);
+    cmd.timeout(Duration::from_secs(10))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("0.0%"))
+        .stdout(predicate::str::is_match(r"\] [1-9][0-9]\.[0-9]%").unwrap())
+        .stdout(predicate::str::contains("Progress completed!"));
+
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args(["--end", "+1h", "--demo", "30s", "--name", "preview"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("--name"));
+}
+
+#[test]
+fn test_record_session() {
+    let dir = tempfile::tempdir().unwrap();
+    let cast = dir.path().join("session.cast");
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args([
+        "--start",
+        "2025-01-27 09:00:00",
+        "--end",
+        "2025-01-27 10:00:00",
+        "--demo",
+        "1s",
+        "--record",
+    ])
+    .arg(&cast);
+    cmd.timeout(Duration::from_secs(10)).assert().success();
+
+    let cast = std::fs::read_to_string(cast).unwrap();
+    let mut lines = cast.lines();
+    let header: serde_json::Value = serde_json::from_str(lines.next().unwrap()).unwrap();
+    assert_eq!(header["version"], 2);
+    let events: Vec<serde_json::Value> = lines
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert!(events.len() > 2, "{cast}");
+    assert!(events.iter().all(|event| event[1] == "o"), "{cast}");
+    assert!(cast.contains("Progress completed!"), "{cast}");
+}
+
+#[test]
+fn test_snapshot_svg() {
+    let dir = tempfile::tempdir().unwrap();
+    let image = dir.path().join("progress.svg");
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args([
+        "snapshot",
+        "--start",
+        "2025-01-27 09:00:00",
+        "--end",
+        "2025-01-27 17:00:00",
+        "--label",
+        "Launch",
+        "-o",
+    ])
+    .arg(&image);
+    cmd.assert().success().stdout("");
+
+    let svg = std::fs::read_to_string(image).unwrap();
+    assert!(svg.starts_with("<svg"), "{svg}");
+    assert!(
+        svg.contains("<text x=\"0\" y=\"14\">Launch</text>"),
+        "{svg}"
+    );
+    assert!(svg.contains("overtime"), "{svg}");
+
+    // Without a file the image goes to the output
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args(["snapshot", "--end", "+1h"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::starts_with("<svg"))
+        .stdout(predicate::str::contains("remaining"));
+
+    let mut cmd = Command::cargo_bin("pmon").unwrap();
+    cmd.args(["snapshot", "--end", "someday"]);
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "could not parse end time 'someday'",
+    ));
+}
+
+#[test]
+fn test_doctor_reports_invalid_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    let doctor = || {
+        let mut cmd = Command::cargo_bin("pmon").unwrap();
+        cmd.env("PMON_CONFIG", &config)
+            .env("LC_ALL", "C")
+            .arg("doctor");
+        cmd
+    };
+
+    doctor()
+        .assert()
+        .code(0)
+        .stdout(predicate::str::contains("not found; using defaults"))
+        .stdout(predicate::str::contains("warn  Unicode"))
+        .stdout(predicate::str::contains("from the local clock"));
+
+    std::fs::write(&config, "[store]\nbackend = \"csv\"\n").unwrap();
+    doctor()
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("FAIL  Config"));
+}
@@ -2,6 +2,8 @@
 //!
 //! This module provides comprehensive performance testing for CLI parsing,
 //! time parsing, progress calculation, and rendering operations.
+// The deprecated renderers stay covered until they are removed
+#![allow(deprecated)]
 
 use chrono::Duration;
 use clap::Parser; // Add this import for try_parse_from
@@ -2,6 +2,8 @@
 //!
 //! This module tests progress calculation accuracy, progress bar rendering,
 //! color management, and performance characteristics.
+// The deprecated renderers stay covered until they are removed
+#![allow(deprecated)]
 
 use chrono::Duration;
 use pmon::progress_bar::*;
@@ -278,7 +280,7 @@ mod render_progress_bar_tests {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "color"))]
 mod render_colored_progress_bar_tests {
     use super::*;
 
@@ -281,8 +281,7 @@ mod parse_relative_time_tests {
 
         for (input, expected_duration) in test_cases {
             let result = parse_relative_time(input, now);
-            if result.is_ok() {
-                let parsed = result.unwrap();
+            if let Ok(parsed) = result {
                 let expected = now + expected_duration;
 
                 // Allow for small differences due to execution time
@@ -319,9 +318,8 @@ mod parse_relative_time_tests {
 
         for input in test_cases {
             let result = parse_time(input);
-            if result.is_ok() {
+            if let Ok(parsed) = result {
                 // Negative relative times should work if supported
-                let parsed = result.unwrap();
                 let now = chrono::Local::now().naive_local();
                 assert!(
                     parsed < now,
@@ -363,11 +361,7 @@ mod parse_relative_time_tests {
         // Test using the comprehensive test data, but be flexible about what's supported
         for (input, expected_valid) in TimeTestData::relative_time_cases() {
             // Remove + prefix for direct parse_relative_time testing
-            let clean_input = if input.starts_with('+') {
-                &input[1..]
-            } else {
-                input
-            };
+            let clean_input = input.strip_prefix('+').unwrap_or(input);
 
             let result = parse_relative_time(clean_input, now);
 
@@ -19,8 +19,8 @@ fn test_verbose_flag_shows_header() {
 
     // With verbose flag, should show header
     assert!(stdout.contains("pmon - Progress Monitor Tool"));
-    assert!(stdout.contains("Start time: 2025-07-21 10:00:00"));
-    assert!(stdout.contains("End time: 2025-07-21 11:00:00"));
+    assert!(stdout.contains("Start time:      2025-07-21 10:00:00"));
+    assert!(stdout.contains("End time:        2025-07-21 11:00:00"));
     assert!(stdout.contains("Update interval: 1 seconds"));
     assert!(stdout.contains("Press Ctrl+C to exit"));
 }
@@ -43,8 +43,8 @@ fn test_verbose_flag_short_form() {
 
     // With -v flag, should show header
     assert!(stdout.contains("pmon - Progress Monitor Tool"));
-    assert!(stdout.contains("Start time: 2025-07-21 10:00:00"));
-    assert!(stdout.contains("End time: 2025-07-21 11:00:00"));
+    assert!(stdout.contains("Start time:      2025-07-21 10:00:00"));
+    assert!(stdout.contains("End time:        2025-07-21 11:00:00"));
     assert!(stdout.contains("Update interval: 1 seconds"));
     assert!(stdout.contains("Press Ctrl+C to exit"));
 }